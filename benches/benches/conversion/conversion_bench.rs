@@ -0,0 +1,59 @@
+//! Скалярные и векторные ядра преобразования Int16 ↔ f32 и CRC32 блока.
+//!
+//! Каждая группа сравнивает `Kernel::Scalar` со всеми ядрами, доступными на
+//! этой машине; ожидаемое ускорение векторного ядра (`std::simd`) над
+//! скаляром — не меньше 2×.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use glos_core::{crc32_checksum, simd::Kernel};
+
+/// Типичный блок: 64 Ки IQ-пар Int16 (256 КиБ).
+const PAIRS: usize = 64 * 1024;
+
+fn bench_i16_to_f32(c: &mut Criterion) {
+    let src: Vec<u8> = (0..PAIRS * 4).map(|i| (i * 31 % 251) as u8).collect();
+    let mut dst = vec![0.0f32; PAIRS * 2];
+
+    let mut group = c.benchmark_group("i16_to_f32");
+    group.throughput(Throughput::Bytes(src.len() as u64));
+
+    for kernel in Kernel::available() {
+        group.bench_function(BenchmarkId::from_parameter(format!("{kernel:?}")), |b| {
+            b.iter(|| kernel.i16_to_f32(black_box(&src), false, &mut dst))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_f32_to_i16(c: &mut Criterion) {
+    let src: Vec<f32> = (0..PAIRS * 2)
+        .map(|i| ((i as f32) * 0.001).sin() * 1.1)
+        .collect();
+    let mut dst = vec![0u8; PAIRS * 4];
+
+    let mut group = c.benchmark_group("f32_to_i16");
+    group.throughput(Throughput::Bytes(dst.len() as u64));
+
+    for kernel in Kernel::available() {
+        group.bench_function(BenchmarkId::from_parameter(format!("{kernel:?}")), |b| {
+            b.iter(|| kernel.f32_to_i16(black_box(&src), false, &mut dst))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_crc32(c: &mut Criterion) {
+    let data: Vec<u8> = (0..PAIRS * 4).map(|i| (i * 7 % 253) as u8).collect();
+
+    let mut group = c.benchmark_group("crc32");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("block", |b| b.iter(|| crc32_checksum(black_box(&data))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_i16_to_f32, bench_f32_to_i16, bench_crc32);
+criterion_main!(benches);
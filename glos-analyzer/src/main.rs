@@ -1,12 +1,21 @@
-use std::{fs, io::Write, path::PathBuf, time::Instant};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
 use clap::Parser;
 use glos_analyzer::{
-    decode_iq, export_spectrum_csv, export_spectrum_png, export_waterfall_csv,
-    export_waterfall_png, render_ascii_spectrum, render_ascii_waterfall, PeakDetector,
-    SpectrumConfig, SpectrumProcessor, WaterfallBuffer, WindowFunction,
+    export_spectrum_csv, export_spectrum_png, export_waterfall_csv, export_waterfall_png,
+    render_ascii_spectrum, render_ascii_waterfall, PeakDetector, SpectrumConfig, SpectrumProcessor,
+    WaterfallBuffer, WindowFunction,
+};
+use glos_core::{
+    dsp::{estimate_cn0, Cn0Params, FormatAdvice, FormatAdvisor},
+    GlosHeaderExt, GlosReader, GlosWriter, SessionDigest,
 };
-use glos_core::GlosReader;
+use glos_types::GlosResult;
 use log::{error, info, warn};
 
 #[derive(Parser, Debug)]
@@ -45,6 +54,20 @@ struct Cli {
     waterfall_csv: Option<PathBuf>,
     #[arg(long)]
     no_display: bool,
+    /// Переписать файл в меньший IqFormat, если данные занимают меньше бит,
+    /// чем отведено форматом (без потерь, см. «Format advice» в итогах)
+    #[arg(long)]
+    transcode: Option<PathBuf>,
+    /// Оценить C/N0 для указанных PRN GPS (можно несколько: --cn0-prn 5
+    /// --cn0-prn 12)
+    #[arg(long)]
+    cn0_prn: Vec<u8>,
+    /// Доплеровский сдвиг для оценки C/N0, Гц
+    #[arg(long, default_value = "0.0")]
+    cn0_doppler: f64,
+    /// Сколько миллисекунд от начала записи использовать для оценки C/N0
+    #[arg(long, default_value = "1000")]
+    cn0_ms: u32,
     #[arg(short, long)]
     quiet: bool,
 }
@@ -113,7 +136,7 @@ fn main() {
 
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     info!("  File          : {:?}", cli.input);
-    info!("  SDR type      : {:?}", header.sdr_type);
+    info!("  SDR type      : {}", header.sdr_type);
     info!(
         "  Sample rate   : {:.3} MHz",
         header.sample_rate as f64 / 1e6
@@ -122,8 +145,15 @@ fn main() {
         "  Center freq   : {:.3} MHz",
         header.center_freq as f64 / 1e6
     );
-    info!("  IQ format     : {:?}", header.iq_format);
+    info!("  IQ format     : {}", header.iq_format);
     info!("  Total samples : {}", header.total_samples);
+    if let Some(d) = header.duration() {
+        info!("  Duration      : {:.3}s", d.as_secs_f64());
+    }
+    info!(
+        "  Data rate     : {:.1} Mbit/s",
+        header.bits_per_second() as f64 / 1e6
+    );
     info!("  FFT size      : {}", cli.fft_size);
     info!("  Window        : {}", window);
     info!("  Avg count     : {}", cli.avg);
@@ -139,6 +169,10 @@ fn main() {
     let mut blocks_total = 0usize;
     let mut last_spectrum = None;
     let mut last_metrics = None;
+    let cn0_limit = header.sample_rate as usize / 1000 * cli.cn0_ms as usize;
+    let mut cn0_samples: Vec<[f32; 2]> = Vec::new();
+    let mut digest = SessionDigest::new();
+    let mut advisor = FormatAdvisor::new(header.iq_format, header.samples_little_endian());
 
     // Продолжаем читать через reader (уже частично прочитан для заголовка)
     while let Some(result) = reader.next_block() {
@@ -151,9 +185,16 @@ fn main() {
         };
 
         blocks_total += 1;
+        digest.push_block(&block);
+        advisor.push(&block.data);
 
         // Декодируем IQ
-        let samples = decode_iq(&block.data, header.iq_format);
+        let samples = block.as_complex_f32_with(header.iq_format, header.samples_little_endian());
+
+        if !cli.cn0_prn.is_empty() && cn0_samples.len() < cn0_limit {
+            let take = (cn0_limit - cn0_samples.len()).min(samples.len());
+            cn0_samples.extend(samples[..take].iter().map(|c| [c.re, c.im]));
+        }
 
         // FFT + скользящее среднее
         if let Some(spectrum) = proc.process_block(&samples, block.timestamp_ns) {
@@ -195,7 +236,21 @@ fn main() {
     info!("  Blocks read    : {blocks_total}");
     info!("  Spectra        : {spectra_count}");
     info!("  Corrupted      : {}", read_stats.blocks_corrupted);
+    for event in &read_stats.corruption_events {
+        info!("  Corrupted at   : {event}");
+    }
+    if read_stats.samples_dropped > 0 {
+        info!("  Dropped        : {} samples", read_stats.samples_dropped);
+        for gap in &read_stats.gaps {
+            info!("  Dropped at     : {gap}");
+        }
+    }
     info!("  Elapsed        : {elapsed:.2}s");
+    // Эталон для сквозной проверки: совпадает с дайджестом приёмника
+    // (`glos-replayer --protocol v2`), если поток дошёл без потерь
+    info!("  Digest         : {digest}");
+    let advice = advisor.advice();
+    info!("  Format advice  : {advice}");
 
     if let Some(ref m) = last_metrics {
         info!("  Noise floor    : {:.1} dBFS", m.noise_floor_db);
@@ -203,6 +258,21 @@ fn main() {
         info!("  Peak SNR       : {:.1} dB", m.peak_snr_db);
         info!("  Peaks found    : {}", m.peaks.len());
     }
+
+    let cn0_params = Cn0Params {
+        doppler_hz: cli.cn0_doppler,
+        ..Cn0Params::new(header.sample_rate)
+    };
+
+    for &prn in &cli.cn0_prn {
+        match estimate_cn0(&cn0_samples, prn, &cn0_params) {
+            Ok(est) => info!(
+                "  C/N0 PRN {prn:<3}  : {:.1} dB-Hz (code phase {:.1} chips, {} ms)",
+                est.cn0_db_hz, est.code_phase_chips, est.intervals
+            ),
+            Err(e) => warn!("  C/N0 PRN {prn:<3}  : {e}"),
+        }
+    }
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
     // --- Waterfall ASCII (финальный) ---
@@ -275,7 +345,59 @@ fn main() {
         }
     }
 
+    if let Some(path) = &cli.transcode {
+        if !advice.is_beneficial() {
+            warn!(
+                "--transcode: {} is already the smallest lossless format",
+                advice.current
+            );
+        } else if path == &cli.input {
+            error!("--transcode: must differ from --input");
+            export_ok = false;
+        } else {
+            match transcode_file(&cli.input, path, &advice) {
+                Ok(blocks) => info!(
+                    "✓ Transcoded to {}: {path:?} ({blocks} blocks)",
+                    advice.recommended
+                ),
+                Err(e) => {
+                    error!("Transcode failed: {e}");
+                    export_ok = false;
+                }
+            }
+        }
+    }
+
     if !export_ok {
         std::process::exit(1);
     }
 }
+
+/// Переписывает `input` в `output` в рекомендованном формате. Повреждённые
+/// блоки пропускаются, как и при анализе.
+fn transcode_file(
+    input: &Path,
+    output: &Path,
+    advice: &FormatAdvice,
+) -> GlosResult<u64> {
+    let mut reader = GlosReader::new(fs::File::open(input)?)?;
+    let little_endian = reader.header().samples_little_endian();
+
+    let mut header = reader.header().clone();
+    header.iq_format = advice.recommended;
+    let timestamp_end = header.timestamp_end;
+
+    let mut writer = GlosWriter::new(fs::File::create(output)?, header)?;
+
+    while let Some(result) = reader.next_block() {
+        let Ok(mut block) = result else {
+            continue;
+        };
+        block.data = advice.transcode(&block.data, little_endian)?;
+        writer.write_block(block)?;
+    }
+
+    let blocks = writer.block_count();
+    writer.finish_at(timestamp_end)?;
+    Ok(blocks)
+}
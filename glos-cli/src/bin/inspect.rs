@@ -0,0 +1,53 @@
+use std::{fs::File, io::BufReader, path::PathBuf};
+
+use clap::Parser;
+use glos_cli::inspect;
+
+/// Показать содержимое .glos файла: заголовок, блоки, разрывы, сжатие и
+/// целостность CRC. Код возврата 2 — файл повреждён или итоги заголовка не
+/// сходятся с блоками.
+#[derive(Parser, Debug)]
+#[command(
+    name = "glos-inspect",
+    version = env!("CARGO_PKG_VERSION"),
+    about = "Inspect a .glos recording",
+    long_about = None,
+)]
+struct Args {
+    /// Файл записи
+    file: PathBuf,
+    /// Печатать JSON вместо текста
+    #[arg(long)]
+    json: bool,
+    /// Не выводить список блоков
+    #[arg(long)]
+    no_blocks: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let info = match File::open(&args.file)
+        .map_err(Into::into)
+        .and_then(|f| inspect(BufReader::new(f), !args.no_blocks))
+    {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("{:?}: {e}", args.file);
+            std::process::exit(1);
+        }
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&info).expect("inspection serializes")
+        );
+    } else {
+        print!("{info}");
+    }
+
+    if !info.healthy() {
+        std::process::exit(2);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use glos_cli::repair;
+
+/// Спасти повреждённую запись: переписать в новый файл только целые блоки,
+/// пересчитать итоги заголовка и вывести отчёт о потерях.
+#[derive(Parser, Debug)]
+#[command(
+    name = "glos-repair",
+    version = env!("CARGO_PKG_VERSION"),
+    about = "Salvage intact blocks from a damaged .glos recording",
+    long_about = None,
+)]
+struct Args {
+    /// Повреждённый файл
+    input: PathBuf,
+    /// Выходной файл (по умолчанию — `<имя>.repaired.glos` рядом с входным)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let output = args.output.unwrap_or_else(|| default_output(&args.input));
+    if output == args.input {
+        eprintln!("--output: would overwrite the input file");
+        std::process::exit(1);
+    }
+
+    let input = match File::open(&args.input) {
+        Ok(f) => BufReader::new(f),
+        Err(e) => {
+            eprintln!("{:?}: {e}", args.input);
+            std::process::exit(1);
+        }
+    };
+    let file = match File::create(&output) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("--output: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    match repair(input, file) {
+        Ok(report) => {
+            print!("{report}");
+            println!("Written   : {output:?}");
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&output);
+            eprintln!("{:?}: {e}", args.input);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn default_output(input: &Path) -> PathBuf {
+    input.with_extension("repaired.glos")
+}
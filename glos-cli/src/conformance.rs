@@ -0,0 +1,294 @@
+//! Прогон сторонней реализации по корпусу тестовых векторов.
+//!
+//! Каждый файл корпуса ([`glos_core::testvec::corpus`]) передаётся внешней
+//! команде: `{}` в аргументах заменяется путём файла, без `{}` путь
+//! дописывается последним аргументом. Команда печатает в stdout JSON-вердикт
+//! ([`Verdict`]), он сверяется с ожидаемым. Код возврата команды не
+//! учитывается: отвергнутый файл — это `"header_ok": false`, а не ошибка.
+
+use std::{
+    fmt, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use glos_core::testvec::{TestVector, Verdict};
+
+/// Сколько ждать вердикта по одному файлу.
+pub const DEFAULT_CASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Итог одного вектора.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    /// Вердикт получен, но расходится с ожидаемым
+    Fail(Vec<String>),
+    /// Команда не запустилась, не уложилась во время или вывела не JSON
+    Error(String),
+}
+
+/// Результат прогона одного вектора.
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub name: &'static str,
+    pub outcome: Outcome,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Pass
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for CaseResult {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match &self.outcome {
+            Outcome::Pass => write!(f, "PASS  {}", self.name),
+            Outcome::Fail(diff) => write!(f, "FAIL  {}: {}", self.name, diff.join("; ")),
+            Outcome::Error(e) => write!(f, "ERROR {}: {e}", self.name),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Записывает корпус в `dir`: `<name>.glos` и `<name>.expected.json` на
+/// каждый вектор плюс `index.json` со списком. Возвращает пути файлов.
+pub fn write_corpus(
+    vectors: &[TestVector],
+    dir: &Path,
+) -> std::io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let mut index = Vec::with_capacity(vectors.len());
+    let mut paths = Vec::with_capacity(vectors.len());
+
+    for v in vectors {
+        let path = dir.join(v.file_name());
+        fs::write(&path, &v.bytes)?;
+
+        let expected = serde_json::to_string_pretty(&v.expected).map_err(std::io::Error::other)?;
+        fs::write(
+            dir.join(format!("{}.expected.json", v.name)),
+            expected + "\n",
+        )?;
+
+        index.push(serde_json::json!({
+            "name": v.name,
+            "file": v.file_name(),
+            "description": v.description,
+            "expected": v.expected,
+        }));
+        paths.push(path);
+    }
+
+    let index = serde_json::to_string_pretty(&index).map_err(std::io::Error::other)?;
+    fs::write(dir.join("index.json"), index + "\n")?;
+
+    Ok(paths)
+}
+
+/// Прогоняет `command` по всем векторам (файлы уже записаны в `dir`).
+pub fn run(
+    vectors: &[TestVector],
+    dir: &Path,
+    command: &[String],
+    timeout: Duration,
+) -> Vec<CaseResult> {
+    vectors
+        .iter()
+        .map(|v| {
+            let outcome = match run_case(command, &dir.join(v.file_name()), timeout) {
+                Ok(stdout) => check_output(&v.expected, &stdout),
+                Err(e) => Outcome::Error(e),
+            };
+            CaseResult {
+                name: v.name,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// Сверяет вывод команды с ожидаемым вердиктом. Берётся последняя непустая
+/// строка stdout: до неё реализация может печатать что угодно.
+pub fn check_output(
+    expected: &Verdict,
+    stdout: &str,
+) -> Outcome {
+    let Some(line) = stdout.lines().rev().find(|l| !l.trim().is_empty()) else {
+        return Outcome::Error("no verdict on stdout".to_string());
+    };
+
+    match serde_json::from_str::<Verdict>(line) {
+        Ok(actual) => {
+            let diff = expected.mismatches(&actual);
+            if diff.is_empty() {
+                Outcome::Pass
+            } else {
+                Outcome::Fail(diff)
+            }
+        }
+        Err(e) => Outcome::Error(format!("invalid verdict JSON {line:?}: {e}")),
+    }
+}
+
+/// Аргументы команды для файла `file`.
+pub fn command_args(
+    command: &[String],
+    file: &Path,
+) -> Vec<String> {
+    let path = file.to_string_lossy();
+    let mut args: Vec<String> = command.iter().map(|a| a.replace("{}", &path)).collect();
+
+    if !command.iter().any(|a| a.contains("{}")) {
+        args.push(path.into_owned());
+    }
+
+    args
+}
+
+/// Запускает команду для одного файла и возвращает её stdout.
+fn run_case(
+    command: &[String],
+    file: &Path,
+    timeout: Duration,
+) -> Result<String, String> {
+    let args = command_args(command, file);
+    let (program, rest) = args.split_first().ok_or("empty command")?;
+
+    let mut child = Command::new(program)
+        .args(rest)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot run {program:?}: {e}"))?;
+
+    // stdout читаем в отдельном потоке, иначе большой вывод заблокирует
+    // команду раньше, чем она завершится
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {:.1}s", timeout.as_secs_f64()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(5)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    reader
+        .join()
+        .map_err(|_| "stdout reader panicked".to_string())?
+        .map_err(|e| format!("cannot read stdout: {e}"))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::testvec::corpus;
+
+    use super::*;
+
+    #[test]
+    fn test_command_args_placeholder() {
+        let file = Path::new("/tmp/c/valid_minimal.glos");
+        let cmd = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            command_args(&cmd(&["reader", "--json"]), file),
+            cmd(&["reader", "--json", "/tmp/c/valid_minimal.glos"])
+        );
+        assert_eq!(
+            command_args(&cmd(&["dotnet", "run", "--file={}", "-q"]), file),
+            cmd(&["dotnet", "run", "--file=/tmp/c/valid_minimal.glos", "-q"])
+        );
+    }
+
+    #[test]
+    fn test_check_output_uses_last_line() {
+        let expected = Verdict::accepted(2, false, 2_000, true);
+        let json = serde_json::to_string(&expected).unwrap();
+
+        assert_eq!(
+            check_output(&expected, &format!("reading...\n{json}\n\n")),
+            Outcome::Pass
+        );
+        assert!(matches!(
+            check_output(&expected, r#"{"header_ok": false}"#),
+            Outcome::Fail(_)
+        ));
+        assert!(matches!(check_output(&expected, ""), Outcome::Error(_)));
+        assert!(matches!(check_output(&expected, "ok"), Outcome::Error(_)));
+    }
+
+    #[test]
+    fn test_write_corpus_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let vectors = corpus();
+
+        let paths = write_corpus(&vectors, dir.path()).unwrap();
+        assert_eq!(paths.len(), vectors.len());
+        assert_eq!(fs::read(&paths[0]).unwrap(), vectors[0].bytes);
+
+        let expected: Verdict = serde_json::from_str(
+            &fs::read_to_string(
+                dir.path()
+                    .join(format!("{}.expected.json", vectors[0].name)),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(expected, vectors[0].expected);
+
+        let index: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("index.json")).unwrap())
+                .unwrap();
+        assert_eq!(index.as_array().unwrap().len(), vectors.len());
+    }
+
+    #[test]
+    fn test_run_reports_missing_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let vectors = &corpus()[..1];
+        write_corpus(vectors, dir.path()).unwrap();
+
+        let results = run(
+            vectors,
+            dir.path(),
+            &["/nonexistent/glos-reader".to_string()],
+            DEFAULT_CASE_TIMEOUT,
+        );
+        assert!(matches!(results[0].outcome, Outcome::Error(_)));
+        assert!(results[0].to_string().starts_with("ERROR valid_minimal"));
+    }
+}
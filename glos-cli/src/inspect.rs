@@ -0,0 +1,531 @@
+//! Сводка по содержимому .glos файла для `glos-inspect`.
+//!
+//! Блоки читаются без распаковки ([`RawBlockReader`]): сводке нужны метки
+//! времени, число выборок и размер данных на диске, а CRC кадра проверяется
+//! и так. Разрывы ищутся по меткам времени и номинальной частоте
+//! дискретизации — как в [`glos_core::export_raw`]. События приёмника
+//! (перегрузка, смена усиления, потери) берутся из атрибутов блоков v2.
+
+use std::{fmt, io::Read};
+
+use glos_core::{
+    prelude::{GlosHeader, GlosResult},
+    RawBlockReader,
+};
+use serde::Serialize;
+
+/// Поля заголовка в виде для печати и JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeaderInfo {
+    pub version: u8,
+    pub flags: u8,
+    pub sdr_type: String,
+    pub iq_format: String,
+    pub compression: String,
+    pub sample_rate: u32,
+    pub center_freq: u64,
+    pub gain_db: f32,
+    pub timestamp_start: u64,
+    pub timestamp_end: u64,
+    /// Источник меток блоков: host, ntp или pps
+    pub time_source: String,
+    /// Поправка к часам хоста, уже учтённая в метках (нс)
+    pub clock_offset_ns: i64,
+    pub total_samples: u64,
+    pub duration_secs: Option<f64>,
+}
+
+/// Один блок с верной CRC.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockInfo {
+    /// Номер среди целых блоков
+    pub index: u64,
+    /// Смещение кадра от начала файла
+    pub offset: u64,
+    pub timestamp_ns: u64,
+    pub sample_count: u32,
+    /// Размер данных на диске (сжатых, если файл сжат)
+    pub stored_bytes: usize,
+}
+
+/// Расхождение метки блока с концом предыдущего.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GapInfo {
+    /// Номер блока, перед которым разрыв
+    pub before_block: u64,
+    /// Где должен был начаться блок (нс)
+    pub expected_ns: u64,
+    pub timestamp_ns: u64,
+    /// Пропущено выборок; отрицательное — блоки перекрываются
+    pub samples: i64,
+}
+
+/// Блок с событиями приёмника (атрибуты блока v2).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventInfo {
+    /// Номер блока среди целых блоков
+    pub block: u64,
+    pub timestamp_ns: u64,
+    /// АЦП в насыщении
+    pub overload: bool,
+    /// Новое усиление, дБ
+    pub gain_db: Option<f32>,
+    /// Потеряно выборок перед блоком
+    pub dropped_samples: Option<u32>,
+    /// Номер метки синхронизации
+    pub sync_marker: Option<u32>,
+    /// Время метки синхронизации по внешним часам (нс)
+    pub external_ns: Option<u64>,
+}
+
+/// Повреждённый участок.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CorruptionInfo {
+    pub offset: u64,
+    /// `crc`, `decompress`, `sample-count` или `malformed`
+    pub kind: String,
+    pub timestamp_guess: Option<u64>,
+}
+
+/// Сводка по файлу.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Inspection {
+    pub header: HeaderInfo,
+    pub block_count: u64,
+    pub samples: u64,
+    /// Пусто, если блоки не запрашивались
+    pub blocks: Vec<BlockInfo>,
+    pub gaps: Vec<GapInfo>,
+    /// Блоки с атрибутами, по порядку
+    pub events: Vec<EventInfo>,
+    /// Блоков с перегрузкой
+    pub overloaded_blocks: u64,
+    /// Несжатый объём IQ данных / объём на диске (1.0 без сжатия)
+    pub compression_ratio: f64,
+    pub data_bytes: u64,
+    pub stored_bytes: u64,
+    pub blocks_corrupted: u64,
+    pub corruption: Vec<CorruptionInfo>,
+    /// `total_samples` заголовка сходится с блоками (или не заполнен)
+    pub totals_ok: bool,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Inspection {
+    /// Файл прочитан без повреждений и расхождений итогов.
+    pub fn healthy(&self) -> bool {
+        self.blocks_corrupted == 0 && self.totals_ok
+    }
+}
+
+impl From<&GlosHeader> for HeaderInfo {
+    fn from(h: &GlosHeader) -> Self {
+        Self {
+            version: h.version,
+            flags: h.flags,
+            sdr_type: h.sdr_type.to_string(),
+            iq_format: h.iq_format.to_string(),
+            compression: h.compression.to_string(),
+            sample_rate: h.sample_rate,
+            center_freq: h.center_freq,
+            gain_db: h.gain_db,
+            timestamp_start: h.timestamp_start,
+            timestamp_end: h.timestamp_end,
+            time_source: h.time_source.to_string(),
+            clock_offset_ns: h.clock_offset_ns,
+            total_samples: h.total_samples,
+            duration_secs: h.duration().map(|d| d.as_secs_f64()),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for Inspection {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let h = &self.header;
+        writeln!(f, "Version     : {} (flags {:#04x})", h.version, h.flags)?;
+        writeln!(f, "SDR         : {}", h.sdr_type)?;
+        writeln!(f, "Format      : {}, {}", h.iq_format, h.compression)?;
+        writeln!(f, "Sample rate : {} Hz", h.sample_rate)?;
+        writeln!(f, "Center freq : {} Hz", h.center_freq)?;
+        writeln!(f, "Gain        : {:.1} dB", h.gain_db)?;
+        writeln!(
+            f,
+            "Time        : {} .. {} (Unix s)",
+            h.timestamp_start, h.timestamp_end
+        )?;
+        writeln!(
+            f,
+            "Clock       : {} (offset {:+.3} ms)",
+            h.time_source,
+            h.clock_offset_ns as f64 / 1e6
+        )?;
+        match h.duration_secs {
+            Some(d) => writeln!(f, "Duration    : {d:.3} s")?,
+            None => writeln!(f, "Duration    : unknown (not finalized)")?,
+        }
+        writeln!(
+            f,
+            "Samples     : {} (header {})",
+            self.samples, h.total_samples
+        )?;
+        writeln!(f, "Blocks      : {}", self.block_count)?;
+        writeln!(
+            f,
+            "Compression : {:.2}x ({} -> {} bytes)",
+            self.compression_ratio, self.data_bytes, self.stored_bytes
+        )?;
+        writeln!(
+            f,
+            "CRC         : {} ok, {} corrupted",
+            self.block_count, self.blocks_corrupted
+        )?;
+        writeln!(
+            f,
+            "Totals      : {}",
+            if self.totals_ok { "ok" } else { "MISMATCH" }
+        )?;
+        if self.overloaded_blocks > 0 {
+            writeln!(
+                f,
+                "Overload    : {} of {} blocks",
+                self.overloaded_blocks, self.block_count
+            )?;
+        }
+
+        if !self.blocks.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{:>8} {:>12} {:>22} {:>10} {:>10}",
+                "block", "offset", "timestamp_ns", "samples", "bytes"
+            )?;
+            for b in &self.blocks {
+                writeln!(
+                    f,
+                    "{:>8} {:>12} {:>22} {:>10} {:>10}",
+                    b.index, b.offset, b.timestamp_ns, b.sample_count, b.stored_bytes
+                )?;
+            }
+        }
+
+        if !self.gaps.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Gaps ({}):", self.gaps.len())?;
+            for g in &self.gaps {
+                let what = if g.samples > 0 { "missing" } else { "overlap" };
+                writeln!(
+                    f,
+                    "  before block {}: {} samples {what} (expected {} ns, got {} ns)",
+                    g.before_block,
+                    g.samples.unsigned_abs(),
+                    g.expected_ns,
+                    g.timestamp_ns
+                )?;
+            }
+        }
+
+        if !self.events.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Events ({}):", self.events.len())?;
+            for e in &self.events {
+                write!(f, "  block {} @ {} ns:", e.block, e.timestamp_ns)?;
+                if e.overload {
+                    write!(f, " overload")?;
+                }
+                if let Some(gain) = e.gain_db {
+                    write!(f, " gain {gain:.1} dB")?;
+                }
+                if let Some(n) = e.dropped_samples {
+                    write!(f, " {n} samples dropped")?;
+                }
+                if let (Some(id), Some(external_ns)) = (e.sync_marker, e.external_ns) {
+                    write!(f, " sync #{id} (external {external_ns} ns)")?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        if !self.corruption.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Corruption ({}):", self.corruption.len())?;
+            for c in &self.corruption {
+                write!(f, "  offset {}: {}", c.offset, c.kind)?;
+                if let Some(ts) = c.timestamp_guess {
+                    write!(f, " near {ts} ns")?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Читает файл целиком и собирает сводку. `with_blocks == false` —
+/// не хранить список блоков (для многочасовых записей).
+pub fn inspect<R: Read>(
+    inner: R,
+    with_blocks: bool,
+) -> GlosResult<Inspection> {
+    let mut reader = RawBlockReader::new(inner)?;
+    let header = reader.header().clone();
+    let rate = header.sample_rate as u128;
+    let sample_size = header.iq_format.sample_size() as u64;
+
+    let mut blocks = Vec::new();
+    let mut gaps = Vec::new();
+    let mut events = Vec::new();
+    let mut overloaded_blocks = 0u64;
+    let (mut block_count, mut samples, mut stored_bytes) = (0u64, 0u64, 0u64);
+    let mut next_ns: Option<u64> = None;
+
+    while let Some(result) = reader.next_block() {
+        // Повреждения учитываются в stats, разбор продолжаем
+        let Ok((offset, block)) = result else {
+            continue;
+        };
+
+        // `next_ns` есть только при известной частоте
+        if let Some(expected) = next_ns {
+            let diff_ns = block.timestamp_ns as i128 - expected as i128;
+            // Округление до выборки: дрожание меток меньше периода — не разрыв
+            let diff = (diff_ns * rate as i128 + diff_ns.signum() * 500_000_000) / 1_000_000_000;
+            if diff != 0 {
+                gaps.push(GapInfo {
+                    before_block: block_count,
+                    expected_ns: expected,
+                    timestamp_ns: block.timestamp_ns,
+                    samples: diff as i64,
+                });
+            }
+        }
+        next_ns = (block.sample_count as u128 * 1_000_000_000)
+            .checked_div(rate)
+            .map(|duration_ns| block.timestamp_ns.saturating_add(duration_ns as u64));
+
+        if with_blocks {
+            blocks.push(BlockInfo {
+                index: block_count,
+                offset,
+                timestamp_ns: block.timestamp_ns,
+                sample_count: block.sample_count,
+                stored_bytes: block.data.len(),
+            });
+        }
+
+        let a = block.attributes;
+        if !a.is_empty() {
+            events.push(EventInfo {
+                block: block_count,
+                timestamp_ns: block.timestamp_ns,
+                overload: a.overload,
+                gain_db: a.gain_db,
+                dropped_samples: a.dropped_samples,
+                sync_marker: a.sync_marker.map(|m| m.id),
+                external_ns: a.sync_marker.map(|m| m.external_ns),
+            });
+        }
+        overloaded_blocks += a.overload as u64;
+
+        block_count += 1;
+        samples += block.sample_count as u64;
+        stored_bytes += block.data.len() as u64;
+    }
+
+    let stats = reader.stats();
+    let data_bytes = samples * sample_size;
+    let corruption = stats
+        .corruption_events
+        .iter()
+        .map(|e| CorruptionInfo {
+            offset: e.approx_offset,
+            kind: e.kind.to_string(),
+            timestamp_guess: e.timestamp_guess,
+        })
+        .collect();
+
+    Ok(Inspection {
+        header: HeaderInfo::from(&header),
+        block_count,
+        samples,
+        blocks,
+        gaps,
+        events,
+        overloaded_blocks,
+        compression_ratio: if stored_bytes > 0 {
+            data_bytes as f64 / stored_bytes as f64
+        } else {
+            1.0
+        },
+        data_bytes,
+        stored_bytes,
+        blocks_corrupted: stats.blocks_corrupted,
+        corruption,
+        totals_ok: header.total_samples == 0 || header.total_samples == samples,
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_core::{
+        prelude::{
+            BlockAttributes, Compression, GlosHeaderExt, GlosWriter, IqBlock, IqBlockExt, SdrType,
+            SyncMarker,
+        },
+        GLOS_BLOCK_ATTRIBUTES_VERSION, GLOS_BLOCK_OVERHEAD, GLOS_HEADER_SIZE,
+    };
+
+    use super::*;
+
+    fn recording(compression: Compression) -> Vec<u8> {
+        let mut header = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+        header.compression = compression;
+
+        let mut buf = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut buf, header).unwrap();
+        // 100 выборок = 100 мкс; перед третьим блоком пропуск 50 выборок
+        for ts in [0, 100_000, 250_000] {
+            writer
+                .write_block(IqBlock::new(ts, 100, vec![0; 400]))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        buf.into_inner()
+    }
+
+    #[test]
+    fn test_inspect_reports_blocks_and_gaps() {
+        let info = inspect(Cursor::new(recording(Compression::None)), true).unwrap();
+
+        assert_eq!(info.block_count, 3);
+        assert_eq!(info.samples, 300);
+        assert!(info.healthy());
+        assert_eq!(info.compression_ratio, 1.0);
+        assert_eq!(
+            info.blocks
+                .iter()
+                .map(|b| b.timestamp_ns)
+                .collect::<Vec<_>>(),
+            vec![0, 100_000, 250_000]
+        );
+        assert_eq!(
+            info.gaps,
+            vec![GapInfo {
+                before_block: 2,
+                expected_ns: 200_000,
+                timestamp_ns: 250_000,
+                samples: 50,
+            }]
+        );
+
+        let text = info.to_string();
+        assert!(text.contains("50 samples missing"), "{text}");
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["header"]["sample_rate"], 1_000_000);
+        assert_eq!(json["gaps"][0]["samples"], 50);
+    }
+
+    #[test]
+    fn test_inspect_compression_and_corruption() {
+        let compressed = inspect(Cursor::new(recording(Compression::Lz4)), false).unwrap();
+        assert!(compressed.blocks.is_empty());
+        assert!(compressed.compression_ratio > 1.0);
+
+        // Порча данных второго блока
+        let mut bytes = recording(Compression::None);
+        let second = GLOS_HEADER_SIZE + GLOS_BLOCK_OVERHEAD + 400 + 30;
+        bytes[second] ^= 0xFF;
+
+        let info = inspect(Cursor::new(bytes), false).unwrap();
+        assert_eq!(info.block_count, 2);
+        // После сбоя кадры ищутся побайтово: повреждений может быть больше
+        assert!(info.blocks_corrupted >= 1);
+        assert!(!info.totals_ok);
+        assert!(!info.healthy());
+        assert_eq!(info.corruption[0].kind, "crc");
+    }
+
+    #[test]
+    fn test_inspect_reports_block_events() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+
+        let mut buf = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut buf, header).unwrap();
+        for (ts, attributes) in [
+            (0, BlockAttributes::default()),
+            (
+                100_000,
+                BlockAttributes {
+                    overload: true,
+                    gain_db: Some(10.0),
+                    dropped_samples: None,
+                    sync_marker: None,
+                },
+            ),
+        ] {
+            let mut block = IqBlock::new(ts, 100, vec![0; 400]);
+            block.attributes = attributes;
+            writer.write_block(block).unwrap();
+        }
+        let marker = SyncMarker {
+            id: 3,
+            external_ns: 77,
+        };
+        writer
+            .write_block(IqBlock::new_sync_marker(200_000, marker))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let info = inspect(Cursor::new(buf.into_inner()), false).unwrap();
+        assert_eq!(info.overloaded_blocks, 1);
+        assert_eq!(info.events.len(), 2);
+        assert_eq!(
+            info.events[0],
+            EventInfo {
+                block: 1,
+                timestamp_ns: 100_000,
+                overload: true,
+                gain_db: Some(10.0),
+                dropped_samples: None,
+                sync_marker: None,
+                external_ns: None,
+            }
+        );
+        assert_eq!(
+            (info.events[1].sync_marker, info.events[1].external_ns),
+            (Some(3), Some(77))
+        );
+
+        let text = info.to_string();
+        assert!(text.contains("Overload    : 1 of 3 blocks"), "{text}");
+        assert!(
+            text.contains("block 1 @ 100000 ns: overload gain 10.0 dB"),
+            "{text}"
+        );
+        assert!(
+            text.contains("block 2 @ 200000 ns: sync #3 (external 77 ns)"),
+            "{text}"
+        );
+    }
+}
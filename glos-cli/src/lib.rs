@@ -1 +1,11 @@
+//! Инструменты командной строки GLOS (`glos`, `glos-inspect`, `glos-repair`).
 
+pub mod conformance;
+pub mod inspect;
+pub mod repair;
+
+pub use conformance::{
+    check_output, command_args, run, write_corpus, CaseResult, Outcome, DEFAULT_CASE_TIMEOUT,
+};
+pub use inspect::{inspect, BlockInfo, CorruptionInfo, EventInfo, GapInfo, HeaderInfo, Inspection};
+pub use repair::{repair, RepairReport};
@@ -1,3 +1,497 @@
+use std::{fs::File, path::PathBuf, time::Duration};
+
+use clap::{Parser, Subcommand};
+use glos_cli::conformance;
+use glos_core::{
+    dsp::{transcode, ConvertOptions},
+    export_raw, import_raw, merge,
+    prelude::{Compression, GlosReader, IqFormat, SdrType},
+    split_by_samples, split_by_time, testvec, RawExportOptions, RawFormat, RawImportOptions,
+    RAW_IMPORT_BLOCK_SAMPLES,
+};
+use log::{error, info, warn};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "glos",
+    version = env!("CARGO_PKG_VERSION"),
+    about = "GLOS file tools",
+    long_about = None,
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+#[derive(Subcommand, Debug)]
+enum Cmd {
+    /// Прогнать стороннюю реализацию по корпусу тестовых файлов.
+    ///
+    /// Команда после `--` получает путь файла (вместо `{}` или последним
+    /// аргументом) и печатает последней строкой stdout JSON-вердикт:
+    /// `{"header_ok": true, "blocks_ok": 2, "corruption_detected": false,
+    /// "samples": 2000, "totals_ok": true}`
+    Conformance {
+        /// Куда записать корпус (по умолчанию — временный каталог)
+        #[arg(long)]
+        corpus_dir: Option<PathBuf>,
+        /// Только записать корпус с ожидаемыми вердиктами
+        #[arg(long, requires = "corpus_dir")]
+        write_only: bool,
+        /// Сколько ждать вердикта по одному файлу (секунды)
+        #[arg(long, default_value_t = conformance::DEFAULT_CASE_TIMEOUT.as_secs())]
+        timeout: u64,
+        /// Проверяемая команда и её аргументы
+        #[arg(last = true, required_unless_present = "write_only")]
+        command: Vec<String>,
+    },
+    /// Вердикт эталонной реализации по файлу (JSON, как в conformance)
+    Verdict { file: PathBuf },
+    /// Завернуть сырой IQ файл (GNU Radio cf32, RTL-SDR cu8, cs16, cs8) в .glos
+    ImportRaw {
+        /// Сырой файл без заголовка
+        input: PathBuf,
+        /// Выходной файл (по умолчанию — входной с расширением .glos)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Частота дискретизации (Гц)
+        #[arg(long)]
+        sample_rate: u32,
+        /// Центральная частота (Гц)
+        #[arg(long)]
+        center_freq: u64,
+        /// Формат выборок: cs8, cu8, cs16, cf32 (по умолчанию —
+        /// автоопределение)
+        #[arg(long)]
+        format: Option<RawFormat>,
+        /// Устройство, записавшее файл
+        #[arg(long, default_value_t = SdrType::Unknown)]
+        sdr: SdrType,
+        /// Время первой выборки (Unix-секунды)
+        #[arg(long, default_value_t = 0)]
+        start_time: u64,
+        /// Сжатие: none, lz4, zstd
+        #[arg(long, default_value_t = Compression::None)]
+        compress: Compression,
+        /// Выборок в блоке
+        #[arg(long, default_value_t = RAW_IMPORT_BLOCK_SAMPLES)]
+        block_samples: u32,
+    },
+    /// Выгрузить .glos в сырой IQ файл (для gnss-sdr, MATLAB, GNU Radio)
+    ExportRaw {
+        /// Исходная запись
+        input: PathBuf,
+        /// Выходной файл (по умолчанию — входной с расширением формата)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Формат выборок: cs8, cu8, cs16, cf32 (по умолчанию — как в записи)
+        #[arg(long)]
+        format: Option<RawFormat>,
+        /// Заполнять разрывы времени нулями
+        #[arg(long)]
+        zero_fill_gaps: bool,
+        /// Начало интервала (Unix-секунды, можно дробные)
+        #[arg(long)]
+        from: Option<f64>,
+        /// Конец интервала, не включая (Unix-секунды, можно дробные)
+        #[arg(long)]
+        to: Option<f64>,
+    },
+    /// Разрезать запись на куски по границам блоков
+    Split {
+        /// Исходная запись
+        input: PathBuf,
+        /// Шаблон имён кусков: `part.glos` → `part_000.glos`, … (по
+        /// умолчанию — рядом с входным)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Длительность куска (секунды)
+        #[arg(long, required_unless_present = "samples", conflicts_with = "samples")]
+        seconds: Option<f64>,
+        /// Выборок в куске (не больше)
+        #[arg(long)]
+        samples: Option<u64>,
+    },
+    /// Перекодировать выборки записи в другой формат (int8, int16, float32)
+    Transcode {
+        /// Исходная запись
+        input: PathBuf,
+        /// Выходной файл
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Формат выборок: int8, int16, float32
+        #[arg(long)]
+        format: IqFormat,
+        /// TPDF-дизеринг при сужении формата
+        #[arg(long)]
+        dither: bool,
+    },
+    /// Склеить совместимые записи (частота, центр, формат) в одну
+    Merge {
+        /// Записи по порядку времени
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+        /// Выходной файл
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
 fn main() {
-    println!("Hello, Glos CLI!");
+    let cli = Cli::parse();
+
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Info)
+        .format_target(false)
+        .format_timestamp(None)
+        .init();
+
+    match cli.command {
+        Cmd::Conformance {
+            corpus_dir,
+            write_only,
+            timeout,
+            command,
+        } => run_conformance(
+            corpus_dir,
+            write_only,
+            Duration::from_secs(timeout),
+            &command,
+        ),
+        Cmd::Verdict { file } => {
+            let bytes = match std::fs::read(&file) {
+                Ok(b) => b,
+                Err(e) => {
+                    error!("{file:?}: {e}");
+                    std::process::exit(1);
+                }
+            };
+            let verdict = testvec::reference_verdict(&bytes);
+            println!(
+                "{}",
+                serde_json::to_string(&verdict).expect("verdict serializes")
+            );
+        }
+        Cmd::ImportRaw {
+            input,
+            output,
+            sample_rate,
+            center_freq,
+            format,
+            sdr,
+            start_time,
+            compress,
+            block_samples,
+        } => {
+            let options = RawImportOptions {
+                format,
+                sdr,
+                start_ns: start_time.saturating_mul(1_000_000_000),
+                block_samples,
+                compression: compress,
+                ..RawImportOptions::new(sample_rate, center_freq)
+            };
+            run_import_raw(input, output, &options);
+        }
+        Cmd::ExportRaw {
+            input,
+            output,
+            format,
+            zero_fill_gaps,
+            from,
+            to,
+        } => {
+            let secs_to_ns = |s: f64| (s.max(0.0) * 1e9) as u64;
+            let options = RawExportOptions {
+                format,
+                zero_fill_gaps,
+                range: (from.is_some() || to.is_some())
+                    .then(|| (from.map_or(0, secs_to_ns), to.map_or(u64::MAX, secs_to_ns))),
+            };
+            run_export_raw(input, output, &options);
+        }
+        Cmd::Split {
+            input,
+            output,
+            seconds,
+            samples,
+        } => run_split(input, output, seconds, samples),
+        Cmd::Transcode {
+            input,
+            output,
+            format,
+            dither,
+        } => {
+            let options = ConvertOptions {
+                dither,
+                ..Default::default()
+            };
+            run_transcode(input, output, format, options);
+        }
+        Cmd::Merge { inputs, output } => run_merge(&inputs, output),
+    }
+}
+
+fn run_import_raw(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    options: &RawImportOptions,
+) {
+    let output = output.unwrap_or_else(|| input.with_extension("glos"));
+    if output == input {
+        error!("--output: would overwrite the input file");
+        std::process::exit(1);
+    }
+
+    let file = match File::create(&output) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("--output: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let import = match import_raw(&input, file, options) {
+        Ok(i) => i,
+        Err(e) => {
+            let _ = std::fs::remove_file(&output);
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    for w in &import.warnings {
+        warn!("{w}");
+    }
+    if !import.format.disagrees() {
+        info!("Format  : {}", import.format);
+    }
+    info!(
+        "Written : {output:?} ({} samples in {} blocks)",
+        import.header.total_samples, import.blocks
+    );
+}
+
+fn run_split(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    seconds: Option<f64>,
+    samples: Option<u64>,
+) {
+    let output = output.unwrap_or_else(|| input.clone());
+
+    let result = match (seconds, samples) {
+        (Some(s), _) => match Duration::try_from_secs_f64(s) {
+            Ok(d) => split_by_time(&input, &output, d),
+            Err(e) => {
+                error!("--seconds: {e}");
+                std::process::exit(1);
+            }
+        },
+        (None, Some(n)) => split_by_samples(&input, &output, n),
+        (None, None) => unreachable!("clap requires --seconds or --samples"),
+    };
+
+    let split = match result {
+        Ok(s) => s,
+        Err(e) => {
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if split.corrupted_blocks > 0 {
+        warn!("Skipped {} corrupted blocks", split.corrupted_blocks);
+    }
+    for part in &split.parts {
+        info!(
+            "Written : {:?} ({} samples in {} blocks)",
+            part.path, part.header.total_samples, part.blocks
+        );
+    }
+}
+
+fn run_transcode(
+    input: PathBuf,
+    output: PathBuf,
+    format: IqFormat,
+    options: ConvertOptions,
+) {
+    if output == input {
+        error!("--output: would overwrite the input file");
+        std::process::exit(1);
+    }
+
+    let mut reader = match File::open(&input)
+        .map_err(Into::into)
+        .and_then(GlosReader::new)
+    {
+        Ok(r) => r,
+        Err(e) => {
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let file = match File::create(&output) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("--output: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let t = match transcode(&mut reader, file, format, options) {
+        Ok(t) => t,
+        Err(e) => {
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if t.corrupted_blocks > 0 {
+        warn!("Skipped {} corrupted blocks", t.corrupted_blocks);
+    }
+    if t.clipped > 0 {
+        warn!("{} I/Q components clipped to the {format} range", t.clipped);
+    }
+    info!(
+        "Written : {output:?} ({} samples in {} blocks)",
+        t.samples, t.blocks
+    );
+}
+
+fn run_merge(
+    inputs: &[PathBuf],
+    output: PathBuf,
+) {
+    let merged = match merge(inputs, &output) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if merged.corrupted_blocks > 0 {
+        warn!("Skipped {} corrupted blocks", merged.corrupted_blocks);
+    }
+    info!(
+        "Written : {output:?} ({} samples in {} blocks)",
+        merged.header.total_samples, merged.blocks
+    );
+}
+
+fn run_conformance(
+    corpus_dir: Option<PathBuf>,
+    write_only: bool,
+    timeout: Duration,
+    command: &[String],
+) {
+    // Временный каталог удаляется при выходе из функции
+    let tmp;
+    let dir = match corpus_dir {
+        Some(d) => d,
+        None => match tempfile::tempdir() {
+            Ok(t) => {
+                tmp = t;
+                tmp.path().to_path_buf()
+            }
+            Err(e) => {
+                error!("--corpus-dir: cannot create temporary directory: {e}");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let vectors = testvec::corpus();
+    if let Err(e) = conformance::write_corpus(&vectors, &dir) {
+        error!("--corpus-dir: {e}");
+        std::process::exit(1);
+    }
+    info!("Corpus: {} files in {dir:?}", vectors.len());
+
+    if write_only {
+        return;
+    }
+
+    let results = conformance::run(&vectors, &dir, command, timeout);
+    for r in &results {
+        println!("{r}");
+    }
+
+    let passed = results.iter().filter(|r| r.passed()).count();
+    println!("{passed}/{} vectors passed", results.len());
+
+    if passed != results.len() {
+        std::process::exit(1);
+    }
+}
+
+fn run_export_raw(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    options: &RawExportOptions,
+) {
+    let mut reader = match File::open(&input)
+        .map_err(Into::into)
+        .and_then(GlosReader::new)
+    {
+        Ok(r) => r,
+        Err(e) => {
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let format = options
+        .format
+        .unwrap_or_else(|| RawFormat::native(reader.header().iq_format));
+    let output = output.unwrap_or_else(|| input.with_extension(format.to_string()));
+    if output == input {
+        error!("--output: would overwrite the input file");
+        std::process::exit(1);
+    }
+
+    let file = match File::create(&output) {
+        Ok(f) => f,
+        Err(e) => {
+            error!("--output: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let export = match export_raw(&mut reader, file, options) {
+        Ok(x) => x,
+        Err(e) => {
+            error!("{input:?}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if export.corrupted_blocks > 0 {
+        warn!("Skipped {} corrupted blocks", export.corrupted_blocks);
+    }
+    if export.clipped > 0 {
+        warn!(
+            "{} I/Q components clipped to the {format} range",
+            export.clipped
+        );
+    }
+    if export.gap_samples > 0 {
+        info!("Gaps    : {} zero samples inserted", export.gap_samples);
+    }
+    info!(
+        "Written : {output:?} ({} samples, {format})",
+        export.samples
+    );
+
+    let header = reader.header();
+    match format.gnss_sdr_item_type() {
+        Some(item_type) => info!(
+            "gnss-sdr: SignalSource.item_type={item_type} SignalSource.sampling_frequency={}",
+            header.sample_rate
+        ),
+        None => info!("gnss-sdr cannot read {format}; use cs8, cs16 or cf32"),
+    }
 }
@@ -0,0 +1,250 @@
+//! Спасение повреждённой записи для `glos-repair`.
+//!
+//! [`GlosReader`] пропускает блоки с неверной CRC, нераспаковывающимися
+//! данными или неверным `sample_count` и находит следующий целый кадр.
+//! [`repair`] переписывает только целые блоки в новый файл с тем же
+//! заголовком и сжатием, пересчитывает `total_samples` и оценивает, сколько
+//! выборок потеряно.
+
+use std::{
+    fmt,
+    io::{Read, Seek, Write},
+};
+
+use glos_core::{
+    prelude::{GlosError, GlosHeader, GlosReader, GlosResult, GlosWriter},
+    CorruptionEvent,
+};
+
+/// Итог [`repair`].
+#[derive(Debug, Clone)]
+pub struct RepairReport {
+    /// Заголовок исходного файла
+    pub original: GlosHeader,
+    /// Заголовок восстановленного файла
+    pub header: GlosHeader,
+    pub blocks_recovered: u64,
+    pub samples_recovered: u64,
+    /// Повреждений, найденных читателем (после сбоя кадры ищутся
+    /// побайтово, так что одно повреждение может дать несколько)
+    pub blocks_corrupted: u64,
+    /// Оценка потерянных выборок: по разрывам меток времени на месте
+    /// повреждений, а если больше — по `total_samples` исходного заголовка
+    /// (он учитывает и потерянный хвост)
+    pub samples_lost: u64,
+    /// Первые повреждения (см. [`glos_core::MAX_CORRUPTION_EVENTS`])
+    pub corruption: Vec<CorruptionEvent>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RepairReport {
+    /// Доля сохранённых выборок от исходных (1.0 — ничего не потеряно).
+    pub fn recovered_ratio(&self) -> f64 {
+        let total = self.samples_recovered + self.samples_lost;
+        if total == 0 {
+            return 1.0;
+        }
+        self.samples_recovered as f64 / total as f64
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for RepairReport {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "Recovered : {} samples in {} blocks ({:.2}%)",
+            self.samples_recovered,
+            self.blocks_recovered,
+            self.recovered_ratio() * 100.0
+        )?;
+        writeln!(f, "Lost      : ~{} samples", self.samples_lost)?;
+        writeln!(f, "Corrupted : {} blocks", self.blocks_corrupted)?;
+        writeln!(
+            f,
+            "Header    : total_samples {} -> {}, timestamp_end {} -> {}",
+            self.original.total_samples,
+            self.header.total_samples,
+            self.original.timestamp_end,
+            self.header.timestamp_end
+        )?;
+        for e in &self.corruption {
+            writeln!(f, "  {e}")?;
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Переписывает целые блоки записи `input` в `output`.
+///
+/// Заголовок берётся от исходного файла; `total_samples` пересчитывается,
+/// `timestamp_end` сохраняется, а у незавершённой записи (0) выставляется
+/// по концу последнего блока. Ошибки ввода-вывода прерывают работу,
+/// повреждения — нет. Файл без единого целого блока — ошибка.
+pub fn repair<R: Read, W: Write + Seek>(
+    input: R,
+    output: W,
+) -> GlosResult<RepairReport> {
+    let mut reader = GlosReader::new(input)?;
+    let original = reader.header().clone();
+    let rate = original.sample_rate as u128;
+
+    let mut writer = GlosWriter::new(output, original.clone())?;
+    let mut gap_lost = 0u64;
+    // Конец последнего целого блока (нс) и число повреждений к тому моменту
+    let mut last: Option<(u64, u64)> = None;
+
+    while let Some(result) = reader.next_block() {
+        let block = match result {
+            Ok(b) => b,
+            Err(e @ GlosError::Io(_)) => return Err(e),
+            // Повреждение уже учтено в stats, читатель ищет следующий кадр
+            Err(_) => continue,
+        };
+
+        let corrupted = reader.stats().blocks_corrupted;
+        if let Some((end_ns, seen)) = last {
+            // Разрыв без повреждений — пауза в самой записи, не потеря
+            if corrupted > seen && block.timestamp_ns > end_ns {
+                let gap_ns = (block.timestamp_ns - end_ns) as u128;
+                gap_lost += ((gap_ns * rate + 500_000_000) / 1_000_000_000) as u64;
+            }
+        }
+
+        let duration_ns = (block.sample_count as u128 * 1_000_000_000)
+            .checked_div(rate)
+            .unwrap_or(0) as u64;
+        last = Some((block.timestamp_ns.saturating_add(duration_ns), corrupted));
+
+        writer.write_block(block)?;
+    }
+
+    let Some((end_ns, _)) = last else {
+        return Err(GlosError::format_violation("no intact blocks to recover"));
+    };
+
+    let stats = reader.stats();
+    let samples_recovered = writer.total_samples();
+    let mut header = writer.header().clone();
+    header.total_samples = samples_recovered;
+    header.timestamp_end = match original.timestamp_end {
+        0 => end_ns / 1_000_000_000,
+        t => t,
+    };
+
+    let report = RepairReport {
+        blocks_recovered: writer.block_count(),
+        samples_recovered,
+        blocks_corrupted: stats.blocks_corrupted,
+        samples_lost: gap_lost.max(original.total_samples.saturating_sub(samples_recovered)),
+        corruption: stats.corruption_events.clone(),
+        original,
+        header,
+    };
+    writer.finish_at(report.header.timestamp_end)?;
+
+    Ok(report)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_core::{
+        prelude::{GlosHeaderExt, IqBlock, IqBlockExt, SdrType},
+        read_all_blocks, GLOS_BLOCK_OVERHEAD, GLOS_HEADER_SIZE,
+    };
+
+    use super::*;
+
+    /// 4 блока по 100 выборок (100 мкс), `total_samples` = 400.
+    fn recording() -> Vec<u8> {
+        let header = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+
+        let mut buf = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut buf, header).unwrap();
+        for i in 0..4u64 {
+            writer
+                .write_block(IqBlock::new(i * 100_000, 100, vec![i as u8; 400]))
+                .unwrap();
+        }
+        writer.finish_at(1_700_000_000).unwrap();
+        buf.into_inner()
+    }
+
+    fn repaired(bytes: Vec<u8>) -> (RepairReport, Vec<IqBlock>, GlosHeader) {
+        let mut out = Cursor::new(Vec::new());
+        let report = repair(Cursor::new(bytes), &mut out).unwrap();
+
+        let mut reader = GlosReader::new(Cursor::new(out.into_inner())).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        (report, blocks, reader.header().clone())
+    }
+
+    #[test]
+    fn test_repair_drops_corrupted_block() {
+        let frame = GLOS_BLOCK_OVERHEAD + 400;
+        let mut bytes = recording();
+        bytes[GLOS_HEADER_SIZE + frame + 50] ^= 0xFF;
+
+        let (report, blocks, header) = repaired(bytes);
+
+        assert_eq!(report.blocks_recovered, 3);
+        assert_eq!(report.samples_recovered, 300);
+        assert_eq!(report.samples_lost, 100);
+        assert!(report.blocks_corrupted >= 1);
+        assert_eq!(report.recovered_ratio(), 0.75);
+
+        assert_eq!(
+            blocks.iter().map(|b| b.timestamp_ns).collect::<Vec<_>>(),
+            vec![0, 200_000, 300_000]
+        );
+        assert_eq!(header.total_samples, 300);
+        assert_eq!(header.timestamp_end, 1_700_000_000);
+        assert!(report.to_string().contains("Lost      : ~100 samples"));
+    }
+
+    #[test]
+    fn test_repair_truncated_unfinalized_file() {
+        // Запись оборвалась посреди последнего блока, заголовок не обновлён
+        let frame = GLOS_BLOCK_OVERHEAD + 400;
+        let mut bytes = recording();
+        bytes.truncate(GLOS_HEADER_SIZE + 3 * frame + 10);
+        let mut header =
+            GlosHeader::deserialize(bytes[..GLOS_HEADER_SIZE].try_into().unwrap()).unwrap();
+        header.total_samples = 0;
+        header.timestamp_end = 0;
+        header.timestamp_start = 0;
+        bytes[..GLOS_HEADER_SIZE].copy_from_slice(&header.serialize().unwrap());
+
+        let (report, blocks, header) = repaired(bytes);
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(report.samples_lost, 0);
+        assert_eq!(header.total_samples, 300);
+        assert_eq!(header.timestamp_end, 0);
+
+        let empty = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000)
+            .serialize()
+            .unwrap();
+        assert!(repair(Cursor::new(empty), Cursor::new(Vec::new())).is_err());
+    }
+}
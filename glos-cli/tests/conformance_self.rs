@@ -0,0 +1,47 @@
+use std::process::Command;
+
+/// Эталонная реализация (`glos verdict`) проходит собственный корпус.
+#[test]
+fn test_reference_passes_conformance() {
+    let glos = env!("CARGO_BIN_EXE_glos");
+
+    let out = Command::new(glos)
+        .args(["conformance", "--", glos, "verdict", "{}"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+
+    assert!(out.status.success(), "{stdout}");
+    assert!(
+        !stdout.contains("FAIL") && !stdout.contains("ERROR"),
+        "{stdout}"
+    );
+}
+
+/// Реализация, принимающая любой файл, проваливает векторы с ошибками.
+#[test]
+#[cfg(unix)]
+fn test_permissive_reader_fails_invalid_vectors() {
+    let glos = env!("CARGO_BIN_EXE_glos");
+    let verdict = r#"{"header_ok": true, "blocks_ok": 2, "corruption_detected": false, "samples": 2000, "totals_ok": true}"#;
+
+    let out = Command::new(glos)
+        .args([
+            "conformance",
+            "--",
+            "sh",
+            "-c",
+            &format!("echo '{verdict}'"),
+            "{}",
+        ])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+
+    assert!(!out.status.success());
+    assert!(stdout.contains("PASS  valid_minimal"), "{stdout}");
+    assert!(
+        stdout.contains("FAIL  invalid_magic: header_ok: expected false, got true"),
+        "{stdout}"
+    );
+}
@@ -7,7 +7,10 @@
 
 use std::fs::File;
 
-use glos_core::serialization::{read_all_blocks, GlosReader};
+use glos_core::{
+    serialization::{read_all_blocks, GlosReader},
+    RecordingMetadata,
+};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input_path = std::env::args()
@@ -15,7 +18,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or_else(|| "recording.glos".to_string());
 
     // --- GlosReader валидирует заголовок при открытии ---
-    let file = File::open(input_path)?;
+    let file = File::open(&input_path)?;
     let mut reader = match GlosReader::new(file) {
         Ok(r) => r,
         Err(e) => {
@@ -34,6 +37,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Compression   : {:?}", h.compression);
     println!("  Total Samples : {}", h.total_samples);
     println!("  Timestamp End : {}", h.timestamp_end);
+    if let Some(d) = h.duration() {
+        println!("  Duration      : {:.3} s", d.as_secs_f64());
+    }
+    println!("  Data Bytes    : {}", h.expected_data_bytes());
+    println!("  Bitrate       : {} bit/s", h.bits_per_second());
+
+    // --- Метаданные рядом с файлом (rec.glos.meta.json) ---
+    match RecordingMetadata::load(input_path.as_ref()) {
+        Ok(Some(meta)) => {
+            if let Some(chain) = &meta.rf_chain {
+                println!("  RF Chain      : {chain}");
+            }
+            if let Some(t) = &meta.thumbnail {
+                println!(
+                    "  Thumbnail     : {}×{} ({:.1}..{:.1} dBFS)",
+                    t.rows, t.cols, t.db_min, t.db_max
+                );
+            }
+        }
+        Ok(None) => {}
+        Err(e) => println!("  Metadata      : ✗ {e}"),
+    }
 
     // --- Читаем все блоки (повреждённые пропускаются) ---
     let blocks = read_all_blocks(&mut reader)?;
@@ -41,6 +66,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n✓ Read complete");
     println!("  Blocks ok        : {}", reader.stats().blocks_ok);
     println!("  Blocks corrupted : {}", reader.stats().blocks_corrupted);
+    for event in &reader.stats().corruption_events {
+        println!("    at {event}");
+    }
     println!("  Samples recovered: {}", reader.stats().samples_recovered);
 
     // --- Валидация total_samples == Σ sample_count ---
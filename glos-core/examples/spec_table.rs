@@ -0,0 +1,32 @@
+//! Пример: таблица компоновки формата из `glos_core::spec`
+//!
+//! `cargo run -p glos-core --example spec_table [markdown|json] [версия]`
+//!
+//! Вывод `markdown` вставляется в приложение A спецификации
+//! (`docs/glos_file_format_spec_v1_0.md`); тест `spec` проверяет, что он
+//! там актуален.
+
+use glos_core::spec;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let format = args.next().unwrap_or_else(|| "markdown".to_string());
+    let version = args
+        .next()
+        .map(|v| v.parse::<u8>().expect("version must be a number"))
+        .unwrap_or(glos_core::GLOS_VERSION);
+
+    let Some(layout) = spec::layout(version) else {
+        eprintln!("✗ Unknown format version {version}");
+        std::process::exit(1);
+    };
+
+    match format.as_str() {
+        "markdown" | "md" => print!("{}", layout.to_markdown()),
+        "json" => println!("{}", layout.to_json()),
+        other => {
+            eprintln!("✗ Unknown output '{other}'. Use: markdown, json");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Пример: разбор .glos файла в браузере через wasm-bindgen
+//!
+//! Сборка:
+//! ```sh
+//! cargo build --release --target wasm32-unknown-unknown
+//! wasm-bindgen --target web --out-dir www/pkg \
+//!     target/wasm32-unknown-unknown/release/glos_wasm_inspector.wasm
+//! python3 -m http.server -d www
+//! ```
+//!
+//! Затем откройте http://localhost:8000 и перетащите .glos файл в окно.
+
+use std::io::Cursor;
+
+use glos_core::GlosReader;
+use glos_types::GlosError;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// Сколько блоков отдавать в JS (остальные только считаются).
+const MAX_BLOCKS: usize = 1000;
+
+#[derive(Serialize)]
+struct HeaderSummary {
+    version: u8,
+    sdr_type: String,
+    iq_format: String,
+    compression: String,
+    sample_rate: u32,
+    center_freq: u64,
+    gain_db: f32,
+    timestamp_start: u64,
+    timestamp_end: u64,
+    total_samples: u64,
+    duration_secs: Option<f64>,
+    bits_per_second: u64,
+    sample_period_ns: f64,
+}
+
+#[derive(Serialize)]
+struct BlockSummary {
+    index: usize,
+    timestamp_ns: u64,
+    sample_count: u32,
+    data_bytes: usize,
+}
+
+#[derive(Serialize)]
+struct FileSummary {
+    header: HeaderSummary,
+    blocks: Vec<BlockSummary>,
+    blocks_ok: u64,
+    blocks_corrupted: u64,
+    samples_recovered: u64,
+    totals_valid: bool,
+}
+
+/// Разбирает содержимое .glos файла и возвращает JSON со сводкой
+/// заголовка и блоков.
+#[wasm_bindgen]
+pub fn inspect(bytes: &[u8]) -> Result<String, JsError> {
+    let summary = summarize(bytes).map_err(|e| JsError::new(&e.to_string()))?;
+
+    serde_json::to_string(&summary).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn summarize(bytes: &[u8]) -> Result<FileSummary, GlosError> {
+    let mut reader = GlosReader::new(Cursor::new(bytes))?;
+    let h = reader.header().clone();
+    let mut blocks = Vec::new();
+    let mut index = 0;
+
+    while let Some(result) = reader.next_block() {
+        // Повреждённые блоки учитываются в stats, разбор продолжаем
+        let Ok(block) = result else { continue };
+
+        if blocks.len() < MAX_BLOCKS {
+            blocks.push(BlockSummary {
+                index,
+                timestamp_ns: block.timestamp_ns,
+                sample_count: block.sample_count,
+                data_bytes: block.data.len(),
+            });
+        }
+        index += 1;
+    }
+
+    let stats = reader.stats();
+
+    Ok(FileSummary {
+        header: HeaderSummary {
+            version: h.version,
+            sdr_type: format!("{:?}", h.sdr_type),
+            iq_format: format!("{:?}", h.iq_format),
+            compression: format!("{:?}", h.compression),
+            sample_rate: h.sample_rate,
+            center_freq: h.center_freq,
+            gain_db: h.gain_db,
+            timestamp_start: h.timestamp_start,
+            timestamp_end: h.timestamp_end,
+            total_samples: h.total_samples,
+            duration_secs: h.duration().map(|d| d.as_secs_f64()),
+            bits_per_second: h.bits_per_second(),
+            sample_period_ns: h.sample_period_ns(),
+        },
+        blocks,
+        blocks_ok: stats.blocks_ok,
+        blocks_corrupted: stats.blocks_corrupted,
+        samples_recovered: stats.samples_recovered,
+        totals_valid: reader.validate_totals().is_ok(),
+    })
+}
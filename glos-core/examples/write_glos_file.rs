@@ -9,8 +9,7 @@
 
 use std::fs::File;
 
-use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt};
-use glos_types::{GlosHeader, IqBlock, IqFormat, SdrType};
+use glos_core::prelude::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let output_path = "glos-core/test_output.glos";
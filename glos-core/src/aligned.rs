@@ -0,0 +1,238 @@
+//! Выровненные буферы полезной нагрузки блоков.
+//!
+//! `Vec<u8>` гарантирует выравнивание только на 1 байт, а SIMD-ядрам нужны
+//! буферы, выровненные на 32/64 байта. [`AlignedBuf`] выделяет память с
+//! запасом и сдвигает начало данных на ближайшую выровненную границу —
+//! без `unsafe`, поверх обычного `Vec<u8>`.
+
+use glos_types::{BlockAttributes, GlosError, GlosResult, IqFormat};
+
+/// Выравнивание по умолчанию: кэш-линия / AVX-512.
+pub const DEFAULT_PAYLOAD_ALIGN: usize = 64;
+
+/// Максимальное поддерживаемое выравнивание (страница).
+pub const MAX_PAYLOAD_ALIGN: usize = 4096;
+
+/// Байтовый буфер, начало которого выровнено на `align`.
+#[derive(Debug, Clone)]
+pub struct AlignedBuf {
+    storage: Vec<u8>,
+    offset: usize,
+    len: usize,
+    align: usize,
+}
+
+/// Блок IQ с выровненной полезной нагрузкой.
+///
+/// Помнит формат и порядок байт выборок, поэтому может безопасно отдать
+/// данные как `&[i16]` / `&[f32]`, если порядок байт файла совпадает с
+/// порядком байт платформы.
+#[derive(Debug, Clone)]
+pub struct AlignedBlock {
+    pub timestamp_ns: u64,
+    pub sample_count: u32,
+    pub format: IqFormat,
+    /// Компоненты выборок записаны в little-endian (флаг заголовка)
+    pub little_endian: bool,
+    pub data: AlignedBuf,
+    /// События приёмника за время блока
+    pub attributes: BlockAttributes,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl AlignedBuf {
+    /// Копирует `data` в новый буфер с началом, выровненным на `align`.
+    ///
+    /// `align` — степень двойки не больше [`MAX_PAYLOAD_ALIGN`].
+    pub fn from_slice(
+        data: &[u8],
+        align: usize,
+    ) -> GlosResult<Self> {
+        check_align(align)?;
+
+        // Буфер больше не растёт, поэтому адрес и смещение стабильны
+        let mut storage = vec![0u8; data.len() + align - 1];
+        let offset = storage.as_ptr().align_offset(align);
+
+        if offset >= align {
+            return Err(GlosError::FormatViolation(format!(
+                "Cannot align buffer to {align} bytes"
+            )));
+        }
+
+        storage[offset..offset + data.len()].copy_from_slice(data);
+
+        Ok(Self {
+            storage,
+            offset,
+            len: data.len(),
+            align,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.storage[self.offset..self.offset + self.len]
+    }
+
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.storage[self.offset..self.offset + self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Гарантированное выравнивание начала данных.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+}
+
+impl AlignedBlock {
+    /// Данные как `i8` (Int8; порядок байт не важен).
+    pub fn as_i8_slice(&self) -> GlosResult<&[i8]> {
+        self.check(IqFormat::Int8)?;
+        cast(self.data.as_bytes())
+    }
+
+    /// Данные как чередующиеся `I, Q` в `i16` (Int16, родной порядок байт).
+    pub fn as_i16_slice(&self) -> GlosResult<&[i16]> {
+        self.check(IqFormat::Int16)?;
+        cast(self.data.as_bytes())
+    }
+
+    /// Данные как чередующиеся `I, Q` в `f32` (Float32, родной порядок байт).
+    pub fn as_f32_slice(&self) -> GlosResult<&[f32]> {
+        self.check(IqFormat::Float32)?;
+        cast(self.data.as_bytes())
+    }
+
+    /// Порядок байт выборок совпадает с порядком байт платформы.
+    pub fn is_native_endian(&self) -> bool {
+        self.format == IqFormat::Int8 || self.little_endian == cfg!(target_endian = "little")
+    }
+
+    /// Переставляет байты компонент в родной порядок платформы (на месте).
+    pub fn convert_to_native_endian(&mut self) {
+        if self.is_native_endian() {
+            return;
+        }
+
+        let width = self.format.sample_size() / 2;
+        for component in self.data.as_bytes_mut().chunks_exact_mut(width) {
+            component.reverse();
+        }
+
+        self.little_endian = cfg!(target_endian = "little");
+    }
+
+    fn check(
+        &self,
+        expected: IqFormat,
+    ) -> GlosResult<()> {
+        if self.format != expected {
+            return Err(GlosError::FormatViolation(format!(
+                "Block format is {:?}, not {expected:?}",
+                self.format
+            )));
+        }
+
+        if !self.is_native_endian() {
+            return Err(GlosError::FormatViolation(
+                "Sample byte order differs from platform; call convert_to_native_endian() first"
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+pub(crate) fn check_align(align: usize) -> GlosResult<()> {
+    if !align.is_power_of_two() || align > MAX_PAYLOAD_ALIGN {
+        return Err(GlosError::FormatViolation(format!(
+            "Payload alignment must be a power of two <= {MAX_PAYLOAD_ALIGN}, got {align}"
+        )));
+    }
+
+    Ok(())
+}
+
+fn cast<T: bytemuck::Pod>(bytes: &[u8]) -> GlosResult<&[T]> {
+    bytemuck::try_cast_slice(bytes)
+        .map_err(|e| GlosError::FormatViolation(format!("Cannot reinterpret payload: {e:?}")))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned_buf_alignment() {
+        let data: Vec<u8> = (0..=255).collect();
+
+        for align in [1, 16, 32, 64, 4096] {
+            let buf = AlignedBuf::from_slice(&data[3..], align).unwrap();
+
+            assert_eq!(buf.as_bytes().as_ptr() as usize % align, 0);
+            assert_eq!(buf.as_bytes(), &data[3..]);
+            assert_eq!(buf.align(), align);
+        }
+
+        assert!(AlignedBuf::from_slice(&data, 48).is_err());
+        assert!(AlignedBuf::from_slice(&data, 8192).is_err());
+        assert!(AlignedBuf::from_slice(&[], 64).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_aligned_block_typed_views() {
+        let samples: [i16; 4] = [1, -2, 300, -32768];
+        let be: Vec<u8> = samples.iter().flat_map(|s| s.to_be_bytes()).collect();
+
+        let mut block = AlignedBlock {
+            timestamp_ns: 0,
+            sample_count: 2,
+            format: IqFormat::Int16,
+            little_endian: false,
+            data: AlignedBuf::from_slice(&be, 64).unwrap(),
+            attributes: BlockAttributes::default(),
+        };
+
+        if cfg!(target_endian = "little") {
+            assert!(block.as_i16_slice().is_err());
+        }
+
+        block.convert_to_native_endian();
+        assert_eq!(block.as_i16_slice().unwrap(), &samples);
+        assert!(block.as_f32_slice().is_err());
+
+        let floats = [0.5f32, -1.25];
+        let le: Vec<u8> = floats.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let mut block = AlignedBlock {
+            timestamp_ns: 0,
+            sample_count: 1,
+            format: IqFormat::Float32,
+            little_endian: true,
+            data: AlignedBuf::from_slice(&le, 32).unwrap(),
+            attributes: BlockAttributes::default(),
+        };
+
+        block.convert_to_native_endian();
+        assert_eq!(block.as_f32_slice().unwrap(), &floats);
+    }
+}
@@ -0,0 +1,624 @@
+//! Индексированный архив `.glosa`: много записей `.glos` в одном файле.
+//!
+//! Тысячи мелких файлов неудобно переносить и хранить. Архив складывает их
+//! целиком (без перепаковки, «stored») друг за другом и дописывает в конец
+//! индекс, по которому член открывается по имени без чтения остальных.
+//! Все числа — big-endian.
+//!
+//! ```text
+//! [0..8]   MAGIC          b"GLOSARC\x01"
+//! [8..]    MEMBERS        полные потоки .glos подряд
+//!          INDEX          { name_len u16 | name utf-8 | offset u64 | len u64 } × count
+//!          TRAILER (20)   index_offset u64 | count u32 | index_crc u32 | b"GARX"
+//! ```
+//!
+//! Дозапись не трогает существующие байты: новый член пишется после
+//! старого трейлера, затем — новый индекс. Индекс с трейлером дописывается
+//! после каждого закрытого члена, поэтому при сбое посреди члена (трейлера
+//! в конце нет) архив открывается по последнему целому трейлеру: теряется
+//! только недописанный член.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Take, Write},
+};
+
+use glos_types::{GlosError, GlosResult};
+
+use crate::{crc32_checksum, GlosReader};
+
+/// Магическое число архива (включает версию контейнера)
+pub const GLOS_ARCHIVE_MAGIC: [u8; 8] = *b"GLOSARC\x01";
+
+/// Магическое число в конце трейлера
+const TRAILER_MAGIC: [u8; 4] = *b"GARX";
+
+/// Размер трейлера (байт)
+const TRAILER_SIZE: u64 = 20;
+
+/// Предел размера индекса при чтении: защита от ложного `index_offset`
+const MAX_INDEX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Окно поиска трейлера с конца незавершённого архива (байт)
+const SCAN_WINDOW: u64 = 64 * 1024;
+
+/// Член архива.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Смещение начала потока .glos от начала архива
+    pub offset: u64,
+    /// Длина потока (байт)
+    pub len: u64,
+}
+
+/// Читатель архива: индекс читается один раз при открытии.
+pub struct GlosArchive<R: Read + Seek> {
+    inner: R,
+    entries: Vec<ArchiveEntry>,
+}
+
+/// Писатель архива.
+///
+/// Члены пишутся по одному через [`GlosArchiveWriter::begin_member`];
+/// член закрывается началом следующего или [`GlosArchiveWriter::finish`].
+pub struct GlosArchiveWriter<W: Write + Seek> {
+    inner: W,
+    entries: Vec<ArchiveEntry>,
+    /// Имя и смещение члена, который сейчас пишется
+    pending: Option<(String, u64)>,
+    /// Индекс в конце потока описывает все закрытые члены
+    indexed: bool,
+}
+
+/// Поток одного члена архива для [`crate::GlosWriter`]: позиции отсчитываются
+/// от начала члена, так что писатель перезаписывает свой заголовок, не задевая
+/// соседей. `S` — поток архива: ссылка на него или отдельный дескриптор того
+/// же файла.
+pub struct ArchiveMember<S: Write + Seek> {
+    inner: S,
+    base: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl<R: Read + Seek> GlosArchive<R> {
+    /// Открывает архив, читая и проверяя индекс. У архива, оборванного
+    /// посреди члена, читается последний целый индекс.
+    pub fn open(mut inner: R) -> GlosResult<Self> {
+        let entries = read_index(&mut inner)?
+            .ok_or_else(|| GlosError::corrupted("Archive trailer missing (unfinished archive?)"))?;
+
+        Ok(Self { inner, entries })
+    }
+
+    /// Члены в порядке записи.
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    pub fn entry(
+        &self,
+        name: &str,
+    ) -> Option<&ArchiveEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Открывает член по имени как обычный поток .glos.
+    pub fn open_member(
+        &mut self,
+        name: &str,
+    ) -> GlosResult<GlosReader<Take<&mut R>>> {
+        let entry = self
+            .entry(name)
+            .cloned()
+            .ok_or_else(|| GlosError::format_violation(format!("No member '{name}' in archive")))?;
+
+        self.inner.seek(SeekFrom::Start(entry.offset))?;
+
+        GlosReader::new((&mut self.inner).take(entry.len))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<W: Write + Seek> GlosArchiveWriter<W> {
+    /// Создаёт пустой архив в начале потока.
+    pub fn create(mut inner: W) -> GlosResult<Self> {
+        inner.seek(SeekFrom::Start(0))?;
+        inner.write_all(&GLOS_ARCHIVE_MAGIC)?;
+
+        Ok(Self {
+            inner,
+            entries: Vec::new(),
+            pending: None,
+            indexed: false,
+        })
+    }
+
+    /// Начинает новый член; поток передаётся в [`crate::GlosWriter::new`].
+    /// Предыдущий член при этом закрывается, и за ним пишется индекс.
+    pub fn begin_member(
+        &mut self,
+        name: &str,
+    ) -> GlosResult<ArchiveMember<&mut W>> {
+        let base = self.start_member(name)?;
+
+        Ok(ArchiveMember {
+            inner: &mut self.inner,
+            base,
+        })
+    }
+
+    /// Закрывает предыдущий член и регистрирует новый; возвращает его
+    /// смещение.
+    fn start_member(
+        &mut self,
+        name: &str,
+    ) -> GlosResult<u64> {
+        self.close_member()?;
+
+        if name.is_empty() || name.len() > u16::MAX as usize {
+            return Err(GlosError::format_violation(format!(
+                "Invalid archive member name length {}",
+                name.len()
+            )));
+        }
+
+        if self.entries.iter().any(|e| e.name == name) {
+            return Err(GlosError::format_violation(format!(
+                "Member '{name}' already exists in archive"
+            )));
+        }
+
+        let base = self.inner.seek(SeekFrom::End(0))?;
+        self.pending = Some((name.to_string(), base));
+        self.indexed = false;
+
+        Ok(base)
+    }
+
+    /// Записанные (закрытые) члены.
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Закрывает последний член и дописывает индекс с трейлером.
+    pub fn finish(mut self) -> GlosResult<W> {
+        self.close_member()?;
+        if !self.indexed {
+            self.write_index()?;
+        }
+
+        Ok(self.inner)
+    }
+
+    /// Дописывает индекс закрытых членов с трейлером.
+    fn write_index(&mut self) -> GlosResult<()> {
+        let index_offset = self.inner.seek(SeekFrom::End(0))?;
+        let mut index = Vec::new();
+
+        for e in &self.entries {
+            index.extend_from_slice(&(e.name.len() as u16).to_be_bytes());
+            index.extend_from_slice(e.name.as_bytes());
+            index.extend_from_slice(&e.offset.to_be_bytes());
+            index.extend_from_slice(&e.len.to_be_bytes());
+        }
+
+        let count = u32::try_from(self.entries.len())
+            .map_err(|_| GlosError::format_violation("Too many archive members"))?;
+
+        self.inner.write_all(&index)?;
+        self.inner.write_all(&index_offset.to_be_bytes())?;
+        self.inner.write_all(&count.to_be_bytes())?;
+        self.inner
+            .write_all(&crc32_checksum(&index).to_be_bytes())?;
+        self.inner.write_all(&TRAILER_MAGIC)?;
+        self.inner.flush()?;
+        self.indexed = true;
+
+        Ok(())
+    }
+
+    /// Закрывает член, который пишется, и сразу фиксирует его в индексе.
+    fn close_member(&mut self) -> GlosResult<()> {
+        if let Some((name, offset)) = self.pending.take() {
+            let end = self.inner.seek(SeekFrom::End(0))?;
+
+            self.entries.push(ArchiveEntry {
+                name,
+                offset,
+                len: end - offset,
+            });
+            self.write_index()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl GlosArchiveWriter<File> {
+    /// Как [`begin_member`](Self::begin_member), но член пишется через свой
+    /// дескриптор того же файла и не занимает архив: писатель члена может
+    /// жить рядом с архивом, например при ротации сегментов в архив.
+    /// Следующий член начинается только после финализации этого.
+    pub fn begin_member_owned(
+        &mut self,
+        name: &str,
+    ) -> GlosResult<ArchiveMember<File>> {
+        let base = self.start_member(name)?;
+
+        Ok(ArchiveMember {
+            inner: self.inner.try_clone()?,
+            base,
+        })
+    }
+}
+
+impl<W: Read + Write + Seek> GlosArchiveWriter<W> {
+    /// Открывает архив для дозаписи; пустой поток становится новым архивом.
+    /// После сбоя дозапись продолжается за недописанным членом, а в индекс
+    /// попадают только целые члены.
+    pub fn open(mut inner: W) -> GlosResult<Self> {
+        if inner.seek(SeekFrom::End(0))? == 0 {
+            return Self::create(inner);
+        }
+
+        let entries = read_index(&mut inner)?.unwrap_or_default();
+
+        Ok(Self {
+            inner,
+            entries,
+            pending: None,
+            indexed: false,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl<S: Write + Seek> Write for ArchiveMember<S> {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> std::io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: Write + Seek> Seek for ArchiveMember<S> {
+    fn seek(
+        &mut self,
+        pos: SeekFrom,
+    ) -> std::io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(n) => SeekFrom::Start(self.base.checked_add(n).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek overflow")
+            })?),
+            other => other,
+        };
+
+        self.inner.seek(pos)?.checked_sub(self.base).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before start of archive member",
+            )
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Читает и проверяет индекс архива. Если поток не кончается трейлером
+/// (сбой посреди члена), берётся последний целый трейлер; `None` — целых
+/// трейлеров нет, закрытых членов тоже.
+fn read_index<R: Read + Seek>(r: &mut R) -> GlosResult<Option<Vec<ArchiveEntry>>> {
+    let file_len = r.seek(SeekFrom::End(0))?;
+
+    let mut magic = [0u8; 8];
+    r.seek(SeekFrom::Start(0))?;
+    r.read_exact(&mut magic)?;
+    if magic != GLOS_ARCHIVE_MAGIC {
+        return Err(GlosError::invalid_magic("Not a GLOS archive"));
+    }
+
+    let min_trailer_pos = GLOS_ARCHIVE_MAGIC.len() as u64;
+    if let Some(trailer_pos) = file_len
+        .checked_sub(TRAILER_SIZE)
+        .filter(|&p| p >= min_trailer_pos)
+    {
+        let mut tail = [0u8; 4];
+        r.seek(SeekFrom::Start(trailer_pos + 16))?;
+        r.read_exact(&mut tail)?;
+
+        // Трейлер на месте — повреждения в нём не прощаются
+        if tail == TRAILER_MAGIC {
+            return parse_trailer(r, trailer_pos).map(Some);
+        }
+    }
+
+    // Незавершённый архив: ищем целый трейлер с конца окнами по
+    // SCAN_WINDOW байт (с перекрытием на длину магии)
+    let mut window_end = file_len;
+    let mut buf = Vec::new();
+
+    while window_end > min_trailer_pos + TRAILER_SIZE - 1 {
+        let start = window_end.saturating_sub(SCAN_WINDOW).max(min_trailer_pos);
+        buf.resize((window_end - start) as usize, 0);
+        r.seek(SeekFrom::Start(start))?;
+        r.read_exact(&mut buf)?;
+
+        for i in (0..buf.len().saturating_sub(3)).rev() {
+            if buf[i..i + 4] != TRAILER_MAGIC {
+                continue;
+            }
+
+            let Some(trailer_pos) = (start + i as u64)
+                .checked_sub(16)
+                .filter(|&p| p >= min_trailer_pos)
+            else {
+                continue;
+            };
+
+            if let Ok(entries) = parse_trailer(r, trailer_pos) {
+                return Ok(Some(entries));
+            }
+        }
+
+        if start == min_trailer_pos {
+            break;
+        }
+        window_end = start + 3;
+    }
+
+    Ok(None)
+}
+
+/// Разбирает трейлер в `trailer_pos` и индекс, на который он указывает.
+fn parse_trailer<R: Read + Seek>(
+    r: &mut R,
+    trailer_pos: u64,
+) -> GlosResult<Vec<ArchiveEntry>> {
+    let mut trailer = [0u8; TRAILER_SIZE as usize];
+    r.seek(SeekFrom::Start(trailer_pos))?;
+    r.read_exact(&mut trailer)?;
+
+    if trailer[16..20] != TRAILER_MAGIC {
+        return Err(GlosError::corrupted(
+            "Archive trailer missing (unfinished archive?)",
+        ));
+    }
+
+    let index_offset = u64::from_be_bytes(trailer[0..8].try_into().unwrap());
+    let count = u32::from_be_bytes(trailer[8..12].try_into().unwrap());
+    let stored_crc = u32::from_be_bytes(trailer[12..16].try_into().unwrap());
+
+    let index_len = trailer_pos
+        .checked_sub(index_offset)
+        .filter(|&n| index_offset >= GLOS_ARCHIVE_MAGIC.len() as u64 && n <= MAX_INDEX_SIZE)
+        .ok_or_else(|| GlosError::corrupted(format!("Invalid index offset {index_offset}")))?;
+
+    let mut index = vec![0u8; index_len as usize];
+    r.seek(SeekFrom::Start(index_offset))?;
+    r.read_exact(&mut index)?;
+
+    let calculated_crc = crc32_checksum(&index);
+    if stored_crc != calculated_crc {
+        return Err(GlosError::CrcMismatch {
+            expected: calculated_crc,
+            found: stored_crc,
+        });
+    }
+
+    let mut entries = Vec::new();
+    let mut rest = index.as_slice();
+
+    for _ in 0..count {
+        let name_len = take(&mut rest, 2).map(|b| u16::from_be_bytes([b[0], b[1]]))? as usize;
+        let name = String::from_utf8(take(&mut rest, name_len)?.to_vec())
+            .map_err(|_| GlosError::corrupted("Archive member name is not UTF-8"))?;
+        let offset = u64::from_be_bytes(take(&mut rest, 8)?.try_into().unwrap());
+        let len = u64::from_be_bytes(take(&mut rest, 8)?.try_into().unwrap());
+
+        // Член должен целиком лежать между magic и индексом
+        if offset < GLOS_ARCHIVE_MAGIC.len() as u64
+            || offset.checked_add(len).is_none_or(|end| end > index_offset)
+        {
+            return Err(GlosError::corrupted(format!(
+                "Archive member '{name}' out of bounds"
+            )));
+        }
+
+        entries.push(ArchiveEntry { name, offset, len });
+    }
+
+    if !rest.is_empty() {
+        return Err(GlosError::corrupted("Trailing bytes in archive index"));
+    }
+
+    Ok(entries)
+}
+
+/// Отрезает `n` байт от начала `buf`.
+fn take<'a>(
+    buf: &mut &'a [u8],
+    n: usize,
+) -> GlosResult<&'a [u8]> {
+    if buf.len() < n {
+        return Err(GlosError::corrupted("Truncated archive index"));
+    }
+
+    let (head, tail) = buf.split_at(n);
+    *buf = tail;
+
+    Ok(head)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_types::{GlosHeader, IqBlock, SdrType};
+
+    use super::*;
+    use crate::{read_all_blocks, GlosHeaderExt, GlosWriter, IqBlockExt};
+
+    fn header() -> GlosHeader {
+        GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000)
+    }
+
+    fn write_member(
+        archive: &mut GlosArchiveWriter<Cursor<Vec<u8>>>,
+        name: &str,
+        blocks: u64,
+    ) {
+        let mut w = GlosWriter::new(archive.begin_member(name).unwrap(), header()).unwrap();
+
+        for i in 0..blocks {
+            w.write_block(IqBlock::new(i * 1_000, 10, vec![i as u8; 40]))
+                .unwrap();
+        }
+
+        w.finish_at(1).unwrap();
+    }
+
+    #[test]
+    fn test_archive_round_trip_and_append() {
+        let mut archive = GlosArchiveWriter::create(Cursor::new(Vec::new())).unwrap();
+        write_member(&mut archive, "seg_000.glos", 3);
+        write_member(&mut archive, "seg_001.glos", 5);
+        assert!(archive.begin_member("seg_000.glos").is_err());
+        let bytes = archive.finish().unwrap().into_inner();
+
+        // Дозапись сохраняет старые члены
+        let mut archive = GlosArchiveWriter::open(Cursor::new(bytes)).unwrap();
+        write_member(&mut archive, "seg_002.glos", 2);
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let mut archive = GlosArchive::open(Cursor::new(bytes)).unwrap();
+        let names: Vec<_> = archive.entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["seg_000.glos", "seg_001.glos", "seg_002.glos"]);
+
+        let mut reader = archive.open_member("seg_001.glos").unwrap();
+        assert_eq!(reader.header().total_samples, 50);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(blocks[4].data, vec![4u8; 40]);
+
+        assert!(archive.open_member("missing.glos").is_err());
+    }
+
+    #[test]
+    fn test_archive_rejects_damage() {
+        let mut archive = GlosArchiveWriter::create(Cursor::new(Vec::new())).unwrap();
+        write_member(&mut archive, "a.glos", 1);
+        let bytes = archive.finish().unwrap().into_inner();
+
+        // Незавершённый архив: трейлера нет
+        let cut = bytes[..bytes.len() - 4].to_vec();
+        assert!(GlosArchive::open(Cursor::new(cut)).is_err());
+
+        // Ложное смещение индекса
+        let mut bad = bytes.clone();
+        let pos = bad.len() - TRAILER_SIZE as usize;
+        bad[pos..pos + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(matches!(
+            GlosArchive::open(Cursor::new(bad)),
+            Err(GlosError::Corrupted(_))
+        ));
+
+        // Повреждённый индекс
+        let mut bad = bytes;
+        let pos = bad.len() - TRAILER_SIZE as usize - 1;
+        bad[pos] ^= 0xFF;
+        assert!(matches!(
+            GlosArchive::open(Cursor::new(bad)),
+            Err(GlosError::CrcMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_archive_survives_crash_mid_member() {
+        let mut archive = GlosArchiveWriter::create(Cursor::new(Vec::new())).unwrap();
+        write_member(&mut archive, "a.glos", 3);
+        write_member(&mut archive, "b.glos", 2);
+
+        // Член c пишется, когда процесс падает: трейлера в конце нет
+        let mut w = GlosWriter::new(archive.begin_member("c.glos").unwrap(), header()).unwrap();
+        for i in 0..4 {
+            w.write_block(IqBlock::new(i * 1_000, 10, vec![7; 40]))
+                .unwrap();
+        }
+        drop(w);
+        let bytes = archive.inner.into_inner();
+        let cut = bytes[..bytes.len() - 7].to_vec();
+
+        let mut recovered = GlosArchive::open(Cursor::new(cut.clone())).unwrap();
+        let names: Vec<_> = recovered
+            .entries()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, ["a.glos", "b.glos"]);
+        let mut reader = recovered.open_member("b.glos").unwrap();
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 2);
+
+        // Дозапись продолжается за обрывком, c пишется заново
+        let mut archive = GlosArchiveWriter::open(Cursor::new(cut)).unwrap();
+        write_member(&mut archive, "c.glos", 1);
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let mut archive = GlosArchive::open(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.entries().len(), 3);
+        let mut reader = archive.open_member("c.glos").unwrap();
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 1);
+
+        // Сбой в первом же члене: закрытых членов нет
+        let mut archive = GlosArchiveWriter::create(Cursor::new(Vec::new())).unwrap();
+        let mut w = GlosWriter::new(archive.begin_member("a.glos").unwrap(), header()).unwrap();
+        w.write_block(IqBlock::new(0, 10, vec![1; 40])).unwrap();
+        drop(w);
+        let bytes = archive.inner.into_inner();
+        assert!(GlosArchive::open(Cursor::new(bytes.clone())).is_err());
+        let archive = GlosArchiveWriter::open(Cursor::new(bytes)).unwrap();
+        assert!(archive.entries().is_empty());
+    }
+
+    #[test]
+    fn test_archive_owned_members_share_file() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let mut archive = GlosArchiveWriter::create(tmp.reopen().unwrap()).unwrap();
+
+        for (name, blocks) in [("seg_000.glos", 2u64), ("seg_001.glos", 3)] {
+            let member = archive.begin_member_owned(name).unwrap();
+            let mut w = GlosWriter::new(member, header()).unwrap();
+            for i in 0..blocks {
+                w.write_block(IqBlock::new(i * 1_000, 10, vec![i as u8; 40]))
+                    .unwrap();
+            }
+            w.finish_at(1).unwrap();
+        }
+        archive.finish().unwrap();
+
+        let mut archive = GlosArchive::open(tmp.reopen().unwrap()).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+        let mut reader = archive.open_member("seg_001.glos").unwrap();
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,279 @@
+//! Чтение и запись `.glos` поверх `tokio::io` (фича `tokio`).
+//!
+//! Формат, сжатие и восстановление после повреждений те же, что у
+//! [`GlosWriter`](crate::GlosWriter) и [`GlosReader`](crate::GlosReader):
+//! асинхронные типы делят с ними разбор и сборку блоков и отличаются только
+//! вводом-выводом. Сжатие и распаковка блока выполняются прямо в задаче —
+//! для блоков до 1 МиБ это доли миллисекунды, `spawn_blocking` не нужен.
+
+use glos_types::{GlosError, GlosHeader, GlosResult, IqBlock};
+use tokio::io::{
+    AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufWriter,
+};
+
+use crate::{
+    serialization::{
+        current_unix_secs, read_header, BlockEncoder, Frame, FrameBuffer, READ_BUF_SIZE,
+    },
+    CompressionOptions, GlosHeaderExt, ReadStats, ReaderOptions, GLOS_HEADER_SIZE,
+};
+
+/// Асинхронный потоковый писатель GLOS файлов.
+pub struct AsyncGlosWriter<W: AsyncWrite + AsyncSeek + Unpin> {
+    writer: BufWriter<W>,
+    header: GlosHeader,
+    total_samples: u64,
+    block_count: u64,
+    encoder: BlockEncoder,
+}
+
+/// Асинхронный потоковый читатель GLOS файлов.
+pub struct AsyncGlosReader<R: AsyncRead + Unpin> {
+    reader: R,
+    header: GlosHeader,
+    read_buf: Vec<u8>,
+    frames: FrameBuffer,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncGlosWriter<W> {
+    /// Создаёт писатель и сразу записывает заголовок.
+    pub async fn new(
+        inner: W,
+        header: GlosHeader,
+    ) -> GlosResult<Self> {
+        let mut writer = BufWriter::new(inner);
+
+        writer.write_all(&header.serialize()?).await?;
+
+        Ok(Self {
+            writer,
+            encoder: BlockEncoder::new(&header),
+            header,
+            total_samples: 0,
+            block_count: 0,
+        })
+    }
+
+    /// Задаёт настройки сжатия, см.
+    /// [`GlosWriter::with_compression`](crate::GlosWriter::with_compression).
+    pub fn with_compression(
+        mut self,
+        options: CompressionOptions,
+    ) -> GlosResult<Self> {
+        self.encoder.set_options(options)?;
+        Ok(self)
+    }
+
+    /// Записывает один блок IQ данных.
+    pub async fn write_block(
+        &mut self,
+        mut block: IqBlock,
+    ) -> GlosResult<()> {
+        let frame = self.encoder.encode(&mut block)?;
+
+        self.total_samples += block.sample_count as u64;
+        self.block_count += 1;
+        self.writer.write_all(&frame).await?;
+
+        Ok(())
+    }
+
+    /// Завершает запись: сбрасывает буфер и перезаписывает заголовок.
+    pub async fn finish(mut self) -> GlosResult<()> {
+        let now = current_unix_secs();
+        if now != 0 {
+            self.header.timestamp_end = now;
+        }
+
+        self.finalize().await
+    }
+
+    /// Как [`finish`](Self::finish), но с явным временем окончания
+    /// (Unix-секунды).
+    pub async fn finish_at(
+        mut self,
+        timestamp_end: u64,
+    ) -> GlosResult<()> {
+        self.header.timestamp_end = timestamp_end;
+        self.finalize().await
+    }
+
+    async fn finalize(mut self) -> GlosResult<()> {
+        self.writer.flush().await?;
+        self.header.total_samples = self.total_samples;
+
+        let mut inner = self.writer.into_inner();
+
+        inner.seek(std::io::SeekFrom::Start(0)).await?;
+        inner.write_all(&self.header.serialize()?).await?;
+        inner.flush().await?;
+
+        Ok(())
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    pub fn block_count(&self) -> u64 {
+        self.block_count
+    }
+
+    pub fn header(&self) -> &GlosHeader {
+        &self.header
+    }
+
+    /// Блоков, записанных без сжатия (короткие или несжимаемые).
+    pub fn stored_blocks(&self) -> u64 {
+        self.encoder.stored_blocks()
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncGlosReader<R> {
+    /// Создаёт читателя, читая и валидируя заголовок из `inner`.
+    pub async fn new(mut inner: R) -> GlosResult<Self> {
+        let mut hdr_buf = [0u8; GLOS_HEADER_SIZE];
+
+        inner.read_exact(&mut hdr_buf).await?;
+
+        Ok(Self {
+            reader: inner,
+            header: read_header(&hdr_buf, ReaderOptions::default())?,
+            read_buf: vec![0u8; READ_BUF_SIZE],
+            frames: FrameBuffer::new(GLOS_HEADER_SIZE as u64),
+        })
+    }
+
+    /// Возвращает следующий блок или `None` на EOF. Повреждённые блоки
+    /// пропускаются и учитываются в [`stats`](Self::stats).
+    pub async fn next_block(&mut self) -> Option<GlosResult<IqBlock>> {
+        self.next_block_with_offset()
+            .await
+            .map(|r| r.map(|(_, block)| block))
+    }
+
+    /// Как [`next_block`](Self::next_block), но вместе со смещением блока от
+    /// начала файла.
+    pub async fn next_block_with_offset(&mut self) -> Option<GlosResult<(u64, IqBlock)>> {
+        loop {
+            match self.frames.next_frame(&self.header, true) {
+                Frame::Block(offset, block) => return Some(Ok((offset, block))),
+                Frame::Error(e) => return Some(Err(e)),
+                Frame::End => return None,
+                Frame::NeedData => {}
+            }
+
+            match self.reader.read(&mut self.read_buf).await {
+                Ok(n) => self.frames.push(&self.read_buf[..n]),
+                Err(e) => return Some(Err(GlosError::Io(e))),
+            }
+        }
+    }
+
+    pub fn header(&self) -> &GlosHeader {
+        &self.header
+    }
+
+    pub fn stats(&self) -> &ReadStats {
+        self.frames.stats()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_types::{Compression, SdrType};
+
+    use super::*;
+    use crate::{GlosReader, GlosWriter, IqBlockExt};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn make_blocks() -> Vec<IqBlock> {
+        (0..4u64)
+            .map(|i| {
+                let data = (0..4_000).map(|j| (j % 13 + i as usize) as u8).collect();
+                IqBlock::new(i * 1_000_000, 1_000, data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_async_writer_matches_sync() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.compression = Compression::Lz4;
+
+        let mut expected = Vec::new();
+        let mut sync = GlosWriter::new(Cursor::new(&mut expected), header.clone()).unwrap();
+        for block in make_blocks() {
+            sync.write_block(block).unwrap();
+        }
+        sync.finish_at(1_700_000_000).unwrap();
+
+        let mut actual = Vec::new();
+        block_on(async {
+            let mut writer = AsyncGlosWriter::new(Cursor::new(&mut actual), header)
+                .await
+                .unwrap();
+            for block in make_blocks() {
+                writer.write_block(block).await.unwrap();
+            }
+            assert_eq!(writer.total_samples(), 4_000);
+            writer.finish_at(1_700_000_000).await.unwrap();
+        });
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_async_reader_skips_corrupted_block() {
+        let header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        let mut bytes = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut bytes), header).unwrap();
+        for block in make_blocks() {
+            writer.write_block(block).unwrap();
+        }
+        writer.finish_at(0).unwrap();
+
+        // Портим данные второго блока
+        let second = GLOS_HEADER_SIZE + 4_000 + crate::GLOS_BLOCK_OVERHEAD;
+        bytes[second + 100] ^= 0xFF;
+
+        let mut sync = GlosReader::new(Cursor::new(bytes.clone())).unwrap();
+        let expected: Vec<_> = sync
+            .blocks_with_offsets()
+            .filter_map(Result::ok)
+            .map(|(offset, b)| (offset, b.timestamp_ns, b.data))
+            .collect();
+
+        let (actual, stats) = block_on(async {
+            let mut reader = AsyncGlosReader::new(bytes.as_slice()).await.unwrap();
+            let mut blocks = Vec::new();
+            while let Some(block) = reader.next_block_with_offset().await {
+                if let Ok((offset, b)) = block {
+                    blocks.push((offset, b.timestamp_ns, b.data));
+                }
+            }
+            (blocks, reader.stats().clone())
+        });
+
+        assert_eq!(actual, expected);
+        assert_eq!(actual.len(), 3);
+        assert_eq!(stats.corruption_events, sync.stats().corruption_events);
+        assert_eq!(stats.corruption_events[0].approx_offset, second as u64);
+    }
+}
@@ -1,5 +1,7 @@
-pub mod read;
-pub mod write;
+//! Кодирование полей заголовка с учётом порядка байт (внутреннее).
 
-pub use read::*;
-pub use write::*;
+mod read;
+mod write;
+
+pub(crate) use read::{read_u32_local, read_u64_local};
+pub(crate) use write::{write_u32_local, write_u64_local};
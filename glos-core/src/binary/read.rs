@@ -1,6 +1,6 @@
 use crate::GLOS_HEADER_SIZE;
 
-pub fn read_u32_local(
+pub(crate) fn read_u32_local(
     buf: &[u8; GLOS_HEADER_SIZE],
     off: &mut usize,
     is_le: bool,
@@ -14,7 +14,7 @@ pub fn read_u32_local(
     }
 }
 
-pub fn read_u64_local(
+pub(crate) fn read_u64_local(
     buf: &[u8; GLOS_HEADER_SIZE],
     off: &mut usize,
     is_le: bool,
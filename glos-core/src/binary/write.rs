@@ -1,6 +1,6 @@
 use crate::GLOS_HEADER_SIZE;
 
-pub fn write_u32_local(
+pub(crate) fn write_u32_local(
     buf: &mut [u8; GLOS_HEADER_SIZE],
     off: &mut usize,
     is_le: bool,
@@ -14,7 +14,7 @@ pub fn write_u32_local(
     *off += 4;
 }
 
-pub fn write_u64_local(
+pub(crate) fn write_u64_local(
     buf: &mut [u8; GLOS_HEADER_SIZE],
     off: &mut usize,
     is_le: bool,
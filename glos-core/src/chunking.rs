@@ -0,0 +1,241 @@
+//! Контентно-зависимое разбиение потока IQ выборок на блоки.
+//!
+//! Границы блоков выбираются по rolling hash (Gear, как в FastCDC), а не по
+//! фиксированному числу выборок. Одинаковые фрагменты сигнала в разных
+//! записях дают одинаковые блоки, что помогает дедуплицирующим хранилищам.
+//! Формат блоков не меняется — читатели работают как обычно.
+
+use std::str::FromStr;
+
+use glos_types::{GlosError, GlosResult};
+
+/// Таблица Gear-хэша: 256 псевдослучайных u64 (splitmix64, фиксированный
+/// seed — границы должны совпадать между запусками и машинами).
+static GEAR: [u64; 256] = gear_table();
+
+/// Разбивщик потока на блоки по содержимому.
+///
+/// Граница ставится только между IQ парами, не раньше `min_samples` и не
+/// позже `max_samples` от начала блока.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentChunker {
+    min_samples: u32,
+    max_samples: u32,
+    mask: u64,
+}
+
+impl ContentChunker {
+    /// Создаёт разбивщик. Средний размер блока ≈ середина диапазона.
+    pub fn new(
+        min_samples: u32,
+        max_samples: u32,
+    ) -> GlosResult<Self> {
+        if min_samples == 0 || min_samples > max_samples {
+            return Err(GlosError::FormatViolation(format!(
+                "Invalid chunking limits: min={min_samples}, max={max_samples}"
+            )));
+        }
+
+        // Ожидаемая длина блока = min + 2^bits (с отсечкой по max)
+        let spread = ((max_samples - min_samples) / 2).max(1);
+        let mask = (1u64 << spread.ilog2()) - 1;
+
+        Ok(Self {
+            min_samples,
+            max_samples,
+            mask,
+        })
+    }
+
+    pub fn min_samples(&self) -> u32 {
+        self.min_samples
+    }
+
+    pub fn max_samples(&self) -> u32 {
+        self.max_samples
+    }
+
+    /// Ищет границу первого блока в `data`.
+    ///
+    /// Возвращает количество выборок в блоке, либо `None`, если данных
+    /// недостаточно и нужно дочитать поток.
+    pub fn next_boundary(
+        &self,
+        data: &[u8],
+        sample_size: usize,
+    ) -> Option<u32> {
+        let available = (data.len() / sample_size) as u64;
+
+        if available < self.min_samples as u64 {
+            return None;
+        }
+
+        let limit = available.min(self.max_samples as u64) as usize;
+
+        // Хэш зависит только от последних 64 байт, поэтому прогреваемся с
+        // отступом и не тратим время на начало блока.
+        let min_bytes = self.min_samples as usize * sample_size;
+        let mut hash = 0u64;
+
+        for &b in &data[min_bytes.saturating_sub(64)..min_bytes] {
+            hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+        }
+
+        for n in self.min_samples as usize..limit {
+            if hash & self.mask == 0 {
+                return Some(n as u32);
+            }
+
+            for &b in &data[n * sample_size..(n + 1) * sample_size] {
+                hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+            }
+        }
+
+        if limit == self.max_samples as usize {
+            Some(self.max_samples)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for ContentChunker {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}:{}", self.min_samples, self.max_samples)
+    }
+}
+
+impl FromStr for ContentChunker {
+    type Err = String;
+
+    /// Парсит `min:max` в выборках, например `16384:131072`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid chunking '{s}': expected min:max"))?;
+
+        let min: u32 = min
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid min samples '{min}': {e}"))?;
+        let max: u32 = max
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid max samples '{max}': {e}"))?;
+
+        ContentChunker::new(min, max).map_err(|e| e.to_string())
+    }
+}
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x474C_4F53_4344_4331; // "GLOSCDC1"
+    let mut i = 0;
+
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Детерминированный «шумовой» поток байт.
+    fn noise(
+        len: usize,
+        seed: u64,
+    ) -> Vec<u8> {
+        let mut x = seed | 1;
+
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect()
+    }
+
+    fn split_all(
+        chunker: &ContentChunker,
+        data: &[u8],
+        sample_size: usize,
+    ) -> Vec<u32> {
+        let mut out = Vec::new();
+        let mut off = 0;
+
+        while let Some(n) = chunker.next_boundary(&data[off..], sample_size) {
+            out.push(n);
+            off += n as usize * sample_size;
+        }
+
+        out
+    }
+
+    #[test]
+    fn test_chunker_respects_limits() {
+        let chunker = ContentChunker::new(256, 4096).unwrap();
+        let data = noise(400_000, 7);
+        let sizes = split_all(&chunker, &data, 4);
+
+        assert!(sizes.len() > 10);
+        for &n in &sizes {
+            assert!((256..=4096).contains(&n), "block of {n} samples");
+        }
+    }
+
+    #[test]
+    fn test_chunker_needs_more_data() {
+        let chunker = ContentChunker::new(1000, 2000).unwrap();
+
+        assert_eq!(chunker.next_boundary(&noise(999 * 4, 1), 4), None);
+        // Однородный сигнал не даёт границ — режем по max
+        assert_eq!(chunker.next_boundary(&[0u8; 2000 * 4], 4), Some(2000));
+    }
+
+    #[test]
+    fn test_chunker_resyncs_after_insertion() {
+        let chunker = ContentChunker::new(128, 2048).unwrap();
+        let original = noise(200_000, 42);
+
+        // Вставляем 37 IQ пар в начало — фиксированное разбиение «съехало» бы
+        let mut shifted = noise(37 * 4, 99);
+        shifted.extend_from_slice(&original);
+
+        let a = split_all(&chunker, &original, 4);
+        let b = split_all(&chunker, &shifted, 4);
+
+        // Хвосты разбиений должны совпасть
+        let common = a
+            .iter()
+            .rev()
+            .zip(b.iter().rev())
+            .take_while(|(x, y)| x == y)
+            .count();
+        assert!(common * 2 > a.len(), "common={common}, total={}", a.len());
+    }
+
+    #[test]
+    fn test_chunker_parse() {
+        let c: ContentChunker = "16384:131072".parse().unwrap();
+
+        assert_eq!(c.min_samples(), 16_384);
+        assert_eq!(c.max_samples(), 131_072);
+        assert_eq!(c.to_string(), "16384:131072");
+        assert!("100".parse::<ContentChunker>().is_err());
+        assert!("0:100".parse::<ContentChunker>().is_err());
+        assert!("200:100".parse::<ContentChunker>().is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Сквозная проверка целостности: дайджесты блоков и сессии (XXH64).
+//!
+//! CRC блока защищает только хранение на диске. Чтобы убедиться, что
+//! приёмник в цепочке «запись → воспроизведение → приём» получил ровно то,
+//! что лежит в файле, каждому блоку сопоставляется XXH64 его IQ данных, а
+//! дайджесты блоков по порядку сворачиваются в дайджест сессии. Одинаковые
+//! дайджесты сессии у файла и приёмника означают, что все блоки дошли
+//! целиком и в том же порядке.
+
+use std::fmt;
+
+use glos_types::IqBlock;
+
+const PRIME64_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME64_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME64_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME64_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME64_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+/// Seed дайджестов GLOS. Менять нельзя: дайджесты сравниваются между
+/// версиями и машинами.
+pub const GLOS_DIGEST_SEED: u64 = 0;
+
+/// Накопленный дайджест последовательности блоков.
+///
+/// Порядок важен: `fold(fold(0, a), b) != fold(fold(0, b), a)`, так что
+/// перестановка или дубликат блока меняют итог.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionDigest {
+    value: u64,
+    blocks: u64,
+    samples: u64,
+}
+
+/// Потоковый XXH64: данные подаются кусками, итог тот же, что у [`xxh64`]
+/// от их склейки.
+#[derive(Debug, Clone)]
+pub struct Xxh64 {
+    seed: u64,
+    v: [u64; 4],
+    /// Неполная 32-байтная полоса
+    buf: [u8; 32],
+    buf_len: usize,
+    total_len: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SessionDigest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Дайджест сессии по блокам в порядке следования.
+    pub fn of_blocks<'a>(blocks: impl IntoIterator<Item = &'a IqBlock>) -> Self {
+        let mut digest = Self::new();
+        for block in blocks {
+            digest.push_block(block);
+        }
+        digest
+    }
+
+    /// Добавляет блок; возвращает его дайджест.
+    pub fn push_block(
+        &mut self,
+        block: &IqBlock,
+    ) -> u64 {
+        let d = block_digest(&block.data);
+        self.push(d, block.sample_count);
+        d
+    }
+
+    /// Добавляет уже посчитанный дайджест блока (например, пришедший в
+    /// UDP-пакете и сверенный с данными).
+    pub fn push(
+        &mut self,
+        block_digest: u64,
+        sample_count: u32,
+    ) {
+        let mut buf = [0u8; 16];
+        buf[..8].copy_from_slice(&self.value.to_le_bytes());
+        buf[8..].copy_from_slice(&block_digest.to_le_bytes());
+
+        self.value = xxh64(&buf, GLOS_DIGEST_SEED);
+        self.blocks += 1;
+        self.samples += sample_count as u64;
+    }
+
+    /// Итоговое значение (0 для пустой сессии).
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+}
+
+impl Xxh64 {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            v: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            buf: [0; 32],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    /// Дайджест блока, как у [`block_digest`].
+    pub fn for_block() -> Self {
+        Self::new(GLOS_DIGEST_SEED)
+    }
+
+    pub fn update(
+        &mut self,
+        mut data: &[u8],
+    ) {
+        self.total_len += data.len() as u64;
+
+        if self.buf_len > 0 {
+            let n = (32 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+
+            if self.buf_len < 32 {
+                return;
+            }
+            let stripe = self.buf;
+            self.stripe(&stripe);
+            self.buf_len = 0;
+        }
+
+        while data.len() >= 32 {
+            self.stripe(&data[..32]);
+            data = &data[32..];
+        }
+
+        self.buf[..data.len()].copy_from_slice(data);
+        self.buf_len = data.len();
+    }
+
+    /// Итог по всем поданным данным; подавать можно и дальше.
+    pub fn finish(&self) -> u64 {
+        let mut h = if self.total_len >= 32 {
+            let mut h = self.v[0]
+                .rotate_left(1)
+                .wrapping_add(self.v[1].rotate_left(7))
+                .wrapping_add(self.v[2].rotate_left(12))
+                .wrapping_add(self.v[3].rotate_left(18));
+
+            for acc in self.v {
+                h = merge_round(h, acc);
+            }
+            h
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+
+        h = h.wrapping_add(self.total_len);
+
+        let mut rest = &self.buf[..self.buf_len];
+
+        while rest.len() >= 8 {
+            h ^= round(0, read_u64(rest));
+            h = h
+                .rotate_left(27)
+                .wrapping_mul(PRIME64_1)
+                .wrapping_add(PRIME64_4);
+            rest = &rest[8..];
+        }
+
+        if rest.len() >= 4 {
+            let k = u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64;
+            h ^= k.wrapping_mul(PRIME64_1);
+            h = h
+                .rotate_left(23)
+                .wrapping_mul(PRIME64_2)
+                .wrapping_add(PRIME64_3);
+            rest = &rest[4..];
+        }
+
+        for &b in rest {
+            h ^= (b as u64).wrapping_mul(PRIME64_5);
+            h = h.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        h ^= h >> 33;
+        h = h.wrapping_mul(PRIME64_2);
+        h ^= h >> 29;
+        h = h.wrapping_mul(PRIME64_3);
+        h ^ (h >> 32)
+    }
+
+    fn stripe(
+        &mut self,
+        stripe: &[u8],
+    ) {
+        for (i, acc) in self.v.iter_mut().enumerate() {
+            *acc = round(*acc, read_u64(&stripe[i * 8..]));
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for SessionDigest {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "xxh64:{:016x} ({} blocks, {} samples)",
+            self.value, self.blocks, self.samples
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Дайджест IQ данных одного блока.
+pub fn block_digest(data: &[u8]) -> u64 {
+    xxh64(data, GLOS_DIGEST_SEED)
+}
+
+/// XXH64 (совместим с эталонной реализацией xxHash).
+pub fn xxh64(
+    data: &[u8],
+    seed: u64,
+) -> u64 {
+    let mut h = Xxh64::new(seed);
+    h.update(data);
+    h.finish()
+}
+
+fn round(
+    acc: u64,
+    input: u64,
+) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn merge_round(
+    acc: u64,
+    val: u64,
+) -> u64 {
+    (acc ^ round(0, val))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+fn read_u64(buf: &[u8]) -> u64 {
+    u64::from_le_bytes(buf[..8].try_into().unwrap())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IqBlockExt;
+
+    #[test]
+    fn test_xxh64_reference_vectors() {
+        assert_eq!(xxh64(b"", 0), 0xEF46_DB37_51D8_E999);
+        assert_eq!(xxh64(b"a", 0), 0xD24E_C4F1_A98C_6E5B);
+        assert_eq!(xxh64(b"abc", 0), 0x44BC_2CF5_AD77_0999);
+        // > 32 байт: полосы, 8-, 4- и 1-байтовые хвосты
+        assert_eq!(
+            xxh64(b"Nobody inspects the spammish repetition", 0),
+            0xFBCE_A83C_8A37_8BF1
+        );
+    }
+
+    #[test]
+    fn test_xxh64_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..1_000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let expected = xxh64(&data, 0);
+
+        for piece in [1, 3, 8, 31, 32, 33, 100, 999] {
+            let mut h = Xxh64::new(0);
+            for part in data.chunks(piece) {
+                h.update(part);
+            }
+            assert_eq!(h.finish(), expected, "pieces of {piece} bytes");
+        }
+
+        let mut h = Xxh64::for_block();
+        h.update(&data[..17]);
+        h.update(&data[17..]);
+        assert_eq!(h.finish(), block_digest(&data));
+    }
+
+    #[test]
+    fn test_session_digest_order_sensitive() {
+        let a = IqBlock::new(0, 2, vec![1u8; 8]);
+        let b = IqBlock::new(1, 2, vec![2u8; 8]);
+
+        let ab = SessionDigest::of_blocks([&a, &b]);
+        let ba = SessionDigest::of_blocks([&b, &a]);
+        let aab = SessionDigest::of_blocks([&a, &a, &b]);
+
+        assert_eq!(ab.blocks(), 2);
+        assert_eq!(ab.samples(), 4);
+        assert_ne!(ab, ba);
+        assert_ne!(ab.value(), aab.value());
+
+        // Дайджест, пришедший отдельно, сворачивается так же
+        let mut manual = SessionDigest::new();
+        manual.push(block_digest(&a.data), 2);
+        manual.push(block_digest(&b.data), 2);
+        assert_eq!(manual, ab);
+    }
+}
@@ -0,0 +1,346 @@
+//! Оценка реально занятого динамического диапазона и совет по `IqFormat`.
+//!
+//! Float32 или Int16, записанные с 8-битного АЦП, тратят в 4 или 2 раза
+//! больше диска, чем нужно. [`FormatAdvisor`] смотрит на значения выборок:
+//! общие нулевые младшие биты (шаг квантования) и пиковую амплитуду, — и
+//! считает, сколько бит на компоненту данные занимают на самом деле. Если
+//! их хватает меньшего формата, [`FormatAdvice::transcode`] перекодирует
+//! блоки без потерь (с точностью до постоянного множителя амплитуды).
+
+use std::fmt;
+
+use glos_types::{GlosError, GlosResult, IqFormat};
+
+/// Накопитель статистики значений для [`FormatAdvice`].
+///
+/// Подходит и для записи на лету (chunk за chunk'ом), и для готового файла.
+#[derive(Debug, Clone)]
+pub struct FormatAdvisor {
+    format: IqFormat,
+    little_endian: bool,
+    samples: u64,
+    /// Целые форматы: OR компонент (общие нулевые младшие биты)
+    int_or: u32,
+    /// Целые форматы: диапазон компонент
+    int_min: i32,
+    int_max: i32,
+    /// Float32: наименьший показатель младшего значащего бита
+    float_lsb_exp: i32,
+    /// Float32: наибольший показатель старшего бита
+    float_msb_exp: i32,
+    /// Встретились NaN, бесконечность или субнормальные числа
+    unquantized: bool,
+}
+
+/// Совет по формату хранения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatAdvice {
+    pub current: IqFormat,
+    pub recommended: IqFormat,
+    /// Бит на компоненту (со знаком), реально занятых данными
+    pub effective_bits: u32,
+    /// Шаг квантования `2^quantum_exp`: каждая компонента равна `k · шаг`
+    /// с целым `k`
+    pub quantum_exp: i32,
+    /// Проанализировано выборок
+    pub samples: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl FormatAdvisor {
+    pub fn new(
+        format: IqFormat,
+        little_endian: bool,
+    ) -> Self {
+        Self {
+            format,
+            little_endian,
+            samples: 0,
+            int_or: 0,
+            int_min: 0,
+            int_max: 0,
+            float_lsb_exp: i32::MAX,
+            float_msb_exp: i32::MIN,
+            unquantized: false,
+        }
+    }
+
+    /// Учитывает сырые IQ байты блока или chunk'а.
+    pub fn push(
+        &mut self,
+        data: &[u8],
+    ) {
+        let width = self.format.sample_size() / 2;
+        let components = data.chunks_exact(width);
+
+        self.samples += (data.len() / self.format.sample_size()) as u64;
+
+        match self.format {
+            IqFormat::Int8 | IqFormat::Int16 => {
+                for c in components {
+                    let v = self.read_int(c);
+                    self.int_or |= v as u32;
+                    self.int_min = self.int_min.min(v);
+                    self.int_max = self.int_max.max(v);
+                }
+            }
+            IqFormat::Float32 => {
+                for c in components {
+                    let bytes = [c[0], c[1], c[2], c[3]];
+                    let bits = if self.little_endian {
+                        u32::from_le_bytes(bytes)
+                    } else {
+                        u32::from_be_bytes(bytes)
+                    };
+                    self.push_float_bits(bits);
+                }
+            }
+        }
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Совет по накопленным данным.
+    pub fn advice(&self) -> FormatAdvice {
+        let (effective_bits, quantum_exp) = match self.format {
+            IqFormat::Int8 | IqFormat::Int16 => {
+                let shift = if self.int_or == 0 {
+                    0
+                } else {
+                    self.int_or.trailing_zeros()
+                };
+                // Дополнительный код: -2^n занимает столько же бит, сколько 2^n - 1
+                let magnitude = (self.int_max >> shift).max(!(self.int_min >> shift)) as u32;
+                (u32::BITS - magnitude.leading_zeros() + 1, shift as i32)
+            }
+            IqFormat::Float32 if self.unquantized => (32, 0),
+            // Все компоненты нулевые
+            IqFormat::Float32 if self.float_lsb_exp == i32::MAX => (1, 0),
+            IqFormat::Float32 => {
+                let bits = (self.float_msb_exp - self.float_lsb_exp + 2) as u32;
+                if bits > 24 {
+                    (32, 0)
+                } else {
+                    (bits, self.float_lsb_exp)
+                }
+            }
+        };
+
+        let recommended = [IqFormat::Int8, IqFormat::Int16]
+            .into_iter()
+            .find(|f| effective_bits <= (f.sample_size() * 4) as u32)
+            .filter(|f| f.sample_size() < self.format.sample_size())
+            .unwrap_or(self.format);
+
+        FormatAdvice {
+            current: self.format,
+            recommended,
+            effective_bits,
+            quantum_exp,
+            samples: self.samples,
+        }
+    }
+
+    fn read_int(
+        &self,
+        c: &[u8],
+    ) -> i32 {
+        match c.len() {
+            1 => c[0] as i8 as i32,
+            _ if self.little_endian => i16::from_le_bytes([c[0], c[1]]) as i32,
+            _ => i16::from_be_bytes([c[0], c[1]]) as i32,
+        }
+    }
+
+    fn push_float_bits(
+        &mut self,
+        bits: u32,
+    ) {
+        let exp_field = (bits >> 23) & 0xff;
+        let mantissa = bits & 0x7f_ffff;
+
+        match exp_field {
+            0 if mantissa == 0 => {}
+            0 | 0xff => self.unquantized = true,
+            _ => {
+                let exp = exp_field as i32 - 127;
+                let lsb = exp - 23 + (mantissa | 0x80_0000).trailing_zeros() as i32;
+                self.float_lsb_exp = self.float_lsb_exp.min(lsb);
+                self.float_msb_exp = self.float_msb_exp.max(exp);
+            }
+        }
+    }
+}
+
+impl FormatAdvice {
+    /// Переход на рекомендованный формат уменьшит файл.
+    pub fn is_beneficial(&self) -> bool {
+        self.recommended.sample_size() < self.current.sample_size()
+    }
+
+    /// Доля объёма IQ данных, которую сэкономит переход (0.0..1.0).
+    pub fn savings_ratio(&self) -> f64 {
+        1.0 - self.recommended.sample_size() as f64 / self.current.sample_size() as f64
+    }
+
+    /// Сэкономленные байты IQ данных на проанализированных выборках.
+    pub fn savings_bytes(&self) -> u64 {
+        self.samples * (self.current.sample_size() - self.recommended.sample_size()) as u64
+    }
+
+    /// Перекодирует IQ байты из `current` в `recommended` с сохранением
+    /// порядка байт. Каждая компонента становится целым `k` (см.
+    /// [`quantum_exp`](Self::quantum_exp)); значение, не представимое в
+    /// новом формате (данные не из проанализированной выборки), — ошибка.
+    pub fn transcode(
+        &self,
+        data: &[u8],
+        little_endian: bool,
+    ) -> GlosResult<Vec<u8>> {
+        if !self.is_beneficial() {
+            return Ok(data.to_vec());
+        }
+
+        let in_width = self.current.sample_size() / 2;
+        let out_width = self.recommended.sample_size() / 2;
+        let max = (1i64 << (out_width * 8 - 1)) - 1;
+        let mut out = Vec::with_capacity(data.len() / in_width * out_width);
+
+        for c in data.chunks_exact(in_width) {
+            let k = match self.current {
+                IqFormat::Int8 => (c[0] as i8 as i64) >> self.quantum_exp,
+                IqFormat::Int16 => {
+                    let v = if little_endian {
+                        i16::from_le_bytes([c[0], c[1]])
+                    } else {
+                        i16::from_be_bytes([c[0], c[1]])
+                    };
+                    (v as i64) >> self.quantum_exp
+                }
+                IqFormat::Float32 => {
+                    let bytes = [c[0], c[1], c[2], c[3]];
+                    let v = if little_endian {
+                        f32::from_le_bytes(bytes)
+                    } else {
+                        f32::from_be_bytes(bytes)
+                    };
+                    let k = (v as f64 * 2f64.powi(-self.quantum_exp)).round();
+                    if !k.is_finite() {
+                        i64::MAX
+                    } else {
+                        k as i64
+                    }
+                }
+            };
+
+            if !(-max - 1..=max).contains(&k) {
+                return Err(GlosError::FormatViolation(format!(
+                    "Sample component {k} does not fit {}",
+                    self.recommended
+                )));
+            }
+
+            match (out_width, little_endian) {
+                (1, _) => out.push(k as i8 as u8),
+                (_, true) => out.extend_from_slice(&(k as i16).to_le_bytes()),
+                (_, false) => out.extend_from_slice(&(k as i16).to_be_bytes()),
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for FormatAdvice {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if self.is_beneficial() {
+            write!(
+                f,
+                "{} → {} ({} effective bits, saves {:.0}% / {:.1} MB)",
+                self.current,
+                self.recommended,
+                self.effective_bits,
+                self.savings_ratio() * 100.0,
+                self.savings_bytes() as f64 / 1e6
+            )
+        } else {
+            write!(
+                f,
+                "{} is adequate ({} effective bits)",
+                self.current, self.effective_bits
+            )
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int16_from_8bit_adc_transcodes_losslessly() {
+        // 8-битный АЦП, сдвинутый в старший байт Int16
+        let data: Vec<u8> = (-128i16..128)
+            .flat_map(|v| (v << 8).to_be_bytes())
+            .collect();
+
+        let mut advisor = FormatAdvisor::new(IqFormat::Int16, false);
+        advisor.push(&data);
+        let advice = advisor.advice();
+
+        assert_eq!(advice.recommended, IqFormat::Int8);
+        assert_eq!(advice.effective_bits, 8);
+        assert_eq!(advice.savings_ratio(), 0.5);
+        assert_eq!(advice.samples, 128);
+
+        let out = advice.transcode(&data, false).unwrap();
+        let expected: Vec<u8> = (-128i16..128).map(|v| v as i8 as u8).collect();
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_float32_quantization_detected() {
+        // Нормированные 8-битные значения k/128
+        let quantized: Vec<u8> = (-127i32..=127)
+            .flat_map(|k| (k as f32 / 128.0).to_be_bytes())
+            .collect();
+
+        let mut advisor = FormatAdvisor::new(IqFormat::Float32, false);
+        advisor.push(&quantized);
+        let advice = advisor.advice();
+        assert_eq!(advice.recommended, IqFormat::Int8);
+        assert_eq!(advice.savings_ratio(), 0.75);
+
+        let out = advice.transcode(&quantized, false).unwrap();
+        assert_eq!(out[0] as i8, -127);
+        assert_eq!(out[out.len() - 1] as i8, 127);
+
+        // Настоящие float после DSP сжимать нельзя
+        let dense: Vec<u8> = (1..100)
+            .flat_map(|i| (i as f32).sin().to_be_bytes())
+            .collect();
+        let mut advisor = FormatAdvisor::new(IqFormat::Float32, false);
+        advisor.push(&dense);
+        assert!(!advisor.advice().is_beneficial());
+        assert_eq!(
+            advisor.advice().to_string(),
+            "float32 is adequate (32 effective bits)"
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Перекодирование IQ выборок между Int8, Int16 и Float32.
+//!
+//! Шкала та же, что у чтения (Int8 — `/128`, Int16 — `/32767`, Float32 —
+//! как есть), поэтому расширение формата (Int8 → Int16 → Float32) и
+//! обратное сужение того же сигнала точны. При сужении с потерей
+//! разрядов округление можно заменить TPDF-дизерингом (±1 младший разряд):
+//! ошибка квантования перестаёт зависеть от сигнала и слабые сигналы не
+//! превращаются в гармоники. Так Float32-архивы ужимаются в Int16/Int8, а
+//! целые записи расширяются для потребителей, читающих только float.
+
+use std::io::{Read, Seek, Write};
+
+use glos_types::{GlosError, GlosHeader, GlosResult, IqBlock, IqFormat};
+
+use crate::{
+    serialization::decode_sample, simd, GlosHeaderExt, GlosReader, GlosWriter, IqBlockExt,
+};
+
+/// Seed дизеринга, если вызывающий не задал свой (xorshift не работает с 0).
+const DEFAULT_DITHER_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Настройки перекодирования.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConvertOptions {
+    /// TPDF-дизеринг при сужении формата (Float32 → Int16/Int8, Int16 →
+    /// Int8); при расширении не нужен и не применяется
+    pub dither: bool,
+    /// Seed генератора дизеринга: одинаковый seed — одинаковые байты
+    pub seed: u64,
+}
+
+/// Потоковый перекодировщик полезной нагрузки блоков.
+///
+/// Порядок байт сохраняется: выход в том же порядке, что вход (флаг
+/// little-endian заголовка не меняется). Генератор дизеринга продолжается
+/// между блоками.
+#[derive(Debug, Clone)]
+pub struct IqConverter {
+    from: IqFormat,
+    to: IqFormat,
+    little_endian: bool,
+    /// Состояние xorshift64*; `None` — без дизеринга
+    dither: Option<u64>,
+    /// Компонент I/Q, обрезанных по краю шкалы целого формата
+    clipped: u64,
+}
+
+/// Итог [`transcode`].
+#[derive(Debug, Clone)]
+pub struct Transcode {
+    /// Заголовок нового файла (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    pub samples: u64,
+    /// Компонент I/Q, обрезанных по краю шкалы
+    pub clipped: u64,
+    /// Пропущено блоков с неверной CRC
+    pub corrupted_blocks: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl IqConverter {
+    /// Перекодировщик `from` → `to` для данных с порядком байт
+    /// `little_endian`.
+    pub fn new(
+        from: IqFormat,
+        to: IqFormat,
+        little_endian: bool,
+        options: ConvertOptions,
+    ) -> Self {
+        let narrowing = bits(to) < bits(from);
+        let seed = if options.seed == 0 {
+            DEFAULT_DITHER_SEED
+        } else {
+            options.seed
+        };
+
+        Self {
+            from,
+            to,
+            little_endian,
+            dither: (options.dither && narrowing).then_some(seed),
+            clipped: 0,
+        }
+    }
+
+    /// Формат выходит уже входного — возможна потеря разрядов.
+    pub fn is_narrowing(&self) -> bool {
+        bits(self.to) < bits(self.from)
+    }
+
+    pub fn clipped(&self) -> u64 {
+        self.clipped
+    }
+
+    /// Перекодирует сырые (распакованные) IQ байты.
+    pub fn convert(
+        &mut self,
+        data: &[u8],
+    ) -> GlosResult<Vec<u8>> {
+        let sample_size = self.from.sample_size();
+        if !data.len().is_multiple_of(sample_size) {
+            return Err(GlosError::format_violation(format!(
+                "IQ data length {} is not a multiple of sample size {sample_size}",
+                data.len()
+            )));
+        }
+
+        if self.from == self.to {
+            return Ok(data.to_vec());
+        }
+
+        // Int16 ↔ Float32 без дизеринга — векторными ядрами
+        match (self.from, self.to, self.dither) {
+            (IqFormat::Int16, IqFormat::Float32, _) => {
+                let mut floats = vec![0.0; data.len() / 2];
+                simd::i16_to_f32(data, self.little_endian, &mut floats);
+                return Ok(floats.iter().flat_map(|v| self.f32_bytes(*v)).collect());
+            }
+            (IqFormat::Float32, IqFormat::Int16, None) => {
+                let floats: Vec<f32> = data
+                    .chunks_exact(4)
+                    .map(|c| {
+                        let c = [c[0], c[1], c[2], c[3]];
+                        if self.little_endian {
+                            f32::from_le_bytes(c)
+                        } else {
+                            f32::from_be_bytes(c)
+                        }
+                    })
+                    .collect();
+                let mut out = vec![0; floats.len() * 2];
+                self.clipped += simd::f32_to_i16(&floats, self.little_endian, &mut out);
+                return Ok(out);
+            }
+            _ => {}
+        }
+
+        let mut out = Vec::with_capacity(data.len() / sample_size * self.to.sample_size());
+        for c in data.chunks_exact(sample_size) {
+            for v in decode_sample(c, self.from, self.little_endian) {
+                self.encode(v, &mut out);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Перекодирует блок; сжатый блок сначала распаковывается. Атрибуты
+    /// блока (перегрузка, смена усиления, потери) сохраняются, метка
+    /// синхронизации переносится без изменений.
+    pub fn convert_block(
+        &mut self,
+        block: &IqBlock,
+    ) -> GlosResult<IqBlock> {
+        if block.attributes.sync_marker.is_some() {
+            return Ok(block.clone());
+        }
+
+        let data = self.convert(&block.get_uncompressed_data()?)?;
+        let mut converted = IqBlock::new(block.timestamp_ns, block.sample_count, data);
+        converted.attributes = block.attributes;
+        Ok(converted)
+    }
+
+    fn f32_bytes(
+        &self,
+        v: f32,
+    ) -> [u8; 4] {
+        if self.little_endian {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        }
+    }
+
+    fn encode(
+        &mut self,
+        v: f32,
+        out: &mut Vec<u8>,
+    ) {
+        let (scale, min, max) = match self.to {
+            IqFormat::Int8 => (128.0, -128.0, 127.0),
+            IqFormat::Int16 => (32767.0, -32768.0, 32767.0),
+            IqFormat::Float32 => {
+                out.extend_from_slice(&self.f32_bytes(v));
+                return;
+            }
+        };
+
+        let mut x = v as f64 * scale;
+        if let Some(state) = self.dither.as_mut() {
+            x += uniform(state) - uniform(state);
+        }
+        let x = x.round();
+        if !(min..=max).contains(&x) {
+            self.clipped += 1;
+        }
+        let x = x.clamp(min, max);
+
+        match self.to {
+            IqFormat::Int8 => out.push(x as i8 as u8),
+            _ if self.little_endian => out.extend_from_slice(&(x as i16).to_le_bytes()),
+            _ => out.extend_from_slice(&(x as i16).to_be_bytes()),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Переписывает запись из `reader` в `output` в формате `target_format`.
+///
+/// Заголовок, сжатие, порядок байт и границы блоков — как в исходной
+/// записи; меняется только формат выборок. Блоки с неверной CRC
+/// пропускаются.
+pub fn transcode<R: Read, W: Write + Seek>(
+    reader: &mut GlosReader<R>,
+    output: W,
+    target_format: IqFormat,
+    options: ConvertOptions,
+) -> GlosResult<Transcode> {
+    let source = reader.header().clone();
+    let mut converter = IqConverter::new(
+        source.iq_format,
+        target_format,
+        source.samples_little_endian(),
+        options,
+    );
+
+    let mut header = source.clone();
+    header.iq_format = target_format;
+    let mut writer = GlosWriter::new(output, header)?;
+    let mut corrupted_blocks = 0;
+
+    while let Some(result) = reader.next_block() {
+        let block = match result {
+            Ok(b) => b,
+            Err(GlosError::CrcMismatch { .. }) => {
+                corrupted_blocks += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        writer.write_block(converter.convert_block(&block)?)?;
+    }
+
+    let mut header = writer.header().clone();
+    header.total_samples = writer.total_samples();
+    let blocks = writer.block_count();
+    writer.finish_at(source.timestamp_end)?;
+
+    Ok(Transcode {
+        samples: header.total_samples,
+        header,
+        blocks,
+        clipped: converter.clipped(),
+        corrupted_blocks,
+    })
+}
+
+/// Разрядность компоненты формата.
+fn bits(format: IqFormat) -> u32 {
+    match format {
+        IqFormat::Int8 => 8,
+        IqFormat::Int16 => 16,
+        // 24 бита мантиссы
+        IqFormat::Float32 => 24,
+    }
+}
+
+/// Равномерное число в `[0, 1)` (xorshift64*).
+fn uniform(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    let x = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_types::{SdrType, SyncMarker};
+
+    use super::*;
+    use crate::{dsp::encode_iq, read_all_blocks, GLOS_BLOCK_ATTRIBUTES_VERSION};
+
+    fn tone(n: usize) -> Vec<[f32; 2]> {
+        (0..n)
+            .map(|i| {
+                let ph = std::f32::consts::TAU * i as f32 / 50.0;
+                [0.5 * ph.cos(), 0.5 * ph.sin()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_widening_roundtrip_is_exact() {
+        let int8 = encode_iq(&tone(500), IqFormat::Int8);
+        let off = ConvertOptions::default();
+
+        let wide = IqConverter::new(IqFormat::Int8, IqFormat::Int16, true, off)
+            .convert(&int8)
+            .unwrap();
+        let float = IqConverter::new(IqFormat::Int16, IqFormat::Float32, true, off)
+            .convert(&wide)
+            .unwrap();
+        assert_eq!(float.len(), int8.len() * 4);
+
+        let back = IqConverter::new(IqFormat::Float32, IqFormat::Int8, true, off)
+            .convert(&float)
+            .unwrap();
+        assert_eq!(back, int8);
+
+        // Дизеринг: ошибка не больше ±1 разряда, зависит от seed
+        let dithered = |seed| {
+            let options = ConvertOptions { dither: true, seed };
+            IqConverter::new(IqFormat::Float32, IqFormat::Int8, true, options)
+                .convert(&float)
+                .unwrap()
+        };
+        let d = dithered(1);
+        assert_ne!(d, int8);
+        assert_eq!(d, dithered(1));
+        assert!(d
+            .iter()
+            .zip(&int8)
+            .all(|(a, b)| (*a as i8 as i16 - *b as i8 as i16).abs() <= 1));
+
+        assert!(
+            IqConverter::new(IqFormat::Int16, IqFormat::Int8, false, off)
+                .convert(&[0; 3])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_transcode_file() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.iq_format = IqFormat::Float32;
+        header.timestamp_end = 1_700_000_001;
+
+        let mut samples = tone(1_000);
+        samples[3] = [1.5, -0.25];
+        let mut src = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut src, header).unwrap();
+        for (i, chunk) in samples.chunks(500).enumerate() {
+            let data = encode_iq(chunk, IqFormat::Float32);
+            writer
+                .write_block(IqBlock::new(i as u64 * 250_000, 500, data))
+                .unwrap();
+        }
+        writer.finish_at(1_700_000_001).unwrap();
+
+        src.set_position(0);
+        let mut reader = GlosReader::new(src).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        let t = transcode(
+            &mut reader,
+            &mut out,
+            IqFormat::Int16,
+            ConvertOptions::default(),
+        )
+        .unwrap();
+        assert_eq!((t.blocks, t.samples, t.clipped), (2, 1_000, 1));
+
+        out.set_position(0);
+        let mut reader = GlosReader::new(out).unwrap();
+        assert_eq!(reader.header().iq_format, IqFormat::Int16);
+        assert_eq!(reader.header().timestamp_end, 1_700_000_001);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+        assert_eq!(blocks[1].timestamp_ns, 250_000);
+        assert_eq!(blocks[0].data.len(), 500 * 4);
+    }
+
+    #[test]
+    fn test_transcode_keeps_block_attributes() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.iq_format = IqFormat::Int16;
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+
+        let mut overloaded = IqBlock::new(0, 100, encode_iq(&tone(100), IqFormat::Int16));
+        overloaded.attributes.overload = true;
+        let mut gain = IqBlock::new(50_000, 100, encode_iq(&tone(100), IqFormat::Int16));
+        gain.attributes.gain_db = Some(32.5);
+        gain.attributes.dropped_samples = Some(7);
+
+        let mut src = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut src, header).unwrap();
+        writer.write_block(overloaded.clone()).unwrap();
+        writer.write_block(gain.clone()).unwrap();
+        writer.finish().unwrap();
+
+        src.set_position(0);
+        let mut reader = GlosReader::new(src).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        transcode(
+            &mut reader,
+            &mut out,
+            IqFormat::Float32,
+            ConvertOptions::default(),
+        )
+        .unwrap();
+
+        out.set_position(0);
+        let mut reader = GlosReader::new(out).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].attributes, overloaded.attributes);
+        assert_eq!(blocks[1].attributes, gain.attributes);
+    }
+
+    #[test]
+    fn test_transcode_keeps_sync_markers() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.iq_format = IqFormat::Int8;
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+        let marker = SyncMarker {
+            id: 3,
+            external_ns: 1_234_567,
+        };
+
+        let mut src = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut src, header).unwrap();
+        let data = encode_iq(&tone(100), IqFormat::Int8);
+        writer.write_block(IqBlock::new(0, 100, data)).unwrap();
+        writer
+            .write_block(IqBlock::new_sync_marker(50_000, marker))
+            .unwrap();
+        writer.finish().unwrap();
+
+        src.set_position(0);
+        let mut reader = GlosReader::new(src).unwrap();
+        let mut out = Cursor::new(Vec::new());
+        let t = transcode(
+            &mut reader,
+            &mut out,
+            IqFormat::Int16,
+            ConvertOptions::default(),
+        )
+        .unwrap();
+        assert_eq!((t.blocks, t.samples), (2, 100));
+
+        out.set_position(0);
+        let mut reader = GlosReader::new(out).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks[1].timestamp_ns, 50_000);
+        assert_eq!(blocks[1].sample_count, 0);
+        assert_eq!(blocks[1].attributes.sync_marker, Some(marker));
+    }
+}
@@ -0,0 +1,452 @@
+//! Оценка C/N0 по сигналу GPS L1 C/A и генератор тестового сигнала.
+//!
+//! Оценщик — корреляционный: для каждого интервала когерентного накопления
+//! снимается несущая (известный Доплер), сигнал коррелируется с локальной
+//! копией C/A кода, а по моментам второго и четвёртого порядка prompt
+//! корреляторов (M2M4) разделяется мощность сигнала и шума. M2M4 не
+//! чувствителен к фазе несущей и к смене навигационных битов.
+//!
+//! [`GnssSignalSim`] генерирует сигнал с заданным C/N0 на фоне белого шума —
+//! по нему оценщик проверяется в тестах.
+
+use glos_types::{GlosError, GlosResult};
+
+/// Длина C/A кода, чипов.
+pub const CA_CODE_LEN: usize = 1023;
+
+/// Чиповая частота C/A кода, Гц.
+pub const CA_CHIP_RATE_HZ: f64 = 1.023e6;
+
+/// Несущая GPS L1, Гц.
+pub const GPS_L1_HZ: f64 = 1_575.42e6;
+
+/// Отводы G2 (фазовый селектор) для PRN 1..=32, IS-GPS-200 табл. 3-Ia.
+const G2_TAPS: [(usize, usize); 32] = [
+    (2, 6),
+    (3, 7),
+    (4, 8),
+    (5, 9),
+    (1, 9),
+    (2, 10),
+    (1, 8),
+    (2, 9),
+    (3, 10),
+    (2, 3),
+    (3, 4),
+    (5, 6),
+    (6, 7),
+    (7, 8),
+    (8, 9),
+    (9, 10),
+    (1, 4),
+    (2, 5),
+    (3, 6),
+    (4, 7),
+    (5, 8),
+    (6, 9),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (6, 8),
+    (7, 9),
+    (8, 10),
+    (1, 6),
+    (2, 7),
+    (3, 8),
+    (4, 9),
+];
+
+/// Параметры оценки C/N0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cn0Params {
+    /// Частота дискретизации выборок, Гц
+    pub sample_rate_hz: u32,
+    /// Доплеровский сдвиг несущей относительно центра, Гц
+    pub doppler_hz: f64,
+    /// Кодовая фаза (чипы) на выборке 0; None = поиск по первым интервалам
+    pub code_phase_chips: Option<f64>,
+    /// Длительность когерентного накопления, мс (1..=20)
+    pub coherent_ms: u32,
+}
+
+/// Результат оценки C/N0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cn0Estimate {
+    /// C/N0, дБ·Гц (±∞ для вырожденных случаев: нет шума / нет сигнала)
+    pub cn0_db_hz: f64,
+    /// Использованная кодовая фаза, чипы
+    pub code_phase_chips: f64,
+    /// Сколько интервалов когерентного накопления усреднено
+    pub intervals: usize,
+}
+
+/// Генератор сигнала GPS L1 C/A с известным C/N0.
+///
+/// Шум — комплексный гауссов с единичной полной мощностью на выборку,
+/// амплитуда сигнала подбирается под `cn0_db_hz`. Выход детерминирован
+/// по `seed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GnssSignalSim {
+    pub prn: u8,
+    pub cn0_db_hz: f64,
+    pub doppler_hz: f64,
+    /// Кодовая фаза на выборке 0, чипы
+    pub code_phase_chips: f64,
+    pub sample_rate_hz: u32,
+    pub seed: u64,
+}
+
+/// Минимум интервалов для устойчивой оценки моментов.
+const MIN_INTERVALS: usize = 10;
+
+/// Сколько интервалов используется при поиске кодовой фазы.
+const ACQUISITION_INTERVALS: usize = 4;
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Cn0Params {
+    /// Параметры по умолчанию: нулевой Доплер, поиск кодовой фазы, 1 мс.
+    pub fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            sample_rate_hz,
+            doppler_hz: 0.0,
+            code_phase_chips: None,
+            coherent_ms: 1,
+        }
+    }
+}
+
+impl GnssSignalSim {
+    /// Генерирует `n` комплексных выборок `[I, Q]`.
+    pub fn generate(
+        &self,
+        n: usize,
+    ) -> GlosResult<Vec<[f32; 2]>> {
+        let code = ca_code(self.prn)?;
+
+        if self.sample_rate_hz == 0 {
+            return Err(GlosError::FormatViolation(
+                "Sample rate must be > 0".to_string(),
+            ));
+        }
+
+        let fs = self.sample_rate_hz as f64;
+        let amplitude = (10f64.powf(self.cn0_db_hz / 10.0) / fs).sqrt();
+        let chip_step = code_rate(self.doppler_hz) / fs;
+        let mut rng = self.seed;
+
+        let samples = (0..n)
+            .map(|i| {
+                let chip = code[chip_index(self.code_phase_chips + i as f64 * chip_step)] as f64;
+                let (sin, cos) = carrier_phase(self.doppler_hz, i, fs).sin_cos();
+                let (ni, nq) = gaussian_pair(&mut rng);
+
+                [
+                    (amplitude * chip * cos + ni * std::f64::consts::FRAC_1_SQRT_2) as f32,
+                    (amplitude * chip * sin + nq * std::f64::consts::FRAC_1_SQRT_2) as f32,
+                ]
+            })
+            .collect();
+
+        Ok(samples)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Публичные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// C/A код GPS L1 для `prn` (1..=32) в биполярной форме: бит 0 → +1,
+/// бит 1 → −1.
+pub fn ca_code(prn: u8) -> GlosResult<[i8; CA_CODE_LEN]> {
+    let (s1, s2) = prn
+        .checked_sub(1)
+        .and_then(|i| G2_TAPS.get(i as usize))
+        .copied()
+        .ok_or_else(|| GlosError::FormatViolation(format!("Unsupported GPS PRN {prn}")))?;
+
+    let mut g1 = [1u8; 10];
+    let mut g2 = [1u8; 10];
+    let mut code = [0i8; CA_CODE_LEN];
+
+    for chip in code.iter_mut() {
+        let bit = g1[9] ^ g2[s1 - 1] ^ g2[s2 - 1];
+        *chip = if bit == 0 { 1 } else { -1 };
+
+        // G1 = 1 + x³ + x¹⁰, G2 = 1 + x² + x³ + x⁶ + x⁸ + x⁹ + x¹⁰
+        let f1 = g1[2] ^ g1[9];
+        let f2 = g2[1] ^ g2[2] ^ g2[5] ^ g2[7] ^ g2[8] ^ g2[9];
+
+        g1.rotate_right(1);
+        g2.rotate_right(1);
+        g1[0] = f1;
+        g2[0] = f2;
+    }
+
+    Ok(code)
+}
+
+/// Оценивает C/N0 спутника `prn` по комплексным выборкам `[I, Q]`.
+///
+/// Доплер должен быть известен с точностью лучше `1 / (2·coherent_ms)` кГц,
+/// иначе накопление теряет энергию и оценка занижается. Кодовая фаза, если
+/// не задана, ищется с шагом в полчипа по первым интервалам.
+pub fn estimate_cn0(
+    samples: &[[f32; 2]],
+    prn: u8,
+    params: &Cn0Params,
+) -> GlosResult<Cn0Estimate> {
+    let code = ca_code(prn)?;
+
+    if params.sample_rate_hz == 0 {
+        return Err(GlosError::FormatViolation(
+            "Sample rate must be > 0".to_string(),
+        ));
+    }
+
+    if !(1..=20).contains(&params.coherent_ms) {
+        return Err(GlosError::FormatViolation(format!(
+            "Coherent integration must be 1..=20 ms, got {}",
+            params.coherent_ms
+        )));
+    }
+
+    let fs = params.sample_rate_hz as f64;
+    let interval_len = (fs * params.coherent_ms as f64 / 1000.0).round() as usize;
+    let intervals = samples.len() / interval_len.max(1);
+
+    if interval_len == 0 || intervals < MIN_INTERVALS {
+        return Err(GlosError::FormatViolation(format!(
+            "Need at least {MIN_INTERVALS} × {} ms of samples, got {}",
+            params.coherent_ms,
+            samples.len()
+        )));
+    }
+
+    // Снятие несущей делается один раз — дальше только умножение на код
+    let wiped: Vec<[f64; 2]> = samples[..intervals * interval_len]
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let (sin, cos) = carrier_phase(params.doppler_hz, i, fs).sin_cos();
+            let (re, im) = (s[0] as f64, s[1] as f64);
+            [re * cos + im * sin, im * cos - re * sin]
+        })
+        .collect();
+
+    let chip_step = code_rate(params.doppler_hz) / fs;
+
+    let code_phase = match params.code_phase_chips {
+        Some(p) => p,
+        None => {
+            let acq = &wiped[..interval_len * ACQUISITION_INTERVALS.min(intervals)];
+            (0..2 * CA_CODE_LEN)
+                .map(|k| k as f64 * 0.5)
+                .map(|p| {
+                    let power: f64 = acq
+                        .chunks_exact(interval_len)
+                        .enumerate()
+                        .map(|(j, c)| {
+                            let (re, im) = correlate(c, &code, p, j * interval_len, chip_step);
+                            re * re + im * im
+                        })
+                        .sum();
+                    (p, power)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(p, _)| p)
+                .unwrap_or(0.0)
+        }
+    };
+
+    // Моменты M2 и M4 модуля prompt коррелятора
+    let (mut m2, mut m4) = (0.0f64, 0.0f64);
+
+    for (j, c) in wiped.chunks_exact(interval_len).enumerate() {
+        let (re, im) = correlate(c, &code, code_phase, j * interval_len, chip_step);
+        let p = re * re + im * im;
+        m2 += p;
+        m4 += p * p;
+    }
+
+    m2 /= intervals as f64;
+    m4 /= intervals as f64;
+
+    let signal = (2.0 * m2 * m2 - m4).max(0.0).sqrt();
+    let noise = m2 - signal;
+    let t = params.coherent_ms as f64 / 1000.0;
+
+    let cn0_db_hz = if noise <= 0.0 {
+        f64::INFINITY
+    } else {
+        10.0 * (signal / (noise * t)).log10()
+    };
+
+    Ok(Cn0Estimate {
+        cn0_db_hz,
+        code_phase_chips: code_phase,
+        intervals,
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Чиповая частота с учётом кодового Доплера.
+fn code_rate(doppler_hz: f64) -> f64 {
+    CA_CHIP_RATE_HZ * (1.0 + doppler_hz / GPS_L1_HZ)
+}
+
+fn carrier_phase(
+    doppler_hz: f64,
+    sample: usize,
+    fs: f64,
+) -> f64 {
+    2.0 * std::f64::consts::PI * doppler_hz * sample as f64 / fs
+}
+
+fn chip_index(phase_chips: f64) -> usize {
+    (phase_chips.rem_euclid(CA_CODE_LEN as f64) as usize).min(CA_CODE_LEN - 1)
+}
+
+/// Корреляция отрезка с кодом; `offset` — номер первой выборки отрезка.
+fn correlate(
+    chunk: &[[f64; 2]],
+    code: &[i8; CA_CODE_LEN],
+    phase_chips: f64,
+    offset: usize,
+    chip_step: f64,
+) -> (f64, f64) {
+    let mut acc = (0.0, 0.0);
+
+    for (i, s) in chunk.iter().enumerate() {
+        let chip = code[chip_index(phase_chips + (offset + i) as f64 * chip_step)] as f64;
+        acc.0 += s[0] * chip;
+        acc.1 += s[1] * chip;
+    }
+
+    acc
+}
+
+/// Пара независимых N(0, 1) (Бокс — Мюллер поверх splitmix64).
+fn gaussian_pair(state: &mut u64) -> (f64, f64) {
+    let u1 = (splitmix64(state) >> 11) as f64 / (1u64 << 53) as f64;
+    let u2 = (splitmix64(state) >> 11) as f64 / (1u64 << 53) as f64;
+
+    let r = (-2.0 * (1.0 - u1).ln()).sqrt();
+    let (sin, cos) = (2.0 * std::f64::consts::PI * u2).sin_cos();
+
+    (r * cos, r * sin)
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: u32 = 2_046_000;
+
+    fn sim(
+        prn: u8,
+        cn0_db_hz: f64,
+        seed: u64,
+    ) -> GnssSignalSim {
+        GnssSignalSim {
+            prn,
+            cn0_db_hz,
+            doppler_hz: 1_250.0,
+            code_phase_chips: 312.0,
+            sample_rate_hz: FS,
+            seed,
+        }
+    }
+
+    #[test]
+    fn test_ca_code_reference_chips() {
+        // Первые 10 чипов в восьмеричной записи (IS-GPS-200): PRN 1 = 1440,
+        // PRN 2 = 1620, PRN 32 = 1712
+        let first10 = |prn: u8| {
+            ca_code(prn).unwrap()[..10]
+                .iter()
+                .fold(0u16, |acc, &c| (acc << 1) | (c < 0) as u16)
+        };
+
+        assert_eq!(first10(1), 0o1440);
+        assert_eq!(first10(2), 0o1620);
+        assert_eq!(first10(32), 0o1712);
+
+        // Код сбалансирован: 512 единиц и 511 нулей
+        let ones = ca_code(7).unwrap().iter().filter(|&&c| c < 0).count();
+        assert_eq!(ones, 512);
+
+        assert!(ca_code(0).is_err());
+        assert!(ca_code(33).is_err());
+    }
+
+    #[test]
+    fn test_estimate_cn0_matches_simulator() {
+        for (i, &cn0) in [35.0, 40.0, 45.0].iter().enumerate() {
+            let s = sim(5, cn0, 100 + i as u64);
+            let samples = s.generate(FS as usize * 2 / 5).unwrap();
+
+            let params = Cn0Params {
+                doppler_hz: s.doppler_hz,
+                code_phase_chips: Some(s.code_phase_chips),
+                ..Cn0Params::new(FS)
+            };
+            let est = estimate_cn0(&samples, 5, &params).unwrap();
+
+            assert_eq!(est.intervals, 400);
+            assert!(
+                (est.cn0_db_hz - cn0).abs() < 1.0,
+                "expected {cn0} dB-Hz, got {:.2}",
+                est.cn0_db_hz
+            );
+        }
+    }
+
+    #[test]
+    fn test_estimate_cn0_acquires_code_phase() {
+        let s = sim(12, 45.0, 7);
+        let samples = s.generate(FS as usize / 5).unwrap();
+
+        let params = Cn0Params {
+            doppler_hz: s.doppler_hz,
+            ..Cn0Params::new(FS)
+        };
+        let est = estimate_cn0(&samples, 12, &params).unwrap();
+
+        assert!((est.code_phase_chips - s.code_phase_chips).abs() <= 0.5);
+        assert!(
+            (est.cn0_db_hz - 45.0).abs() < 1.5,
+            "got {:.2}",
+            est.cn0_db_hz
+        );
+
+        // Чужой PRN: сигнал не обнаруживается
+        let other = estimate_cn0(&samples, 13, &params).unwrap();
+        assert!(other.cn0_db_hz < 35.0, "got {:.2}", other.cn0_db_hz);
+
+        // Слишком короткий отрезок и некорректные параметры
+        assert!(estimate_cn0(&samples[..FS as usize / 1000], 12, &params).is_err());
+        let bad = Cn0Params {
+            coherent_ms: 0,
+            ..params
+        };
+        assert!(estimate_cn0(&samples, 12, &bad).is_err());
+    }
+}
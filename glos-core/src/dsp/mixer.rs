@@ -0,0 +1,117 @@
+//! Перенос спектра комплексным гетеродином (NCO).
+//!
+//! Умножение на `exp(-j·2π·shift·n / rate)` сдвигает спектр на `-shift`:
+//! составляющая на `+shift` от центра оказывается на нулевой частоте. Так
+//! узкую полосу вне центра записи можно вырезать передискретизатором
+//! ([`crate::dsp::Resampler`]), фильтр которого центрирован на нуле.
+
+use glos_types::{GlosError, GlosResult};
+
+const TAU: f64 = std::f64::consts::TAU;
+
+/// Потоковый сдвиг частоты комплексных выборок.
+///
+/// Фаза гетеродина сохраняется между вызовами: обработка по блокам
+/// совпадает с обработкой всего потока разом.
+#[derive(Debug, Clone)]
+pub struct Mixer {
+    shift_hz: f64,
+    /// Приращение фазы на выборку, рад
+    step: f64,
+    /// Текущая фаза, рад (в `[0, 2π)`)
+    phase: f64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Mixer {
+    /// Создаёт гетеродин, переносящий частоту `shift_hz` (относительно
+    /// центра) в ноль при частоте дискретизации `sample_rate` (Гц).
+    pub fn new(
+        shift_hz: f64,
+        sample_rate: u32,
+    ) -> GlosResult<Self> {
+        if sample_rate == 0 || !shift_hz.is_finite() || shift_hz.abs() > sample_rate as f64 / 2.0 {
+            return Err(GlosError::FormatViolation(format!(
+                "Invalid frequency shift {shift_hz} Hz at {sample_rate} Hz"
+            )));
+        }
+
+        Ok(Self {
+            shift_hz,
+            step: -TAU * shift_hz / sample_rate as f64,
+            phase: 0.0,
+        })
+    }
+
+    pub fn shift_hz(&self) -> f64 {
+        self.shift_hz
+    }
+
+    /// Сбрасывает фазу гетеродина.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Сдвигает спектр выборок `[I, Q]` на месте.
+    pub fn process(
+        &mut self,
+        samples: &mut [[f32; 2]],
+    ) {
+        for s in samples {
+            let (sin, cos) = self.phase.sin_cos();
+            let (i, q) = (s[0] as f64, s[1] as f64);
+            *s = [(i * cos - q * sin) as f32, (i * sin + q * cos) as f32];
+
+            self.phase = (self.phase + self.step).rem_euclid(TAU);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(
+        n: usize,
+        freq: f64,
+        rate: f64,
+    ) -> Vec<[f32; 2]> {
+        (0..n)
+            .map(|i| {
+                let ph = TAU * freq * i as f64 / rate;
+                [ph.cos() as f32, ph.sin() as f32]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mixer_moves_tone_to_dc() {
+        let mut whole = tone(4_000, -250.0, 4_000.0);
+        Mixer::new(-250.0, 4_000).unwrap().process(&mut whole);
+
+        for s in &whole {
+            assert!((s[0] - 1.0).abs() < 1e-4 && s[1].abs() < 1e-4, "{s:?}");
+        }
+
+        // По блокам — то же, что разом
+        let mut chunked = tone(4_000, 300.0, 4_000.0);
+        let mut expected = chunked.clone();
+        Mixer::new(100.0, 4_000).unwrap().process(&mut expected);
+
+        let mut m = Mixer::new(100.0, 4_000).unwrap();
+        for c in chunked.chunks_mut(333) {
+            m.process(c);
+        }
+        assert_eq!(chunked, expected);
+
+        assert!(Mixer::new(2_001.0, 4_000).is_err());
+        assert!(Mixer::new(0.0, 0).is_err());
+    }
+}
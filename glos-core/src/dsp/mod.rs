@@ -0,0 +1,226 @@
+//! Пост-обработка сырых IQ выборок прямо в байтовом представлении.
+//!
+//! Функции работают с данными блока «как есть» (в порядке байт файла, без
+//! декодирования в float), поэтому годятся и для записи в реальном времени,
+//! и для исправления уже записанных файлов.
+
+pub mod advice;
+pub mod convert;
+pub mod gnss;
+pub mod mixer;
+pub mod resample;
+pub mod thumbnail;
+
+pub use advice::{FormatAdvice, FormatAdvisor};
+pub use convert::{transcode, ConvertOptions, IqConverter, Transcode};
+use glos_types::{GlosError, GlosHeader, GlosResult, IqFormat};
+pub use gnss::{
+    ca_code, estimate_cn0, Cn0Estimate, Cn0Params, GnssSignalSim, CA_CHIP_RATE_HZ, CA_CODE_LEN,
+    GPS_L1_HZ,
+};
+pub use mixer::Mixer;
+pub use resample::{decode_iq, encode_iq, ResampleQuality, Resampler};
+pub use thumbnail::{Thumbnail, ThumbnailBuilder, THUMBNAIL_BINS, THUMBNAIL_MAX_ROWS};
+
+use crate::{GLOS_FLAG_IQ_SWAPPED, GLOS_FLAG_SPECTRUM_INVERTED};
+
+/// Исправление IQ, применяемое к потоку.
+///
+/// Порядок фиксирован: сначала перестановка I/Q, затем инверсия спектра.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IqCorrection {
+    /// Поменять местами I и Q
+    pub swap_iq: bool,
+    /// Инвертировать спектр (комплексное сопряжение: Q → -Q)
+    pub invert_spectrum: bool,
+}
+
+impl IqCorrection {
+    /// Ничего не нужно делать.
+    pub fn is_identity(&self) -> bool {
+        !self.swap_iq && !self.invert_spectrum
+    }
+
+    /// Применяет исправление к сырым IQ байтам с компонентами в порядке
+    /// `little_endian`.
+    pub fn apply(
+        &self,
+        data: &mut [u8],
+        format: IqFormat,
+        little_endian: bool,
+    ) -> GlosResult<()> {
+        if self.swap_iq {
+            swap_iq(data, format)?;
+        }
+        if self.invert_spectrum {
+            invert_spectrum(data, format, little_endian)?;
+        }
+        Ok(())
+    }
+
+    /// Флаги заголовка, фиксирующие применённое исправление.
+    pub fn header_flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.swap_iq {
+            flags |= GLOS_FLAG_IQ_SWAPPED;
+        }
+        if self.invert_spectrum {
+            flags |= GLOS_FLAG_SPECTRUM_INVERTED;
+        }
+        flags
+    }
+
+    /// Исправление, записанное в заголовке файла.
+    pub fn from_header(header: &GlosHeader) -> Self {
+        Self {
+            swap_iq: header.flags & GLOS_FLAG_IQ_SWAPPED != 0,
+            invert_spectrum: header.flags & GLOS_FLAG_SPECTRUM_INVERTED != 0,
+        }
+    }
+}
+
+/// Меняет местами I и Q в каждой паре.
+pub fn swap_iq(
+    data: &mut [u8],
+    format: IqFormat,
+) -> GlosResult<()> {
+    let sample_size = check_len(data, format)?;
+    let half = sample_size / 2;
+
+    for pair in data.chunks_exact_mut(sample_size) {
+        let (i, q) = pair.split_at_mut(half);
+        i.swap_with_slice(q);
+    }
+
+    Ok(())
+}
+
+/// Инвертирует спектр: Q → -Q (зеркалирование относительно несущей).
+///
+/// Для целых форматов используется насыщение: `-(-32768)` даёт `32767`.
+/// Компоненты — в порядке `little_endian`.
+pub fn invert_spectrum(
+    data: &mut [u8],
+    format: IqFormat,
+    little_endian: bool,
+) -> GlosResult<()> {
+    let sample_size = check_len(data, format)?;
+
+    for pair in data.chunks_exact_mut(sample_size) {
+        match (format, little_endian) {
+            (IqFormat::Int8, _) => {
+                pair[1] = (pair[1] as i8).saturating_neg() as u8;
+            }
+            (IqFormat::Int16, false) => {
+                let q = i16::from_be_bytes([pair[2], pair[3]]).saturating_neg();
+                pair[2..4].copy_from_slice(&q.to_be_bytes());
+            }
+            (IqFormat::Int16, true) => {
+                let q = i16::from_le_bytes([pair[2], pair[3]]).saturating_neg();
+                pair[2..4].copy_from_slice(&q.to_le_bytes());
+            }
+            // Знаковый бит в старшем байте f32
+            (IqFormat::Float32, false) => pair[4] ^= 0x80,
+            (IqFormat::Float32, true) => pair[7] ^= 0x80,
+        }
+    }
+
+    Ok(())
+}
+
+fn check_len(
+    data: &[u8],
+    format: IqFormat,
+) -> GlosResult<usize> {
+    let sample_size = format.sample_size();
+
+    if !data.len().is_multiple_of(sample_size) {
+        return Err(GlosError::FormatViolation(format!(
+            "IQ data length {} is not a multiple of sample size {sample_size}",
+            data.len()
+        )));
+    }
+
+    Ok(sample_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use glos_types::SdrType;
+
+    use super::*;
+
+    fn int16_pairs(pairs: &[(i16, i16)]) -> Vec<u8> {
+        pairs
+            .iter()
+            .flat_map(|(i, q)| [i.to_be_bytes(), q.to_be_bytes()].concat())
+            .collect()
+    }
+
+    #[test]
+    fn test_swap_iq_all_formats() {
+        let mut d = vec![1u8, 2, 3, 4];
+        swap_iq(&mut d, IqFormat::Int8).unwrap();
+        assert_eq!(d, [2, 1, 4, 3]);
+
+        let mut d = int16_pairs(&[(100, -200)]);
+        swap_iq(&mut d, IqFormat::Int16).unwrap();
+        assert_eq!(d, int16_pairs(&[(-200, 100)]));
+
+        let mut d = [1.5f32.to_be_bytes(), (-2.5f32).to_be_bytes()].concat();
+        swap_iq(&mut d, IqFormat::Float32).unwrap();
+        assert_eq!(d, [(-2.5f32).to_be_bytes(), 1.5f32.to_be_bytes()].concat());
+
+        assert!(swap_iq(&mut [0u8; 3], IqFormat::Int16).is_err());
+    }
+
+    #[test]
+    fn test_invert_spectrum_all_formats() {
+        let mut d = vec![5u8, (-7i8) as u8, 0, 0x80];
+        invert_spectrum(&mut d, IqFormat::Int8, false).unwrap();
+        assert_eq!(d, [5, 7, 0, 127]);
+
+        let mut d = int16_pairs(&[(100, -200), (1, i16::MIN)]);
+        invert_spectrum(&mut d, IqFormat::Int16, false).unwrap();
+        assert_eq!(d, int16_pairs(&[(100, 200), (1, i16::MAX)]));
+
+        let mut d = [1.5f32.to_be_bytes(), (-2.5f32).to_be_bytes()].concat();
+        invert_spectrum(&mut d, IqFormat::Float32, false).unwrap();
+        assert_eq!(d, [1.5f32.to_be_bytes(), 2.5f32.to_be_bytes()].concat());
+
+        let mut d = [100i16, i16::MIN]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        invert_spectrum(&mut d, IqFormat::Int16, true).unwrap();
+        assert_eq!(
+            d,
+            [100i16, i16::MAX]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<_>>()
+        );
+
+        let mut d = [1.5f32.to_le_bytes(), (-2.5f32).to_le_bytes()].concat();
+        invert_spectrum(&mut d, IqFormat::Float32, true).unwrap();
+        assert_eq!(d, [1.5f32.to_le_bytes(), 2.5f32.to_le_bytes()].concat());
+    }
+
+    #[test]
+    fn test_correction_order_and_flags() {
+        let c = IqCorrection {
+            swap_iq: true,
+            invert_spectrum: true,
+        };
+
+        // (I, Q) → (Q, I) → (Q, -I)
+        let mut d = int16_pairs(&[(3, 4)]);
+        c.apply(&mut d, IqFormat::Int16, false).unwrap();
+        assert_eq!(d, int16_pairs(&[(4, -3)]));
+
+        let mut header = <GlosHeader as crate::GlosHeaderExt>::new(SdrType::Unknown, 1, 1);
+        header.flags |= c.header_flags();
+        assert_eq!(IqCorrection::from_header(&header), c);
+        assert!(IqCorrection::default().is_identity());
+    }
+}
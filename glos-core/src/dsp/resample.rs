@@ -0,0 +1,456 @@
+//! Передискретизация IQ потока с произвольным отношением частот.
+//!
+//! Полифазный фильтр: прототип — windowed-sinc (окно Кайзера), разбитый на
+//! `phases` подфильтров. Для каждой выходной выборки берутся два соседних
+//! подфильтра и линейно интерполируются по дробной позиции, поэтому
+//! отношение частот может быть любым (например 2.048 → 2.000 Msps).
+//!
+//! Позиция во входном потоке ведётся рациональной арифметикой — дрейфа на
+//! длинных записях нет.
+
+use std::str::FromStr;
+
+use glos_types::{GlosError, GlosResult, IqFormat};
+
+use crate::simd;
+
+/// Пресет качества: длина фильтра и число фаз.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// 8 отводов × 32 фазы — для предпросмотра
+    Fast,
+    /// 16 отводов × 128 фаз — по умолчанию
+    #[default]
+    Balanced,
+    /// 32 отвода × 256 фаз — для архивной обработки
+    High,
+}
+
+/// Потоковый передискретизатор комплексных выборок.
+///
+/// Блоки можно подавать любого размера: результат совпадает с обработкой
+/// всего потока разом. Выход задержан на половину фильтра, но начальная
+/// задержка компенсирована — выборка 0 выхода соответствует выборке 0 входа.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    in_rate: u32,
+    out_rate: u32,
+    quality: ResampleQuality,
+    taps: usize,
+    phases: usize,
+    /// `(phases + 1) × taps` коэффициентов; последняя фаза — копия первой,
+    /// сдвинутой на отвод (для интерполяции без ветвлений)
+    bank: Vec<f32>,
+    /// Необработанный хвост входа
+    history: Vec<[f32; 2]>,
+    /// Дробная позиция в единицах `1 / out_rate` входной выборки
+    frac_num: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl ResampleQuality {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResampleQuality::Fast => "fast",
+            ResampleQuality::Balanced => "balanced",
+            ResampleQuality::High => "high",
+        }
+    }
+
+    /// `(отводов на фазу, фаз, β окна Кайзера)`
+    fn params(&self) -> (usize, usize, f64) {
+        match self {
+            ResampleQuality::Fast => (8, 32, 5.0),
+            ResampleQuality::Balanced => (16, 128, 7.0),
+            ResampleQuality::High => (32, 256, 9.0),
+        }
+    }
+}
+
+impl Resampler {
+    /// Создаёт передискретизатор `in_rate` → `out_rate` (Гц).
+    pub fn new(
+        in_rate: u32,
+        out_rate: u32,
+        quality: ResampleQuality,
+    ) -> GlosResult<Self> {
+        if in_rate == 0 || out_rate == 0 {
+            return Err(GlosError::FormatViolation(format!(
+                "Invalid resample rates: {in_rate} → {out_rate}"
+            )));
+        }
+
+        let (taps, phases, beta) = quality.params();
+
+        // При понижении частоты срез сдвигается вниз — антиалиасинг
+        let cutoff = (out_rate as f64 / in_rate as f64).min(1.0) * 0.95;
+        let delay = (taps / 2 - 1) as f64;
+        let mut bank = Vec::with_capacity((phases + 1) * taps);
+
+        for p in 0..=phases {
+            let frac = p as f64 / phases as f64;
+            let start = bank.len();
+
+            for k in 0..taps {
+                let x = delay + frac - k as f64;
+                bank.push((cutoff * sinc(cutoff * x) * kaiser(x, taps, beta)) as f32);
+            }
+
+            // Единичное усиление на постоянной составляющей в каждой фазе
+            let sum: f32 = bank[start..].iter().sum();
+            for c in &mut bank[start..] {
+                *c /= sum;
+            }
+        }
+
+        let mut r = Self {
+            in_rate,
+            out_rate,
+            quality,
+            taps,
+            phases,
+            bank,
+            history: Vec::new(),
+            frac_num: 0,
+        };
+        r.reset();
+
+        Ok(r)
+    }
+
+    pub fn in_rate(&self) -> u32 {
+        self.in_rate
+    }
+
+    pub fn out_rate(&self) -> u32 {
+        self.out_rate
+    }
+
+    pub fn quality(&self) -> ResampleQuality {
+        self.quality
+    }
+
+    /// Отношение `out_rate / in_rate`.
+    pub fn ratio(&self) -> f64 {
+        self.out_rate as f64 / self.in_rate as f64
+    }
+
+    /// Сбрасывает состояние (например, при перемотке в начало файла).
+    pub fn reset(&mut self) {
+        // Предзаполнение нулями компенсирует групповую задержку фильтра
+        self.history.clear();
+        self.history.resize(self.taps / 2 - 1, [0.0, 0.0]);
+        self.frac_num = 0;
+    }
+
+    /// Обрабатывает очередную порцию комплексных выборок `[I, Q]`.
+    pub fn process(
+        &mut self,
+        input: &[[f32; 2]],
+    ) -> Vec<[f32; 2]> {
+        self.history.extend_from_slice(input);
+
+        let in_rate = self.in_rate as u64;
+        let out_rate = self.out_rate as u64;
+        let mut out = Vec::with_capacity((input.len() as f64 * self.ratio()) as usize + 1);
+        let mut idx = 0usize;
+
+        while idx + self.taps <= self.history.len() {
+            // Фаза и коэффициент интерполяции между соседними фазами
+            let pos = self.frac_num as f64 / out_rate as f64 * self.phases as f64;
+            let p = (pos as usize).min(self.phases - 1);
+            let mu = (pos - p as f64) as f32;
+
+            let h0 = &self.bank[p * self.taps..(p + 1) * self.taps];
+            let h1 = &self.bank[(p + 1) * self.taps..(p + 2) * self.taps];
+            let window = &self.history[idx..idx + self.taps];
+
+            let mut acc = [0.0f32; 2];
+            for ((s, &a), &b) in window.iter().zip(h0).zip(h1) {
+                let h = a + (b - a) * mu;
+                acc[0] += s[0] * h;
+                acc[1] += s[1] * h;
+            }
+            out.push(acc);
+
+            // Следующая выходная выборка: +in_rate/out_rate входных
+            self.frac_num += in_rate;
+            idx += (self.frac_num / out_rate) as usize;
+            self.frac_num %= out_rate;
+        }
+
+        self.history.drain(..idx.min(self.history.len()));
+
+        out
+    }
+
+    /// Передискретизирует сырые IQ байты (big-endian) в том же формате.
+    ///
+    /// Возвращает новые байты; число выборок = `len / format.sample_size()`.
+    pub fn process_bytes(
+        &mut self,
+        data: &[u8],
+        format: IqFormat,
+    ) -> GlosResult<Vec<u8>> {
+        let input = decode_iq(data, format)?;
+
+        Ok(encode_iq(&self.process(&input), format))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for ResampleQuality {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ResampleQuality {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fast" => Ok(ResampleQuality::Fast),
+            "balanced" => Ok(ResampleQuality::Balanced),
+            "high" => Ok(ResampleQuality::High),
+            _ => Err(format!(
+                "Unknown resample quality '{s}'. Use: fast, balanced, high"
+            )),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
+/// Окно Кайзера длины `taps`, центрированное на задержке фильтра.
+fn kaiser(
+    x: f64,
+    taps: usize,
+    beta: f64,
+) -> f64 {
+    let half = taps as f64 / 2.0;
+    let r = x / half;
+
+    if r.abs() >= 1.0 {
+        return 0.0;
+    }
+
+    bessel_i0(beta * (1.0 - r * r).sqrt()) / bessel_i0(beta)
+}
+
+/// Модифицированная функция Бесселя первого рода нулевого порядка (ряд).
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let q = x * x / 4.0;
+
+    for k in 1..50 {
+        term *= q / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-16 {
+            break;
+        }
+    }
+
+    sum
+}
+
+/// Декодирует сырые IQ байты (big-endian) в комплексные выборки `[I, Q]`
+/// с полной шкалой ±1.0.
+pub fn decode_iq(
+    data: &[u8],
+    format: IqFormat,
+) -> GlosResult<Vec<[f32; 2]>> {
+    let sample_size = format.sample_size();
+
+    if !data.len().is_multiple_of(sample_size) {
+        return Err(GlosError::FormatViolation(format!(
+            "IQ data length {} is not a multiple of sample size {sample_size}",
+            data.len()
+        )));
+    }
+
+    if format == IqFormat::Int16 {
+        let mut out = vec![[0.0; 2]; data.len() / sample_size];
+        simd::i16_to_f32(data, false, bytemuck::cast_slice_mut(&mut out));
+        return Ok(out);
+    }
+
+    Ok(data
+        .chunks_exact(sample_size)
+        .map(|c| decode_pair(c, format))
+        .collect())
+}
+
+/// Кодирует выборки `[I, Q]` в сырые байты (big-endian); целые форматы
+/// насыщаются на краях шкалы.
+pub fn encode_iq(
+    samples: &[[f32; 2]],
+    format: IqFormat,
+) -> Vec<u8> {
+    if format == IqFormat::Int16 {
+        let mut bytes = vec![0; samples.len() * format.sample_size()];
+        simd::f32_to_i16(bytemuck::cast_slice(samples), false, &mut bytes);
+        return bytes;
+    }
+
+    let mut bytes = Vec::with_capacity(samples.len() * format.sample_size());
+
+    for s in samples {
+        encode_pair(&mut bytes, *s, format);
+    }
+
+    bytes
+}
+
+fn decode_pair(
+    c: &[u8],
+    format: IqFormat,
+) -> [f32; 2] {
+    match format {
+        IqFormat::Int8 => [c[0] as i8 as f32 / 128.0, c[1] as i8 as f32 / 128.0],
+        IqFormat::Int16 => [
+            i16::from_be_bytes([c[0], c[1]]) as f32 / 32767.0,
+            i16::from_be_bytes([c[2], c[3]]) as f32 / 32767.0,
+        ],
+        IqFormat::Float32 => [
+            f32::from_be_bytes([c[0], c[1], c[2], c[3]]),
+            f32::from_be_bytes([c[4], c[5], c[6], c[7]]),
+        ],
+    }
+}
+
+fn encode_pair(
+    out: &mut Vec<u8>,
+    s: [f32; 2],
+    format: IqFormat,
+) {
+    match format {
+        IqFormat::Int8 => {
+            for v in s {
+                out.push((v * 128.0).round().clamp(-128.0, 127.0) as i8 as u8);
+            }
+        }
+        IqFormat::Int16 => {
+            for v in s {
+                let q = (v * 32767.0).round().clamp(-32768.0, 32767.0) as i16;
+                out.extend_from_slice(&q.to_be_bytes());
+            }
+        }
+        IqFormat::Float32 => {
+            for v in s {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(
+        n: usize,
+        freq: f64,
+        rate: f64,
+    ) -> Vec<[f32; 2]> {
+        (0..n)
+            .map(|i| {
+                let ph = 2.0 * std::f64::consts::PI * freq * i as f64 / rate;
+                [ph.cos() as f32, ph.sin() as f32]
+            })
+            .collect()
+    }
+
+    /// Оценка частоты тона по среднему набегу фазы между выборками.
+    fn estimate_freq(
+        s: &[[f32; 2]],
+        rate: f64,
+    ) -> f64 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+
+        for w in s.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            re += (b[0] * a[0] + b[1] * a[1]) as f64;
+            im += (b[1] * a[0] - b[0] * a[1]) as f64;
+        }
+
+        im.atan2(re) * rate / (2.0 * std::f64::consts::PI)
+    }
+
+    #[test]
+    fn test_resampler_output_length_and_frequency() {
+        let mut r = Resampler::new(2_048_000, 2_000_000, ResampleQuality::Balanced).unwrap();
+        let input = tone(20_480, 100_000.0, 2_048_000.0);
+
+        let out = r.process(&input);
+
+        // 20480 входных → 20000 выходных (минус хвост, ожидающий данных)
+        assert!((19_990..=20_000).contains(&out.len()), "len={}", out.len());
+
+        let f = estimate_freq(&out[100..], 2_000_000.0);
+        assert!((f - 100_000.0).abs() < 10.0, "freq={f}");
+
+        // Амплитуда тона в полосе пропускания сохраняется
+        let mag = (out[5_000][0].powi(2) + out[5_000][1].powi(2)).sqrt();
+        assert!((mag - 1.0).abs() < 0.01, "mag={mag}");
+    }
+
+    #[test]
+    fn test_resampler_streaming_matches_one_shot() {
+        let input = tone(10_000, 37_000.0, 1_000_000.0);
+
+        let mut one = Resampler::new(1_000_000, 1_500_000, ResampleQuality::Fast).unwrap();
+        let whole = one.process(&input);
+
+        let mut chunked = Resampler::new(1_000_000, 1_500_000, ResampleQuality::Fast).unwrap();
+        let parts: Vec<[f32; 2]> = input.chunks(777).flat_map(|c| chunked.process(c)).collect();
+
+        assert_eq!(whole, parts);
+    }
+
+    #[test]
+    fn test_resampler_bytes_and_presets() {
+        let mut r = Resampler::new(4_000, 2_000, ResampleQuality::High).unwrap();
+
+        // Постоянная составляющая проходит без изменений
+        let data: Vec<u8> = (0..4_000).flat_map(|_| [0x10, 0x00, 0xF0, 0x00]).collect();
+        let out = r.process_bytes(&data, IqFormat::Int16).unwrap();
+        let last = &out[out.len() - 4..];
+
+        assert_eq!(i16::from_be_bytes([last[0], last[1]]), 0x1000);
+        assert_eq!(i16::from_be_bytes([last[2], last[3]]), -0x1000);
+
+        assert!(r.process_bytes(&[0u8; 3], IqFormat::Int16).is_err());
+        assert!(Resampler::new(0, 1, ResampleQuality::Fast).is_err());
+        assert_eq!(
+            "HIGH".parse::<ResampleQuality>().unwrap(),
+            ResampleQuality::High
+        );
+        assert!("ultra".parse::<ResampleQuality>().is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! Миниатюра спектрограммы записи для предпросмотра.
+//!
+//! Каталогу и диалогу открытия нужно показать, что внутри записи, не читая
+//! гигабайты IQ. [`ThumbnailBuilder`] берёт с каждого блока один короткий
+//! спектр (ДПФ на [`THUMBNAIL_BINS`] точек) и на лету прореживает строки
+//! попарным усреднением, так что память не растёт с длиной записи. Итоговая
+//! [`Thumbnail`] — квантованная до 8 бит картинка, которая хранится в файле
+//! метаданных записи ([`crate::RecordingMetadata`]) и читается без открытия
+//! самого `.glos`.
+
+use std::f32::consts::PI;
+
+use glos_types::IqFormat;
+use serde::{Deserialize, Serialize};
+
+use crate::serialization::decode_sample;
+
+/// Столбцов (частотных бинов) в миниатюре.
+pub const THUMBNAIL_BINS: usize = 64;
+
+/// Наибольшее число строк (отсчётов времени) в миниатюре.
+pub const THUMBNAIL_MAX_ROWS: usize = 64;
+
+/// Квантованная спектрограмма записи.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub rows: usize,
+    pub cols: usize,
+    /// Мощность, соответствующая уровню 0 (дБ относительно полной шкалы)
+    pub db_min: f32,
+    /// Мощность, соответствующая уровню 255 (дБ относительно полной шкалы)
+    pub db_max: f32,
+    /// `rows × cols` уровней по строкам: строки по возрастанию времени,
+    /// столбцы от нижней частоты к верхней (центральная — посередине)
+    pub levels: Vec<u8>,
+}
+
+/// Накопитель миниатюры: блок за блоком, при записи или по готовому файлу.
+#[derive(Debug, Clone)]
+pub struct ThumbnailBuilder {
+    format: IqFormat,
+    little_endian: bool,
+    /// Окно Ханна
+    window: Vec<f32>,
+    /// `e^{-2πik/N}` для `k` в `0..N`
+    twiddles: Vec<[f32; 2]>,
+    /// Сумма линейной мощности и число блоков в каждой строке
+    rows: Vec<([f32; THUMBNAIL_BINS], u32)>,
+    /// Блоков в полной строке
+    blocks_per_row: u32,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Thumbnail {
+    /// Уровень пикселя 0..=255.
+    pub fn level(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Option<u8> {
+        if col >= self.cols {
+            return None;
+        }
+        self.levels.get(row * self.cols + col).copied()
+    }
+
+    /// Мощность пикселя в дБ (с точностью квантования).
+    pub fn power_db(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Option<f32> {
+        let level = self.level(row, col)?;
+        Some(self.db_min + level as f32 / 255.0 * (self.db_max - self.db_min))
+    }
+}
+
+impl ThumbnailBuilder {
+    pub fn new(
+        format: IqFormat,
+        little_endian: bool,
+    ) -> Self {
+        let n = THUMBNAIL_BINS as f32;
+        let window = (0..THUMBNAIL_BINS)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n).cos())
+            .collect();
+        let twiddles = (0..THUMBNAIL_BINS)
+            .map(|k| {
+                let phase = -2.0 * PI * k as f32 / n;
+                [phase.cos(), phase.sin()]
+            })
+            .collect();
+
+        Self {
+            format,
+            little_endian,
+            window,
+            twiddles,
+            rows: Vec::with_capacity(2 * THUMBNAIL_MAX_ROWS),
+            blocks_per_row: 1,
+        }
+    }
+
+    /// Учитывает сырые IQ байты блока: спектр первых [`THUMBNAIL_BINS`]
+    /// выборок. Более короткие блоки пропускаются.
+    pub fn push(
+        &mut self,
+        data: &[u8],
+    ) {
+        let sample_size = self.format.sample_size();
+        if data.len() < THUMBNAIL_BINS * sample_size {
+            return;
+        }
+
+        let x: Vec<[f32; 2]> = data
+            .chunks_exact(sample_size)
+            .take(THUMBNAIL_BINS)
+            .zip(&self.window)
+            .map(|(c, w)| {
+                let [i, q] = decode_sample(c, self.format, self.little_endian);
+                [i * w, q * w]
+            })
+            .collect();
+
+        // Нормировка к полной шкале: тон амплитуды 1.0 даёт 0 дБ
+        let gain: f32 = self.window.iter().sum();
+        let norm = 1.0 / (gain * gain);
+
+        if self.rows.last().is_none_or(|r| r.1 == self.blocks_per_row) {
+            if self.rows.len() == 2 * THUMBNAIL_MAX_ROWS {
+                self.merge_rows();
+            }
+            self.rows.push(([0.0; THUMBNAIL_BINS], 0));
+        }
+        let (row, count) = self.rows.last_mut().expect("row pushed above");
+
+        for k in 0..THUMBNAIL_BINS {
+            let (mut re, mut im) = (0.0f32, 0.0f32);
+            for (n, [i, q]) in x.iter().enumerate() {
+                let [c, s] = self.twiddles[(k * n) % THUMBNAIL_BINS];
+                re += i * c - q * s;
+                im += i * s + q * c;
+            }
+            // Нулевая частота — в середине строки
+            row[(k + THUMBNAIL_BINS / 2) % THUMBNAIL_BINS] += (re * re + im * im) * norm;
+        }
+        *count += 1;
+    }
+
+    /// Учтено блоков.
+    pub fn blocks(&self) -> u64 {
+        self.rows.iter().map(|r| r.1 as u64).sum()
+    }
+
+    /// Миниатюра по накопленным блокам; `None`, если блоков не было.
+    pub fn finish(&self) -> Option<Thumbnail> {
+        let mut rows = self.rows.clone();
+        while rows.len() > THUMBNAIL_MAX_ROWS {
+            rows = merge_pairs(&rows);
+        }
+
+        let db: Vec<f32> = rows
+            .iter()
+            .flat_map(|(sum, count)| sum.map(|p| 10.0 * (p / *count as f32 + 1e-20).log10()))
+            .collect();
+        if db.is_empty() {
+            return None;
+        }
+
+        let db_min = db.iter().copied().fold(f32::INFINITY, f32::min);
+        let db_max = db.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let span = (db_max - db_min).max(f32::EPSILON);
+
+        Some(Thumbnail {
+            rows: rows.len(),
+            cols: THUMBNAIL_BINS,
+            db_min,
+            db_max,
+            levels: db
+                .iter()
+                .map(|v| ((v - db_min) / span * 255.0).round() as u8)
+                .collect(),
+        })
+    }
+
+    /// Вдвое уменьшает число строк, вдвое увеличивая число блоков в строке.
+    fn merge_rows(&mut self) {
+        self.rows = merge_pairs(&self.rows);
+        self.blocks_per_row *= 2;
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Складывает соседние строки; нечётная последняя остаётся как есть.
+fn merge_pairs(rows: &[([f32; THUMBNAIL_BINS], u32)]) -> Vec<([f32; THUMBNAIL_BINS], u32)> {
+    rows.chunks(2)
+        .map(|pair| {
+            pair.iter()
+                .fold(([0.0; THUMBNAIL_BINS], 0), |(mut acc, n), (row, count)| {
+                    acc.iter_mut().zip(row).for_each(|(a, p)| *a += p);
+                    (acc, n + count)
+                })
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RecordingMetadata;
+
+    /// Блок Int16 big-endian с комплексным тоном в бине `bin` от центра.
+    fn tone_block(
+        bin: i32,
+        samples: usize,
+    ) -> Vec<u8> {
+        (0..samples)
+            .flat_map(|n| {
+                let phase = 2.0 * PI * bin as f32 * n as f32 / THUMBNAIL_BINS as f32;
+                let i = (phase.cos() * 16_000.0) as i16;
+                let q = (phase.sin() * 16_000.0) as i16;
+                [i.to_be_bytes(), q.to_be_bytes()].concat()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_thumbnail_tone_column_and_row_decimation() {
+        let mut builder = ThumbnailBuilder::new(IqFormat::Int16, false);
+        assert_eq!(builder.finish(), None);
+
+        // Короткий блок не даёт спектра
+        builder.push(&tone_block(5, THUMBNAIL_BINS - 1));
+        assert_eq!(builder.blocks(), 0);
+
+        for _ in 0..1000 {
+            builder.push(&tone_block(5, 1000));
+        }
+        assert_eq!(builder.blocks(), 1000);
+
+        let thumb = builder.finish().unwrap();
+        assert!(thumb.rows <= THUMBNAIL_MAX_ROWS && thumb.rows > THUMBNAIL_MAX_ROWS / 2);
+        assert_eq!(thumb.levels.len(), thumb.rows * thumb.cols);
+
+        // Тон — в столбце центр + 5, около -6 дБ от полной шкалы
+        let peak = THUMBNAIL_BINS / 2 + 5;
+        for row in 0..thumb.rows {
+            assert_eq!(thumb.level(row, peak), Some(255));
+        }
+        assert!((thumb.db_max + 6.2).abs() < 0.5, "{}", thumb.db_max);
+        assert!(thumb.power_db(0, peak - 10).unwrap() < thumb.db_max - 40.0);
+        assert_eq!(thumb.level(0, THUMBNAIL_BINS), None);
+    }
+
+    #[test]
+    fn test_thumbnail_stored_in_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let recording = dir.path().join("rec.glos");
+
+        let mut builder = ThumbnailBuilder::new(IqFormat::Int16, false);
+        builder.push(&tone_block(-3, 128));
+        let thumb = builder.finish().unwrap();
+
+        let meta = RecordingMetadata {
+            thumbnail: Some(thumb.clone()),
+            ..Default::default()
+        };
+        assert!(!meta.is_empty());
+        meta.save(&recording).unwrap();
+
+        assert_eq!(
+            RecordingMetadata::load_thumbnail(&recording).unwrap(),
+            Some(thumb)
+        );
+        assert_eq!(
+            RecordingMetadata::load_thumbnail(&dir.path().join("none.glos")).unwrap(),
+            None
+        );
+    }
+}
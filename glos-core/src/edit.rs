@@ -0,0 +1,819 @@
+//! Правка записей: поля заголовка на месте и вырезка фрагмента.
+//!
+//! Исправить неверный `gain_db` или `center_freq` в многогигабайтной записи
+//! копированием долго и требует второго места на диске. [`patch_header`]
+//! переписывает только 128 байт заголовка (с пересчётом CRC); блоки и
+//! зарезервированные байты заголовка не трогаются.
+//!
+//! [`cut_range`] копирует в новый файл интервал времени записи с точностью
+//! до выборки — чтобы поделиться несколькими секундами вместо всей сессии.
+//!
+//! [`shard`] делит запись на N шардов по границам блоков для обработки на
+//! кластере; рядом кладётся манифест ([`ShardManifest`]), по которому
+//! [`unshard`] собирает исходный файл байт в байт.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glos_types::{GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ops::part_path, GlosHeaderExt, GlosReader, GlosWriter, RawBlockReader, RecordingMetadata,
+    SessionDigest, GLOS_HEADER_CRC_OFFSET, GLOS_HEADER_SIZE,
+};
+
+/// Версия формата манифеста шардов.
+pub const SHARD_MANIFEST_VERSION: u32 = 1;
+
+/// Новые значения полей заголовка; `None` — оставить как есть.
+///
+/// `sample_rate`, `iq_format` и `flags` меняют толкование данных блоков
+/// (время выборок, размер выборки, порядок I/Q) — без `force` такие правки
+/// отклоняются. Сжатие не меняется никогда: блоки остались бы нечитаемыми.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderChanges {
+    pub sdr_type: Option<SdrType>,
+    pub center_freq: Option<u64>,
+    pub gain_db: Option<f32>,
+    pub timestamp_start: Option<u64>,
+    pub timestamp_end: Option<u64>,
+    pub total_samples: Option<u64>,
+    pub sample_rate: Option<u32>,
+    pub iq_format: Option<IqFormat>,
+    pub flags: Option<u8>,
+    /// Разрешить опасные правки
+    pub force: bool,
+}
+
+/// Заголовок до и после правки.
+#[derive(Debug, Clone)]
+pub struct HeaderPatch {
+    pub before: GlosHeader,
+    pub after: GlosHeader,
+    /// `false`, если значения совпали и файл не записывался
+    pub written: bool,
+}
+
+/// Итог [`cut_range`].
+#[derive(Debug, Clone)]
+pub struct CutRange {
+    /// Заголовок нового файла (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    pub samples: u64,
+    /// Метка первой выборки и конец последней (нс) — фактический интервал,
+    /// он уже запрошенного, если запись начинается позже или кончается
+    /// раньше
+    pub start_ns: u64,
+    pub end_ns: u64,
+    /// Пропущено блоков с неверной CRC
+    pub corrupted_blocks: u64,
+    /// Файл перенесённых метаданных (None — у записи их нет)
+    pub metadata: Option<PathBuf>,
+}
+
+/// Как делить запись в [`shard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardBy {
+    /// Не больше `n` шардов с примерно равным числом выборок
+    Parts(usize),
+    /// Шарды длительностью около заданной (по меткам блоков; шард может
+    /// быть длиннее на блок)
+    Duration(Duration),
+}
+
+/// Манифест шардов: всё, что нужно [`unshard`] для сборки исходной записи.
+///
+/// Лежит рядом с записью в JSON: `rec.glos` → `rec.glos.shards.json`;
+/// шарды — там же (`rec_000.glos`, `rec_001.glos`, …).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardManifest {
+    pub version: u32,
+    /// Имя исходного файла
+    pub source: String,
+    /// Исходный заголовок (128 байт в hex) — при сборке пишется как был
+    pub header: String,
+    /// Дайджест сессии всех блоков по хранимым (сжатым) данным
+    pub digest: u64,
+    /// Пропущено блоков с неверной CRC; если не 0, сборка уже не побайтовая
+    #[serde(default)]
+    pub skipped_blocks: u64,
+    pub shards: Vec<ShardEntry>,
+}
+
+/// Один шард в манифесте.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardEntry {
+    /// Имя файла шарда (в каталоге манифеста)
+    pub file: String,
+    pub blocks: u64,
+    pub samples: u64,
+    /// Метка первой выборки и конец последней (нс)
+    pub start_ns: u64,
+    pub end_ns: u64,
+    /// Дайджест сессии блоков шарда
+    pub digest: u64,
+}
+
+/// Открытый шард в [`shard`].
+struct ShardWriter {
+    writer: GlosWriter<File>,
+    entry: ShardEntry,
+    digest: SessionDigest,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl HeaderChanges {
+    /// Применяет правки к `header` с проверкой значений и опасных полей.
+    pub fn apply(
+        &self,
+        header: &GlosHeader,
+    ) -> GlosResult<GlosHeader> {
+        let dangerous = self.dangerous_fields(header);
+        if !dangerous.is_empty() && !self.force {
+            return Err(GlosError::format_violation(format!(
+                "changing {} reinterprets block data; use force to apply",
+                dangerous.join(", ")
+            )));
+        }
+
+        if self.sample_rate == Some(0) {
+            return Err(GlosError::format_violation("sample_rate must be > 0"));
+        }
+        if self.gain_db.is_some_and(|g| !g.is_finite()) {
+            return Err(GlosError::format_violation("gain_db must be finite"));
+        }
+
+        let mut after = header.clone();
+        if let Some(v) = self.sdr_type {
+            after.sdr_type = v;
+        }
+        if let Some(v) = self.center_freq {
+            after.center_freq = v;
+        }
+        if let Some(v) = self.gain_db {
+            after.gain_db = v;
+        }
+        if let Some(v) = self.timestamp_start {
+            after.timestamp_start = v;
+        }
+        if let Some(v) = self.timestamp_end {
+            after.timestamp_end = v;
+        }
+        if let Some(v) = self.total_samples {
+            after.total_samples = v;
+        }
+        if let Some(v) = self.sample_rate {
+            after.sample_rate = v;
+        }
+        if let Some(v) = self.iq_format {
+            after.iq_format = v;
+        }
+        if let Some(v) = self.flags {
+            after.flags = v;
+        }
+
+        Ok(after)
+    }
+
+    /// Опасные поля, значения которых действительно меняются.
+    pub fn dangerous_fields(
+        &self,
+        header: &GlosHeader,
+    ) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+
+        if self.sample_rate.is_some_and(|v| v != header.sample_rate) {
+            fields.push("sample_rate");
+        }
+        if self.iq_format.is_some_and(|v| v != header.iq_format) {
+            fields.push("iq_format");
+        }
+        if self.flags.is_some_and(|v| v != header.flags) {
+            fields.push("flags");
+        }
+
+        fields
+    }
+}
+
+impl ShardManifest {
+    /// Путь манифеста записи: `rec.glos` → `rec.glos.shards.json`.
+    pub fn path(recording: &Path) -> PathBuf {
+        let mut name = recording.as_os_str().to_owned();
+        name.push(".shards.json");
+        PathBuf::from(name)
+    }
+
+    pub fn load(path: &Path) -> GlosResult<Self> {
+        let manifest: Self = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|e| GlosError::corrupted(format!("{}: {e}", path.display())))?;
+
+        if manifest.version > SHARD_MANIFEST_VERSION {
+            return Err(GlosError::format_violation(format!(
+                "{}: unsupported shard manifest version {}",
+                path.display(),
+                manifest.version
+            )));
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn save(
+        &self,
+        path: &Path,
+    ) -> GlosResult<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+
+        serde_json::to_writer_pretty(&mut w, self)
+            .map_err(|e| GlosError::format_violation(e.to_string()))?;
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Байты исходного заголовка.
+    pub fn header_bytes(&self) -> GlosResult<[u8; GLOS_HEADER_SIZE]> {
+        let bad = || GlosError::corrupted("shard manifest: header is not 128 hex bytes");
+
+        let hex = self.header.as_bytes();
+        if hex.len() != GLOS_HEADER_SIZE * 2 {
+            return Err(bad());
+        }
+
+        let mut buf = [0u8; GLOS_HEADER_SIZE];
+        for (b, pair) in buf.iter_mut().zip(hex.chunks_exact(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| bad())?;
+            *b = u8::from_str_radix(pair, 16).map_err(|_| bad())?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Выборок во всех шардах.
+    pub fn total_samples(&self) -> u64 {
+        self.shards.iter().map(|s| s.samples).sum()
+    }
+}
+
+impl ShardWriter {
+    fn create(
+        path: &Path,
+        header: &GlosHeader,
+        first: &IqBlock,
+    ) -> GlosResult<Self> {
+        let mut header = header.clone();
+        header.timestamp_start = first.timestamp_ns / 1_000_000_000;
+
+        Ok(Self {
+            writer: GlosWriter::new(File::create(path)?, header)?,
+            entry: ShardEntry {
+                file: file_name(path),
+                blocks: 0,
+                samples: 0,
+                start_ns: first.timestamp_ns,
+                end_ns: first.timestamp_ns,
+                digest: 0,
+            },
+            digest: SessionDigest::new(),
+        })
+    }
+
+    fn finish(mut self) -> GlosResult<ShardEntry> {
+        self.entry.digest = self.digest.value();
+        self.writer.finish_at(self.entry.end_ns / 1_000_000_000)?;
+
+        Ok(self.entry)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl From<usize> for ShardBy {
+    fn from(n: usize) -> Self {
+        Self::Parts(n)
+    }
+}
+
+impl From<Duration> for ShardBy {
+    fn from(d: Duration) -> Self {
+        Self::Duration(d)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Переписывает заголовок файла `path` с правками `changes`.
+///
+/// Файл не должен быть открыт на запись: писатель перезапишет заголовок
+/// при финализации. Заголовок с неверным CRC не правится.
+pub fn patch_header<P: AsRef<Path>>(
+    path: P,
+    changes: &HeaderChanges,
+) -> GlosResult<HeaderPatch> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+    let mut buf = [0u8; GLOS_HEADER_SIZE];
+    file.read_exact(&mut buf)?;
+
+    let before = GlosHeader::deserialize(&buf)?;
+    let after = changes.apply(&before)?;
+
+    let patched = after.serialize()?;
+    // Поля и CRC; зарезервированный хвост остаётся как был
+    let end = GLOS_HEADER_CRC_OFFSET + 4;
+    let written = patched[..end] != buf[..end];
+
+    if written {
+        buf[..end].copy_from_slice(&patched[..end]);
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&buf)?;
+        file.sync_data()?;
+    }
+
+    Ok(HeaderPatch {
+        before,
+        after,
+        written,
+    })
+}
+
+/// Копирует из `input` в новый файл `output` выборки с метками в
+/// `[start_ns, end_ns)`.
+///
+/// Блоки берутся из [`GlosReader::blocks_between`]: начало ищется по
+/// префиксам блоков без чтения данных, крайние блоки обрезаются до выборки.
+/// Заголовок — от исходной записи с новыми временем начала, окончания и
+/// числом выборок; сжатие то же. Метаданные записи
+/// ([`RecordingMetadata`]) переносятся рядом с `output`: радиочасть и дрейф
+/// часов — как есть, журнал сессии — только события интервала, миниатюра
+/// (она описывает всю запись) — нет.
+pub fn cut_range<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    start_ns: u64,
+    end_ns: u64,
+) -> GlosResult<CutRange> {
+    let (input, output) = (input.as_ref(), output.as_ref());
+    if end_ns <= start_ns {
+        return Err(GlosError::format_violation(format!(
+            "empty range {start_ns}..{end_ns}"
+        )));
+    }
+
+    let mut reader = GlosReader::new(File::open(input)?)?;
+    let header = reader.header().clone();
+    let rate = header.sample_rate.max(1) as u128;
+    let samples_ns = |n: u64| (n as u128 * 1_000_000_000 / rate) as u64;
+
+    let mut writer: Option<GlosWriter<File>> = None;
+    let mut cut = CutRange {
+        header: header.clone(),
+        blocks: 0,
+        samples: 0,
+        start_ns: 0,
+        end_ns: 0,
+        corrupted_blocks: 0,
+        metadata: None,
+    };
+
+    for result in reader.blocks_between(start_ns, end_ns)?.trimmed() {
+        let block = match result {
+            Ok(b) => b,
+            Err(GlosError::CrcMismatch { .. }) => {
+                cut.corrupted_blocks += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let count = block.sample_count as u64;
+
+        // Файл создаётся на первой выборке интервала: её время — начало
+        if writer.is_none() {
+            let mut first = header.clone();
+            first.timestamp_start = block.timestamp_ns / 1_000_000_000;
+            cut.start_ns = block.timestamp_ns;
+            writer = Some(GlosWriter::new(File::create(output)?, first)?);
+        }
+
+        cut.blocks += 1;
+        cut.samples += count;
+        cut.end_ns = block.timestamp_ns + samples_ns(count);
+
+        if let Some(w) = writer.as_mut() {
+            w.write_block(block)?;
+        }
+    }
+
+    let Some(writer) = writer else {
+        return Err(GlosError::format_violation(format!(
+            "no samples in range {start_ns}..{end_ns}"
+        )));
+    };
+    cut.header = writer.header().clone();
+    cut.header.total_samples = cut.samples;
+    cut.header.timestamp_end = cut.end_ns / 1_000_000_000;
+    writer.finish_at(cut.header.timestamp_end)?;
+
+    if let Some(mut meta) = RecordingMetadata::load(input)? {
+        meta.thumbnail = None;
+        meta.session_log
+            .retain(|e| (cut.start_ns..cut.end_ns).contains(&e.timestamp_ns));
+        if !meta.is_empty() {
+            cut.metadata = Some(meta.save(output)?);
+        }
+    }
+
+    Ok(cut)
+}
+
+/// Делит `input` на шарды по границам блоков: `shard(path, 8)` — не больше
+/// восьми шардов поровну по выборкам, `shard(path, Duration::from_secs(60))`
+/// — по минуте.
+///
+/// Каждый шард — полноценный .glos с заголовком исходной записи и своими
+/// временем начала, окончания и числом выборок. Блоки копируются как
+/// хранятся, без пересжатия. Манифест сохраняется в
+/// [`ShardManifest::path`] и возвращается. Блоки с неверной CRC
+/// пропускаются и считаются в [`ShardManifest::skipped_blocks`].
+pub fn shard<P: AsRef<Path>>(
+    input: P,
+    by: impl Into<ShardBy>,
+) -> GlosResult<ShardManifest> {
+    let input = input.as_ref();
+    let by = by.into();
+
+    let mut raw_header = [0u8; GLOS_HEADER_SIZE];
+    File::open(input)?.read_exact(&mut raw_header)?;
+
+    let mut reader = RawBlockReader::new(File::open(input)?)?;
+    let header = reader.header().clone();
+    let rate = header.sample_rate.max(1) as u128;
+
+    let total = match by {
+        ShardBy::Parts(0) => {
+            return Err(GlosError::format_violation("shard count must be > 0"));
+        }
+        ShardBy::Duration(d) if d.is_zero() => {
+            return Err(GlosError::format_violation("shard duration must be > 0"));
+        }
+        // Заголовок оборванной записи может заявлять 0 выборок
+        ShardBy::Parts(_) if header.total_samples == 0 => count_samples(input)?,
+        ShardBy::Parts(_) => header.total_samples,
+        ShardBy::Duration(_) => 0,
+    };
+
+    let mut manifest = ShardManifest {
+        version: SHARD_MANIFEST_VERSION,
+        source: file_name(input),
+        header: raw_header.iter().map(|b| format!("{b:02x}")).collect(),
+        digest: 0,
+        skipped_blocks: 0,
+        shards: Vec::new(),
+    };
+    let mut digest = SessionDigest::new();
+    let mut current: Option<ShardWriter> = None;
+    let mut samples_before = 0u64;
+
+    while let Some(result) = reader.next_block() {
+        let block = match result {
+            Ok((_, b)) => b,
+            Err(GlosError::CrcMismatch { .. }) => {
+                manifest.skipped_blocks += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let index = manifest.shards.len();
+        let starts_new = |s: &mut ShardWriter| match by {
+            // Номер шарда по доле выборок до блока; не больше n - 1, даже
+            // если заголовок занижает total_samples
+            ShardBy::Parts(n) => {
+                let share = samples_before as u128 * n as u128 / total.max(1) as u128;
+                share.min(n as u128 - 1) > index as u128
+            }
+            ShardBy::Duration(d) => {
+                block.timestamp_ns >= s.entry.start_ns.saturating_add(d.as_nanos() as u64)
+            }
+        };
+        if let Some(done) = current.take_if(starts_new) {
+            manifest.shards.push(done.finish()?);
+        }
+
+        let open = match current.as_mut() {
+            Some(s) => s,
+            None => {
+                let path = part_path(input, manifest.shards.len());
+                current.insert(ShardWriter::create(&path, &header, &block)?)
+            }
+        };
+
+        let count = block.sample_count as u64;
+        digest.push_block(&block);
+        open.digest.push_block(&block);
+        open.entry.blocks += 1;
+        open.entry.samples += count;
+        open.entry.end_ns = block.timestamp_ns + (count as u128 * 1_000_000_000 / rate) as u64;
+        open.writer.write_block(block)?;
+
+        samples_before += count;
+    }
+
+    match current {
+        Some(s) => manifest.shards.push(s.finish()?),
+        None => {
+            return Err(GlosError::format_violation(format!(
+                "{input:?} has no blocks to shard"
+            )))
+        }
+    }
+
+    manifest.digest = digest.value();
+    manifest.save(&ShardManifest::path(input))?;
+
+    Ok(manifest)
+}
+
+/// Собирает запись из шардов по манифесту `manifest` в `output`.
+///
+/// Блоки сверяются с дайджестами манифеста; несовпадение, пропавший или
+/// лишний блок — ошибка. Если при делении не было пропущенных блоков,
+/// заголовок пишется исходный и файл совпадает с исходным байт в байт.
+/// Возвращает заголовок собранного файла.
+pub fn unshard<P: AsRef<Path>, Q: AsRef<Path>>(
+    manifest: P,
+    output: Q,
+) -> GlosResult<GlosHeader> {
+    let (manifest_path, output) = (manifest.as_ref(), output.as_ref());
+    let manifest = ShardManifest::load(manifest_path)?;
+    let raw_header = manifest.header_bytes()?;
+    let header = GlosHeader::deserialize(&raw_header)?;
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+    let mut writer = GlosWriter::new(File::create(output)?, header.clone())?;
+    let mut digest = SessionDigest::new();
+
+    for entry in &manifest.shards {
+        let mut reader = RawBlockReader::new(File::open(dir.join(&entry.file))?)?;
+        if reader.header().compression != header.compression
+            || reader.header().iq_format != header.iq_format
+        {
+            return Err(GlosError::format_violation(format!(
+                "shard {}: format differs from the source",
+                entry.file
+            )));
+        }
+
+        let mut shard_digest = SessionDigest::new();
+        while let Some(result) = reader.next_block() {
+            let (_, block) = result?;
+            shard_digest.push_block(&block);
+            digest.push_block(&block);
+            writer.write_block(block)?;
+        }
+
+        if shard_digest.value() != entry.digest
+            || shard_digest.blocks() != entry.blocks
+            || shard_digest.samples() != entry.samples
+        {
+            return Err(GlosError::corrupted(format!(
+                "shard {}: blocks do not match the manifest",
+                entry.file
+            )));
+        }
+    }
+
+    if digest.value() != manifest.digest {
+        return Err(GlosError::corrupted(
+            "shards do not match the manifest digest",
+        ));
+    }
+
+    let mut assembled = header;
+    assembled.total_samples = writer.total_samples();
+    writer.finish_at(assembled.timestamp_end)?;
+
+    if manifest.skipped_blocks == 0 {
+        let mut file = OpenOptions::new().write(true).open(output)?;
+        file.write_all(&raw_header)?;
+        assembled = GlosHeader::deserialize(&raw_header)?;
+    }
+
+    Ok(assembled)
+}
+
+/// Выборок во всех целых блоках записи (без распаковки данных).
+fn count_samples(input: &Path) -> GlosResult<u64> {
+    let mut reader = RawBlockReader::new(File::open(input)?)?;
+    let mut total = 0;
+
+    while let Some(result) = reader.next_block() {
+        match result {
+            Ok((_, block)) => total += block.sample_count as u64,
+            Err(GlosError::CrcMismatch { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(total)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{read_all_blocks, IqBlockExt};
+
+    fn write_file() -> tempfile::NamedTempFile {
+        let header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        let mut writer = GlosWriter::new(file.reopen().unwrap(), header).unwrap();
+        for i in 0..3u64 {
+            writer
+                .write_block(IqBlock::new(i * 500_000, 1_000, vec![i as u8; 4_000]))
+                .unwrap();
+        }
+        writer.finish_at(1_700_000_000).unwrap();
+
+        file
+    }
+
+    #[test]
+    fn test_patch_header_rewrites_only_header() {
+        let file = write_file();
+        let original = std::fs::read(file.path()).unwrap();
+
+        let changes = HeaderChanges {
+            center_freq: Some(1_575_420_000),
+            gain_db: Some(32.5),
+            ..Default::default()
+        };
+        let patch = patch_header(file.path(), &changes).unwrap();
+
+        assert!(patch.written);
+        assert_eq!(patch.before.center_freq, 1_602_000_000);
+
+        let patched = std::fs::read(file.path()).unwrap();
+        assert_eq!(patched.len(), original.len());
+        assert_eq!(patched[GLOS_HEADER_SIZE..], original[GLOS_HEADER_SIZE..]);
+
+        let mut reader = GlosReader::new(std::fs::File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().center_freq, 1_575_420_000);
+        assert_eq!(reader.header().gain_db, 32.5);
+        assert_eq!(reader.header().total_samples, 3_000);
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 3);
+
+        // Повторная правка теми же значениями файл не трогает
+        assert!(!patch_header(file.path(), &changes).unwrap().written);
+    }
+
+    #[test]
+    fn test_patch_header_dangerous_changes_need_force() {
+        let file = write_file();
+        let original = std::fs::read(file.path()).unwrap();
+
+        let mut changes = HeaderChanges {
+            sample_rate: Some(4_000_000),
+            gain_db: Some(10.0),
+            ..Default::default()
+        };
+        let err = patch_header(file.path(), &changes).unwrap_err();
+        assert!(err.to_string().contains("sample_rate"), "{err}");
+        assert_eq!(std::fs::read(file.path()).unwrap(), original);
+
+        // Прежнее значение опасным не считается
+        let same = HeaderChanges {
+            sample_rate: Some(2_000_000),
+            ..Default::default()
+        };
+        assert!(!patch_header(file.path(), &same).unwrap().written);
+
+        changes.force = true;
+        let patch = patch_header(file.path(), &changes).unwrap();
+        assert_eq!(patch.after.sample_rate, 4_000_000);
+
+        let invalid = HeaderChanges {
+            sample_rate: Some(0),
+            force: true,
+            ..Default::default()
+        };
+        assert!(patch_header(file.path(), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_cut_range_trims_edge_blocks() {
+        let file = write_file();
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("cut.glos");
+
+        let log = |timestamp_ns| crate::LogEntry {
+            timestamp_ns,
+            level: "WARN".to_string(),
+            message: "overflow".to_string(),
+        };
+        let meta = RecordingMetadata {
+            session_log: vec![log(100_000), log(600_000)],
+            ..Default::default()
+        };
+        meta.save(file.path()).unwrap();
+
+        // Блоки по 1000 выборок (500 мкс): вторая половина первого, второй
+        // целиком и первая половина третьего
+        let cut = cut_range(file.path(), &output, 250_000, 1_250_000).unwrap();
+        assert_eq!(cut.samples, 2_000);
+        assert_eq!((cut.start_ns, cut.end_ns), (250_000, 1_250_000));
+
+        let mut reader = GlosReader::new(std::fs::File::open(&output).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 2_000);
+        assert_eq!(reader.header().center_freq, 1_602_000_000);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let spans: Vec<_> = blocks
+            .iter()
+            .map(|b| (b.timestamp_ns, b.sample_count, b.data[0]))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(250_000, 500, 0), (500_000, 1_000, 1), (1_000_000, 500, 2)]
+        );
+
+        let copied = RecordingMetadata::load(&output).unwrap().unwrap();
+        assert_eq!(copied.session_log, vec![log(600_000)]);
+
+        assert!(cut_range(file.path(), &output, 2_000_000, 3_000_000).is_err());
+        assert!(cut_range(file.path(), &output, 500, 500).is_err());
+    }
+
+    #[test]
+    fn test_shard_and_unshard_lossless() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("rec.glos");
+        std::fs::copy(write_file().path(), &input).unwrap();
+
+        // 3 блока по 1000 выборок на 2 шарда: [0, 1], [2]
+        let manifest = shard(&input, 2).unwrap();
+        let sizes: Vec<_> = manifest.shards.iter().map(|s| s.blocks).collect();
+        assert_eq!(sizes, vec![2, 1]);
+        assert_eq!(manifest.shards[1].file, "rec_001.glos");
+        assert_eq!(manifest.shards[1].start_ns, 1_000_000);
+        assert_eq!(manifest.total_samples(), 3_000);
+
+        let mut reader =
+            GlosReader::new(File::open(dir.path().join("rec_001.glos")).unwrap()).unwrap();
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 1);
+        reader.validate_totals().unwrap();
+
+        let manifest_path = ShardManifest::path(&input);
+        assert_eq!(ShardManifest::load(&manifest_path).unwrap(), manifest);
+
+        let output = dir.path().join("joined.glos");
+        let header = unshard(&manifest_path, &output).unwrap();
+        assert_eq!(header.total_samples, 3_000);
+        assert_eq!(
+            std::fs::read(&output).unwrap(),
+            std::fs::read(&input).unwrap()
+        );
+
+        // По длительности: новый шард с метки ≥ начало + 0.5 мс
+        let manifest = shard(&input, Duration::from_micros(500)).unwrap();
+        assert_eq!(manifest.shards.len(), 3);
+
+        // Подменённый шард (целый, но с другими данными) не собирается
+        let shard_path = dir.path().join("rec_002.glos");
+        let header = GlosReader::new(File::open(&shard_path).unwrap())
+            .unwrap()
+            .header()
+            .clone();
+        let mut writer = GlosWriter::new(File::create(&shard_path).unwrap(), header).unwrap();
+        writer
+            .write_block(IqBlock::new(1_000_000, 1_000, vec![9; 4_000]))
+            .unwrap();
+        writer.finish_at(0).unwrap();
+        assert!(unshard(&manifest_path, &output).is_err());
+
+        assert!(shard(&input, 0).is_err());
+    }
+}
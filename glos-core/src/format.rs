@@ -5,9 +5,15 @@
 //! последовательность).
 
 use crc32fast::Hasher;
-use glos_types::{Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType};
+use glos_types::{
+    BlockAttributes, Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType,
+    SyncMarker, TimeSource,
+};
 
-use crate::{read_u32_local, read_u64_local, write_u32_local, write_u64_local};
+use crate::{
+    binary::{read_u32_local, read_u64_local, write_u32_local, write_u64_local},
+    serialization::current_unix_secs,
+};
 
 /// Магическое число для идентификации GLOS файлов: b"GLOS"
 pub const GLOS_MAGIC: [u8; 4] = [b'G', b'L', b'O', b'S'];
@@ -15,15 +21,90 @@ pub const GLOS_MAGIC: [u8; 4] = [b'G', b'L', b'O', b'S'];
 /// Текущая версия формата
 pub const GLOS_VERSION: u8 = 1;
 
+/// Новейшая версия формата, которую читатель разбирает полностью
+pub const GLOS_MAX_VERSION: u8 = 2;
+
+/// Версия формата, с которой блоки могут нести атрибуты (см.
+/// [`GLOS_BLOCK_HAS_ATTRIBUTES`])
+pub const GLOS_BLOCK_ATTRIBUTES_VERSION: u8 = 2;
+
 /// Размер фиксированного заголовка (128 байт)
 pub const GLOS_HEADER_SIZE: usize = 128;
 
+/// Смещение CRC32 заголовка; CRC покрывает байты `[0..GLOS_HEADER_CRC_OFFSET)`
+pub const GLOS_HEADER_CRC_OFFSET: usize = 72;
+
+/// Накладные расходы блока: размер, число выборок, метка и CRC (байт)
+pub const GLOS_BLOCK_OVERHEAD: usize = 20;
+
+/// Бит 31 поля `sample_count` кадра: между меткой времени и данными
+/// лежит поле атрибутов размером [`GLOS_BLOCK_ATTRIBUTES_SIZE`]
+pub const GLOS_BLOCK_HAS_ATTRIBUTES: u32 = 0x8000_0000;
+
+/// Размер поля атрибутов блока: флаги, усиление и потерянные выборки
+pub const GLOS_BLOCK_ATTRIBUTES_SIZE: usize = 12;
+
+/// Флаг атрибутов блока: АЦП в насыщении
+pub const GLOS_BLOCK_FLAG_OVERLOAD: u32 = 0x01;
+
+/// Флаг атрибутов блока: усиление сменилось, новое значение в поле `gain_db`
+pub const GLOS_BLOCK_FLAG_GAIN_CHANGED: u32 = 0x02;
+
+/// Флаг атрибутов блока: перед блоком потеряны выборки, их число в поле
+/// `dropped_samples`
+pub const GLOS_BLOCK_FLAG_SAMPLES_DROPPED: u32 = 0x04;
+
+/// Флаг атрибутов блока: блок — метка синхронизации. Выборок в нём нет,
+/// номер метки лежит в битах 8..31 флагов, а время по внешним часам —
+/// вместо полей `gain_db` и `dropped_samples` (u64, старшая половина
+/// первой)
+pub const GLOS_BLOCK_FLAG_SYNC_MARKER: u32 = 0x08;
+
+/// Наибольший номер метки синхронизации: под него отведено 24 бита флагов
+pub const GLOS_SYNC_MARKER_ID_MAX: u32 = 0x00FF_FFFF;
+
+/// Сдвиг номера метки синхронизации во флагах атрибутов
+const SYNC_MARKER_ID_SHIFT: u32 = 8;
+
 /// Минимальный размер блока IQ данных
 pub const GLOS_MIN_BLOCK_SIZE: usize = 32;
 
 /// Максимальный размер блока IQ данных (1 МБ)
 pub const GLOS_MAX_BLOCK_SIZE: usize = 1024 * 1024;
 
+/// Флаг заголовка: числа в little-endian
+pub const GLOS_FLAG_LITTLE_ENDIAN: u8 = 0x01;
+
+/// Флаг заголовка: границы блоков выбраны по содержимому (см.
+/// [`crate::ContentChunker`]), а не фиксированным числом выборок
+pub const GLOS_FLAG_CONTENT_DEFINED: u8 = 0x02;
+
+/// Флаг заголовка: при записи I и Q поменяны местами (см. [`crate::dsp`])
+pub const GLOS_FLAG_IQ_SWAPPED: u8 = 0x04;
+
+/// Флаг заголовка: при записи спектр инвертирован (Q → -Q)
+pub const GLOS_FLAG_SPECTRUM_INVERTED: u8 = 0x08;
+
+/// Флаг заголовка: многобайтовые IQ-компоненты в little-endian при
+/// big-endian полях заголовка. Так пишутся выборки, которые устройство
+/// отдаёт в little-endian, без перестановки байт на лету
+pub const GLOS_FLAG_SAMPLES_LITTLE_ENDIAN: u8 = 0x10;
+
+/// Уровень Zstd по умолчанию
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// Допустимые уровни Zstd: выше 9 выигрыш на IQ данных мал, а скорость
+/// падает в разы
+pub const ZSTD_LEVELS: std::ops::RangeInclusive<i32> = 1..=9;
+
+/// Предельная степень сжатия LZ4 (один байт токена на 255 байт совпадения)
+const LZ4_MAX_RATIO: usize = 255;
+
+/// Магическое число кадра Zstd (little-endian). Префикс размера LZ4 с таким
+/// значением означал бы ~4 ГБ данных, что больше любого допустимого блока,
+/// поэтому сжатые данные различаются без сведений о заголовке.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
 pub trait GlosHeaderExt {
     /// Создание нового заголовка с настройками по умолчанию.
     fn new(
@@ -37,9 +118,21 @@ pub trait GlosHeaderExt {
     fn serialize(&self) -> GlosResult<[u8; GLOS_HEADER_SIZE]>;
     /// Десериализация заголовка из 128 байт
     fn deserialize(buf: &[u8; GLOS_HEADER_SIZE]) -> GlosResult<Self>
+    where
+        Self: Sized;
+    /// Как [`deserialize`](Self::deserialize), но принимает и более новые
+    /// версии формата: читаются только поля v1 на их местах (новые поля
+    /// v2+ по спецификации лежат в зарезервированных байтах и
+    /// игнорируются). `version` в результате — как в файле.
+    fn deserialize_best_effort(buf: &[u8; GLOS_HEADER_SIZE]) -> GlosResult<Self>
     where
         Self: Sized;
     fn is_little_endian(&self) -> bool;
+    /// IQ-компоненты блоков в little-endian: бит 0 (весь файл в
+    /// little-endian) или [`GLOS_FLAG_SAMPLES_LITTLE_ENDIAN`].
+    fn samples_little_endian(&self) -> bool;
+    /// Блоки записаны с контентно-зависимыми границами.
+    fn is_content_defined(&self) -> bool;
 }
 
 pub trait IqBlockExt {
@@ -49,6 +142,14 @@ pub trait IqBlockExt {
         sample_count: u32,
         data: Vec<u8>,
     ) -> Self
+    where
+        Self: Sized;
+    /// Создаёт пустой блок — метку синхронизации `marker` в момент
+    /// `timestamp_ns`.
+    fn new_sync_marker(
+        timestamp_ns: u64,
+        marker: SyncMarker,
+    ) -> Self
     where
         Self: Sized;
     /// Создаёт блок с предварительно сжатыми данными.
@@ -61,6 +162,13 @@ pub trait IqBlockExt {
         Self: Sized;
     /// Сжимает данные блока с помощью LZ4.
     fn compress(&mut self) -> GlosResult<()>;
+    /// Сжимает данные блока алгоритмом `compression`; `level` учитывается
+    /// только для Zstd (см. [`ZSTD_LEVELS`]).
+    fn compress_with(
+        &mut self,
+        compression: Compression,
+        level: i32,
+    ) -> GlosResult<()>;
     /// Распаковать данные блока (если сжаты)
     fn decompress(&mut self) -> GlosResult<()>;
     /// Проверяет соответствие `sample_count * iq_format.sample_size() ==
@@ -69,6 +177,9 @@ pub trait IqBlockExt {
         &self,
         iq_format: IqFormat,
     ) -> GlosResult<()>;
+    /// Размер кадра блока в файле: данные, накладные расходы и поле
+    /// атрибутов, если оно есть.
+    fn frame_size(&self) -> usize;
     /// Сериализует блок в байты с CRC.
     fn serialize(&self) -> GlosResult<Vec<u8>>;
     /// Десериализует блок из ьайтового среза.
@@ -80,6 +191,29 @@ pub trait IqBlockExt {
         Self: Sized;
     /// Возвращает несжатые данные (автоматически распаковывает если нужно).
     fn get_uncompressed_data(&self) -> GlosResult<Vec<u8>>;
+    /// Делит блок на `[0, sample_idx)` и `[sample_idx, sample_count)`.
+    ///
+    /// Метка второй части сдвигается на `sample_idx` периодов
+    /// дискретизации. Обе части непустые.
+    fn split_at(
+        &self,
+        sample_idx: u32,
+        format: IqFormat,
+        sample_rate: u32,
+    ) -> GlosResult<(Self, Self)>
+    where
+        Self: Sized;
+    /// Склеивает подряд идущие блоки в один.
+    ///
+    /// Метки должны продолжать друг друга (с точностью до периода
+    /// дискретизации), иначе между блоками разрыв — это ошибка.
+    fn concat(
+        blocks: &[Self],
+        format: IqFormat,
+        sample_rate: u32,
+    ) -> GlosResult<Self>
+    where
+        Self: Sized;
 }
 
 impl GlosHeaderExt for GlosHeader {
@@ -88,10 +222,7 @@ impl GlosHeaderExt for GlosHeader {
         sample_rate: u32,
         center_freq: u64,
     ) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = current_unix_secs();
 
         GlosHeader {
             version: GLOS_VERSION,
@@ -105,6 +236,8 @@ impl GlosHeaderExt for GlosHeader {
             timestamp_start: now,
             timestamp_end: 0,
             total_samples: 0,
+            time_source: TimeSource::Host,
+            clock_offset_ns: 0,
         }
     }
 
@@ -144,83 +277,37 @@ impl GlosHeaderExt for GlosHeader {
         write_u64_local(&mut buf, &mut off, is_le, self.timestamp_end);
         write_u64_local(&mut buf, &mut off, is_le, self.total_samples);
 
+        buf[off] = self.time_source.as_u8();
+        off += 8; // источник времени + padding
+
+        write_u64_local(&mut buf, &mut off, is_le, self.clock_offset_ns as u64);
+
         // CRC32 всегда big-endian, покрывает [0..72)
-        let crc = crc32_checksum(&buf[0..72]);
-        buf[72..76].copy_from_slice(&crc.to_be_bytes());
+        let crc = crc32_checksum(&buf[0..GLOS_HEADER_CRC_OFFSET]);
+        buf[GLOS_HEADER_CRC_OFFSET..GLOS_HEADER_CRC_OFFSET + 4].copy_from_slice(&crc.to_be_bytes());
 
         // [76..128] — reserved, уже нули
         Ok(buf)
     }
 
     fn deserialize(buf: &[u8; GLOS_HEADER_SIZE]) -> GlosResult<Self> {
-        let mut off = 0;
-
-        if buf[off..off + 4] != GLOS_MAGIC {
-            return Err(GlosError::invalid_magic("Invalid GLOS magic number"));
-        }
-        off += 4;
-
-        let version = buf[off];
-        if version != GLOS_VERSION {
-            return Err(GlosError::UnsupportedVersion {
-                found: version,
-                expected: GLOS_VERSION,
-            });
-        }
-        off += 1;
-
-        let flags = buf[off];
-        let is_le = (flags & 0x01) != 0;
-        off += 1;
-
-        off += 6; // padding
-
-        let sdr_type = SdrType::from_u8(buf[off]);
-        off += 1;
-
-        let iq_format = IqFormat::from_u8(buf[off])?;
-        off += 1;
-
-        let compression = Compression::from_u8(buf[off])?;
-        off += 1;
+        parse_header(buf, false)
+    }
 
-        off += 1; // padding
+    fn deserialize_best_effort(buf: &[u8; GLOS_HEADER_SIZE]) -> GlosResult<Self> {
+        parse_header(buf, true)
+    }
 
-        // вызовы (заменяют let sample_rate = read_u32!(); и т.д.)
-        let sample_rate = read_u32_local(buf, &mut off, is_le);
-        let center_freq = read_u64_local(buf, &mut off, is_le);
-        let gain_db = f32::from_bits(read_u32_local(buf, &mut off, is_le));
-        let timestamp_start = read_u64_local(buf, &mut off, is_le);
-        let timestamp_end = read_u64_local(buf, &mut off, is_le);
-        let total_samples = read_u64_local(buf, &mut off, is_le);
-
-        // CRC всегда big-endian
-        let stored_crc = u32::from_be_bytes([buf[72], buf[73], buf[74], buf[75]]);
-        let calculated_crc = crc32_checksum(&buf[0..72]);
-        if stored_crc != calculated_crc {
-            return Err(GlosError::CrcMismatch {
-                expected: calculated_crc,
-                found: stored_crc,
-            });
-        }
+    fn is_little_endian(&self) -> bool {
+        (self.flags & GLOS_FLAG_LITTLE_ENDIAN) != 0
+    }
 
-        Ok(GlosHeader {
-            version,
-            flags,
-            sdr_type,
-            iq_format,
-            compression,
-            sample_rate,
-            center_freq,
-            gain_db,
-            timestamp_start,
-            timestamp_end,
-            total_samples,
-        })
+    fn samples_little_endian(&self) -> bool {
+        (self.flags & (GLOS_FLAG_LITTLE_ENDIAN | GLOS_FLAG_SAMPLES_LITTLE_ENDIAN)) != 0
     }
 
-    fn is_little_endian(&self) -> bool {
-        (self.flags & 0x01) != 0
+    fn is_content_defined(&self) -> bool {
+        (self.flags & GLOS_FLAG_CONTENT_DEFINED) != 0
     }
 }
 
@@ -235,6 +322,23 @@ impl IqBlockExt for IqBlock {
             sample_count,
             data,
             is_compressed: false,
+            attributes: BlockAttributes::default(),
+        }
+    }
+
+    fn new_sync_marker(
+        timestamp_ns: u64,
+        marker: SyncMarker,
+    ) -> Self {
+        IqBlock {
+            timestamp_ns,
+            sample_count: 0,
+            data: Vec::new(),
+            is_compressed: false,
+            attributes: BlockAttributes {
+                sync_marker: Some(marker),
+                ..Default::default()
+            },
         }
     }
 
@@ -248,15 +352,28 @@ impl IqBlockExt for IqBlock {
             sample_count,
             data: compressed_data,
             is_compressed: true,
+            attributes: BlockAttributes::default(),
         }
     }
 
     fn compress(&mut self) -> GlosResult<()> {
+        self.compress_with(Compression::Lz4, 0)
+    }
+
+    fn compress_with(
+        &mut self,
+        compression: Compression,
+        level: i32,
+    ) -> GlosResult<()> {
         if self.is_compressed {
             return Ok(());
         }
 
-        self.data = lz4_flex::compress_prepend_size(&self.data);
+        if compression == Compression::None {
+            return Ok(());
+        }
+
+        self.data = compress_payload(&self.data, compression, level)?;
         self.is_compressed = true;
 
         Ok(())
@@ -267,10 +384,7 @@ impl IqBlockExt for IqBlock {
             return Ok(()); // Не сжато
         }
 
-        let decompressed = lz4_flex::decompress_size_prepended(&self.data)
-            .map_err(|e| GlosError::Corrupted(format!("LZ4 decompression failed: {e}")))?;
-
-        self.data = decompressed;
+        self.data = decompress_payload(&self.data)?;
         self.is_compressed = false;
 
         Ok(())
@@ -284,34 +398,48 @@ impl IqBlockExt for IqBlock {
             return Ok(());
         }
 
-        let expected = self.sample_count as usize * iq_format.sample_size();
+        check_sample_count(self.sample_count, self.data.len(), iq_format)
+    }
 
-        if self.data.len() != expected {
-            return Err(GlosError::FormatViolation(format!(
-                "sample_count={} × sample_size={} = {} ≠ data.len()={}",
-                self.sample_count,
-                iq_format.sample_size(),
-                expected,
-                self.data.len(),
-            )));
-        }
+    fn frame_size(&self) -> usize {
+        let attributes = if self.attributes.is_empty() {
+            0
+        } else {
+            GLOS_BLOCK_ATTRIBUTES_SIZE
+        };
 
-        Ok(())
+        self.data.len() + GLOS_BLOCK_OVERHEAD + attributes
     }
 
     fn serialize(&self) -> GlosResult<Vec<u8>> {
-        let block_size = 4 + 4 + 8 + self.data.len() + 4; // size+count+ts+data+crc
-
-        if block_size > GLOS_MAX_BLOCK_SIZE {
-            return Err(GlosError::InvalidBlockSize(block_size));
+        if self.sample_count & GLOS_BLOCK_HAS_ATTRIBUTES != 0 {
+            return Err(GlosError::FormatViolation(format!(
+                "sample_count {} does not fit in 31 bits",
+                self.sample_count
+            )));
         }
+        check_sync_marker(self)?;
+
+        // size+count+ts+[attrs]+data+crc
+        let block_size = Some(self.frame_size())
+            .filter(|&n| n <= GLOS_MAX_BLOCK_SIZE)
+            .ok_or(GlosError::InvalidBlockSize(self.data.len()))?;
 
         let mut buf = Vec::with_capacity(block_size);
-        let content_size = (4 + 8 + self.data.len()) as u32;
+        // block_size ≤ GLOS_MAX_BLOCK_SIZE, поэтому помещается в u32
+        let content_size = (block_size - 8) as u32;
+        let attributes = encode_attributes(&self.attributes);
+        let sample_count = match attributes {
+            Some(_) => self.sample_count | GLOS_BLOCK_HAS_ATTRIBUTES,
+            None => self.sample_count,
+        };
 
         buf.extend_from_slice(&content_size.to_be_bytes());
-        buf.extend_from_slice(&self.sample_count.to_be_bytes());
+        buf.extend_from_slice(&sample_count.to_be_bytes());
         buf.extend_from_slice(&self.timestamp_ns.to_be_bytes());
+        if let Some(attributes) = attributes {
+            buf.extend_from_slice(&attributes);
+        }
         buf.extend_from_slice(&self.data);
 
         let crc = crc32_checksum(&buf[4..]); // CRC покрывает [4..end-4]
@@ -325,37 +453,22 @@ impl IqBlockExt for IqBlock {
         buf: &[u8],
         compression: Compression,
     ) -> GlosResult<(Self, usize)> {
-        if buf.len() < 20 {
+        if buf.len() < GLOS_BLOCK_OVERHEAD {
             return Err(GlosError::corrupted("Block too small"));
         }
 
-        // Размер содержимого блока
-        let content_size = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        // Размер содержимого блока; сумма проверена до индексации
+        let total_bytes = block_frame_size(buf)?;
+        let content_size = total_bytes - 8;
 
-        if 4 + content_size + 4 > buf.len() {
+        if total_bytes > buf.len() {
             return Err(GlosError::corrupted("Incomplete block"));
         }
 
-        let sample_count = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]);
-
-        // Время блока
-        let timestamp_ns = u64::from_be_bytes([
-            buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15],
-        ]);
-
-        // IQ данные
-        let data_len = content_size
-            .checked_sub(12)
-            .ok_or_else(|| GlosError::corrupted("Invalid content_size"))?;
-        let data = buf[16..16 + data_len].to_vec();
-
         // CRC32 покрывает байты [4..4 + content_size]
-        let stored_crc = u32::from_be_bytes([
-            buf[4 + content_size],
-            buf[4 + content_size + 1],
-            buf[4 + content_size + 2],
-            buf[4 + content_size + 3],
-        ]);
+        let crc_bytes = &buf[4 + content_size..total_bytes];
+        let stored_crc =
+            u32::from_be_bytes([crc_bytes[0], crc_bytes[1], crc_bytes[2], crc_bytes[3]]);
         let calculated_crc = crc32_checksum(&buf[4..4 + content_size]);
 
         if stored_crc != calculated_crc {
@@ -365,10 +478,24 @@ impl IqBlockExt for IqBlock {
             });
         }
 
-        // is_compressed определяется из заголовка файла, а не эвристикой
-        let is_compressed = compression == Compression::Lz4;
+        let frame = &buf[..total_bytes];
+        let sample_count = frame_sample_count(frame);
 
-        let total_bytes = 4 + content_size + 4;
+        // Время блока
+        let timestamp_ns = u64::from_be_bytes([
+            buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15],
+        ]);
+
+        // IQ данные: content_size ≥ 12 гарантирует block_frame_size, поле
+        // атрибутов проверяет frame_payload
+        let payload = frame_payload(frame).ok_or_else(|| {
+            GlosError::FormatViolation("Block attributes exceed block size".to_string())
+        })?;
+        let data = frame[payload].to_vec();
+        let attributes = decode_attributes(frame);
+
+        // is_compressed определяется из заголовка файла, а не эвристикой
+        let is_compressed = compression != Compression::None;
 
         Ok((
             IqBlock {
@@ -376,6 +503,7 @@ impl IqBlockExt for IqBlock {
                 sample_count,
                 data,
                 is_compressed,
+                attributes,
             },
             total_bytes,
         ))
@@ -383,12 +511,524 @@ impl IqBlockExt for IqBlock {
 
     fn get_uncompressed_data(&self) -> GlosResult<Vec<u8>> {
         if self.is_compressed {
-            lz4_flex::decompress_size_prepended(&self.data)
-                .map_err(|e| GlosError::Corrupted(format!("LZ4 decompression failed: {e}")))
+            decompress_payload(&self.data)
         } else {
             Ok(self.data.clone())
         }
     }
+
+    fn split_at(
+        &self,
+        sample_idx: u32,
+        format: IqFormat,
+        sample_rate: u32,
+    ) -> GlosResult<(Self, Self)> {
+        if self.is_compressed {
+            return Err(GlosError::FormatViolation(
+                "Cannot split compressed block; decompress first".to_string(),
+            ));
+        }
+
+        if sample_idx == 0 || sample_idx >= self.sample_count {
+            return Err(GlosError::FormatViolation(format!(
+                "Split index {sample_idx} out of range 1..{}",
+                self.sample_count
+            )));
+        }
+
+        self.validate_sample_count(format)?;
+
+        let (head, tail) = self
+            .data
+            .split_at(sample_idx as usize * format.sample_size());
+
+        // События начала блока остаются первой части, перегрузка — обеим
+        let mut first = IqBlock::new(self.timestamp_ns, sample_idx, head.to_vec());
+        first.attributes = self.attributes;
+        let mut second = IqBlock::new(
+            self.timestamp_ns + samples_to_ns(sample_idx as u64, sample_rate.into())?,
+            self.sample_count - sample_idx,
+            tail.to_vec(),
+        );
+        second.attributes.overload = self.attributes.overload;
+
+        Ok((first, second))
+    }
+
+    fn concat(
+        blocks: &[Self],
+        format: IqFormat,
+        sample_rate: u32,
+    ) -> GlosResult<Self> {
+        let first = blocks
+            .first()
+            .ok_or_else(|| GlosError::FormatViolation("Nothing to concat".to_string()))?;
+
+        // Допуск: один период дискретизации (метки округляются до нс)
+        let tolerance = samples_to_ns(1, sample_rate.into())?.max(1);
+        let mut offset_samples: u64 = 0;
+        let mut data = Vec::new();
+        let mut attributes = BlockAttributes::default();
+
+        for (i, block) in blocks.iter().enumerate() {
+            if block.is_compressed {
+                return Err(GlosError::FormatViolation(format!(
+                    "Block {i} is compressed; decompress first"
+                )));
+            }
+            if block.attributes.sync_marker.is_some() {
+                return Err(GlosError::FormatViolation(format!(
+                    "Block {i} is a sync marker and cannot be merged"
+                )));
+            }
+            block.validate_sample_count(format)?;
+
+            let expected = first.timestamp_ns + samples_to_ns(offset_samples, sample_rate.into())?;
+            if block.timestamp_ns.abs_diff(expected) > tolerance {
+                return Err(GlosError::FormatViolation(format!(
+                    "Block {i} is not contiguous: timestamp {} ns, expected {expected} ns",
+                    block.timestamp_ns
+                )));
+            }
+
+            offset_samples += block.sample_count as u64;
+            data.extend_from_slice(&block.data);
+            attributes.merge(block.attributes);
+        }
+
+        let sample_count = u32::try_from(offset_samples).map_err(|_| {
+            GlosError::FormatViolation(format!(
+                "Concatenated block too large: {offset_samples} samples"
+            ))
+        })?;
+
+        let mut block = IqBlock::new(first.timestamp_ns, sample_count, data);
+        block.attributes = attributes;
+
+        Ok(block)
+    }
+}
+
+/// Разбор заголовка; с `allow_newer` версии выше [`GLOS_MAX_VERSION`]
+/// читаются по компоновке v1. Версий ниже [`GLOS_VERSION`] не существует —
+/// это всегда ошибка.
+fn parse_header(
+    buf: &[u8; GLOS_HEADER_SIZE],
+    allow_newer: bool,
+) -> GlosResult<GlosHeader> {
+    let mut off = 0;
+
+    if buf[off..off + 4] != GLOS_MAGIC {
+        return Err(GlosError::invalid_magic("Invalid GLOS magic number"));
+    }
+    off += 4;
+
+    let version = buf[off];
+    if version < GLOS_VERSION || (version > GLOS_MAX_VERSION && !allow_newer) {
+        return Err(GlosError::UnsupportedVersion {
+            found: version,
+            expected: GLOS_MAX_VERSION,
+        });
+    }
+    off += 1;
+
+    let flags = buf[off];
+    let is_le = (flags & 0x01) != 0;
+    off += 1;
+
+    off += 6; // padding
+
+    let sdr_type = SdrType::from_u8(buf[off]);
+    off += 1;
+
+    let iq_format = IqFormat::from_u8(buf[off])?;
+    off += 1;
+
+    let compression = Compression::from_u8(buf[off])?;
+    off += 1;
+
+    off += 1; // padding
+
+    // вызовы (заменяют let sample_rate = read_u32!(); и т.д.)
+    let sample_rate = read_u32_local(buf, &mut off, is_le);
+    let center_freq = read_u64_local(buf, &mut off, is_le);
+    let gain_db = f32::from_bits(read_u32_local(buf, &mut off, is_le));
+    let timestamp_start = read_u64_local(buf, &mut off, is_le);
+    let timestamp_end = read_u64_local(buf, &mut off, is_le);
+    let total_samples = read_u64_local(buf, &mut off, is_le);
+
+    let time_source = TimeSource::from_u8(buf[off]);
+    off += 8; // источник времени + padding
+
+    let clock_offset_ns = read_u64_local(buf, &mut off, is_le) as i64;
+
+    // CRC всегда big-endian
+    let crc_bytes = &buf[GLOS_HEADER_CRC_OFFSET..GLOS_HEADER_CRC_OFFSET + 4];
+    let stored_crc = u32::from_be_bytes(crc_bytes.try_into().unwrap());
+    let calculated_crc = crc32_checksum(&buf[0..GLOS_HEADER_CRC_OFFSET]);
+    if stored_crc != calculated_crc {
+        return Err(GlosError::CrcMismatch {
+            expected: calculated_crc,
+            found: stored_crc,
+        });
+    }
+
+    Ok(GlosHeader {
+        version,
+        flags,
+        sdr_type,
+        iq_format,
+        compression,
+        sample_rate,
+        center_freq,
+        gain_db,
+        timestamp_start,
+        timestamp_end,
+        total_samples,
+        time_source,
+        clock_offset_ns,
+    })
+}
+
+/// Полный размер блока (`4 + content_size + 4`) по его первым 4 байтам.
+///
+/// `content_size` берётся из файла и не заслуживает доверия: размер меньше
+/// `count + ts` или больше [`GLOS_MAX_BLOCK_SIZE`] означает мусор, а не
+/// «данных пока не хватает» — возвращается [`GlosError::Corrupted`].
+pub(crate) fn block_frame_size(buf: &[u8]) -> GlosResult<usize> {
+    let prefix: [u8; 4] = buf
+        .get(..4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| GlosError::corrupted("Block too small"))?;
+    let content_size = u32::from_be_bytes(prefix) as usize;
+
+    if content_size < 12 {
+        return Err(GlosError::corrupted(format!(
+            "Invalid content_size {content_size}"
+        )));
+    }
+
+    content_size
+        .checked_add(8)
+        .filter(|&n| n <= GLOS_MAX_BLOCK_SIZE)
+        .ok_or_else(|| GlosError::corrupted(format!("content_size {content_size} exceeds limit")))
+}
+
+/// Число выборок кадра без бита [`GLOS_BLOCK_HAS_ATTRIBUTES`].
+pub(crate) fn frame_sample_count(frame: &[u8]) -> u32 {
+    u32::from_be_bytes([frame[4], frame[5], frame[6], frame[7]]) & !GLOS_BLOCK_HAS_ATTRIBUTES
+}
+
+/// Диапазон данных в целом кадре `frame` (от размера до CRC включительно);
+/// `None`, если заявленное поле атрибутов не помещается в кадр.
+pub(crate) fn frame_payload(frame: &[u8]) -> Option<std::ops::Range<usize>> {
+    let start = if has_attributes(frame) {
+        16 + GLOS_BLOCK_ATTRIBUTES_SIZE
+    } else {
+        16
+    };
+    let end = frame.len().checked_sub(4)?;
+
+    (start <= end).then_some(start..end)
+}
+
+fn has_attributes(frame: &[u8]) -> bool {
+    frame[4] & 0x80 != 0
+}
+
+/// Поле атрибутов кадра; перед вызовом кадр проверен [`frame_payload`].
+/// Неизвестные биты флагов пропускаются.
+pub(crate) fn decode_attributes(frame: &[u8]) -> BlockAttributes {
+    if !has_attributes(frame) {
+        return BlockAttributes::default();
+    }
+
+    let field = |i: usize| {
+        let at = 16 + i * 4;
+        [frame[at], frame[at + 1], frame[at + 2], frame[at + 3]]
+    };
+    let flags = u32::from_be_bytes(field(0));
+
+    if flags & GLOS_BLOCK_FLAG_SYNC_MARKER != 0 {
+        let high = u32::from_be_bytes(field(1)) as u64;
+        let low = u32::from_be_bytes(field(2)) as u64;
+        return BlockAttributes {
+            sync_marker: Some(SyncMarker {
+                id: flags >> SYNC_MARKER_ID_SHIFT,
+                external_ns: high << 32 | low,
+            }),
+            ..Default::default()
+        };
+    }
+
+    BlockAttributes {
+        overload: flags & GLOS_BLOCK_FLAG_OVERLOAD != 0,
+        gain_db: (flags & GLOS_BLOCK_FLAG_GAIN_CHANGED != 0).then(|| f32::from_be_bytes(field(1))),
+        dropped_samples: (flags & GLOS_BLOCK_FLAG_SAMPLES_DROPPED != 0)
+            .then(|| u32::from_be_bytes(field(2))),
+        sync_marker: None,
+    }
+}
+
+/// Поле атрибутов для записи; `None`, если событий нет.
+fn encode_attributes(attributes: &BlockAttributes) -> Option<[u8; GLOS_BLOCK_ATTRIBUTES_SIZE]> {
+    if attributes.is_empty() {
+        return None;
+    }
+
+    let mut flags = 0;
+    if attributes.overload {
+        flags |= GLOS_BLOCK_FLAG_OVERLOAD;
+    }
+    if attributes.gain_db.is_some() {
+        flags |= GLOS_BLOCK_FLAG_GAIN_CHANGED;
+    }
+    if attributes.dropped_samples.is_some() {
+        flags |= GLOS_BLOCK_FLAG_SAMPLES_DROPPED;
+    }
+
+    let mut buf = [0u8; GLOS_BLOCK_ATTRIBUTES_SIZE];
+    if let Some(marker) = attributes.sync_marker {
+        flags = GLOS_BLOCK_FLAG_SYNC_MARKER | marker.id << SYNC_MARKER_ID_SHIFT;
+        buf[..4].copy_from_slice(&flags.to_be_bytes());
+        buf[4..].copy_from_slice(&marker.external_ns.to_be_bytes());
+        return Some(buf);
+    }
+
+    buf[..4].copy_from_slice(&flags.to_be_bytes());
+    buf[4..8].copy_from_slice(&attributes.gain_db.unwrap_or(0.0).to_be_bytes());
+    buf[8..].copy_from_slice(&attributes.dropped_samples.unwrap_or(0).to_be_bytes());
+
+    Some(buf)
+}
+
+/// Метка синхронизации не несёт ни выборок, ни других событий, а её номер
+/// помещается в 24 бита.
+fn check_sync_marker(block: &IqBlock) -> GlosResult<()> {
+    let Some(marker) = block.attributes.sync_marker else {
+        return Ok(());
+    };
+
+    if marker.id > GLOS_SYNC_MARKER_ID_MAX {
+        return Err(GlosError::FormatViolation(format!(
+            "sync marker id {} exceeds {GLOS_SYNC_MARKER_ID_MAX}",
+            marker.id
+        )));
+    }
+    let attributes = BlockAttributes {
+        sync_marker: None,
+        ..block.attributes
+    };
+    if block.sample_count != 0 || !attributes.is_empty() {
+        return Err(GlosError::FormatViolation(format!(
+            "sync marker #{} must be an empty block without other attributes",
+            marker.id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Проверяет `sample_count × sample_size == data_len` для несжатых данных.
+pub(crate) fn check_sample_count(
+    sample_count: u32,
+    data_len: usize,
+    iq_format: IqFormat,
+) -> GlosResult<()> {
+    let expected = (sample_count as usize)
+        .checked_mul(iq_format.sample_size())
+        .ok_or_else(|| GlosError::corrupted(format!("sample_count={sample_count} overflows")))?;
+
+    if data_len != expected {
+        return Err(GlosError::FormatViolation(format!(
+            "sample_count={} × sample_size={} = {} ≠ data.len()={}",
+            sample_count,
+            iq_format.sample_size(),
+            expected,
+            data_len,
+        )));
+    }
+
+    Ok(())
+}
+
+/// Сжимает данные блока алгоритмом `compression`.
+pub(crate) fn compress_payload(
+    data: &[u8],
+    compression: Compression,
+    level: i32,
+) -> GlosResult<Vec<u8>> {
+    match compression {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        Compression::Zstd => zstd_compress(data, level),
+    }
+}
+
+/// Оборачивает данные в кадр `compression` без сжатия: читатель
+/// распаковывает их как обычно, а писатель не тратит на них CPU.
+///
+/// LZ4 — один литеральный прогон, Zstd — кадр из raw-блоков; накладные
+/// расходы — доли процента.
+pub(crate) fn store_payload(
+    data: &[u8],
+    compression: Compression,
+) -> Vec<u8> {
+    match compression {
+        Compression::None => data.to_vec(),
+        Compression::Lz4 => lz4_stored(data),
+        Compression::Zstd => zstd_stored(data),
+    }
+}
+
+/// LZ4 с префиксом размера из одних литералов.
+fn lz4_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 255 + 6);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+    if data.len() < 15 {
+        out.push((data.len() as u8) << 4);
+    } else {
+        out.push(0xF0);
+        let mut rest = data.len() - 15;
+        while rest >= 255 {
+            out.push(255);
+            rest -= 255;
+        }
+        out.push(rest as u8);
+    }
+    out.extend_from_slice(data);
+
+    out
+}
+
+/// Кадр Zstd из raw-блоков с известным размером данных.
+fn zstd_stored(data: &[u8]) -> Vec<u8> {
+    /// Наибольший размер блока Zstd
+    const ZSTD_BLOCK_MAX: usize = 128 * 1024;
+
+    let blocks = data.len().div_ceil(ZSTD_BLOCK_MAX).max(1);
+    let mut out = Vec::with_capacity(data.len() + 13 + 3 * blocks);
+    out.extend_from_slice(&ZSTD_MAGIC);
+    // Single_Segment, 8 байт Frame_Content_Size, без контрольной суммы
+    out.push(0xE0);
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+
+    for i in 0..blocks {
+        let chunk =
+            &data[(i * ZSTD_BLOCK_MAX).min(data.len())..((i + 1) * ZSTD_BLOCK_MAX).min(data.len())];
+        let last = (i + 1 == blocks) as u32;
+        // Last_Block | Block_Type = Raw (0) | Block_Size
+        let block_header = last | (chunk.len() as u32) << 3;
+        out.extend_from_slice(&block_header.to_le_bytes()[..3]);
+        out.extend_from_slice(chunk);
+    }
+
+    out
+}
+
+/// Распаковка сжатых данных блока: Zstd узнаётся по магическому числу
+/// кадра, всё остальное считается LZ4 с префиксом размера.
+pub(crate) fn decompress_payload(data: &[u8]) -> GlosResult<Vec<u8>> {
+    if data.starts_with(&ZSTD_MAGIC) {
+        zstd_decompress(data)
+    } else {
+        lz4_decompress(data)
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(
+    data: &[u8],
+    level: i32,
+) -> GlosResult<Vec<u8>> {
+    if !ZSTD_LEVELS.contains(&level) {
+        return Err(GlosError::format_violation(format!(
+            "zstd level {level} out of range {}..={}",
+            ZSTD_LEVELS.start(),
+            ZSTD_LEVELS.end()
+        )));
+    }
+
+    Ok(zstd::bulk::compress(data, level)?)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_compress(
+    _data: &[u8],
+    _level: i32,
+) -> GlosResult<Vec<u8>> {
+    Err(zstd_unsupported())
+}
+
+/// Распаковка кадра Zstd.
+///
+/// Размер данных берётся из заголовка кадра и ограничен тем же пределом,
+/// что и для LZ4, — до выделения памяти.
+#[cfg(feature = "zstd")]
+fn zstd_decompress(data: &[u8]) -> GlosResult<Vec<u8>> {
+    let limit = GLOS_MAX_BLOCK_SIZE * LZ4_MAX_RATIO;
+    let declared = match zstd::zstd_safe::get_frame_content_size(data) {
+        Ok(Some(n)) => n,
+        Ok(None) => return Err(GlosError::corrupted("Zstd frame without content size")),
+        Err(_) => return Err(GlosError::corrupted("Zstd frame header is invalid")),
+    };
+
+    if declared > limit as u64 {
+        return Err(GlosError::corrupted(format!(
+            "Zstd declared size {declared} exceeds limit {limit}"
+        )));
+    }
+
+    zstd::bulk::decompress(data, declared as usize)
+        .map_err(|e| GlosError::Corrupted(format!("Zstd decompression failed: {e}")))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decompress(_data: &[u8]) -> GlosResult<Vec<u8>> {
+    Err(zstd_unsupported())
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> GlosError {
+    GlosError::format_violation("zstd support is disabled (glos-core feature `zstd`)")
+}
+
+/// Распаковка LZ4 с префиксом размера.
+///
+/// Заявленный в префиксе размер проверяется до выделения памяти: LZ4 не
+/// сжимает сильнее ~255:1, так что больший размер — признак повреждения.
+fn lz4_decompress(data: &[u8]) -> GlosResult<Vec<u8>> {
+    let prefix: [u8; 4] = data
+        .get(..4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| GlosError::corrupted("LZ4 payload too small"))?;
+    let declared = u32::from_le_bytes(prefix) as usize;
+    let limit = (data.len() - 4).saturating_mul(LZ4_MAX_RATIO);
+
+    if declared > limit {
+        return Err(GlosError::corrupted(format!(
+            "LZ4 declared size {declared} exceeds limit {limit}"
+        )));
+    }
+
+    lz4_flex::decompress_size_prepended(data)
+        .map_err(|e| GlosError::Corrupted(format!("LZ4 decompression failed: {e}")))
+}
+
+/// Длительность `samples` выборок в наносекундах (с округлением вниз,
+/// с насыщением на `u64::MAX`). Нулевая частота — ошибка формата.
+pub(crate) fn samples_to_ns(
+    samples: u64,
+    sample_rate: u64,
+) -> GlosResult<u64> {
+    if sample_rate == 0 {
+        return Err(GlosError::FormatViolation(
+            "Sample rate must be > 0".to_string(),
+        ));
+    }
+
+    Ok((samples as u128 * 1_000_000_000 / sample_rate as u128).min(u64::MAX as u128) as u64)
 }
 
 /// CRC32 (IEEE 802.3 / crc32fast)
@@ -511,6 +1151,43 @@ mod tests {
         deserialized.validate_sample_count(IqFormat::Int16).unwrap();
     }
 
+    #[test]
+    fn test_iq_block_typed_samples() {
+        let data: Vec<u8> = [-32768i16, 32767, 1, -2]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let mut block = IqBlock::new(0, 2, data.clone());
+
+        assert_eq!(
+            block.samples_i16().collect::<Vec<_>>(),
+            [-32768, 32767, 1, -2]
+        );
+        assert_eq!(block.samples_i8().len(), 8);
+
+        let iq = block.as_complex_f32(IqFormat::Int16);
+        assert_eq!(iq.len(), 2);
+        assert_eq!((iq[0].re, iq[0].im), (-32768.0 / 32767.0, 1.0));
+        assert_eq!((iq[1].re, iq[1].im), (1.0 / 32767.0, -2.0 / 32767.0));
+
+        // Тот же блок в little-endian
+        let swapped: Vec<u8> = data.chunks_exact(2).flat_map(|c| [c[1], c[0]]).collect();
+        let le = IqBlock::new(0, 2, swapped).as_complex_f32_with(IqFormat::Int16, true);
+        assert_eq!(le, iq);
+
+        let floats: Vec<u8> = [0.25f32, -0.5]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let float_block = IqBlock::new(0, 1, floats);
+        assert_eq!(float_block.samples_f32().collect::<Vec<_>>(), [0.25, -0.5]);
+
+        // У сжатого блока выборок нет
+        block.is_compressed = true;
+        assert!(block.as_complex_f32(IqFormat::Int16).is_empty());
+        assert_eq!(block.samples_i16().len(), 0);
+    }
+
     #[test]
     fn test_iq_block_corrupted_crc() {
         let data = vec![1u8, 2, 3, 4];
@@ -572,6 +1249,45 @@ mod tests {
         assert!(!block.is_compressed);
     }
 
+    #[test]
+    fn test_compression_zstd_round_trip() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i % 97) as u8).collect();
+        let mut lz4 = IqBlock::new(7, 10_000, data.clone());
+        lz4.compress().unwrap();
+
+        let mut block = IqBlock::new(7, 10_000, data.clone());
+        block.compress_with(Compression::Zstd, 9).unwrap();
+        assert!(block.is_compressed);
+        assert!(block.data.len() < lz4.data.len(), "Zstd плотнее LZ4");
+
+        // Кодек узнаётся по данным, а не по заголовку
+        let serialized = block.serialize().unwrap();
+        let (parsed, _) = IqBlock::deserialize(&serialized, Compression::Zstd).unwrap();
+        assert_eq!(parsed.get_uncompressed_data().unwrap(), data);
+        assert_eq!(lz4.get_uncompressed_data().unwrap(), data);
+
+        // Несжатый кадр из нескольких raw-блоков (и LZ4-литералы)
+        let big: Vec<u8> = (0..300_000u32).map(|i| (i * 7) as u8).collect();
+        for compression in [Compression::Lz4, Compression::Zstd] {
+            for len in [0, 14, 15, 270, big.len()] {
+                let stored = store_payload(&big[..len], compression);
+                assert_eq!(decompress_payload(&stored).unwrap(), &big[..len]);
+            }
+        }
+
+        let mut bad_level = IqBlock::new(0, 1, vec![0; 4]);
+        assert!(bad_level.compress_with(Compression::Zstd, 10).is_err());
+        assert!(!bad_level.is_compressed);
+
+        // Кадр с ложным размером отвергается до выделения памяти
+        let mut hostile = block.clone();
+        hostile.data.truncate(4);
+        hostile.data.push(0xE0); // Single_Segment, 8 байт размера
+        hostile.data.extend_from_slice(&(1u64 << 40).to_le_bytes());
+        let err = hostile.decompress().unwrap_err().to_string();
+        assert!(err.contains("exceeds limit"), "{err}");
+    }
+
     #[test]
     fn test_compression_idempotent() {
         let data = vec![0u8; 1000];
@@ -607,4 +1323,194 @@ mod tests {
         let uncompressed = deserialized.get_uncompressed_data().unwrap();
         assert_eq!(uncompressed, data);
     }
+
+    #[test]
+    fn test_header_derived_accessors() {
+        let mut h = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        h.iq_format = IqFormat::Int16;
+        h.timestamp_start = 1_000;
+        h.timestamp_end = 0;
+
+        // Незавершённая запись
+        assert_eq!(h.duration(), None);
+
+        // Только метки времени — точность до секунды
+        h.timestamp_end = 1_090;
+        assert_eq!(h.duration(), Some(std::time::Duration::from_secs(90)));
+
+        // Счётчик выборок приоритетнее меток
+        h.total_samples = 3_000_000;
+        assert_eq!(h.duration(), Some(std::time::Duration::from_millis(1_500)));
+        assert_eq!(h.expected_data_bytes(), 12_000_000);
+        assert_eq!(h.bits_per_second(), 64_000_000);
+        assert_eq!(h.sample_period_ns(), 500.0);
+
+        h.sample_rate = 0;
+        assert_eq!(h.sample_period_ns(), 0.0);
+    }
+
+    #[test]
+    fn test_iq_block_split_and_concat() {
+        let data: Vec<u8> = (0..40u8).collect();
+        let block = IqBlock::new(1_000, 10, data.clone());
+
+        let (a, b) = block.split_at(4, IqFormat::Int16, 2_000_000).unwrap();
+        assert_eq!((a.sample_count, b.sample_count), (4, 6));
+        assert_eq!(a.timestamp_ns, 1_000);
+        assert_eq!(b.timestamp_ns, 1_000 + 4 * 500);
+        assert_eq!(b.data, data[16..]);
+
+        let joined = IqBlock::concat(&[a, b], IqFormat::Int16, 2_000_000).unwrap();
+        assert_eq!(joined.timestamp_ns, 1_000);
+        assert_eq!(joined.sample_count, 10);
+        assert_eq!(joined.data, data);
+
+        assert!(block.split_at(0, IqFormat::Int16, 2_000_000).is_err());
+        assert!(block.split_at(10, IqFormat::Int16, 2_000_000).is_err());
+        assert!(block.split_at(4, IqFormat::Int16, 0).is_err());
+    }
+
+    #[test]
+    fn test_iq_block_attributes() {
+        let mut block = IqBlock::new(1_000, 10, (0..40u8).collect());
+        assert_eq!(block.serialize().unwrap().len(), block.frame_size());
+
+        block.attributes = BlockAttributes {
+            overload: true,
+            gain_db: Some(31.5),
+            dropped_samples: Some(12),
+            sync_marker: None,
+        };
+        let bytes = block.serialize().unwrap();
+        assert_eq!(
+            bytes.len(),
+            40 + GLOS_BLOCK_OVERHEAD + GLOS_BLOCK_ATTRIBUTES_SIZE
+        );
+        assert_eq!(bytes.len(), block.frame_size());
+
+        let (parsed, read) = IqBlock::deserialize(&bytes, Compression::None).unwrap();
+        assert_eq!(read, bytes.len());
+        assert_eq!(parsed.sample_count, 10);
+        assert_eq!(parsed.data, block.data);
+        assert_eq!(parsed.attributes, block.attributes);
+
+        // События начала блока — первой части, перегрузка — обеим
+        let (a, b) = block.split_at(4, IqFormat::Int16, 2_000_000).unwrap();
+        assert_eq!(a.attributes, block.attributes);
+        assert!(b.attributes.overload);
+        assert_eq!(
+            (b.attributes.gain_db, b.attributes.dropped_samples),
+            (None, None)
+        );
+        let joined = IqBlock::concat(&[a, b], IqFormat::Int16, 2_000_000).unwrap();
+        assert_eq!(joined.attributes, block.attributes);
+
+        // Заявленные атрибуты, не помещающиеся в кадр, — нарушение формата
+        let mut short = IqBlock::new(0, 1, vec![0u8; 4]).serialize().unwrap();
+        short[4] |= 0x80;
+        let crc = crc32_checksum(&short[4..short.len() - 4]);
+        let at = short.len() - 4;
+        short[at..].copy_from_slice(&crc.to_be_bytes());
+        assert!(matches!(
+            IqBlock::deserialize(&short, Compression::None),
+            Err(GlosError::FormatViolation(_))
+        ));
+
+        assert!(IqBlock::new(0, GLOS_BLOCK_HAS_ATTRIBUTES, vec![])
+            .serialize()
+            .is_err());
+    }
+
+    #[test]
+    fn test_iq_block_sync_marker() {
+        let marker = SyncMarker {
+            id: 7,
+            external_ns: 0x0123_4567_89AB_CDEF,
+        };
+        let block = IqBlock::new_sync_marker(5_000, marker);
+        let bytes = block.serialize().unwrap();
+        assert_eq!(
+            bytes.len(),
+            GLOS_BLOCK_OVERHEAD + GLOS_BLOCK_ATTRIBUTES_SIZE
+        );
+        assert_eq!(
+            u32::from_be_bytes(bytes[16..20].try_into().unwrap()),
+            GLOS_BLOCK_FLAG_SYNC_MARKER | 7 << 8
+        );
+
+        let (parsed, _) = IqBlock::deserialize(&bytes, Compression::None).unwrap();
+        assert_eq!(parsed.sample_count, 0);
+        assert!(parsed.data.is_empty());
+        assert_eq!(parsed.attributes.sync_marker, Some(marker));
+        assert_eq!(parsed.attributes.gain_db, None);
+
+        // Метка не несёт выборок и других событий, номер — не шире 24 бит
+        let mut bad = IqBlock::new(5_000, 1, vec![0u8; 4]);
+        bad.attributes.sync_marker = Some(marker);
+        assert!(bad.serialize().is_err());
+        let mut bad = block.clone();
+        bad.attributes.overload = true;
+        assert!(bad.serialize().is_err());
+        let too_big = SyncMarker {
+            id: GLOS_SYNC_MARKER_ID_MAX + 1,
+            ..marker
+        };
+        assert!(IqBlock::new_sync_marker(0, too_big).serialize().is_err());
+
+        let data = IqBlock::new(5_000, 0, vec![]);
+        assert!(IqBlock::concat(&[data, block], IqFormat::Int16, 2_000_000).is_err());
+    }
+
+    #[test]
+    fn test_iq_block_concat_rejects_gap() {
+        let a = IqBlock::new(0, 2, vec![0u8; 8]);
+        let b = IqBlock::new(10_000, 2, vec![0u8; 8]);
+
+        assert!(IqBlock::concat(&[a, b], IqFormat::Int16, 2_000_000).is_err());
+        assert!(IqBlock::concat(&[], IqFormat::Int16, 2_000_000).is_err());
+    }
+
+    #[test]
+    fn test_block_deserialize_rejects_hostile_sizes() {
+        let valid = IqBlock::new(7, 4, vec![1u8; 16]).serialize().unwrap();
+
+        for content_size in [u32::MAX, u32::MAX - 3, u32::MAX - 7, 0, 11] {
+            let mut buf = valid.clone();
+            buf[..4].copy_from_slice(&content_size.to_be_bytes());
+
+            assert!(matches!(
+                IqBlock::deserialize(&buf, Compression::None),
+                Err(GlosError::Corrupted(_))
+            ));
+        }
+
+        // Размер больше лимита — мусор, даже если буфер достаточно длинный
+        let oversize = (GLOS_MAX_BLOCK_SIZE - 7) as u32;
+        let mut buf = vec![0u8; GLOS_MAX_BLOCK_SIZE + 8];
+        buf[..4].copy_from_slice(&oversize.to_be_bytes());
+        assert!(matches!(
+            IqBlock::deserialize(&buf, Compression::None),
+            Err(GlosError::Corrupted(_))
+        ));
+
+        // Огромный sample_count не переполняет проверку
+        let block = IqBlock::new(0, u32::MAX, vec![0u8; 16]);
+        assert!(block.validate_sample_count(IqFormat::Float32).is_err());
+    }
+
+    #[test]
+    fn test_lz4_rejects_hostile_declared_size() {
+        let mut block = IqBlock::new(0, 256, vec![0u8; 1024]);
+        block.compress().unwrap();
+
+        block.data[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            block.get_uncompressed_data(),
+            Err(GlosError::Corrupted(_))
+        ));
+        assert!(matches!(block.decompress(), Err(GlosError::Corrupted(_))));
+
+        block.data.truncate(2);
+        assert!(block.decompress().is_err());
+    }
 }
@@ -0,0 +1,11 @@
+//! Обмен записями с форматами других инструментов.
+//!
+//! Модули переводят `.glos` в чужой формат и обратно, сохраняя то, что в
+//! нём выражается: частоту дискретизации, несущую, формат выборок и время.
+
+pub mod sigmf;
+
+pub use sigmf::{
+    export_sigmf, import_sigmf, sigmf_datatype, sigmf_paths, SigmfCapture, SigmfGlobal,
+    SigmfImport, SigmfImportOptions, SigmfMeta, SIGMF_IMPORT_BLOCK_SAMPLES, SIGMF_VERSION,
+};
@@ -0,0 +1,657 @@
+//! SigMF: пара файлов `.sigmf-meta` (JSON) и `.sigmf-data` (сырые выборки).
+//!
+//! Многие GNSS-SDR инструменты читают только SigMF. [`export_sigmf`] пишет
+//! данные блоков подряд в порядке байт исходного файла, а каждый разрыв меток
+//! времени (потерянные при записи выборки) открывает новый сегмент
+//! `captures` со своим `core:datetime`. [`import_sigmf`] делает обратное:
+//! блоки не пересекают границы сегментов, метка блока считается от времени
+//! его сегмента.
+//!
+//! Усиление и поля, которых нет в ядре SigMF, не переносятся; несущая у
+//! `.glos` одна на файл, поэтому при импорте берётся частота первого
+//! сегмента.
+
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use glos_types::{Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    format::samples_to_ns, GlosHeaderExt, GlosReader, GlosWriter, IqBlockExt,
+    GLOS_FLAG_LITTLE_ENDIAN, GLOS_MAX_BLOCK_SIZE,
+};
+
+/// Версия SigMF, которую пишет экспорт.
+pub const SIGMF_VERSION: &str = "1.0.0";
+
+/// Выборок в блоке при импорте по умолчанию.
+pub const SIGMF_IMPORT_BLOCK_SAMPLES: u32 = 65_536;
+
+/// Содержимое `.sigmf-meta` (только поля ядра, которые переносятся).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigmfMeta {
+    pub global: SigmfGlobal,
+    #[serde(default)]
+    pub captures: Vec<SigmfCapture>,
+    /// Аннотации не переносятся, но сохраняются при чтении
+    #[serde(default)]
+    pub annotations: Vec<serde_json::Value>,
+}
+
+/// Объект `global`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigmfGlobal {
+    /// Формат выборок: `ci8`, `cu8`, `ci16_le`, `cf32_be` и т.п.
+    #[serde(rename = "core:datatype")]
+    pub datatype: String,
+    /// Частота дискретизации (Гц)
+    #[serde(
+        rename = "core:sample_rate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sample_rate: Option<f64>,
+    #[serde(rename = "core:version")]
+    pub version: String,
+    #[serde(
+        rename = "core:recorder",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recorder: Option<String>,
+    /// Приёмник (для `.glos` — тип SDR из заголовка)
+    #[serde(rename = "core:hw", default, skip_serializing_if = "Option::is_none")]
+    pub hw: Option<String>,
+    #[serde(
+        rename = "core:description",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<String>,
+}
+
+/// Сегмент непрерывной записи (элемент `captures`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigmfCapture {
+    /// Номер первой выборки сегмента в `.sigmf-data`
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    /// Несущая (Гц)
+    #[serde(
+        rename = "core:frequency",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub frequency: Option<f64>,
+    /// Время первой выборки, ISO 8601 UTC
+    #[serde(
+        rename = "core:datetime",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub datetime: Option<String>,
+}
+
+/// Настройки импорта SigMF в `.glos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigmfImportOptions {
+    /// Выборок в блоке (последний блок сегмента может быть короче)
+    pub block_samples: u32,
+    /// Сжатие блоков `.glos`
+    pub compression: Compression,
+}
+
+/// Итог импорта.
+#[derive(Debug, Clone)]
+pub struct SigmfImport {
+    /// Заголовок записанного `.glos` (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    /// Сегментов `captures`
+    pub captures: usize,
+    /// Что не удалось перенести точно
+    pub warnings: Vec<String>,
+}
+
+/// Формат выборок SigMF в терминах `.glos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Datatype {
+    format: IqFormat,
+    little_endian: bool,
+    /// `cu8`: беззнаковые со смещением 128, переводятся в `ci8`
+    unsigned: bool,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Datatype {
+    fn parse(s: &str) -> GlosResult<Self> {
+        let (format, little_endian, unsigned) = match s {
+            "ci8" => (IqFormat::Int8, false, false),
+            "cu8" => (IqFormat::Int8, false, true),
+            "ci16_le" => (IqFormat::Int16, true, false),
+            "ci16_be" => (IqFormat::Int16, false, false),
+            "cf32_le" => (IqFormat::Float32, true, false),
+            "cf32_be" => (IqFormat::Float32, false, false),
+            _ => {
+                return Err(GlosError::format_violation(format!(
+                    "Unsupported SigMF datatype '{s}'. Supported: ci8, cu8, ci16_le, ci16_be, cf32_le, cf32_be"
+                )))
+            }
+        };
+
+        Ok(Self {
+            format,
+            little_endian,
+            unsigned,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for SigmfImportOptions {
+    fn default() -> Self {
+        Self {
+            block_samples: SIGMF_IMPORT_BLOCK_SAMPLES,
+            compression: Compression::None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Пути `.sigmf-meta` и `.sigmf-data` записи. `path` — любой из двух файлов
+/// или общее имя без расширения.
+pub fn sigmf_paths(path: &Path) -> (PathBuf, PathBuf) {
+    let base = match path.extension().and_then(|e| e.to_str()) {
+        Some("sigmf-meta" | "sigmf-data" | "sigmf") => path.with_extension(""),
+        _ => path.to_path_buf(),
+    };
+    let with = |ext: &str| {
+        let mut name = OsString::from(base.as_os_str());
+        name.push(ext);
+        PathBuf::from(name)
+    };
+
+    (with(".sigmf-meta"), with(".sigmf-data"))
+}
+
+/// Тип данных SigMF для формата выборок `.glos`.
+pub fn sigmf_datatype(
+    format: IqFormat,
+    little_endian: bool,
+) -> &'static str {
+    match (format, little_endian) {
+        (IqFormat::Int8, _) => "ci8",
+        (IqFormat::Int16, true) => "ci16_le",
+        (IqFormat::Int16, false) => "ci16_be",
+        (IqFormat::Float32, true) => "cf32_le",
+        (IqFormat::Float32, false) => "cf32_be",
+    }
+}
+
+/// Экспортирует запись в `.sigmf-meta` и `.sigmf-data` по пути `path`
+/// (см. [`sigmf_paths`]). Возвращает записанные метаданные.
+///
+/// `core:datetime` пишется, только если в заголовке есть время начала
+/// (метки блоков тогда — Unix-нс); иначе сегменты выделяются без времени.
+pub fn export_sigmf<R: Read>(
+    reader: &mut GlosReader<R>,
+    path: &Path,
+) -> GlosResult<SigmfMeta> {
+    let header = reader.header().clone();
+    let (meta_path, data_path) = sigmf_paths(path);
+
+    let rate = header.sample_rate as u64;
+    let absolute = header.timestamp_start != 0;
+    let frequency = Some(header.center_freq as f64);
+    // Метки округляются до нс: сдвиг в пределах периода — не разрыв
+    let tolerance = samples_to_ns(1, rate)?;
+
+    let mut data = BufWriter::new(File::create(&data_path)?);
+    let mut captures: Vec<SigmfCapture> = Vec::new();
+    let mut written: u64 = 0;
+    // Номер выборки и метка начала текущего сегмента
+    let mut segment: Option<(u64, u64)> = None;
+
+    while let Some(block) = reader.next_block() {
+        let block = block?;
+
+        let contiguous = match segment {
+            Some((start, ts)) => {
+                let expected = ts + samples_to_ns(written - start, rate)?;
+                block.timestamp_ns.abs_diff(expected) <= tolerance
+            }
+            None => false,
+        };
+        if !contiguous {
+            segment = Some((written, block.timestamp_ns));
+            captures.push(SigmfCapture {
+                sample_start: written,
+                frequency,
+                datetime: absolute.then(|| format_datetime(block.timestamp_ns)),
+            });
+        }
+
+        data.write_all(&block.data)?;
+        written += block.sample_count as u64;
+    }
+    data.flush()?;
+
+    if captures.is_empty() {
+        captures.push(SigmfCapture {
+            sample_start: 0,
+            frequency,
+            datetime: absolute.then(|| format_datetime(header.timestamp_start * 1_000_000_000)),
+        });
+    }
+
+    let meta = SigmfMeta {
+        global: SigmfGlobal {
+            datatype: sigmf_datatype(header.iq_format, header.samples_little_endian()).to_string(),
+            sample_rate: Some(header.sample_rate as f64),
+            version: SIGMF_VERSION.to_string(),
+            recorder: Some("glos".to_string()),
+            hw: (header.sdr_type != SdrType::Unknown).then(|| header.sdr_type.to_string()),
+            description: None,
+        },
+        captures,
+        annotations: Vec::new(),
+    };
+
+    let mut w = BufWriter::new(File::create(&meta_path)?);
+    serde_json::to_writer_pretty(&mut w, &meta)
+        .map_err(|e| GlosError::format_violation(e.to_string()))?;
+    w.flush()?;
+
+    Ok(meta)
+}
+
+/// Импортирует SigMF запись (`path` — см. [`sigmf_paths`]) в `.glos`,
+/// записываемый в `output`.
+pub fn import_sigmf<W: Write + Seek>(
+    path: &Path,
+    output: W,
+    options: &SigmfImportOptions,
+) -> GlosResult<SigmfImport> {
+    let (meta_path, data_path) = sigmf_paths(path);
+    let invalid =
+        |msg: &str| GlosError::format_violation(format!("{}: {msg}", meta_path.display()));
+
+    let meta: SigmfMeta = serde_json::from_reader(BufReader::new(File::open(&meta_path)?))
+        .map_err(|e| GlosError::corrupted(format!("{}: {e}", meta_path.display())))?;
+    let datatype = Datatype::parse(&meta.global.datatype)?;
+    let sample_size = datatype.format.sample_size();
+    let mut warnings = Vec::new();
+
+    let rate = match meta.global.sample_rate {
+        Some(r) if r.is_finite() && r >= 1.0 && r <= u32::MAX as f64 => r,
+        Some(_) => return Err(invalid("core:sample_rate out of range")),
+        None => return Err(invalid("core:sample_rate is required")),
+    };
+    if rate.fract() != 0.0 {
+        warnings.push(format!(
+            "Sample rate {rate} Hz rounded to {} Hz",
+            rate.round()
+        ));
+    }
+    let rate = rate.round() as u64;
+
+    let block_bytes = options.block_samples as usize * sample_size;
+    if options.block_samples == 0 || block_bytes > GLOS_MAX_BLOCK_SIZE {
+        return Err(GlosError::format_violation(format!(
+            "Block of {} samples does not fit 1..={GLOS_MAX_BLOCK_SIZE} bytes",
+            options.block_samples
+        )));
+    }
+
+    let mut captures = meta.captures.clone();
+    captures.sort_by_key(|c| c.sample_start);
+    if captures.is_empty() {
+        captures.push(SigmfCapture {
+            sample_start: 0,
+            frequency: None,
+            datetime: None,
+        });
+    }
+
+    let center = captures[0].frequency.unwrap_or(0.0);
+    if !center.is_finite() || center < 0.0 {
+        return Err(invalid("core:frequency out of range"));
+    }
+    if captures
+        .iter()
+        .any(|c| c.frequency.is_some_and(|f| f != center))
+    {
+        warnings.push(format!(
+            "Captures use several frequencies; header keeps {center} Hz"
+        ));
+    }
+
+    // Время начала каждого сегмента (нс); сегмент без `core:datetime`
+    // продолжает предыдущий
+    let mut starts: Vec<u64> = Vec::with_capacity(captures.len());
+    for (i, c) in captures.iter().enumerate() {
+        let ns = match &c.datetime {
+            Some(s) => {
+                parse_datetime(s).ok_or_else(|| invalid(&format!("invalid core:datetime '{s}'")))?
+            }
+            None => match i.checked_sub(1) {
+                Some(p) => {
+                    starts[p] + samples_to_ns(c.sample_start - captures[p].sample_start, rate)?
+                }
+                None => 0,
+            },
+        };
+        starts.push(ns);
+    }
+
+    let data_file = File::open(&data_path)?;
+    let data_bytes = data_file.metadata()?.len();
+    let total = data_bytes / sample_size as u64;
+    if data_bytes % sample_size as u64 != 0 {
+        warnings.push(format!(
+            "Data size is not a multiple of {sample_size} bytes; last {} bytes dropped",
+            data_bytes % sample_size as u64
+        ));
+    }
+
+    let mut header = GlosHeader::new(
+        meta.global
+            .hw
+            .as_deref()
+            .and_then(|hw| hw.parse().ok())
+            .unwrap_or(SdrType::Unknown),
+        rate as u32,
+        center.round() as u64,
+    );
+    header.iq_format = datatype.format;
+    header.compression = options.compression;
+    header.flags = if datatype.little_endian {
+        GLOS_FLAG_LITTLE_ENDIAN
+    } else {
+        0
+    };
+    header.timestamp_start = starts[0] / 1_000_000_000;
+
+    let mut writer = GlosWriter::new(output, header)?;
+    let mut data = BufReader::new(data_file);
+    data.seek(SeekFrom::Start(
+        captures[0].sample_start.min(total) * sample_size as u64,
+    ))?;
+
+    for (i, c) in captures.iter().enumerate() {
+        let end = captures
+            .get(i + 1)
+            .map_or(total, |n| n.sample_start)
+            .min(total);
+        let mut pos = c.sample_start.min(end);
+
+        while pos < end {
+            let n = (end - pos).min(options.block_samples as u64) as u32;
+            let mut buf = vec![0u8; n as usize * sample_size];
+            data.read_exact(&mut buf)?;
+
+            if datatype.unsigned {
+                buf.iter_mut().for_each(|b| *b ^= 0x80);
+            }
+
+            let timestamp_ns = starts[i] + samples_to_ns(pos - c.sample_start, rate)?;
+            writer.write_block(IqBlock::new(timestamp_ns, n, buf))?;
+            pos += n as u64;
+        }
+    }
+
+    let last = captures.len() - 1;
+    let end_ns =
+        starts[last] + samples_to_ns(total.saturating_sub(captures[last].sample_start), rate)?;
+
+    let blocks = writer.block_count();
+    let mut header = writer.header().clone();
+    header.total_samples = writer.total_samples();
+    header.timestamp_end = end_ns / 1_000_000_000;
+    writer.finish_at(header.timestamp_end)?;
+
+    Ok(SigmfImport {
+        header,
+        blocks,
+        captures: captures.len(),
+        warnings,
+    })
+}
+
+/// Unix-нс → `YYYY-MM-DDTHH:MM:SS.nnnnnnnnnZ`.
+fn format_datetime(ns: u64) -> String {
+    let secs = ns / 1_000_000_000;
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let t = secs % 86_400;
+
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}.{:09}Z",
+        t / 3600,
+        t / 60 % 60,
+        t % 60,
+        ns % 1_000_000_000
+    )
+}
+
+/// ISO 8601 в UTC (`Z` или `+00:00`, дробная часть до нс) → Unix-нс.
+fn parse_datetime(s: &str) -> Option<u64> {
+    let s = s.strip_suffix('Z').or_else(|| s.strip_suffix("+00:00"))?;
+    let (date, time) = s.split_once('T')?;
+    let (hms, frac) = time.split_once('.').unwrap_or((time, ""));
+
+    let [y, mo, d] = three_fields(date, '-')?;
+    let [h, mi, sec] = three_fields(hms, ':')?;
+    if !(1..=12).contains(&mo) || !(1..=31).contains(&d) || h > 23 || mi > 59 || sec > 60 {
+        return None;
+    }
+    if !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanos: u64 = format!("{frac:0<9}")[..9].parse().ok()?;
+
+    let secs = days_from_civil(y as i64, mo, d) * 86_400 + (h * 3600 + mi * 60 + sec) as i64;
+    u64::try_from(secs)
+        .ok()?
+        .checked_mul(1_000_000_000)?
+        .checked_add(nanos)
+}
+
+fn three_fields(
+    s: &str,
+    sep: char,
+) -> Option<[u32; 3]> {
+    let mut it = s.split(sep).map(|p| p.parse().ok());
+    let fields = [it.next()??, it.next()??, it.next()??];
+    it.next().is_none().then_some(fields)
+}
+
+/// Дни от 1970-01-01 → (год, месяц, день) по григорианскому календарю.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+fn days_from_civil(
+    y: i64,
+    m: u32,
+    d: u32,
+) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_all_blocks;
+
+    const T0: u64 = 1_700_000_000_000_000_000;
+
+    fn block(
+        timestamp_ns: u64,
+        samples: u32,
+        seed: u8,
+    ) -> IqBlock {
+        let data = (0..samples as usize * 4)
+            .map(|i| (i as u8).wrapping_mul(seed))
+            .collect();
+        IqBlock::new(timestamp_ns, samples, data)
+    }
+
+    #[test]
+    fn test_sigmf_roundtrip_keeps_captures() {
+        let dir = tempfile::tempdir().unwrap();
+        let glos = dir.path().join("rec.glos");
+
+        // 1 кГц: 100 выборок = 100 мс; после второго блока — пропуск 300 мс
+        let mut header = GlosHeader::new(SdrType::HackRf, 1_000, 1_602_000_000);
+        header.flags = GLOS_FLAG_LITTLE_ENDIAN;
+        header.timestamp_start = T0 / 1_000_000_000;
+        let blocks = [
+            block(T0, 100, 3),
+            block(T0 + 100_000_000, 100, 5),
+            block(T0 + 500_000_000, 100, 7),
+        ];
+
+        let mut writer = GlosWriter::new(File::create(&glos).unwrap(), header).unwrap();
+        for b in &blocks {
+            writer.write_block(b.clone()).unwrap();
+        }
+        writer.finish_at(T0 / 1_000_000_000 + 1).unwrap();
+
+        let mut reader = GlosReader::new(File::open(&glos).unwrap()).unwrap();
+        let meta = export_sigmf(&mut reader, &dir.path().join("rec.sigmf-meta")).unwrap();
+
+        assert_eq!(meta.global.datatype, "ci16_le");
+        assert_eq!(meta.global.sample_rate, Some(1_000.0));
+        assert_eq!(meta.global.hw.as_deref(), Some("hackrf"));
+        let starts: Vec<u64> = meta.captures.iter().map(|c| c.sample_start).collect();
+        assert_eq!(starts, [0, 200]);
+        assert_eq!(
+            meta.captures[1].datetime.as_deref(),
+            Some("2023-11-14T22:13:20.500000000Z")
+        );
+
+        let base = dir.path().join("rec");
+        assert_eq!(
+            std::fs::metadata(sigmf_paths(&base).1).unwrap().len(),
+            300 * 4
+        );
+
+        let back = dir.path().join("back.glos");
+        let options = SigmfImportOptions {
+            block_samples: 64,
+            ..Default::default()
+        };
+        let import = import_sigmf(&base, File::create(&back).unwrap(), &options).unwrap();
+        assert_eq!(import.captures, 2);
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+
+        let mut reader = GlosReader::new(File::open(&back).unwrap()).unwrap();
+        let header = reader.header().clone();
+        assert!(header.is_little_endian());
+        assert_eq!(header.sdr_type, SdrType::HackRf);
+        assert_eq!(header.center_freq, 1_602_000_000);
+        assert_eq!(header.total_samples, 300);
+        assert_eq!(header.timestamp_start, T0 / 1_000_000_000);
+
+        // 200 выборок первого сегмента: 64+64+64+8, затем второй сегмент
+        let imported = read_all_blocks(&mut reader).unwrap();
+        let counts: Vec<u32> = imported.iter().map(|b| b.sample_count).collect();
+        assert_eq!(counts, [64, 64, 64, 8, 64, 36]);
+        assert_eq!(imported[1].timestamp_ns, T0 + 64_000_000);
+        assert_eq!(imported[4].timestamp_ns, T0 + 500_000_000);
+
+        let original: Vec<u8> = blocks.iter().flat_map(|b| b.data.clone()).collect();
+        let restored: Vec<u8> = imported.iter().flat_map(|b| b.data.clone()).collect();
+        assert_eq!(restored, original);
+    }
+
+    #[test]
+    fn test_sigmf_import_cu8_without_datetime() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("rtl");
+        let (meta_path, data_path) = sigmf_paths(&base);
+
+        std::fs::write(
+            &meta_path,
+            r#"{
+                "global": { "core:datatype": "cu8", "core:sample_rate": 2048000.5, "core:version": "1.0.0" },
+                "captures": [{ "core:sample_start": 0, "core:frequency": 1575420000 }],
+                "annotations": []
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(&data_path, [128, 127, 255, 0, 7]).unwrap();
+
+        let out = dir.path().join("rtl.glos");
+        let import =
+            import_sigmf(&data_path, File::create(&out).unwrap(), &Default::default()).unwrap();
+        // Дробная частота и лишний байт
+        assert_eq!(import.warnings.len(), 2);
+        assert_eq!(import.header.sample_rate, 2_048_001);
+
+        let mut reader = GlosReader::new(File::open(&out).unwrap()).unwrap();
+        assert_eq!(reader.header().iq_format, IqFormat::Int8);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks[0].timestamp_ns, 0);
+        assert_eq!(blocks[0].data, [0, 255, 127, 128]);
+
+        std::fs::write(
+            &meta_path,
+            r#"{ "global": { "core:datatype": "ri16_le", "core:sample_rate": 1e6, "core:version": "1.0.0" } }"#,
+        )
+        .unwrap();
+        assert!(
+            import_sigmf(&base, std::io::Cursor::new(Vec::new()), &Default::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_sigmf_datetime_roundtrip() {
+        assert_eq!(format_datetime(0), "1970-01-01T00:00:00.000000000Z");
+        assert_eq!(parse_datetime("1970-01-01T00:00:00Z"), Some(0));
+
+        let ns = parse_datetime("2024-02-29T12:34:56.5Z").unwrap();
+        assert_eq!(format_datetime(ns), "2024-02-29T12:34:56.500000000Z");
+        assert_eq!(parse_datetime("2024-02-29T12:34:56.500+00:00"), Some(ns));
+        assert_eq!(format_datetime(T0 + 1), "2023-11-14T22:13:20.000000001Z");
+
+        assert_eq!(parse_datetime("2024-02-29 12:34:56Z"), None);
+        assert_eq!(parse_datetime("2024-02-29T12:34:56+03:00"), None);
+        assert_eq!(parse_datetime("1969-12-31T23:59:59Z"), None);
+        assert_eq!(parse_datetime("2024-13-01T00:00:00Z"), None);
+    }
+}
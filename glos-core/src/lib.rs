@@ -1,15 +1,143 @@
-pub mod binary;
+//! Ядро формата `.glos`: заголовок, блоки IQ, чтение/запись и DSP-утилиты.
+//!
+//! Публичный API перечислен явно ниже; для типичного использования
+//! достаточно `use glos_core::prelude::*;`. Внутренние помощники
+//! сериализации в API не входят.
+
+#![feature(portable_simd)]
+
+pub mod aligned;
+pub mod archive;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+mod binary;
+pub mod chunking;
+pub mod digest;
+pub mod dsp;
+pub mod edit;
 pub mod error;
 pub mod format;
+pub mod interop;
+pub mod metadata;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod ops;
+pub mod prelude;
+pub mod raw;
 pub mod serialization;
+pub mod simd;
+pub mod spec;
+pub mod testvec;
+pub mod udp;
 
-pub use binary::*;
-pub use error::*;
-pub use format::*;
-pub use serialization::*;
+pub use aligned::{AlignedBlock, AlignedBuf, DEFAULT_PAYLOAD_ALIGN, MAX_PAYLOAD_ALIGN};
+pub use archive::{
+    ArchiveEntry, ArchiveMember, GlosArchive, GlosArchiveWriter, GLOS_ARCHIVE_MAGIC,
+};
+#[cfg(feature = "tokio")]
+pub use async_io::{AsyncGlosReader, AsyncGlosWriter};
+pub use chunking::ContentChunker;
+pub use digest::{block_digest, SessionDigest, Xxh64};
+pub use edit::{
+    cut_range, patch_header, shard, unshard, CutRange, HeaderChanges, HeaderPatch, ShardBy,
+    ShardEntry, ShardManifest, SHARD_MANIFEST_VERSION,
+};
+pub use error::{CoreError, CoreResult};
+pub use format::{
+    crc32_checksum, GlosHeaderExt, IqBlockExt, DEFAULT_ZSTD_LEVEL, GLOS_BLOCK_ATTRIBUTES_SIZE,
+    GLOS_BLOCK_ATTRIBUTES_VERSION, GLOS_BLOCK_FLAG_GAIN_CHANGED, GLOS_BLOCK_FLAG_OVERLOAD,
+    GLOS_BLOCK_FLAG_SAMPLES_DROPPED, GLOS_BLOCK_FLAG_SYNC_MARKER, GLOS_BLOCK_HAS_ATTRIBUTES,
+    GLOS_BLOCK_OVERHEAD, GLOS_FLAG_CONTENT_DEFINED, GLOS_FLAG_IQ_SWAPPED, GLOS_FLAG_LITTLE_ENDIAN,
+    GLOS_FLAG_SAMPLES_LITTLE_ENDIAN, GLOS_FLAG_SPECTRUM_INVERTED, GLOS_HEADER_CRC_OFFSET,
+    GLOS_HEADER_SIZE, GLOS_MAGIC, GLOS_MAX_BLOCK_SIZE, GLOS_MAX_VERSION, GLOS_MIN_BLOCK_SIZE,
+    GLOS_SYNC_MARKER_ID_MAX, GLOS_VERSION, ZSTD_LEVELS,
+};
+pub use metadata::{ClockDrift, LogEntry, RecordingMetadata, SyncPoint};
+#[cfg(feature = "mmap")]
+pub use mmap::{GlosMmapReader, MappedBlock};
+pub use ops::{merge, split_by_samples, split_by_time, Merge, Split, SplitPart};
+pub use raw::{
+    export_raw, import_raw, RawExport, RawExportOptions, RawFormat, RawImport, RawImportOptions,
+    RAW_IMPORT_BLOCK_SAMPLES,
+};
+pub use serialization::{
+    read_all_blocks, read_blocks_limited, BlockCompressor, BlockOffsets, BlocksBetween,
+    CompressionOptions, CorruptionEvent, CorruptionKind, EveryNthBlock, FollowOptions, GapEvent,
+    GapPolicy, GlosReader, GlosWriter, RawBlockReader, ReadStats, ReaderOptions, SampleBuffer,
+    SeekPosition, DEFAULT_CHECKPOINT_INTERVAL, DEFAULT_FOLLOW_POLL, DEFAULT_READ_ALL_BUDGET,
+    INCOMPRESSIBLE_BACKOFF, INCOMPRESSIBLE_RATIO, MAX_CORRUPTION_EVENTS, MAX_GAP_EVENTS,
+};
+pub use udp::{
+    BlockCheck, StreamVerifier, UdpCompression, UdpFragment, UdpPacket, UdpPacketizer, UdpProtocol,
+    VerifyStats, UDP_FLAG_LZ4, UDP_FRAGMENT_COUNT_MASK, UDP_HEADER_SIZE, UDP_MAX_PAYLOAD,
+    UDP_SAMPLE_COUNT_SIZE, UDP_TIMESTAMP_SIZE, UDP_V2_HEADER_SIZE,
+};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+////////////////////////////////////////////////////////////////////////////////
+// Устаревшие псевдонимы
+////////////////////////////////////////////////////////////////////////////////
+
+/// Устаревшие помощники кодирования полей заголовка.
+///
+/// Раньше попадали в API через `pub use binary::*`. Оставлены на один цикл
+/// версий, чтобы внешние пользователи успели отказаться от них; будут
+/// удалены в 1.0.
+#[doc(hidden)]
+#[deprecated(
+    since = "0.2.0",
+    note = "internal header helper; use GlosHeaderExt::serialize/deserialize"
+)]
+pub fn read_u32_local(
+    buf: &[u8; GLOS_HEADER_SIZE],
+    off: &mut usize,
+    is_le: bool,
+) -> u32 {
+    binary::read_u32_local(buf, off, is_le)
+}
+
+#[doc(hidden)]
+#[deprecated(
+    since = "0.2.0",
+    note = "internal header helper; use GlosHeaderExt::serialize/deserialize"
+)]
+pub fn read_u64_local(
+    buf: &[u8; GLOS_HEADER_SIZE],
+    off: &mut usize,
+    is_le: bool,
+) -> u64 {
+    binary::read_u64_local(buf, off, is_le)
+}
+
+#[doc(hidden)]
+#[deprecated(
+    since = "0.2.0",
+    note = "internal header helper; use GlosHeaderExt::serialize/deserialize"
+)]
+pub fn write_u32_local(
+    buf: &mut [u8; GLOS_HEADER_SIZE],
+    off: &mut usize,
+    is_le: bool,
+    val: u32,
+) {
+    binary::write_u32_local(buf, off, is_le, val)
+}
+
+#[doc(hidden)]
+#[deprecated(
+    since = "0.2.0",
+    note = "internal header helper; use GlosHeaderExt::serialize/deserialize"
+)]
+pub fn write_u64_local(
+    buf: &mut [u8; GLOS_HEADER_SIZE],
+    off: &mut usize,
+    is_le: bool,
+    val: u64,
+) {
+    binary::write_u64_local(buf, off, is_le, val)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -19,4 +147,18 @@ mod tests {
         assert_eq!(GLOS_VERSION, 1);
         assert_eq!(GLOS_HEADER_SIZE, 128);
     }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_aliases_still_work() {
+        let mut buf = [0u8; GLOS_HEADER_SIZE];
+        let mut off = 0;
+
+        write_u32_local(&mut buf, &mut off, false, 0xDEAD_BEEF);
+        write_u64_local(&mut buf, &mut off, true, 42);
+
+        let mut off = 0;
+        assert_eq!(read_u32_local(&buf, &mut off, false), 0xDEAD_BEEF);
+        assert_eq!(read_u64_local(&buf, &mut off, true), 42);
+    }
 }
@@ -0,0 +1,244 @@
+//! Метаданные записи, не влезающие в 128-байтный заголовок: описание
+//! радиочасти ([`RfChain`]), миниатюра спектрограммы ([`Thumbnail`]), журнал
+//! сессии записи ([`LogEntry`]), дрейф часов устройства ([`ClockDrift`]),
+//! метки синхронизации ([`SyncPoint`]) и т.п.
+//!
+//! В формате v1 для них нет места в самом файле, поэтому они лежат рядом в
+//! JSON: `rec.glos` → `rec.glos.meta.json`. Поле `version` позволит позже
+//! перенести ту же структуру в секцию метаданных внутри `.glos`.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use glos_types::{GlosError, GlosResult, RfChain};
+use serde::{Deserialize, Serialize};
+
+use crate::dsp::Thumbnail;
+
+/// Версия формата файла метаданных.
+pub const METADATA_VERSION: u32 = 1;
+
+/// Метаданные одной записи.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecordingMetadata {
+    pub version: u32,
+    /// Антенна, кабель, МШУ и фильтры перед SDR
+    pub rf_chain: Option<RfChain>,
+    /// Миниатюра спектрограммы для предпросмотра
+    pub thumbnail: Option<Thumbnail>,
+    /// Предупреждения и ошибки рекордера во время записи (потери,
+    /// перестройки, смены усиления) — в порядке появления
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub session_log: Vec<LogEntry>,
+    /// Расхождение часов выборок устройства с часами хоста за сессию
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock_drift: Option<ClockDrift>,
+    /// Метки синхронизации с другими датчиками — в порядке записи
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sync_markers: Vec<SyncPoint>,
+}
+
+/// Оценка дрейфа часов выборок SDR относительно часов хоста.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClockDrift {
+    /// Отклонение частоты дискретизации от номинала, ppm: положительное —
+    /// устройство выдаёт больше выборок в секунду хоста, чем заявлено
+    pub ppm: f64,
+    /// На каком отрезке записи получена оценка (секунды хоста)
+    pub observed_secs: f64,
+    /// Метки блоков пересчитаны по измеренной частоте
+    pub timestamps_corrected: bool,
+}
+
+/// Метка синхронизации в записи: где она стоит и какое время ей
+/// соответствует по внешним часам.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncPoint {
+    /// Номер метки, за сессию растёт с 1
+    pub id: u32,
+    /// Время метки по внешним часам (нс)
+    pub external_ns: u64,
+    /// Метка блока-метки в файле (нс)
+    pub timestamp_ns: u64,
+    /// Номер выборки от начала записи, перед которой стоит метка; потерянные
+    /// выборки тоже считаются
+    pub sample_index: u64,
+}
+
+/// Одно событие журнала сессии.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Unix-время события (нс), в тех же часах, что метки блоков
+    pub timestamp_ns: u64,
+    /// Уровень: `WARN` или `ERROR`
+    pub level: String,
+    pub message: String,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RecordingMetadata {
+    /// Путь файла метаданных: `rec.glos` → `rec.glos.meta.json`.
+    pub fn sidecar_path(recording: &Path) -> PathBuf {
+        let mut name = recording.as_os_str().to_owned();
+        name.push(".meta.json");
+        PathBuf::from(name)
+    }
+
+    /// Ничего не записано.
+    pub fn is_empty(&self) -> bool {
+        self.rf_chain.as_ref().is_none_or(RfChain::is_empty)
+            && self.thumbnail.is_none()
+            && self.session_log.is_empty()
+            && self.clock_drift.is_none()
+            && self.sync_markers.is_empty()
+    }
+
+    /// Сохраняет метаданные рядом с записью. Возвращает путь файла.
+    pub fn save(
+        &self,
+        recording: &Path,
+    ) -> GlosResult<PathBuf> {
+        let path = Self::sidecar_path(recording);
+        let mut w = BufWriter::new(File::create(&path)?);
+
+        serde_json::to_writer_pretty(&mut w, self)
+            .map_err(|e| GlosError::format_violation(e.to_string()))?;
+        w.flush()?;
+
+        Ok(path)
+    }
+
+    /// Читает метаданные записи. `None` — файла метаданных нет.
+    pub fn load(recording: &Path) -> GlosResult<Option<Self>> {
+        let path = Self::sidecar_path(recording);
+
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let meta: Self = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| GlosError::corrupted(format!("{}: {e}", path.display())))?;
+
+        if meta.version > METADATA_VERSION {
+            return Err(GlosError::format_violation(format!(
+                "{}: unsupported metadata version {}",
+                path.display(),
+                meta.version
+            )));
+        }
+
+        Ok(Some(meta))
+    }
+
+    /// Миниатюра записи — только из файла метаданных, без чтения IQ.
+    pub fn load_thumbnail(recording: &Path) -> GlosResult<Option<Thumbnail>> {
+        Ok(Self::load(recording)?.and_then(|m| m.thumbnail))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Описание радиочасти из отдельного JSON-файла — в том же виде, что поле
+/// `rf_chain` файла метаданных (так его задаёт рекордер).
+pub fn read_rf_chain(path: &Path) -> GlosResult<RfChain> {
+    let file = File::open(path)?;
+
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| GlosError::corrupted(format!("{}: {e}", path.display())))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for RecordingMetadata {
+    fn default() -> Self {
+        Self {
+            version: METADATA_VERSION,
+            rf_chain: None,
+            thumbnail: None,
+            session_log: Vec::new(),
+            clock_drift: None,
+            sync_markers: Vec::new(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::{Antenna, AntennaKind, Lna, RfFilter};
+
+    use super::*;
+
+    #[test]
+    fn test_rf_chain_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let recording = dir.path().join("rec.glos");
+
+        assert_eq!(RecordingMetadata::load(&recording).unwrap(), None);
+
+        let meta = RecordingMetadata {
+            rf_chain: Some(RfChain {
+                antenna: Some(Antenna {
+                    kind: AntennaKind::ChokeRing,
+                    model: Some("NovAtel GNSS-750".into()),
+                    gain_dbi: Some(5.0),
+                }),
+                cable_loss_db: Some(2.5),
+                lna: Some(Lna {
+                    gain_db: 28.0,
+                    noise_figure_db: Some(0.9),
+                }),
+                filters: vec![RfFilter {
+                    name: "SAW".into(),
+                    center_hz: Some(1_602_000_000),
+                    bandwidth_hz: Some(10_000_000),
+                    insertion_loss_db: Some(1.5),
+                }],
+            }),
+            ..Default::default()
+        };
+
+        let path = meta.save(&recording).unwrap();
+        assert_eq!(path, dir.path().join("rec.glos.meta.json"));
+
+        let loaded = RecordingMetadata::load(&recording).unwrap().unwrap();
+        assert_eq!(loaded, meta);
+
+        let chain = loaded.rf_chain.unwrap();
+        assert!((chain.net_gain_db() - 24.0).abs() < 1e-6);
+        assert_eq!(
+            chain.to_string(),
+            "choke_ring NovAtel GNSS-750 (+5.0 dBi) → cable -2.5 dB → LNA +28.0 dB \
+             (NF 0.9 dB) → SAW 1602.000 MHz/10.000 MHz -1.5 dB → net +24.0 dB"
+        );
+
+        // Отсутствующие поля (и сама версия) — по умолчанию
+        std::fs::write(
+            &path,
+            r#"{"rf_chain": {"lna": {"gain_db": 20.0}, "antenna": {"kind": "patch"}}}"#,
+        )
+        .unwrap();
+        let partial = RecordingMetadata::load(&recording).unwrap().unwrap();
+        assert_eq!(partial.version, METADATA_VERSION);
+        assert_eq!(
+            partial.rf_chain.unwrap().to_string(),
+            "patch → LNA +20.0 dB → net +20.0 dB"
+        );
+    }
+}
@@ -0,0 +1,570 @@
+//! Чтение завершённых `.glos` через отображение файла в память (фича
+//! `mmap`).
+//!
+//! [`GlosReader`](crate::GlosReader) копирует каждый блок из `BufReader` во
+//! временный буфер, а затем в `IqBlock::data`; на многогигабайтных записях
+//! это основная статья расходов. [`GlosMmapReader`] один раз строит индекс
+//! кадров и отдаёт данные блоков срезами прямо из отображения, а CRC
+//! проверяет параллельно на пуле rayon.
+//!
+//! Восстановление после повреждений то же, что у потокового читателя:
+//! кадр принимается только при верном `content_size` и CRC, после мусора
+//! поиск продолжается побайтово.
+
+use std::{borrow::Cow, fs::File, path::Path};
+
+use glos_types::{
+    BlockAttributes, Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat,
+};
+use memmap2::Mmap;
+use rayon::prelude::*;
+
+use crate::{
+    crc32_checksum,
+    format::{
+        block_frame_size, check_sample_count, decode_attributes, decompress_payload, frame_payload,
+        frame_sample_count,
+    },
+    serialization::read_header,
+    CorruptionEvent, CorruptionKind, ReadStats, ReaderOptions, SyncPoint, GLOS_BLOCK_OVERHEAD,
+    GLOS_HEADER_SIZE, MAX_CORRUPTION_EVENTS,
+};
+
+/// Читатель завершённого GLOS файла, отображённого в память.
+///
+/// Файл не должен меняться, пока читатель жив: усечение отображённого
+/// файла другим процессом приводит к `SIGBUS`. Для файлов, которые ещё
+/// пишутся, используйте [`GlosReader`](crate::GlosReader).
+pub struct GlosMmapReader {
+    map: Mmap,
+    header: GlosHeader,
+    frames: Vec<FrameRef>,
+    stats: ReadStats,
+    sync_points: Vec<SyncPoint>,
+}
+
+/// Блок, данные которого указывают прямо в отображение файла.
+#[derive(Debug, Clone, Copy)]
+pub struct MappedBlock<'a> {
+    /// Смещение кадра от начала файла.
+    pub offset: u64,
+    pub timestamp_ns: u64,
+    pub sample_count: u32,
+    /// Данные блока как они лежат в файле (сжатые, если файл сжат).
+    pub payload: &'a [u8],
+    /// События приёмника за время блока
+    pub attributes: BlockAttributes,
+    compressed: bool,
+    iq_format: IqFormat,
+}
+
+/// Кадр блока в отображении; CRC уже проверен.
+#[derive(Debug, Clone, Copy)]
+struct FrameRef {
+    offset: usize,
+    size: usize,
+}
+
+/// Построение индекса кадров.
+struct IndexBuilder<'a> {
+    data: &'a [u8],
+    sample_rate: u32,
+    frames: Vec<FrameRef>,
+    stats: ReadStats,
+    sync_points: Vec<SyncPoint>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl GlosMmapReader {
+    /// Отображает файл в память, читает заголовок и строит индекс блоков.
+    pub fn open<P: AsRef<Path>>(path: P) -> GlosResult<Self> {
+        let file = File::open(path)?;
+        // SAFETY: читатель рассчитан на завершённые файлы, которые никто не
+        // меняет (см. документацию типа); данные только читаются.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let hdr_buf: &[u8; GLOS_HEADER_SIZE] = map
+            .get(..GLOS_HEADER_SIZE)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| GlosError::corrupted("File shorter than header"))?;
+        let header = read_header(hdr_buf, ReaderOptions::default())?;
+
+        let (frames, stats, sync_points) = IndexBuilder::new(&map, header.sample_rate).build();
+
+        Ok(Self {
+            map,
+            header,
+            frames,
+            stats,
+            sync_points,
+        })
+    }
+
+    pub fn header(&self) -> &GlosHeader {
+        &self.header
+    }
+
+    /// Статистика построения индекса: целые блоки и повреждения.
+    pub fn stats(&self) -> &ReadStats {
+        &self.stats
+    }
+
+    /// Метки синхронизации из индекса, по порядку. Номер выборки считается
+    /// по целым блокам и потерям из их атрибутов: выборки повреждённых
+    /// блоков в него не входят.
+    pub fn sync_points(&self) -> &[SyncPoint] {
+        &self.sync_points
+    }
+
+    /// Число целых блоков в файле.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Блок с номером `index` (среди целых блоков).
+    pub fn block(
+        &self,
+        index: usize,
+    ) -> Option<MappedBlock<'_>> {
+        self.frames.get(index).map(|&frame| self.mapped(frame))
+    }
+
+    /// Все целые блоки по порядку.
+    pub fn blocks(&self) -> impl ExactSizeIterator<Item = MappedBlock<'_>> + '_ {
+        self.frames.iter().map(|&frame| self.mapped(frame))
+    }
+
+    /// Все целые блоки как параллельный итератор rayon (нужен
+    /// `rayon::prelude::*`). Порядок сохраняется при `collect`.
+    pub fn par_blocks(&self) -> impl IndexedParallelIterator<Item = MappedBlock<'_>> + '_ {
+        self.frames.par_iter().map(|&frame| self.mapped(frame))
+    }
+
+    fn mapped(
+        &self,
+        frame: FrameRef,
+    ) -> MappedBlock<'_> {
+        let buf = &self.map[frame.offset..frame.offset + frame.size];
+
+        MappedBlock {
+            offset: frame.offset as u64,
+            timestamp_ns: frame_timestamp(buf),
+            sample_count: frame_sample_count(buf),
+            // Кадр принят только с допустимым полем атрибутов, см. `crc_ok`
+            payload: &buf[frame_payload(buf).unwrap_or(16..16)],
+            attributes: decode_attributes(buf),
+            compressed: self.header.compression != Compression::None,
+            iq_format: self.header.iq_format,
+        }
+    }
+}
+
+impl<'a> MappedBlock<'a> {
+    pub fn is_compressed(self) -> bool {
+        self.compressed
+    }
+
+    /// Несжатые IQ данные: для несжатых файлов — срез без копирования.
+    /// Проверяет `sample_count` так же, как потоковый читатель.
+    pub fn samples(self) -> GlosResult<Cow<'a, [u8]>> {
+        let data = if self.compressed {
+            Cow::Owned(decompress_payload(self.payload)?)
+        } else {
+            Cow::Borrowed(self.payload)
+        };
+
+        check_sample_count(self.sample_count, data.len(), self.iq_format)?;
+
+        Ok(data)
+    }
+
+    /// Копия блока в виде [`IqBlock`] без распаковки — для переписывания
+    /// через [`GlosWriter`](crate::GlosWriter) без повторного сжатия.
+    pub fn to_block(self) -> IqBlock {
+        IqBlock {
+            timestamp_ns: self.timestamp_ns,
+            sample_count: self.sample_count,
+            data: self.payload.to_vec(),
+            is_compressed: self.compressed,
+            attributes: self.attributes,
+        }
+    }
+}
+
+impl<'a> IndexBuilder<'a> {
+    fn new(
+        data: &'a [u8],
+        sample_rate: u32,
+    ) -> Self {
+        Self {
+            data,
+            sample_rate,
+            frames: Vec::new(),
+            stats: ReadStats::default(),
+            sync_points: Vec::new(),
+        }
+    }
+
+    fn build(mut self) -> (Vec<FrameRef>, ReadStats, Vec<SyncPoint>) {
+        let mut pos = GLOS_HEADER_SIZE;
+
+        while let Some(start) = self.resync(pos, self.data.len()) {
+            pos = self.walk(start.offset);
+        }
+
+        (self.frames, self.stats, self.sync_points)
+    }
+
+    /// Проходит кадры от целого кадра в `start` по их `content_size`, затем
+    /// параллельно проверяет CRC. Промежутки между целыми кадрами
+    /// (повреждённый размер сбил проход) пересматриваются побайтово.
+    /// Возвращает смещение за последним принятым кадром.
+    fn walk(
+        &mut self,
+        start: usize,
+    ) -> usize {
+        let mut candidates = Vec::new();
+        let mut pos = start;
+        while let Some(size) = self.frame_size(pos, self.data.len()) {
+            candidates.push(FrameRef { offset: pos, size });
+            pos += size;
+        }
+
+        let data = self.data;
+        let intact: Vec<bool> = candidates
+            .par_iter()
+            .map(|&frame| crc_ok(data, frame))
+            .collect();
+
+        let mut pos = start;
+        for (frame, ok) in candidates.into_iter().zip(intact) {
+            if !ok {
+                continue;
+            }
+            while let Some(found) = self.resync(pos, frame.offset) {
+                pos = self.accept(found);
+            }
+            pos = self.accept(frame);
+        }
+
+        pos
+    }
+
+    /// Ищет в `[from, end)` первый кадр с верным размером и CRC. Если он
+    /// начинается не в `from`, отмечает повреждение в `from`. Кадр, не
+    /// помещающийся в файл (усечённый хвост), повреждением не считается.
+    fn resync(
+        &mut self,
+        from: usize,
+        end: usize,
+    ) -> Option<FrameRef> {
+        let mut kind = None;
+        let mut pos = from;
+
+        while pos + GLOS_BLOCK_OVERHEAD <= end {
+            if let Some(size) = self.frame_size(pos, end) {
+                let frame = FrameRef { offset: pos, size };
+                if crc_ok(self.data, frame) {
+                    if pos != from {
+                        self.count_corrupted(from, kind.unwrap_or(CorruptionKind::Malformed));
+                    }
+                    return Some(frame);
+                }
+                kind = Some(CorruptionKind::Crc);
+            }
+            pos += 1;
+        }
+
+        if let Some(kind) = kind {
+            self.count_corrupted(from, kind);
+        }
+
+        None
+    }
+
+    /// Полный размер кадра в `pos`, если `content_size` допустим и кадр
+    /// заканчивается не дальше `end`.
+    fn frame_size(
+        &self,
+        pos: usize,
+        end: usize,
+    ) -> Option<usize> {
+        let size = block_frame_size(self.data.get(pos..end)?).ok()?;
+        (pos + size <= end).then_some(size)
+    }
+
+    fn accept(
+        &mut self,
+        frame: FrameRef,
+    ) -> usize {
+        let buf = &self.data[frame.offset..frame.offset + frame.size];
+        let sample_count = frame_sample_count(buf);
+
+        self.frames.push(frame);
+        self.stats.blocks_ok += 1;
+        self.stats.samples_recovered = self
+            .stats
+            .samples_recovered
+            .saturating_add(sample_count as u64);
+        self.stats.bytes_processed = self.stats.bytes_processed.saturating_add(frame.size as u64);
+        let timestamp_ns = frame_timestamp(buf);
+        let attributes = decode_attributes(buf);
+        self.stats
+            .count_gap(timestamp_ns, attributes, self.sample_rate);
+        if let Some(marker) = attributes.sync_marker {
+            self.sync_points.push(SyncPoint {
+                id: marker.id,
+                external_ns: marker.external_ns,
+                timestamp_ns,
+                sample_index: self.stats.samples_recovered + self.stats.samples_dropped,
+            });
+        }
+
+        frame.offset + frame.size
+    }
+
+    fn count_corrupted(
+        &mut self,
+        offset: usize,
+        kind: CorruptionKind,
+    ) {
+        self.stats.blocks_corrupted += 1;
+        if self.stats.corruption_events.len() >= MAX_CORRUPTION_EVENTS {
+            return;
+        }
+
+        // Ожидаемое время — конец последнего целого блока
+        let timestamp_guess = self.frames.last().map(|frame| {
+            let buf = &self.data[frame.offset..];
+            let duration_ns =
+                frame_sample_count(buf) as u128 * 1_000_000_000 / self.sample_rate.max(1) as u128;
+            frame_timestamp(buf).saturating_add(duration_ns as u64)
+        });
+
+        self.stats.corruption_events.push(CorruptionEvent {
+            approx_offset: offset as u64,
+            kind,
+            timestamp_guess,
+        });
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// CRC32 кадра покрывает байты `[4..size - 4]`; поле атрибутов, если оно
+/// заявлено, должно поместиться в кадр.
+fn crc_ok(
+    data: &[u8],
+    frame: FrameRef,
+) -> bool {
+    let buf = &data[frame.offset..frame.offset + frame.size];
+    let (content, stored) = buf[4..].split_at(frame.size - 8);
+
+    u32::from_be_bytes([stored[0], stored[1], stored[2], stored[3]]) == crc32_checksum(content)
+        && frame_payload(buf).is_some()
+}
+
+fn frame_timestamp(buf: &[u8]) -> u64 {
+    u64::from_be_bytes([
+        buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15],
+    ])
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use glos_types::{SdrType, SyncMarker};
+
+    use super::*;
+    use crate::{GlosHeaderExt, GlosReader, GlosWriter, IqBlockExt};
+
+    fn write_file(
+        compression: Compression,
+        corrupt: impl FnOnce(&mut Vec<u8>),
+    ) -> (tempfile::NamedTempFile, Vec<u8>) {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.compression = compression;
+
+        let mut bytes = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut bytes), header).unwrap();
+        for i in 0..6u64 {
+            let data = (0..4_000).map(|j| (j % 17 + i as usize) as u8).collect();
+            writer
+                .write_block(IqBlock::new(i * 1_000_000, 1_000, data))
+                .unwrap();
+        }
+        writer.finish_at(0).unwrap();
+        corrupt(&mut bytes);
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+        (file, bytes)
+    }
+
+    #[test]
+    fn test_mmap_reader_matches_stream_reader() {
+        for compression in [Compression::None, Compression::Lz4] {
+            let (file, bytes) = write_file(compression, |_| {});
+            let reader = GlosMmapReader::open(file.path()).unwrap();
+
+            let expected: Vec<_> = GlosReader::new(Cursor::new(bytes))
+                .unwrap()
+                .blocks_with_offsets()
+                .map(|r| r.unwrap())
+                .map(|(offset, b)| (offset, b.timestamp_ns, b.data))
+                .collect();
+            let actual: Vec<_> = reader
+                .par_blocks()
+                .map(|b| (b.offset, b.timestamp_ns, b.samples().unwrap().into_owned()))
+                .collect();
+
+            assert_eq!(actual, expected);
+            assert_eq!(reader.stats().samples_recovered, 6_000);
+            assert!(reader.stats().corruption_events.is_empty());
+        }
+
+        // Несжатые данные отдаются без копирования
+        let (file, _) = write_file(Compression::None, |_| {});
+        let reader = GlosMmapReader::open(file.path()).unwrap();
+        let block = reader.block(0).unwrap();
+        assert!(matches!(block.samples().unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_mmap_reader_resyncs_after_corruption() {
+        let frame = 4_000 + GLOS_BLOCK_OVERHEAD;
+        let (file, bytes) = write_file(Compression::None, |bytes| {
+            // Данные второго блока и content_size четвёртого
+            bytes[GLOS_HEADER_SIZE + frame + 100] ^= 0xFF;
+            bytes[GLOS_HEADER_SIZE + 3 * frame + 2] ^= 0x01;
+        });
+        let reader = GlosMmapReader::open(file.path()).unwrap();
+
+        let mut stream = GlosReader::new(Cursor::new(bytes)).unwrap();
+        let expected: Vec<_> = stream
+            .blocks_with_offsets()
+            .filter_map(Result::ok)
+            .map(|(offset, b)| (offset, b.timestamp_ns))
+            .collect();
+        let actual: Vec<_> = reader
+            .blocks()
+            .map(|b| (b.offset, b.timestamp_ns))
+            .collect();
+
+        assert_eq!(actual, expected);
+        assert_eq!(reader.len(), 4);
+
+        let events = &reader.stats().corruption_events;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].approx_offset, (GLOS_HEADER_SIZE + frame) as u64);
+        assert_eq!(events[0].kind, CorruptionKind::Crc);
+        assert_eq!(events[0].timestamp_guess, Some(500_000));
+        assert_eq!(
+            events[1].approx_offset,
+            (GLOS_HEADER_SIZE + 3 * frame) as u64
+        );
+    }
+
+    #[test]
+    fn test_mmap_reader_block_attributes() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.version = crate::GLOS_BLOCK_ATTRIBUTES_VERSION;
+        let attributes = BlockAttributes {
+            overload: true,
+            gain_db: Some(12.0),
+            dropped_samples: Some(500),
+            sync_marker: None,
+        };
+
+        let mut bytes = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut bytes), header).unwrap();
+        for i in 0..3u64 {
+            let mut block = IqBlock::new(i * 1_000_000, 1_000, vec![i as u8; 4_000]);
+            if i == 1 {
+                block.attributes = attributes;
+            }
+            writer.write_block(block).unwrap();
+        }
+        writer.finish_at(0).unwrap();
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+        let reader = GlosMmapReader::open(file.path()).unwrap();
+
+        assert_eq!(reader.len(), 3);
+        assert_eq!(reader.stats().samples_recovered, 3_000);
+        assert_eq!(reader.stats().samples_dropped, 500);
+        assert_eq!(
+            reader.stats().gaps,
+            [crate::GapEvent {
+                start_ns: 750_000,
+                end_ns: 1_000_000,
+                samples: 500,
+            }]
+        );
+        let block = reader.block(1).unwrap();
+        assert_eq!(block.sample_count, 1_000);
+        assert_eq!(block.attributes, attributes);
+        assert_eq!(block.samples().unwrap().as_ref(), &[1u8; 4_000][..]);
+        assert_eq!(block.to_block().attributes, attributes);
+        assert!(reader.block(2).unwrap().attributes.is_empty());
+        assert!(reader.sync_points().is_empty());
+    }
+
+    #[test]
+    fn test_mmap_reader_sync_points() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.version = crate::GLOS_BLOCK_ATTRIBUTES_VERSION;
+        header.compression = Compression::Lz4;
+        let marker = SyncMarker {
+            id: 1,
+            external_ns: 42_000,
+        };
+
+        let mut bytes = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut bytes), header).unwrap();
+        writer
+            .write_block(IqBlock::new(0, 1_000, vec![1u8; 4_000]))
+            .unwrap();
+        writer
+            .write_block(IqBlock::new_sync_marker(500_000, marker))
+            .unwrap();
+        let mut after = IqBlock::new(750_000, 1_000, vec![2u8; 4_000]);
+        after.attributes.dropped_samples = Some(500);
+        writer.write_block(after).unwrap();
+        writer.finish_at(0).unwrap();
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+        let reader = GlosMmapReader::open(file.path()).unwrap();
+
+        assert_eq!(reader.len(), 3);
+        assert_eq!(
+            reader.sync_points(),
+            [SyncPoint {
+                id: 1,
+                external_ns: 42_000,
+                timestamp_ns: 500_000,
+                sample_index: 1_000,
+            }]
+        );
+        let block = reader.block(1).unwrap();
+        assert_eq!(block.sample_count, 0);
+        assert!(block.samples().unwrap().is_empty());
+        assert_eq!(reader.stats().samples_dropped, 500);
+    }
+}
@@ -0,0 +1,452 @@
+//! Разбиение и склейка записей по границам блоков.
+//!
+//! Поделиться десятью секундами часовой записи или собрать сессию из
+//! нескольких файлов без собственного кода. [`split_by_time`] и
+//! [`split_by_samples`] режут запись на куски, не разрезая блоков (вырезка
+//! с точностью до выборки — [`crate::cut_range`]); [`merge`] склеивает
+//! совместимые записи в одну. Заголовок каждого выходного файла описывает
+//! только его блоки: время начала, окончания и число выборок пересчитаны.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glos_types::{GlosError, GlosHeader, GlosResult, IqBlock};
+
+use crate::{GlosReader, GlosWriter};
+
+/// Один файл, записанный [`split_by_time`] или [`split_by_samples`].
+#[derive(Debug, Clone)]
+pub struct SplitPart {
+    pub path: PathBuf,
+    /// Заголовок файла (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    /// Метка первой выборки и конец последней (нс)
+    pub start_ns: u64,
+    pub end_ns: u64,
+}
+
+/// Итог разбиения.
+#[derive(Debug, Clone, Default)]
+pub struct Split {
+    pub parts: Vec<SplitPart>,
+    /// Пропущено блоков с неверной CRC
+    pub corrupted_blocks: u64,
+}
+
+/// Итог [`merge`].
+#[derive(Debug, Clone)]
+pub struct Merge {
+    /// Заголовок склеенного файла (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    /// Пропущено блоков с неверной CRC во всех входных файлах
+    pub corrupted_blocks: u64,
+}
+
+/// Текущий выходной файл разбиения.
+struct PartWriter {
+    writer: GlosWriter<File>,
+    part: SplitPart,
+    samples: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Split {
+    /// Выборок во всех частях.
+    pub fn total_samples(&self) -> u64 {
+        self.parts.iter().map(|p| p.header.total_samples).sum()
+    }
+}
+
+impl PartWriter {
+    fn create(
+        path: PathBuf,
+        header: &GlosHeader,
+        first: &IqBlock,
+    ) -> GlosResult<Self> {
+        let mut header = header.clone();
+        header.timestamp_start = first.timestamp_ns / 1_000_000_000;
+
+        Ok(Self {
+            writer: GlosWriter::new(File::create(&path)?, header.clone())?,
+            part: SplitPart {
+                path,
+                header,
+                blocks: 0,
+                start_ns: first.timestamp_ns,
+                end_ns: first.timestamp_ns,
+            },
+            samples: 0,
+        })
+    }
+
+    fn write(
+        &mut self,
+        block: IqBlock,
+        end_ns: u64,
+    ) -> GlosResult<()> {
+        self.samples += block.sample_count as u64;
+        self.writer.write_block(block)?;
+
+        self.part.blocks += 1;
+        self.part.end_ns = end_ns;
+
+        Ok(())
+    }
+
+    fn finish(self) -> GlosResult<SplitPart> {
+        let mut part = self.part;
+        part.header = self.writer.header().clone();
+        part.header.total_samples = self.samples;
+        part.header.timestamp_end = part.end_ns / 1_000_000_000;
+        self.writer.finish_at(part.header.timestamp_end)?;
+
+        Ok(part)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Режет запись на куски длительностью `piece` по меткам блоков.
+///
+/// Новый кусок начинается с первого блока, метка которого не раньше
+/// `начало куска + piece`, так что кусок может быть длиннее на блок.
+/// Файлы именуются по `output` с номером: `slice.glos` → `slice_000.glos`,
+/// `slice_001.glos`, …
+pub fn split_by_time<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    piece: Duration,
+) -> GlosResult<Split> {
+    let piece_ns = piece.as_nanos() as u64;
+    if piece_ns == 0 {
+        return Err(GlosError::format_violation("split duration must be > 0"));
+    }
+
+    split_with(input.as_ref(), output.as_ref(), |part, block| {
+        block.timestamp_ns >= part.part.start_ns.saturating_add(piece_ns)
+    })
+}
+
+/// Режет запись на куски не больше `samples` выборок.
+///
+/// Блок, который не помещается в текущий кусок, начинает следующий; блок
+/// больше предела получает свой файл. Имена — как у [`split_by_time`].
+pub fn split_by_samples<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    samples: u64,
+) -> GlosResult<Split> {
+    if samples == 0 {
+        return Err(GlosError::format_violation(
+            "split size must be > 0 samples",
+        ));
+    }
+
+    split_with(input.as_ref(), output.as_ref(), |part, block| {
+        part.samples > 0 && part.samples + block.sample_count as u64 > samples
+    })
+}
+
+/// Склеивает записи `inputs` по порядку в `output`.
+///
+/// Частота дискретизации, центр, формат и флаги у всех файлов должны
+/// совпадать, и каждый следующий файл должен начинаться не раньше конца
+/// предыдущего; иначе ошибка, и `output` не создаётся (проверяются
+/// заголовки до записи) или остаётся неполным (перекрытие находится по
+/// блокам). Заголовок и сжатие — от первого файла, `timestamp_end` —
+/// наибольший из входных и конца последнего блока.
+pub fn merge<P: AsRef<Path>, Q: AsRef<Path>>(
+    inputs: &[P],
+    output: Q,
+) -> GlosResult<Merge> {
+    let output = output.as_ref();
+    let Some(first) = inputs.first() else {
+        return Err(GlosError::format_violation("nothing to merge"));
+    };
+
+    let header = GlosReader::new(File::open(first)?)?.header().clone();
+    for input in inputs {
+        let input = input.as_ref();
+        if input == output {
+            return Err(GlosError::format_violation(format!(
+                "output {output:?} is one of the inputs"
+            )));
+        }
+
+        let other = GlosReader::new(File::open(input)?)?.header().clone();
+        let differs = incompatible_fields(&header, &other);
+        if !differs.is_empty() {
+            return Err(GlosError::format_violation(format!(
+                "{input:?} differs from {:?} in {}",
+                first.as_ref(),
+                differs.join(", ")
+            )));
+        }
+    }
+
+    let rate = header.sample_rate.max(1) as u128;
+    let mut writer = GlosWriter::new(File::create(output)?, header.clone())?;
+    let mut corrupted_blocks = 0;
+    let mut timestamp_end = 0;
+    let mut end_ns: Option<u64> = None;
+
+    for input in inputs {
+        let input = input.as_ref();
+        let mut reader = GlosReader::new(File::open(input)?)?;
+        timestamp_end = timestamp_end.max(reader.header().timestamp_end);
+        let mut first_block = true;
+
+        while let Some(result) = reader.next_block() {
+            let block = match result {
+                Ok(b) => b,
+                Err(GlosError::CrcMismatch { .. }) => {
+                    corrupted_blocks += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            if first_block && end_ns.is_some_and(|end| block.timestamp_ns < end) {
+                return Err(GlosError::format_violation(format!(
+                    "{input:?} starts before the end of the previous input"
+                )));
+            }
+            first_block = false;
+
+            end_ns = Some(
+                block.timestamp_ns + (block.sample_count as u128 * 1_000_000_000 / rate) as u64,
+            );
+            writer.write_block(block)?;
+        }
+    }
+
+    let mut merged = writer.header().clone();
+    merged.total_samples = writer.total_samples();
+    merged.timestamp_end = timestamp_end.max(end_ns.unwrap_or(0) / 1_000_000_000);
+    let blocks = writer.block_count();
+    writer.finish_at(merged.timestamp_end)?;
+
+    Ok(Merge {
+        header: merged,
+        blocks,
+        corrupted_blocks,
+    })
+}
+
+/// Поля заголовка, из-за которых записи нельзя склеить.
+fn incompatible_fields(
+    a: &GlosHeader,
+    b: &GlosHeader,
+) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+
+    if a.sample_rate != b.sample_rate {
+        fields.push("sample_rate");
+    }
+    if a.center_freq != b.center_freq {
+        fields.push("center_freq");
+    }
+    if a.iq_format != b.iq_format {
+        fields.push("iq_format");
+    }
+    if a.flags != b.flags {
+        fields.push("flags");
+    }
+
+    fields
+}
+
+/// Общая часть разбиения: `starts_new` решает, начать ли с блока новый файл.
+fn split_with(
+    input: &Path,
+    output: &Path,
+    mut starts_new: impl FnMut(&PartWriter, &IqBlock) -> bool,
+) -> GlosResult<Split> {
+    let mut reader = GlosReader::new(File::open(input)?)?;
+    let header = reader.header().clone();
+    let rate = header.sample_rate.max(1) as u128;
+
+    let mut split = Split::default();
+    let mut current: Option<PartWriter> = None;
+
+    while let Some(result) = reader.next_block() {
+        let block = match result {
+            Ok(b) => b,
+            Err(GlosError::CrcMismatch { .. }) => {
+                split.corrupted_blocks += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(part) = current.take_if(|p| starts_new(p, &block)) {
+            split.parts.push(part.finish()?);
+        }
+
+        let part = match current.as_mut() {
+            Some(p) => p,
+            None => {
+                let path = part_path(output, split.parts.len());
+                if path == input {
+                    return Err(GlosError::format_violation(format!(
+                        "output {path:?} would overwrite the input"
+                    )));
+                }
+                current.insert(PartWriter::create(path, &header, &block)?)
+            }
+        };
+
+        let end_ns =
+            block.timestamp_ns + (block.sample_count as u128 * 1_000_000_000 / rate) as u64;
+        part.write(block, end_ns)?;
+    }
+
+    match current {
+        Some(part) => split.parts.push(part.finish()?),
+        None if split.parts.is_empty() => {
+            return Err(GlosError::format_violation(format!(
+                "{input:?} has no blocks to split"
+            )))
+        }
+        None => {}
+    }
+
+    Ok(split)
+}
+
+/// `slice.glos` → `slice_003.glos`.
+pub(crate) fn part_path(
+    output: &Path,
+    index: usize,
+) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match output.extension() {
+        Some(ext) => format!("{stem}_{index:03}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{index:03}"),
+    };
+
+    output.with_file_name(name)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::SdrType;
+
+    use super::*;
+    use crate::{read_all_blocks, GlosHeaderExt, IqBlockExt};
+
+    /// Блоки по 1000 выборок (500 мкс при 2 Msps) с метками `starts` (мкс).
+    fn write_file(
+        path: &Path,
+        starts_us: &[u64],
+    ) {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.timestamp_start = starts_us[0] / 1_000_000;
+
+        let mut writer = GlosWriter::new(File::create(path).unwrap(), header).unwrap();
+        for (i, us) in starts_us.iter().enumerate() {
+            writer
+                .write_block(IqBlock::new(us * 1_000, 1_000, vec![i as u8; 4_000]))
+                .unwrap();
+        }
+        writer.finish_at(starts_us[0] / 1_000_000).unwrap();
+    }
+
+    fn blocks(path: &Path) -> (GlosHeader, Vec<IqBlock>) {
+        let mut reader = GlosReader::new(File::open(path).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+        (reader.header().clone(), blocks)
+    }
+
+    #[test]
+    fn test_split_and_merge_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("session.glos");
+        write_file(
+            &input,
+            &[0, 500, 1_000, 1_500, 2_000_000, 2_000_500, 2_001_000],
+        );
+
+        // Кусками по 1 мс: 2 + 2 блока, затем после паузы 2 + 1
+        let split = split_by_time(
+            &input,
+            dir.path().join("part.glos"),
+            Duration::from_millis(1),
+        )
+        .unwrap();
+        let spans: Vec<_> = split
+            .parts
+            .iter()
+            .map(|p| (p.blocks, p.header.total_samples))
+            .collect();
+        assert_eq!(spans, vec![(2, 2_000), (2, 2_000), (2, 2_000), (1, 1_000)]);
+        assert_eq!(split.parts[3].path, dir.path().join("part_003.glos"));
+        assert_eq!(split.parts[2].header.timestamp_start, 2);
+        assert_eq!(split.parts[2].end_ns, 2_001_000_000);
+        assert_eq!(split.total_samples(), 7_000);
+
+        let paths: Vec<_> = split.parts.iter().map(|p| p.path.clone()).collect();
+        let merged_path = dir.path().join("merged.glos");
+        let merged = merge(&paths, &merged_path).unwrap();
+        assert_eq!(merged.blocks, 7);
+        assert_eq!(merged.header.timestamp_end, 2);
+
+        let (header, merged_blocks) = blocks(&merged_path);
+        let (_, original) = blocks(&input);
+        assert_eq!(header.total_samples, 7_000);
+        assert_eq!(header.timestamp_start, 0);
+        for (a, b) in merged_blocks.iter().zip(&original) {
+            assert_eq!((a.timestamp_ns, &a.data), (b.timestamp_ns, &b.data));
+        }
+
+        // Не больше 2500 выборок: блоки не режутся
+        let split = split_by_samples(&input, dir.path().join("n.glos"), 2_500).unwrap();
+        let sizes: Vec<_> = split.parts.iter().map(|p| p.header.total_samples).collect();
+        assert_eq!(sizes, vec![2_000, 2_000, 2_000, 1_000]);
+        assert!(split_by_samples(&input, &input, 0).is_err());
+    }
+
+    #[test]
+    fn test_merge_rejects_incompatible_and_overlapping() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let a = dir.path().join("a.glos");
+        let b = dir.path().join("b.glos");
+        let out = dir.path().join("out.glos");
+        write_file(&a, &[0, 500]);
+        write_file(&b, &[700, 1_200]);
+
+        let err = merge(&[&a, &b], &out).unwrap_err();
+        assert!(err.to_string().contains("previous input"), "{err}");
+
+        let changes = crate::HeaderChanges {
+            center_freq: Some(1_575_420_000),
+            ..Default::default()
+        };
+        crate::patch_header(&b, &changes).unwrap();
+        std::fs::remove_file(&out).unwrap();
+
+        let err = merge(&[&a, &b], &out).unwrap_err();
+        assert!(err.to_string().contains("center_freq"), "{err}");
+        assert!(!out.exists());
+        assert!(merge(&[&a, &out], &out).is_err());
+        assert!(merge::<&Path, _>(&[], &out).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+//! Всё, что нужно для чтения и записи `.glos` одним импортом.
+//!
+//! ```
+//! use glos_core::prelude::*;
+//!
+//! let header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+//! assert_eq!(header.serialize().unwrap().len(), GLOS_HEADER_SIZE);
+//! ```
+
+pub use glos_types::{
+    BlockAttributes, Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType,
+    SyncMarker,
+};
+
+pub use crate::{
+    read_all_blocks, GapPolicy, GlosHeaderExt, GlosReader, GlosWriter, IqBlockExt, ReadStats,
+    SampleBuffer, GLOS_HEADER_SIZE,
+};
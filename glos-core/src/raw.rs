@@ -0,0 +1,907 @@
+//! Сырые IQ файлы без заголовка: форматы выборок и их автоопределение.
+//!
+//! Файлы GNU Radio, RTL-SDR и `hackrf_transfer` — просто поток пар I/Q в
+//! порядке байт машины, записавшей их (на практике little-endian). Формат
+//! пользователь обычно не знает, поэтому [`detect_format`] оценивает его
+//! по самим данным:
+//!
+//! - float32 узнаётся по показателям: у настоящих float они лежат в узком
+//!   правдоподобном диапазоне только при выравнивании на 4 байта;
+//! - int16 — по энтропии байт: младший байт почти равномерен, старший (знак и
+//!   старшие разряды) заметно беднее;
+//! - int8 со знаком и без (RTL-SDR `cu8`) — по тому, вокруг какого нуля (0 или
+//!   127.5) сосредоточены значения (постоянная составляющая).
+//!
+//! [`import_raw`] заворачивает такой файл в `.glos`: частоту дискретизации и
+//! несущую сообщает пользователь, формат — он же или автоопределение.
+//! [`export_raw`] делает обратное — для gnss-sdr, MATLAB и GNU Radio.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{BufReader, Read, Seek, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use glos_types::{Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType};
+
+use crate::{
+    dsp::CA_CHIP_RATE_HZ, format::samples_to_ns, serialization::decode_sample, GlosHeaderExt,
+    GlosReader, GlosWriter, IqBlockExt, GLOS_FLAG_LITTLE_ENDIAN, GLOS_MAX_BLOCK_SIZE,
+};
+
+/// Меньше этого данных не хватает для гистограмм.
+pub const RAW_DETECT_MIN_BYTES: usize = 4_096;
+
+/// Сколько байт начала файла отдаётся автоопределению при импорте.
+pub const RAW_DETECT_BYTES: usize = 256 * 1024;
+
+/// Выборок в блоке `.glos` при импорте по умолчанию.
+pub const RAW_IMPORT_BLOCK_SAMPLES: u32 = 65_536;
+
+/// Формат выборок сырого IQ файла (порядок байт — little-endian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawFormat {
+    /// Знаковые 8 бит (HackRF `hackrf_transfer`)
+    Cs8,
+    /// Беззнаковые 8 бит со смещением 127.5 (RTL-SDR)
+    Cu8,
+    /// Знаковые 16 бит
+    Cs16,
+    /// float32 (GNU Radio `gr_complex`)
+    Cf32,
+}
+
+/// Оценка одного формата.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatScore {
+    pub format: RawFormat,
+    /// Нормированная оценка (0.0..=1.0, сумма по форматам — 1.0)
+    pub score: f64,
+}
+
+/// Результат автоопределения формата.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatDetection {
+    /// Оценки от самой вероятной к наименее вероятной
+    pub scores: Vec<FormatScore>,
+    /// Доля правдоподобных float32 при сдвиге на 0..4 байта
+    pub float_alignment: [f64; 4],
+    /// Разность энтропий младших и старших байт int16 (бит)
+    pub int16_entropy_gap: f64,
+    /// Среднее |значение| байта как cs8 и как cu8 (от своего нуля)
+    pub int8_deviation: (f64, f64),
+    /// Проанализировано байт
+    pub bytes: usize,
+}
+
+/// Итоговый выбор формата: определённый или заданный явно.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatChoice {
+    pub format: RawFormat,
+    /// Что предложило автоопределение
+    pub detected: RawFormat,
+    /// Уверенность автоопределения (0.0..=1.0)
+    pub confidence: f64,
+    /// Формат задан явно
+    pub overridden: bool,
+}
+
+/// Параметры импорта сырого файла: то, чего в самом файле нет.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawImportOptions {
+    /// Формат выборок (None — автоопределение)
+    pub format: Option<RawFormat>,
+    pub sample_rate: u32,
+    pub center_freq_hz: u64,
+    /// Устройство, записавшее файл, если известно
+    pub sdr: SdrType,
+    /// Время первой выборки (Unix-нс)
+    pub start_ns: u64,
+    pub block_samples: u32,
+    pub compression: Compression,
+}
+
+/// Итог импорта.
+#[derive(Debug, Clone)]
+pub struct RawImport {
+    /// Заголовок записанного `.glos` (после финализации)
+    pub header: GlosHeader,
+    pub blocks: u64,
+    /// Каким форматом прочитан файл
+    pub format: FormatChoice,
+    /// Сомнения в параметрах и отброшенный хвост
+    pub warnings: Vec<String>,
+}
+
+/// Параметры выгрузки `.glos` в сырой файл.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RawExportOptions {
+    /// Формат выборок (None — как в записи)
+    pub format: Option<RawFormat>,
+    /// Заполнять разрывы меток времени нулями, чтобы номер выборки в файле
+    /// оставался временем от начала записи
+    pub zero_fill_gaps: bool,
+    /// Выгрузить только выборки с метками в `[start, end)` (Unix-нс),
+    /// начиная с текущего места чтения; крайние блоки обрезаются (None —
+    /// всё до конца записи)
+    pub range: Option<(u64, u64)>,
+}
+
+/// Итог выгрузки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawExport {
+    pub format: RawFormat,
+    /// Записано IQ пар (вместе с заполнением)
+    pub samples: u64,
+    pub blocks: u64,
+    /// Пропущено блоков с неверной CRC
+    pub corrupted_blocks: u64,
+    /// Пар нулей, вставленных в разрывы
+    pub gap_samples: u64,
+    /// Компонент I/Q, не поместившихся в целый формат и обрезанных
+    pub clipped: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RawFormat {
+    pub const ALL: [RawFormat; 4] = [
+        RawFormat::Cs8,
+        RawFormat::Cu8,
+        RawFormat::Cs16,
+        RawFormat::Cf32,
+    ];
+
+    /// Размер IQ пары в байтах.
+    pub fn sample_size(&self) -> usize {
+        self.iq_format().sample_size()
+    }
+
+    /// Формат выборок в `.glos` (cu8 переводится в int8 сдвигом на 128).
+    pub fn iq_format(&self) -> IqFormat {
+        match self {
+            RawFormat::Cs8 | RawFormat::Cu8 => IqFormat::Int8,
+            RawFormat::Cs16 => IqFormat::Int16,
+            RawFormat::Cf32 => IqFormat::Float32,
+        }
+    }
+
+    /// Сырой формат, в котором выборки `.glos` лежат без пересчёта.
+    pub fn native(format: IqFormat) -> Self {
+        match format {
+            IqFormat::Int8 => RawFormat::Cs8,
+            IqFormat::Int16 => RawFormat::Cs16,
+            IqFormat::Float32 => RawFormat::Cf32,
+        }
+    }
+
+    /// Значение `item_type` для `File_Signal_Source` в конфигурации
+    /// gnss-sdr (cu8 gnss-sdr не читает).
+    pub fn gnss_sdr_item_type(&self) -> Option<&'static str> {
+        match self {
+            RawFormat::Cs8 => Some("ibyte"),
+            RawFormat::Cu8 => None,
+            RawFormat::Cs16 => Some("ishort"),
+            RawFormat::Cf32 => Some("gr_complex"),
+        }
+    }
+
+    /// Кодирует нормированную выборку; возвращает число обрезанных
+    /// компонент. Масштаб тот же, что у чтения, поэтому int8 → int8 и
+    /// int16 → int16 через float точны.
+    fn encode(
+        &self,
+        [i, q]: [f32; 2],
+        out: &mut Vec<u8>,
+    ) -> u64 {
+        let mut clipped = 0;
+        let mut int = |x: f32, scale: f32, min: f32, max: f32| {
+            let v = (x * scale).round();
+            if !(min..=max).contains(&v) {
+                clipped += 1;
+            }
+            v.clamp(min, max)
+        };
+
+        match self {
+            RawFormat::Cs8 | RawFormat::Cu8 => {
+                let flip = if *self == RawFormat::Cu8 { 0x80 } else { 0 };
+                for x in [i, q] {
+                    out.push(int(x, 128.0, -128.0, 127.0) as i8 as u8 ^ flip);
+                }
+            }
+            RawFormat::Cs16 => {
+                for x in [i, q] {
+                    out.extend_from_slice(
+                        &(int(x, 32767.0, -32768.0, 32767.0) as i16).to_le_bytes(),
+                    );
+                }
+            }
+            RawFormat::Cf32 => {
+                out.extend_from_slice(&i.to_le_bytes());
+                out.extend_from_slice(&q.to_le_bytes());
+            }
+        }
+
+        clipped
+    }
+}
+
+impl FormatDetection {
+    /// Самый вероятный формат.
+    pub fn best(&self) -> RawFormat {
+        self.scores[0].format
+    }
+
+    /// Уверенность: нормированная оценка лучшего формата.
+    pub fn confidence(&self) -> f64 {
+        self.scores[0].score
+    }
+
+    pub fn score(
+        &self,
+        format: RawFormat,
+    ) -> f64 {
+        self.scores
+            .iter()
+            .find(|s| s.format == format)
+            .map_or(0.0, |s| s.score)
+    }
+
+    /// Выбор с учётом явного указания формата пользователем.
+    pub fn choose(
+        &self,
+        manual: Option<RawFormat>,
+    ) -> FormatChoice {
+        FormatChoice {
+            format: manual.unwrap_or(self.best()),
+            detected: self.best(),
+            confidence: self.confidence(),
+            overridden: manual.is_some(),
+        }
+    }
+}
+
+impl FormatChoice {
+    /// Явно заданный формат без автоопределения (данных для него мало).
+    pub fn manual(format: RawFormat) -> Self {
+        Self {
+            format,
+            detected: format,
+            confidence: 0.0,
+            overridden: true,
+        }
+    }
+
+    /// Явно заданный формат расходится с уверенным автоопределением.
+    pub fn disagrees(&self) -> bool {
+        self.overridden && self.format != self.detected && self.confidence >= 0.5
+    }
+}
+
+impl RawImportOptions {
+    pub fn new(
+        sample_rate: u32,
+        center_freq_hz: u64,
+    ) -> Self {
+        Self {
+            format: None,
+            sample_rate,
+            center_freq_hz,
+            sdr: SdrType::Unknown,
+            start_ns: 0,
+            block_samples: RAW_IMPORT_BLOCK_SAMPLES,
+            compression: Compression::None,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for RawFormat {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            RawFormat::Cs8 => write!(f, "cs8"),
+            RawFormat::Cu8 => write!(f, "cu8"),
+            RawFormat::Cs16 => write!(f, "cs16"),
+            RawFormat::Cf32 => write!(f, "cf32"),
+        }
+    }
+}
+
+impl FromStr for RawFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cs8" | "i8" | "int8" => Ok(RawFormat::Cs8),
+            "cu8" | "u8" => Ok(RawFormat::Cu8),
+            "cs16" | "i16" | "int16" | "sc16" => Ok(RawFormat::Cs16),
+            "cf32" | "f32" | "float32" | "fc32" => Ok(RawFormat::Cf32),
+            _ => Err(format!(
+                "Unknown raw format '{s}'. Use: cs8, cu8, cs16, cf32"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FormatDetection {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "Detected {} ({:.0}% confidence, {} bytes analyzed)",
+            self.best(),
+            self.confidence() * 100.0,
+            self.bytes
+        )?;
+
+        for s in &self.scores {
+            writeln!(f, "  {:<5} {:>5.1}%", s.format, s.score * 100.0)?;
+        }
+
+        let [a0, a1, a2, a3] = self.float_alignment;
+        writeln!(
+            f,
+            "  float32 plausible at offset 0/1/2/3: {:.2}/{:.2}/{:.2}/{:.2}",
+            a0, a1, a2, a3
+        )?;
+        writeln!(
+            f,
+            "  int16 low/high byte entropy gap: {:.2} bits",
+            self.int16_entropy_gap
+        )?;
+        write!(
+            f,
+            "  int8 mean |value|: {:.1} as cs8, {:.1} as cu8",
+            self.int8_deviation.0, self.int8_deviation.1
+        )
+    }
+}
+
+impl fmt::Display for FormatChoice {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if !self.overridden {
+            write!(
+                f,
+                "{} (detected, {:.0}% confidence)",
+                self.format,
+                self.confidence * 100.0
+            )
+        } else if self.format == self.detected {
+            write!(f, "{} (override, matches detection)", self.format)
+        } else {
+            write!(
+                f,
+                "{} (override; detection suggested {} at {:.0}%)",
+                self.format,
+                self.detected,
+                self.confidence * 100.0
+            )
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Оценивает формат выборок по началу сырого файла.
+///
+/// Достаточно нескольких сотен килобайт; данных меньше
+/// [`RAW_DETECT_MIN_BYTES`] — ошибка.
+pub fn detect_format(data: &[u8]) -> GlosResult<FormatDetection> {
+    if data.len() < RAW_DETECT_MIN_BYTES {
+        return Err(GlosError::FormatViolation(format!(
+            "Need at least {RAW_DETECT_MIN_BYTES} bytes to detect raw format, got {}",
+            data.len()
+        )));
+    }
+
+    let float_alignment = [0, 1, 2, 3].map(|offset| plausible_floats(&data[offset..]));
+
+    // Float32: правдоподобны только выровненные слова
+    let shifted = float_alignment[1..].iter().copied().fold(0.0, f64::max);
+    let cf32 = (float_alignment[0] - shifted).max(0.0);
+
+    // Int16: старший байт беднее младшего
+    let entropy = byte_entropy_by_lane(data);
+    let int16_entropy_gap = (entropy[0] + entropy[2] - entropy[1] - entropy[3]) / 2.0;
+    let wide = (int16_entropy_gap / 1.5).clamp(0.0, 1.0);
+
+    // Int8: насколько значения прижаты к своему нулю
+    let n = data.len() as f64;
+    let dev_s = data.iter().map(|&b| (b as i8 as f64).abs()).sum::<f64>() / n;
+    let dev_u = data.iter().map(|&b| (b as f64 - 127.5).abs()).sum::<f64>() / n;
+    let near_s = 1.0 - dev_s / 128.0;
+    let near_u = 1.0 - dev_u / 128.0;
+    let signed_share = near_s / (near_s + near_u).max(f64::EPSILON);
+
+    let ints = 1.0 - cf32;
+    let raw = [
+        (RawFormat::Cs8, ints * (1.0 - wide) * signed_share),
+        (RawFormat::Cu8, ints * (1.0 - wide) * (1.0 - signed_share)),
+        (RawFormat::Cs16, ints * wide),
+        (RawFormat::Cf32, cf32),
+    ];
+
+    let total = raw.iter().map(|(_, s)| s).sum::<f64>().max(f64::EPSILON);
+    let mut scores: Vec<FormatScore> = raw
+        .into_iter()
+        .map(|(format, s)| FormatScore {
+            format,
+            score: s / total,
+        })
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(FormatDetection {
+        scores,
+        float_alignment,
+        int16_entropy_gap,
+        int8_deviation: (dev_s, dev_u),
+        bytes: data.len(),
+    })
+}
+
+/// Проверка частоты дискретизации на правдоподобие для файла размером
+/// `file_bytes`. Возвращает предупреждения (пусто — всё в порядке).
+pub fn check_sample_rate(
+    sample_rate: u32,
+    file_bytes: u64,
+    format: RawFormat,
+) -> GlosResult<Vec<String>> {
+    if sample_rate == 0 {
+        return Err(GlosError::FormatViolation(
+            "Sample rate must be > 0".to_string(),
+        ));
+    }
+
+    let mut warnings = Vec::new();
+    let size = format.sample_size() as u64;
+    let tail = file_bytes % size;
+    let secs = (file_bytes / size) as f64 / sample_rate as f64;
+
+    if tail != 0 {
+        warnings.push(format!(
+            "File size is not a multiple of {size} bytes ({format}); last {tail} bytes will be dropped"
+        ));
+    }
+
+    if (sample_rate as f64) < CA_CHIP_RATE_HZ {
+        warnings.push(format!(
+            "Sample rate {sample_rate} Hz is below the GPS C/A chip rate; GNSS signals will not fit"
+        ));
+    } else if sample_rate > 100_000_000 {
+        warnings.push(format!(
+            "Sample rate {sample_rate} Hz exceeds any supported SDR (max 100 MHz)"
+        ));
+    }
+
+    if secs < 0.001 {
+        warnings.push(format!(
+            "Only {:.3} ms of signal at this rate — rate or format is probably wrong",
+            secs * 1e3
+        ));
+    } else if secs > 86_400.0 {
+        warnings.push(format!(
+            "{:.1} h of signal at this rate — rate is probably too low",
+            secs / 3_600.0
+        ));
+    }
+
+    Ok(warnings)
+}
+
+/// Заворачивает сырой IQ файл `path` в `.glos`.
+///
+/// Выборки переносятся без пересчёта (little-endian, флаг
+/// [`GLOS_FLAG_LITTLE_ENDIAN`]); `cu8` сдвигается в знаковый int8. Неполная
+/// IQ пара в конце файла отбрасывается с предупреждением.
+pub fn import_raw<W: Write + Seek>(
+    path: &Path,
+    output: W,
+    options: &RawImportOptions,
+) -> GlosResult<RawImport> {
+    let file = File::open(path)?;
+    let file_bytes = file.metadata()?.len();
+    let mut input = BufReader::new(file);
+
+    let mut head = Vec::with_capacity(RAW_DETECT_BYTES);
+    input
+        .by_ref()
+        .take(RAW_DETECT_BYTES as u64)
+        .read_to_end(&mut head)?;
+
+    let choice = match (options.format, detect_format(&head)) {
+        (manual, Ok(detection)) => detection.choose(manual),
+        (Some(format), Err(_)) => FormatChoice::manual(format),
+        (None, Err(e)) => return Err(e),
+    };
+    let format = choice.format;
+    let sample_size = format.sample_size();
+
+    let mut warnings = check_sample_rate(options.sample_rate, file_bytes, format)?;
+    if choice.disagrees() {
+        warnings.insert(0, format!("Format {choice}"));
+    }
+
+    let block_bytes = options.block_samples as usize * sample_size;
+    if options.block_samples == 0 || block_bytes > GLOS_MAX_BLOCK_SIZE {
+        return Err(GlosError::format_violation(format!(
+            "Block of {} samples does not fit 1..={GLOS_MAX_BLOCK_SIZE} bytes",
+            options.block_samples
+        )));
+    }
+
+    let mut header = GlosHeader::new(options.sdr, options.sample_rate, options.center_freq_hz);
+    header.iq_format = format.iq_format();
+    header.compression = options.compression;
+    header.flags = GLOS_FLAG_LITTLE_ENDIAN;
+    header.timestamp_start = options.start_ns / 1_000_000_000;
+
+    let mut writer = GlosWriter::new(output, header)?;
+    let mut data = std::io::Cursor::new(head).chain(input);
+    let total = file_bytes / sample_size as u64;
+    let mut pos = 0u64;
+
+    while pos < total {
+        let n = (total - pos).min(options.block_samples as u64) as u32;
+        let mut buf = vec![0u8; n as usize * sample_size];
+        data.read_exact(&mut buf)?;
+
+        if format == RawFormat::Cu8 {
+            buf.iter_mut().for_each(|b| *b ^= 0x80);
+        }
+
+        let timestamp_ns = options.start_ns + samples_to_ns(pos, options.sample_rate.into())?;
+        writer.write_block(IqBlock::new(timestamp_ns, n, buf))?;
+        pos += n as u64;
+    }
+
+    let end_ns = options.start_ns + samples_to_ns(total, options.sample_rate.into())?;
+
+    let blocks = writer.block_count();
+    let mut header = writer.header().clone();
+    header.total_samples = writer.total_samples();
+    header.timestamp_end = end_ns / 1_000_000_000;
+    writer.finish_at(header.timestamp_end)?;
+
+    Ok(RawImport {
+        header,
+        blocks,
+        format: choice,
+        warnings,
+    })
+}
+
+/// Выгружает полезную нагрузку записи сплошным сырым файлом
+/// (little-endian, пары I/Q подряд), распаковывая и при необходимости
+/// пересчитывая выборки в `options.format`.
+///
+/// Блоки с неверной CRC пропускаются; без `zero_fill_gaps` разрывы
+/// времени в сыром файле не видны.
+pub fn export_raw<R: Read, W: Write>(
+    reader: &mut GlosReader<R>,
+    output: W,
+    options: &RawExportOptions,
+) -> GlosResult<RawExport> {
+    let header = reader.header().clone();
+    let source = header.iq_format;
+    let little_endian = header.samples_little_endian();
+    let format = options.format.unwrap_or_else(|| RawFormat::native(source));
+    let rate = header.sample_rate as u64;
+
+    let mut out = std::io::BufWriter::new(output);
+    let mut buf = Vec::new();
+    let mut export = RawExport {
+        format,
+        samples: 0,
+        blocks: 0,
+        corrupted_blocks: 0,
+        gap_samples: 0,
+        clipped: 0,
+    };
+    let mut next_ns: Option<u64> = None;
+    let (start_ns, end_ns) = options.range.unwrap_or((0, u64::MAX));
+
+    for result in reader.scan_blocks_between(start_ns, end_ns).trimmed() {
+        let block = match result {
+            Ok(b) => b,
+            Err(GlosError::CrcMismatch { .. }) => {
+                export.corrupted_blocks += 1;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let (true, Some(expected)) = (options.zero_fill_gaps && rate > 0, next_ns) {
+            let gap_ns = block.timestamp_ns.saturating_sub(expected);
+            // Меньше половины выборки — дрожание меток, не разрыв
+            let gap = ((gap_ns as u128 * rate as u128 + 500_000_000) / 1_000_000_000) as u64;
+            if gap > 0 {
+                let mut zero = Vec::with_capacity(format.sample_size());
+                format.encode([0.0, 0.0], &mut zero);
+                for _ in 0..gap {
+                    out.write_all(&zero)?;
+                }
+                export.gap_samples += gap;
+                export.samples += gap;
+            }
+        }
+
+        buf.clear();
+        if format == RawFormat::native(source) && (little_endian || source == IqFormat::Int8) {
+            buf.extend_from_slice(&block.data);
+        } else {
+            for c in block.data.chunks_exact(source.sample_size()) {
+                let sample = decode_sample(c, source, little_endian);
+                export.clipped += format.encode(sample, &mut buf);
+            }
+        }
+        out.write_all(&buf)?;
+
+        export.samples += block.sample_count as u64;
+        export.blocks += 1;
+        next_ns = Some(
+            block.timestamp_ns
+                + samples_to_ns(block.sample_count as u64, header.sample_rate.into())?,
+        );
+    }
+
+    out.flush()?;
+    Ok(export)
+}
+
+/// Доля little-endian float32 с правдоподобной величиной: ноль или
+/// нормальное число с |x| в 2^-40..2^24.
+fn plausible_floats(data: &[u8]) -> f64 {
+    let words = data.chunks_exact(4);
+    let total = words.len().max(1) as f64;
+
+    let plausible = words
+        .filter(|w| {
+            let bits = u32::from_le_bytes([w[0], w[1], w[2], w[3]]);
+            let exp = ((bits >> 23) & 0xff) as i32 - 127;
+            bits << 1 == 0 || (-40..24).contains(&exp)
+        })
+        .count();
+
+    plausible as f64 / total
+}
+
+/// Энтропия Шеннона (бит) байт на позициях `i % 4 == lane`.
+fn byte_entropy_by_lane(data: &[u8]) -> [f64; 4] {
+    let mut hist = [[0u32; 256]; 4];
+    for (i, &b) in data.iter().enumerate() {
+        hist[i % 4][b as usize] += 1;
+    }
+
+    hist.map(|h| {
+        let n = h.iter().sum::<u32>().max(1) as f64;
+        h.iter()
+            .filter(|&&c| c > 0)
+            .map(|&c| {
+                let p = c as f64 / n;
+                -p * p.log2()
+            })
+            .sum()
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Приближённо нормальный шум (сумма 4 равномерных, xorshift).
+    fn noise(
+        n: usize,
+        sigma: f64,
+    ) -> impl Iterator<Item = f64> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..n).map(move |_| {
+            let mut sum = 0.0;
+            for _ in 0..4 {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                sum += (x >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            }
+            sum * sigma * 3f64.sqrt()
+        })
+    }
+
+    #[test]
+    fn test_detects_each_format() {
+        const N: usize = 64 * 1024;
+
+        let cs8: Vec<u8> = noise(N, 20.0).map(|v| v.round() as i8 as u8).collect();
+        let cu8: Vec<u8> = noise(N, 20.0)
+            .map(|v| (v + 127.5).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        let cs16: Vec<u8> = noise(N, 1_000.0)
+            .flat_map(|v| (v.round() as i16).to_le_bytes())
+            .collect();
+        let cf32: Vec<u8> = noise(N, 0.3)
+            .flat_map(|v| (v as f32).to_le_bytes())
+            .collect();
+
+        for (data, expected) in [
+            (cs8, RawFormat::Cs8),
+            (cu8, RawFormat::Cu8),
+            (cs16, RawFormat::Cs16),
+            (cf32, RawFormat::Cf32),
+        ] {
+            let d = detect_format(&data).unwrap();
+            assert_eq!(d.best(), expected, "{d}");
+            assert!(d.confidence() > 0.6, "{d}");
+        }
+    }
+
+    #[test]
+    fn test_override_and_rate_check() {
+        let cf32: Vec<u8> = noise(4_096, 0.3)
+            .flat_map(|v| (v as f32).to_le_bytes())
+            .collect();
+        let d = detect_format(&cf32).unwrap();
+
+        let auto = d.choose(None);
+        assert_eq!(auto.format, RawFormat::Cf32);
+        assert!(!auto.disagrees());
+
+        let manual = d.choose(Some(RawFormat::Cs16));
+        assert_eq!(manual.format, RawFormat::Cs16);
+        assert!(manual.disagrees());
+        assert!(manual.to_string().contains("detection suggested cf32"));
+
+        assert!(detect_format(&[0u8; 100]).is_err());
+        assert_eq!("sc16".parse::<RawFormat>().unwrap(), RawFormat::Cs16);
+
+        // 1 с при 2 MSps cs16
+        assert!(check_sample_rate(2_000_000, 8_000_000, RawFormat::Cs16)
+            .unwrap()
+            .is_empty());
+        let w = check_sample_rate(500_000, 8_000_001, RawFormat::Cs16).unwrap();
+        assert_eq!(w.len(), 2);
+        assert!(check_sample_rate(0, 8, RawFormat::Cs8).is_err());
+    }
+
+    #[test]
+    fn test_import_raw_detects_cu8_and_keeps_manual_format() {
+        use crate::{read_all_blocks, GlosReader};
+
+        let dir = tempfile::tempdir().unwrap();
+        let raw = dir.path().join("rtl.bin");
+        let cu8: Vec<u8> = noise(10_000, 20.0)
+            .map(|v| (v + 127.5).round().clamp(0.0, 255.0) as u8)
+            .collect();
+        std::fs::write(&raw, &cu8).unwrap();
+
+        let out = dir.path().join("rtl.glos");
+        let options = RawImportOptions {
+            start_ns: 1_700_000_000_000_000_000,
+            block_samples: 2_000,
+            ..RawImportOptions::new(2_048_000, 1_575_420_000)
+        };
+        let import = import_raw(&raw, File::create(&out).unwrap(), &options).unwrap();
+        assert_eq!(import.format.format, RawFormat::Cu8);
+        assert!(!import.format.overridden);
+        assert_eq!(import.blocks, 3);
+
+        let mut reader = GlosReader::new(File::open(&out).unwrap()).unwrap();
+        let header = reader.header().clone();
+        assert_eq!(header.iq_format, IqFormat::Int8);
+        assert_eq!(header.sample_rate, 2_048_000);
+        assert_eq!(header.center_freq, 1_575_420_000);
+        assert_eq!(header.total_samples, 5_000);
+        assert_eq!(header.timestamp_start, 1_700_000_000);
+
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks[1].timestamp_ns, 1_700_000_000_000_000_000 + 976_562);
+        let restored: Vec<u8> = blocks.iter().flat_map(|b| b.data.clone()).collect();
+        let expected: Vec<u8> = cu8.iter().map(|b| b ^ 0x80).collect();
+        assert_eq!(restored, expected);
+
+        // Маленький файл: без автоопределения, только явный формат
+        std::fs::write(&raw, [0u8; 9]).unwrap();
+        let options = RawImportOptions {
+            format: Some(RawFormat::Cs16),
+            ..RawImportOptions::new(2_000_000, 0)
+        };
+        let import = import_raw(&raw, std::io::Cursor::new(Vec::new()), &options).unwrap();
+        assert_eq!(import.format, FormatChoice::manual(RawFormat::Cs16));
+        assert_eq!(import.header.total_samples, 2);
+        assert!(import.warnings.iter().any(|w| w.contains("last 1 bytes")));
+
+        let auto = RawImportOptions::new(2_000_000, 0);
+        assert!(import_raw(&raw, std::io::Cursor::new(Vec::new()), &auto).is_err());
+    }
+
+    #[test]
+    fn test_export_raw_converts_and_fills_gaps() {
+        use std::io::Cursor;
+
+        // int16 big-endian, 1 кГц: 2 блока по 2 выборки, между ними 3 мс пропуска
+        let mut header = GlosHeader::new(SdrType::HackRf, 1_000, 1_575_420_000);
+        header.iq_format = IqFormat::Int16;
+        let pair = |i: i16, q: i16| [i.to_be_bytes(), q.to_be_bytes()].concat();
+
+        let mut buf = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut buf, header).unwrap();
+        writer
+            .write_block(IqBlock::new(
+                0,
+                2,
+                [pair(1, -1), pair(32767, -32768)].concat(),
+            ))
+            .unwrap();
+        writer
+            .write_block(IqBlock::new(
+                5_000_000,
+                2,
+                [pair(0, 256), pair(-256, 0)].concat(),
+            ))
+            .unwrap();
+        writer.finish_at(0).unwrap();
+        let glos = buf.into_inner();
+
+        let export = |options: RawExportOptions| {
+            let mut reader = GlosReader::new(Cursor::new(glos.clone())).unwrap();
+            let mut out = Vec::new();
+            let stats = export_raw(&mut reader, &mut out, &options).unwrap();
+            (stats, out)
+        };
+
+        // Как в записи: int16, но уже little-endian
+        let (stats, out) = export(RawExportOptions::default());
+        assert_eq!(stats.format, RawFormat::Cs16);
+        assert_eq!(stats.samples, 4);
+        assert_eq!(&out[..4], [1, 0, 0xff, 0xff]);
+        assert_eq!(out.len(), 16);
+
+        let (stats, out) = export(RawExportOptions {
+            format: Some(RawFormat::Cs8),
+            zero_fill_gaps: true,
+            range: None,
+        });
+        assert_eq!(stats.gap_samples, 3);
+        assert_eq!(stats.samples, 7);
+        assert_eq!(out, [0, 0, 127, 0x80, 0, 0, 0, 0, 0, 0, 0, 1, 0xff, 0]);
+        // +1.0 не помещается в int8
+        assert_eq!(stats.clipped, 1);
+
+        let (_, out) = export(RawExportOptions {
+            format: Some(RawFormat::Cf32),
+            zero_fill_gaps: false,
+            range: None,
+        });
+        assert_eq!(out.len(), 4 * 8);
+        assert_eq!(f32::from_le_bytes(out[8..12].try_into().unwrap()), 1.0);
+
+        // [1 мс, 6 мс): хвост первого блока и голова второго
+        let (stats, out) = export(RawExportOptions {
+            range: Some((1_000_000, 6_000_000)),
+            ..Default::default()
+        });
+        assert_eq!((stats.blocks, stats.samples), (2, 2));
+        assert_eq!(out, [0xff, 0x7f, 0x00, 0x80, 0, 0, 0, 1]);
+    }
+}
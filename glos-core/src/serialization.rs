@@ -1,8 +1,24 @@
-use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
-
-use glos_types::{Compression, GlosError, GlosHeader, GlosResult, IqBlock};
-
-use crate::{format::GLOS_HEADER_SIZE, GlosHeaderExt, IqBlockExt};
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use glos_types::{
+    BlockAttributes, Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat,
+};
+
+use crate::{
+    aligned::{check_align, AlignedBlock, AlignedBuf, DEFAULT_PAYLOAD_ALIGN},
+    format::{
+        block_frame_size, compress_payload, frame_sample_count, store_payload, DEFAULT_ZSTD_LEVEL,
+        GLOS_BLOCK_ATTRIBUTES_VERSION, GLOS_BLOCK_OVERHEAD, GLOS_FLAG_LITTLE_ENDIAN,
+        GLOS_FLAG_SAMPLES_LITTLE_ENDIAN, GLOS_HEADER_SIZE, ZSTD_LEVELS,
+    },
+    simd, GlosHeaderExt, IqBlockExt,
+};
 
 /// Потоковый писатель GLOS файлов.
 pub struct GlosWriter<W: Write + Seek> {
@@ -10,16 +26,274 @@ pub struct GlosWriter<W: Write + Seek> {
     header: GlosHeader,
     total_samples: u64,
     block_count: u64,
+    bytes_written: u64,
+    encoder: BlockEncoder,
+    /// Период контрольных точек заголовка по меткам блоков (нс)
+    checkpoint_interval_ns: Option<u64>,
+    /// Метка блока, на котором была последняя контрольная точка
+    last_checkpoint_ns: Option<u64>,
+}
+
+/// Сжатие и сериализация блоков по настройкам писателя. Общая часть
+/// синхронного и асинхронного писателей.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockEncoder {
+    compressor: BlockCompressor,
+    /// Версия файла допускает атрибуты блоков
+    attributes: bool,
+}
+
+/// Сжатие блоков по правилам [`GlosWriter`] вне писателя.
+///
+/// Тяжёлая часть — [`pack`](Self::pack) — не зависит от соседних блоков и
+/// может идти в нескольких потоках; [`finish`](Self::finish) вызывается по
+/// порядку блоков и применяет [`CompressionOptions::skip_incompressible`].
+/// Результат побайтно совпадает с тем, что записал бы сам писатель, а
+/// сжатые блоки писатель пишет как есть.
+#[derive(Debug, Clone)]
+pub struct BlockCompressor {
+    compression: Compression,
+    options: CompressionOptions,
+    /// Сколько ещё блоков писать без сжатия после несжимаемого
+    skip_blocks: u32,
+    /// Блоков, записанных без сжатия
+    stored_blocks: u64,
 }
 
+/// Настройки сжатия [`GlosWriter`]. Алгоритм задаёт заголовок файла
+/// (`compression`), здесь — как много CPU на него тратить.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionOptions {
+    /// Уровень Zstd (см. [`ZSTD_LEVELS`]); для LZ4 не используется
+    pub level: i32,
+    /// Блоки с меньшим объёмом данных (байт) пишутся без сжатия
+    pub min_block_bytes: usize,
+    /// Не сжимать несжимаемое: после блока, который сжался хуже чем на
+    /// [`INCOMPRESSIBLE_RATIO`], следующие [`INCOMPRESSIBLE_BACKOFF`] блоков
+    /// пишутся без попытки сжатия
+    pub skip_incompressible: bool,
+}
+
+/// Блок, сжатый до большей доли исходного размера, считается несжимаемым.
+pub const INCOMPRESSIBLE_RATIO: f64 = 0.97;
+
+/// Сколько блоков пропускать без сжатия после несжимаемого.
+pub const INCOMPRESSIBLE_BACKOFF: u32 = 32;
+
+/// Период контрольных точек заголовка, который рекордер использует по
+/// умолчанию (см. [`GlosWriter::with_checkpoint_interval`]).
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Сколько байт данных блоков [`read_all_blocks`] собирает в память (1 ГиБ).
+pub const DEFAULT_READ_ALL_BUDGET: usize = 1 << 30;
+
+/// Пауза между попытками дочитать растущий файл по умолчанию (см.
+/// [`FollowOptions`]).
+pub const DEFAULT_FOLLOW_POLL: Duration = Duration::from_millis(100);
+
 /// Потоковый читатель GLOS файлов.
 pub struct GlosReader<R: Read> {
     reader: BufReader<R>,
     header: GlosHeader,
     read_buf: Vec<u8>,
+    frames: FrameBuffer,
+    /// Выравнивание буферов для [`GlosReader::next_aligned_block`]
+    payload_align: usize,
+    /// Выборки, прочитанные, но ещё не отданные
+    /// [`GlosReader::read_exact_samples`]
+    cursor: SampleCursor,
+    /// Блок, найденный поиском и ещё не отданный, его размер и смещение в
+    /// файле
+    peeked: Option<(IqBlock, usize, u64)>,
+    /// Переставлять байты компонент little-endian выборок (см.
+    /// [`ReaderOptions::normalize_byte_order`])
+    swap_samples: bool,
+    /// Режим `tail -f`, см. [`GlosReader::follow`]
+    follow: Option<FollowOptions>,
+}
+
+/// Прочитанные, но ещё не разобранные на блоки байты потока и статистика
+/// разбора. Общая часть синхронного и асинхронного читателей: они только
+/// подкладывают байты, поиск границ блоков и пропуск мусора — здесь.
+#[derive(Debug)]
+pub(crate) struct FrameBuffer {
     leftover: Vec<u8>,
+    /// Смещение в файле первого байта `leftover`
+    offset: u64,
     stats: ReadStats,
     eof: bool,
+    /// `leftover` начинается на границе кадра: сразу за целым блоком или
+    /// в начале потока, а не посреди поиска после повреждения
+    at_boundary: bool,
+    /// Конец последнего целого блока (нс), см.
+    /// [`CorruptionEvent::timestamp_guess`]
+    next_timestamp: Option<u64>,
+}
+
+/// Итог одного шага [`FrameBuffer::next_frame`].
+pub(crate) enum Frame {
+    /// Блок и его смещение от начала файла
+    Block(u64, IqBlock),
+    Error(GlosError),
+    /// Нужно дочитать поток и передать байты в [`FrameBuffer::push`]
+    NeedData,
+    End,
+}
+
+/// Итератор по блокам вместе с их смещениями в файле, см.
+/// [`GlosReader::blocks_with_offsets`].
+pub struct BlockOffsets<'a, R: Read> {
+    reader: &'a mut GlosReader<R>,
+}
+
+/// Каждый `n`-й блок файла, см. [`GlosReader::read_every_nth_block`].
+pub struct EveryNthBlock<'a, R: Read + Seek> {
+    reader: &'a mut GlosReader<R>,
+    n: u64,
+    started: bool,
+}
+
+/// Блоки, пересекающие интервал времени, см.
+/// [`GlosReader::blocks_between`].
+pub struct BlocksBetween<'a, R: Read> {
+    reader: &'a mut GlosReader<R>,
+    start_ns: u64,
+    end_ns: u64,
+    trim: bool,
+    done: bool,
+}
+
+/// Читатель блоков без распаковки и проверки `sample_count`: данные
+/// отдаются как лежат в файле (в сжатом файле — сжатыми, с
+/// `is_compressed == true`). Для копирования, нарезки и индексации, где
+/// выборки не нужны: [`GlosWriter::write_block`] пишет такие блоки в файл с
+/// тем же сжатием как есть. CRC кадра проверяется — по нему читатель
+/// находит границы блоков после повреждений.
+pub struct RawBlockReader<R: Read> {
+    inner: GlosReader<R>,
+}
+
+/// Сколько байт читатели запрашивают из потока за раз.
+pub(crate) const READ_BUF_SIZE: usize = 2 * 1024 * 1024;
+
+/// Сколько событий повреждения хранит [`ReadStats`].
+pub const MAX_CORRUPTION_EVENTS: usize = 256;
+
+/// Сколько разрывов записи хранит [`ReadStats`].
+pub const MAX_GAP_EVENTS: usize = 256;
+
+/// Чем оказался повреждён блок.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// CRC кадра не совпал
+    Crc,
+    /// Сжатые данные не распаковываются
+    Decompress,
+    /// `sample_count` не сходится с объёмом данных
+    SampleCount,
+    /// Прочие нарушения формата кадра
+    Malformed,
+}
+
+/// Место, где читатель нашёл повреждение.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CorruptionEvent {
+    /// Смещение от начала файла. Для [`CorruptionKind::Crc`] это байт, с
+    /// которого начинался предполагаемый кадр: после сбоя кадры ищутся
+    /// побайтово, поэтому одно повреждение может дать несколько событий
+    pub approx_offset: u64,
+    pub kind: CorruptionKind,
+    /// Ожидаемая метка времени (нс): конец последнего целого блока по
+    /// частоте дискретизации. `None`, пока целых блоков не было
+    pub timestamp_guess: Option<u64>,
+}
+
+/// Выборки, потерянные при записи: перед блоком с
+/// [`BlockAttributes::dropped_samples`] устройство их не отдало.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapEvent {
+    /// Где начался пропуск (нс): метка блока минус длительность
+    /// потерянных выборок по номинальной частоте
+    pub start_ns: u64,
+    /// Метка времени блока после пропуска (нс)
+    pub end_ns: u64,
+    /// Сколько выборок потеряно
+    pub samples: u32,
+}
+
+/// Что делать с разрывом меток времени между блоками при чтении по
+/// выборкам ([`GlosReader::read_exact_samples`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GapPolicy {
+    /// Вернуть ошибку. Выборки до разрыва отбрасываются, следующий вызов
+    /// продолжает с блока после разрыва
+    #[default]
+    Error,
+    /// Заполнить пропуск нулями; перекрывающиеся выборки отбрасываются
+    ZeroFill,
+}
+
+/// Выборки фиксированной длины, собранные через границы блоков.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleBuffer {
+    /// Метка времени первой выборки (нс)
+    pub timestamp_ns: u64,
+    /// Пары `[I, Q]`, нормированные к ±1.0 для целых форматов
+    pub samples: Vec<[f32; 2]>,
+}
+
+/// Куда встало чтение после [`GlosReader::seek_to_timestamp`] или
+/// [`GlosReader::seek_to_sample`]: блок, который вернёт следующий
+/// [`GlosReader::next_block`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeekPosition {
+    /// Метка времени блока (нс)
+    pub timestamp_ns: u64,
+    /// Номер первой выборки блока от начала файла
+    pub sample_index: u64,
+}
+
+#[derive(Debug, Default)]
+struct SampleCursor {
+    pending: VecDeque<[f32; 2]>,
+    /// Метка времени, от которой отсчитываются выборки (None до первого блока)
+    anchor_ns: Option<u64>,
+    /// Номер первой выборки `pending` от `anchor_ns`
+    front_index: u64,
+}
+
+/// Настройки чтения для [`GlosReader::with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReaderOptions {
+    /// Читать файлы более новой версии формата (best-effort): из заголовка
+    /// берутся поля v1 на их местах, зарезервированные байты с новыми
+    /// полями игнорируются, а непонятные кадры в потоке пропускаются
+    /// обычным поиском границ блоков и попадают в
+    /// [`ReadStats::blocks_corrupted`]. Неизвестные формат выборок или
+    /// сжатие остаются ошибкой: данные без них не разобрать. По умолчанию
+    /// выключено — такой файл отклоняется с
+    /// [`GlosError::UnsupportedVersion`]
+    pub allow_newer_versions: bool,
+    /// Отдавать IQ-компоненты в big-endian, даже если файл записан с
+    /// little-endian выборками (бит 0 или
+    /// [`GLOS_FLAG_SAMPLES_LITTLE_ENDIAN`](crate::GLOS_FLAG_SAMPLES_LITTLE_ENDIAN)):
+    /// байты переставляются при чтении, а из [`GlosReader::header`] флаги
+    /// порядка байт сбрасываются, чтобы заголовок описывал то, что отдаёт
+    /// читатель. Сырые кадры ([`RawBlockReader`]) не затрагиваются
+    pub normalize_byte_order: bool,
+}
+
+/// Настройки чтения файла, который ещё пишется, см.
+/// [`GlosReader::follow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FollowOptions {
+    /// Пауза между попытками дочитать файл
+    pub poll_interval: Duration,
+    /// Сколько ждать следующего целого блока, прежде чем
+    /// [`GlosReader::next_block`] вернёт `None`: `None` — ждать без
+    /// ограничения, `Some(Duration::ZERO)` — не ждать (вызывающий сам
+    /// опрашивает читателя, например раз в кадр UI)
+    pub timeout: Option<Duration>,
 }
 
 /// Статистика, накопленная [`GlosReader`] в процессе чтения.
@@ -29,10 +303,21 @@ pub struct ReadStats {
     pub blocks_ok: u64,
     /// Блоков с ошибкой CRC или повреждённых.
     pub blocks_corrupted: u64,
+    /// Где и когда находились повреждения (первые
+    /// [`MAX_CORRUPTION_EVENTS`]).
+    pub corruption_events: Vec<CorruptionEvent>,
     /// Сумма `sample_count` по всем успешным блокам.
     pub samples_recovered: u64,
     /// Всего обработано байт (включая служебные поля блоков).
     pub bytes_processed: u64,
+    /// Байт недописанного последнего блока: кадр начат, но файл кончился
+    /// раньше (запись ещё идёт или оборвалась). Повреждением не считается.
+    pub truncated_tail_bytes: u64,
+    /// Выборок, потерянных при записи (сумма
+    /// [`BlockAttributes::dropped_samples`] прочитанных блоков).
+    pub samples_dropped: u64,
+    /// Где были потери (первые [`MAX_GAP_EVENTS`]).
+    pub gaps: Vec<GapEvent>,
 }
 
 impl<W: Write + Seek> GlosWriter<W> {
@@ -47,33 +332,120 @@ impl<W: Write + Seek> GlosWriter<W> {
 
         Ok(Self {
             writer,
+            encoder: BlockEncoder::new(&header),
             header,
             total_samples: 0,
             block_count: 0,
+            bytes_written: GLOS_HEADER_SIZE as u64,
+            checkpoint_interval_ns: None,
+            last_checkpoint_ns: None,
         })
     }
 
+    /// Задаёт настройки сжатия (по умолчанию — уровень
+    /// [`DEFAULT_ZSTD_LEVEL`], сжимается каждый блок).
+    pub fn with_compression(
+        mut self,
+        options: CompressionOptions,
+    ) -> GlosResult<Self> {
+        self.encoder.set_options(options)?;
+        Ok(self)
+    }
+
+    /// Включает контрольные точки: раз в `interval` по меткам блоков
+    /// заголовок переписывается через [`checkpoint`](Self::checkpoint).
+    /// Без них файл, оборванный сбоем питания, заявляет 0 выборок.
+    pub fn with_checkpoint_interval(
+        mut self,
+        interval: Duration,
+    ) -> Self {
+        self.checkpoint_interval_ns = Some(interval.as_nanos() as u64);
+        self
+    }
+
     /// Записывает один блок IQ данных.
     pub fn write_block(
         &mut self,
-        mut block: IqBlock,
+        block: IqBlock,
     ) -> GlosResult<()> {
-        if self.header.compression == Compression::Lz4 && !block.is_compressed {
-            block.compress()?;
-        }
+        self.write_block_reclaim(block).map(drop)
+    }
+
+    /// Как [`write_block`](Self::write_block), но возвращает опустевший
+    /// буфер данных блока: в него можно копить следующий блок без новой
+    /// аллокации. У сжатого при записи блока это буфер сжатых данных.
+    pub fn write_block_reclaim(
+        &mut self,
+        mut block: IqBlock,
+    ) -> GlosResult<Vec<u8>> {
+        let timestamp_ns = block.timestamp_ns;
+        let frame = self.encoder.encode(&mut block)?;
 
         self.total_samples += block.sample_count as u64;
         self.block_count += 1;
-        self.writer.write_all(&block.serialize()?)?;
+        self.bytes_written += frame.len() as u64;
+        self.writer.write_all(&frame)?;
+
+        if let Some(interval) = self.checkpoint_interval_ns {
+            let last = *self.last_checkpoint_ns.get_or_insert(timestamp_ns);
+            if timestamp_ns.saturating_sub(last) >= interval {
+                self.checkpoint()?;
+                self.last_checkpoint_ns = Some(timestamp_ns);
+            }
+        }
+
+        block.data.clear();
+        Ok(block.data)
+    }
+
+    /// Контрольная точка: сбрасывает буфер и переписывает заголовок с
+    /// текущими `total_samples` и `timestamp_end` (`timestamp_start` плюс
+    /// длительность записанных выборок), после чего запись продолжается.
+    ///
+    /// Данные и заголовок передаются ОС без fsync: на диск они попадают
+    /// вместе, по расписанию сброса страничного кэша. Финализация
+    /// перезаписывает заголовок окончательными значениями.
+    pub fn checkpoint(&mut self) -> GlosResult<()> {
+        let mut header = self.header.clone();
+        header.total_samples = self.total_samples;
+        header.timestamp_end =
+            header.timestamp_start + self.total_samples / header.sample_rate.max(1) as u64;
+
+        let bytes = header.serialize()?;
+
+        // Seek у BufWriter сначала сбрасывает накопленные блоки
+        self.writer.seek(SeekFrom::Start(0))?;
+        self.writer.write_all(&bytes)?;
+        self.writer.seek(SeekFrom::Start(self.bytes_written))?;
+        self.writer.flush()?;
 
         Ok(())
     }
 
     /// Завершает запись: сбрасывает буфер и перезаписывает заголовок.
     pub fn finish(mut self) -> GlosResult<()> {
+        // Без системных часов сохраняем то, что выставил вызывающий
+        let now = current_unix_secs();
+        if now != 0 {
+            self.header.timestamp_end = now;
+        }
+
+        self.finalize()
+    }
+
+    /// Как [`finish`](Self::finish), но с явным временем окончания
+    /// (Unix-секунды) — для воспроизводимых файлов.
+    pub fn finish_at(
+        mut self,
+        timestamp_end: u64,
+    ) -> GlosResult<()> {
+        self.header.timestamp_end = timestamp_end;
+        self.finalize()
+    }
+
+    fn finalize(mut self) -> GlosResult<()> {
         self.writer.flush()?;
         self.header.total_samples = self.total_samples;
-        self.header.timestamp_end = current_unix_secs();
 
         let mut inner = self
             .writer
@@ -97,151 +469,1277 @@ impl<W: Write + Seek> GlosWriter<W> {
         self.block_count
     }
 
+    /// Размер файла в байтах: заголовок и все записанные блоки (после
+    /// сжатия), включая ещё не сброшенные из буфера.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
     /// Ссылка на текущий заголовок (до финализации).
     pub fn header(&self) -> &GlosHeader {
         &self.header
     }
+
+    /// Блоков, записанных без сжатия (короткие или несжимаемые).
+    pub fn stored_blocks(&self) -> u64 {
+        self.encoder.stored_blocks()
+    }
+}
+
+impl GlosWriter<File> {
+    /// Открывает существующий файл для дописывания — продолжение прерванной
+    /// или упавшей записи.
+    ///
+    /// Заголовок проверяется (CRC, версия), блоки просматриваются без
+    /// распаковки с проверкой CRC кадров: `total_samples` и число блоков
+    /// считаются по ним, а не берутся из заголовка — после сбоя он не
+    /// финализирован. Хвост после последнего целого блока (недописанный
+    /// блок) обрезается. Сжатие — из заголовка, уровень задаётся
+    /// [`with_compression`](Self::with_compression). При финализации
+    /// заголовок получает новые `total_samples` и `timestamp_end`.
+    pub fn append<P: AsRef<Path>>(path: P) -> GlosResult<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut blocks = RawBlockReader::new(&file)?;
+        let header = blocks.header().clone();
+
+        let mut end = GLOS_HEADER_SIZE as u64;
+        let mut total_samples = 0u64;
+        let mut block_count = 0u64;
+
+        while let Some(next) = blocks.next_block() {
+            match next {
+                Ok((offset, block)) => {
+                    end = offset + block.frame_size() as u64;
+                    total_samples += block.sample_count as u64;
+                    block_count += 1;
+                }
+                Err(GlosError::Io(e)) => return Err(GlosError::Io(e)),
+                // Повреждённые блоки посередине остаются, как есть
+                Err(_) => {}
+            }
+        }
+        drop(blocks);
+
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+            encoder: BlockEncoder::new(&header),
+            header,
+            total_samples,
+            block_count,
+            bytes_written: end,
+            checkpoint_interval_ns: None,
+            last_checkpoint_ns: None,
+        })
+    }
 }
 
 impl<R: Read> GlosReader<R> {
     /// Создаёт читателя, читая и валидируя заголовок из `inner`.
     pub fn new(inner: R) -> GlosResult<Self> {
+        Self::with_options(inner, ReaderOptions::default())
+    }
+
+    /// Как [`new`](Self::new), но с настройками `options`.
+    pub fn with_options(
+        inner: R,
+        options: ReaderOptions,
+    ) -> GlosResult<Self> {
         let mut reader = BufReader::new(inner);
         let mut hdr_buf = [0u8; GLOS_HEADER_SIZE];
 
         reader.read_exact(&mut hdr_buf)?;
 
-        let header = GlosHeaderExt::deserialize(&hdr_buf)?;
+        let mut header = read_header(&hdr_buf, options)?;
+        let swap_samples = options.normalize_byte_order && header.samples_little_endian();
+        if swap_samples {
+            header.flags &= !(GLOS_FLAG_LITTLE_ENDIAN | GLOS_FLAG_SAMPLES_LITTLE_ENDIAN);
+        }
 
         Ok(Self {
             reader,
             header,
-            read_buf: vec![0u8; 2 * 1024 * 1024],
-            leftover: Vec::new(),
-            stats: ReadStats::default(),
-            eof: false,
+            read_buf: vec![0u8; READ_BUF_SIZE],
+            frames: FrameBuffer::new(GLOS_HEADER_SIZE as u64),
+            payload_align: DEFAULT_PAYLOAD_ALIGN,
+            cursor: SampleCursor::default(),
+            peeked: None,
+            swap_samples,
+            follow: None,
         })
     }
 
+    /// Задаёт выравнивание полезной нагрузки для
+    /// [`next_aligned_block`](Self::next_aligned_block) (степень двойки,
+    /// по умолчанию 64 байта).
+    pub fn with_payload_alignment(
+        mut self,
+        align: usize,
+    ) -> GlosResult<Self> {
+        check_align(align)?;
+        self.payload_align = align;
+        Ok(self)
+    }
+
+    pub fn payload_alignment(&self) -> usize {
+        self.payload_align
+    }
+
+    /// Режим `tail -f` для файла, который ещё пишет рекордер.
+    ///
+    /// Конец файла больше не означает конец потока: недописанный последний
+    /// кадр остаётся в буфере, а [`next_block`](Self::next_block) дочитывает
+    /// файл раз в `poll_interval`, пока кадр не появится целиком или не
+    /// выйдет `timeout` (тогда `None`, и следующий вызов продолжит с того
+    /// же места). Заголовок читается один раз при открытии: `total_samples`
+    /// и `timestamp_end` в нём — с последней контрольной точки писателя.
+    pub fn follow(
+        mut self,
+        options: FollowOptions,
+    ) -> Self {
+        self.follow = Some(options);
+        self
+    }
+
+    /// Выходит из режима [`follow`](Self::follow): следующий конец файла
+    /// завершает поток, а недописанный кадр учитывается в
+    /// [`ReadStats::truncated_tail_bytes`].
+    pub fn stop_following(&mut self) {
+        self.follow = None;
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.follow.is_some()
+    }
+
+    /// Байт недописанного кадра, ждущих продолжения в режиме
+    /// [`follow`](Self::follow).
+    pub fn pending_tail_bytes(&self) -> usize {
+        self.frames.leftover.len()
+    }
+
+    /// Как [`next_block`](Self::next_block), но данные блока лежат в
+    /// выровненном буфере и доступны как `&[i16]` / `&[f32]`.
+    pub fn next_aligned_block(&mut self) -> Option<GlosResult<AlignedBlock>> {
+        let block = match self.next_block()? {
+            Ok(b) => b,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let data = match AlignedBuf::from_slice(&block.data, self.payload_align) {
+            Ok(d) => d,
+            Err(e) => return Some(Err(e)),
+        };
+
+        Some(Ok(AlignedBlock {
+            timestamp_ns: block.timestamp_ns,
+            sample_count: block.sample_count,
+            format: self.header.iq_format,
+            little_endian: self.header.samples_little_endian(),
+            data,
+            attributes: block.attributes,
+        }))
+    }
+
     /// Возвращает следующий блок или `None` на EOF.
     pub fn next_block(&mut self) -> Option<GlosResult<IqBlock>> {
+        self.next_block_with_offset()
+            .map(|r| r.map(|(_, block)| block))
+    }
+
+    /// Как [`next_block`](Self::next_block), но вместе со смещением блока от
+    /// начала файла. В v1 нет индекса, поэтому смещение считается по
+    /// прочитанным байтам и работает для любого `Read`.
+    pub fn next_block_with_offset(&mut self) -> Option<GlosResult<(u64, IqBlock)>> {
+        if let Some((block, bytes_read, offset)) = self.peeked.take() {
+            self.frames
+                .count_block(&block, bytes_read, self.header.sample_rate);
+            return Some(Ok((offset, block)));
+        }
+
+        self.next_frame(true)
+    }
+
+    /// Следующий блок из потока. С `decode` данные распаковываются и
+    /// сверяются с `sample_count`, без него блок отдаётся как в файле
+    /// (проверяется только CRC кадра).
+    fn next_frame(
+        &mut self,
+        decode: bool,
+    ) -> Option<GlosResult<(u64, IqBlock)>> {
+        let mut deadline = None;
+
         loop {
-            if self.leftover.len() >= 20 {
-                match IqBlock::deserialize(&self.leftover, self.header.compression) {
-                    Ok((mut block, bytes_read)) => {
-                        // Распаковка (если нужна)
-                        if block.decompress().is_err() {
-                            // Сжатые данные повреждены — пропускаем весь блок
-                            self.leftover.drain(..bytes_read);
-                            self.stats.blocks_corrupted += 1;
-                            continue;
+            match self.frames.next_frame(&self.header, decode) {
+                Frame::Block(offset, mut block) => {
+                    if decode && self.swap_samples {
+                        if let Err(e) = block.swap_byte_order(self.header.iq_format) {
+                            return Some(Err(e));
                         }
+                    }
+                    return Some(Ok((offset, block)));
+                }
+                Frame::Error(e) => return Some(Err(e)),
+                Frame::End => return None,
+                Frame::NeedData => {}
+            }
 
-                        // Валидация: sample_count × sample_size == data.len()
-                        // (спецификация п.5)
-                        if block.validate_sample_count(self.header.iq_format).is_err() {
-                            self.leftover.drain(..bytes_read);
-                            self.stats.blocks_corrupted += 1;
-                            continue;
-                        }
+            match (self.reader.read(&mut self.read_buf), self.follow) {
+                (Ok(0), Some(follow)) => {
+                    let now = Instant::now();
+                    let deadline = *deadline.get_or_insert_with(|| follow.timeout.map(|t| now + t));
+
+                    match deadline {
+                        Some(d) if now >= d => return None,
+                        Some(d) => std::thread::sleep(follow.poll_interval.min(d - now)),
+                        None => std::thread::sleep(follow.poll_interval),
+                    }
+                }
+                (Ok(n), _) => self.frames.push(&self.read_buf[..n]),
+                (Err(e), _) => return Some(Err(GlosError::Io(e))),
+            }
+        }
+    }
+
+    /// Итератор по блокам вместе с их смещениями от начала файла.
+    pub fn blocks_with_offsets(&mut self) -> BlockOffsets<'_, R> {
+        BlockOffsets { reader: self }
+    }
+
+    /// Как [`blocks_between`](Self::blocks_between), но без поиска: блоки
+    /// читаются подряд с текущего места, всё до `start_ns` пропускается.
+    /// Для потоков без `Seek` (pipe, сеть).
+    pub fn scan_blocks_between(
+        &mut self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> BlocksBetween<'_, R> {
+        BlocksBetween {
+            reader: self,
+            start_ns,
+            end_ns,
+            trim: false,
+            done: end_ns <= start_ns,
+        }
+    }
+
+    /// Следующие ровно `n` выборок независимо от границ блоков, в `f32`.
+    ///
+    /// Разрыв определяется по меткам времени блоков и частоте дискретизации
+    /// (допуск — полпериода выборки) и обрабатывается по `policy`; блоки,
+    /// отброшенные из-за CRC, тоже дают разрыв. Возвращает `None`, когда до
+    /// конца файла осталось меньше `n` выборок. Не смешивайте с
+    /// [`next_block`](Self::next_block): выборки, уже переданные сюда,
+    /// блоками повторно не отдаются.
+    pub fn read_exact_samples(
+        &mut self,
+        n: usize,
+        policy: GapPolicy,
+    ) -> GlosResult<Option<SampleBuffer>> {
+        while self.cursor.pending.len() < n {
+            let block = match self.next_block() {
+                Some(Ok(block)) => block,
+                Some(Err(GlosError::CrcMismatch { .. })) => continue,
+                Some(Err(e)) => return Err(e),
+                None => return Ok(None),
+            };
+            self.push_samples(&block, policy)?;
+        }
+
+        let rate = self.header.sample_rate;
+        let timestamp_ns = self.cursor.timestamp_at(0, rate);
+        let samples = self.cursor.pending.drain(..n).collect();
+        self.cursor.front_index += n as u64;
+
+        Ok(Some(SampleBuffer {
+            timestamp_ns,
+            samples,
+        }))
+    }
+
+    fn push_samples(
+        &mut self,
+        block: &IqBlock,
+        policy: GapPolicy,
+    ) -> GlosResult<()> {
+        let format = self.header.iq_format;
+        let little_endian = self.header.samples_little_endian();
+        let rate = self.header.sample_rate.max(1);
+        let cursor = &mut self.cursor;
+
+        let mut skip = 0usize;
+        if cursor.anchor_ns.is_none() {
+            cursor.reanchor(block.timestamp_ns);
+        } else {
+            let expected = cursor.timestamp_at(cursor.pending.len() as u64, rate);
+            let drift_ns = block.timestamp_ns as i128 - expected as i128;
+            let drift_samples = (drift_ns * rate as i128 + 500_000_000).div_euclid(1_000_000_000);
+
+            if drift_samples != 0 {
+                match policy {
+                    GapPolicy::Error => {
+                        cursor.reanchor(block.timestamp_ns);
+                        cursor.extend(&block.data, format, little_endian, 0);
+                        return Err(GlosError::Corrupted(format!(
+                            "Gap of {drift_samples} samples before block at {} ns",
+                            block.timestamp_ns
+                        )));
+                    }
+                    GapPolicy::ZeroFill if drift_samples > 0 => {
+                        let zeros = std::iter::repeat_n([0.0; 2], drift_samples as usize);
+                        cursor.pending.extend(zeros);
+                    }
+                    GapPolicy::ZeroFill => skip = (-drift_samples) as usize,
+                }
+            }
+        }
+
+        cursor.extend(&block.data, format, little_endian, skip);
+        Ok(())
+    }
+
+    /// Проверяет, что `header.total_samples = Σ block.sample_count`.
+    pub fn validate_totals(&self) -> GlosResult<()> {
+        let expected = self.header.total_samples;
+
+        if expected == 0 {
+            return Ok(());
+        }
+
+        if self.frames.stats.samples_recovered != expected {
+            return Err(GlosError::FormatViolation(format!(
+                "total_samples mismatch: header={}, recovered={}",
+                expected, self.frames.stats.samples_recovered,
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Прочитанный и проверенный заголовок файла.
+    pub fn header(&self) -> &GlosHeader {
+        &self.header
+    }
+
+    /// Накопленная статистика чтения.
+    pub fn stats(&self) -> &ReadStats {
+        self.frames.stats()
+    }
+}
+
+impl BlockEncoder {
+    pub(crate) fn new(header: &GlosHeader) -> Self {
+        Self {
+            compressor: BlockCompressor {
+                compression: header.compression,
+                options: CompressionOptions::default(),
+                skip_blocks: 0,
+                stored_blocks: 0,
+            },
+            attributes: header.version >= GLOS_BLOCK_ATTRIBUTES_VERSION,
+        }
+    }
+
+    pub(crate) fn set_options(
+        &mut self,
+        options: CompressionOptions,
+    ) -> GlosResult<()> {
+        options.validate()?;
+        self.compressor.options = options;
+        Ok(())
+    }
+
+    pub(crate) fn stored_blocks(&self) -> u64 {
+        self.compressor.stored_blocks
+    }
+
+    /// Кадр блока для записи. Несжатый блок сжимается алгоритмом файла,
+    /// уже сжатый (например, из [`RawBlockReader`]) пишется как есть.
+    /// Атрибуты блока требуют заголовка версии
+    /// [`GLOS_BLOCK_ATTRIBUTES_VERSION`] и выше.
+    pub(crate) fn encode(
+        &mut self,
+        block: &mut IqBlock,
+    ) -> GlosResult<Vec<u8>> {
+        if !self.attributes && !block.attributes.is_empty() {
+            return Err(GlosError::FormatViolation(format!(
+                "block attributes require format version {GLOS_BLOCK_ATTRIBUTES_VERSION}"
+            )));
+        }
+
+        self.compressor.finish(block, None)?;
+
+        block.serialize()
+    }
+}
+
+impl BlockCompressor {
+    /// Сжатие для файлов с заголовком `header`, как у писателя с
+    /// настройками `options`.
+    pub fn new(
+        header: &GlosHeader,
+        options: CompressionOptions,
+    ) -> GlosResult<Self> {
+        options.validate()?;
+        Ok(Self {
+            compression: header.compression,
+            options,
+            skip_blocks: 0,
+            stored_blocks: 0,
+        })
+    }
+
+    /// Блоков, оставленных без сжатия.
+    pub fn stored_blocks(&self) -> u64 {
+        self.stored_blocks
+    }
+
+    /// Сжатые данные блока, если их может понадобиться сжимать; `None` —
+    /// блок уже сжат, файл без сжатия или блок меньше
+    /// [`CompressionOptions::min_block_bytes`].
+    pub fn pack(
+        &self,
+        block: &IqBlock,
+    ) -> GlosResult<Option<Vec<u8>>> {
+        if block.is_compressed
+            || self.compression == Compression::None
+            || block.data.len() < self.options.min_block_bytes
+        {
+            return Ok(None);
+        }
+        compress_payload(&block.data, self.compression, self.options.level).map(Some)
+    }
+
+    /// По порядку блоков: заменяет данные несжатого блока тем, что записал
+    /// бы писатель. `packed` — результат [`pack`](Self::pack) для этого
+    /// блока; без него блок сжимается здесь же. Сжатые данные, посчитанные
+    /// для блока в паузе после несжимаемого, отбрасываются.
+    pub fn finish(
+        &mut self,
+        block: &mut IqBlock,
+        packed: Option<Vec<u8>>,
+    ) -> GlosResult<()> {
+        if block.is_compressed || self.compression == Compression::None {
+            return Ok(());
+        }
+
+        let store = if block.data.len() < self.options.min_block_bytes {
+            true
+        } else if self.skip_blocks > 0 {
+            self.skip_blocks -= 1;
+            true
+        } else {
+            let packed = match packed {
+                Some(p) => p,
+                None => compress_payload(&block.data, self.compression, self.options.level)?,
+            };
+            let incompressible = self.options.skip_incompressible
+                && packed.len() as f64 >= block.data.len() as f64 * INCOMPRESSIBLE_RATIO;
+
+            if incompressible {
+                self.skip_blocks = INCOMPRESSIBLE_BACKOFF;
+            } else {
+                block.data = packed;
+            }
+            incompressible
+        };
+
+        if store {
+            block.data = store_payload(&block.data, self.compression);
+            self.stored_blocks += 1;
+        }
+        block.is_compressed = true;
+
+        Ok(())
+    }
+}
+
+impl FrameBuffer {
+    /// Пустой буфер, первый байт которого окажется по смещению `offset`.
+    pub(crate) fn new(offset: u64) -> Self {
+        Self {
+            leftover: Vec::new(),
+            offset,
+            stats: ReadStats::default(),
+            eof: false,
+            at_boundary: true,
+            next_timestamp: None,
+        }
+    }
+
+    /// Добавляет прочитанные байты; пустой срез — конец потока.
+    pub(crate) fn push(
+        &mut self,
+        bytes: &[u8],
+    ) {
+        if bytes.is_empty() {
+            self.eof = true;
+        } else {
+            self.leftover.extend_from_slice(bytes);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> &ReadStats {
+        &self.stats
+    }
+
+    /// Разбирает следующий блок из накопленных байт. С `decode` данные
+    /// распаковываются и сверяются с `sample_count`.
+    pub(crate) fn next_frame(
+        &mut self,
+        header: &GlosHeader,
+        decode: bool,
+    ) -> Frame {
+        loop {
+            if self.leftover.len() < GLOS_BLOCK_OVERHEAD {
+                // На EOF это усечённый хвост файла
+                return if self.eof {
+                    Frame::End
+                } else {
+                    Frame::NeedData
+                };
+            }
+
+            match IqBlock::deserialize(&self.leftover, header.compression) {
+                Ok((block, bytes_read)) if !decode => {
+                    let offset = self.offset;
+                    self.consume(bytes_read);
+                    self.count_block(&block, bytes_read, header.sample_rate);
+                    return Frame::Block(offset, block);
+                }
+
+                Ok((mut block, bytes_read)) => {
+                    // Распаковка (если нужна)
+                    if block.decompress().is_err() {
+                        // Сжатые данные повреждены — пропускаем весь блок
+                        self.count_corrupted(CorruptionKind::Decompress);
+                        self.consume(bytes_read);
+                        continue;
+                    }
+
+                    // Валидация: sample_count × sample_size == data.len()
+                    // (спецификация п.5)
+                    if block.validate_sample_count(header.iq_format).is_err() {
+                        self.count_corrupted(CorruptionKind::SampleCount);
+                        self.consume(bytes_read);
+                        continue;
+                    }
+
+                    let offset = self.offset;
+                    self.consume(bytes_read);
+                    self.count_block(&block, bytes_read, header.sample_rate);
+                    return Frame::Block(offset, block);
+                }
+
+                Err(GlosError::Corrupted(_)) => {
+                    // leftover.len() >= 20, значит данные есть, но
+                    // content_size либо вне допустимого диапазона, либо
+                    // (на EOF) указывает за конец буфера — мусор после
+                    // повреждённого блока. Сканируем побайтово, не
+                    // дочитывая файл в память ради заведомо ложного размера.
+                    let frame = block_frame_size(&self.leftover);
+                    if self.eof && self.at_boundary && frame.is_ok() {
+                        // Правдоподобный кадр сразу за целым блоком,
+                        // обрезанный концом файла, — недописанный хвост, а
+                        // не мусор
+                        self.stats.truncated_tail_bytes = self.leftover.len() as u64;
+                        self.consume(self.leftover.len());
+                        return Frame::End;
+                    }
+                    if self.eof || frame.is_err() {
+                        self.consume(1);
+                        continue;
+                    }
+                    // Данных не хватает — дочитываем
+                    return Frame::NeedData;
+                }
+
+                Err(GlosError::CrcMismatch { .. }) => {
+                    self.count_corrupted(CorruptionKind::Crc);
+                    self.consume(1);
+                    continue;
+                }
+
+                Err(e) => {
+                    self.count_corrupted(CorruptionKind::Malformed);
+                    self.consume(1);
+                    return Frame::Error(e);
+                }
+            }
+        }
+    }
+
+    /// Отбрасывает `n` байт из начала `leftover`.
+    fn consume(
+        &mut self,
+        n: usize,
+    ) {
+        self.leftover.drain(..n);
+        self.offset += n as u64;
+        self.at_boundary = false;
+    }
+
+    /// Учитывает повреждённый блок в начале `leftover`.
+    fn count_corrupted(
+        &mut self,
+        kind: CorruptionKind,
+    ) {
+        self.stats.blocks_corrupted += 1;
+        if self.stats.corruption_events.len() < MAX_CORRUPTION_EVENTS {
+            self.stats.corruption_events.push(CorruptionEvent {
+                approx_offset: self.offset,
+                kind,
+                timestamp_guess: self.next_timestamp,
+            });
+        }
+    }
+
+    fn count_block(
+        &mut self,
+        block: &IqBlock,
+        bytes_read: usize,
+        sample_rate: u32,
+    ) {
+        let duration_ns = block.sample_count as u128 * 1_000_000_000 / sample_rate.max(1) as u128;
+        self.next_timestamp = Some(block.timestamp_ns.saturating_add(duration_ns as u64));
+
+        self.at_boundary = true;
+        self.stats.blocks_ok += 1;
+        self.stats.samples_recovered = self
+            .stats
+            .samples_recovered
+            .saturating_add(block.sample_count as u64);
+        self.stats.bytes_processed = self.stats.bytes_processed.saturating_add(bytes_read as u64);
+        self.stats
+            .count_gap(block.timestamp_ns, block.attributes, sample_rate);
+    }
+}
+
+impl ReadStats {
+    /// Учитывает потерю выборок перед блоком с меткой `timestamp_ns`.
+    pub(crate) fn count_gap(
+        &mut self,
+        timestamp_ns: u64,
+        attributes: BlockAttributes,
+        sample_rate: u32,
+    ) {
+        let Some(samples) = attributes.dropped_samples else {
+            return;
+        };
+
+        self.samples_dropped = self.samples_dropped.saturating_add(samples as u64);
+        if self.gaps.len() < MAX_GAP_EVENTS {
+            let duration_ns = samples as u128 * 1_000_000_000 / sample_rate.max(1) as u128;
+            self.gaps.push(GapEvent {
+                start_ns: timestamp_ns.saturating_sub(duration_ns as u64),
+                end_ns: timestamp_ns,
+                samples,
+            });
+        }
+    }
+}
+
+impl<R: Read> RawBlockReader<R> {
+    /// Создаёт читателя, читая и валидируя заголовок из `inner`.
+    pub fn new(inner: R) -> GlosResult<Self> {
+        GlosReader::new(inner).map(|inner| Self { inner })
+    }
+
+    /// Следующий блок вместе со смещением от начала файла или `None` на EOF.
+    pub fn next_block(&mut self) -> Option<GlosResult<(u64, IqBlock)>> {
+        self.inner.next_frame(false)
+    }
+
+    pub fn header(&self) -> &GlosHeader {
+        self.inner.header()
+    }
+
+    /// Статистика чтения. `samples_recovered` здесь — сумма `sample_count`
+    /// из блоков, не сверенная с данными.
+    pub fn stats(&self) -> &ReadStats {
+        self.inner.stats()
+    }
+}
+
+impl<R: Read + Seek> GlosReader<R> {
+    /// Ставит чтение на первый блок с меткой времени не раньше
+    /// `timestamp_ns` (метки блоков не убывают). `None` — таких блоков нет,
+    /// чтение стоит в конце файла.
+    pub fn seek_to_timestamp(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> GlosResult<Option<SeekPosition>> {
+        self.seek_to(|ts, _, _| ts >= timestamp_ns)
+    }
+
+    /// Ставит чтение на блок, содержащий момент `timestamp_ns` (по меткам
+    /// и номинальной частоте дискретизации), а если момент попал в разрыв —
+    /// на первый блок после него. В отличие от
+    /// [`seek_to_timestamp`](Self::seek_to_timestamp) не теряет начало
+    /// интервала, начинающегося посреди блока.
+    pub fn seek_covering(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> GlosResult<Option<SeekPosition>> {
+        let rate = self.header.sample_rate.max(1) as u128;
+        // Пустой блок (метка синхронизации) ничего не покрывает, но в
+        // самом моменте `timestamp_ns` уже относится к интервалу после него
+        self.seek_to(|ts, _, count| {
+            ts as u128 + count as u128 * 1_000_000_000 / rate > timestamp_ns as u128
+                || (count == 0 && ts >= timestamp_ns)
+        })
+    }
+
+    /// Блоки, пересекающие `[start_ns, end_ns)` (по меткам и номинальной
+    /// частоте дискретизации). Начало ищется по префиксам блоков без
+    /// чтения данных ([`seek_covering`](Self::seek_covering)), чтение
+    /// останавливается на первом блоке, начинающемся не раньше `end_ns`.
+    /// Крайние блоки отдаются целиком, с
+    /// [`BlocksBetween::trimmed`] — обрезанными до выборок интервала.
+    /// Метки синхронизации внутри интервала отдаются как есть.
+    ///
+    /// Ошибки блоков (CRC) передаются как есть, итерация продолжается.
+    pub fn blocks_between(
+        &mut self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> GlosResult<BlocksBetween<'_, R>> {
+        if end_ns > start_ns {
+            self.seek_covering(start_ns)?;
+        }
+        Ok(self.scan_blocks_between(start_ns, end_ns))
+    }
+
+    /// Ставит чтение на первый блок, начинающийся с выборки `sample` или
+    /// позже (номер от начала файла).
+    pub fn seek_to_sample(
+        &mut self,
+        sample: u64,
+    ) -> GlosResult<Option<SeekPosition>> {
+        self.seek_to(|_, index, _| index >= sample)
+    }
+
+    /// Итератор по каждому `n`-му блоку, начиная со следующего: 0-й, `n`-й,
+    /// `2n`-й, … Остальные блоки пропускаются по префиксам (размер кадра)
+    /// без чтения данных, распаковки и проверки CRC — для предпросмотра
+    /// спектра или миниатюры часовой записи читается `1/n` файла.
+    ///
+    /// Пропущенные блоки в [`stats`](Self::stats) не учитываются. Если
+    /// вместо размера пропускаемого блока мусор, пропуск идёт обычным
+    /// чтением с поиском следующего целого блока.
+    ///
+    /// # Panics
+    ///
+    /// Если `n == 0`.
+    pub fn read_every_nth_block(
+        &mut self,
+        n: usize,
+    ) -> EveryNthBlock<'_, R> {
+        assert!(n > 0, "read_every_nth_block: n must be > 0");
+
+        EveryNthBlock {
+            reader: self,
+            n: n as u64,
+            started: false,
+        }
+    }
+
+    /// Пропускает `count` блоков: по префиксам с `seek`, после мусора —
+    /// чтением кадров без распаковки.
+    fn skip_blocks(
+        &mut self,
+        mut count: u64,
+    ) -> GlosResult<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        if self.peeked.take().is_some() {
+            count -= 1;
+        }
+        self.cursor = SampleCursor::default();
+
+        // Непрочитанный остаток буфера начинается с `frames.offset`
+        let mut offset = self.frames.offset;
+        let mut prefix = [0u8; 4];
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        while count > 0 {
+            match self.reader.read_exact(&mut prefix) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            let Ok(frame) = block_frame_size(&prefix) else {
+                break;
+            };
+
+            self.reader
+                .seek_relative(frame as i64 - prefix.len() as i64)?;
+            offset += frame as u64;
+            count -= 1;
+        }
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let stats = std::mem::take(&mut self.frames.stats);
+        self.frames = FrameBuffer::new(offset);
+        self.frames.stats = stats;
+
+        if count == 0 {
+            return self.load_frame(offset);
+        }
+
+        while count > 0 {
+            let before = self.frames.stats.clone();
+
+            match self.next_frame(false) {
+                Some(Ok(_)) => {
+                    // Пропущенный блок не считается прочитанным
+                    self.frames.stats.blocks_ok = before.blocks_ok;
+                    self.frames.stats.samples_recovered = before.samples_recovered;
+                    self.frames.stats.bytes_processed = before.bytes_processed;
+                    self.frames.stats.samples_dropped = before.samples_dropped;
+                    self.frames.stats.gaps.truncate(before.gaps.len());
+                    count -= 1;
+                }
+                Some(Err(GlosError::Io(e))) => return Err(GlosError::Io(e)),
+                Some(Err(_)) => {}
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Читает в буфер ровно один кадр с `offset`, а не
+    /// [`READ_BUF_SIZE`] байт: иначе после каждого пропуска читалось бы
+    /// много больше нужного. Если кадр не читается целиком, поток
+    /// возвращается на `offset` — разберётся обычное чтение.
+    fn load_frame(
+        &mut self,
+        offset: u64,
+    ) -> GlosResult<()> {
+        let mut frame = vec![0u8; 4];
+
+        let loaded = match self.reader.read_exact(&mut frame) {
+            Ok(()) => match block_frame_size(&frame) {
+                Ok(size) => {
+                    frame.resize(size, 0);
+                    self.reader.read_exact(&mut frame[4..]).is_ok()
+                }
+                Err(_) => false,
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => false,
+            Err(e) => return Err(e.into()),
+        };
+
+        if loaded {
+            self.frames.push(&frame);
+        } else {
+            self.reader.seek(SeekFrom::Start(offset))?;
+        }
+
+        Ok(())
+    }
+
+    /// Поиск всегда идёт от начала файла: сначала по префиксам блоков с
+    /// пропуском данных, затем найденный блок читается целиком с проверкой
+    /// CRC. Повреждённый блок на месте найденного пропускается, а после
+    /// мусора в размере блока поиск продолжается обычным чтением. Номер
+    /// выборки считается по `sample_count` блоков до найденного.
+    ///
+    /// Статистика чтения и накопленные
+    /// [`read_exact_samples`](Self::read_exact_samples) выборки сбрасываются.
+    fn seek_to(
+        &mut self,
+        mut reached: impl FnMut(u64, u64, u32) -> bool,
+    ) -> GlosResult<Option<SeekPosition>> {
+        self.frames = FrameBuffer::new(GLOS_HEADER_SIZE as u64);
+        self.peeked = None;
+        self.cursor = SampleCursor::default();
+
+        let mut offset = GLOS_HEADER_SIZE as u64;
+        let mut sample_index = 0u64;
+        let mut prefix = [0u8; 16];
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        loop {
+            match self.reader.read_exact(&mut prefix) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+
+            // Мусор вместо размера — дальше только полным чтением
+            let Ok(frame) = block_frame_size(&prefix) else {
+                break;
+            };
+            let count = frame_sample_count(&prefix);
+            let ts = u64::from_be_bytes([
+                prefix[8], prefix[9], prefix[10], prefix[11], prefix[12], prefix[13], prefix[14],
+                prefix[15],
+            ]);
+
+            if reached(ts, sample_index, count) {
+                break;
+            }
+
+            self.reader
+                .seek_relative(frame as i64 - prefix.len() as i64)?;
+            offset += frame as u64;
+            sample_index += count as u64;
+        }
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.frames = FrameBuffer::new(offset);
+
+        let position = loop {
+            let bytes_before = self.frames.stats.bytes_processed;
+
+            match self.next_block_with_offset() {
+                Some(Ok((block_offset, block))) => {
+                    if reached(block.timestamp_ns, sample_index, block.sample_count) {
+                        let bytes_read =
+                            (self.frames.stats.bytes_processed - bytes_before) as usize;
+                        let position = SeekPosition {
+                            timestamp_ns: block.timestamp_ns,
+                            sample_index,
+                        };
+                        self.peeked = Some((block, bytes_read, block_offset));
+                        break Some(position);
+                    }
+                    sample_index += block.sample_count as u64;
+                }
+                Some(Err(GlosError::CrcMismatch { .. })) => continue,
+                Some(Err(e)) => return Err(e),
+                None => break None,
+            }
+        };
+
+        self.frames.stats = ReadStats::default();
+        Ok(position)
+    }
+}
+
+impl<R: Read> BlocksBetween<'_, R> {
+    /// Обрезать крайние блоки до выборок интервала: у головы сдвигается
+    /// метка, события начала блока (смена усиления, потери) остаются
+    /// только у блока без обрезанной головы — как в
+    /// [`IqBlockExt::split_at`].
+    pub fn trimmed(mut self) -> Self {
+        self.trim = true;
+        self
+    }
+
+    /// Часть блока внутри интервала; `None` — выборок интервала в нём нет.
+    /// Блок без выборок (метка синхронизации) остаётся целиком, если его
+    /// метка попадает в интервал.
+    fn trim(
+        &self,
+        block: IqBlock,
+    ) -> Option<IqBlock> {
+        if block.sample_count == 0 {
+            let inside = (self.start_ns..self.end_ns).contains(&block.timestamp_ns);
+            return inside.then_some(block);
+        }
+
+        let header = self.reader.header();
+        let rate = header.sample_rate.max(1) as u128;
+        let sample_size = header.iq_format.sample_size();
+
+        // Выборок блока раньше момента `t` (с округлением вверх)
+        let before = |t: u64| {
+            let n = (t.saturating_sub(block.timestamp_ns) as u128 * rate).div_ceil(1_000_000_000);
+            n.min(block.sample_count as u128) as u32
+        };
+        let (skip, keep) = (before(self.start_ns), before(self.end_ns));
+
+        if keep <= skip {
+            return None;
+        }
+        if skip == 0 && keep == block.sample_count {
+            return Some(block);
+        }
+
+        let timestamp_ns = block.timestamp_ns + (skip as u128 * 1_000_000_000 / rate) as u64;
+        let data = block.data[skip as usize * sample_size..keep as usize * sample_size].to_vec();
+        let mut trimmed = IqBlock::new(timestamp_ns, keep - skip, data);
+        trimmed.attributes = if skip == 0 {
+            block.attributes
+        } else {
+            BlockAttributes {
+                overload: block.attributes.overload,
+                ..Default::default()
+            }
+        };
+
+        Some(trimmed)
+    }
+}
+
+impl CompressionOptions {
+    /// Проверяет уровень Zstd.
+    pub fn validate(&self) -> GlosResult<()> {
+        if !ZSTD_LEVELS.contains(&self.level) {
+            return Err(GlosError::format_violation(format!(
+                "zstd level {} out of range {}..={}",
+                self.level,
+                ZSTD_LEVELS.start(),
+                ZSTD_LEVELS.end()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_ZSTD_LEVEL,
+            min_block_bytes: 0,
+            skip_incompressible: false,
+        }
+    }
+}
+
+impl Default for FollowOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: DEFAULT_FOLLOW_POLL,
+            timeout: None,
+        }
+    }
+}
+
+impl<R: Read> Iterator for GlosReader<R> {
+    type Item = GlosResult<IqBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block()
+    }
+}
+
+impl<R: Read> Iterator for BlockOffsets<'_, R> {
+    type Item = GlosResult<(u64, IqBlock)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next_block_with_offset()
+    }
+}
 
-                        self.stats.blocks_ok += 1;
-                        self.stats.samples_recovered += block.sample_count as u64;
-                        self.stats.bytes_processed += bytes_read as u64;
-                        self.leftover.drain(..bytes_read);
-                        return Some(Ok(block));
-                    }
+impl<R: Read> Iterator for BlocksBetween<'_, R> {
+    type Item = GlosResult<IqBlock>;
 
-                    Err(GlosError::Corrupted(_)) => {
-                        if self.eof {
-                            // leftover.len() >= 20, значит данные есть, но
-                            // content_size указывает за конец буфера — мусор
-                            // после повреждённого блока. Сканируем побайтово.
-                            self.leftover.drain(..1);
-                            continue;
-                        }
-                        // Данных не хватает — дочитываем
-                    }
+    fn next(&mut self) -> Option<Self::Item> {
+        let rate = self.reader.header().sample_rate.max(1) as u128;
 
-                    Err(GlosError::CrcMismatch { .. }) => {
-                        self.stats.blocks_corrupted += 1;
-                        self.leftover.drain(..1);
-                        continue;
-                    }
+        while !self.done {
+            let block = match self.reader.next_block()? {
+                Ok(b) => b,
+                Err(e) => return Some(Err(e)),
+            };
 
-                    Err(e) => {
-                        self.stats.blocks_corrupted += 1;
-                        self.leftover.drain(..1);
-                        return Some(Err(e));
-                    }
-                }
+            if block.timestamp_ns >= self.end_ns {
+                self.done = true;
+                break;
             }
 
-            if self.eof {
-                // leftover < 20: усечённый хвост файла, завершаем
-                return None;
+            let end_ns =
+                block.timestamp_ns as u128 + block.sample_count as u128 * 1_000_000_000 / rate;
+            let inside = match block.sample_count {
+                0 => block.timestamp_ns >= self.start_ns,
+                _ => end_ns > self.start_ns as u128,
+            };
+            if !inside {
+                continue;
             }
 
-            match self.reader.read(&mut self.read_buf) {
-                Ok(0) => {
-                    self.eof = true;
-                    if self.leftover.is_empty() {
-                        return None;
-                    }
-                }
-                Ok(n) => {
-                    self.leftover.extend_from_slice(&self.read_buf[..n]);
-                }
-                Err(e) => return Some(Err(GlosError::Io(e))),
+            if !self.trim {
+                return Some(Ok(block));
+            }
+            if let Some(b) = self.trim(block) {
+                return Some(Ok(b));
             }
         }
+
+        None
     }
+}
 
-    /// Проверяет, что `header.total_samples = Σ block.sample_count`.
-    pub fn validate_totals(&self) -> GlosResult<()> {
-        let expected = self.header.total_samples;
+impl<R: Read + Seek> Iterator for EveryNthBlock<'_, R> {
+    type Item = GlosResult<IqBlock>;
 
-        if expected == 0 {
-            return Ok(());
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started {
+            if let Err(e) = self.reader.skip_blocks(self.n - 1) {
+                return Some(Err(e));
+            }
         }
+        self.started = true;
 
-        if self.stats.samples_recovered != expected {
-            return Err(GlosError::FormatViolation(format!(
-                "total_samples mismatch: header={}, recovered={}",
-                expected, self.stats.samples_recovered,
-            )));
+        self.reader.next_block()
+    }
+}
+
+impl<R: Read> Iterator for RawBlockReader<R> {
+    type Item = GlosResult<(u64, IqBlock)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block()
+    }
+}
+
+impl std::fmt::Display for CorruptionKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        f.write_str(match self {
+            CorruptionKind::Crc => "crc",
+            CorruptionKind::Decompress => "decompress",
+            CorruptionKind::SampleCount => "sample-count",
+            CorruptionKind::Malformed => "malformed",
+        })
+    }
+}
+
+impl std::str::FromStr for CorruptionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crc" => Ok(CorruptionKind::Crc),
+            "decompress" => Ok(CorruptionKind::Decompress),
+            "sample-count" => Ok(CorruptionKind::SampleCount),
+            "malformed" => Ok(CorruptionKind::Malformed),
+            _ => Err(format!(
+                "Unknown corruption kind: '{s}'. Use: crc, decompress, sample-count, malformed"
+            )),
         }
+    }
+}
 
+/// `0x1a2b crc ~1700000000.123456789s` — для отчётов проверяющих утилит.
+impl std::fmt::Display for CorruptionEvent {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{:#x} {}", self.approx_offset, self.kind)?;
+        if let Some(ts) = self.timestamp_guess {
+            write!(f, " ~{}.{:09}s", ts / 1_000_000_000, ts % 1_000_000_000)?;
+        }
         Ok(())
     }
+}
 
-    /// Прочитанный и проверенный заголовок файла.
-    pub fn header(&self) -> &GlosHeader {
-        &self.header
+impl std::fmt::Display for GapEvent {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{} samples ~{}.{:09}s..{}.{:09}s",
+            self.samples,
+            self.start_ns / 1_000_000_000,
+            self.start_ns % 1_000_000_000,
+            self.end_ns / 1_000_000_000,
+            self.end_ns % 1_000_000_000
+        )
     }
+}
 
-    /// Накопленная статистика чтения.
-    pub fn stats(&self) -> &ReadStats {
-        &self.stats
+impl SampleCursor {
+    /// Метка времени выборки с номером `offset` от начала `pending`.
+    fn timestamp_at(
+        &self,
+        offset: u64,
+        sample_rate: u32,
+    ) -> u64 {
+        let index = (self.front_index + offset) as u128;
+        let elapsed = index * 1_000_000_000 / sample_rate.max(1) as u128;
+        self.anchor_ns.unwrap_or(0) + elapsed as u64
     }
-}
 
-impl<R: Read> Iterator for GlosReader<R> {
-    type Item = GlosResult<IqBlock>;
+    /// Начинает отсчёт заново с `timestamp_ns`, отбрасывая накопленное.
+    fn reanchor(
+        &mut self,
+        timestamp_ns: u64,
+    ) {
+        self.pending.clear();
+        self.anchor_ns = Some(timestamp_ns);
+        self.front_index = 0;
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.next_block()
+    fn extend(
+        &mut self,
+        data: &[u8],
+        format: IqFormat,
+        little_endian: bool,
+        skip: usize,
+    ) {
+        if format == IqFormat::Int16 {
+            let data = &data[(skip * 4).min(data.len())..];
+            let mut pairs = vec![[0.0; 2]; data.len() / 4];
+            let whole = pairs.len() * 4;
+            simd::i16_to_f32(
+                &data[..whole],
+                little_endian,
+                bytemuck::cast_slice_mut(&mut pairs),
+            );
+            self.pending.extend(pairs);
+            return;
+        }
+
+        let pairs = data
+            .chunks_exact(format.sample_size())
+            .skip(skip)
+            .map(|c| decode_sample(c, format, little_endian));
+        self.pending.extend(pairs);
     }
 }
 
 /// Convenience: читает все блоки из файла, собирая их в вектор.
 ///
-/// Повреждённые блоки пропускаются.
+/// Повреждённые блоки пропускаются. Данных блоков собирается не больше
+/// [`DEFAULT_READ_ALL_BUDGET`] байт, дальше —
+/// [`GlosError::MemoryBudgetExceeded`]. Для больших записей читайте блоки
+/// по одному ([`GlosReader::next_block`], итератор [`GlosReader`],
+/// [`GlosReader::blocks_between`]) или через `GlosMmapReader`; свой
+/// предел — [`read_blocks_limited`].
 pub fn read_all_blocks<R: Read>(reader: &mut GlosReader<R>) -> GlosResult<Vec<IqBlock>> {
+    read_blocks_limited(reader, DEFAULT_READ_ALL_BUDGET)
+}
+
+/// Как [`read_all_blocks`], но с явным пределом `max_bytes` на данные
+/// блоков в памяти (несжатые выборки, без накладных расходов `Vec`).
+/// Блок, с которым предел был бы превышен, не читается дальше: ошибка
+/// возвращается сразу, прочитанное отбрасывается.
+pub fn read_blocks_limited<R: Read>(
+    reader: &mut GlosReader<R>,
+    max_bytes: usize,
+) -> GlosResult<Vec<IqBlock>> {
     let mut blocks = Vec::new();
+    let mut bytes = 0usize;
+
     while let Some(result) = reader.next_block() {
         match result {
-            Ok(block) => blocks.push(block),
+            Ok(block) => {
+                bytes = bytes.saturating_add(block.data.len());
+                if bytes > max_bytes {
+                    return Err(GlosError::MemoryBudgetExceeded {
+                        budget: max_bytes,
+                        needed: bytes,
+                    });
+                }
+                blocks.push(block);
+            }
             Err(GlosError::CrcMismatch { .. }) => continue,
             Err(e) => return Err(e),
         }
@@ -249,20 +1747,86 @@ pub fn read_all_blocks<R: Read>(reader: &mut GlosReader<R>) -> GlosResult<Vec<Iq
     Ok(blocks)
 }
 
-fn current_unix_secs() -> u64 {
+/// Одна IQ выборка в `f32` (целые форматы нормируются к ±1.0).
+pub(crate) fn decode_sample(
+    c: &[u8],
+    format: IqFormat,
+    little_endian: bool,
+) -> [f32; 2] {
+    let i16_at = |b: [u8; 2]| {
+        if little_endian {
+            i16::from_le_bytes(b)
+        } else {
+            i16::from_be_bytes(b)
+        }
+    };
+    let f32_at = |b: [u8; 4]| {
+        if little_endian {
+            f32::from_le_bytes(b)
+        } else {
+            f32::from_be_bytes(b)
+        }
+    };
+
+    match format {
+        IqFormat::Int8 => [c[0] as i8 as f32 / 128.0, c[1] as i8 as f32 / 128.0],
+        IqFormat::Int16 => [
+            i16_at([c[0], c[1]]) as f32 / 32767.0,
+            i16_at([c[2], c[3]]) as f32 / 32767.0,
+        ],
+        IqFormat::Float32 => [
+            f32_at([c[0], c[1], c[2], c[3]]),
+            f32_at([c[4], c[5], c[6], c[7]]),
+        ],
+    }
+}
+
+/// Разбирает заголовок файла для чтения: кроме проверок формата,
+/// отказывает в файлах со сжатием, которое не собрано в эту сборку.
+pub(crate) fn read_header(
+    buf: &[u8; GLOS_HEADER_SIZE],
+    options: ReaderOptions,
+) -> GlosResult<GlosHeader> {
+    let header: GlosHeader = if options.allow_newer_versions {
+        GlosHeaderExt::deserialize_best_effort(buf)?
+    } else {
+        GlosHeaderExt::deserialize(buf)?
+    };
+
+    // Иначе каждый блок молча ушёл бы в повреждённые
+    #[cfg(not(feature = "zstd"))]
+    if header.compression == Compression::Zstd {
+        return Err(GlosError::format_violation(
+            "zstd-compressed file, but zstd support is disabled (glos-core feature `zstd`)",
+        ));
+    }
+
+    Ok(header)
+}
+
+/// Текущее Unix-время в секундах (0 без фичи `std-time`).
+#[cfg(feature = "std-time")]
+pub(crate) fn current_unix_secs() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs()
 }
 
+/// Текущее Unix-время в секундах (0 без фичи `std-time`).
+#[cfg(not(feature = "std-time"))]
+pub(crate) fn current_unix_secs() -> u64 {
+    0
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
 
-    use glos_types::{Compression, IqFormat, SdrType};
+    use glos_types::{BlockAttributes, Compression, IqFormat, SdrType, SyncMarker};
 
     use super::*;
+    use crate::{crc32_checksum, GLOS_HEADER_CRC_OFFSET, GLOS_MAX_VERSION};
 
     fn make_header() -> GlosHeader {
         GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000)
@@ -336,6 +1900,169 @@ mod tests {
         assert_eq!(blocks[1].sample_count, 200);
     }
 
+    #[test]
+    fn test_reader_newer_version_best_effort() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+        raw.extend_from_slice(&make_block(0, 100).serialize().unwrap());
+        raw.extend_from_slice(&make_block(50_000, 100).serialize().unwrap());
+
+        // Версия новее поддерживаемой, новое поле в зарезервированных байтах
+        let newer = GLOS_MAX_VERSION + 1;
+        raw[4] = newer;
+        raw[80..84].copy_from_slice(b"NEW!");
+        let crc = crc32_checksum(&raw[..GLOS_HEADER_CRC_OFFSET]);
+        raw[GLOS_HEADER_CRC_OFFSET..GLOS_HEADER_CRC_OFFSET + 4].copy_from_slice(&crc.to_be_bytes());
+
+        let err = GlosReader::new(Cursor::new(raw.clone())).err().unwrap();
+        assert!(
+            matches!(err, GlosError::UnsupportedVersion { found, .. } if found == newer),
+            "{err}"
+        );
+
+        let options = ReaderOptions {
+            allow_newer_versions: true,
+            ..Default::default()
+        };
+        let mut reader = GlosReader::with_options(Cursor::new(raw.clone()), options).unwrap();
+        assert_eq!(reader.header().version, newer);
+        assert_eq!(reader.header().sample_rate, 2_000_000);
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 2);
+
+        // Версии 0 не бывает — отказ и в best-effort режиме
+        raw[4] = 0;
+        let crc = crc32_checksum(&raw[..GLOS_HEADER_CRC_OFFSET]);
+        raw[GLOS_HEADER_CRC_OFFSET..GLOS_HEADER_CRC_OFFSET + 4].copy_from_slice(&crc.to_be_bytes());
+        assert!(GlosReader::with_options(Cursor::new(raw), options).is_err());
+    }
+
+    #[test]
+    fn test_block_attributes_roundtrip() {
+        let overload = BlockAttributes {
+            overload: true,
+            ..Default::default()
+        };
+        let events = BlockAttributes {
+            gain_db: Some(20.0),
+            dropped_samples: Some(300),
+            ..Default::default()
+        };
+
+        // В v1 атрибутов нет — писатель отказывается
+        let mut v1 = GlosWriter::new(Cursor::new(Vec::new()), make_header()).unwrap();
+        let mut block = make_block(0, 100);
+        block.attributes = overload;
+        assert!(matches!(
+            v1.write_block(block),
+            Err(GlosError::FormatViolation(_))
+        ));
+
+        let mut header = make_header();
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+        header.compression = Compression::Lz4;
+
+        let mut raw = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut raw), header).unwrap();
+        for (i, attributes) in [BlockAttributes::default(), overload, events]
+            .into_iter()
+            .enumerate()
+        {
+            let mut block = make_block(i as u64 * 50_000, 100);
+            block.attributes = attributes;
+            writer.write_block(block).unwrap();
+        }
+        writer.finish_at(0).unwrap();
+
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let attributes: Vec<_> = blocks.iter().map(|b| b.attributes).collect();
+        assert_eq!(attributes, [BlockAttributes::default(), overload, events]);
+        assert!(blocks.iter().all(|b| b.sample_count == 100));
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+
+        // Поиск по префиксам кадров не путает бит атрибутов с числом выборок
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let pos = reader.seek_to_sample(150).unwrap().unwrap();
+        assert_eq!(pos.sample_index, 200);
+        assert_eq!(reader.next_block().unwrap().unwrap().attributes, events);
+    }
+
+    #[test]
+    fn test_reader_normalizes_little_endian_samples() {
+        let mut header = make_header();
+        header.iq_format = IqFormat::Int16;
+        header.flags |= GLOS_FLAG_SAMPLES_LITTLE_ENDIAN;
+
+        let values = [1i16, -2, 300, i16::MIN];
+        let le: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let be: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+
+        let mut raw = Vec::new();
+        let mut writer = GlosWriter::new(Cursor::new(&mut raw), header).unwrap();
+        writer.write_block(IqBlock::new(0, 2, le.clone())).unwrap();
+        writer.finish_at(0).unwrap();
+
+        // Заголовок остаётся big-endian, выборки — как записаны
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
+        assert!(!reader.header().is_little_endian());
+        assert!(reader.header().samples_little_endian());
+        assert_eq!(reader.next_block().unwrap().unwrap().data, le);
+
+        let options = ReaderOptions {
+            normalize_byte_order: true,
+            ..Default::default()
+        };
+        let mut reader = GlosReader::with_options(Cursor::new(raw), options).unwrap();
+        assert!(!reader.header().samples_little_endian());
+        let block = reader.next_block().unwrap().unwrap();
+        assert_eq!(block.data, be);
+        assert_eq!(block.samples_i16().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_follow_waits_for_complete_blocks() {
+        let header = make_header();
+        let frames: Vec<Vec<u8>> = (0..3)
+            .map(|i| make_block(i * 1_000, 10).serialize().unwrap())
+            .collect();
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut out = file.reopen().unwrap();
+        out.write_all(&header.serialize().unwrap()).unwrap();
+        out.write_all(&frames[0]).unwrap();
+        // Второй кадр дописан наполовину
+        let half = frames[1].len() / 2;
+        out.write_all(&frames[1][..half]).unwrap();
+
+        // Без follow недописанный кадр — обрезанный хвост, не повреждение
+        let mut plain = GlosReader::new(file.reopen().unwrap()).unwrap();
+        assert_eq!(read_all_blocks(&mut plain).unwrap().len(), 1);
+        assert_eq!(plain.stats().blocks_corrupted, 0);
+        assert_eq!(plain.stats().truncated_tail_bytes, half as u64);
+
+        let options = FollowOptions {
+            poll_interval: Duration::from_millis(1),
+            timeout: Some(Duration::ZERO),
+        };
+        let mut reader = GlosReader::new(file.reopen().unwrap())
+            .unwrap()
+            .follow(options);
+        assert_eq!(reader.next_block().unwrap().unwrap().timestamp_ns, 0);
+        assert!(reader.next_block().is_none());
+        assert_eq!(reader.pending_tail_bytes(), half);
+
+        out.write_all(&frames[1][half..]).unwrap();
+        out.write_all(&frames[2]).unwrap();
+        assert_eq!(reader.next_block().unwrap().unwrap().timestamp_ns, 1_000);
+        assert_eq!(reader.next_block().unwrap().unwrap().timestamp_ns, 2_000);
+        assert!(reader.next_block().is_none());
+
+        reader.stop_following();
+        assert!(reader.next_block().is_none());
+        assert_eq!(reader.stats().blocks_ok, 3);
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+    }
+
     #[test]
     fn test_corrupted_block_skipped() {
         let mut raw = Vec::<u8>::new();
@@ -365,6 +2092,68 @@ mod tests {
         // Блоки 1 и 3 читаются, блок 2 пропускается
         assert_eq!(ok, 2);
         assert!(reader.stats().blocks_corrupted > 0);
+        // Метка времени — конец блока 1: 10 выборок при 2 МГц
+        let events = &reader.stats().corruption_events;
+        assert_eq!(
+            events[0].to_string(),
+            format!("{:#x} crc ~0.000005001s", events[0].approx_offset)
+        );
+        assert_eq!("sample-count".parse(), Ok(CorruptionKind::SampleCount));
+        assert_eq!(
+            reader.stats().corruption_events,
+            vec![CorruptionEvent {
+                approx_offset: (GLOS_HEADER_SIZE + b1.len()) as u64,
+                kind: CorruptionKind::Crc,
+                timestamp_guess: Some(1 + 5_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_blocks_with_offsets() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+
+        let mut expected = Vec::new();
+        for (i, count) in [10u32, 250, 3].into_iter().enumerate() {
+            expected.push(raw.len() as u64);
+            raw.extend_from_slice(&make_block(i as u64, count).serialize().unwrap());
+        }
+        // Мусор перед последним блоком не сдвигает его смещение
+        raw.extend_from_slice(&[0xAB; 7]);
+        expected.push(raw.len() as u64);
+        raw.extend_from_slice(&make_block(3, 1).serialize().unwrap());
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let offsets: Vec<u64> = reader.blocks_with_offsets().map(|r| r.unwrap().0).collect();
+        assert_eq!(offsets, expected);
+        assert_eq!(offsets[0], GLOS_HEADER_SIZE as u64);
+
+        // После поиска смещения продолжают считаться от найденного блока
+        reader.seek_to_timestamp(1).unwrap().unwrap();
+        let (offset, block) = reader.next_block_with_offset().unwrap().unwrap();
+        assert_eq!((offset, block.timestamp_ns), (expected[1], 1));
+        let (offset, _) = reader.next_block_with_offset().unwrap().unwrap();
+        assert_eq!(offset, expected[2]);
+    }
+
+    #[test]
+    fn test_hostile_content_size_resyncs() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+
+        // Мусор с content_size ≈ u32::MAX перед нормальным блоком
+        raw.extend_from_slice(&u32::MAX.to_be_bytes());
+        raw.extend_from_slice(&[0xAB; 20]);
+        raw.extend_from_slice(&make_block(5, 10).serialize().unwrap());
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let blocks: Vec<_> = std::iter::from_fn(|| reader.next_block())
+            .filter_map(|r| r.ok())
+            .collect();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].timestamp_ns, 5);
     }
 
     #[test]
@@ -391,6 +2180,177 @@ mod tests {
         assert!(!block_out.is_compressed);
     }
 
+    #[test]
+    fn test_raw_block_reader_passes_payload_through() {
+        let mut header = make_header();
+        header.compression = Compression::Lz4;
+
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&header.serialize().unwrap());
+        for i in 0..3u64 {
+            let mut block = make_block(i, 1000);
+            block.compress().unwrap();
+            raw.extend_from_slice(&block.serialize().unwrap());
+        }
+        // Кадр с верным CRC, но не-LZ4 данными: обычный читатель его
+        // отбросит, сырой — отдаст как есть
+        let bad_offset = raw.len() as u64;
+        raw.extend_from_slice(
+            &IqBlock::new_compressed(3, 999, vec![0xFF; 16])
+                .serialize()
+                .unwrap(),
+        );
+
+        let decoded: Vec<_> = GlosReader::new(Cursor::new(raw.clone()))
+            .unwrap()
+            .blocks_with_offsets()
+            .filter_map(Result::ok)
+            .collect();
+        assert_eq!(decoded.len(), 3);
+
+        let mut reader = RawBlockReader::new(Cursor::new(raw)).unwrap();
+        let blocks: Vec<_> = reader.by_ref().collect::<GlosResult<_>>().unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[3].0, bad_offset);
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+
+        for ((offset, raw_block), (expected_offset, block)) in blocks.iter().zip(&decoded) {
+            assert_eq!(offset, expected_offset);
+            assert!(raw_block.is_compressed);
+            assert_eq!(raw_block.get_uncompressed_data().unwrap(), block.data);
+        }
+
+        // Копия сырыми блоками читается так же, как оригинал
+        let mut copy = GlosWriter::new(Cursor::new(Vec::new()), header).unwrap();
+        for (_, block) in blocks.into_iter().take(3) {
+            copy.write_block(block).unwrap();
+        }
+        let copied = copy.writer.into_inner().unwrap().into_inner();
+        let copied: Vec<_> = GlosReader::new(Cursor::new(copied))
+            .unwrap()
+            .blocks_with_offsets()
+            .collect::<GlosResult<_>>()
+            .unwrap();
+        assert_eq!(copied.len(), 3);
+        assert_eq!(copied[2].1.data, decoded[2].1.data);
+    }
+
+    #[test]
+    fn test_compression_options_store_short_and_incompressible() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let noise: Vec<u8> = (0..40_000).map(|_| rng.gen()).collect();
+        let zeros = vec![0u8; 40_000];
+
+        for compression in [Compression::Lz4, Compression::Zstd] {
+            let mut header = make_header();
+            header.compression = compression;
+
+            let options = CompressionOptions {
+                level: 1,
+                min_block_bytes: 100,
+                skip_incompressible: true,
+            };
+            let mut writer = GlosWriter::new(Cursor::new(Vec::new()), header)
+                .unwrap()
+                .with_compression(options)
+                .unwrap();
+
+            // Короткий блок, шум (дальше пропуск) и нули, попавшие в пропуск
+            writer
+                .write_block(IqBlock::new(0, 16, vec![7; 64]))
+                .unwrap();
+            writer
+                .write_block(IqBlock::new(1, 10_000, noise.clone()))
+                .unwrap();
+            writer
+                .write_block(IqBlock::new(2, 10_000, zeros.clone()))
+                .unwrap();
+            assert_eq!(writer.stored_blocks(), 3, "{compression}");
+
+            let mut inner = writer.writer.into_inner().unwrap();
+            inner.set_position(0);
+            let mut reader = GlosReader::new(inner).unwrap();
+            let blocks = read_all_blocks(&mut reader).unwrap();
+
+            assert_eq!(reader.stats().blocks_corrupted, 0);
+            let data: Vec<&[u8]> = blocks.iter().map(|b| b.data.as_slice()).collect();
+            assert_eq!(data, [&[7u8; 64][..], &noise, &zeros], "{compression}");
+        }
+
+        let bad = CompressionOptions {
+            level: 0,
+            ..Default::default()
+        };
+        let header = make_header();
+        assert!(GlosWriter::new(Cursor::new(Vec::new()), header)
+            .unwrap()
+            .with_compression(bad)
+            .is_err());
+    }
+
+    #[test]
+    fn test_block_compressor_matches_writer() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let noise: Vec<u8> = (0..40_000).map(|_| rng.gen()).collect();
+
+        // Короткий блок, шум и нули дольше пропуска после него
+        let mut blocks = vec![
+            IqBlock::new(0, 16, vec![7; 64]),
+            IqBlock::new(1, 10_000, noise),
+        ];
+        for i in 0..INCOMPRESSIBLE_BACKOFF as u64 + 2 {
+            blocks.push(IqBlock::new(2 + i, 10_000, vec![0; 40_000]));
+        }
+
+        let mut header = make_header();
+        header.compression = Compression::Zstd;
+        let options = CompressionOptions {
+            level: 1,
+            min_block_bytes: 100,
+            skip_incompressible: true,
+        };
+        let write = |blocks: Vec<IqBlock>| {
+            let mut writer = GlosWriter::new(Cursor::new(Vec::new()), header.clone())
+                .unwrap()
+                .with_compression(options)
+                .unwrap();
+            for block in blocks {
+                writer.write_block(block).unwrap();
+            }
+            let stored = writer.stored_blocks();
+            (writer.writer.into_inner().unwrap().into_inner(), stored)
+        };
+
+        // Сжатие в обратном порядке — как вперемешку из нескольких потоков
+        let mut compressor = BlockCompressor::new(&header, options).unwrap();
+        let mut packed: Vec<_> = blocks
+            .iter()
+            .rev()
+            .map(|b| compressor.pack(b).unwrap())
+            .collect();
+        packed.reverse();
+        assert!(packed[0].is_none());
+
+        let mut precompressed = blocks.clone();
+        for (block, packed) in precompressed.iter_mut().zip(packed) {
+            compressor.finish(block, packed).unwrap();
+        }
+        assert_eq!(
+            compressor.stored_blocks(),
+            2 + INCOMPRESSIBLE_BACKOFF as u64
+        );
+
+        let (expected, expected_stored) = write(blocks);
+        let (actual, stored) = write(precompressed);
+        assert_eq!(expected_stored, compressor.stored_blocks());
+        assert_eq!(stored, 0);
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_read_all_blocks_helper() {
         let mut raw = Vec::<u8>::new();
@@ -399,9 +2359,21 @@ mod tests {
             raw.extend_from_slice(&make_block(i, 50).serialize().unwrap());
         }
 
-        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
         let blocks = read_all_blocks(&mut reader).unwrap();
         assert_eq!(blocks.len(), 4);
+
+        // Ровно по пределу — можно, на байт меньше — типизированная ошибка
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
+        assert_eq!(read_blocks_limited(&mut reader, 800).unwrap().len(), 4);
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        assert!(matches!(
+            read_blocks_limited(&mut reader, 799),
+            Err(GlosError::MemoryBudgetExceeded {
+                budget: 799,
+                needed: 800,
+            })
+        ));
     }
 
     #[test]
@@ -416,6 +2388,7 @@ mod tests {
     #[test]
     fn test_writer_block_count() {
         let mut raw = Vec::<u8>::new();
+        let bytes_written;
         {
             let cursor = Cursor::new(&mut raw);
             let mut writer = GlosWriter::new(cursor, make_header()).unwrap();
@@ -423,8 +2396,10 @@ mod tests {
             writer.write_block(make_block(1, 200)).unwrap();
             assert_eq!(writer.block_count(), 2);
             assert_eq!(writer.total_samples(), 300);
+            bytes_written = writer.bytes_written();
             writer.finish().unwrap();
         }
+        assert_eq!(bytes_written, raw.len() as u64);
 
         // После finish заголовок должен содержать total_samples = 300
         let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
@@ -434,6 +2409,83 @@ mod tests {
         assert_eq!(blocks.len(), 2);
     }
 
+    #[test]
+    fn test_append_continues_interrupted_file() {
+        let mut header = make_header();
+        header.compression = Compression::Lz4;
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        // Сессия упала: заголовок не финализирован, последний блок
+        // дописан наполовину
+        let mut writer = GlosWriter::new(file.reopen().unwrap(), header).unwrap();
+        for i in 0..3 {
+            writer.write_block(make_block(i * 1_000, 100)).unwrap();
+        }
+        drop(writer);
+        let complete = std::fs::metadata(file.path()).unwrap().len();
+        let partial = make_block(3_000, 100).serialize().unwrap();
+        let mut raw = std::fs::read(file.path()).unwrap();
+        raw.extend_from_slice(&partial[..partial.len() / 2]);
+        std::fs::write(file.path(), &raw).unwrap();
+
+        let mut writer = GlosWriter::append(file.path()).unwrap();
+        assert_eq!(writer.block_count(), 3);
+        assert_eq!(writer.total_samples(), 300);
+        assert_eq!(writer.bytes_written(), complete);
+
+        writer.write_block(make_block(5_000, 200)).unwrap();
+        writer.finish_at(1_700_000_000).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 500);
+        assert_eq!(reader.header().compression, Compression::Lz4);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(
+            blocks.iter().map(|b| b.timestamp_ns).collect::<Vec<_>>(),
+            vec![0, 1_000, 2_000, 5_000]
+        );
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+        reader.validate_totals().unwrap();
+
+        // Не-GLOS файл не дописывается
+        std::fs::write(file.path(), [0u8; 200]).unwrap();
+        assert!(GlosWriter::append(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_checkpoint_rewrites_header_while_writing() {
+        let mut header = make_header();
+        header.sample_rate = 20_000;
+        header.timestamp_start = 1_700_000_000;
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        // Блоки по секунде, контрольная точка раз в 10 с
+        let mut writer = GlosWriter::new(file.reopen().unwrap(), header)
+            .unwrap()
+            .with_checkpoint_interval(Duration::from_secs(10));
+        for i in 0..25u64 {
+            writer
+                .write_block(make_block(i * 1_000_000_000, 20_000))
+                .unwrap();
+        }
+
+        // Сбой: финализации не было, заголовок — с последней точки (блок 20)
+        let raw = std::fs::read(file.path()).unwrap();
+        let crashed = GlosHeader::deserialize(raw[..GLOS_HEADER_SIZE].try_into().unwrap()).unwrap();
+        assert_eq!(crashed.total_samples, 21 * 20_000);
+        assert_eq!(crashed.timestamp_end, 1_700_000_021);
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        assert!(read_all_blocks(&mut reader).unwrap().len() >= 21);
+
+        writer.finish_at(1_700_000_025).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(file.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 25 * 20_000);
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 25);
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+    }
+
     #[test]
     fn test_empty_file_no_blocks() {
         let mut raw = Vec::<u8>::new();
@@ -443,4 +2495,308 @@ mod tests {
         assert!(reader.next_block().is_none());
         assert_eq!(reader.stats().blocks_ok, 0);
     }
+    #[test]
+    fn test_next_aligned_block() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+
+        let data: Vec<u8> = [7i16, -7, 1000, -1000]
+            .iter()
+            .flat_map(|s| s.to_be_bytes())
+            .collect();
+        raw.extend_from_slice(&IqBlock::new(5, 2, data).serialize().unwrap());
+
+        let mut reader = GlosReader::new(Cursor::new(raw))
+            .unwrap()
+            .with_payload_alignment(32)
+            .unwrap();
+        let mut block = reader.next_aligned_block().unwrap().unwrap();
+
+        assert_eq!(block.data.as_bytes().as_ptr() as usize % 32, 0);
+        assert_eq!(block.timestamp_ns, 5);
+        assert!(!block.little_endian);
+
+        block.convert_to_native_endian();
+        assert_eq!(block.as_i16_slice().unwrap(), &[7, -7, 1000, -1000]);
+        assert!(reader.next_aligned_block().is_none());
+
+        let raw = make_header().serialize().unwrap().to_vec();
+        assert!(GlosReader::new(Cursor::new(raw))
+            .unwrap()
+            .with_payload_alignment(3)
+            .is_err());
+    }
+
+    fn int8_file(blocks: &[(u64, Vec<i8>)]) -> Vec<u8> {
+        // 1 МГц: 1 выборка = 1000 нс
+        let mut header = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+        header.iq_format = IqFormat::Int8;
+
+        let mut raw = header.serialize().unwrap().to_vec();
+        for (ts, values) in blocks {
+            let data: Vec<u8> = values.iter().flat_map(|&v| [v as u8, 0]).collect();
+            let block = IqBlock::new(*ts, values.len() as u32, data);
+            raw.extend_from_slice(&block.serialize().unwrap());
+        }
+        raw
+    }
+
+    #[test]
+    fn test_seek_to_timestamp_and_sample() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+        for i in 0..10u64 {
+            raw.extend_from_slice(&make_block(i * 1_000_000, 1000).serialize().unwrap());
+        }
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+
+        let pos = reader.seek_to_timestamp(3_500_000).unwrap().unwrap();
+        assert_eq!(
+            pos,
+            SeekPosition {
+                timestamp_ns: 4_000_000,
+                sample_index: 4000,
+            }
+        );
+        assert_eq!(
+            reader.next_block().unwrap().unwrap().timestamp_ns,
+            4_000_000
+        );
+        assert_eq!(
+            reader.next_block().unwrap().unwrap().timestamp_ns,
+            5_000_000
+        );
+        assert_eq!(reader.stats().blocks_ok, 2);
+
+        // Назад и точно на границу блока
+        let pos = reader.seek_to_timestamp(0).unwrap().unwrap();
+        assert_eq!(pos.sample_index, 0);
+        assert_eq!(
+            reader.seek_to_sample(4000).unwrap().unwrap().sample_index,
+            4000
+        );
+        let pos = reader.seek_to_sample(4001).unwrap().unwrap();
+        assert_eq!(pos.timestamp_ns, 5_000_000);
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 5);
+
+        // За концом файла
+        assert_eq!(reader.seek_to_timestamp(10_000_000).unwrap(), None);
+        assert!(reader.next_block().is_none());
+        assert_eq!(reader.seek_to_sample(10_001).unwrap(), None);
+    }
+
+    #[test]
+    fn test_blocks_between() {
+        // 2 МГц: блоки по 0.5 мс каждую миллисекунду
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+        for i in 0..10u64 {
+            raw.extend_from_slice(&make_block(i * 1_000_000, 1000).serialize().unwrap());
+        }
+
+        let spans = |it: &mut dyn Iterator<Item = GlosResult<IqBlock>>| -> Vec<(u64, u32)> {
+            it.map(|b| b.unwrap())
+                .map(|b| (b.timestamp_ns, b.sample_count))
+                .collect()
+        };
+
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
+
+        let mut whole = reader.blocks_between(1_200_000, 3_250_000).unwrap();
+        assert_eq!(
+            spans(&mut whole),
+            [(1_000_000, 1000), (2_000_000, 1000), (3_000_000, 1000)]
+        );
+
+        let expected = [(1_200_000, 600), (2_000_000, 1000), (3_000_000, 500)];
+        let mut trimmed = reader
+            .blocks_between(1_200_000, 3_250_000)
+            .unwrap()
+            .trimmed();
+        assert_eq!(spans(&mut trimmed), expected);
+
+        // Интервал в разрыве и пустой интервал
+        assert_eq!(
+            reader.blocks_between(1_600_000, 1_900_000).unwrap().count(),
+            0
+        );
+        assert_eq!(
+            reader.blocks_between(5_000_000, 5_000_000).unwrap().count(),
+            0
+        );
+
+        // Без поиска — тот же результат чтением подряд
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let mut scanned = reader.scan_blocks_between(1_200_000, 3_250_000).trimmed();
+        assert_eq!(spans(&mut scanned), expected);
+        assert_eq!(
+            reader.next_block().unwrap().unwrap().timestamp_ns,
+            5_000_000
+        );
+    }
+
+    #[test]
+    fn test_blocks_between_keeps_sync_markers() {
+        let mut header = make_header();
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+        let marker = |id| SyncMarker {
+            id,
+            external_ns: id as u64 * 7,
+        };
+
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&header.serialize().unwrap());
+        for i in 0..4u64 {
+            raw.extend_from_slice(&make_block(i * 1_000_000, 2000).serialize().unwrap());
+            let ts = i * 1_000_000 + 1_000_000;
+            let block = IqBlock::new_sync_marker(ts, marker(i as u32 + 1));
+            raw.extend_from_slice(&block.serialize().unwrap());
+        }
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+        let spans: Vec<(u64, u32, Option<u32>)> = reader
+            .blocks_between(1_000_000, 2_500_000)
+            .unwrap()
+            .trimmed()
+            .map(|b| b.unwrap())
+            .map(|b| {
+                let id = b.attributes.sync_marker.map(|m| m.id);
+                (b.timestamp_ns, b.sample_count, id)
+            })
+            .collect();
+
+        // Метка на самом начале интервала входит в него, на конце — нет
+        assert_eq!(
+            spans,
+            [
+                (1_000_000, 0, Some(1)),
+                (1_000_000, 2000, None),
+                (2_000_000, 0, Some(2)),
+                (2_000_000, 1000, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_every_nth_block() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+        for i in 0..10u64 {
+            raw.extend_from_slice(&make_block(i * 1_000_000, 1000).serialize().unwrap());
+        }
+
+        let timestamps = |raw: &[u8], n: usize| {
+            let mut reader = GlosReader::new(Cursor::new(raw.to_vec())).unwrap();
+            let ts: Vec<u64> = reader
+                .read_every_nth_block(n)
+                .map(|b| b.unwrap().timestamp_ns / 1_000_000)
+                .collect();
+            (ts, reader.stats().blocks_ok)
+        };
+
+        assert_eq!(timestamps(&raw, 3), (vec![0, 3, 6, 9], 4));
+        assert_eq!(timestamps(&raw, 4), (vec![0, 4, 8], 3));
+        assert_eq!(timestamps(&raw, 1).0.len(), 10);
+        assert_eq!(timestamps(&raw, 20), (vec![0], 1));
+
+        // После поиска отсчёт идёт от найденного блока
+        let mut reader = GlosReader::new(Cursor::new(raw.clone())).unwrap();
+        reader.seek_to_timestamp(5_000_000).unwrap();
+        let ts: Vec<u64> = reader
+            .read_every_nth_block(2)
+            .map(|b| b.unwrap().timestamp_ns)
+            .collect();
+        assert_eq!(ts, vec![5_000_000, 7_000_000, 9_000_000]);
+
+        // Мусор в размере пропускаемого блока: пропуск дочитывается
+        // обычным чтением, блок 1 потерян
+        let second = GLOS_HEADER_SIZE + 4000 + GLOS_BLOCK_OVERHEAD;
+        raw[second..second + 4].copy_from_slice(&[0xFF; 4]);
+        assert_eq!(timestamps(&raw, 3).0, vec![0, 4, 7]);
+    }
+
+    #[test]
+    fn test_seek_past_corrupted_blocks() {
+        let mut raw = Vec::<u8>::new();
+        raw.extend_from_slice(&make_header().serialize().unwrap());
+
+        for i in 0..8u64 {
+            let mut block = make_block(i * 1_000_000, 100).serialize().unwrap();
+            match i {
+                // Мусорный размер: быстрый проход останавливается здесь
+                2 => block[..4].copy_from_slice(&u32::MAX.to_be_bytes()),
+                // Ошибка CRC у найденного блока
+                6 => *block.last_mut().unwrap() ^= 0xFF,
+                _ => {}
+            }
+            raw.extend_from_slice(&block);
+        }
+
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+
+        let pos = reader.seek_to_timestamp(5_000_000).unwrap().unwrap();
+        assert_eq!(pos.timestamp_ns, 5_000_000);
+        // Блок 2 потерян: 0, 1, 3, 4
+        assert_eq!(pos.sample_index, 400);
+        assert_eq!(
+            reader.next_block().unwrap().unwrap().timestamp_ns,
+            5_000_000
+        );
+
+        let pos = reader.seek_to_timestamp(6_000_000).unwrap().unwrap();
+        assert_eq!(pos.timestamp_ns, 7_000_000);
+    }
+
+    #[test]
+    fn test_read_exact_samples_across_blocks() {
+        let raw = int8_file(&[(1_000_000, vec![1, 2, 3]), (1_003_000, vec![4, 5, 6, 7])]);
+        let mut reader = GlosReader::new(Cursor::new(raw)).unwrap();
+
+        let first = reader
+            .read_exact_samples(2, GapPolicy::Error)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.timestamp_ns, 1_000_000);
+        assert_eq!(first.samples, [[1.0 / 128.0, 0.0], [2.0 / 128.0, 0.0]]);
+
+        // Пересекает границу блоков
+        let second = reader
+            .read_exact_samples(4, GapPolicy::Error)
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.timestamp_ns, 1_002_000);
+        let i: Vec<f32> = second.samples.iter().map(|s| s[0] * 128.0).collect();
+        assert_eq!(i, [3.0, 4.0, 5.0, 6.0]);
+
+        // Осталась одна выборка — меньше запрошенного
+        assert!(reader
+            .read_exact_samples(2, GapPolicy::Error)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_read_exact_samples_gap_policy() {
+        // Между блоками пропущено 2 выборки
+        let blocks = [(0, vec![1, 2]), (4_000, vec![5, 6])];
+
+        let mut reader = GlosReader::new(Cursor::new(int8_file(&blocks))).unwrap();
+        let filled = reader
+            .read_exact_samples(6, GapPolicy::ZeroFill)
+            .unwrap()
+            .unwrap();
+        let i: Vec<f32> = filled.samples.iter().map(|s| s[0] * 128.0).collect();
+        assert_eq!(i, [1.0, 2.0, 0.0, 0.0, 5.0, 6.0]);
+
+        let mut reader = GlosReader::new(Cursor::new(int8_file(&blocks))).unwrap();
+        assert!(reader.read_exact_samples(3, GapPolicy::Error).is_err());
+        // Чтение продолжается с блока после разрыва
+        let after = reader
+            .read_exact_samples(2, GapPolicy::Error)
+            .unwrap()
+            .unwrap();
+        assert_eq!(after.timestamp_ns, 4_000);
+        assert_eq!(after.samples[0][0] * 128.0, 5.0);
+    }
 }
@@ -0,0 +1,321 @@
+//! Ядра преобразования выборок Int16 ↔ f32: скалярное и векторное на
+//! `std::simd`.
+//!
+//! При чтении Int16-записей время уходит на CRC блоков и на перевод каждой
+//! компоненты из `i16` в `f32`. CRC считает `crc32fast`: он сам выбирает
+//! PCLMULQDQ (x86_64) или инструкции CRC ARMv8 во время выполнения. Для
+//! выборок здесь переносимое векторное ядро по [`LANES`] компонент:
+//! компилятор раскладывает его на инструкции целевой платформы (SSE2 на
+//! x86_64, NEON на aarch64, шире — с `-C target-cpu`), без `unsafe`.
+//!
+//! Векторное ядро даёт тот же результат, что скалярное, бит в бит: деление
+//! на 32767, округление половины от нуля и насыщение как у
+//! `f32::round().clamp()`, NaN → 0 (и считается обрезанным).
+
+use std::simd::{
+    cmp::{SimdOrd, SimdPartialEq, SimdPartialOrd},
+    num::{SimdFloat, SimdInt},
+    Select, Simd, ToBytes,
+};
+
+/// Шкала Int16: `i16 / 32767` ↔ `f32`.
+const I16_SCALE: f32 = 32767.0;
+
+/// Компонент в векторе векторного ядра.
+pub const LANES: usize = 8;
+
+/// Наибольшее f32 меньше 0.5: `trunc(x + copysign(это, x))` округляет
+/// половину от нуля, как `f32::round`.
+const ALMOST_HALF: f32 = 0.499_999_97;
+
+/// Ядро преобразования.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kernel {
+    Scalar,
+    /// `std::simd`, [`LANES`] компонент за шаг
+    Simd,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl Kernel {
+    /// Лучшее ядро этой машины.
+    pub fn detect() -> Self {
+        Kernel::Simd
+    }
+
+    /// Ядро собрано для этой платформы. Векторное ядро переносимо и
+    /// доступно везде.
+    pub fn is_available(self) -> bool {
+        match self {
+            Kernel::Scalar | Kernel::Simd => true,
+        }
+    }
+
+    /// Все ядра, доступные на этой машине (для сравнения и бенчмарков).
+    pub fn available() -> Vec<Kernel> {
+        [Kernel::Scalar, Kernel::Simd]
+            .into_iter()
+            .filter(|k| k.is_available())
+            .collect()
+    }
+
+    /// Переводит компоненты `src` (Int16, порядок байт `little_endian`) в
+    /// `dst`, нормируя к ±1.0.
+    ///
+    /// # Panics
+    ///
+    /// Если `src.len() != dst.len() * 2`.
+    pub fn i16_to_f32(
+        self,
+        src: &[u8],
+        little_endian: bool,
+        dst: &mut [f32],
+    ) {
+        assert_eq!(src.len(), dst.len() * 2, "i16_to_f32: length mismatch");
+
+        match self {
+            Kernel::Simd => i16_to_f32_simd(src, little_endian, dst),
+            Kernel::Scalar => i16_to_f32_scalar(src, little_endian, dst),
+        }
+    }
+
+    /// Переводит компоненты `src` в Int16 (порядок байт `little_endian`)
+    /// в `dst`, округляя и насыщая; возвращает число обрезанных компонент
+    /// (вне шкалы или NaN).
+    ///
+    /// # Panics
+    ///
+    /// Если `dst.len() != src.len() * 2`.
+    pub fn f32_to_i16(
+        self,
+        src: &[f32],
+        little_endian: bool,
+        dst: &mut [u8],
+    ) -> u64 {
+        assert_eq!(dst.len(), src.len() * 2, "f32_to_i16: length mismatch");
+
+        match self {
+            Kernel::Simd => f32_to_i16_simd(src, little_endian, dst),
+            Kernel::Scalar => f32_to_i16_scalar(src, little_endian, dst),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// [`Kernel::i16_to_f32`] лучшим ядром машины.
+pub fn i16_to_f32(
+    src: &[u8],
+    little_endian: bool,
+    dst: &mut [f32],
+) {
+    Kernel::detect().i16_to_f32(src, little_endian, dst)
+}
+
+/// [`Kernel::f32_to_i16`] лучшим ядром машины.
+pub fn f32_to_i16(
+    src: &[f32],
+    little_endian: bool,
+    dst: &mut [u8],
+) -> u64 {
+    Kernel::detect().f32_to_i16(src, little_endian, dst)
+}
+
+fn i16_to_f32_scalar(
+    src: &[u8],
+    little_endian: bool,
+    dst: &mut [f32],
+) {
+    for (c, out) in src.chunks_exact(2).zip(dst) {
+        let v = if little_endian {
+            i16::from_le_bytes([c[0], c[1]])
+        } else {
+            i16::from_be_bytes([c[0], c[1]])
+        };
+        *out = v as f32 / I16_SCALE;
+    }
+}
+
+fn f32_to_i16_scalar(
+    src: &[f32],
+    little_endian: bool,
+    dst: &mut [u8],
+) -> u64 {
+    let mut clipped = 0;
+
+    for (v, out) in src.iter().zip(dst.chunks_exact_mut(2)) {
+        let x = (v * I16_SCALE).round();
+        if !(-32768.0..=32767.0).contains(&x) {
+            clipped += 1;
+        }
+        let x = x.clamp(-32768.0, 32767.0) as i16;
+
+        out.copy_from_slice(&if little_endian {
+            x.to_le_bytes()
+        } else {
+            x.to_be_bytes()
+        });
+    }
+
+    clipped
+}
+
+fn i16_to_f32_simd(
+    src: &[u8],
+    little_endian: bool,
+    dst: &mut [f32],
+) {
+    let scale = Simd::<f32, LANES>::splat(I16_SCALE);
+    let mut src_chunks = src.chunks_exact(LANES * 2);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES);
+
+    for (bytes, out) in (&mut src_chunks).zip(&mut dst_chunks) {
+        let bytes = Simd::<u8, { LANES * 2 }>::from_slice(bytes);
+        let v = if little_endian {
+            Simd::<i16, LANES>::from_le_bytes(bytes)
+        } else {
+            Simd::<i16, LANES>::from_be_bytes(bytes)
+        };
+        (v.cast::<f32>() / scale).copy_to_slice(out);
+    }
+
+    i16_to_f32_scalar(
+        src_chunks.remainder(),
+        little_endian,
+        dst_chunks.into_remainder(),
+    );
+}
+
+fn f32_to_i16_simd(
+    src: &[f32],
+    little_endian: bool,
+    dst: &mut [u8],
+) -> u64 {
+    let mut src_chunks = src.chunks_exact(LANES);
+    let mut dst_chunks = dst.chunks_exact_mut(LANES * 2);
+    let mut clipped = 0;
+
+    for (v, out) in (&mut src_chunks).zip(&mut dst_chunks) {
+        let (r, clip) = round_simd(Simd::from_slice(v));
+        clipped += clip;
+
+        let x = r
+            .simd_clamp(Simd::splat(-32768), Simd::splat(32767))
+            .cast::<i16>();
+        let bytes = if little_endian {
+            x.to_le_bytes()
+        } else {
+            x.to_be_bytes()
+        };
+        bytes.copy_to_slice(out);
+    }
+
+    clipped
+        + f32_to_i16_scalar(
+            src_chunks.remainder(),
+            little_endian,
+            dst_chunks.into_remainder(),
+        )
+}
+
+/// Компоненты → i32 с округлением как у `f32::round` и число обрезанных
+/// (вне шкалы Int16 или NaN).
+fn round_simd(x: Simd<f32, LANES>) -> (Simd<i32, LANES>, u64) {
+    let p = x * Simd::splat(I16_SCALE);
+    let nan = p.simd_ne(p);
+    // Ограничение до округления — только чтобы не выйти за i32; ±1 за
+    // шкалой ещё видно как обрезание
+    let p = nan
+        .select(Simd::splat(0.0), p)
+        .simd_clamp(Simd::splat(-32769.0), Simd::splat(32768.0));
+
+    let half = Simd::splat(ALMOST_HALF).copysign(p);
+    let r = (p + half).cast::<i32>();
+
+    let clip = r.simd_gt(Simd::splat(32767)) | r.simd_lt(Simd::splat(-32768)) | nan.cast();
+    (r, clip.to_bitmask().count_ones() as u64)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Значения на краях шкалы, половины разряда и мусор.
+    fn floats() -> Vec<f32> {
+        let mut v: Vec<f32> = (0..1_000).map(|i| (i as f32 * 0.731).sin() * 1.1).collect();
+        v.extend([
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            1.5,
+            -1.5,
+            0.5 / I16_SCALE,
+            -0.5 / I16_SCALE,
+            2.5 / I16_SCALE,
+            32767.5 / I16_SCALE,
+            -32768.5 / I16_SCALE,
+            f32::NAN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            1e30,
+        ]);
+        v
+    }
+
+    #[test]
+    fn test_kernels_match_scalar() {
+        assert!(Kernel::detect().is_available());
+        let src = floats();
+        let bits = |v: &[f32]| v.iter().map(|f| f.to_bits()).collect::<Vec<_>>();
+
+        for little_endian in [false, true] {
+            let mut expected = vec![0; src.len() * 2];
+            let clipped = Kernel::Scalar.f32_to_i16(&src, little_endian, &mut expected);
+            let mut expected_f = vec![0.0; src.len()];
+            Kernel::Scalar.i16_to_f32(&expected, little_endian, &mut expected_f);
+
+            for kernel in Kernel::available() {
+                // Разная длина — хвосты короче вектора
+                for len in [src.len(), src.len() - 7, 5] {
+                    let mut bytes = vec![0; len * 2];
+                    let c = kernel.f32_to_i16(&src[..len], little_endian, &mut bytes);
+                    assert_eq!(bytes, expected[..len * 2], "{kernel:?} {len}");
+                    if len == src.len() {
+                        assert_eq!(c, clipped, "{kernel:?}");
+                    }
+
+                    let mut back = vec![0.0; len];
+                    kernel.i16_to_f32(&expected[..len * 2], little_endian, &mut back);
+                    assert_eq!(bits(&back), bits(&expected_f[..len]), "{kernel:?} {len}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_i16_to_f32_exhaustive() {
+        let src: Vec<u8> = (i16::MIN..=i16::MAX).flat_map(i16::to_le_bytes).collect();
+        let mut expected = vec![0.0; src.len() / 2];
+        Kernel::Scalar.i16_to_f32(&src, true, &mut expected);
+
+        for kernel in Kernel::available() {
+            let mut out = vec![0.0; src.len() / 2];
+            kernel.i16_to_f32(&src, true, &mut out);
+            let diff = out
+                .iter()
+                .zip(&expected)
+                .position(|(a, b)| a.to_bits() != b.to_bits());
+            assert_eq!(diff, None, "{kernel:?}");
+        }
+    }
+}
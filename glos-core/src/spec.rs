@@ -0,0 +1,589 @@
+//! Машиночитаемое описание бинарной компоновки `.glos`.
+//!
+//! Таблицы ниже — единственный источник смещений для сторонних реализаций:
+//! из них генерируются Markdown-таблица приложения спецификации и JSON, а
+//! тесты сверяют их с тем, что реально пишет [`GlosHeaderExt::serialize`] и
+//! [`IqBlockExt::serialize`]. Поэтому код и документ не могут разойтись.
+//!
+//! [`GlosHeaderExt::serialize`]: crate::GlosHeaderExt::serialize
+//! [`IqBlockExt::serialize`]: crate::IqBlockExt::serialize
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::{
+    GLOS_BLOCK_ATTRIBUTES_SIZE, GLOS_BLOCK_ATTRIBUTES_VERSION, GLOS_BLOCK_OVERHEAD,
+    GLOS_HEADER_CRC_OFFSET, GLOS_HEADER_SIZE,
+};
+
+/// Порядок байт поля.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteOrder {
+    /// Однобайтовое поле или массив байт
+    None,
+    /// Всегда big-endian
+    Big,
+    /// Big-endian, little-endian при бите 0 поля `flags`
+    PerFlag,
+}
+
+/// Тип значения поля.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Bytes,
+    U8,
+    U32,
+    U64,
+    I64,
+    F32,
+    /// Зарезервировано, пишется нулями
+    Reserved,
+}
+
+/// Одно поле фиксированной структуры.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    #[serde(rename = "type")]
+    pub ty: FieldType,
+    pub byte_order: ByteOrder,
+    pub description: &'static str,
+}
+
+/// Компоновка одной версии формата.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatLayout {
+    pub version: u8,
+    pub header_size: usize,
+    /// CRC заголовка покрывает байты `[0..header_crc_covers)`
+    pub header_crc_covers: usize,
+    pub header: &'static [FieldSpec],
+    /// Поля блока до полезной нагрузки; смещения от начала блока
+    pub block_prefix: &'static [FieldSpec],
+    /// Необязательное поле атрибутов (бит 31 `sample_count`): идёт сразу за
+    /// `block_prefix` и сдвигает полезную нагрузку на свой размер
+    pub block_attributes: &'static [FieldSpec],
+    /// Смещение полезной нагрузки (N байт) от начала блока
+    pub block_payload_offset: usize,
+    /// Поля после полезной нагрузки; смещения от её конца
+    pub block_trailer: &'static [FieldSpec],
+    /// CRC блока покрывает байты `[block_crc_from..payload_end)`
+    pub block_crc_from: usize,
+}
+
+/// Поля заголовка: у v1 и v2 совпадают.
+const HEADER: &[FieldSpec] = &[
+    field("magic", 0, 4, FieldType::Bytes, ByteOrder::None, "ASCII \"GLOS\""),
+    field("version", 4, 1, FieldType::U8, ByteOrder::None, "Format version"),
+    field(
+        "flags",
+        5,
+        1,
+        FieldType::U8,
+        ByteOrder::None,
+        "Bit 0: LE, bit 1: content-defined blocks, bit 2: I/Q swapped, bit 3: spectrum inverted, bit 4: LE samples",
+    ),
+    field("reserved0", 6, 6, FieldType::Reserved, ByteOrder::None, "Must be 0x00"),
+    field("sdr_type", 12, 1, FieldType::U8, ByteOrder::None, "SdrType enum"),
+    field("iq_format", 13, 1, FieldType::U8, ByteOrder::None, "IqFormat enum"),
+    field("compression", 14, 1, FieldType::U8, ByteOrder::None, "Compression enum"),
+    field("reserved1", 15, 1, FieldType::Reserved, ByteOrder::None, "Must be 0x00"),
+    field("sample_rate", 16, 4, FieldType::U32, ByteOrder::PerFlag, "Hz"),
+    field("center_freq", 20, 8, FieldType::U64, ByteOrder::PerFlag, "Hz"),
+    field("gain_db", 28, 4, FieldType::F32, ByteOrder::PerFlag, "Receiver gain, dB"),
+    field("timestamp_start", 32, 8, FieldType::U64, ByteOrder::PerFlag, "Unix seconds"),
+    field(
+        "timestamp_end",
+        40,
+        8,
+        FieldType::U64,
+        ByteOrder::PerFlag,
+        "Unix seconds, 0 = ongoing",
+    ),
+    field("total_samples", 48, 8, FieldType::U64, ByteOrder::PerFlag, "IQ samples in file"),
+    field(
+        "time_source",
+        56,
+        1,
+        FieldType::U8,
+        ByteOrder::None,
+        "TimeSource enum, 0 = host clock",
+    ),
+    field("reserved2", 57, 7, FieldType::Reserved, ByteOrder::None, "Must be 0x00"),
+    field(
+        "clock_offset_ns",
+        64,
+        8,
+        FieldType::I64,
+        ByteOrder::PerFlag,
+        "Reference − host clock, ns; already applied to block timestamps",
+    ),
+    field(
+        "header_crc32",
+        72,
+        4,
+        FieldType::U32,
+        ByteOrder::Big,
+        "CRC-32 (IEEE) of bytes [0..72)",
+    ),
+    field("reserved3", 76, 52, FieldType::Reserved, ByteOrder::None, "Must be 0x00"),
+];
+
+/// Поля блока до полезной нагрузки (v1).
+const BLOCK_PREFIX: &[FieldSpec] = &[
+    field(
+        "content_size",
+        0,
+        4,
+        FieldType::U32,
+        ByteOrder::Big,
+        "sample_count + timestamp_ns + payload = 12+N",
+    ),
+    field(
+        "sample_count",
+        4,
+        4,
+        FieldType::U32,
+        ByteOrder::Big,
+        "IQ pairs in block",
+    ),
+    field(
+        "timestamp_ns",
+        8,
+        8,
+        FieldType::U64,
+        ByteOrder::Big,
+        "Unix nanoseconds",
+    ),
+];
+
+/// Поля блока после полезной нагрузки (v1).
+const BLOCK_TRAILER: &[FieldSpec] = &[field(
+    "block_crc32",
+    0,
+    4,
+    FieldType::U32,
+    ByteOrder::Big,
+    "CRC-32 (IEEE) of bytes [4..16+N)",
+)];
+
+/// Компоновка формата v1.
+pub const V1: FormatLayout = FormatLayout {
+    version: 1,
+    header_size: GLOS_HEADER_SIZE,
+    header_crc_covers: GLOS_HEADER_CRC_OFFSET,
+    header: HEADER,
+    block_prefix: BLOCK_PREFIX,
+    block_attributes: &[],
+    block_payload_offset: 16,
+    block_trailer: BLOCK_TRAILER,
+    block_crc_from: 4,
+};
+
+/// Компоновка формата v2: заголовок и кадр v1 плюс необязательные атрибуты
+/// блока.
+pub const V2: FormatLayout = FormatLayout {
+    version: GLOS_BLOCK_ATTRIBUTES_VERSION,
+    block_prefix: &[
+        field(
+            "content_size",
+            0,
+            4,
+            FieldType::U32,
+            ByteOrder::Big,
+            "sample_count + timestamp_ns + attributes + payload = 12+A+N",
+        ),
+        field(
+            "sample_count",
+            4,
+            4,
+            FieldType::U32,
+            ByteOrder::Big,
+            "IQ pairs in block (bits 0-30), bit 31: attributes present",
+        ),
+        field(
+            "timestamp_ns",
+            8,
+            8,
+            FieldType::U64,
+            ByteOrder::Big,
+            "Unix nanoseconds",
+        ),
+    ],
+    block_attributes: &[
+        field(
+            "block_flags",
+            16,
+            4,
+            FieldType::U32,
+            ByteOrder::Big,
+            "Bit 0: overload, bit 1: gain changed, bit 2: samples dropped, bit 3: sync marker; bits 8-31: marker id (bit 3)",
+        ),
+        field(
+            "block_gain_db",
+            20,
+            4,
+            FieldType::F32,
+            ByteOrder::Big,
+            "New receiver gain, dB (bit 1); external time high half (bit 3); else 0",
+        ),
+        field(
+            "dropped_samples",
+            24,
+            4,
+            FieldType::U32,
+            ByteOrder::Big,
+            "Samples lost before the block (bit 2); external time low half (bit 3); else 0",
+        ),
+    ],
+    block_trailer: &[field(
+        "block_crc32",
+        0,
+        4,
+        FieldType::U32,
+        ByteOrder::Big,
+        "CRC-32 (IEEE) of bytes [4..16+A+N)",
+    )],
+    ..V1
+};
+
+/// Все известные версии формата.
+pub const LAYOUTS: &[FormatLayout] = &[V1, V2];
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl FormatLayout {
+    /// Поле заголовка по имени.
+    pub fn header_field(
+        &self,
+        name: &str,
+    ) -> Option<&'static FieldSpec> {
+        self.header.iter().find(|f| f.name == name)
+    }
+
+    /// Размер блока без полезной нагрузки.
+    pub fn block_overhead(&self) -> usize {
+        self.block_payload_offset + self.block_trailer.iter().map(|f| f.size).sum::<usize>()
+    }
+
+    /// Размер поля атрибутов блока (0, если версия их не знает).
+    pub fn block_attributes_size(&self) -> usize {
+        self.block_attributes.iter().map(|f| f.size).sum()
+    }
+
+    /// Таблицы заголовка и блока в Markdown (для приложения спецификации).
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "#### Заголовок v{} ({} байт)\n",
+            self.version, self.header_size
+        );
+        table_head(&mut out);
+        for f in self.header {
+            table_row(&mut out, &f.offset.to_string(), &f.size.to_string(), f);
+        }
+
+        let _ = writeln!(out, "\n#### Блок v{}\n", self.version);
+        table_head(&mut out);
+        for f in self.block_prefix {
+            table_row(&mut out, &f.offset.to_string(), &f.size.to_string(), f);
+        }
+
+        // Атрибуты необязательны: за ними всё сдвигается на A = 0 или их
+        // размер
+        let payload = if self.block_attributes.is_empty() {
+            self.block_payload_offset.to_string()
+        } else {
+            for f in self.block_attributes {
+                table_row(&mut out, &f.offset.to_string(), &f.size.to_string(), f);
+            }
+            format!("{}+A", self.block_payload_offset)
+        };
+
+        let _ = writeln!(
+            out,
+            "| {payload} | N | `payload` | bytes | per flag | IQ samples, N = sample_count × sample size |",
+        );
+
+        for f in self.block_trailer {
+            let offset = match f.offset {
+                0 => format!("{payload}+N"),
+                o => format!("{payload}+N+{o}"),
+            };
+            table_row(&mut out, &offset, &f.size.to_string(), f);
+        }
+
+        if !self.block_attributes.is_empty() {
+            let _ = writeln!(
+                out,
+                "\nA = {} при установленном бите 31 `sample_count`, иначе 0.",
+                self.block_attributes_size()
+            );
+        }
+
+        out
+    }
+
+    /// Компоновка в JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("layout is always serializable")
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Компоновка версии `version`, если она известна.
+pub fn layout(version: u8) -> Option<&'static FormatLayout> {
+    LAYOUTS.iter().find(|l| l.version == version)
+}
+
+const fn field(
+    name: &'static str,
+    offset: usize,
+    size: usize,
+    ty: FieldType,
+    byte_order: ByteOrder,
+    description: &'static str,
+) -> FieldSpec {
+    FieldSpec {
+        name,
+        offset,
+        size,
+        ty,
+        byte_order,
+        description,
+    }
+}
+
+fn table_head(out: &mut String) {
+    out.push_str("| Смещение | Размер | Поле | Тип | Порядок байт | Описание |\n");
+    out.push_str("| -------- | ------ | ---- | --- | ------------ | -------- |\n");
+}
+
+fn table_row(
+    out: &mut String,
+    offset: &str,
+    size: &str,
+    f: &FieldSpec,
+) {
+    let ty = match f.ty {
+        FieldType::Bytes => "bytes",
+        FieldType::U8 => "u8",
+        FieldType::U32 => "u32",
+        FieldType::U64 => "u64",
+        FieldType::I64 => "i64",
+        FieldType::F32 => "f32",
+        FieldType::Reserved => "reserved",
+    };
+
+    let order = match f.byte_order {
+        ByteOrder::None => "—",
+        ByteOrder::Big => "BE",
+        ByteOrder::PerFlag => "per flag",
+    };
+
+    let _ = writeln!(
+        out,
+        "| {offset} | {size} | `{}` | {ty} | {order} | {} |",
+        f.name, f.description
+    );
+}
+
+// Компоновка блока обязана совпадать с константами формата
+const _: () = assert!(V1.block_payload_offset + 4 == GLOS_BLOCK_OVERHEAD);
+const _: () = {
+    let last = V2.block_attributes[V2.block_attributes.len() - 1];
+    assert!(last.offset + last.size == V2.block_payload_offset + GLOS_BLOCK_ATTRIBUTES_SIZE);
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::{BlockAttributes, GlosHeader, IqBlock, SdrType, TimeSource};
+
+    use super::*;
+    use crate::{
+        crc32_checksum, GlosHeaderExt, IqBlockExt, GLOS_BLOCK_FLAG_GAIN_CHANGED,
+        GLOS_BLOCK_FLAG_OVERLOAD, GLOS_BLOCK_FLAG_SAMPLES_DROPPED, GLOS_BLOCK_HAS_ATTRIBUTES,
+        GLOS_FLAG_LITTLE_ENDIAN,
+    };
+
+    fn read_uint(
+        bytes: &[u8],
+        f: &FieldSpec,
+        little_endian: bool,
+    ) -> u64 {
+        let raw = &bytes[f.offset..f.offset + f.size];
+        let le = match f.byte_order {
+            ByteOrder::PerFlag => little_endian,
+            ByteOrder::Big => false,
+            ByteOrder::None => true,
+        };
+
+        let mut v = 0u64;
+        for i in 0..raw.len() {
+            let b = if le { raw[raw.len() - 1 - i] } else { raw[i] };
+            v = (v << 8) | b as u64;
+        }
+        v
+    }
+
+    #[test]
+    fn test_v1_header_fields_tile_the_header() {
+        let mut next = 0;
+        for f in V1.header {
+            assert_eq!(f.offset, next, "gap or overlap before {}", f.name);
+            next += f.size;
+        }
+        assert_eq!(next, GLOS_HEADER_SIZE);
+
+        let mut next = 0;
+        for f in V1.block_prefix {
+            assert_eq!(f.offset, next, "gap or overlap before {}", f.name);
+            next += f.size;
+        }
+        assert_eq!(next, V1.block_payload_offset);
+        assert_eq!(V1.block_overhead(), GLOS_BLOCK_OVERHEAD);
+        assert!(layout(1).is_some() && layout(2).is_some() && layout(3).is_none());
+    }
+
+    #[test]
+    fn test_v1_header_matches_serializer() {
+        for flags in [0, GLOS_FLAG_LITTLE_ENDIAN] {
+            let mut h = GlosHeader::new(SdrType::PlutoSdr, 2_048_000, 1_575_420_000);
+            h.flags = flags;
+            h.gain_db = 37.5;
+            h.timestamp_start = 1_704_067_200;
+            h.timestamp_end = 1_704_067_260;
+            h.total_samples = 122_880_000;
+            h.time_source = TimeSource::Pps;
+            h.clock_offset_ns = -1_250_000;
+
+            let bytes = h.serialize().unwrap();
+            let le = flags & GLOS_FLAG_LITTLE_ENDIAN != 0;
+            let get = |name| read_uint(&bytes, V1.header_field(name).unwrap(), le);
+
+            let magic = V1.header_field("magic").unwrap();
+            assert_eq!(&bytes[magic.offset..magic.offset + magic.size], b"GLOS");
+            assert_eq!(get("version"), V1.version as u64);
+            assert_eq!(get("flags"), flags as u64);
+            assert_eq!(get("sdr_type"), SdrType::PlutoSdr.as_u8() as u64);
+            assert_eq!(get("sample_rate"), 2_048_000);
+            assert_eq!(get("center_freq"), 1_575_420_000);
+            assert_eq!(get("gain_db"), 37.5f32.to_bits() as u64);
+            assert_eq!(get("timestamp_start"), 1_704_067_200);
+            assert_eq!(get("timestamp_end"), 1_704_067_260);
+            assert_eq!(get("total_samples"), 122_880_000);
+            assert_eq!(get("time_source"), TimeSource::Pps.as_u8() as u64);
+            assert_eq!(get("clock_offset_ns") as i64, -1_250_000);
+            assert_eq!(
+                get("header_crc32"),
+                crc32_checksum(&bytes[..V1.header_crc_covers]) as u64
+            );
+
+            for f in V1.header.iter().filter(|f| f.ty == FieldType::Reserved) {
+                assert!(bytes[f.offset..f.offset + f.size].iter().all(|&b| b == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_v1_block_matches_serializer() {
+        let payload = vec![0xA5u8; 40];
+        let block = IqBlock::new(1_704_067_200_123_456_789, 10, payload.clone());
+        let bytes = block.serialize().unwrap();
+
+        let get = |name: &str| {
+            let f = V1.block_prefix.iter().find(|f| f.name == name).unwrap();
+            read_uint(&bytes, f, false)
+        };
+
+        assert_eq!(get("content_size"), 12 + payload.len() as u64);
+        assert_eq!(get("sample_count"), 10);
+        assert_eq!(get("timestamp_ns"), 1_704_067_200_123_456_789);
+
+        let payload_end = V1.block_payload_offset + payload.len();
+        assert_eq!(&bytes[V1.block_payload_offset..payload_end], &payload[..]);
+
+        let crc = V1.block_trailer[0];
+        let stored = &bytes[payload_end + crc.offset..payload_end + crc.offset + crc.size];
+        assert_eq!(
+            stored,
+            crc32_checksum(&bytes[V1.block_crc_from..payload_end]).to_be_bytes()
+        );
+        assert_eq!(bytes.len(), payload.len() + V1.block_overhead());
+    }
+
+    #[test]
+    fn test_v2_block_attributes_match_serializer() {
+        let payload = vec![0x5Au8; 40];
+        let mut block = IqBlock::new(1_704_067_200_123_456_789, 10, payload.clone());
+        block.attributes = BlockAttributes {
+            overload: true,
+            gain_db: Some(-6.5),
+            dropped_samples: Some(4_096),
+            sync_marker: None,
+        };
+        let bytes = block.serialize().unwrap();
+
+        let prefix = |name: &str| V2.block_prefix.iter().find(|f| f.name == name).unwrap();
+        let attr = |name: &str| V2.block_attributes.iter().find(|f| f.name == name).unwrap();
+
+        let count = read_uint(&bytes, prefix("sample_count"), false);
+        assert_eq!(count, 10 | GLOS_BLOCK_HAS_ATTRIBUTES as u64);
+        assert_eq!(
+            read_uint(&bytes, prefix("content_size"), false),
+            (12 + V2.block_attributes_size() + payload.len()) as u64
+        );
+        assert_eq!(
+            read_uint(&bytes, attr("block_flags"), false),
+            (GLOS_BLOCK_FLAG_OVERLOAD
+                | GLOS_BLOCK_FLAG_GAIN_CHANGED
+                | GLOS_BLOCK_FLAG_SAMPLES_DROPPED) as u64
+        );
+        assert_eq!(
+            read_uint(&bytes, attr("block_gain_db"), false),
+            (-6.5f32).to_bits() as u64
+        );
+        assert_eq!(read_uint(&bytes, attr("dropped_samples"), false), 4_096);
+
+        let start = V2.block_payload_offset + V2.block_attributes_size();
+        assert_eq!(&bytes[start..start + payload.len()], &payload[..]);
+        assert_eq!(
+            bytes.len(),
+            payload.len() + V2.block_overhead() + V2.block_attributes_size()
+        );
+    }
+
+    #[test]
+    fn test_spec_document_is_up_to_date() {
+        let doc = include_str!("../../docs/glos_file_format_spec_v1_0.md");
+
+        for layout in LAYOUTS {
+            assert!(
+                doc.contains(&layout.to_markdown()),
+                "docs/glos_file_format_spec_v1_0.md is out of date for v{}; regenerate the \
+                 appendix with `cargo run -p glos-core --example spec_table`",
+                layout.version
+            );
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&V1.to_json()).unwrap();
+        assert_eq!(json["header"][8]["name"], "sample_rate");
+        assert_eq!(json["header"][8]["byte_order"], "per_flag");
+    }
+}
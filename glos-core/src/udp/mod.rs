@@ -0,0 +1,14 @@
+//! UDP-протокол потока IQ между `glos-replayer` и `glos-recorder`.
+//!
+//! Здесь лежит всё, что нужно обеим сторонам: формат пакетов v1/v2,
+//! нарезка блоков на пакеты и сборка с проверкой дайджестов на приёме.
+
+pub mod packet;
+pub mod verify;
+
+pub use packet::{
+    UdpCompression, UdpFragment, UdpPacket, UdpPacketizer, UdpProtocol, UDP_FLAG_LZ4,
+    UDP_FRAGMENT_COUNT_MASK, UDP_HEADER_SIZE, UDP_MAX_PAYLOAD, UDP_SAMPLE_COUNT_SIZE,
+    UDP_TIMESTAMP_SIZE, UDP_V2_HEADER_SIZE,
+};
+pub use verify::{BlockCheck, StreamVerifier, VerifyStats};
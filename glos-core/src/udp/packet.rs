@@ -0,0 +1,378 @@
+use glos_types::IqBlock;
+
+use crate::{block_digest, IqBlockExt};
+
+/// Максимальный размер UDP payload (стандартный IPv4).
+pub const UDP_MAX_PAYLOAD: usize = 65_507;
+
+pub const UDP_TIMESTAMP_SIZE: usize = 8;
+pub const UDP_SAMPLE_COUNT_SIZE: usize = 2;
+
+/// Размер заголовка UDP-пакета GLOS.
+pub const UDP_HEADER_SIZE: usize = UDP_TIMESTAMP_SIZE + UDP_SAMPLE_COUNT_SIZE;
+
+/// Размер заголовка пакета протокола v2: заголовок v1 + номер и число
+/// фрагментов + дайджест блока.
+pub const UDP_V2_HEADER_SIZE: usize = UDP_HEADER_SIZE + 2 + 8;
+
+/// Бит байта FRAGMENT_COUNT в протоколе v2: данные блока сжаты LZ4.
+pub const UDP_FLAG_LZ4: u8 = 0x80;
+
+/// Маска числа фрагментов в байте FRAGMENT_COUNT.
+pub const UDP_FRAGMENT_COUNT_MASK: u8 = 0x7f;
+
+/// Версия UDP-протокола потока IQ.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UdpProtocol {
+    /// Только метка времени и число выборок
+    #[default]
+    V1,
+    /// Как v1 плюс фрагментация блока и XXH64 его данных (см. [`UdpFragment`])
+    V2,
+}
+
+/// Сжатие данных блока в протоколе v2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UdpCompression {
+    #[default]
+    None,
+    /// LZ4 всего блока до фрагментации (флаг [`UDP_FLAG_LZ4`])
+    Lz4,
+}
+
+/// Фрагмент блока в протоколе v2.
+///
+/// Формат передачи данных (big-endian):
+/// ```text
+/// [0..8]   TIMESTAMP       u64  — метка времени блока (наносекунды)
+/// [8..10]  SAMPLE_COUNT    u16  — количество IQ пар во всём блоке
+/// [10]     FRAGMENT_INDEX  u8   — номер фрагмента, с нуля
+/// [11]     FRAGMENT_COUNT  u8   — фрагментов в блоке (биты 0..7) и
+///                                 UDP_FLAG_LZ4 (бит 7)
+/// [12..20] BLOCK_DIGEST    u64  — XXH64 несжатых IQ данных всего блока
+/// [20..]   IQ_DATA         [u8] — часть IQ байт блока (сжатых, если LZ4)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdpFragment<'a> {
+    pub timestamp_ns: u64,
+    pub sample_count: u16,
+    pub index: u8,
+    pub count: u8,
+    /// Собранные данные блока сжаты LZ4
+    pub compressed: bool,
+    pub digest: u64,
+    pub data: &'a [u8],
+}
+
+/// UDP-пакет с IQ-данными.
+///
+/// Формат передачи данных (big-endian):
+/// ```text
+/// [0..8]  TIMESTAMP       u64  — метка времени блока (наносекунды)
+/// [8..10] SAMPLE_COUNT    u16  — количество IQ пар
+/// [10..]  IQ_DATA         [u8] — сырые IQ байты
+/// ```
+pub struct UdpPacket;
+
+pub struct UdpPacketizer;
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl UdpPacket {
+    /// Сериализует блок в UDP payload.
+    pub fn encode(block: &IqBlock) -> Result<Vec<u8>, String> {
+        let max_data = UDP_MAX_PAYLOAD - UDP_HEADER_SIZE;
+
+        if block.data.len() > max_data {
+            return Err(format!(
+                "Block data {} bytes exceeds UDP payload lomit {} bytes",
+                block.data.len(),
+                max_data,
+            ));
+        }
+
+        if block.sample_count > u16::MAX as u32 {
+            return Err(format!(
+                "sample_count {} exceeds u16 range ({})",
+                block.sample_count,
+                u16::MAX
+            ));
+        }
+
+        let sample_count = block.sample_count as u16;
+        let mut buf = Vec::with_capacity(UDP_HEADER_SIZE + block.data.len());
+
+        buf.extend_from_slice(&block.timestamp_ns.to_be_bytes());
+        buf.extend_from_slice(&sample_count.to_be_bytes());
+        buf.extend_from_slice(&block.data);
+
+        Ok(buf)
+    }
+
+    /// Десериализует UDP payload в `(timestamp_ns, sample_count, iq_data)`.
+    pub fn decode(buf: &[u8]) -> Result<(u64, u16, &[u8]), String> {
+        if buf.len() < UDP_HEADER_SIZE {
+            return Err(format!(
+                "Packet too short: {} < {}",
+                buf.len(),
+                UDP_HEADER_SIZE,
+            ));
+        }
+
+        let timestamp_ns = u64::from_be_bytes(buf[0..8].try_into().unwrap());
+        let sample_count = u16::from_be_bytes(buf[8..10].try_into().unwrap());
+        let iq_data = &buf[UDP_HEADER_SIZE..];
+
+        Ok((timestamp_ns, sample_count, iq_data))
+    }
+
+    /// Десериализует фрагмент протокола v2.
+    pub fn decode_v2(buf: &[u8]) -> Result<UdpFragment<'_>, String> {
+        if buf.len() < UDP_V2_HEADER_SIZE {
+            return Err(format!(
+                "Packet too short: {} < {}",
+                buf.len(),
+                UDP_V2_HEADER_SIZE,
+            ));
+        }
+
+        let fragment = UdpFragment {
+            timestamp_ns: u64::from_be_bytes(buf[0..8].try_into().unwrap()),
+            sample_count: u16::from_be_bytes(buf[8..10].try_into().unwrap()),
+            index: buf[10],
+            count: buf[11] & UDP_FRAGMENT_COUNT_MASK,
+            compressed: buf[11] & UDP_FLAG_LZ4 != 0,
+            digest: u64::from_be_bytes(buf[12..20].try_into().unwrap()),
+            data: &buf[UDP_V2_HEADER_SIZE..],
+        };
+
+        if fragment.index >= fragment.count {
+            return Err(format!(
+                "Invalid fragment {}/{}",
+                fragment.index, fragment.count
+            ));
+        }
+
+        Ok(fragment)
+    }
+}
+
+impl UdpPacketizer {
+    pub fn packetize(block: &IqBlock) -> Result<Vec<Vec<u8>>, String> {
+        let max_data = UDP_MAX_PAYLOAD - UDP_HEADER_SIZE;
+
+        if block.sample_count > u16::MAX as u32 {
+            return Err("sample_count exceeds u16".into());
+        }
+
+        let mut packets = Vec::new();
+        let mut offset = 0;
+
+        while offset < block.data.len() {
+            let end = (offset + max_data).min(block.data.len());
+            let chunk = &block.data[offset..end];
+            let mut buf = Vec::with_capacity(UDP_HEADER_SIZE + chunk.len());
+
+            buf.extend_from_slice(&block.timestamp_ns.to_be_bytes());
+            buf.extend_from_slice(&(block.sample_count as u16).to_be_bytes());
+            buf.extend_from_slice(chunk);
+
+            packets.push(buf);
+
+            offset = end;
+        }
+
+        Ok(packets)
+    }
+
+    /// Режет блок на пакеты протокола v2 с дайджестом его данных.
+    pub fn packetize_v2(block: &IqBlock) -> Result<Vec<Vec<u8>>, String> {
+        Self::packetize_v2_with(block, UdpCompression::None)
+    }
+
+    /// Пакеты протокола v2 со сжатием данных блока. Дайджест считается по
+    /// несжатым данным, так что сквозная проверка ловит и ошибки сжатия.
+    pub fn packetize_v2_with(
+        block: &IqBlock,
+        compression: UdpCompression,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        let max_data = UDP_MAX_PAYLOAD - UDP_V2_HEADER_SIZE;
+
+        if block.sample_count > u16::MAX as u32 {
+            return Err("sample_count exceeds u16".into());
+        }
+
+        let digest = block_digest(&block.data);
+        let (payload, flags) = match compression {
+            UdpCompression::None => (block.data.clone(), 0),
+            UdpCompression::Lz4 => {
+                let mut compressed = block.clone();
+                compressed.compress().map_err(|e| e.to_string())?;
+                (compressed.data, UDP_FLAG_LZ4)
+            }
+        };
+
+        let count = payload.len().div_ceil(max_data);
+        if count > UDP_FRAGMENT_COUNT_MASK as usize {
+            return Err(format!(
+                "Block data {} bytes needs {count} fragments (max {})",
+                payload.len(),
+                UDP_FRAGMENT_COUNT_MASK
+            ));
+        }
+
+        let mut packets = Vec::with_capacity(count);
+
+        for (index, chunk) in payload.chunks(max_data).enumerate() {
+            let mut buf = Vec::with_capacity(UDP_V2_HEADER_SIZE + chunk.len());
+
+            buf.extend_from_slice(&block.timestamp_ns.to_be_bytes());
+            buf.extend_from_slice(&(block.sample_count as u16).to_be_bytes());
+            buf.push(index as u8);
+            buf.push(count as u8 | flags);
+            buf.extend_from_slice(&digest.to_be_bytes());
+            buf.extend_from_slice(chunk);
+
+            packets.push(buf);
+        }
+
+        Ok(packets)
+    }
+
+    /// Пакеты блока в заданной версии протокола.
+    pub fn packetize_with(
+        block: &IqBlock,
+        protocol: UdpProtocol,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        match protocol {
+            UdpProtocol::V1 => Self::packetize(block),
+            UdpProtocol::V2 => Self::packetize_v2(block),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for UdpProtocol {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            UdpProtocol::V1 => write!(f, "v1"),
+            UdpProtocol::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+impl std::str::FromStr for UdpProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "v1" | "1" => Ok(UdpProtocol::V1),
+            "v2" | "2" => Ok(UdpProtocol::V2),
+            _ => Err(format!("Unknown UDP protocol '{s}'. Use: v1, v2")),
+        }
+    }
+}
+
+impl std::fmt::Display for UdpCompression {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            UdpCompression::None => write!(f, "none"),
+            UdpCompression::Lz4 => write!(f, "lz4"),
+        }
+    }
+}
+
+impl std::str::FromStr for UdpCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "off" => Ok(UdpCompression::None),
+            "lz4" => Ok(UdpCompression::Lz4),
+            _ => Err(format!("Unknown UDP compression '{s}'. Use: none, lz4")),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_udp_packet_encode_decode_roundtrip() {
+        let block = IqBlockExt::new(1_704_067_200_000_000_000, 100, vec![42u8; 400]);
+        let encoded = UdpPacket::encode(&block).unwrap();
+
+        assert_eq!(encoded.len(), UDP_HEADER_SIZE + 400);
+
+        let (ts, count, data) = UdpPacket::decode(&encoded).unwrap();
+
+        assert_eq!(ts, 1_704_067_200_000_000_000);
+        assert_eq!(count, 100);
+        assert_eq!(data, vec![42u8; 400]);
+    }
+
+    #[test]
+    fn test_udp_packet_header_big_endian() {
+        let block = IqBlock::new(0x0102030405060708, 0x0A0B, vec![0u8; 4]);
+        let encoded = UdpPacket::encode(&block).unwrap();
+
+        // timestamp BE
+        assert_eq!(
+            &encoded[0..8],
+            &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+        );
+
+        // sample_count BE (0x0A0B)
+        assert_eq!(&encoded[8..10], &[0x0A, 0x0B]);
+    }
+
+    #[test]
+    fn test_udp_packet_too_large() {
+        let block = IqBlock::new(0, 1, vec![0u8; UDP_MAX_PAYLOAD]);
+
+        assert!(UdpPacket::encode(&block).is_err());
+    }
+
+    #[test]
+    fn test_udp_decode_too_short() {
+        assert!(UdpPacket::decode(&[0u8; 5]).is_err());
+    }
+
+    #[test]
+    fn test_udp_v2_lz4_packetize() {
+        let block = IqBlock::new(42, 1_000, vec![5u8; 4_000]);
+        let packets = UdpPacketizer::packetize_v2_with(&block, UdpCompression::Lz4).unwrap();
+
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].len() < UDP_V2_HEADER_SIZE + 200);
+
+        let f = UdpPacket::decode_v2(&packets[0]).unwrap();
+        assert!(f.compressed);
+        assert_eq!(f.count, 1);
+        assert_eq!(f.digest, block_digest(&block.data));
+
+        let plain = UdpPacketizer::packetize_v2(&block).unwrap();
+        assert!(!UdpPacket::decode_v2(&plain[0]).unwrap().compressed);
+        assert_eq!(
+            "lz4".parse::<UdpCompression>().unwrap(),
+            UdpCompression::Lz4
+        );
+        assert!("zip".parse::<UdpCompression>().is_err());
+    }
+}
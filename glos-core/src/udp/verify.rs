@@ -0,0 +1,292 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
+
+use glos_types::IqBlock;
+
+use super::UdpPacket;
+use crate::{block_digest, IqBlockExt, SessionDigest};
+
+/// Сколько недавно собранных блоков помнить, чтобы не учесть дубликат дважды.
+const RECENT_BLOCKS: usize = 1024;
+
+/// Приёмная сторона сквозной проверки (протокол v2).
+///
+/// Собирает фрагменты блоков, сверяет XXH64 данных с дайджестом из пакета
+/// (после распаковки, если блок пришёл сжатым — см. [`super::UDP_FLAG_LZ4`])
+/// и сворачивает подтверждённые блоки в [`SessionDigest`] в порядке
+/// прихода. Итог сравнивается с дайджестом файла (`glos-analyzer`) или с
+/// дайджестом, который печатает replayer.
+#[derive(Debug, Default)]
+pub struct StreamVerifier {
+    session: SessionDigest,
+    pending: HashMap<u64, PendingBlock>,
+    recent: VecDeque<u64>,
+    stats: VerifyStats,
+}
+
+/// Счётчики проверки.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifyStats {
+    /// Блоков, собранных целиком с совпавшим дайджестом
+    pub verified: u64,
+    /// Блоков, данные которых не совпали с дайджестом
+    pub mismatched: u64,
+    /// Повторно пришедших блоков или фрагментов (не учитываются)
+    pub duplicates: u64,
+    /// Пакетов, которые не удалось разобрать
+    pub malformed: u64,
+    /// Байт сжатых данных в собранных блоках
+    pub compressed_bytes: u64,
+    /// Байт после их распаковки
+    pub decompressed_bytes: u64,
+    /// Время, потраченное на распаковку (нс)
+    pub decompress_ns: u64,
+}
+
+/// Результат сборки одного блока.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockCheck {
+    pub timestamp_ns: u64,
+    /// Дайджест из пакета
+    pub expected: u64,
+    /// Дайджест принятых данных
+    pub actual: u64,
+}
+
+#[derive(Debug)]
+struct PendingBlock {
+    sample_count: u16,
+    compressed: bool,
+    digest: u64,
+    fragments: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl StreamVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Принимает UDP payload. Возвращает результат, когда блок собран
+    /// целиком; повторы и неполные блоки дают `Ok(None)`.
+    pub fn accept(
+        &mut self,
+        packet: &[u8],
+    ) -> Result<Option<BlockCheck>, String> {
+        Ok(self.accept_block(packet)?.map(|(check, _)| check))
+    }
+
+    /// Как [`accept`](Self::accept), но отдаёт и собранный блок (данные
+    /// распакованы) — для приёмника, который пишет поток в файл.
+    pub fn accept_block(
+        &mut self,
+        packet: &[u8],
+    ) -> Result<Option<(BlockCheck, IqBlock)>, String> {
+        let fragment = match UdpPacket::decode_v2(packet) {
+            Ok(f) => f,
+            Err(e) => {
+                self.stats.malformed += 1;
+                return Err(e);
+            }
+        };
+        let ts = fragment.timestamp_ns;
+
+        if self.recent.contains(&ts) {
+            self.stats.duplicates += 1;
+            return Ok(None);
+        }
+
+        let pending = self.pending.entry(ts).or_insert_with(|| PendingBlock {
+            sample_count: fragment.sample_count,
+            compressed: fragment.compressed,
+            digest: fragment.digest,
+            fragments: vec![None; fragment.count as usize],
+            received: 0,
+        });
+
+        if pending.fragments.len() != fragment.count as usize
+            || pending.digest != fragment.digest
+            || pending.compressed != fragment.compressed
+        {
+            self.stats.malformed += 1;
+            return Err(format!(
+                "Fragment of block {ts} disagrees with earlier ones"
+            ));
+        }
+
+        let slot = &mut pending.fragments[fragment.index as usize];
+        if slot.is_some() {
+            self.stats.duplicates += 1;
+            return Ok(None);
+        }
+        *slot = Some(fragment.data.to_vec());
+        pending.received += 1;
+
+        if pending.received < pending.fragments.len() {
+            return Ok(None);
+        }
+
+        let block = self.pending.remove(&ts).expect("pending block");
+        let mut data: Vec<u8> = block.fragments.into_iter().flatten().flatten().collect();
+
+        if block.compressed {
+            let started = Instant::now();
+            let mut iq = IqBlock::new_compressed(ts, block.sample_count as u32, data);
+            let compressed_len = iq.data.len();
+
+            if let Err(e) = iq.decompress() {
+                self.stats.malformed += 1;
+                return Err(format!("Block {ts}: {e}"));
+            }
+
+            self.stats.decompress_ns += started.elapsed().as_nanos() as u64;
+            self.stats.compressed_bytes += compressed_len as u64;
+            self.stats.decompressed_bytes += iq.data.len() as u64;
+            data = iq.data;
+        }
+
+        let check = BlockCheck {
+            timestamp_ns: ts,
+            expected: block.digest,
+            actual: block_digest(&data),
+        };
+
+        if check.is_ok() {
+            self.session.push(check.actual, block.sample_count as u32);
+            self.stats.verified += 1;
+        } else {
+            self.stats.mismatched += 1;
+        }
+
+        if self.recent.len() == RECENT_BLOCKS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(ts);
+
+        Ok(Some((
+            check,
+            IqBlock::new(ts, block.sample_count as u32, data),
+        )))
+    }
+
+    /// Дайджест подтверждённых блоков.
+    pub fn session_digest(&self) -> SessionDigest {
+        self.session
+    }
+
+    pub fn stats(&self) -> VerifyStats {
+        self.stats
+    }
+
+    /// Блоков, у которых пришли не все фрагменты.
+    pub fn incomplete(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Поток принят без потерь и искажений и совпадает с `expected`.
+    pub fn matches(
+        &self,
+        expected: &SessionDigest,
+    ) -> bool {
+        self.stats.mismatched == 0 && self.incomplete() == 0 && self.session == *expected
+    }
+}
+
+impl VerifyStats {
+    /// Степень сжатия принятого потока (None, если блоки не сжаты).
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.compressed_bytes > 0)
+            .then(|| self.decompressed_bytes as f64 / self.compressed_bytes as f64)
+    }
+}
+
+impl BlockCheck {
+    pub fn is_ok(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::udp::{UdpCompression, UdpPacketizer, UDP_MAX_PAYLOAD};
+
+    #[test]
+    fn test_verifier_reassembles_and_matches_file_digest() {
+        // Второй блок не помещается в один пакет
+        let blocks = [
+            IqBlock::new(10, 100, vec![7u8; 400]),
+            IqBlock::new(20, 40_000, vec![3u8; UDP_MAX_PAYLOAD + 1_000]),
+            IqBlock::new(30, 100, vec![9u8; 400]),
+        ];
+
+        let mut verifier = StreamVerifier::new();
+        for block in &blocks {
+            let mut packets = UdpPacketizer::packetize_v2(block).unwrap();
+            // Фрагменты приходят в обратном порядке и с повтором
+            packets.reverse();
+            packets.push(packets[0].clone());
+
+            for p in &packets {
+                verifier.accept(p).unwrap();
+            }
+        }
+
+        let expected = SessionDigest::of_blocks(&blocks);
+        assert!(verifier.matches(&expected));
+        assert_eq!(verifier.stats().verified, 3);
+        assert_eq!(verifier.stats().duplicates, 3);
+        assert_eq!(verifier.stats().compression_ratio(), None);
+    }
+
+    #[test]
+    fn test_verifier_decompresses_lz4_blocks() {
+        // Несжимаемые данные на несколько фрагментов и хорошо сжимаемые
+        let noisy: Vec<u8> = (0..UDP_MAX_PAYLOAD as u32 * 2)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+            .collect();
+        let blocks = [
+            IqBlock::new(10, 100, vec![7u8; 400]),
+            IqBlock::new(20, 40_000, noisy),
+        ];
+
+        let mut verifier = StreamVerifier::new();
+        for block in &blocks {
+            let packets = UdpPacketizer::packetize_v2_with(block, UdpCompression::Lz4).unwrap();
+            for p in &packets {
+                verifier.accept(p).unwrap();
+            }
+        }
+
+        assert!(verifier.matches(&SessionDigest::of_blocks(&blocks)));
+        let stats = verifier.stats();
+        assert_eq!(stats.verified, 2);
+        assert_eq!(stats.decompressed_bytes, (400 + UDP_MAX_PAYLOAD * 2) as u64);
+        assert!(stats.compression_ratio().unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_verifier_detects_corruption() {
+        let block = IqBlock::new(10, 100, vec![7u8; 400]);
+        let mut packet = UdpPacketizer::packetize_v2(&block).unwrap().remove(0);
+        *packet.last_mut().unwrap() ^= 0x01;
+
+        let mut verifier = StreamVerifier::new();
+        let check = verifier.accept(&packet).unwrap().unwrap();
+
+        assert!(!check.is_ok());
+        assert_eq!(verifier.stats().mismatched, 1);
+        assert!(!verifier.matches(&SessionDigest::of_blocks([&block])));
+        assert!(verifier.accept(&[0u8; 5]).is_err());
+    }
+}
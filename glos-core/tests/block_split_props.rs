@@ -0,0 +1,77 @@
+//! Property-тесты разбиения и склейки блоков.
+
+use glos_core::IqBlockExt;
+use glos_types::{IqBlock, IqFormat};
+use proptest::prelude::*;
+
+fn iq_format() -> impl Strategy<Value = IqFormat> {
+    prop_oneof![
+        Just(IqFormat::Int8),
+        Just(IqFormat::Int16),
+        Just(IqFormat::Float32),
+    ]
+}
+
+/// Блок со случайными данными, форматом и частотой дискретизации.
+fn block() -> impl Strategy<Value = (IqBlock, IqFormat, u32)> {
+    (
+        iq_format(),
+        2u32..2_000,
+        0u64..u64::MAX / 2,
+        1_000u32..40_000_000,
+    )
+        .prop_flat_map(|(format, samples, ts, rate)| {
+            let len = samples as usize * format.sample_size();
+            proptest::collection::vec(any::<u8>(), len)
+                .prop_map(move |data| (IqBlock::new(ts, samples, data), format, rate))
+        })
+}
+
+proptest! {
+    #[test]
+    fn split_then_concat_is_identity(
+        (block, format, rate) in block(),
+        at in any::<proptest::sample::Index>(),
+    ) {
+        let idx = 1 + at.index(block.sample_count as usize - 1) as u32;
+        let (a, b) = block.split_at(idx, format, rate).unwrap();
+
+        prop_assert_eq!(a.sample_count + b.sample_count, block.sample_count);
+        prop_assert_eq!(a.timestamp_ns, block.timestamp_ns);
+        prop_assert_eq!(
+            b.timestamp_ns,
+            block.timestamp_ns + (idx as u128 * 1_000_000_000 / rate as u128) as u64
+        );
+
+        let joined = IqBlock::concat(&[a, b], format, rate).unwrap();
+        prop_assert_eq!(joined.timestamp_ns, block.timestamp_ns);
+        prop_assert_eq!(joined.sample_count, block.sample_count);
+        prop_assert_eq!(joined.data, block.data);
+    }
+
+    #[test]
+    fn repeated_splits_stay_contiguous(
+        (block, format, rate) in block(),
+        cuts in proptest::collection::vec(any::<proptest::sample::Index>(), 1..8),
+    ) {
+        // Режем хвост несколько раз подряд: погрешность меток не должна
+        // накапливаться сверх допуска concat
+        let mut parts = Vec::new();
+        let mut rest = block.clone();
+
+        for cut in cuts {
+            if rest.sample_count < 2 {
+                break;
+            }
+            let idx = 1 + cut.index(rest.sample_count as usize - 1) as u32;
+            let (head, tail) = rest.split_at(idx, format, rate).unwrap();
+            parts.push(head);
+            rest = tail;
+        }
+        parts.push(rest);
+
+        let joined = IqBlock::concat(&parts, format, rate).unwrap();
+        prop_assert_eq!(joined.data, block.data);
+        prop_assert_eq!(joined.sample_count, block.sample_count);
+    }
+}
@@ -0,0 +1,246 @@
+//! Property-тесты сериализации заголовка в обоих порядках байт и эталонные
+//! байты из `tests/fixtures`.
+//!
+//! Ожидаемые байты строятся через `to_be_bytes` / `to_le_bytes`, которые не
+//! зависят от порядка байт платформы, так что тесты одинаково ловят ошибку
+//! и на little-endian, и на big-endian машинах.
+
+use glos_core::{
+    GlosHeaderExt, GLOS_FLAG_IQ_SWAPPED, GLOS_FLAG_LITTLE_ENDIAN, GLOS_HEADER_CRC_OFFSET,
+    GLOS_HEADER_SIZE, GLOS_VERSION,
+};
+use glos_types::{Compression, GlosHeader, IqFormat, SdrType, TimeSource};
+use proptest::prelude::*;
+
+/// Эталон: big-endian, PlutoSdr / Float32 / LZ4 (сгенерирован независимо от
+/// кода crate, по спецификации)
+const GOLDEN_BE: &[u8; GLOS_HEADER_SIZE] = include_bytes!("fixtures/header_be.bin");
+
+/// Тот же заголовок в little-endian с флагом `GLOS_FLAG_IQ_SWAPPED`
+const GOLDEN_LE: &[u8; GLOS_HEADER_SIZE] = include_bytes!("fixtures/header_le.bin");
+
+fn golden_header(flags: u8) -> GlosHeader {
+    GlosHeader {
+        version: GLOS_VERSION,
+        flags,
+        sdr_type: SdrType::PlutoSdr,
+        iq_format: IqFormat::Float32,
+        compression: Compression::Lz4,
+        sample_rate: 2_048_000,
+        center_freq: 1_602_562_500,
+        gain_db: 40.5,
+        timestamp_start: 1_704_067_200,
+        timestamp_end: 1_704_070_800,
+        total_samples: 7_372_800_000,
+        time_source: TimeSource::Host,
+        clock_offset_ns: 0,
+    }
+}
+
+/// Заголовок со случайными полями; `little_endian` задаёт бит порядка байт,
+/// остальные флаги случайны.
+fn header(little_endian: bool) -> impl Strategy<Value = GlosHeader> {
+    (
+        any::<u8>(),
+        prop_oneof![
+            Just(SdrType::HackRf),
+            Just(SdrType::PlutoSdr),
+            Just(SdrType::UsrpB200),
+            Just(SdrType::Unknown),
+        ],
+        prop_oneof![
+            Just(IqFormat::Int8),
+            Just(IqFormat::Int16),
+            Just(IqFormat::Float32),
+        ],
+        prop_oneof![Just(Compression::None), Just(Compression::Lz4)],
+        any::<u32>(),
+        any::<u64>(),
+        // Биты, а не f32: NaN и субнормальные числа тоже должны доехать
+        any::<u32>(),
+        any::<[u64; 3]>(),
+        (
+            prop_oneof![
+                Just(TimeSource::Host),
+                Just(TimeSource::Ntp),
+                Just(TimeSource::Pps),
+            ],
+            any::<i64>(),
+        ),
+    )
+        .prop_map(
+            move |(flags, sdr_type, iq_format, compression, rate, freq, gain, ts, time)| {
+                let flags = if little_endian {
+                    flags | GLOS_FLAG_LITTLE_ENDIAN
+                } else {
+                    flags & !GLOS_FLAG_LITTLE_ENDIAN
+                };
+
+                GlosHeader {
+                    version: GLOS_VERSION,
+                    flags,
+                    sdr_type,
+                    iq_format,
+                    compression,
+                    sample_rate: rate,
+                    center_freq: freq,
+                    gain_db: f32::from_bits(gain),
+                    timestamp_start: ts[0],
+                    timestamp_end: ts[1],
+                    total_samples: ts[2],
+                    time_source: time.0,
+                    clock_offset_ns: time.1,
+                }
+            },
+        )
+}
+
+fn any_header() -> impl Strategy<Value = GlosHeader> {
+    any::<bool>().prop_flat_map(header)
+}
+
+/// Числовые поля `[16..56)` и поправка часов `[64..72)` в заданном
+/// порядке байт.
+fn expected_fields(h: &GlosHeader) -> Vec<u8> {
+    let le = h.flags & GLOS_FLAG_LITTLE_ENDIAN != 0;
+    let mut out = Vec::with_capacity(48);
+
+    macro_rules! put {
+        ($v:expr) => {
+            if le {
+                out.extend_from_slice(&$v.to_le_bytes())
+            } else {
+                out.extend_from_slice(&$v.to_be_bytes())
+            }
+        };
+    }
+
+    put!(h.sample_rate);
+    put!(h.center_freq);
+    put!(h.gain_db.to_bits());
+    put!(h.timestamp_start);
+    put!(h.timestamp_end);
+    put!(h.total_samples);
+    put!(h.clock_offset_ns);
+    out
+}
+
+fn assert_same(
+    a: &GlosHeader,
+    b: &GlosHeader,
+) {
+    assert_eq!(a.version, b.version);
+    assert_eq!(a.flags, b.flags);
+    assert_eq!(a.sdr_type, b.sdr_type);
+    assert_eq!(a.iq_format, b.iq_format);
+    assert_eq!(a.compression, b.compression);
+    assert_eq!(a.sample_rate, b.sample_rate);
+    assert_eq!(a.center_freq, b.center_freq);
+    assert_eq!(a.gain_db.to_bits(), b.gain_db.to_bits());
+    assert_eq!(a.timestamp_start, b.timestamp_start);
+    assert_eq!(a.timestamp_end, b.timestamp_end);
+    assert_eq!(a.total_samples, b.total_samples);
+    assert_eq!(a.time_source, b.time_source);
+    assert_eq!(a.clock_offset_ns, b.clock_offset_ns);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1024))]
+
+    #[test]
+    fn roundtrip_big_endian(h in header(false)) {
+        let bytes = h.serialize().unwrap();
+        let parsed = GlosHeader::deserialize(&bytes).unwrap();
+        prop_assert!(!parsed.is_little_endian());
+        assert_same(&parsed, &h);
+    }
+
+    #[test]
+    fn roundtrip_little_endian(h in header(true)) {
+        let bytes = h.serialize().unwrap();
+        let parsed = GlosHeader::deserialize(&bytes).unwrap();
+        prop_assert!(parsed.is_little_endian());
+        assert_same(&parsed, &h);
+    }
+
+    #[test]
+    fn byte_layout_matches_spec(h in any_header()) {
+        let bytes = h.serialize().unwrap();
+
+        prop_assert_eq!(&bytes[0..4], b"GLOS");
+        prop_assert_eq!(bytes[4], GLOS_VERSION);
+        prop_assert_eq!(bytes[5], h.flags);
+        prop_assert_eq!(&bytes[6..12], &[0u8; 6]);
+        prop_assert_eq!(bytes[12], h.sdr_type.as_u8());
+        prop_assert_eq!(bytes[13], h.iq_format.as_u8());
+        prop_assert_eq!(bytes[14], h.compression.as_u8());
+        prop_assert_eq!(bytes[15], 0);
+        let fields = expected_fields(&h);
+        prop_assert_eq!(&bytes[16..56], &fields[..40]);
+        prop_assert_eq!(bytes[56], h.time_source.as_u8());
+        prop_assert_eq!(&bytes[57..64], &[0u8; 7]);
+        prop_assert_eq!(&bytes[64..GLOS_HEADER_CRC_OFFSET], &fields[40..]);
+
+        // CRC всегда big-endian, независимо от флага
+        let crc = crc32fast::hash(&bytes[..GLOS_HEADER_CRC_OFFSET]);
+        prop_assert_eq!(
+            &bytes[GLOS_HEADER_CRC_OFFSET..GLOS_HEADER_CRC_OFFSET + 4],
+            &crc.to_be_bytes()
+        );
+        prop_assert!(bytes[GLOS_HEADER_CRC_OFFSET + 4..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn endianness_flag_only_reverses_fields(h in header(false)) {
+        let mut le = h.clone();
+        le.flags |= GLOS_FLAG_LITTLE_ENDIAN;
+
+        let be_bytes = h.serialize().unwrap();
+        let le_bytes = le.serialize().unwrap();
+
+        // Каждое поле — те же байты в обратном порядке
+        for (start, len) in [(16, 4), (20, 8), (28, 4), (32, 8), (40, 8), (48, 8), (64, 8)] {
+            let mut field = be_bytes[start..start + len].to_vec();
+            field.reverse();
+            prop_assert_eq!(&le_bytes[start..start + len], field.as_slice());
+        }
+
+        prop_assert_eq!(&le_bytes[6..16], &be_bytes[6..16]);
+        prop_assert_eq!(&le_bytes[56..64], &be_bytes[56..64]);
+    }
+
+    #[test]
+    fn corrupted_byte_is_rejected(
+        h in any_header(),
+        at in 0..GLOS_HEADER_CRC_OFFSET + 4,
+        xor in 1u8..=255,
+    ) {
+        let mut bytes = h.serialize().unwrap();
+        bytes[at] ^= xor;
+        prop_assert!(GlosHeader::deserialize(&bytes).is_err());
+    }
+}
+
+#[test]
+fn golden_big_endian_header() {
+    let h = golden_header(0);
+    assert_eq!(&h.serialize().unwrap(), GOLDEN_BE);
+
+    let parsed = GlosHeader::deserialize(GOLDEN_BE).unwrap();
+    assert!(!parsed.is_little_endian());
+    assert_same(&parsed, &h);
+}
+
+#[test]
+fn golden_little_endian_header() {
+    let h = golden_header(GLOS_FLAG_LITTLE_ENDIAN | GLOS_FLAG_IQ_SWAPPED);
+    assert_eq!(&h.serialize().unwrap(), GOLDEN_LE);
+
+    let parsed = GlosHeader::deserialize(GOLDEN_LE).unwrap();
+    assert!(parsed.is_little_endian());
+    assert_same(&parsed, &h);
+
+    // sample_rate = 2_048_000 = 0x001F4000
+    assert_eq!(&GOLDEN_BE[16..20], &[0x00, 0x1F, 0x40, 0x00]);
+    assert_eq!(&GOLDEN_LE[16..20], &[0x00, 0x40, 0x1F, 0x00]);
+}
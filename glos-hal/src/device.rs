@@ -1,9 +1,13 @@
-use std::sync::{atomic::AtomicBool, Arc};
+use std::{
+    ops::RangeInclusive,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use crossbeam_channel::Sender;
+use glos_types::IqFormat;
 
 use crate::{
-    types::{DeviceInfo, IqChunk},
+    types::{DeviceInfo, DeviceKind, DeviceTelemetry, IqChunk},
     HalError, HalStats,
 };
 
@@ -18,4 +22,70 @@ pub trait SdrDevice: Send {
         tx: Sender<IqChunk>,
         stop_flag: Arc<AtomicBool>,
     ) -> Result<HalStats, HalError>;
+
+    /// Датчики устройства (температура, уровень сигнала, счётчики USB).
+    /// Берётся до [`SdrDevice::run`] и опрашивается из другого потока, пока
+    /// идёт стриминг. `None` — устройство датчиков не даёт.
+    fn telemetry(&self) -> Option<Arc<dyn TelemetrySource>> {
+        None
+    }
+}
+
+/// Опрос датчиков устройства во время стриминга.
+pub trait TelemetrySource: Send + Sync {
+    fn read(&self) -> Result<DeviceTelemetry, HalError>;
+}
+
+/// Найденное устройство: что передать в `--device` и в каких пределах его
+/// можно настроить.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceDescriptor {
+    pub kind: DeviceKind,
+    pub name: String,
+    pub serial: Option<String>,
+    pub sample_rate_hz: RangeInclusive<u32>,
+    pub center_freq_hz: RangeInclusive<u64>,
+    pub gain_db: RangeInclusive<f32>,
+    pub sample_format: IqFormat,
+}
+
+/// Бэкенды, собранные в эту сборку (по features).
+pub fn backends() -> Vec<DeviceKind> {
+    [
+        (cfg!(feature = "sim"), DeviceKind::Simulated),
+        (cfg!(feature = "hackrf"), DeviceKind::HackRf),
+        (cfg!(feature = "pluto"), DeviceKind::PlutoSdr),
+    ]
+    .into_iter()
+    .filter_map(|(compiled, kind)| compiled.then_some(kind))
+    .collect()
+}
+
+/// Опрашивает бэкенд `kind`: подключённые устройства или причина, по
+/// которой опросить их не удалось.
+pub fn probe(kind: &DeviceKind) -> Result<Vec<DeviceDescriptor>, HalError> {
+    match kind {
+        #[cfg(feature = "sim")]
+        DeviceKind::Simulated => Ok(vec![crate::SimulatedDevice::descriptor()]),
+        #[cfg(feature = "hackrf")]
+        DeviceKind::HackRf => Err(HalError::Other(
+            "HackRF driver is not implemented yet".to_string(),
+        )),
+        #[cfg(feature = "pluto")]
+        DeviceKind::PlutoSdr => Err(HalError::Other(
+            "PlutoSDR driver is not implemented yet".to_string(),
+        )),
+        #[allow(unreachable_patterns)]
+        _ => Err(HalError::Unsupported),
+    }
+}
+
+/// Устройства всех собранных бэкендов. Бэкенды, которые не удалось
+/// опросить, пропускаются — подробности даёт [`probe`].
+pub fn enumerate() -> Vec<DeviceDescriptor> {
+    backends()
+        .iter()
+        .filter_map(|kind| probe(kind).ok())
+        .flatten()
+        .collect()
 }
@@ -1,17 +1,28 @@
 use std::{
     f32::consts::PI,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
-    thread,
-    time::{Duration, Instant},
+    time::Duration,
 };
 
 use crossbeam_channel::{Sender, TrySendError};
-use glos_types::IqFormat;
+use glos_types::{BlockAttributes, IqFormat, SharedClock, SystemClock};
 
-use crate::{DeviceInfo, HalError, HalStats, IqChunk, SdrDevice};
+use crate::{
+    DeviceDescriptor, DeviceInfo, DeviceKind, DeviceTelemetry, HalError, HalStats, IqChunk,
+    SdrDevice, TelemetrySource,
+};
+
+const SIM_NAME: &str = "Simulate SDR";
+const SIM_SERIAL: &str = "SIM-0001";
+
+/// Модель нагрева: температура простоя, прирост под нагрузкой и
+/// постоянная времени (с).
+const SIM_IDLE_TEMP_C: f64 = 35.0;
+const SIM_LOAD_TEMP_C: f64 = 25.0;
+const SIM_HEATING_SECS: f64 = 600.0;
 
 /// Генерация синтетический IQ сигнал (комплексная синусойда) для тестов.
 pub struct SimulatedDevice {
@@ -20,9 +31,55 @@ pub struct SimulatedDevice {
     pub gain_db: f32,
     pub chunk_samples: u32,
     pub tone_freq_hz: f32,
+    /// Детерминированный режим (None = реальное время, как настоящий SDR)
+    pub deterministic: Option<SimDeterminism>,
+    /// Часы для pacing'а (в тестах — `VirtualClock`)
+    pub clock: SharedClock,
+    /// Счётчики для модели датчиков (см. [`SdrDevice::telemetry`])
+    pub sensors: Arc<SimSensors>,
+}
+
+/// Датчики симулятора: «USB»-байты — выданные chunk'и, температура растёт
+/// с длительностью стриминга к установившейся. Никак не влияет на сигнал.
+#[derive(Debug, Default)]
+pub struct SimSensors {
+    bytes: AtomicU64,
+    streamed_ns: AtomicU64,
+    tone_dbfs: AtomicU64,
+}
+
+/// Параметры детерминированного режима симулятора.
+///
+/// Устройство не спит (виртуальные часы), не теряет chunk'и (блокирующая
+/// отправка) и выдаёт ровно `chunks` порций, после чего завершается.
+/// Сигнал считается только сложением/умножением f64 — результат побитово
+/// совпадает между запусками и платформами.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimDeterminism {
+    /// Seed генератора шума
+    pub seed: u64,
+    /// Сколько chunk'ов выдать
+    pub chunks: u64,
+    /// Амплитуда равномерного шума (0 = чистый тон)
+    pub noise_amplitude: i16,
 }
 
 impl SimulatedDevice {
+    /// Описание для перечисления устройств. Симулятор принимает любые
+    /// значения, пределы объявлены как у HackRF One, чтобы настройки,
+    /// проверенные на нём, годились и для железа.
+    pub fn descriptor() -> DeviceDescriptor {
+        DeviceDescriptor {
+            kind: DeviceKind::Simulated,
+            name: SIM_NAME.to_string(),
+            serial: Some(SIM_SERIAL.to_string()),
+            sample_rate_hz: 2_000_000..=20_000_000,
+            center_freq_hz: 1_000_000..=6_000_000_000,
+            gain_db: 0.0..=102.0,
+            sample_format: IqFormat::Int16,
+        }
+    }
+
     pub fn new(
         sample_rate_hz: u32,
         center_freq_hz: u64,
@@ -34,15 +91,157 @@ impl SimulatedDevice {
             gain_db,
             chunk_samples: 4_096,
             tone_freq_hz: 1_000.0,
+            deterministic: None,
+            clock: SystemClock::shared(),
+            sensors: Arc::default(),
         }
     }
+
+    /// Подменяет часы, по которым устройство выдерживает темп.
+    pub fn with_clock(
+        mut self,
+        clock: SharedClock,
+    ) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Включает детерминированный режим: ровно `chunks` порций с шумом из
+    /// `seed`, без привязки к реальному времени.
+    pub fn deterministic(
+        mut self,
+        seed: u64,
+        chunks: u64,
+    ) -> Self {
+        self.deterministic = Some(SimDeterminism {
+            seed,
+            chunks,
+            noise_amplitude: 64,
+        });
+        self
+    }
+
+    fn run_deterministic(
+        &mut self,
+        det: SimDeterminism,
+        tx: Sender<IqChunk>,
+        stop_flag: Arc<AtomicBool>,
+    ) -> Result<HalStats, HalError> {
+        let mut stats = HalStats::default();
+        let mut rng = det.seed;
+        let amplitude = 32_767.0 - det.noise_amplitude as f64;
+        let step =
+            2.0 * std::f64::consts::PI * self.tone_freq_hz as f64 / self.sample_rate_hz as f64;
+        let mut global_sample: u64 = 0;
+
+        for _ in 0..det.chunks {
+            if stop_flag.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let mut data =
+                Vec::with_capacity(self.chunk_samples as usize * IqFormat::Int16.sample_size());
+
+            for i in 0..self.chunk_samples as u64 {
+                let (sin, cos) = portable_sin_cos(step * (global_sample + i) as f64);
+                let i_val = (amplitude * sin) as i16 + noise(&mut rng, det.noise_amplitude);
+                let q_val = (amplitude * cos) as i16 + noise(&mut rng, det.noise_amplitude);
+
+                data.extend_from_slice(&i_val.to_be_bytes());
+                data.extend_from_slice(&q_val.to_be_bytes());
+            }
+
+            let chunk = IqChunk {
+                first_sample: global_sample,
+                sample_count: self.chunk_samples,
+                data,
+                attributes: BlockAttributes::default(),
+            };
+
+            // Блокирующая отправка: медленный потребитель не должен менять
+            // содержимое потока
+            if tx.send(chunk).is_err() {
+                break;
+            }
+
+            stats.chunks_sent += 1;
+            global_sample += self.chunk_samples as u64;
+            self.sensors.record(self.chunk_samples, self.sample_rate_hz);
+        }
+
+        Ok(stats)
+    }
+}
+
+impl SimSensors {
+    /// Учитывает выданный chunk Int16.
+    fn record(
+        &self,
+        samples: u32,
+        sample_rate_hz: u32,
+    ) {
+        self.bytes.fetch_add(
+            samples as u64 * IqFormat::Int16.sample_size() as u64,
+            Ordering::Relaxed,
+        );
+        self.streamed_ns.fetch_add(
+            samples as u64 * 1_000_000_000 / sample_rate_hz.max(1) as u64,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// sin/cos без libm: приведение к [-π, π] и ряд Тейлора. Нужен для
+/// воспроизводимости — системные `sin`/`cos` могут отличаться в младших
+/// битах между платформами.
+fn portable_sin_cos(x: f64) -> (f64, f64) {
+    use std::f64::consts::PI;
+
+    let tau = 2.0 * PI;
+    let x = x - tau * (x / tau).round();
+    let x2 = x * x;
+
+    let mut sin = 0.0;
+    let mut cos = 0.0;
+    let mut s_term = x;
+    let mut c_term = 1.0;
+
+    for k in 1..=12 {
+        sin += s_term;
+        cos += c_term;
+
+        let k = k as f64;
+        s_term *= -x2 / ((2.0 * k) * (2.0 * k + 1.0));
+        c_term *= -x2 / ((2.0 * k - 1.0) * (2.0 * k));
+    }
+
+    (sin, cos)
+}
+
+/// Равномерный шум в `[-amplitude, amplitude]` (splitmix64).
+fn noise(
+    state: &mut u64,
+    amplitude: i16,
+) -> i16 {
+    if amplitude == 0 {
+        return 0;
+    }
+
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+
+    let span = 2 * amplitude as u64 + 1;
+    ((z % span) as i64 - amplitude as i64) as i16
 }
 
 impl SdrDevice for SimulatedDevice {
     fn info(&self) -> crate::DeviceInfo {
         DeviceInfo {
-            name: "Simulate SDR".to_string(),
-            serial: Some("SIM-0001".to_string()),
+            name: SIM_NAME.to_string(),
+            serial: Some(SIM_SERIAL.to_string()),
             sample_rate_hz: self.sample_rate_hz,
             center_freq_hz: self.center_freq_hz,
             gain_db: self.gain_db,
@@ -55,11 +254,24 @@ impl SdrDevice for SimulatedDevice {
         tx: Sender<IqChunk>,
         stop_flag: Arc<AtomicBool>,
     ) -> Result<HalStats, HalError> {
+        // Тон идёт почти на полной шкале Int16
+        let tone_dbfs = match self.deterministic {
+            Some(det) => 20.0 * ((32_767.0 - det.noise_amplitude as f64) / 32_768.0).log10(),
+            None => 20.0 * (32_767.0f64 / 32_768.0).log10(),
+        };
+        self.sensors
+            .tone_dbfs
+            .store(tone_dbfs.to_bits(), Ordering::Relaxed);
+
+        if let Some(det) = self.deterministic {
+            return self.run_deterministic(det, tx, stop_flag);
+        }
+
         // период одного сэмпла в нс
         let sample_period_ns = 1_000_000_000f64 / self.sample_rate_hz as f64;
         let mut stats = HalStats::default();
 
-        let start_mono = Instant::now();
+        let start_mono = self.clock.now();
 
         let mut global_sample: u64 = 0;
         let mut _chunks_sent: u64 = 0;
@@ -85,8 +297,10 @@ impl SdrDevice for SimulatedDevice {
             let chunk_data = std::mem::take(&mut data);
 
             let chunk = IqChunk {
+                first_sample: global_sample,
                 sample_count: self.chunk_samples,
                 data: chunk_data,
+                attributes: BlockAttributes::default(),
             };
 
             match tx.try_send(chunk) {
@@ -101,17 +315,38 @@ impl SdrDevice for SimulatedDevice {
 
             global_sample += self.chunk_samples as u64;
             _chunks_sent += 1;
+            self.sensors.record(self.chunk_samples, self.sample_rate_hz);
 
             // pacing — синхронизация по реальному времени
             let expected = Duration::from_nanos((global_sample as f64 * sample_period_ns) as u64);
 
-            let elapsed = start_mono.elapsed();
+            let elapsed = self.clock.now().saturating_sub(start_mono);
 
             if expected > elapsed {
-                thread::sleep(expected - elapsed);
+                self.clock.sleep(expected - elapsed);
             }
         }
 
         Ok(stats)
     }
+
+    fn telemetry(&self) -> Option<Arc<dyn TelemetrySource>> {
+        Some(self.sensors.clone())
+    }
+}
+
+impl TelemetrySource for SimSensors {
+    fn read(&self) -> Result<DeviceTelemetry, HalError> {
+        let streamed_secs = self.streamed_ns.load(Ordering::Relaxed) as f64 / 1e9;
+        let temperature =
+            SIM_IDLE_TEMP_C + SIM_LOAD_TEMP_C * (1.0 - (-streamed_secs / SIM_HEATING_SECS).exp());
+
+        Ok(DeviceTelemetry {
+            temperature_c: Some(temperature as f32),
+            rssi_dbfs: (streamed_secs > 0.0)
+                .then(|| f64::from_bits(self.tone_dbfs.load(Ordering::Relaxed)) as f32),
+            usb_errors: Some(0),
+            usb_bytes: Some(self.bytes.load(Ordering::Relaxed)),
+        })
+    }
 }
@@ -1,4 +1,4 @@
-use glos_types::IqFormat;
+use glos_types::{BlockAttributes, IqFormat};
 
 /// Тип SDR устройства (выбор при старте).
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -25,10 +25,31 @@ pub struct DeviceInfo {
 /// Порция сырых IQ байт, полученная от устройства за один callback/poll.
 #[derive(Debug, Clone)]
 pub struct IqChunk {
+    /// Номер первой выборки chunk'а в потоке устройства. Считается и по
+    /// потерянным chunk'ам, так что разрыв номеров — это пропуск в потоке
+    pub first_sample: u64,
     /// Кол-во IQ пар в `data`
     pub sample_count: u32,
     /// Сырые байты
     pub data: Vec<u8>,
+    /// Что устройство сообщило о приёме chunk'а: перегрузка, смена
+    /// усиления. Попадает в атрибуты блоков с этими выборками
+    pub attributes: BlockAttributes,
+}
+
+/// Показания датчиков устройства. `None` — такого датчика у устройства
+/// нет или он сейчас не читается.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeviceTelemetry {
+    /// Температура платы или трансивера (°C)
+    pub temperature_c: Option<f32>,
+    /// Уровень сигнала на входе АЦП (дБ от полной шкалы)
+    pub rssi_dbfs: Option<f32>,
+    /// Ошибок передачи по USB с начала стриминга
+    pub usb_errors: Option<u64>,
+    /// Байт, принятых по USB с начала стриминга (по разности двух замеров
+    /// считается пропускная способность)
+    pub usb_bytes: Option<u64>,
 }
 
 #[derive(Debug, Default)]
@@ -0,0 +1,86 @@
+use crossbeam_channel::{Receiver, Sender};
+
+/// Сколько свободных буферов держать про запас: один копится, второй
+/// пишется, остальные сглаживают очередь.
+pub const SPARE_BLOCK_BUFFERS: usize = 4;
+
+/// Переиспользуемые буферы данных блоков.
+///
+/// Накопление копирует выборки прямо в буфер блока и отдаёт его писателю
+/// целиком; писатель после записи возвращает опустевший буфер сюда. Так на
+/// высоких частотах дискретизации блоки не аллоцируются и не сдвигаются в
+/// памяти. Клоны разделяют один запас.
+#[derive(Debug, Clone)]
+pub struct BlockBuffers {
+    /// Ёмкость нового буфера — размер полного блока
+    block_bytes: usize,
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlockBuffers {
+    pub fn new(block_bytes: usize) -> Self {
+        let (tx, rx) = crossbeam_channel::bounded(SPARE_BLOCK_BUFFERS);
+        Self {
+            block_bytes,
+            tx,
+            rx,
+        }
+    }
+
+    /// Пустой буфер на целый блок: из запаса, если там есть, иначе новый.
+    pub fn take(&self) -> Vec<u8> {
+        self.rx
+            .try_recv()
+            .unwrap_or_else(|_| Vec::with_capacity(self.block_bytes))
+    }
+
+    /// Возвращает буфер в запас. Меньшие блока (например, сжатые данные)
+    /// и не поместившиеся в запас буферы освобождаются.
+    pub fn give(
+        &self,
+        mut buf: Vec<u8>,
+    ) {
+        if buf.capacity() < self.block_bytes {
+            return;
+        }
+        buf.clear();
+        let _ = self.tx.try_send(buf);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_buffers_reuse() {
+        let buffers = BlockBuffers::new(1024);
+
+        let mut buf = buffers.take();
+        assert!(buf.capacity() >= 1024);
+        buf.extend_from_slice(&[1; 1024]);
+        let ptr = buf.as_ptr();
+        buffers.give(buf);
+
+        let reused = buffers.take();
+        assert!(reused.is_empty());
+        assert_eq!(reused.as_ptr(), ptr);
+
+        // Маленький буфер не возвращается, лишние — тоже
+        buffers.give(Vec::with_capacity(16));
+        assert!(buffers.rx.is_empty());
+        for _ in 0..SPARE_BLOCK_BUFFERS + 2 {
+            buffers.give(Vec::with_capacity(1024));
+        }
+        assert_eq!(buffers.rx.len(), SPARE_BLOCK_BUFFERS);
+    }
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use glos_core::{GLOS_BLOCK_OVERHEAD, GLOS_MAX_BLOCK_SIZE};
+use glos_types::IqFormat;
+
+/// Цель по умолчанию: 20 мс на блок. Выше ~3,3 Msps блок упирается в
+/// [`MAX_UDP_BLOCK_SAMPLES`] и становится короче.
+pub const DEFAULT_BLOCK_TARGET: BlockTarget = BlockTarget::Duration(Duration::from_millis(20));
+
+/// Верхняя граница авто-размера блока (выборок): поле `sample_count`
+/// UDP-пакета реплеера 16-битное, блоки крупнее не воспроизвести по сети.
+pub const MAX_UDP_BLOCK_SAMPLES: u32 = u16::MAX as u32;
+
+/// Нижняя граница авто-размера блока (выборок): меньше — накладные расходы
+/// на заголовок и CRC становятся заметны.
+pub const MIN_BLOCK_SAMPLES: u32 = 1024;
+
+/// Цель авто-подбора `block_samples`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTarget {
+    /// Длительность блока
+    Duration(Duration),
+    /// Объём IQ данных в блоке (байт, до сжатия)
+    Bytes(usize),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlockTarget {
+    /// Число выборок в блоке для частоты и формата, в пределах
+    /// [`MIN_BLOCK_SAMPLES`]..=[`max_block_samples`], но не больше
+    /// [`MAX_UDP_BLOCK_SAMPLES`].
+    pub fn block_samples(
+        &self,
+        sample_rate_hz: u32,
+        format: IqFormat,
+    ) -> u32 {
+        let samples = match *self {
+            BlockTarget::Duration(d) => (d.as_secs_f64() * sample_rate_hz as f64).round() as u64,
+            BlockTarget::Bytes(b) => (b / format.sample_size()) as u64,
+        };
+
+        let max = max_block_samples(format).min(MAX_UDP_BLOCK_SAMPLES);
+        samples.clamp(MIN_BLOCK_SAMPLES.min(max) as u64, max as u64) as u32
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for BlockTarget {
+    fn default() -> Self {
+        DEFAULT_BLOCK_TARGET
+    }
+}
+
+impl std::fmt::Display for BlockTarget {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            BlockTarget::Duration(d) => write!(f, "{}ms", d.as_secs_f64() * 1e3),
+            BlockTarget::Bytes(b) => write!(f, "{b}B"),
+        }
+    }
+}
+
+impl std::str::FromStr for BlockTarget {
+    type Err = String;
+
+    /// `20ms`, `0.5s`, `500us` — длительность; `256KiB`, `1MiB`, `64KB`,
+    /// `1MB`, `65536B` — объём.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let split = lower
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .ok_or_else(|| format!("Missing unit in block target '{s}'. Use e.g. 20ms, 256KiB"))?;
+        let (num, unit) = lower.split_at(split);
+
+        let n: f64 = num
+            .parse()
+            .map_err(|e| format!("Invalid block target '{s}': {e}"))?;
+
+        if !(n.is_finite() && n > 0.0) {
+            return Err(format!("Block target must be positive, got '{s}'"));
+        }
+
+        let target = match unit.trim() {
+            "us" => BlockTarget::Duration(Duration::from_secs_f64(n / 1e6)),
+            "ms" => BlockTarget::Duration(Duration::from_secs_f64(n / 1e3)),
+            "s" => BlockTarget::Duration(Duration::from_secs_f64(n)),
+            "b" => BlockTarget::Bytes(n as usize),
+            "kb" => BlockTarget::Bytes((n * 1e3) as usize),
+            "mb" => BlockTarget::Bytes((n * 1e6) as usize),
+            "kib" => BlockTarget::Bytes((n * 1024.0) as usize),
+            "mib" => BlockTarget::Bytes((n * 1024.0 * 1024.0) as usize),
+            _ => {
+                return Err(format!(
+                    "Unknown unit in block target '{s}'. Use: us, ms, s, B, KB, MB, KiB, MiB"
+                ))
+            }
+        };
+
+        Ok(target)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Максимум выборок, помещающихся в один блок формата.
+pub fn max_block_samples(format: IqFormat) -> u32 {
+    ((GLOS_MAX_BLOCK_SIZE - GLOS_BLOCK_OVERHEAD) / format.sample_size()) as u32
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_target_parse() {
+        assert_eq!(
+            "20ms".parse::<BlockTarget>(),
+            Ok(BlockTarget::Duration(Duration::from_millis(20)))
+        );
+        assert_eq!(
+            "0.5s".parse::<BlockTarget>(),
+            Ok(BlockTarget::Duration(Duration::from_millis(500)))
+        );
+        assert_eq!(
+            "256KiB".parse::<BlockTarget>(),
+            Ok(BlockTarget::Bytes(262_144))
+        );
+        assert_eq!(
+            "1MB".parse::<BlockTarget>(),
+            Ok(BlockTarget::Bytes(1_000_000))
+        );
+
+        assert!("20".parse::<BlockTarget>().is_err());
+        assert!("20min".parse::<BlockTarget>().is_err());
+        assert!("0ms".parse::<BlockTarget>().is_err());
+    }
+
+    #[test]
+    fn test_block_samples_follow_rate_and_format() {
+        let t = DEFAULT_BLOCK_TARGET;
+
+        // 20 мс — одна и та же длительность при любой частоте
+        assert_eq!(t.block_samples(2_000_000, IqFormat::Int16), 40_000);
+        assert_eq!(t.block_samples(3_000_000, IqFormat::Int16), 60_000);
+        assert_eq!(t.block_samples(10_000, IqFormat::Int16), MIN_BLOCK_SAMPLES);
+
+        // Выше ~3,3 Msps — не больше, чем влезает в UDP-пакет реплеера
+        assert_eq!(
+            t.block_samples(10_000_000, IqFormat::Int16),
+            MAX_UDP_BLOCK_SAMPLES
+        );
+
+        let bytes = BlockTarget::Bytes(256 * 1024);
+        assert_eq!(bytes.block_samples(2_000_000, IqFormat::Int8), 65_535);
+        assert_eq!(bytes.block_samples(2_000_000, IqFormat::Float32), 32_768);
+
+        // Не больше, чем помещается в блок
+        let huge = BlockTarget::Duration(Duration::from_secs(10));
+        let max = huge.block_samples(20_000_000, IqFormat::Float32);
+        assert!(max <= max_block_samples(IqFormat::Float32));
+        assert!(max as usize * 8 + GLOS_BLOCK_OVERHEAD <= GLOS_MAX_BLOCK_SIZE);
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crossbeam_channel::{Sender, TrySendError};
+use glos_core::{
+    dsp::{decode_iq, encode_iq, Mixer, ResampleQuality, Resampler},
+    CompressionOptions, GlosHeaderExt, GlosWriter, IqBlockExt, GLOS_FLAG_CONTENT_DEFINED,
+};
+use glos_types::{Compression, GlosError, GlosHeader, GlosResult, IqBlock, IqFormat};
+use log::warn;
+
+use crate::{parse_freq_hz, RecorderResult};
+
+/// Глубина очереди блоков к ветви. Если обработка или диск ветви отстают
+/// сильнее, ветвь отключается, а основная запись продолжается.
+pub const BRANCH_QUEUE_BLOCKS: usize = 64;
+
+/// Дополнительный выход записи: тот же захват, но со своей обработкой,
+/// форматом, сжатием и файлом.
+///
+/// Например, основной файл — полная полоса в Int16, а ветвь — Float32 с
+/// полосой 500 кГц вокруг интересующего сигнала. Задаётся строкой
+/// `path[,key=value…]` (см. [`FromStr`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchConfig {
+    /// Путь к .glos файлу ветви
+    pub output_path: PathBuf,
+    /// Формат IQ выборок ветви
+    pub iq_format: IqFormat,
+    /// Сжатие блоков ветви
+    pub compression: Compression,
+    /// Сдвиг центра ветви относительно центра записи (Гц): сигнал на
+    /// `center + freq_shift_hz` переносится в ноль (0 = без сдвига)
+    pub freq_shift_hz: f64,
+    /// Частота дискретизации ветви (None = как у устройства)
+    pub sample_rate_hz: Option<u32>,
+    /// Качество фильтра передискретизации
+    pub resample_quality: ResampleQuality,
+}
+
+/// Запись ветви на отдельном потоке.
+///
+/// Блоки основной записи обрабатываются и пишутся параллельно с ней. Сбой
+/// ветви (ошибка ввода-вывода, обработки или переполнение очереди) не
+/// прерывает запись: ветвь отключается с предупреждением, а итог виден в
+/// [`BranchReport`].
+pub struct BranchWriter {
+    tx: Sender<BranchCmd>,
+    handle: JoinHandle<BranchReport>,
+    failed: Arc<AtomicBool>,
+    path: PathBuf,
+}
+
+/// Итог работы ветви после [`BranchWriter::finish`].
+#[derive(Debug, Clone, Default)]
+pub struct BranchReport {
+    pub blocks_written: u64,
+    pub samples_written: u64,
+    /// Причина отключения ветви (None = ветвь дописана полностью)
+    pub error: Option<String>,
+}
+
+enum BranchCmd {
+    Block(IqBlock),
+    Finish(u64),
+}
+
+/// Обработка ветви: формат основной записи → сдвиг частоты →
+/// передискретизация → формат ветви.
+struct BranchDsp {
+    in_format: IqFormat,
+    in_rate: u32,
+    out_format: IqFormat,
+    out_rate: u32,
+    mixer: Option<Mixer>,
+    resampler: Option<Resampler>,
+    /// Метка первой выходной выборки после последнего разрыва (нс) и число
+    /// выборок, выданных с тех пор
+    anchor: Option<(u64, u64)>,
+    /// Где должен начаться следующий входной блок без разрыва (нс)
+    next_input_ns: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl BranchConfig {
+    /// Ветвь без обработки: Int16 без сжатия в `output_path`.
+    pub fn new(output_path: impl Into<PathBuf>) -> Self {
+        Self {
+            output_path: output_path.into(),
+            iq_format: IqFormat::Int16,
+            compression: Compression::None,
+            freq_shift_hz: 0.0,
+            sample_rate_hz: None,
+            resample_quality: ResampleQuality::default(),
+        }
+    }
+
+    /// Заголовок ветви по заголовку основной записи: свои формат, сжатие,
+    /// частота дискретизации и центр.
+    pub fn header(
+        &self,
+        primary: &GlosHeader,
+    ) -> GlosHeader {
+        let mut header = primary.clone();
+
+        header.iq_format = self.iq_format;
+        header.compression = self.compression;
+        header.sample_rate = self.sample_rate_hz.unwrap_or(primary.sample_rate);
+        header.center_freq = (primary.center_freq as f64 + self.freq_shift_hz).round() as u64;
+        // Границы блоков ветви следуют за основными, но длина меняется
+        // передискретизацией
+        header.flags &= !GLOS_FLAG_CONTENT_DEFINED;
+
+        header
+    }
+}
+
+impl BranchWriter {
+    /// Создаёт файл ветви и запускает поток обработки и записи.
+    ///
+    /// Ошибка открытия файла или неверные параметры обработки
+    /// возвращаются сразу, чтобы вызывающий мог решить, продолжать ли без
+    /// ветви.
+    pub fn create(
+        branch: &BranchConfig,
+        primary: &GlosHeader,
+        options: CompressionOptions,
+        checkpoint_interval: Option<Duration>,
+    ) -> RecorderResult<Self> {
+        let path = branch.output_path.clone();
+        let mut dsp = BranchDsp::new(branch, primary)?;
+
+        let mut writer = GlosWriter::new(File::create(&path)?, branch.header(primary))?
+            .with_compression(options)?;
+        if let Some(interval) = checkpoint_interval {
+            writer = writer.with_checkpoint_interval(interval);
+        }
+
+        let (tx, rx) = crossbeam_channel::bounded(BRANCH_QUEUE_BLOCKS);
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let thread_failed = failed.clone();
+        let thread_path = path.clone();
+
+        let handle = std::thread::Builder::new()
+            .name("glos-branch".into())
+            .spawn(move || {
+                let mut writer = Some(writer);
+                let mut report = BranchReport::default();
+
+                for cmd in rx {
+                    match cmd {
+                        BranchCmd::Block(block) => {
+                            let Some(w) = writer.as_mut() else {
+                                continue;
+                            };
+
+                            let written = match dsp.process(&block) {
+                                Ok(Some(b)) => {
+                                    let n = b.sample_count as u64;
+                                    w.write_block(b).map(|()| n)
+                                }
+                                Ok(None) => continue,
+                                Err(e) => Err(e),
+                            };
+
+                            match written {
+                                Ok(n) => {
+                                    report.blocks_written += 1;
+                                    report.samples_written += n;
+                                }
+                                Err(e) => {
+                                    warn!("Branch {thread_path:?} failed: {e}. Disabling");
+                                    thread_failed.store(true, Ordering::Relaxed);
+                                    report.error = Some(e.to_string());
+                                    writer = None;
+                                }
+                            }
+                        }
+                        BranchCmd::Finish(timestamp_end) => {
+                            if let Some(w) = writer.take() {
+                                if let Err(e) = w.finish_at(timestamp_end) {
+                                    report.error = Some(e.to_string());
+                                }
+                            }
+                            break;
+                        }
+                    }
+                }
+
+                report
+            })?;
+
+        Ok(Self {
+            tx,
+            handle,
+            failed,
+            path,
+        })
+    }
+
+    /// Ставит копию блока основной записи в очередь ветви. Не блокируется:
+    /// если очередь полна, ветвь считается отставшей и отключается.
+    pub fn write_block(
+        &self,
+        block: &IqBlock,
+    ) {
+        if self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+
+        match self.tx.try_send(BranchCmd::Block(block.clone())) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!("Branch {:?} fell behind. Disabling", self.path);
+                self.failed.store(true, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.failed.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Ветвь отключена после сбоя.
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Финализирует заголовок ветви тем же `timestamp_end`, что и у
+    /// основного файла, и дожидается потока записи.
+    pub fn finish(
+        self,
+        timestamp_end: u64,
+    ) -> BranchReport {
+        let fell_behind = self.is_failed();
+        // Блокирующая отправка: все ранее поставленные блоки будут дописаны
+        let _ = self.tx.send(BranchCmd::Finish(timestamp_end));
+
+        let mut report = self.handle.join().unwrap_or_else(|_| BranchReport {
+            error: Some("branch thread panicked".into()),
+            ..Default::default()
+        });
+
+        if fell_behind && report.error.is_none() {
+            report.error = Some("branch fell behind the primary writer".into());
+        }
+
+        report
+    }
+}
+
+impl BranchDsp {
+    fn new(
+        branch: &BranchConfig,
+        primary: &GlosHeader,
+    ) -> GlosResult<Self> {
+        if primary.samples_little_endian() {
+            return Err(GlosError::format_violation(
+                "branches require big-endian primary samples",
+            ));
+        }
+
+        let in_rate = primary.sample_rate;
+        let out_rate = branch.sample_rate_hz.unwrap_or(in_rate);
+
+        let mixer = (branch.freq_shift_hz != 0.0)
+            .then(|| Mixer::new(branch.freq_shift_hz, in_rate))
+            .transpose()?;
+        let resampler = (out_rate != in_rate)
+            .then(|| Resampler::new(in_rate, out_rate, branch.resample_quality))
+            .transpose()?;
+
+        Ok(Self {
+            in_format: primary.iq_format,
+            in_rate,
+            out_format: branch.iq_format,
+            out_rate,
+            mixer,
+            resampler,
+            anchor: None,
+            next_input_ns: 0,
+        })
+    }
+
+    /// Обрабатывает блок основной записи. `None` — фильтру пока не хватает
+    /// выборок для выхода.
+    fn process(
+        &mut self,
+        block: &IqBlock,
+    ) -> GlosResult<Option<IqBlock>> {
+        let mut samples = decode_iq(&block.data, self.in_format)?;
+
+        // Пропуск в захвате: фильтр не должен сглаживать через разрыв, а
+        // метки выхода снова отсчитываются от входа. Допуск — одна выборка
+        // на округление и коррекцию дрейфа
+        let tolerance_ns = 1_000_000_000 / self.in_rate as u64 + 1;
+        if self.anchor.is_none() || block.timestamp_ns > self.next_input_ns + tolerance_ns {
+            if let Some(r) = self.resampler.as_mut() {
+                r.reset();
+            }
+            self.anchor = Some((block.timestamp_ns, 0));
+        }
+        self.next_input_ns = block.timestamp_ns + samples_ns(samples.len() as u64, self.in_rate);
+
+        if let Some(m) = self.mixer.as_mut() {
+            m.process(&mut samples);
+        }
+        if let Some(r) = self.resampler.as_mut() {
+            samples = r.process(&samples);
+        }
+
+        if samples.is_empty() {
+            return Ok(None);
+        }
+
+        let Some((start_ns, emitted)) = self.anchor.as_mut() else {
+            return Ok(None);
+        };
+        let timestamp_ns = *start_ns + samples_ns(*emitted, self.out_rate);
+        *emitted += samples.len() as u64;
+
+        Ok(Some(IqBlock::new(
+            timestamp_ns,
+            samples.len() as u32,
+            encode_iq(&samples, self.out_format),
+        )))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+/// `path[,key=value…]`, ключи: `format` (int8, int16, float32), `compress`
+/// (none, lz4, zstd), `rate` (частота дискретизации, `500kHz`), `shift`
+/// (сдвиг центра, `-250kHz`), `quality` (fast, balanced, high).
+///
+/// ```
+/// use glos_recorder::BranchConfig;
+/// use glos_types::IqFormat;
+///
+/// let b: BranchConfig = "narrow.glos,format=float32,rate=500kHz,shift=-250kHz"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(b.iq_format, IqFormat::Float32);
+/// assert_eq!(b.sample_rate_hz, Some(500_000));
+/// assert_eq!(b.freq_shift_hz, -250_000.0);
+/// ```
+impl FromStr for BranchConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let path = parts.next().unwrap_or_default().trim();
+        if path.is_empty() {
+            return Err(format!("Branch '{s}': missing output path"));
+        }
+
+        let mut branch = BranchConfig::new(path);
+
+        for part in parts {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Branch option '{part}': expected key=value"))?;
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "format" => branch.iq_format = value.parse()?,
+                "compress" => branch.compression = value.parse()?,
+                "quality" => branch.resample_quality = value.parse()?,
+                "rate" => {
+                    let rate = parse_freq_hz(value)?;
+                    if rate == 0 || rate > u32::MAX as u64 {
+                        return Err(format!("Branch rate '{value}': out of range"));
+                    }
+                    branch.sample_rate_hz = Some(rate as u32);
+                }
+                "shift" => {
+                    let (sign, abs) = match value.strip_prefix('-') {
+                        Some(v) => (-1.0, v),
+                        None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+                    };
+                    branch.freq_shift_hz = sign * parse_freq_hz(abs)? as f64;
+                }
+                other => {
+                    return Err(format!(
+                    "Unknown branch option '{other}'. Use: format, compress, rate, shift, quality"
+                ))
+                }
+            }
+        }
+
+        Ok(branch)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn samples_ns(
+    samples: u64,
+    rate: u32,
+) -> u64 {
+    (samples as u128 * 1_000_000_000 / rate as u128) as u64
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::SdrType;
+
+    use super::*;
+
+    #[test]
+    fn test_branch_config_parse_and_header() {
+        let b: BranchConfig = "out.glos, format=f32 ,compress=zstd,rate=1MHz,shift=+100kHz"
+            .parse()
+            .unwrap();
+        assert_eq!(b.output_path, PathBuf::from("out.glos"));
+        assert_eq!(b.compression, Compression::Zstd);
+        assert_eq!(b.freq_shift_hz, 100_000.0);
+
+        let mut primary = GlosHeader::new(SdrType::HackRf, 4_000_000, 1_602_000_000);
+        primary.flags |= GLOS_FLAG_CONTENT_DEFINED;
+        let h = b.header(&primary);
+        assert_eq!(h.sample_rate, 1_000_000);
+        assert_eq!(h.center_freq, 1_602_100_000);
+        assert_eq!(h.iq_format, IqFormat::Float32);
+        assert_eq!(h.flags & GLOS_FLAG_CONTENT_DEFINED, 0);
+
+        assert!("".parse::<BranchConfig>().is_err());
+        assert!("out.glos,rate".parse::<BranchConfig>().is_err());
+        assert!("out.glos,level=3".parse::<BranchConfig>().is_err());
+        assert!("out.glos,format=int12".parse::<BranchConfig>().is_err());
+    }
+
+    #[test]
+    fn test_branch_dsp_timestamps_follow_gaps() {
+        let mut branch = BranchConfig::new("unused.glos");
+        branch.iq_format = IqFormat::Float32;
+        branch.sample_rate_hz = Some(500_000);
+        let primary = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+        let mut dsp = BranchDsp::new(&branch, &primary).unwrap();
+
+        // 1000 выборок Int16 = 1 мс
+        let block = |ts| IqBlock::new(ts, 1_000, vec![0x10; 4_000]);
+
+        let first = dsp.process(&block(0)).unwrap().unwrap();
+        let second = dsp.process(&block(1_000_000)).unwrap().unwrap();
+        assert_eq!(first.timestamp_ns, 0);
+        assert_eq!(
+            second.timestamp_ns,
+            first.sample_count as u64 * 2_000,
+            "continues the output stream"
+        );
+        assert_eq!(first.data.len(), first.sample_count as usize * 8);
+
+        // После пропуска в 5 мс метка снова берётся от входа
+        let after_gap = dsp.process(&block(7_000_000)).unwrap().unwrap();
+        assert_eq!(after_gap.timestamp_ns, 7_000_000);
+    }
+}
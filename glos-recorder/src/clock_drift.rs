@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use glos_core::ClockDrift;
+
+/// Раньше оценка не выдаётся: на коротком отрезке джиттер доставки
+/// chunk'ов (USB, планировщик) перекрывает дрейф в единицы ppm.
+pub const DRIFT_MIN_SPAN: Duration = Duration::from_secs(10);
+
+/// Как часто выводить оценку дрейфа в лог.
+pub const DRIFT_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Настройки оценки дрейфа часов устройства.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClockDriftConfig {
+    /// С какого отрезка наблюдения оценка считается годной
+    pub min_span: Duration,
+    /// Период вывода оценки в лог
+    pub log_interval: Duration,
+    /// Считать метки блоков по измеренной частоте устройства, а не по
+    /// номинальной (до появления оценки — по номинальной)
+    pub correct_timestamps: bool,
+}
+
+/// Оценка частоты дискретизации устройства по часам хоста.
+///
+/// Каждый принятый chunk даёт точку «время хоста → номер выборки после
+/// chunk'а»; наклон прямой МНК через эти точки — фактическая частота.
+/// Задержка доставки почти постоянна и уходит в свободный член, джиттер
+/// усредняется, поэтому точность растёт с длиной записи.
+#[derive(Debug, Clone)]
+pub struct DriftEstimator {
+    nominal_rate_hz: u32,
+    min_span: Duration,
+    correct_timestamps: bool,
+    /// Первая точка: от неё отсчитываются обе оси (точность f64)
+    origin: Option<(u64, Duration)>,
+    n: f64,
+    mean_x: f64,
+    mean_y: f64,
+    /// Σ(x − x̄)(y − ȳ) и Σ(x − x̄)² (по Уэлфорду)
+    c_xy: f64,
+    m2_x: f64,
+    span: Duration,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl DriftEstimator {
+    pub fn new(
+        sample_rate_hz: u32,
+        config: &ClockDriftConfig,
+    ) -> Self {
+        Self {
+            nominal_rate_hz: sample_rate_hz,
+            min_span: config.min_span,
+            correct_timestamps: config.correct_timestamps,
+            origin: None,
+            n: 0.0,
+            mean_x: 0.0,
+            mean_y: 0.0,
+            c_xy: 0.0,
+            m2_x: 0.0,
+            span: Duration::ZERO,
+        }
+    }
+
+    /// Учитывает chunk: `sample_end` — номер выборки устройства сразу после
+    /// chunk'а, `host` — время его приёма по часам сессии.
+    pub fn observe(
+        &mut self,
+        sample_end: u64,
+        host: Duration,
+    ) {
+        let (origin_sample, origin_host) = *self.origin.get_or_insert((sample_end, host));
+
+        let x = host.saturating_sub(origin_host).as_secs_f64();
+        let y = sample_end.saturating_sub(origin_sample) as f64;
+
+        self.n += 1.0;
+        let dx = x - self.mean_x;
+        self.mean_x += dx / self.n;
+        self.mean_y += (y - self.mean_y) / self.n;
+        self.c_xy += dx * (y - self.mean_y);
+        self.m2_x += dx * (x - self.mean_x);
+
+        self.span = self.span.max(host.saturating_sub(origin_host));
+    }
+
+    /// Фактическая частота дискретизации (Гц). `None`, пока отрезок
+    /// наблюдения короче `min_span`.
+    pub fn measured_rate(&self) -> Option<f64> {
+        if self.span < self.min_span || self.span.is_zero() || self.m2_x <= 0.0 {
+            return None;
+        }
+
+        let rate = self.c_xy / self.m2_x;
+        (rate.is_finite() && rate > 0.0).then_some(rate)
+    }
+
+    /// Текущая оценка дрейфа для лога и метаданных.
+    pub fn estimate(&self) -> Option<ClockDrift> {
+        self.measured_rate().map(|rate| ClockDrift {
+            ppm: (rate / self.nominal_rate_hz as f64 - 1.0) * 1e6,
+            observed_secs: self.span.as_secs_f64(),
+            timestamps_corrected: self.correct_timestamps,
+        })
+    }
+
+    /// Длительность `samples` выборок (нс): с коррекцией меток — по
+    /// измеренной частоте, иначе и до появления оценки — по номинальной.
+    pub fn samples_to_ns(
+        &self,
+        samples: u64,
+    ) -> u64 {
+        match self.measured_rate().filter(|_| self.correct_timestamps) {
+            Some(rate) => (samples as f64 * 1e9 / rate).round() as u64,
+            None => (samples as u128 * 1_000_000_000 / self.nominal_rate_hz as u128) as u64,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for ClockDriftConfig {
+    fn default() -> Self {
+        Self {
+            min_span: DRIFT_MIN_SPAN,
+            log_interval: DRIFT_LOG_INTERVAL,
+            correct_timestamps: false,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates_ppm_despite_delivery_jitter() {
+        let nominal = 2_000_000u32;
+        // Устройство спешит на 25 ppm
+        let actual = nominal as f64 * (1.0 + 25e-6);
+        let config = ClockDriftConfig {
+            correct_timestamps: true,
+            ..Default::default()
+        };
+        let mut est = DriftEstimator::new(nominal, &config);
+        let mut rng = 12345u64;
+
+        let chunk = 4_096u64;
+        for i in 1..=200_000u64 {
+            let sample_end = i * chunk;
+            // Доставка: постоянная задержка 3 мс и джиттер до 2 мс
+            rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let jitter = (rng >> 33) as f64 / (1u64 << 31) as f64 * 2e-3;
+            let host = sample_end as f64 / actual + 3e-3 + jitter;
+
+            est.observe(sample_end, Duration::from_secs_f64(host));
+
+            if i == 100 {
+                // 0.2 с — оценки ещё нет, счёт по номиналу
+                assert!(est.estimate().is_none());
+                assert_eq!(est.samples_to_ns(2_000_000), 1_000_000_000);
+            }
+        }
+
+        let drift = est.estimate().unwrap();
+        assert!((drift.ppm - 25.0).abs() < 0.5, "{drift:?}");
+        assert!(drift.observed_secs > 400.0);
+        assert!(drift.timestamps_corrected);
+
+        // Секунда по часам хоста — на 50 выборок больше номинала
+        let ns = est.samples_to_ns(2_000_050);
+        assert!(ns.abs_diff(1_000_000_000) < 2_000, "{ns}");
+    }
+
+    #[test]
+    fn test_no_estimate_without_host_time() {
+        // Детерминированный источник без хода часов
+        let config = ClockDriftConfig {
+            min_span: Duration::ZERO,
+            ..Default::default()
+        };
+        let mut est = DriftEstimator::new(1_000_000, &config);
+        for i in 0..100 {
+            est.observe(i * 1_000, Duration::from_secs(5));
+        }
+        assert!(est.measured_rate().is_none());
+        assert_eq!(est.samples_to_ns(1_000_000), 1_000_000_000);
+    }
+}
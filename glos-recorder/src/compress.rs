@@ -0,0 +1,233 @@
+use std::{collections::BTreeMap, thread::JoinHandle};
+
+use crossbeam_channel::{Receiver, Sender};
+use glos_core::BlockCompressor;
+use glos_types::{GlosResult, IqBlock};
+use log::warn;
+
+use crate::RecorderResult;
+
+/// Больше потоков сжатия по умолчанию рекордер не заводит: ядра нужны и
+/// приёму с DSP, а дальше запись всё равно упирается в диск.
+pub const MAX_AUTO_COMPRESS_THREADS: usize = 4;
+
+/// Сколько блоков на поток может быть в работе одновременно.
+const IN_FLIGHT_PER_THREAD: usize = 2;
+
+/// Блок с номером по порядку поступления.
+type Job = (u64, IqBlock);
+
+/// Блок и его сжатые данные от потока сжатия.
+type Packed = (IqBlock, GlosResult<Option<Vec<u8>>>);
+
+/// Сжатие блоков в нескольких потоках между накоплением и очередью записи.
+///
+/// Потоки только сжимают данные ([`BlockCompressor::pack`]); решение, что
+/// записать, принимается по порядку блоков в вызывающем потоке, поэтому
+/// файл побайтно такой же, как при сжатии в самом писателе. Блоки выходят
+/// в том порядке, в каком пришли. Если сжать блок не удалось, он уходит
+/// дальше несжатым — писатель попробует сам.
+pub struct CompressPool {
+    compressor: BlockCompressor,
+    jobs: Sender<Job>,
+    done: Receiver<(u64, Packed)>,
+    workers: Vec<JoinHandle<()>>,
+    /// Сжатые не по порядку и ещё не отданные блоки
+    ready: BTreeMap<u64, Packed>,
+    /// Номер следующего блока на выход
+    next_out: u64,
+    /// Сколько блоков принято
+    submitted: u64,
+    max_in_flight: usize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl CompressPool {
+    /// Запускает `threads` потоков сжатия (хотя бы один).
+    pub fn new(
+        compressor: BlockCompressor,
+        threads: usize,
+    ) -> RecorderResult<Self> {
+        let threads = threads.max(1);
+        let (jobs, jobs_rx) = crossbeam_channel::unbounded::<Job>();
+        let (done_tx, done) = crossbeam_channel::unbounded::<(u64, Packed)>();
+
+        let workers = (0..threads)
+            .map(|i| {
+                let jobs_rx = jobs_rx.clone();
+                let done_tx = done_tx.clone();
+                let packer = compressor.clone();
+                std::thread::Builder::new()
+                    .name(format!("glos-compress-{i}"))
+                    .spawn(move || {
+                        for (seq, block) in jobs_rx {
+                            let packed = packer.pack(&block);
+                            if done_tx.send((seq, (block, packed))).is_err() {
+                                break;
+                            }
+                        }
+                    })
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(Self {
+            compressor,
+            jobs,
+            done,
+            workers,
+            ready: BTreeMap::new(),
+            next_out: 0,
+            submitted: 0,
+            max_in_flight: threads * IN_FLIGHT_PER_THREAD,
+        })
+    }
+
+    /// Отдаёт блок на сжатие. Готовые по порядку блоки передаются в `out`;
+    /// когда в работе слишком много блоков, ждёт, пока потоки их догонят.
+    pub fn submit(
+        &mut self,
+        block: IqBlock,
+        mut out: impl FnMut(IqBlock),
+    ) {
+        if let Err(e) = self.jobs.send((self.submitted, block)) {
+            // Потоков сжатия не осталось — блок пишется как есть
+            warn!("Compression workers are gone. Block left to the writer");
+            out(e.into_inner().1);
+            return;
+        }
+        self.submitted += 1;
+
+        while let Ok(packed) = self.done.try_recv() {
+            self.accept(packed);
+        }
+        self.emit(&mut out);
+
+        while self.submitted - self.next_out >= self.max_in_flight as u64 {
+            match self.done.recv() {
+                Ok(packed) => self.accept(packed),
+                Err(_) => break,
+            }
+            self.emit(&mut out);
+        }
+    }
+
+    /// Дожидается всех блоков, передаёт их в `out` и останавливает потоки.
+    /// Возвращает число блоков, оставленных без сжатия.
+    pub fn finish(
+        mut self,
+        mut out: impl FnMut(IqBlock),
+    ) -> u64 {
+        while self.next_out < self.submitted {
+            match self.done.recv() {
+                Ok(packed) => self.accept(packed),
+                Err(_) => break,
+            }
+            self.emit(&mut out);
+        }
+
+        drop(self.jobs);
+        for w in self.workers {
+            let _ = w.join();
+        }
+
+        self.compressor.stored_blocks()
+    }
+
+    fn accept(
+        &mut self,
+        (seq, packed): (u64, Packed),
+    ) {
+        self.ready.insert(seq, packed);
+    }
+
+    /// Передаёт в `out` блоки, идущие подряд от `next_out`.
+    fn emit(
+        &mut self,
+        out: &mut impl FnMut(IqBlock),
+    ) {
+        while let Some((mut block, packed)) = self.ready.remove(&self.next_out) {
+            let packed = packed.unwrap_or_else(|e| {
+                warn!(
+                    "Compression of block @ {} ns failed: {e}",
+                    block.timestamp_ns
+                );
+                None
+            });
+            if let Err(e) = self.compressor.finish(&mut block, packed) {
+                warn!(
+                    "Compression of block @ {} ns failed: {e}. Block left to the writer",
+                    block.timestamp_ns
+                );
+            }
+
+            self.next_out += 1;
+            out(block);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use glos_core::{CompressionOptions, GlosHeaderExt, GlosWriter, IqBlockExt};
+    use glos_types::{Compression, GlosHeader, SdrType};
+
+    use super::*;
+
+    #[test]
+    fn test_compress_pool_keeps_order_and_output() {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.compression = Compression::Zstd;
+        let options = CompressionOptions {
+            level: 1,
+            ..Default::default()
+        };
+
+        // Блоки разной сжимаемости, чтобы потоки заканчивали не по порядку
+        let blocks: Vec<IqBlock> = (0..64u64)
+            .map(|i| {
+                let data = (0..40_000u64)
+                    .map(|j| ((j * (i % 7 + 1)) % 251) as u8)
+                    .collect();
+                IqBlock::new(i * 1_000_000, 10_000, data)
+            })
+            .collect();
+
+        let compressor = BlockCompressor::new(&header, options).unwrap();
+        let mut pool = CompressPool::new(compressor, 4).unwrap();
+        let mut out = Vec::new();
+        for block in blocks.clone() {
+            pool.submit(block, |b| out.push(b));
+        }
+        assert_eq!(pool.finish(|b| out.push(b)), 0);
+
+        assert_eq!(out.len(), blocks.len());
+        assert!(out.iter().all(|b| b.is_compressed));
+        assert!(out
+            .iter()
+            .zip(&blocks)
+            .all(|(a, b)| a.timestamp_ns == b.timestamp_ns));
+
+        let write = |blocks: Vec<IqBlock>| {
+            let mut bytes = Vec::new();
+            let mut writer = GlosWriter::new(Cursor::new(&mut bytes), header.clone())
+                .unwrap()
+                .with_compression(options)
+                .unwrap();
+            for block in blocks {
+                writer.write_block(block).unwrap();
+            }
+            writer.finish_at(0).unwrap();
+            bytes
+        };
+        assert_eq!(write(out), write(blocks));
+    }
+}
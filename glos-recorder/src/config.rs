@@ -1,7 +1,18 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
+use glos_core::{
+    dsp::IqCorrection, CompressionOptions, ContentChunker, GlosHeaderExt,
+    DEFAULT_CHECKPOINT_INTERVAL, GLOS_BLOCK_ATTRIBUTES_VERSION, GLOS_FLAG_CONTENT_DEFINED,
+    GLOS_FLAG_SAMPLES_LITTLE_ENDIAN,
+};
 use glos_hal::DeviceKind;
-use glos_types::{Compression, IqFormat, SdrType};
+use glos_types::{Compression, GlosHeader, IqFormat, RfChain, SdrType, SharedClock, SystemClock};
+
+use crate::{
+    BranchConfig, ClockDriftConfig, DiskHealthConfig, Notifier, RingAutoTune, RotationConfig,
+    SessionLog, StatsFormat, TelemetryConfig, TimeSyncConfig, WriteQueueConfig,
+    RING_AUTOTUNE_START,
+};
 
 /// Полная конфигурация сессия записи.
 #[derive(Debug, Clone)]
@@ -18,16 +29,108 @@ pub struct RecorderConfig {
     pub iq_format: IqFormat,
     /// Сжатие блоков
     pub compression: Compression,
+    /// Уровень Zstd, порог размера блока и пропуск несжимаемых данных
+    pub compression_options: CompressionOptions,
+    /// Потоков сжатия между накоплением блоков и очередью записи (0 —
+    /// сжимает сам поток записи)
+    pub compress_threads: usize,
+    /// Исправление перепутанных I/Q или инвертированного спектра
+    pub iq_correction: IqCorrection,
+    /// Устройство отдаёт компоненты в little-endian: они пишутся как есть
+    /// с флагом [`GLOS_FLAG_SAMPLES_LITTLE_ENDIAN`], без перестановки байт
+    pub samples_little_endian: bool,
     /// Путь к выходному .glos файлу
     pub output_path: PathBuf,
     /// Ограничение по времени (None = до Ctrl+C)
     pub duration_secs: Option<u64>,
-    /// Выборок в одном IqBlock (влияет на latency и overhead)
+    /// Ровно столько выборок попадёт в файл: последний chunk обрезается
+    /// (None = без ограничения). Не зависит от джиттера часов
+    pub sample_limit: Option<u64>,
+    /// Выборок в одном IqBlock (влияет на latency и overhead). CLI
+    /// подбирает его по частоте через [`crate::BlockTarget`]
     pub block_samples: u32,
+    /// Контентно-зависимые границы блоков (None = по `block_samples`)
+    pub chunking: Option<ContentChunker>,
     /// Ёмкость кольцевого буфера (chunks; 1 chunk ~ 4096 * sample_size байт)
     pub ring_capacity: usize,
+    /// Авто-подбор ёмкости: начать с запаса, замерить пик занятости и
+    /// сообщить (или сохранить в историю) рекомендацию (None = выключено)
+    pub ring_autotune: Option<RingAutoTune>,
     /// Интервал вывода статистики (секунды)
     pub stats_interval_secs: u64,
+    /// Запись метрик в `<basename>.stats.csv|jsonl` с тем же интервалом
+    /// (None = выключено)
+    pub stats_export: Option<StatsFormat>,
+    /// Фиксированное время начала сессии (Unix-нс). Метки блоков и
+    /// заголовка считаются от него, а не от системных часов — вместе с
+    /// детерминированным симулятором даёт побитово одинаковые файлы
+    pub start_time_ns: Option<u64>,
+    /// Часы сессии: ограничение длительности, интервал статистики и
+    /// Unix-время начала (в тестах — `VirtualClock`)
+    pub clock: SharedClock,
+    /// Второй файл, куда параллельно пишется та же запись (лучше на другом
+    /// физическом диске). Сбой зеркала не прерывает запись
+    pub mirror_path: Option<PathBuf>,
+    /// Индексированный архив `.glosa` (см. [`glos_core::archive`]): запись
+    /// дописывается в него членом с именем файла из `output_path`, отдельный
+    /// файл не создаётся (None = обычный файл)
+    pub archive_path: Option<PathBuf>,
+    /// Ротация по размеру или длительности: `output_path` — первый файл
+    /// серии, следующие именуются по шаблону (см. [`crate::RotatingWriter`]).
+    /// С архивом файлы серии пишутся его членами. Несовместима с зеркалом
+    /// (None = один файл)
+    pub rotation: Option<RotationConfig>,
+    /// Предзапись: до срабатывания триггера
+    /// ([`crate::RecordingPipeline::trigger_flag`]) в памяти держится
+    /// только последнее окно такой длины, затем оно сохраняется в файл и
+    /// запись продолжается (None = писать сразу)
+    pub prebuffer: Option<Duration>,
+    /// Следить за реально занятым динамическим диапазоном и в конце
+    /// посоветовать меньший формат (см. [`glos_core::dsp::FormatAdvisor`])
+    pub format_advice: bool,
+    /// Очередь между накоплением блоков и диском: лимит памяти и
+    /// необязательный перелив во временный файл (см. [`crate::WriteQueue`])
+    pub write_queue: WriteQueueConfig,
+    /// Антенна, кабель, МШУ и фильтры перед SDR. Сохраняются в файл
+    /// метаданных рядом с записью (см. [`glos_core::RecordingMetadata`])
+    pub rf_chain: Option<RfChain>,
+    /// Сохранить в файл метаданных миниатюру спектрограммы для предпросмотра
+    /// (см. [`glos_core::dsp::ThumbnailBuilder`])
+    pub thumbnail: bool,
+    /// Наблюдение за диском записи: write amplification и износ по SMART
+    /// (см. [`crate::DiskHealthMonitor`]; None = выключено)
+    pub disk_health: Option<DiskHealthConfig>,
+    /// Опрос датчиков устройства (температура, уровень сигнала, USB): в
+    /// метрики, файл статистики и предупреждения по порогам (см.
+    /// [`crate::TelemetryMonitor`]; None = выключено, как и для устройств
+    /// без датчиков)
+    pub telemetry: Option<TelemetryConfig>,
+    /// Журнал предупреждений рекордера: при финализации события переносятся
+    /// в файл метаданных записи (см. [`crate::SessionLogger`]; None = не
+    /// сохранять)
+    pub session_log: Option<SessionLog>,
+    /// Уведомления о завершении, сбое устройства и заполнении диска
+    /// (см. [`crate::Notifier`]; None = не рассылать)
+    pub notify: Option<Notifier>,
+    /// Оценка дрейфа часов устройства относительно хоста: в лог и в файл
+    /// метаданных, по желанию — с коррекцией меток блоков (см.
+    /// [`crate::DriftEstimator`]; None = выключено)
+    pub clock_drift: Option<ClockDriftConfig>,
+    /// Как часто переписывать заголовок по ходу записи, чтобы после сбоя
+    /// питания в нём были близкие к правде `total_samples` и
+    /// `timestamp_end` (см.
+    /// [`glos_core::GlosWriter::with_checkpoint_interval`]; None = только
+    /// при финализации)
+    pub checkpoint_interval: Option<Duration>,
+    /// Дополнительные выходы того же захвата со своими обработкой,
+    /// форматом, сжатием и файлом (см. [`crate::BranchConfig`]). Сбой ветви
+    /// не прерывает запись
+    pub branches: Vec<BranchConfig>,
+    /// Привязка меток блоков к внешнему эталону времени (NTP или PPS):
+    /// поправка к часам измеряется перед захватом и сохраняется в заголовке
+    /// (см. [`crate::TimeSyncConfig`]; None = часы хоста как есть).
+    /// Фиксированное `start_time_ns` не корректируется
+    pub time_sync: Option<TimeSyncConfig>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -43,6 +146,51 @@ impl RecorderConfig {
             DeviceKind::PlutoSdr => SdrType::PlutoSdr,
         }
     }
+
+    /// Заголовок .glos для сессии, начавшейся в `session_start_unix_ns`.
+    pub fn glos_header(
+        &self,
+        session_start_unix_ns: u64,
+    ) -> GlosHeader {
+        let mut header = GlosHeader::new(self.sdr_type(), self.sample_rate_hz, self.center_freq_hz);
+
+        // Блоки помечаются событиями устройства (перегрузка, потери); блоки
+        // без событий в v2 такие же, как в v1
+        header.version = GLOS_BLOCK_ATTRIBUTES_VERSION;
+        header.gain_db = self.gain_db;
+        header.iq_format = self.iq_format;
+        header.compression = self.compression;
+        header.timestamp_start = session_start_unix_ns / 1_000_000_000;
+
+        if self.chunking.is_some() {
+            header.flags |= GLOS_FLAG_CONTENT_DEFINED;
+        }
+
+        if self.samples_little_endian {
+            header.flags |= GLOS_FLAG_SAMPLES_LITTLE_ENDIAN;
+        }
+
+        header.flags |= self.iq_correction.header_flags();
+        header
+    }
+
+    /// Ёмкость, с которой создаётся кольцевой буфер: при авто-подборе — не
+    /// меньше [`RING_AUTOTUNE_START`], чтобы пик не упирался в потолок.
+    pub fn effective_ring_capacity(&self) -> usize {
+        match self.ring_autotune {
+            Some(_) => self.ring_capacity.max(RING_AUTOTUNE_START),
+            None => self.ring_capacity,
+        }
+    }
+
+    /// Имя члена архива: имя файла из `output_path`.
+    pub fn archive_member_name(&self) -> String {
+        self.output_path
+            .file_name()
+            .unwrap_or(self.output_path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -58,11 +206,37 @@ impl Default for RecorderConfig {
             gain_db: 40.0,
             iq_format: IqFormat::Int16,
             compression: Compression::None,
+            compression_options: CompressionOptions::default(),
+            compress_threads: 0,
+            iq_correction: IqCorrection::default(),
+            samples_little_endian: false,
             output_path: PathBuf::from("recording.glos"),
             duration_secs: None,
+            sample_limit: None,
             block_samples: 50_000,
+            chunking: None,
             ring_capacity: 64, // 64 * 4096 * 4 ~ 1 Мб ring buffer
+            ring_autotune: None,
             stats_interval_secs: 5,
+            stats_export: None,
+            start_time_ns: None,
+            clock: SystemClock::shared(),
+            mirror_path: None,
+            archive_path: None,
+            rotation: None,
+            prebuffer: None,
+            format_advice: false,
+            write_queue: WriteQueueConfig::default(),
+            rf_chain: None,
+            thumbnail: false,
+            disk_health: None,
+            telemetry: None,
+            session_log: None,
+            notify: None,
+            clock_drift: None,
+            checkpoint_interval: Some(DEFAULT_CHECKPOINT_INTERVAL),
+            branches: Vec::new(),
+            time_sync: None,
         }
     }
 }
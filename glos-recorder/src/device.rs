@@ -4,18 +4,17 @@
 // crossbeam_channel используется для асинхронно передачи чанков между потоками.
 // stop_flag: Arc<AtomicBool> поток можно остановить безопасно.
 
-use glos_hal::{DeviceKind, SdrDevice, SimulatedDevice};
+use glos_hal::{DeviceDescriptor, DeviceKind, SdrDevice, SimulatedDevice};
 
 use crate::{RecorderConfig, RecorderError, RecorderResult};
 
 /// Создаёт нужное устройство по конфигурации.
 pub fn create_device(config: &RecorderConfig) -> RecorderResult<Box<dyn SdrDevice>> {
     match &config.device {
-        DeviceKind::Simulated => Ok(Box::new(SimulatedDevice::new(
-            config.sample_rate_hz,
-            config.center_freq_hz,
-            config.gain_db,
-        ))),
+        DeviceKind::Simulated => Ok(Box::new(
+            SimulatedDevice::new(config.sample_rate_hz, config.center_freq_hz, config.gain_db)
+                .with_clock(config.clock.clone()),
+        )),
         DeviceKind::HackRf => {
             #[cfg(feature = "hackrf")]
             {
@@ -44,6 +43,30 @@ pub fn create_device(config: &RecorderConfig) -> RecorderResult<Box<dyn SdrDevic
     }
 }
 
+/// Описание устройства для `glos-recorder list-devices`.
+pub fn describe_device(device: &DeviceDescriptor) -> String {
+    let mhz = |hz: f64| format!("{:.3} MHz", hz / 1e6);
+
+    format!(
+        "{} ({})\n  \
+         --device      : {}\n  \
+         Sample rate   : {} – {}\n  \
+         Center freq   : {} – {}\n  \
+         Gain          : {:.1} – {:.1} dB\n  \
+         Sample format : {}",
+        device.name,
+        device.serial.as_deref().unwrap_or("no serial"),
+        device.kind,
+        mhz(*device.sample_rate_hz.start() as f64),
+        mhz(*device.sample_rate_hz.end() as f64),
+        mhz(*device.center_freq_hz.start() as f64),
+        mhz(*device.center_freq_hz.end() as f64),
+        device.gain_db.start(),
+        device.gain_db.end(),
+        device.sample_format,
+    )
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Тесты
 ////////////////////////////////////////////////////////////////////////////////
@@ -56,6 +79,7 @@ mod tests {
     };
 
     use glos_hal::{IqChunk, SimulatedDevice};
+    use glos_types::SystemClock;
 
     use super::*;
 
@@ -70,6 +94,18 @@ mod tests {
         assert!(info.serial.is_some());
     }
 
+    #[test]
+    fn test_describe_simulated_device() {
+        let devices = glos_hal::enumerate();
+        assert_eq!(devices, vec![SimulatedDevice::descriptor()]);
+
+        let text = describe_device(&devices[0]);
+        assert!(text.starts_with("Simulate SDR (SIM-0001)\n"));
+        assert!(text.contains("--device      : sim\n"));
+        assert!(text.contains("Sample rate   : 2.000 MHz – 20.000 MHz\n"));
+        assert!(text.contains("Gain          : 0.0 – 102.0 dB\n"));
+    }
+
     #[test]
     fn test_simulated_device_generates_chunks() {
         // Буфер достаточно большой чтобы не переполниться за 50мс при 2 Msps:
@@ -85,6 +121,9 @@ mod tests {
                 gain_db: 40.0,
                 chunk_samples: 512,
                 tone_freq_hz: 1_000.0,
+                deterministic: None,
+                clock: SystemClock::shared(),
+                sensors: Default::default(),
             };
             dev.run(tx, stop_clone)
         });
@@ -119,6 +158,9 @@ mod tests {
                 gain_db: 0.0,
                 chunk_samples: 256,
                 tone_freq_hz: 1_000.0,
+                deterministic: None,
+                clock: SystemClock::shared(),
+                sensors: Default::default(),
             };
             dev.run(tx, stop_clone) // 2 аргумента
         });
@@ -149,6 +191,9 @@ mod tests {
                 gain_db: 0.0,
                 chunk_samples: 4,
                 tone_freq_hz: 250.0, // 250 Гц при 1 kHz → 1/4 периода
+                deterministic: None,
+                clock: SystemClock::shared(),
+                sensors: Default::default(),
             };
             dev.run(tx, stop_clone) // 2 аргумента
         });
@@ -167,4 +212,40 @@ mod tests {
         assert!(i0.abs() < 100, "I[0] ≈ 0, got {i0}");
         assert!(q0 > 32_000, "Q[0] ≈ 32767, got {q0}");
     }
+
+    fn collect_all(dev: &mut SimulatedDevice) -> Vec<IqChunk> {
+        // Ёмкость 1: блокирующая отправка не должна терять данные
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+
+        std::thread::scope(|s| {
+            let handle = s.spawn(|| dev.run(tx, stop_flag.clone()));
+            let chunks: Vec<IqChunk> = rx.iter().collect();
+            let hal_stats = handle.join().unwrap().unwrap();
+
+            assert_eq!(hal_stats.chunks_dropped, 0);
+            assert_eq!(hal_stats.chunks_sent, chunks.len() as u64);
+            chunks
+        })
+    }
+
+    #[test]
+    fn test_simulated_device_deterministic() {
+        let mut a = SimulatedDevice::new(2_000_000, 1_602_000_000, 40.0).deterministic(7, 25);
+        let mut b = SimulatedDevice::new(2_000_000, 1_602_000_000, 40.0).deterministic(7, 25);
+        let mut c = SimulatedDevice::new(2_000_000, 1_602_000_000, 40.0).deterministic(8, 25);
+
+        let ca = collect_all(&mut a);
+        let cb = collect_all(&mut b);
+        let cc = collect_all(&mut c);
+
+        // Ровно N chunk'ов, побитово одинаковые при одном seed
+        assert_eq!(ca.len(), 25);
+        assert!(ca.iter().zip(&cb).all(|(x, y)| x.data == y.data));
+        assert_ne!(ca[0].data, cc[0].data, "другой seed — другой шум");
+
+        // Тон на месте: Q[0] ≈ cos(0) * амплитуда
+        let q0 = i16::from_be_bytes([ca[0].data[2], ca[0].data[3]]);
+        assert!(q0 > 32_000, "Q[0] ≈ 32767, got {q0}");
+    }
 }
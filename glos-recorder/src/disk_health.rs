@@ -0,0 +1,468 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use log::{info, warn};
+use serde_json::Value;
+
+use crate::RecorderMetrics;
+
+/// Порог износа носителя по умолчанию (% ресурса по SMART).
+pub const DEFAULT_WEAR_WARN_PCT: u8 = 80;
+
+/// Интервал опроса SMART по умолчанию: счётчики износа меняются медленно, а
+/// `smartctl` заметно дороже чтения счётчиков устройства.
+pub const DEFAULT_SMART_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Размер сектора в счётчиках `/sys/block/*/stat` — всегда 512 байт,
+/// независимо от реального сектора носителя.
+const SYSFS_SECTOR_BYTES: u64 = 512;
+
+/// Атрибуты ATA SMART с остатком ресурса SSD (нормализованное значение
+/// 100 → 0): Wear_Leveling_Count, Percent_Lifetime_Remain, SSD_Life_Left,
+/// Media_Wearout_Indicator.
+const ATA_WEAR_ATTRIBUTES: [u64; 4] = [177, 202, 231, 233];
+
+/// Настройки наблюдения за диском записи.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskHealthConfig {
+    /// Как часто читать счётчик записанных на устройство байт
+    pub interval: Duration,
+    /// Опрашивать SMART через `smartctl` (нужна фича `smart`)
+    pub smart: bool,
+    /// Как часто опрашивать SMART
+    pub smart_interval: Duration,
+    /// Устройство для SMART (None = диск, на котором лежит запись)
+    pub smart_device: Option<PathBuf>,
+    /// Предупреждать, когда износ по SMART достигает этого процента
+    pub wear_warn_pct: u8,
+}
+
+/// Состояние носителя по SMART.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaHealth {
+    /// Израсходованный ресурс (%): NVMe `percentage_used` или остаток из
+    /// ATA-атрибута износа. Для NVMe может превышать 100
+    pub wear_pct: Option<u8>,
+    /// Накопитель сам сообщает о проблеме (SMART failed, NVMe
+    /// `critical_warning`)
+    pub critical: bool,
+}
+
+/// Байты, записанные на блочное устройство с момента создания счётчика.
+///
+/// Считает все записи на устройство (в том числе метаданные ФС, журнал и
+/// чужие процессы), поэтому в сравнении с полезными байтами даёт оценку
+/// сверху для write amplification на уровне ОС. Износ внутри SSD (FTL)
+/// виден только по SMART.
+#[derive(Debug)]
+pub struct DeviceWriteCounter {
+    stat_path: PathBuf,
+    start_sectors: u64,
+}
+
+/// Периодически читает счётчики диска записи и SMART, обновляет
+/// [`RecorderMetrics`] и предупреждает о пересечении порога износа.
+pub struct DiskHealthMonitor {
+    config: DiskHealthConfig,
+    metrics: Arc<RecorderMetrics>,
+    counter: Option<DeviceWriteCounter>,
+    smart_device: Option<PathBuf>,
+    last_smart: Option<Instant>,
+    wear_warned: bool,
+    critical_warned: bool,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl MediaHealth {
+    /// Разбирает вывод `smartctl --json -H -A`.
+    pub fn from_smartctl_json(json: &str) -> Result<Self, String> {
+        let v: Value = serde_json::from_str(json).map_err(|e| format!("smartctl JSON: {e}"))?;
+        let mut health = MediaHealth::default();
+
+        if let Some(nvme) = v.get("nvme_smart_health_information_log") {
+            health.wear_pct = nvme
+                .get("percentage_used")
+                .and_then(Value::as_u64)
+                .map(|p| p.min(u8::MAX as u64) as u8);
+            health.critical |= nvme
+                .get("critical_warning")
+                .and_then(Value::as_u64)
+                .is_some_and(|w| w != 0);
+        }
+
+        if health.wear_pct.is_none() {
+            health.wear_pct = v
+                .pointer("/ata_smart_attributes/table")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .find(|a| {
+                    a.get("id")
+                        .and_then(Value::as_u64)
+                        .is_some_and(|id| ATA_WEAR_ATTRIBUTES.contains(&id))
+                })
+                .and_then(|a| a.get("value").and_then(Value::as_u64))
+                .map(|remaining| 100 - remaining.min(100) as u8);
+        }
+
+        health.critical |=
+            v.pointer("/smart_status/passed").and_then(Value::as_bool) == Some(false);
+
+        Ok(health)
+    }
+}
+
+impl DeviceWriteCounter {
+    /// Счётчик устройства, на котором лежит `path` (файл или каталог).
+    pub fn for_path(path: &Path) -> std::io::Result<Self> {
+        let stat_path = block_device_sysfs(path)?.join("stat");
+        let start_sectors = read_sectors_written(&stat_path)?;
+
+        Ok(Self {
+            stat_path,
+            start_sectors,
+        })
+    }
+
+    pub fn bytes_written(&self) -> std::io::Result<u64> {
+        let sectors = read_sectors_written(&self.stat_path)?;
+        Ok(sectors.saturating_sub(self.start_sectors) * SYSFS_SECTOR_BYTES)
+    }
+}
+
+impl DiskHealthMonitor {
+    /// Монитор диска, на котором лежит `recording`. Недоступные источники
+    /// (не Linux, tmpfs, нет `smartctl`) отключаются с сообщением в лог.
+    pub fn new(
+        config: DiskHealthConfig,
+        recording: &Path,
+        metrics: Arc<RecorderMetrics>,
+    ) -> Self {
+        let counter = match DeviceWriteCounter::for_path(recording) {
+            Ok(c) => {
+                info!("Disk health: device counters {:?}", c.stat_path);
+                Some(c)
+            }
+            Err(e) => {
+                info!("Disk health: no device write counter for {recording:?} ({e})");
+                None
+            }
+        };
+
+        let smart_device = match (&config.smart_device, config.smart) {
+            (_, false) => None,
+            (Some(d), true) => Some(d.clone()),
+            (None, true) => match smart_device_for(recording) {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    warn!("Disk health: cannot find SMART device for {recording:?}: {e}");
+                    None
+                }
+            },
+        };
+
+        Self {
+            config,
+            metrics,
+            counter,
+            smart_device,
+            last_smart: None,
+            wear_warned: false,
+            critical_warned: false,
+        }
+    }
+
+    /// Опрашивает диск до закрытия `stop` (отправитель сброшен).
+    pub fn run(
+        &mut self,
+        stop: &Receiver<()>,
+    ) {
+        loop {
+            self.poll_counters();
+            self.poll_smart();
+
+            match stop.recv_timeout(self.config.interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    /// Итоговый замер после финализации: запись сбрасывается на диск
+    /// (fsync), иначе счётчик устройства не увидит данные из page cache.
+    pub fn finish(
+        &mut self,
+        recording: &Path,
+    ) {
+        if self.counter.is_none() {
+            return;
+        }
+
+        if let Err(e) = fs::File::open(recording).and_then(|f| f.sync_all()) {
+            warn!("Disk health: cannot sync {recording:?}: {e}");
+        }
+
+        self.poll_counters();
+    }
+
+    /// Обновляет записанные на устройство байты.
+    pub fn poll_counters(&mut self) {
+        let Some(counter) = &self.counter else {
+            return;
+        };
+
+        match counter.bytes_written() {
+            Ok(bytes) => self.metrics.set_device_bytes_written(bytes),
+            Err(e) => {
+                warn!(
+                    "Disk health: cannot read {:?}: {e}. Disabling",
+                    counter.stat_path
+                );
+                self.counter = None;
+            }
+        }
+    }
+
+    /// Опрашивает SMART, если подошёл интервал. Ошибка отключает опрос.
+    pub fn poll_smart(&mut self) {
+        let Some(device) = &self.smart_device else {
+            return;
+        };
+
+        if self
+            .last_smart
+            .is_some_and(|t| t.elapsed() < self.config.smart_interval)
+        {
+            return;
+        }
+        self.last_smart = Some(Instant::now());
+
+        match query_smart(device) {
+            Ok(health) => self.check_media(&health),
+            Err(e) => {
+                warn!("Disk health: SMART query for {device:?} failed: {e}. Disabling");
+                self.smart_device = None;
+            }
+        }
+    }
+
+    /// Публикует износ в метрики и один раз предупреждает о пересечении
+    /// порога и о критическом состоянии носителя.
+    pub fn check_media(
+        &mut self,
+        health: &MediaHealth,
+    ) {
+        let device = self
+            .smart_device
+            .as_deref()
+            .unwrap_or(Path::new("recording disk"));
+
+        if let Some(wear) = health.wear_pct {
+            self.metrics.set_media_wear_pct(wear);
+
+            if wear >= self.config.wear_warn_pct && !self.wear_warned {
+                warn!(
+                    "⚠ Media wear {wear}% on {device:?} reached the {}% threshold. Plan to replace the disk",
+                    self.config.wear_warn_pct
+                );
+                self.wear_warned = true;
+            }
+        }
+
+        if health.critical && !self.critical_warned {
+            warn!("⚠ {device:?} reports a critical SMART state. Recording may fail");
+            self.critical_warned = true;
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for DiskHealthConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            smart: false,
+            smart_interval: DEFAULT_SMART_INTERVAL,
+            smart_device: None,
+            wear_warn_pct: DEFAULT_WEAR_WARN_PCT,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Секторы, записанные на устройство: 7-е поле `/sys/block/*/stat`.
+fn parse_sectors_written(stat: &str) -> Option<u64> {
+    stat.split_whitespace().nth(6)?.parse().ok()
+}
+
+fn read_sectors_written(stat_path: &Path) -> std::io::Result<u64> {
+    let stat = fs::read_to_string(stat_path)?;
+
+    parse_sectors_written(&stat).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unexpected format of {stat_path:?}"),
+        )
+    })
+}
+
+/// Каталог устройства в sysfs (`/sys/devices/.../block/sda/sda1`) для
+/// файловой системы, на которой лежит `path`.
+#[cfg(target_os = "linux")]
+fn block_device_sysfs(path: &Path) -> std::io::Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = fs::metadata(path)?.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+
+    fs::canonicalize(format!("/sys/dev/block/{major}:{minor}"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn block_device_sysfs(_path: &Path) -> std::io::Result<PathBuf> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "device counters are only read on Linux",
+    ))
+}
+
+/// Узел диска для `smartctl`: для раздела — родительский диск
+/// (`sda1` → `/dev/sda`, `nvme0n1p2` → `/dev/nvme0n1`).
+fn smart_device_for(path: &Path) -> std::io::Result<PathBuf> {
+    let sys = block_device_sysfs(path)?;
+    let disk = if sys.join("partition").exists() {
+        sys.parent().unwrap_or(&sys)
+    } else {
+        &sys
+    };
+
+    disk.file_name()
+        .map(|name| Path::new("/dev").join(name))
+        .ok_or_else(|| std::io::Error::other(format!("no device name in {sys:?}")))
+}
+
+#[cfg(feature = "smart")]
+fn query_smart(device: &Path) -> Result<MediaHealth, String> {
+    let out = std::process::Command::new("smartctl")
+        .args(["--json=c", "-H", "-A"])
+        .arg(device)
+        .output()
+        .map_err(|e| format!("smartctl: {e}"))?;
+
+    // Код выхода smartctl — битовая маска; биты 0-1 — команда не выполнена,
+    // остальные описывают состояние диска и уже есть в JSON
+    match out.status.code() {
+        Some(code) if code & 0b11 == 0 => {
+            MediaHealth::from_smartctl_json(&String::from_utf8_lossy(&out.stdout))
+        }
+        code => Err(format!(
+            "smartctl exited with {code:?}: {}",
+            String::from_utf8_lossy(&out.stderr).trim()
+        )),
+    }
+}
+
+#[cfg(not(feature = "smart"))]
+fn query_smart(_device: &Path) -> Result<MediaHealth, String> {
+    Err("glos-recorder is built without the `smart` feature".into())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sectors_written() {
+        let stat = "  184635    56012  9876543   71234   402311   198765 31415926  1234567 \
+                    0   345678  1305801        0        0        0        0";
+
+        assert_eq!(parse_sectors_written(stat), Some(31_415_926));
+        assert_eq!(parse_sectors_written("1 2 3"), None);
+    }
+
+    #[test]
+    fn test_smartctl_json() {
+        let nvme = r#"{
+            "smart_status": {"passed": true},
+            "nvme_smart_health_information_log": {
+                "critical_warning": 0, "available_spare": 100, "percentage_used": 83
+            }
+        }"#;
+        assert_eq!(
+            MediaHealth::from_smartctl_json(nvme).unwrap(),
+            MediaHealth {
+                wear_pct: Some(83),
+                critical: false,
+            }
+        );
+
+        let ata = r#"{
+            "smart_status": {"passed": false},
+            "ata_smart_attributes": {"table": [
+                {"id": 9, "name": "Power_On_Hours", "value": 97},
+                {"id": 177, "name": "Wear_Leveling_Count", "value": 94}
+            ]}
+        }"#;
+        assert_eq!(
+            MediaHealth::from_smartctl_json(ata).unwrap(),
+            MediaHealth {
+                wear_pct: Some(6),
+                critical: true,
+            }
+        );
+
+        // HDD: атрибута износа нет
+        let hdd = r#"{"smart_status": {"passed": true}, "ata_smart_attributes": {"table": []}}"#;
+        assert_eq!(
+            MediaHealth::from_smartctl_json(hdd).unwrap(),
+            MediaHealth::default()
+        );
+        assert!(MediaHealth::from_smartctl_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_wear_threshold_sets_metrics_and_warns_once() {
+        let metrics = RecorderMetrics::new();
+        let mut monitor = DiskHealthMonitor {
+            config: DiskHealthConfig::default(),
+            metrics: metrics.clone(),
+            counter: None,
+            smart_device: None,
+            last_smart: None,
+            wear_warned: false,
+            critical_warned: false,
+        };
+
+        let health = |wear| MediaHealth {
+            wear_pct: Some(wear),
+            critical: false,
+        };
+
+        monitor.check_media(&health(40));
+        assert_eq!(metrics.media_wear_pct(), Some(40));
+        assert!(!monitor.wear_warned);
+
+        monitor.check_media(&health(DEFAULT_WEAR_WARN_PCT));
+        assert!(monitor.wear_warned);
+        assert_eq!(metrics.media_wear_pct(), Some(DEFAULT_WEAR_WARN_PCT));
+    }
+}
@@ -1,3 +1,5 @@
+use std::io::ErrorKind;
+
 use glos_types::GlosError;
 use thiserror::Error;
 
@@ -23,6 +25,25 @@ pub enum RecorderError {
     #[error("Pipeline error: {0}")]
     Pipeline(String),
 
+    #[error("Time sync error: {0}")]
+    TimeSync(String),
+
     #[error("Duration limit reached")]
     DurationElapsed,
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RecorderError {
+    /// Ошибка записи на заполненный диск.
+    pub fn is_disk_full(&self) -> bool {
+        match self {
+            RecorderError::Io(e) | RecorderError::Glos(GlosError::Io(e)) => {
+                e.kind() == ErrorKind::StorageFull
+            }
+            _ => false,
+        }
+    }
+}
@@ -1,11 +1,48 @@
+pub mod block_buffers;
+pub mod block_size;
+pub mod branch;
+pub mod clock_drift;
+pub mod compress;
 pub mod config;
 pub mod device;
+pub mod disk_health;
 pub mod error;
 pub mod metrics;
+pub mod mirror;
+pub mod naming;
+pub mod network;
+pub mod notify;
 pub mod pipeline;
+pub mod prebuffer;
+pub mod ring_tune;
+pub mod rotation;
+pub mod session_log;
+pub mod stats_export;
+pub mod sync_marker;
+pub mod telemetry;
+pub mod time_sync;
+pub mod write_queue;
 
+pub use block_buffers::*;
+pub use block_size::*;
+pub use branch::*;
+pub use clock_drift::*;
+pub use compress::*;
 pub use config::*;
 pub use device::*;
+pub use disk_health::*;
 pub use error::*;
 pub use metrics::*;
+pub use mirror::*;
+pub use network::*;
+pub use notify::*;
 pub use pipeline::*;
+pub use prebuffer::*;
+pub use ring_tune::*;
+pub use rotation::*;
+pub use session_log::*;
+pub use stats_export::*;
+pub use sync_marker::*;
+pub use telemetry::*;
+pub use time_sync::*;
+pub use write_queue::*;
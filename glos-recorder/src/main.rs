@@ -4,14 +4,26 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use glos_core::{
+    dsp::IqCorrection, metadata, CompressionOptions, ContentChunker, DEFAULT_CHECKPOINT_INTERVAL,
+    DEFAULT_ZSTD_LEVEL,
+};
 use glos_hal::DeviceKind;
-use glos_recorder::{create_device, parse_freq_hz, RecorderConfig, RecordingPipeline};
-use glos_types::{Compression, IqFormat};
-use log::{error, info, warn};
+use glos_recorder::{
+    create_device, describe_device, max_block_samples, naming, parse_freq_hz, rotation,
+    BlockTarget, BranchConfig, ClockDriftConfig, DiskHealthConfig, Notifier, NotifyConfig,
+    NotifyEvent, RecorderConfig, RecordingPipeline, RingAutoTune, RingHistory, RotationConfig,
+    SessionLog, SessionLogger, SmtpConfig, StatsExporter, StatsFormat, TelemetryConfig,
+    TimeSyncConfig, WriteQueueConfig, DEFAULT_RING_CAPACITY, DEFAULT_TEMP_WARN_C,
+    DEFAULT_WEAR_WARN_PCT, MAX_AUTO_COMPRESS_THREADS, NOTIFY_TIMEOUT, RING_AUTOTUNE_WINDOW,
+};
+use glos_types::{Compression, IqFormat, RuntimeControl, SystemClock, VERBOSE_LOG_LEVEL};
+use log::{error, info, warn, LevelFilter};
+use signal_hook::{consts::signal::SIGUSR2, iterator::Signals};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -19,8 +31,11 @@ use log::{error, info, warn};
     version = env!("CARGO_PKG_VERSION"),
     about = "Record IQ samples from SDR device to .glos file",
     long_about = None,
+    args_conflicts_with_subcommands = true,
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Cmd>,
     /// SDR устройство: sim, hackrf, pluto
     #[arg(short, long, default_value = "sim")]
     device: String,
@@ -33,60 +48,259 @@ struct Cli {
     /// Усиление приёмника, дБ
     #[arg(short, long, default_value = "40.0")]
     gain: f32,
-    /// Путь к выходному файлу
+    /// Путь к выходному файлу. Допускает шаблон, например
+    /// `capture_{date}_{time}_{freq}.glos` (поля: date, time, datetime, unix,
+    /// freq, rate, gain, sdr, format, seq); занятые имена получают суффикс `_N`
     #[arg(short, long, default_value = "recording.glos")]
     output: PathBuf,
+    /// Зеркальная копия записи (желательно на другом диске). Сбой зеркала
+    /// не останавливает запись; в конце файлы сверяются побайтно
+    #[arg(long)]
+    mirror: Option<PathBuf>,
+    /// Дополнительный выход того же захвата со своей обработкой (можно
+    /// повторять): `path[,format=float32][,compress=zstd][,rate=500kHz]
+    /// [,shift=-250kHz][,quality=high]`. `shift` переносит сигнал на
+    /// `center + shift` в центр ветви перед передискретизацией. Путь
+    /// допускает тот же шаблон, что --output
+    #[arg(long)]
+    branch: Vec<String>,
+    /// Писать запись членом индексированного архива `.glosa` вместо
+    /// отдельного файла (архив создаётся или дописывается). Имя члена — имя
+    /// файла из --output, члены читаются по имени через `GlosArchive`
+    #[arg(long, conflicts_with = "mirror")]
+    archive: Option<PathBuf>,
+    /// Начинать новый файл, когда текущий достигнет размера (`2GB`,
+    /// `500MiB`). Номер файла подставляется в `{seq}` из --output, без него
+    /// добавляется суффиксом: `recording_000.glos`, `recording_001.glos`, …
+    /// С --archive файлы пишутся членами архива с теми же именами
+    #[arg(long, conflicts_with = "mirror")]
+    rotate_size: Option<String>,
+    /// Начинать новый файл каждые N секунд записи (по меткам блоков, можно
+    /// вместе с --rotate-size)
+    #[arg(long, conflicts_with = "mirror")]
+    rotate_secs: Option<u64>,
     /// Ограничение записи (секунды). По умолчанию: до Ctrl+C
     #[arg(short, long)]
     duration: Option<u64>,
-    /// Формат IQ выборок: int8, int16, float32
-    #[arg(long, default_value = "int16")]
-    format: String,
-    /// Сжатие: none, lz4
-    #[arg(long, default_value = "none")]
-    compress: String,
-    /// Выборок в блоке (влияет на latency/overhead)
-    #[arg(long, default_value = "50000")]
-    block_samples: u32,
-    /// Ёмкость кольцевого буфера (кол-во chunk-слотов, 1 chunk ≈ 16 KB)
-    #[arg(long, default_value = "256")]
-    ring_capacity: usize,
+    /// Предзапись: держать в памяти последние N секунд и начать файл только
+    /// по триггеру (Enter в терминале) — в него попадут эти N секунд и всё,
+    /// что дальше
+    #[arg(long)]
+    prebuffer_secs: Option<f64>,
+    /// Записать ровно N выборок и остановиться (последний chunk
+    /// обрезается). Альтернатива --duration
+    #[arg(long, conflicts_with = "duration")]
+    samples: Option<u64>,
+    /// Проверять, сколько бит выборки заняты на самом деле, и в конце
+    /// посоветовать меньший --format (например, 8-битный АЦП в float32)
+    #[arg(long)]
+    format_advice: bool,
+    /// Описание радиочасти (антенна, кабель, МШУ, фильтры) в JSON. Копия
+    /// сохраняется рядом с записью в `<output>.meta.json`
+    #[arg(long)]
+    rf_chain: Option<PathBuf>,
+    /// Сохранить миниатюру спектрограммы в `<output>.meta.json` для
+    /// предпросмотра в каталоге и UI
+    #[arg(long)]
+    thumbnail: bool,
+    /// Формат IQ выборок
+    #[arg(long, value_enum, ignore_case = true, default_value_t = IqFormat::Int16)]
+    format: IqFormat,
+    /// Сжатие
+    #[arg(long, value_enum, ignore_case = true, default_value_t = Compression::None)]
+    compress: Compression,
+    /// Уровень сжатия для --compress zstd (1–9)
+    #[arg(long, default_value_t = DEFAULT_ZSTD_LEVEL)]
+    zstd_level: i32,
+    /// Блоки меньше этого размера (байт) писать без сжатия
+    #[arg(long, default_value_t = 0)]
+    compress_min_bytes: usize,
+    /// Не тратить CPU на несжимаемые данные (шум): после блока, который почти
+    /// не сжался, следующие блоки пишутся без сжатия
+    #[arg(long)]
+    skip_incompressible: bool,
+    /// Потоков сжатия перед записью (0 — сжимает поток записи). По
+    /// умолчанию — по числу ядер, но не больше 4
+    #[arg(long)]
+    compress_threads: Option<usize>,
+    /// Поменять местами I и Q (фронтенд с перепутанными каналами)
+    #[arg(long)]
+    swap_iq: bool,
+    /// Инвертировать спектр (Q → -Q), применяется после --swap-iq
+    #[arg(long)]
+    invert_spectrum: bool,
+    /// Устройство отдаёт выборки в little-endian: писать их как есть (флаг
+    /// заголовка), не тратя CPU на перестановку байт
+    #[arg(long)]
+    samples_le: bool,
+    /// Выборок в блоке. Явное значение отменяет авто-размер --block-target
+    #[arg(long)]
+    block_samples: Option<u32>,
+    /// Авто-размер блока по частоте и формату: длительность (`20ms`,
+    /// `0.5s`) или объём IQ данных (`256KiB`, `1MB`)
+    ///
+    /// Короткие блоки: меньше задержка до диска и меньше теряется при сбое
+    /// или повреждении блока, точнее метки времени и поиск по файлу, но
+    /// больше накладных расходов (20 байт заголовка и CRC на блок) и
+    /// системных вызовов. Длинные блоки: меньше накладных расходов и лучше
+    /// сжатие, но больше памяти на буфер и больше данных теряется вместе с
+    /// одним повреждённым блоком. Размер ограничен 1 МиБ и 65535 выборками
+    /// на блок — больше не передать по UDP при воспроизведении.
+    #[arg(long, default_value = "20ms")]
+    block_target: String,
+    /// Границы блоков по содержимому: `min:max` выборок (например
+    /// `16384:131072`). Переопределяет --block-samples
+    #[arg(long)]
+    chunking: Option<String>,
+    /// Ёмкость кольцевого буфера (кол-во chunk-слотов, 1 chunk ≈ 16 KB).
+    /// По умолчанию — рекомендация из --ring-history для этого устройства
+    /// и частоты, без истории — 256
+    #[arg(long)]
+    ring_capacity: Option<usize>,
+    /// Авто-подбор ёмкости кольцевого буфера: первая минута записи идёт с
+    /// запасом (не меньше 1024 слотов), по пику занятости в лог выводится
+    /// рекомендуемая --ring-capacity
+    #[arg(long)]
+    ring_autotune: bool,
+    /// JSON-файл истории авто-подбора: --ring-autotune дописывает в него
+    /// рекомендацию, следующие сессии берут из него ёмкость
+    #[arg(long)]
+    ring_history: Option<PathBuf>,
+    /// Лимит памяти очереди записи (МБ): столько блоков копится в RAM,
+    /// если диск или сжатие ненадолго не успевают
+    #[arg(long, default_value = "64")]
+    queue_mb: usize,
+    /// Каталог временного файла перелива очереди записи. Без него при
+    /// заполнении очереди захват ждёт диск (и теряет chunk'и на переполнении
+    /// кольцевого буфера)
+    #[arg(long)]
+    spill_dir: Option<PathBuf>,
+    /// Двойная проверка блоков: XXH64 данных, посчитанный при приёме
+    /// chunk'ов, сверяется с несжатыми данными перед сериализацией. Ловит
+    /// порчу RAM, которую CRC блока уже не заметит; номера испорченных
+    /// блоков пишутся в лог
+    #[arg(long)]
+    verify_blocks: bool,
+    /// Переписывать заголовок каждые N секунд записи, чтобы после сбоя
+    /// питания в нём были близкие к правде число выборок и время
+    /// окончания (0 — только в конце)
+    #[arg(long, default_value_t = DEFAULT_CHECKPOINT_INTERVAL.as_secs())]
+    checkpoint_secs: u64,
+    /// Привязать метки блоков к NTP: перед записью измерить поправку
+    /// системных часов по серверу (`host` или `host:port`) и сохранить её
+    /// в заголовке
+    #[arg(long, conflicts_with = "pps_device")]
+    ntp_server: Option<String>,
+    /// Привязать метки блоков к секундной метке GNSS-приёмника на линии
+    /// DCD последовательного порта (Linux, напр. `/dev/ttyUSB0`). Часы
+    /// хоста уже должны идти по UTC точнее полсекунды
+    #[arg(long)]
+    pps_device: Option<PathBuf>,
+    /// Опрашивать датчики устройства (температура, уровень сигнала,
+    /// USB) раз в --stats-interval: в итоги, --stats-export и
+    /// предупреждения по порогам
+    #[arg(long)]
+    telemetry: bool,
+    /// Предупредить, когда температура устройства достигнет N °C
+    #[arg(long, default_value_t = DEFAULT_TEMP_WARN_C)]
+    temp_warn_c: f32,
+    /// Предупредить, когда уровень на входе АЦП достигнет N дБFS
+    #[arg(long, allow_negative_numbers = true)]
+    rssi_warn_dbfs: Option<f32>,
+    /// Предупредить, когда ошибок USB за сессию наберётся N
+    #[arg(long, default_value_t = 1)]
+    usb_errors_warn: u64,
     /// Интервал вывода статистики (секунды)
     #[arg(long, default_value = "5")]
     stats_interval: u64,
+    /// Писать метрики каждые --stats-interval секунд в
+    /// `<output>.stats.csv` или `.stats.jsonl`: csv, jsonl
+    #[arg(long)]
+    stats_export: Option<String>,
+    /// Следить за диском записи: оценка write amplification (байты на
+    /// устройстве / байты записи) в итогах и --stats-export
+    #[arg(long)]
+    disk_health: bool,
+    /// Опрашивать износ диска по SMART через `smartctl` (сборка с
+    /// `--features smart`, обычно нужен root). Включает --disk-health
+    #[arg(long)]
+    smart: bool,
+    /// Устройство для SMART (по умолчанию — диск с --output)
+    #[arg(long, requires = "smart")]
+    smart_device: Option<PathBuf>,
+    /// Предупредить, когда износ по SMART достигнет N% ресурса
+    #[arg(long, default_value_t = DEFAULT_WEAR_WARN_PCT)]
+    wear_warn_pct: u8,
+    /// Оценивать дрейф часов выборок SDR относительно часов хоста (ppm):
+    /// раз в минуту в лог, итог — в `<output>.meta.json`
+    #[arg(long)]
+    clock_drift: bool,
+    /// Считать метки блоков по измеренной частоте устройства, а не по
+    /// номинальной (после первых 10 с замера). Включает --clock-drift
+    #[arg(long)]
+    correct_drift: bool,
+    /// Не сохранять предупреждения рекордера в файл метаданных записи. По
+    /// умолчанию события WARN и ERROR (потери, перестройки, смены усиления)
+    /// с метками времени попадают в `session_log` (с --quiet — только ERROR)
+    #[arg(long)]
+    no_session_log: bool,
+    /// Webhook для уведомлений (`http://host[:port]/path`): POST с JSON —
+    /// событие, файл записи и итоговые метрики
+    #[arg(long)]
+    notify_webhook: Option<String>,
+    /// SMTP-релей для уведомлений по почте (`host:port`, без TLS и
+    /// авторизации — локальный MTA)
+    #[arg(long, requires = "notify_mail_to")]
+    notify_smtp: Option<String>,
+    /// Получатель письма (можно повторять)
+    #[arg(long, requires = "notify_smtp")]
+    notify_mail_to: Vec<String>,
+    /// Отправитель письма
+    #[arg(long, default_value = "glos-recorder@localhost")]
+    notify_mail_from: String,
+    /// О каких событиях сообщать, через запятую: completed, failed,
+    /// device-failure, disk-full, rotated. По умолчанию — обо всех
+    #[arg(long, value_delimiter = ',')]
+    notify_on: Vec<String>,
     /// Тихий режим (только ошибки)
     #[arg(short, long)]
     quiet: bool,
 }
 
-fn parse_iq_format(s: &str) -> Result<IqFormat, String> {
-    match s.to_lowercase().as_str() {
-        "int8" | "i8" => Ok(IqFormat::Int8),
-        "int16" | "i16" => Ok(IqFormat::Int16),
-        "float32" | "f32" => Ok(IqFormat::Float32),
-        _ => Err(format!(
-            "Unknown IQ format '{s}'. Use: int8, int16, float32"
-        )),
-    }
-}
-
-fn parse_compression(s: &str) -> Result<Compression, String> {
-    match s.to_lowercase().as_str() {
-        "none" | "no" | "off" => Ok(Compression::None),
-        "lz4" => Ok(Compression::Lz4),
-        _ => Err(format!("Unknown compression '{s}'. Use: none, lz4")),
-    }
+#[derive(Subcommand, Debug)]
+enum Cmd {
+    /// Опросить собранные бэкенды и показать подключённые устройства:
+    /// значение для --device, серийный номер, частоты и усиление
+    ListDevices,
 }
 
 fn main() {
     let cli = Cli::parse();
-    let level = if cli.quiet { "error" } else { "info" };
+    let level = if cli.quiet {
+        LevelFilter::Error
+    } else {
+        LevelFilter::Info
+    };
 
-    env_logger::Builder::new()
-        .filter_level(level.parse().unwrap())
+    // Логгер пропускает и debug, реальный уровень задаёт max_level —
+    // его можно поднять на лету (SIGUSR2)
+    let console = env_logger::Builder::new()
+        .filter_level(VERBOSE_LOG_LEVEL)
         .format_target(false)
         .format_timestamp_secs()
-        .init();
+        .build();
+    let session_log = (!cli.no_session_log).then(|| SessionLog::new(SystemClock::shared()));
+    match &session_log {
+        Some(log) => log::set_boxed_logger(Box::new(SessionLogger::new(console, log.clone()))),
+        None => log::set_boxed_logger(Box::new(console)),
+    }
+    .expect("logger is installed once");
+    log::set_max_level(level);
+
+    if let Some(Cmd::ListDevices) = cli.command {
+        list_devices();
+        return;
+    }
 
     let device_kind: DeviceKind = match cli.device.parse() {
         Ok(d) => d,
@@ -116,36 +330,310 @@ fn main() {
         }
     };
 
-    let iq_format = match parse_iq_format(&cli.format) {
-        Ok(f) => f,
-        Err(e) => {
-            error!("--format: {e}");
+    let iq_format = cli.format;
+    let compression = cli.compress;
+
+    let compression_options = CompressionOptions {
+        level: cli.zstd_level,
+        min_block_bytes: cli.compress_min_bytes,
+        skip_incompressible: cli.skip_incompressible,
+    };
+    if let Err(e) = compression_options.validate() {
+        error!("--zstd-level: {e}");
+        std::process::exit(1);
+    }
+
+    let compress_threads = cli.compress_threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_AUTO_COMPRESS_THREADS)
+    });
+
+    let chunking = match cli.chunking.as_deref().map(str::parse::<ContentChunker>) {
+        None => None,
+        Some(Ok(c)) if c.max_samples() > max_block_samples(iq_format) => {
+            error!(
+                "--chunking: max {} samples exceeds block size limit",
+                c.max_samples()
+            );
+            std::process::exit(1);
+        }
+        Some(Ok(c)) => Some(c),
+        Some(Err(e)) => {
+            error!("--chunking: {e}");
             std::process::exit(1);
         }
     };
 
-    let compression = match parse_compression(&cli.compress) {
-        Ok(c) => c,
+    let block_samples = match (cli.block_samples, cli.block_target.parse::<BlockTarget>()) {
+        (Some(n), _) if n == 0 || n > max_block_samples(iq_format) => {
+            error!(
+                "--block-samples: must be 1..={} for {iq_format}",
+                max_block_samples(iq_format)
+            );
+            std::process::exit(1);
+        }
+        (Some(n), _) => n,
+        (None, Ok(t)) => t.block_samples(sample_rate_hz, iq_format),
+        (None, Err(e)) => {
+            error!("--block-target: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let stats_export = match cli.stats_export.as_deref().map(str::parse::<StatsFormat>) {
+        None => None,
+        Some(Ok(f)) => Some(f),
+        Some(Err(e)) => {
+            error!("--stats-export: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let prebuffer = match cli.prebuffer_secs {
+        None => None,
+        Some(s) if s.is_finite() && s > 0.0 => Some(Duration::from_secs_f64(s)),
+        Some(s) => {
+            error!("--prebuffer-secs: must be > 0, got {s}");
+            std::process::exit(1);
+        }
+    };
+
+    let rf_chain = match cli.rf_chain.as_deref().map(metadata::read_rf_chain) {
+        None => None,
+        Some(Ok(c)) => Some(c),
+        Some(Err(e)) => {
+            error!("--rf-chain: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if cli.smart && !cfg!(feature = "smart") {
+        error!("--smart: glos-recorder is built without the `smart` feature");
+        std::process::exit(1);
+    }
+
+    if cli.wear_warn_pct == 0 || cli.wear_warn_pct > 100 {
+        error!("--wear-warn-pct: must be 1..=100");
+        std::process::exit(1);
+    }
+
+    let disk_health = (cli.disk_health || cli.smart).then(|| DiskHealthConfig {
+        interval: Duration::from_secs(cli.stats_interval.max(1)),
+        smart: cli.smart,
+        smart_device: cli.smart_device.clone(),
+        wear_warn_pct: cli.wear_warn_pct,
+        ..Default::default()
+    });
+
+    let notify_webhook = match cli.notify_webhook.as_deref().map(str::parse).transpose() {
+        Ok(url) => url,
+        Err(e) => {
+            error!("--notify-webhook: {e}");
+            std::process::exit(1);
+        }
+    };
+    let notify_events = match cli
+        .notify_on
+        .iter()
+        .map(|s| s.parse())
+        .collect::<Result<Vec<NotifyEvent>, _>>()
+    {
+        Ok(events) => events,
         Err(e) => {
-            error!("--compress: {e}");
+            error!("--notify-on: {e}");
+            std::process::exit(1);
+        }
+    };
+    let notify_config = NotifyConfig {
+        webhook: notify_webhook,
+        smtp: cli.notify_smtp.clone().map(|server| SmtpConfig {
+            server,
+            from: cli.notify_mail_from.clone(),
+            to: cli.notify_mail_to.clone(),
+        }),
+        events: notify_events,
+        timeout: NOTIFY_TIMEOUT,
+    };
+    let notify = (!notify_config.is_empty()).then(|| Notifier::new(notify_config));
+
+    let rotate_size = match cli.rotate_size.as_deref().map(rotation::parse_byte_size) {
+        None => None,
+        Some(Ok(n)) => Some(n),
+        Some(Err(e)) => {
+            error!("--rotate-size: {e}");
             std::process::exit(1);
         }
     };
+    let rotation = (rotate_size.is_some() || cli.rotate_secs.is_some()).then(|| RotationConfig {
+        max_bytes: rotate_size,
+        max_duration: cli.rotate_secs.map(Duration::from_secs),
+        path_template: rotation::segment_template(&cli.output),
+    });
+    if let Some(Err(e)) = rotation.as_ref().map(RotationConfig::validate) {
+        error!("--rotate-size/--rotate-secs: {e}");
+        std::process::exit(1);
+    }
+
+    if cli.queue_mb == 0 {
+        error!("--queue-mb: must be > 0");
+        std::process::exit(1);
+    }
+
+    // Ёмкость кольца: явная, иначе из истории прошлых сессий
+    let ring_key = RingHistory::key(&device_kind, sample_rate_hz);
+    let ring_from_history = match &cli.ring_history {
+        Some(path) => match RingHistory::load(path) {
+            Ok(h) => h.recommended(&ring_key),
+            Err(e) => {
+                warn!("--ring-history: {e}. Using default ring capacity");
+                None
+            }
+        },
+        None => None,
+    };
+    let ring_capacity = cli
+        .ring_capacity
+        .or(ring_from_history)
+        .unwrap_or(DEFAULT_RING_CAPACITY);
 
-    let config = RecorderConfig {
+    if ring_capacity == 0 {
+        error!("--ring-capacity: must be > 0");
+        std::process::exit(1);
+    }
+
+    let ring_autotune = cli.ring_autotune.then(|| RingAutoTune {
+        window: RING_AUTOTUNE_WINDOW,
+        history: cli.ring_history.clone(),
+    });
+
+    let mut config = RecorderConfig {
         device: device_kind,
         center_freq_hz,
         sample_rate_hz,
         gain_db: cli.gain,
         iq_format,
         compression,
+        compression_options,
+        compress_threads,
+        iq_correction: IqCorrection {
+            swap_iq: cli.swap_iq,
+            invert_spectrum: cli.invert_spectrum,
+        },
+        samples_little_endian: cli.samples_le,
         output_path: cli.output.clone(),
         duration_secs: cli.duration,
-        block_samples: cli.block_samples,
-        ring_capacity: cli.ring_capacity,
+        sample_limit: cli.samples,
+        block_samples,
+        chunking,
+        ring_capacity,
+        ring_autotune,
         stats_interval_secs: cli.stats_interval,
+        stats_export,
+        start_time_ns: None,
+        mirror_path: cli.mirror.clone(),
+        archive_path: cli.archive.clone(),
+        rotation,
+        prebuffer,
+        format_advice: cli.format_advice,
+        write_queue: WriteQueueConfig {
+            memory_cap_bytes: cli.queue_mb * 1_000_000,
+            spill_dir: cli.spill_dir.clone(),
+            verify_digests: cli.verify_blocks,
+        },
+        rf_chain: rf_chain.clone(),
+        thumbnail: cli.thumbnail,
+        disk_health,
+        telemetry: cli.telemetry.then(|| TelemetryConfig {
+            interval: Duration::from_secs(cli.stats_interval.max(1)),
+            temp_warn_c: Some(cli.temp_warn_c),
+            rssi_warn_dbfs: cli.rssi_warn_dbfs,
+            usb_errors_warn: Some(cli.usb_errors_warn),
+        }),
+        session_log,
+        notify,
+        clock_drift: (cli.clock_drift || cli.correct_drift).then(|| ClockDriftConfig {
+            correct_timestamps: cli.correct_drift,
+            ..Default::default()
+        }),
+        checkpoint_interval: (cli.checkpoint_secs > 0)
+            .then(|| Duration::from_secs(cli.checkpoint_secs)),
+        time_sync: cli
+            .ntp_server
+            .clone()
+            .map(TimeSyncConfig::ntp)
+            .or_else(|| cli.pps_device.clone().map(TimeSyncConfig::pps)),
+        ..Default::default()
+    };
+
+    // Шаблоны имён (`capture_{date}_{time}.glos`) раскрываем по заголовку
+    // сессии; время — на момент запуска
+    let header = config.glos_header(config.clock.unix_time_ns());
+
+    // С ротацией первый файл — сегмент 000
+    let output_template = match &config.rotation {
+        Some(r) => r.path_template.clone(),
+        None => cli.output.clone(),
+    };
+    config.output_path = match naming::resolve_output_path(&output_template, &header, 0) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("--output: {e}");
+            std::process::exit(1);
+        }
     };
 
+    if let Some(m) = &cli.mirror {
+        config.mirror_path = match naming::resolve_output_path(m, &header, 0) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                error!("--mirror: {e}");
+                std::process::exit(1);
+            }
+        };
+    }
+
+    if config.mirror_path.as_ref() == Some(&config.output_path) {
+        error!("--mirror: must differ from --output");
+        std::process::exit(1);
+    }
+
+    for spec in &cli.branch {
+        let mut branch: BranchConfig = match spec.parse() {
+            Ok(b) => b,
+            Err(e) => {
+                error!("--branch: {e}");
+                std::process::exit(1);
+            }
+        };
+        branch.output_path = match naming::resolve_output_path(&branch.output_path, &header, 0) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("--branch: {e}");
+                std::process::exit(1);
+            }
+        };
+
+        let taken = std::iter::once(&config.output_path)
+            .chain(&config.mirror_path)
+            .chain(config.branches.iter().map(|b| &b.output_path));
+        if taken.into_iter().any(|p| *p == branch.output_path) {
+            error!(
+                "--branch: {:?} is already used by another output",
+                branch.output_path
+            );
+            std::process::exit(1);
+        }
+
+        config.branches.push(branch);
+    }
+
+    let output_path = config.output_path.clone();
+    let mirror_path = config.mirror_path.clone();
+    let branches = config.branches.clone();
+    let rotation = config.rotation.clone();
+    let ring_start = config.effective_ring_capacity();
+
     let device = match create_device(&config) {
         Ok(d) => d,
         Err(e) => {
@@ -157,6 +645,32 @@ fn main() {
     let (pipeline, metrics) = RecordingPipeline::new(config);
     let stop_flag: Arc<AtomicBool> = pipeline.stop_flag();
 
+    // Enter в терминале: триггер предзаписи, после него — метка
+    // синхронизации. Число в строке — время метки по внешним часам (нс)
+    let trigger = prebuffer.is_some().then(|| pipeline.trigger_flag());
+    let sync_markers = pipeline.sync_markers();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        while std::io::stdin().read_line(&mut line).is_ok_and(|n| n > 0) {
+            let triggered = trigger
+                .as_ref()
+                .is_some_and(|t| !t.swap(true, Ordering::SeqCst));
+            if triggered {
+                info!("Trigger pressed");
+            } else {
+                let text = line.trim();
+                let external_ns = text.parse::<u64>().ok();
+                if !text.is_empty() && external_ns.is_none() {
+                    warn!("Sync marker: {text:?} is not a time in ns, using host time");
+                }
+                sync_markers.mark(external_ns);
+            }
+            line.clear();
+        }
+    });
+
+    spawn_sigusr2_handler(pipeline.runtime_control());
+
     let stop_ctrlc = stop_flag.clone();
 
     if let Err(e) = ctrlc::set_handler(move || {
@@ -178,11 +692,141 @@ fn main() {
     info!("  Device        : {}", cli.device);
     info!("  Center freq   : {:.3} MHz", center_freq_hz as f64 / 1e6);
     info!("  Sample rate   : {:.3} Msps", sample_rate_hz as f64 / 1e6);
-    info!("  IQ format     : {:?} ({sample_size} B/sample)", iq_format);
-    info!("  Compression   : {:?}", compression);
+    info!("  IQ format     : {iq_format} ({sample_size} B/sample)");
+    match compression {
+        Compression::Zstd => info!(
+            "  Compression   : zstd (level {}, {compress_threads} threads)",
+            cli.zstd_level
+        ),
+        Compression::Lz4 => info!("  Compression   : lz4 ({compress_threads} threads)"),
+        _ => info!("  Compression   : {compression}"),
+    }
     info!("  Data rate     : {:.1} MB/s", data_rate_mbs);
-    info!("  Output        : {:?}", cli.output);
+    match &cli.archive {
+        Some(a) => info!("  Output        : {:?} in archive {:?}", output_path, a),
+        None => info!("  Output        : {:?}", output_path),
+    }
+    if cli.chunking.is_none() {
+        let source = match cli.block_samples {
+            Some(_) => "explicit".to_string(),
+            None => format!("auto {}", cli.block_target),
+        };
+        info!(
+            "  Block size    : {block_samples} samples ({:.1} ms, {:.0} KiB, {source})",
+            block_samples as f64 / sample_rate_hz as f64 * 1e3,
+            (block_samples as usize * sample_size) as f64 / 1024.0
+        );
+    }
+    if let Some(n) = cli.samples {
+        info!("  Sample limit  : {n}");
+    }
+    if let Some(p) = prebuffer {
+        info!(
+            "  Pre-buffer    : {:.1}s ({:.1} MB RAM), press Enter to save",
+            p.as_secs_f64(),
+            p.as_secs_f64() * data_rate_mbs
+        );
+    }
+    info!("  Sync markers  : press Enter (optionally after external time, ns)");
+    if let Some(m) = &mirror_path {
+        info!("  Mirror        : {:?}", m);
+    }
+    for b in &branches {
+        info!(
+            "  Branch        : {:?} ({}, {}, {:.3} Msps, shift {:+.3} MHz)",
+            b.output_path,
+            b.iq_format,
+            b.compression,
+            b.sample_rate_hz.unwrap_or(sample_rate_hz) as f64 / 1e6,
+            b.freq_shift_hz / 1e6
+        );
+    }
+    if let Some(r) = &rotation {
+        let limits: Vec<String> = r
+            .max_bytes
+            .map(|b| format!("{:.1} MB", b as f64 / 1e6))
+            .into_iter()
+            .chain(r.max_duration.map(|d| format!("{}s", d.as_secs())))
+            .collect();
+        info!(
+            "  Rotation      : every {}, files {:?}",
+            limits.join(" or "),
+            r.path_template
+        );
+    }
+    match cli.checkpoint_secs {
+        0 => info!("  Checkpoints   : off (header written on finalize)"),
+        n => info!("  Checkpoints   : header every {n}s"),
+    }
+    if let Some(c) = &rf_chain {
+        info!("  RF chain      : {c}");
+    }
+    let ring_source = match (cli.ring_capacity, ring_from_history) {
+        (Some(_), _) => "explicit".to_string(),
+        (None, Some(_)) => format!("history {ring_key}"),
+        (None, None) => "default".to_string(),
+    };
+    info!("  Ring buffer   : {ring_capacity} chunks ({ring_source})");
+    if cli.ring_autotune {
+        info!(
+            "  Ring autotune : {:.0}s window, start at {} chunks",
+            RING_AUTOTUNE_WINDOW.as_secs_f64(),
+            ring_start
+        );
+    }
+    match &cli.spill_dir {
+        Some(d) => info!("  Write queue   : {} MB RAM, spill to {d:?}", cli.queue_mb),
+        None => info!("  Write queue   : {} MB RAM", cli.queue_mb),
+    }
+    if cli.verify_blocks {
+        info!("  Block verify  : XXH64 at enqueue and before write");
+    }
+    if cli.smart {
+        info!(
+            "  Disk health   : write amplification, SMART wear (warn at {}%)",
+            cli.wear_warn_pct
+        );
+    } else if cli.disk_health {
+        info!("  Disk health   : write amplification");
+    }
+    if cli.telemetry {
+        info!(
+            "  Telemetry     : every {}s, warn at {:.1} °C",
+            cli.stats_interval.max(1),
+            cli.temp_warn_c
+        );
+    }
+    if let Some(url) = &cli.notify_webhook {
+        info!("  Notify        : webhook {url}");
+    }
+    if let Some(server) = &cli.notify_smtp {
+        info!(
+            "  Notify        : mail via {server} to {}",
+            cli.notify_mail_to.join(", ")
+        );
+    }
+    if let Some(c) = &cli.chunking {
+        info!("  Chunking      : content-defined {c} samples");
+    }
+    if cli.correct_drift {
+        info!("  Clock drift   : estimate, correct block timestamps");
+    } else if cli.clock_drift {
+        info!("  Clock drift   : estimate");
+    }
+    if cli.swap_iq || cli.invert_spectrum {
+        info!(
+            "  IQ correction : swap={} invert={}",
+            cli.swap_iq, cli.invert_spectrum
+        );
+    }
+    if let Some(f) = stats_export {
+        info!(
+            "  Stats export  : {:?}",
+            StatsExporter::sidecar_path(&output_path, f)
+        );
+    }
 
+    info!("  Verbose stats : kill -USR2 {}", std::process::id());
     info!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
     let session_start = Instant::now();
@@ -201,12 +845,20 @@ fn main() {
 
     if metrics.dropped_samples.load(Ordering::Relaxed) > 0 {
         warn!(
-            "⚠ {} samples dropped ({:.2}% loss). Consider: larger --ring-capacity or lower --rate",
+            "⚠ {} samples dropped ({:.2}% loss). Consider: larger --ring-capacity (or --ring-autotune) or lower --rate",
             metrics.dropped_samples.load(Ordering::Relaxed),
             summary.drop_rate_pct
         );
     }
 
+    if summary.digest_mismatches > 0 {
+        warn!(
+            "⚠ {} blocks changed in RAM before they were written (indices in the log above). \
+             The file has valid CRCs but corrupted data; check the machine's memory",
+            summary.digest_mismatches
+        );
+    }
+
     if metrics.write_errors.load(Ordering::Relaxed) > 0 {
         warn!(
             "⚠ {} write errors occurred. Check disk space and I/O.",
@@ -215,5 +867,45 @@ fn main() {
         std::process::exit(1);
     }
 
-    info!("✓ Recording complete: {:?}", cli.output);
+    info!("✓ Recording complete: {:?}", output_path);
+}
+
+/// SIGUSR2 переключает подробный режим: статистика раз в секунду и
+/// debug-логи; повторный сигнал возвращает прежние настройки.
+fn spawn_sigusr2_handler(control: Arc<RuntimeControl>) {
+    let mut signals = match Signals::new([SIGUSR2]) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to register SIGUSR2 handler: {e}");
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            control.toggle_verbose();
+        }
+    });
+}
+
+/// `glos-recorder list-devices`: устройства по бэкендам, ошибки опроса — в лог.
+fn list_devices() {
+    let mut found = 0;
+
+    for kind in glos_hal::backends() {
+        match glos_hal::probe(&kind) {
+            Ok(devices) => {
+                for device in &devices {
+                    println!("{}", describe_device(device));
+                }
+                found += devices.len();
+            }
+            Err(e) => warn!("{kind}: {e}"),
+        }
+    }
+
+    if found == 0 {
+        error!("No devices found");
+        std::process::exit(1);
+    }
 }
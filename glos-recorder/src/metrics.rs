@@ -1,12 +1,17 @@
 use std::{
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     time::Instant,
 };
 
-/// Метрики, обновляемые lock-free из нескольких потоков.
+use parking_lot::Mutex;
+
+use crate::TelemetryReading;
+
+/// Метрики, обновляемые из нескольких потоков. Счётчики горячего пути
+/// lock-free; под мьютексом только редко обновляемые показания датчиков.
 #[derive(Debug, Default)]
 pub struct RecorderMetrics {
     pub samples_recorded: AtomicU64,
@@ -14,6 +19,28 @@ pub struct RecorderMetrics {
     pub dropped_samples: AtomicU64,
     pub write_errors: AtomicU64,
     pub bytes_written: AtomicU64,
+    /// Наибольший объём блоков в памяти очереди записи (байты)
+    pub queue_peak_bytes: AtomicU64,
+    /// Блоков, ушедших во временный файл перелива
+    pub spilled_blocks: AtomicU64,
+    /// Сколько раз накопление ждало писателя (очередь полна, перелива нет)
+    pub queue_stalls: AtomicU64,
+    /// Блоков, данные которых изменились в RAM между постановкой в очередь
+    /// и сериализацией (двойная проверка дайджестов)
+    pub digest_mismatches: AtomicU64,
+    /// Байт, записанных на устройство записи за сессию всеми процессами
+    /// (0 — не измерялось, см. [`crate::DiskHealthMonitor`])
+    pub device_bytes_written: AtomicU64,
+    /// Наибольшая занятость кольцевого буфера между захватом и записью
+    /// (chunk'ов)
+    pub ring_peak_chunks: AtomicU64,
+    /// Запись блока упёрлась в заполненный диск
+    pub disk_full: AtomicBool,
+    /// Износ носителя по SMART в процентах плюс один (0 — неизвестен)
+    media_wear: AtomicU64,
+    /// Последние показания датчиков устройства (см.
+    /// [`crate::TelemetryMonitor`])
+    device_telemetry: Mutex<Option<TelemetryReading>>,
 }
 
 /// Snapshot мутрики для отображения / тестирования.
@@ -28,6 +55,15 @@ pub struct MetricsSummary {
     pub throughput_msps: f64,
     pub write_speed_mbps: f64,
     pub drop_rate_pct: f64,
+    pub digest_mismatches: u64,
+    /// Байт на устройство на байт записи (None — не измерялось)
+    pub write_amplification: Option<f64>,
+    /// Израсходованный ресурс носителя по SMART (%)
+    pub media_wear_pct: Option<u8>,
+    /// Наибольшая занятость кольцевого буфера (chunk'ов)
+    pub ring_peak_chunks: u64,
+    /// Датчики устройства (None — не опрашивались)
+    pub telemetry: Option<TelemetryReading>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -79,6 +115,47 @@ impl RecorderMetrics {
         }
     }
 
+    /// Оценка write amplification: байты на устройстве (с метаданными ФС,
+    /// журналом и записями других процессов) на байт записи.
+    pub fn write_amplification(&self) -> Option<f64> {
+        let device = self.device_bytes_written.load(Ordering::Relaxed);
+        let payload = self.bytes_written.load(Ordering::Relaxed);
+
+        (device > 0 && payload > 0).then(|| device as f64 / payload as f64)
+    }
+
+    pub fn set_device_bytes_written(
+        &self,
+        bytes: u64,
+    ) {
+        self.device_bytes_written.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn media_wear_pct(&self) -> Option<u8> {
+        match self.media_wear.load(Ordering::Relaxed) {
+            0 => None,
+            v => Some((v - 1) as u8),
+        }
+    }
+
+    pub fn set_media_wear_pct(
+        &self,
+        pct: u8,
+    ) {
+        self.media_wear.store(pct as u64 + 1, Ordering::Relaxed);
+    }
+
+    pub fn device_telemetry(&self) -> Option<TelemetryReading> {
+        *self.device_telemetry.lock()
+    }
+
+    pub fn set_device_telemetry(
+        &self,
+        reading: TelemetryReading,
+    ) {
+        *self.device_telemetry.lock() = Some(reading);
+    }
+
     /// Итоговая сводка для вывода в конце сессии.
     pub fn summary(
         &self,
@@ -94,10 +171,44 @@ impl RecorderMetrics {
             throughput_msps: self.throughput_msps(elapsed),
             write_speed_mbps: self.write_speed_mbps(elapsed),
             drop_rate_pct: self.drop_rate_pct(),
+            digest_mismatches: self.digest_mismatches.load(Ordering::Relaxed),
+            write_amplification: self.write_amplification(),
+            media_wear_pct: self.media_wear_pct(),
+            ring_peak_chunks: self.ring_peak_chunks.load(Ordering::Relaxed),
+            telemetry: self.device_telemetry(),
         }
     }
 }
 
+impl MetricsSummary {
+    /// Сводка в JSON (тело webhook-уведомлений).
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "duration_secs": self.duration_secs,
+            "samples_recorded": self.samples_recorded,
+            "blocks_written": self.blocks_written,
+            "dropped_samples": self.dropped_samples,
+            "write_errors": self.write_errors,
+            "bytes_written": self.bytes_written,
+            "throughput_msps": self.throughput_msps,
+            "write_speed_mbps": self.write_speed_mbps,
+            "drop_rate_pct": self.drop_rate_pct,
+            "digest_mismatches": self.digest_mismatches,
+            "write_amplification": self.write_amplification,
+            "media_wear_pct": self.media_wear_pct,
+            "ring_peak_chunks": self.ring_peak_chunks,
+            "device_temperature_c": self.telemetry.and_then(|t| t.temperature_c),
+            "rssi_dbfs": self.telemetry.and_then(|t| t.rssi_dbfs),
+            "usb_errors": self.telemetry.and_then(|t| t.usb_errors),
+            "usb_throughput_mbps": self.telemetry.and_then(|t| t.usb_throughput_mbps),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
 impl std::fmt::Display for MetricsSummary {
     fn fmt(
         &self,
@@ -113,6 +224,10 @@ impl std::fmt::Display for MetricsSummary {
             self.dropped_samples, self.drop_rate_pct
         )?;
         writeln!(f, "  Write errors  : {}", self.write_errors)?;
+        writeln!(f, "  Ring peak     : {} chunks", self.ring_peak_chunks)?;
+        if self.digest_mismatches > 0 {
+            writeln!(f, "  RAM corrupted : {} blocks", self.digest_mismatches)?;
+        }
         writeln!(
             f,
             "  Bytes written : {:.1} MB",
@@ -120,6 +235,26 @@ impl std::fmt::Display for MetricsSummary {
         )?;
         writeln!(f, "  Throughput    : {:.3} Msps", self.throughput_msps)?;
         writeln!(f, "  Write speed   : {:.1} MB/s", self.write_speed_mbps)?;
+        if let Some(wa) = self.write_amplification {
+            writeln!(f, "  Write ampl.   : {wa:.2}x")?;
+        }
+        if let Some(wear) = self.media_wear_pct {
+            writeln!(f, "  Media wear    : {wear}%")?;
+        }
+        if let Some(t) = &self.telemetry {
+            if let Some(temp) = t.temperature_c {
+                writeln!(f, "  Device temp   : {temp:.1} °C")?;
+            }
+            if let Some(rssi) = t.rssi_dbfs {
+                writeln!(f, "  Input level   : {rssi:.1} dBFS")?;
+            }
+            if let Some(mbps) = t.usb_throughput_mbps {
+                writeln!(f, "  USB           : {mbps:.1} MB/s")?;
+            }
+            if let Some(errors) = t.usb_errors.filter(|&e| e > 0) {
+                writeln!(f, "  USB errors    : {errors}")?;
+            }
+        }
         write!(f, "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
     }
 }
@@ -148,6 +283,11 @@ mod tests {
         assert_eq!(summary.throughput_msps, 0.0);
         assert_eq!(summary.write_speed_mbps, 0.0);
         assert_eq!(summary.drop_rate_pct, 0.0);
+        assert_eq!(summary.digest_mismatches, 0);
+        assert_eq!(summary.write_amplification, None);
+        assert_eq!(summary.media_wear_pct, None);
+        assert_eq!(summary.ring_peak_chunks, 0);
+        assert_eq!(summary.telemetry, None);
     }
 
     #[test]
@@ -178,6 +318,19 @@ mod tests {
         assert!((summary.write_speed_mbps - 5.0).abs() < 0.1);
     }
 
+    #[test]
+    fn test_disk_health_in_summary() {
+        let metrics = RecorderMetrics::new();
+        metrics.bytes_written.store(1_000_000, Ordering::Relaxed);
+        metrics.set_device_bytes_written(1_250_000);
+        metrics.set_media_wear_pct(0);
+
+        let summary = metrics.summary(&Instant::now());
+
+        assert!((summary.write_amplification.unwrap() - 1.25).abs() < 1e-9);
+        assert_eq!(summary.media_wear_pct, Some(0));
+    }
+
     #[test]
     fn test_summary_snapshot_consistency() {
         let metrics = RecorderMetrics::new();
@@ -0,0 +1,198 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+};
+
+use crossbeam_channel::{Sender, TrySendError};
+use glos_core::GlosWriter;
+use glos_types::{GlosHeader, IqBlock};
+use log::warn;
+
+use crate::RecorderResult;
+
+/// Глубина очереди блоков к зеркалу. Если зеркальный диск отстаёт сильнее,
+/// зеркало отключается, а основная запись продолжается.
+pub const MIRROR_QUEUE_BLOCKS: usize = 64;
+
+/// Вторая копия записи на отдельном потоке (RAID-1 на уровне файлов).
+///
+/// Блоки пишутся параллельно с основным файлом. Сбой зеркала (ошибка
+/// ввода-вывода или переполнение очереди) не прерывает запись: зеркало
+/// отключается с предупреждением, а итог виден в [`MirrorReport`].
+pub struct MirrorWriter {
+    tx: Sender<MirrorCmd>,
+    handle: JoinHandle<MirrorReport>,
+    failed: Arc<AtomicBool>,
+    path: PathBuf,
+}
+
+/// Итог работы зеркала после [`MirrorWriter::finish`].
+#[derive(Debug, Clone, Default)]
+pub struct MirrorReport {
+    pub blocks_written: u64,
+    /// Причина отключения зеркала (None = зеркало дописано полностью)
+    pub error: Option<String>,
+}
+
+enum MirrorCmd {
+    Block(IqBlock),
+    Finish(u64),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl MirrorWriter {
+    /// Создаёт файл зеркала с тем же заголовком и запускает поток записи.
+    ///
+    /// Ошибка открытия файла возвращается сразу, чтобы вызывающий мог
+    /// решить, продолжать ли без зеркала.
+    pub fn create(
+        path: &Path,
+        header: GlosHeader,
+    ) -> RecorderResult<Self> {
+        let writer = GlosWriter::new(File::create(path)?, header)?;
+        let (tx, rx) = crossbeam_channel::bounded(MIRROR_QUEUE_BLOCKS);
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let thread_failed = failed.clone();
+        let thread_path = path.to_path_buf();
+
+        let handle = std::thread::Builder::new()
+            .name("glos-mirror".into())
+            .spawn(move || {
+                let mut writer = Some(writer);
+                let mut report = MirrorReport::default();
+
+                for cmd in rx {
+                    match cmd {
+                        MirrorCmd::Block(block) => {
+                            let Some(w) = writer.as_mut() else {
+                                continue;
+                            };
+
+                            match w.write_block(block) {
+                                Ok(()) => report.blocks_written += 1,
+                                Err(e) => {
+                                    warn!("Mirror {thread_path:?} write failed: {e}. Disabling");
+                                    thread_failed.store(true, Ordering::Relaxed);
+                                    report.error = Some(e.to_string());
+                                    writer = None;
+                                }
+                            }
+                        }
+                        MirrorCmd::Finish(timestamp_end) => {
+                            if let Some(w) = writer.take() {
+                                if let Err(e) = w.finish_at(timestamp_end) {
+                                    report.error = Some(e.to_string());
+                                }
+                            }
+                            break;
+                        }
+                    }
+                }
+
+                report
+            })?;
+
+        Ok(Self {
+            tx,
+            handle,
+            failed,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Ставит копию блока в очередь зеркала. Не блокируется: если очередь
+    /// полна, зеркало считается отставшим и отключается.
+    pub fn write_block(
+        &self,
+        block: &IqBlock,
+    ) {
+        if self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+
+        match self.tx.try_send(MirrorCmd::Block(block.clone())) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!("Mirror {:?} fell behind. Disabling", self.path);
+                self.failed.store(true, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                self.failed.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Зеркало отключено после сбоя.
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Финализирует заголовок зеркала тем же `timestamp_end`, что и у
+    /// основного файла, и дожидается потока записи.
+    pub fn finish(
+        self,
+        timestamp_end: u64,
+    ) -> MirrorReport {
+        let fell_behind = self.is_failed();
+        // Блокирующая отправка: все ранее поставленные блоки будут дописаны
+        let _ = self.tx.send(MirrorCmd::Finish(timestamp_end));
+
+        let mut report = self.handle.join().unwrap_or_else(|_| MirrorReport {
+            blocks_written: 0,
+            error: Some("mirror thread panicked".into()),
+        });
+
+        if fell_behind && report.error.is_none() {
+            report.error = Some("mirror fell behind the primary writer".into());
+        }
+
+        report
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Побайтно сравнивает два файла.
+pub fn files_identical(
+    a: &Path,
+    b: &Path,
+) -> std::io::Result<bool> {
+    let (fa, fb) = (File::open(a)?, File::open(b)?);
+
+    if fa.metadata()?.len() != fb.metadata()?.len() {
+        return Ok(false);
+    }
+
+    let mut ra = BufReader::new(fa);
+    let mut rb = BufReader::new(fb);
+    let mut buf_a = vec![0u8; 64 * 1024];
+    let mut buf_b = vec![0u8; 64 * 1024];
+
+    loop {
+        let n = ra.read(&mut buf_a)?;
+        if n == 0 {
+            return Ok(true);
+        }
+
+        rb.read_exact(&mut buf_b[..n])?;
+        if buf_a[..n] != buf_b[..n] {
+            return Ok(false);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use glos_types::{GlosHeader, SdrType};
+
+/// Подстановки, поддерживаемые в шаблоне имени файла.
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "date", "time", "datetime", "unix", "freq", "rate", "gain", "sdr", "format", "seq",
+];
+
+/// Максимальный суффикс `_N`, перебираемый при совпадении имён.
+const MAX_COLLISION_SUFFIX: u32 = 9_999;
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Путь содержит подстановки вида `{date}`.
+pub fn is_template(path: &Path) -> bool {
+    path.to_string_lossy().contains('{')
+}
+
+/// Раскрывает шаблон имени файла по полям заголовка.
+///
+/// Время берётся из `header.timestamp_start` (UTC). `seq` — номер сегмента
+/// для ротации и расписания (в имени — три цифры, `{seq}` → `007`).
+///
+/// | Поле         | Пример             |
+/// |--------------|--------------------|
+/// | `{date}`     | `20240101`         |
+/// | `{time}`     | `134501`           |
+/// | `{datetime}` | `20240101T134501Z` |
+/// | `{unix}`     | `1704116701`       |
+/// | `{freq}`     | `1602MHz`          |
+/// | `{rate}`     | `2Msps`            |
+/// | `{gain}`     | `40dB`             |
+/// | `{sdr}`      | `hackrf`           |
+/// | `{format}`   | `int16`            |
+/// | `{seq}`      | `000`              |
+pub fn expand_template(
+    template: &str,
+    header: &GlosHeader,
+    seq: u32,
+) -> Result<String, String> {
+    let start = DateTime::<Utc>::from_timestamp(header.timestamp_start as i64, 0)
+        .ok_or_else(|| format!("Invalid start time {}", header.timestamp_start))?;
+
+    let mut out = String::with_capacity(template.len() + 32);
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in output template '{template}'"))?;
+        let field = &rest[open + 1..open + close];
+
+        let value = match field {
+            "date" => start.format("%Y%m%d").to_string(),
+            "time" => start.format("%H%M%S").to_string(),
+            "datetime" => start.format("%Y%m%dT%H%M%SZ").to_string(),
+            "unix" => header.timestamp_start.to_string(),
+            "freq" => format!("{}MHz", trim_number(header.center_freq as f64 / 1e6)),
+            "rate" => format!("{}Msps", trim_number(header.sample_rate as f64 / 1e6)),
+            "gain" => format!("{}dB", trim_number(header.gain_db as f64)),
+            "sdr" => sdr_name(header.sdr_type),
+            "format" => header.iq_format.to_string(),
+            "seq" => format!("{seq:03}"),
+            _ => {
+                return Err(format!(
+                    "Unknown field '{{{field}}}' in output template. Use: {}",
+                    TEMPLATE_FIELDS.join(", ")
+                ))
+            }
+        };
+
+        out.push_str(&value);
+        rest = &rest[open + close + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Возвращает `path`, если он свободен, иначе первый свободный вариант с
+/// суффиксом: `capture.glos` → `capture_1.glos`, `capture_2.glos`, …
+pub fn unique_path(path: &Path) -> Result<PathBuf, String> {
+    if !path.exists() {
+        return Ok(path.to_path_buf());
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = path.extension().map(|e| e.to_string_lossy().into_owned());
+
+    for n in 1..=MAX_COLLISION_SUFFIX {
+        let name = match &ext {
+            Some(ext) => format!("{stem}_{n}.{ext}"),
+            None => format!("{stem}_{n}"),
+        };
+        let candidate = path.with_file_name(name);
+
+        if !candidate.exists() {
+            return Ok(candidate);
+        }
+    }
+
+    Err(format!("No free file name for {path:?}"))
+}
+
+/// Итоговый путь записи.
+///
+/// Обычный путь возвращается как есть (существующий файл перезаписывается,
+/// как раньше). Шаблон раскрывается и при совпадении получает суффикс, так
+/// что повторные сессии не затирают друг друга.
+pub fn resolve_output_path(
+    path: &Path,
+    header: &GlosHeader,
+    seq: u32,
+) -> Result<PathBuf, String> {
+    if !is_template(path) {
+        return Ok(path.to_path_buf());
+    }
+
+    let expanded = expand_template(&path.to_string_lossy(), header, seq)?;
+    unique_path(Path::new(&expanded))
+}
+
+/// `1602.0` → `1602`, `1575.42` → `1575.42`.
+fn trim_number(v: f64) -> String {
+    let s = format!("{v:.6}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Имя устройства в файле; симулятор пишет `Unknown` и называется `sim`.
+fn sdr_name(sdr: SdrType) -> String {
+    match sdr {
+        SdrType::Unknown => "sim".to_string(),
+        other => other.to_string(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::GlosHeaderExt;
+
+    use super::*;
+
+    fn header() -> GlosHeader {
+        let mut h = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_575_420_000);
+        h.timestamp_start = 1_704_116_701; // 2024-01-01 13:45:01 UTC
+        h.gain_db = 40.0;
+        h
+    }
+
+    #[test]
+    fn test_expand_template_fields() {
+        let h = header();
+
+        assert_eq!(
+            expand_template("capture_{date}_{time}_{freq}.glos", &h, 0).unwrap(),
+            "capture_20240101_134501_1575.42MHz.glos"
+        );
+        assert_eq!(
+            expand_template("{sdr}-{rate}-{gain}-{format}-{seq}-{unix}", &h, 7).unwrap(),
+            "hackrf-2Msps-40dB-int16-007-1704116701"
+        );
+        assert_eq!(
+            expand_template("{datetime}.glos", &h, 0).unwrap(),
+            "20240101T134501Z.glos"
+        );
+
+        assert!(expand_template("{nope}.glos", &h, 0).is_err());
+        assert!(expand_template("{date.glos", &h, 0).is_err());
+    }
+
+    #[test]
+    fn test_resolve_output_path_avoids_collisions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let h = header();
+        let template = dir.path().join("rec_{date}.glos");
+
+        let first = resolve_output_path(&template, &h, 0).unwrap();
+        assert_eq!(first, dir.path().join("rec_20240101.glos"));
+        std::fs::write(&first, b"x").unwrap();
+
+        let second = resolve_output_path(&template, &h, 0).unwrap();
+        assert_eq!(second, dir.path().join("rec_20240101_1.glos"));
+        std::fs::write(&second, b"x").unwrap();
+
+        assert_eq!(
+            resolve_output_path(&template, &h, 0).unwrap(),
+            dir.path().join("rec_20240101_2.glos")
+        );
+
+        // Обычный путь не меняется, даже если файл существует
+        assert_eq!(resolve_output_path(&first, &h, 0).unwrap(), first);
+    }
+}
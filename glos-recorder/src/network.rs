@@ -0,0 +1,555 @@
+use std::{
+    fs::File,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use glos_core::{
+    CompressionOptions, GlosWriter, IqBlockExt, StreamVerifier, UdpPacket, UdpProtocol,
+    GLOS_BLOCK_OVERHEAD,
+};
+use glos_types::{GlosHeader, IqBlock};
+use log::{debug, info, warn};
+
+use crate::{BlockSink, RecorderMetrics, RecorderResult, RotatingWriter, RotationConfig};
+
+/// Как часто цикл приёма проверяет флаг остановки и тишину.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Приём IQ потока по UDP (протокол [`glos_core::udp`]) с записью в `.glos`.
+///
+/// Библиотечная замена отдельному процессу-приёмнику: сервис встраивает
+/// «слушать порт, писать файл с ротацией и метриками» одним вызовом
+/// [`run`](Self::run). UDP не несёт параметров записи, поэтому частота,
+/// центр, формат и сжатие берутся из заголовка по умолчанию, а
+/// `timestamp_start` — по первому принятому блоку.
+///
+/// Блоки пишутся в порядке прихода; опоздавшие (с меткой не позже уже
+/// записанного) отбрасываются, пропуски по меткам считаются потерями.
+pub struct NetworkCapture {
+    socket: UdpSocket,
+    header: GlosHeader,
+    protocol: UdpProtocol,
+    output_path: PathBuf,
+    rotation: Option<RotationConfig>,
+    compression: CompressionOptions,
+    checkpoint_interval: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    metrics: Arc<RecorderMetrics>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+/// Итог [`NetworkCapture::run`].
+#[derive(Debug, Clone, Default)]
+pub struct NetworkCaptureReport {
+    pub packets_received: u64,
+    /// Пакетов, которые не удалось разобрать или собрать в блок
+    pub packets_malformed: u64,
+    pub blocks_written: u64,
+    pub samples_written: u64,
+    /// Блоков, от которых пришли не все пакеты
+    pub blocks_incomplete: u64,
+    /// Блоков, данные которых не совпали с дайджестом (только v2)
+    pub blocks_mismatched: u64,
+    /// Блоков, пришедших после более поздних (не записаны)
+    pub blocks_late: u64,
+    /// Оценка потерянных выборок по разрывам меток блоков
+    pub samples_lost: u64,
+    /// Записанные файлы по порядку (пусто — не принято ни одного блока)
+    pub files: Vec<PathBuf>,
+}
+
+/// Сборка блоков из пакетов.
+enum Assembler {
+    /// Пакеты блока идут подряд с одной меткой; блок готов, когда данных
+    /// набралось на `sample_count` выборок
+    V1 {
+        sample_size: usize,
+        pending: Option<(u64, u16, Vec<u8>)>,
+    },
+    V2(StreamVerifier),
+}
+
+enum CaptureWriter {
+    Single(GlosWriter<File>, PathBuf),
+    Rotating(Box<RotatingWriter>),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl NetworkCapture {
+    /// Слушает `bind`; заголовок записи строится из `header_defaults`.
+    ///
+    /// По умолчанию: протокол v1, файл `network.glos`, без ротации, без
+    /// контрольных точек, до [`stop_flag`](Self::stop_flag).
+    pub fn new(
+        bind: impl ToSocketAddrs,
+        header_defaults: GlosHeader,
+    ) -> RecorderResult<Self> {
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        Ok(Self {
+            socket,
+            header: header_defaults,
+            protocol: UdpProtocol::default(),
+            output_path: PathBuf::from("network.glos"),
+            rotation: None,
+            compression: CompressionOptions::default(),
+            checkpoint_interval: None,
+            idle_timeout: None,
+            metrics: RecorderMetrics::new(),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn with_protocol(
+        mut self,
+        protocol: UdpProtocol,
+    ) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Путь к файлу записи (с ротацией — первый сегмент).
+    pub fn with_output(
+        mut self,
+        path: impl Into<PathBuf>,
+    ) -> Self {
+        self.output_path = path.into();
+        self
+    }
+
+    /// Ротация по размеру или длительности (см. [`RotatingWriter`]).
+    pub fn with_rotation(
+        mut self,
+        rotation: RotationConfig,
+    ) -> Self {
+        self.rotation = Some(rotation);
+        self
+    }
+
+    /// Уровень и пороги сжатия; алгоритм задаёт заголовок по умолчанию.
+    pub fn with_compression(
+        mut self,
+        options: CompressionOptions,
+    ) -> Self {
+        self.compression = options;
+        self
+    }
+
+    /// Переписывать заголовок по ходу записи (см.
+    /// [`GlosWriter::with_checkpoint_interval`]).
+    pub fn with_checkpoint_interval(
+        mut self,
+        interval: Duration,
+    ) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Завершить приём, если пакетов нет дольше `timeout`.
+    pub fn with_idle_timeout(
+        mut self,
+        timeout: Duration,
+    ) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn local_addr(&self) -> RecorderResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Метрики приёма, обновляются во время [`run`](Self::run).
+    pub fn metrics(&self) -> Arc<RecorderMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Флаг остановки. Установка в `true` завершает приём и финализирует
+    /// файл.
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+
+    /// Принимает поток до остановки или тишины. Блокируется.
+    pub fn run(self) -> RecorderResult<NetworkCaptureReport> {
+        let addr = self.local_addr()?;
+        info!(
+            "Network capture: listening on {addr} ({}), output {:?}",
+            self.protocol, self.output_path
+        );
+
+        let mut assembler = Assembler::new(self.protocol, self.header.iq_format.sample_size());
+        let mut report = NetworkCaptureReport::default();
+        let mut writer: Option<CaptureWriter> = None;
+        // Конец последнего записанного блока (нс)
+        let mut end_ns: Option<u64> = None;
+
+        let mut buf = vec![0u8; 65_536];
+        let mut last_packet = Instant::now();
+
+        while !self.stop_flag.load(Ordering::Relaxed) {
+            let n = match self.socket.recv(&mut buf) {
+                Ok(n) => n,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    if self
+                        .idle_timeout
+                        .is_some_and(|t| last_packet.elapsed() >= t)
+                    {
+                        info!("Network capture: no packets for {:?}", self.idle_timeout);
+                        break;
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            last_packet = Instant::now();
+            report.packets_received += 1;
+
+            let Some(block) = assembler.accept(&buf[..n], &mut report) else {
+                continue;
+            };
+
+            let block_end_ns = block.timestamp_ns + self.samples_ns(block.sample_count as u64);
+            match end_ns {
+                Some(end) if block.timestamp_ns < end => {
+                    debug!("Late block @ {} ns dropped", block.timestamp_ns);
+                    report.blocks_late += 1;
+                    continue;
+                }
+                Some(end) if block.timestamp_ns > end => {
+                    let lost = (block.timestamp_ns - end) as u128 * self.header.sample_rate as u128
+                        / 1_000_000_000;
+                    report.samples_lost += lost as u64;
+                    self.metrics
+                        .dropped_samples
+                        .fetch_add(lost as u64, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+            end_ns = Some(block_end_ns);
+
+            let w = match writer.as_mut() {
+                Some(w) => w,
+                None => writer.insert(self.open(&block)?),
+            };
+
+            let samples = block.sample_count as u64;
+            let bytes = block.data.len() as u64 + GLOS_BLOCK_OVERHEAD as u64;
+            if let Err(e) = w.write_block(block) {
+                self.metrics.write_errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+
+            report.blocks_written += 1;
+            report.samples_written += samples;
+            self.metrics
+                .samples_recorded
+                .fetch_add(samples, Ordering::Relaxed);
+            self.metrics.blocks_written.fetch_add(1, Ordering::Relaxed);
+            self.metrics
+                .bytes_written
+                .fetch_add(bytes, Ordering::Relaxed);
+        }
+
+        report.blocks_incomplete += assembler.incomplete();
+
+        if let (Some(w), Some(end)) = (writer, end_ns) {
+            report.files = w.files();
+            w.finish_at(end.div_ceil(1_000_000_000))?;
+        }
+
+        if report.blocks_incomplete + report.blocks_mismatched + report.packets_malformed > 0 {
+            warn!(
+                "Network capture: {} incomplete, {} mismatched blocks, {} malformed packets",
+                report.blocks_incomplete, report.blocks_mismatched, report.packets_malformed
+            );
+        }
+        info!(
+            "Network capture finished: {} samples in {} blocks, ~{} samples lost",
+            report.samples_written, report.blocks_written, report.samples_lost
+        );
+
+        Ok(report)
+    }
+
+    /// Открывает файл по первому блоку: от него считается `timestamp_start`.
+    fn open(
+        &self,
+        first: &IqBlock,
+    ) -> RecorderResult<CaptureWriter> {
+        let mut header = self.header.clone();
+        header.timestamp_start = first.timestamp_ns / 1_000_000_000;
+        header.timestamp_end = 0;
+        header.total_samples = 0;
+
+        let mut writer = match &self.rotation {
+            Some(r) => CaptureWriter::Rotating(Box::new(RotatingWriter::create(
+                &self.output_path,
+                header,
+                self.compression,
+                r.clone(),
+            )?)),
+            None => CaptureWriter::Single(
+                GlosWriter::new(File::create(&self.output_path)?, header)?
+                    .with_compression(self.compression)?,
+                self.output_path.clone(),
+            ),
+        };
+        if let Some(interval) = self.checkpoint_interval {
+            writer = writer.with_checkpoint_interval(interval);
+        }
+
+        Ok(writer)
+    }
+
+    fn samples_ns(
+        &self,
+        samples: u64,
+    ) -> u64 {
+        (samples as u128 * 1_000_000_000 / self.header.sample_rate.max(1) as u128) as u64
+    }
+}
+
+impl Assembler {
+    fn new(
+        protocol: UdpProtocol,
+        sample_size: usize,
+    ) -> Self {
+        match protocol {
+            UdpProtocol::V1 => Assembler::V1 {
+                sample_size,
+                pending: None,
+            },
+            UdpProtocol::V2 => Assembler::V2(StreamVerifier::new()),
+        }
+    }
+
+    /// Принимает пакет; возвращает блок, когда он собран целиком.
+    fn accept(
+        &mut self,
+        packet: &[u8],
+        report: &mut NetworkCaptureReport,
+    ) -> Option<IqBlock> {
+        match self {
+            Assembler::V1 {
+                sample_size,
+                pending,
+            } => {
+                let Ok((ts, count, data)) = UdpPacket::decode(packet) else {
+                    report.packets_malformed += 1;
+                    return None;
+                };
+
+                // Пакет другого блока: предыдущий так и не дособрался
+                if pending.as_ref().is_some_and(|(p, ..)| *p != ts) {
+                    report.blocks_incomplete += 1;
+                    *pending = None;
+                }
+
+                let (_, _, buf) = pending.get_or_insert_with(|| (ts, count, Vec::new()));
+                buf.extend_from_slice(data);
+
+                let expected = count as usize * *sample_size;
+                match buf.len() {
+                    n if n < expected => None,
+                    n if n > expected => {
+                        report.packets_malformed += 1;
+                        *pending = None;
+                        None
+                    }
+                    _ => {
+                        let (ts, count, data) = pending.take()?;
+                        Some(IqBlock::new(ts, count as u32, data))
+                    }
+                }
+            }
+            Assembler::V2(verifier) => match verifier.accept_block(packet) {
+                Ok(Some((check, block))) if check.is_ok() => Some(block),
+                Ok(Some(_)) => {
+                    report.blocks_mismatched += 1;
+                    None
+                }
+                Ok(None) => None,
+                Err(_) => {
+                    report.packets_malformed += 1;
+                    None
+                }
+            },
+        }
+    }
+
+    /// Блоков, так и не собранных к концу приёма.
+    fn incomplete(&self) -> u64 {
+        match self {
+            Assembler::V1 { pending, .. } => pending.is_some() as u64,
+            Assembler::V2(verifier) => verifier.incomplete() as u64,
+        }
+    }
+}
+
+impl CaptureWriter {
+    fn with_checkpoint_interval(
+        self,
+        interval: Duration,
+    ) -> Self {
+        match self {
+            CaptureWriter::Single(w, path) => {
+                CaptureWriter::Single(w.with_checkpoint_interval(interval), path)
+            }
+            CaptureWriter::Rotating(w) => {
+                CaptureWriter::Rotating(Box::new(w.with_checkpoint_interval(interval)))
+            }
+        }
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        match self {
+            CaptureWriter::Single(_, path) => vec![path.clone()],
+            CaptureWriter::Rotating(w) => w.segments().to_vec(),
+        }
+    }
+
+    fn finish_at(
+        self,
+        timestamp_end: u64,
+    ) -> RecorderResult<()> {
+        match self {
+            CaptureWriter::Single(w, _) => Ok(w.finish_at(timestamp_end)?),
+            CaptureWriter::Rotating(w) => w.finish_at(timestamp_end),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlockSink for CaptureWriter {
+    fn write_block(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<()> {
+        match self {
+            CaptureWriter::Single(w, _) => Ok(w.write_block(block)?),
+            CaptureWriter::Rotating(w) => w.write_block(block),
+        }
+    }
+}
+
+impl NetworkCaptureReport {
+    /// Путь первого файла записи.
+    pub fn first_file(&self) -> Option<&Path> {
+        self.files.first().map(PathBuf::as_path)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::{read_all_blocks, GlosHeaderExt, GlosReader, UdpPacketizer};
+    use glos_types::{IqFormat, SdrType};
+
+    use super::*;
+
+    fn header() -> GlosHeader {
+        let mut h = GlosHeader::new(SdrType::HackRf, 1_000_000, 1_602_000_000);
+        h.iq_format = IqFormat::Int8;
+        h
+    }
+
+    #[test]
+    fn test_network_capture_v2_writes_glos() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("net.glos");
+
+        let capture = NetworkCapture::new("127.0.0.1:0", header())
+            .unwrap()
+            .with_protocol(UdpProtocol::V2)
+            .with_output(&path)
+            .with_idle_timeout(Duration::from_millis(300));
+        let addr = capture.local_addr().unwrap();
+        let metrics = capture.metrics();
+
+        // 1 мс = 1000 выборок Int8; третий блок не помещается в пакет,
+        // четвёртый потерян, последний опоздал (повтор уже принятого
+        // отбросил бы сборщик v2)
+        let t0 = 1_700_000_000_000_000_000;
+        let blocks = [
+            IqBlock::new(t0, 1_000, vec![1; 2_000]),
+            IqBlock::new(t0 + 1_000_000, 1_000, vec![2; 2_000]),
+            IqBlock::new(t0 + 2_000_000, 40_000, vec![3; 80_000]),
+            IqBlock::new(t0 + 42_000_000, 1_000, vec![4; 2_000]),
+            IqBlock::new(t0 + 43_000_000, 1_000, vec![5; 2_000]),
+            IqBlock::new(t0 + 1_500_000, 100, vec![6; 200]),
+        ];
+
+        let sender = std::thread::spawn(move || {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            for (i, b) in blocks.iter().enumerate() {
+                if i == 3 {
+                    continue;
+                }
+                for p in UdpPacketizer::packetize_v2(b).unwrap() {
+                    socket.send_to(&p, addr).unwrap();
+                    std::thread::sleep(Duration::from_millis(2));
+                }
+            }
+        });
+
+        let report = capture.run().unwrap();
+        sender.join().unwrap();
+
+        assert_eq!(report.blocks_written, 4);
+        assert_eq!(report.samples_written, 43_000);
+        assert_eq!(report.samples_lost, 1_000);
+        assert_eq!(report.blocks_late, 1);
+        assert_eq!(report.first_file(), Some(path.as_path()));
+        assert_eq!(metrics.samples_recorded.load(Ordering::Relaxed), 43_000);
+
+        let mut reader = GlosReader::new(File::open(&path).unwrap()).unwrap();
+        let written = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+        assert_eq!(written.len(), 4);
+        assert_eq!(written[3].data, vec![5; 2_000]);
+        assert_eq!(reader.header().timestamp_start, 1_700_000_000);
+        assert_eq!(reader.header().iq_format, IqFormat::Int8);
+    }
+
+    #[test]
+    fn test_v1_assembler_joins_split_block() {
+        let mut assembler = Assembler::new(UdpProtocol::V1, 2);
+        let mut report = NetworkCaptureReport::default();
+
+        let big = IqBlock::new(10, 40_000, vec![7; 80_000]);
+        let packets = UdpPacketizer::packetize(&big).unwrap();
+        assert_eq!(packets.len(), 2);
+
+        // Начало другого блока без продолжения
+        let orphan = UdpPacketizer::packetize(&IqBlock::new(5, 40_000, vec![1; 80_000])).unwrap();
+        assert!(assembler.accept(&orphan[0], &mut report).is_none());
+
+        assert!(assembler.accept(&packets[0], &mut report).is_none());
+        let block = assembler.accept(&packets[1], &mut report).unwrap();
+        assert_eq!((block.timestamp_ns, block.sample_count), (10, 40_000));
+        assert_eq!(block.data, big.data);
+        assert_eq!(report.blocks_incomplete, 1);
+
+        assert!(assembler.accept(&[0; 3], &mut report).is_none());
+        assert_eq!(report.packets_malformed, 1);
+        assert_eq!(assembler.incomplete(), 0);
+    }
+}
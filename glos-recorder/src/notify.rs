@@ -0,0 +1,653 @@
+use std::{
+    fmt,
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+use log::{info, warn};
+use serde_json::json;
+
+use crate::MetricsSummary;
+
+/// Сколько ждать ответа webhook или SMTP-сервера.
+pub const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Событие сессии, о котором рассылается уведомление.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// Запись завершилась штатно (лимит, Ctrl+C, конец потока)
+    Completed,
+    /// Запись прервана ошибкой
+    Failed,
+    /// Поток захвата остановился с ошибкой устройства
+    DeviceFailure,
+    /// Диск записи заполнен, блоки не пишутся
+    DiskFull,
+    /// Ротация: сегмент `prev` финализирован, запись идёт в `next` (в
+    /// архиве — имена членов)
+    Rotated { prev: PathBuf, next: PathBuf },
+}
+
+/// Адрес webhook: `http://host[:port][/path]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrl {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+/// Почтовое уведомление через SMTP-релей без шифрования и авторизации
+/// (локальный MTA или релей внутренней сети).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmtpConfig {
+    /// `host:port` релея
+    pub server: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Куда рассылать уведомления.
+#[derive(Debug, Clone)]
+pub struct NotifyConfig {
+    /// HTTP POST с JSON (событие и [`MetricsSummary`])
+    pub webhook: Option<WebhookUrl>,
+    pub smtp: Option<SmtpConfig>,
+    /// О каких событиях сообщать (пусто — обо всех)
+    pub events: Vec<NotifyEvent>,
+    pub timeout: Duration,
+}
+
+/// Уведомление о событии сессии.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub event: NotifyEvent,
+    /// Файл (или архив) записи
+    pub recording: PathBuf,
+    pub message: String,
+    pub summary: MetricsSummary,
+}
+
+/// Рассылает уведомления. Ошибки доставки только пишутся в лог: запись из-за
+/// них не прерывается.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    config: NotifyConfig,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl NotifyEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyEvent::Completed => "completed",
+            NotifyEvent::Failed => "failed",
+            NotifyEvent::DeviceFailure => "device-failure",
+            NotifyEvent::DiskFull => "disk-full",
+            NotifyEvent::Rotated { .. } => "rotated",
+        }
+    }
+}
+
+impl Notification {
+    /// Тело webhook.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = json!({
+            "event": self.event.as_str(),
+            "recording": self.recording.display().to_string(),
+            "message": self.message,
+            "summary": self.summary.to_json(),
+        });
+        if let NotifyEvent::Rotated { prev, next } = &self.event {
+            json["prev"] = prev.display().to_string().into();
+            json["next"] = next.display().to_string().into();
+        }
+
+        json
+    }
+
+    /// Тема письма.
+    pub fn subject(&self) -> String {
+        format!(
+            "[glos-recorder] {}: {}",
+            self.event,
+            self.recording.display()
+        )
+    }
+}
+
+impl NotifyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.webhook.is_none() && self.smtp.is_none()
+    }
+
+    /// Выбрано ли событие; сравнивается только вид события (у `Rotated` —
+    /// без путей).
+    pub fn wants(
+        &self,
+        event: &NotifyEvent,
+    ) -> bool {
+        self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|e| std::mem::discriminant(e) == std::mem::discriminant(event))
+    }
+}
+
+impl Notifier {
+    pub fn new(config: NotifyConfig) -> Self {
+        Self { config }
+    }
+
+    /// Отправляет уведомление во все настроенные каналы, если событие
+    /// выбрано в `events`. Блокируется до ответа каждого канала (не дольше
+    /// `timeout` на операцию).
+    pub fn send(
+        &self,
+        notification: &Notification,
+    ) {
+        if !self.config.wants(&notification.event) {
+            return;
+        }
+
+        let timeout = self.config.timeout;
+
+        if let Some(url) = &self.config.webhook {
+            match post_json(url, &notification.to_json().to_string(), timeout) {
+                Ok(()) => info!("Notification '{}' sent to {url}", notification.event),
+                Err(e) => warn!("Webhook {url}: {e}"),
+            }
+        }
+
+        if let Some(smtp) = &self.config.smtp {
+            let body = format!("{}\n\n{}\n", notification.message, notification.summary);
+            match send_mail(smtp, &notification.subject(), &body, timeout) {
+                Ok(()) => info!(
+                    "Notification '{}' mailed to {}",
+                    notification.event,
+                    smtp.to.join(", ")
+                ),
+                Err(e) => warn!("SMTP {}: {e}", smtp.server),
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl fmt::Display for NotifyEvent {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NotifyEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "completed" => Ok(NotifyEvent::Completed),
+            "failed" => Ok(NotifyEvent::Failed),
+            "device-failure" => Ok(NotifyEvent::DeviceFailure),
+            "disk-full" => Ok(NotifyEvent::DiskFull),
+            "rotated" => Ok(NotifyEvent::Rotated {
+                prev: PathBuf::new(),
+                next: PathBuf::new(),
+            }),
+            _ => Err(format!(
+                "Unknown event '{s}'. Valid: completed, failed, device-failure, disk-full, rotated"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for WebhookUrl {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "http://{}:{}{}", self.host, self.port, self.path)
+    }
+}
+
+impl FromStr for WebhookUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("https://") {
+            return Err(
+                "https is not supported, point the webhook at a local http relay".to_string(),
+            );
+        }
+
+        let rest = s
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("'{s}': expected http://host[:port][/path]"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| format!("'{s}': invalid port '{port}'"))?,
+            ),
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(format!("'{s}': missing host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn connect(
+    addr: impl ToSocketAddrs,
+    timeout: Duration,
+) -> std::io::Result<TcpStream> {
+    let mut last_err = None;
+
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_err = Some(e),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| std::io::Error::other("address resolved to nothing")))
+}
+
+/// HTTP/1.1 POST; успех — любой ответ 2xx.
+fn post_json(
+    url: &WebhookUrl,
+    body: &str,
+    timeout: Duration,
+) -> std::io::Result<()> {
+    let mut stream = connect((url.host.as_str(), url.port), timeout)?;
+
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: glos-recorder/{}\r\n\
+         Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        url.path,
+        url.host,
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+    )?;
+    stream.flush()?;
+
+    let mut status = String::new();
+    BufReader::new(stream).read_line(&mut status)?;
+    let status = status.trim_end();
+
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(std::io::Error::other(format!(
+            "unexpected response '{status}'"
+        ))),
+    }
+}
+
+/// Читает ответ SMTP (с многострочными `250-...`) и проверяет код.
+fn smtp_expect(
+    reader: &mut impl BufRead,
+    code: &str,
+) -> std::io::Result<()> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::other("connection closed"));
+        }
+
+        if !line.starts_with(code) {
+            return Err(std::io::Error::other(format!(
+                "expected {code}, got '{}'",
+                line.trim_end()
+            )));
+        }
+        // `250-` — продолжение, `250 ` — последняя строка ответа
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+fn send_mail(
+    smtp: &SmtpConfig,
+    subject: &str,
+    body: &str,
+    timeout: Duration,
+) -> std::io::Result<()> {
+    let mut stream = connect(smtp.server.as_str(), timeout)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    smtp_expect(&mut reader, "220")?;
+
+    let mut command = |line: &str, code: &str| {
+        write!(stream, "{line}\r\n")?;
+        smtp_expect(&mut reader, code)
+    };
+
+    command("HELO glos-recorder", "250")?;
+    command(&format!("MAIL FROM:<{}>", smtp.from), "250")?;
+    for to in &smtp.to {
+        // 250 или 251 (пересылка)
+        command(&format!("RCPT TO:<{to}>"), "25")?;
+    }
+    command("DATA", "354")?;
+
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {subject}\r\nMIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\r\n",
+        smtp.from,
+        smtp.to.join(", "),
+    );
+    for line in body.lines() {
+        // Точка в начале строки удваивается, иначе сервер примет её за
+        // конец письма
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push('.');
+
+    command(&message, "250")?;
+    command("QUIT", "221")
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, thread};
+
+    use super::*;
+
+    fn summary() -> MetricsSummary {
+        MetricsSummary {
+            duration_secs: 60.0,
+            samples_recorded: 120_000_000,
+            blocks_written: 1_200,
+            dropped_samples: 0,
+            write_errors: 0,
+            bytes_written: 480_000_000,
+            throughput_msps: 2.0,
+            write_speed_mbps: 8.0,
+            drop_rate_pct: 0.0,
+            digest_mismatches: 0,
+            write_amplification: None,
+            media_wear_pct: None,
+            ring_peak_chunks: 3,
+            telemetry: None,
+        }
+    }
+
+    fn notification(event: NotifyEvent) -> Notification {
+        Notification {
+            event,
+            recording: "station/rec.glos".into(),
+            message: "Recording complete".into(),
+            summary: summary(),
+        }
+    }
+
+    fn config(
+        webhook: Option<WebhookUrl>,
+        smtp: Option<SmtpConfig>,
+    ) -> NotifyConfig {
+        NotifyConfig {
+            webhook,
+            smtp,
+            events: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_webhook_url_parse() {
+        let url: WebhookUrl = "http://hooks.local:8080/glos/notify".parse().unwrap();
+        assert_eq!(
+            (url.host.as_str(), url.port, url.path.as_str()),
+            ("hooks.local", 8080, "/glos/notify")
+        );
+        assert_eq!(url.to_string(), "http://hooks.local:8080/glos/notify");
+
+        let url: WebhookUrl = "http://10.0.0.5".parse().unwrap();
+        assert_eq!((url.port, url.path.as_str()), (80, "/"));
+
+        assert!("https://hooks.example.com/x".parse::<WebhookUrl>().is_err());
+        assert!("hooks.local/x".parse::<WebhookUrl>().is_err());
+        assert!("http://:80/x".parse::<WebhookUrl>().is_err());
+        assert!("http://host:port/x".parse::<WebhookUrl>().is_err());
+    }
+
+    #[test]
+    fn test_event_filter_ignores_rotation_paths() {
+        let mut cfg = config(None, None);
+        cfg.events = vec!["rotated".parse().unwrap()];
+
+        let rotated = NotifyEvent::Rotated {
+            prev: "rec_000.glos".into(),
+            next: "rec_001.glos".into(),
+        };
+        assert!(cfg.wants(&rotated));
+        assert!(!cfg.wants(&NotifyEvent::Completed));
+
+        let json = notification(rotated).to_json();
+        assert_eq!(json["event"], "rotated");
+        assert_eq!(json["prev"], "rec_000.glos");
+        assert_eq!(json["next"], "rec_001.glos");
+    }
+
+    #[test]
+    fn test_webhook_posts_summary_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(v) = line.strip_prefix("Content-Length: ") {
+                    length = v.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+
+        let url = format!("http://127.0.0.1:{port}/hook").parse().unwrap();
+        post_json(
+            &url,
+            &notification(NotifyEvent::Completed).to_json().to_string(),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /hook HTTP/1.1\r\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "completed");
+        assert_eq!(json["recording"], "station/rec.glos");
+        assert_eq!(json["summary"]["blocks_written"], 1_200);
+        assert_eq!(
+            json["summary"]["write_amplification"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_webhook_error_status_is_reported() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4096];
+            let _ = stream.read(&mut buf).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 500 Internal Server Error\r\n\r\n")
+                .unwrap();
+        });
+
+        let url = format!("http://127.0.0.1:{port}/").parse().unwrap();
+        let err = post_json(&url, "{}", Duration::from_secs(5)).unwrap_err();
+        server.join().unwrap();
+
+        assert!(err.to_string().contains("500"));
+    }
+
+    #[test]
+    fn test_smtp_dialog() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut w = stream;
+            let mut transcript = Vec::new();
+
+            w.write_all(b"220 relay ESMTP\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        b""
+                    }
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else if line.starts_with("HELO") {
+                    b"250-relay\r\n250 OK\r\n"
+                } else {
+                    b"250 OK\r\n"
+                };
+                transcript.push(line);
+                w.write_all(reply).unwrap();
+            }
+            transcript
+        });
+
+        let smtp = SmtpConfig {
+            server: addr.to_string(),
+            from: "station@glos.local".into(),
+            to: vec!["ops@glos.local".into(), "oncall@glos.local".into()],
+        };
+        Notifier::new(config(None, Some(smtp))).send(&notification(NotifyEvent::DiskFull));
+
+        let transcript = server.join().unwrap();
+        assert_eq!(transcript[0], "HELO glos-recorder");
+        assert_eq!(transcript[1], "MAIL FROM:<station@glos.local>");
+        assert_eq!(transcript[2], "RCPT TO:<ops@glos.local>");
+        assert_eq!(transcript[3], "RCPT TO:<oncall@glos.local>");
+        assert!(transcript
+            .contains(&"Subject: [glos-recorder] disk-full: station/rec.glos".to_string()));
+        assert!(transcript
+            .iter()
+            .any(|l| l.starts_with("  Blocks        : 1200")));
+        assert_eq!(transcript.last().unwrap(), "QUIT");
+    }
+
+    #[test]
+    fn test_smtp_body_dot_stuffing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut w = stream;
+            let mut data = Vec::new();
+
+            w.write_all(b"220 relay\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        w.write_all(b"250 queued\r\n").unwrap();
+                    } else {
+                        data.push(line);
+                    }
+                } else if line == "DATA" {
+                    in_data = true;
+                    w.write_all(b"354 go ahead\r\n").unwrap();
+                } else if line == "QUIT" {
+                    w.write_all(b"221 bye\r\n").unwrap();
+                } else {
+                    w.write_all(b"250 OK\r\n").unwrap();
+                }
+            }
+            data
+        });
+
+        let smtp = SmtpConfig {
+            server: addr.to_string(),
+            from: "station@glos.local".into(),
+            to: vec!["ops@glos.local".into()],
+        };
+        send_mail(&smtp, "subject", ".hidden\nvisible", Duration::from_secs(5)).unwrap();
+
+        let data = server.join().unwrap();
+        assert!(data.ends_with(&["..hidden".to_string(), "visible".to_string()]));
+    }
+}
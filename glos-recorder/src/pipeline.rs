@@ -1,25 +1,74 @@
 use std::{
-    fs::File,
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    ops::Range,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant},
 };
 
-use crossbeam_channel::RecvTimeoutError;
-use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt};
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use glos_core::{
+    dsp::{FormatAdvisor, ThumbnailBuilder},
+    ArchiveMember, BlockCompressor, GlosArchiveWriter, GlosHeaderExt, GlosWriter, IqBlockExt,
+    RecordingMetadata, SyncPoint, Xxh64, GLOS_SYNC_MARKER_ID_MAX,
+};
 use glos_hal::{IqChunk, SdrDevice};
-use glos_types::{GlosHeader, IqBlock};
-use log::{info, warn};
+use glos_types::{BlockAttributes, Clock, Compression, IqBlock, RuntimeControl, SyncMarker};
+use log::{debug, info, warn};
+
+use crate::{
+    metrics::RecorderMetrics, mirror::files_identical, BlockBuffers, BlockSink, BranchWriter,
+    ClockOffset, CompressPool, DiskHealthMonitor, DriftEstimator, MirrorWriter, Notification,
+    Notifier, NotifyEvent, PreBuffer, RecorderConfig, RecorderError, RecorderResult, RingHistory,
+    RingTuner, RotatingWriter, SessionLog, StatsExporter, SyncMarkers, SyncRequest,
+    TelemetryMonitor, WriteQueue,
+};
+
+/// Куда пишется основная запись.
+enum RecordingOutput<'a> {
+    File(File),
+    Archive(ArchiveMember<&'a mut File>),
+}
+
+/// Писатель основной записи: один файл (или член архива) либо серия файлов
+/// с ротацией.
+enum RecordingWriter<'a> {
+    Single(GlosWriter<RecordingOutput<'a>>),
+    Rotating(Box<RotatingWriter>),
+}
+
+/// События устройства, ещё не привязанные к блокам.
+#[derive(Debug, Default)]
+struct BlockEvents {
+    /// Смена усиления и потери — первому блоку после события
+    pending: BlockAttributes,
+    /// Выборки устройства с перегрузкой: каждый блок, который их задевает,
+    /// помечается
+    overload: Option<Range<u64>>,
+}
 
-use crate::{metrics::RecorderMetrics, RecorderConfig, RecorderResult};
+/// XXH64 копящегося блока, считаемый по кускам chunk'ов сразу при приёме
+/// (см. [`crate::WriteQueueConfig::verify_digests`]). Посчитаны первые
+/// `hashed` байт накопителя; остальные — хвост текущего куска chunk'а:
+/// граница блока при нарезке по содержимому может пройти внутри него.
+struct AccDigest {
+    hasher: Xxh64,
+    hashed: usize,
+}
 
 /// Оркестрирует сессию записи.
 pub struct RecordingPipeline {
     config: RecorderConfig,
     metrics: Arc<RecorderMetrics>,
     stop_flag: Arc<AtomicBool>,
+    trigger_flag: Arc<AtomicBool>,
+    control: Arc<RuntimeControl>,
+    sync_markers: SyncMarkers,
+    sync_requests: Receiver<SyncRequest>,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -31,10 +80,16 @@ impl RecordingPipeline {
     pub fn new(config: RecorderConfig) -> (Self, Arc<RecorderMetrics>) {
         let metrics = RecorderMetrics::new();
         let stop_flag = Arc::new(AtomicBool::new(false));
+        let control = RuntimeControl::new(Duration::from_secs(config.stats_interval_secs));
+        let (sync_markers, sync_requests) = SyncMarkers::new();
         let p = Self {
             config,
             metrics: metrics.clone(),
             stop_flag,
+            trigger_flag: Arc::new(AtomicBool::new(false)),
+            control,
+            sync_markers,
+            sync_requests,
         };
 
         (p, metrics)
@@ -45,12 +100,30 @@ impl RecordingPipeline {
         self.stop_flag.clone()
     }
 
+    /// Флаг триггера предзаписи (`prebuffer`). Установка в `true` сохраняет
+    /// накопленное окно и продолжает запись дальше; без предзаписи не нужен.
+    pub fn trigger_flag(&self) -> Arc<AtomicBool> {
+        self.trigger_flag.clone()
+    }
+
+    /// Метки синхронизации с другими датчиками, вставляемые во время
+    /// записи.
+    pub fn sync_markers(&self) -> SyncMarkers {
+        self.sync_markers.clone()
+    }
+
+    /// Интервал статистики и уровень логов, меняемые во время записи.
+    pub fn runtime_control(&self) -> Arc<RuntimeControl> {
+        self.control.clone()
+    }
+
     /// Запускает запись. Блокируется до завершения.
     pub fn run(
         self,
         mut device: Box<dyn SdrDevice>,
     ) -> RecorderResult<()> {
         let info = device.info();
+        let session_start = Instant::now();
 
         info!(
             "Starting recording: {} @ {} Hz, center={} Hz, gain={} dB",
@@ -62,10 +135,46 @@ impl RecordingPipeline {
             self.config.output_path, self.config.duration_secs
         );
 
-        let (tx, rx) = crossbeam_channel::bounded::<IqChunk>(self.config.ring_capacity);
+        let ring_capacity = self.config.effective_ring_capacity();
+        if let Some(tune) = &self.config.ring_autotune {
+            info!(
+                "Ring auto-tune: {ring_capacity} chunks, measuring peak occupancy for {:.0}s",
+                tune.window.as_secs_f64()
+            );
+        }
+
+        // Поправку меряем до захвата: PPS занимает несколько секунд, за
+        // которые переполнился бы ring buffer
+        let clock_offset = self.measure_clock_offset();
+
+        // Датчики берём до того, как устройство уйдёт в поток захвата
+        let telemetry = match (&self.config.telemetry, device.telemetry()) {
+            (Some(tc), Some(source)) => Some(TelemetryMonitor::new(
+                tc.clone(),
+                source,
+                self.metrics.clone(),
+            )),
+            (Some(_), None) => {
+                info!("Device telemetry: {} has no sensors", info.name);
+                None
+            }
+            (None, _) => None,
+        };
+
+        // Опрос датчиков; завершается, когда сброшен `telemetry_stop`
+        let (telemetry_stop, telemetry_stop_rx) = crossbeam_channel::bounded::<()>(0);
+        let telemetry_handle = match telemetry {
+            Some(mut monitor) => Some(
+                std::thread::Builder::new()
+                    .name("glos-telemetry".into())
+                    .spawn(move || monitor.run(&telemetry_stop_rx))?,
+            ),
+            None => None,
+        };
+
+        let (tx, rx) = crossbeam_channel::bounded::<IqChunk>(ring_capacity);
         let stop_flag = self.stop_flag.clone();
         let stop_flag_capture = stop_flag.clone();
-        let metrics_capture = self.metrics.clone();
 
         // Захват потока
         let capture_handle = std::thread::spawn(move || {
@@ -77,331 +186,2590 @@ impl RecordingPipeline {
         });
 
         // Цикл записи (текущий поток)
-        let writer_result = self.writer_loop(rx);
+        let writer_result = self.writer_loop(rx, clock_offset);
+
+        drop(telemetry_stop);
+        if let Some(h) = telemetry_handle {
+            let _ = h.join();
+        }
 
         // Сигнализируем потоку захвата остановиться
         stop_flag.store(true, Ordering::Relaxed);
 
         // Дожидаемся завершения потока захвата
+        let mut capture_error = None;
         match capture_handle.join() {
             Ok(Ok(hal_stats)) => {
+                // Потерянные выборки уже посчитаны по разрывам номеров в
+                // потоке записи
                 if hal_stats.chunks_dropped > 0 {
-                    metrics_capture
-                        .dropped_samples
-                        .fetch_add(hal_stats.chunks_dropped, Ordering::Relaxed);
                     warn!(
                         "Capture: {} chunks dropped (ring buffer overflow)",
                         hal_stats.chunks_dropped
                     );
                 }
             }
-            Ok(Err(e)) => warn!("Capture thread finished with error: {e}"),
-            Err(_) => warn!("Capture thread panicked"),
+            Ok(Err(e)) => {
+                warn!("Capture thread finished with error: {e}");
+                capture_error = Some(e.to_string());
+            }
+            Err(_) => {
+                warn!("Capture thread panicked");
+                capture_error = Some("capture thread panicked".to_string());
+            }
         }
 
+        self.notify_outcome(&writer_result, capture_error.as_deref(), &session_start);
+
         writer_result
     }
 
     fn writer_loop(
         &self,
         rx: crossbeam_channel::Receiver<IqChunk>,
+        clock_offset: Option<ClockOffset>,
     ) -> RecorderResult<()> {
         let cfg = &self.config;
         let metrics = &self.metrics;
 
-        // Открываем файл и создаём GlosWriter
-        let file = File::create(&cfg.output_path)?;
-        let mut header = GlosHeader::new(cfg.sdr_type(), cfg.sample_rate_hz, cfg.center_freq_hz);
+        // Конфигурация проверяется до ожидания триггера, иначе ошибка
+        // всплыла бы только после него
+        if cfg.rotation.is_some() && cfg.mirror_path.is_some() {
+            return Err(RecorderError::Pipeline(
+                "rotation cannot be combined with mirror".to_string(),
+            ));
+        }
+
+        // Предзапись: до триггера в памяти только последнее окно, файл не
+        // создаётся
+        let (mut pending, prebuffer_start_ns) = match cfg.prebuffer {
+            Some(window) => match self.wait_for_trigger(&rx, window, clock_offset) {
+                Some((chunks, start_ns)) => (chunks, Some(start_ns)),
+                None => return Ok(()),
+            },
+            None => (VecDeque::new(), None),
+        };
+
+        // Открываем файл (или член архива) и создаём GlosWriter
+        let mut archive = match &cfg.archive_path {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .create(true)
+                    .truncate(false)
+                    .open(path)?;
+                Some(GlosArchiveWriter::open(file)?)
+            }
+            None => None,
+        };
+        let session_start_unix_ns = prebuffer_start_ns
+            .or(cfg.start_time_ns)
+            .unwrap_or_else(|| disciplined_unix_ns(&*cfg.clock, clock_offset));
+        let mut header = cfg.glos_header(session_start_unix_ns);
+        if let Some(offset) = clock_offset {
+            header.time_source = offset.source;
+            header.clock_offset_ns = offset.offset_ns;
+        }
+
+        let mut writer = match (archive.as_mut(), &cfg.rotation) {
+            (Some(_), Some(rotation)) => {
+                info!("Archive: {:?}, rotating members", cfg.archive_path);
+                let archive = archive.take().expect("archive is opened above");
+                RecordingWriter::Rotating(Box::new(RotatingWriter::create_in_archive(
+                    archive,
+                    &cfg.output_path,
+                    header.clone(),
+                    cfg.compression_options,
+                    rotation.clone(),
+                )?))
+            }
+            (Some(a), None) => {
+                let name = cfg.archive_member_name();
+                info!("Archive: {:?}, member {name:?}", cfg.archive_path);
+                let output = RecordingOutput::Archive(a.begin_member(&name)?);
+                RecordingWriter::Single(
+                    GlosWriter::new(output, header.clone())?
+                        .with_compression(cfg.compression_options)?,
+                )
+            }
+            (None, Some(rotation)) => RecordingWriter::Rotating(Box::new(RotatingWriter::create(
+                &cfg.output_path,
+                header.clone(),
+                cfg.compression_options,
+                rotation.clone(),
+            )?)),
+            (None, None) => {
+                let output = RecordingOutput::File(File::create(&cfg.output_path)?);
+                RecordingWriter::Single(
+                    GlosWriter::new(output, header.clone())?
+                        .with_compression(cfg.compression_options)?,
+                )
+            }
+        };
+        if let Some(interval) = cfg.checkpoint_interval {
+            writer = writer.with_checkpoint_interval(interval);
+        }
+        if let Some(notifier) = &cfg.notify {
+            writer = writer.with_rotation_notifier(notifier.clone(), metrics.clone());
+        }
 
-        header.gain_db = cfg.gain_db;
-        header.iq_format = cfg.iq_format;
-        header.compression = cfg.compression;
+        // Зеркало не обязательно: если его не удалось открыть, пишем только
+        // основной файл
+        let mut mirror = match &cfg.mirror_path {
+            Some(path) => match MirrorWriter::create(path, header.clone()) {
+                Ok(m) => {
+                    info!("Mirror: {path:?}");
+                    Some(m)
+                }
+                Err(e) => {
+                    warn!("Cannot open mirror {path:?}: {e}. Recording without mirror");
+                    None
+                }
+            },
+            None => None,
+        };
 
-        let mut writer = GlosWriter::new(file, header)?;
+        // Ветви, как и зеркало, не обязательны
+        let branches: Vec<BranchWriter> = cfg
+            .branches
+            .iter()
+            .filter_map(|b| {
+                match BranchWriter::create(
+                    b,
+                    &header,
+                    cfg.compression_options,
+                    cfg.checkpoint_interval,
+                ) {
+                    Ok(w) => {
+                        info!(
+                            "Branch: {:?} ({}, {} Hz, shift {:+} Hz)",
+                            b.output_path,
+                            b.iq_format,
+                            b.sample_rate_hz.unwrap_or(cfg.sample_rate_hz),
+                            b.freq_shift_hz
+                        );
+                        Some(w)
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Cannot open branch {:?}: {e}. Recording without it",
+                            b.output_path
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let mut advisor = cfg
+            .format_advice
+            .then(|| FormatAdvisor::new(cfg.iq_format, header.samples_little_endian()));
+        let mut thumbnail = cfg
+            .thumbnail
+            .then(|| ThumbnailBuilder::new(cfg.iq_format, header.samples_little_endian()));
+        let mut ring_tuner = cfg.ring_autotune.as_ref().map(|t| RingTuner::new(t.window));
+        let mut drift = cfg
+            .clock_drift
+            .as_ref()
+            .map(|d| DriftEstimator::new(cfg.sample_rate_hz, d));
+
+        let mut stats_exporter = match cfg.stats_export {
+            Some(format) => {
+                let path = StatsExporter::sidecar_path(&cfg.output_path, format);
+                let exporter = StatsExporter::create(&path, format)?;
+                info!("Stats export: {path:?}");
+                Some(exporter)
+            }
+            None => None,
+        };
 
         let sample_size = cfg.iq_format.sample_size();
         let block_samples = cfg.block_samples;
         let recv_timeout = Duration::from_millis(100);
-        let stats_interval = Duration::from_secs(cfg.stats_interval_secs);
 
-        let session_start_unix_ns = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
+        let clock = &cfg.clock;
+        let session_start = Instant::now();
+        let clock_start = clock.now();
+        // Буферы блоков ходят по кругу: накопление → очередь → писатель →
+        // снова накопление
+        let buffers = BlockBuffers::new(block_samples as usize * sample_size);
+        let queue =
+            WriteQueue::new(cfg.write_queue.clone(), metrics.clone()).with_buffers(buffers.clone());
+        // Сжатие в нескольких потоках до очереди: блоки приходят к писателю
+        // уже сжатыми и пишутся как есть
+        let mut compress = match (cfg.compression, cfg.compress_threads) {
+            (Compression::None, _) | (_, 0) => None,
+            (_, threads) => Some(CompressPool::new(
+                BlockCompressor::new(&header, cfg.compression_options)?,
+                threads,
+            )?),
+        };
 
-        let mut global_sample_index: u64 = 0;
+        // Блоки пишет отдельный поток: пока очередь не упёрлась в лимит,
+        // задержка диска или сжатия не останавливает приём chunk'ов
+        let recording_path = cfg.archive_path.as_ref().unwrap_or(&cfg.output_path);
+
+        let (stream_span, disk_health, precompressed_stored, sync_points) = std::thread::scope(
+            |s| -> RecorderResult<_> {
+                std::thread::Builder::new()
+                    .name("glos-writer".into())
+                    .spawn_scoped(s, || queue.drain_into(&mut writer))?;
+
+                // Закрываем очередь при любом выходе, иначе scope ждёт писателя
+                let _close = queue.close_on_drop();
+
+                // Опрос диска (SMART может занимать сотни мс) — в своём потоке;
+                // он завершается, когда сброшен `health_stop`
+                let (health_stop, health_stop_rx) = crossbeam_channel::bounded::<()>(0);
+                let disk_health = match &cfg.disk_health {
+                    Some(hc) => {
+                        let monitor =
+                            DiskHealthMonitor::new(hc.clone(), recording_path, metrics.clone());
+                        let handle = std::thread::Builder::new()
+                            .name("glos-disk-health".into())
+                            .spawn_scoped(s, move || {
+                                let mut monitor = monitor;
+                                monitor.run(&health_stop_rx);
+                                monitor
+                            })?;
+                        Some(handle)
+                    }
+                    None => None,
+                };
+
+                let mut samples_received: u64 = 0;
+
+                // Номера выборок устройства: первой в записи и первой в `acc`.
+                // Метка блока считается от них, а не по числу записанных выборок,
+                // поэтому потерянные chunk'и не сдвигают время следующих блоков
+                let mut stream_base: Option<u64> = None;
+                let mut acc_first: u64 = 0;
+
+                // Накопитель частичного блока
+                let mut acc: Vec<u8> = buffers.take();
+                let mut acc_samples: u32 = 0;
+                let mut last_stats = clock_start;
+                let mut last_drift_log = clock_start;
+                let mut last_timestamp_ns = 0u64;
+                let mut disk_full_reported = false;
+                let mut events = BlockEvents::default();
+                let mut sync_points: Vec<SyncPoint> = Vec::new();
+
+                // `offset_ns` — смещение блока от начала записи, см.
+                // `stream_offset_ns`
+                // Дайджесты блоков, ушедших на сжатие: пул отдаёт блоки в том
+                // же порядке
+                let mut pending_digests: VecDeque<Option<u64>> = VecDeque::new();
+                // Дайджест копящегося блока по данным chunk'ов при приёме
+                let mut acc_digest = cfg.write_queue.verify_digests.then(AccDigest::new);
+
+                let mut emit_block = |data: Vec<u8>,
+                                      n_samples: u32,
+                                      offset_ns: u64,
+                                      attributes: BlockAttributes,
+                                      digest: Option<u64>| {
+                    if let Some(t) = thumbnail.as_mut() {
+                        t.push(&data);
+                    }
 
-        // Накопитель частичного блока
-        let mut acc: Vec<u8> = Vec::with_capacity(block_samples as usize * sample_size);
-        let mut acc_samples: u32 = 0;
-        let session_start = Instant::now();
-        let mut last_stats = Instant::now();
+                    // С коррекцией дрейфа уточнение частоты может сдвинуть
+                    // смещение назад — метки блоков не должны убывать
+                    let timestamp_ns = (session_start_unix_ns + offset_ns).max(last_timestamp_ns);
+                    last_timestamp_ns = timestamp_ns;
+                    let mut block = IqBlock::new(timestamp_ns, n_samples, data);
+                    block.attributes = attributes;
+                    let block_bytes = block.frame_size();
 
-        loop {
-            //  Проверяем ограничение по времени
-            if let Some(dur) = cfg.duration_secs {
-                if session_start.elapsed().as_secs() >= dur {
-                    info!("Duration limit reached ({dur}s). Finalizing...");
-                    break;
-                }
-            }
+                    debug!("Block @ {timestamp_ns} ns: {n_samples} samples, {block_bytes} bytes");
 
-            //  Проверяем внешний stop_flag (Ctrl+C)
-            if self.stop_flag.load(Ordering::Relaxed) {
-                info!("Stop signal received. Finalizing...");
-                break;
-            }
+                    if let Some(m) = &mirror {
+                        m.write_block(&block);
+                    }
+                    for b in &branches {
+                        b.write_block(&block);
+                    }
 
-            //  Получаем следующий chunk
-            let chunk = match rx.recv_timeout(recv_timeout) {
-                Ok(c) => c,
-                Err(RecvTimeoutError::Timeout) => continue,
-                Err(RecvTimeoutError::Disconnected) => {
-                    info!("Capture channel closed. Flushing...");
-                    break;
-                }
-            };
+                    match compress.as_mut() {
+                        Some(pool) => {
+                            pending_digests.push_back(digest);
+                            pool.submit(block, |b| {
+                                queue.push(b, pending_digests.pop_front().flatten())
+                            })
+                        }
+                        None => queue.push(block, digest),
+                    }
+
+                    timestamp_ns
+                };
+
+                loop {
+                    // Метки синхронизации встают за последней принятой
+                    // выборкой: копившийся блок закрывается раньше
+                    let requests: Vec<SyncRequest> = self.sync_requests.try_iter().collect();
+                    if !requests.is_empty() {
+                        let base = stream_base.unwrap_or(acc_first);
+                        if acc_samples > 0 {
+                            emit_block(
+                                std::mem::replace(&mut acc, buffers.take()),
+                                acc_samples,
+                                stream_offset_ns(
+                                    acc_first - base,
+                                    cfg.sample_rate_hz,
+                                    drift.as_ref(),
+                                ),
+                                events.take(acc_first, acc_samples),
+                                acc_digest.as_mut().map(AccDigest::take),
+                            );
+                            acc_first += acc_samples as u64;
+                            acc_samples = 0;
+                        }
+
+                        for (id, external_ns) in requests {
+                            if id > GLOS_SYNC_MARKER_ID_MAX {
+                                warn!("Sync marker #{id} skipped: marker ids exhausted");
+                                continue;
+                            }
+                            let marker = SyncMarker {
+                                id,
+                                external_ns: external_ns
+                                    .unwrap_or_else(|| disciplined_unix_ns(&**clock, clock_offset)),
+                            };
+                            let sample_index = acc_first - base;
+                            let timestamp_ns = emit_block(
+                                Vec::new(),
+                                0,
+                                stream_offset_ns(sample_index, cfg.sample_rate_hz, drift.as_ref()),
+                                BlockAttributes {
+                                    sync_marker: Some(marker),
+                                    ..Default::default()
+                                },
+                                None,
+                            );
+
+                            info!(
+                                "Sync marker #{id} at sample {sample_index} ({timestamp_ns} ns), external {} ns",
+                                marker.external_ns
+                            );
+                            sync_points.push(SyncPoint {
+                                id,
+                                external_ns: marker.external_ns,
+                                timestamp_ns,
+                                sample_index,
+                            });
+                        }
+                    }
 
-            // Обновляем счётчик выборок
-            metrics
-                .samples_recorded
-                .fetch_add(chunk.sample_count as u64, Ordering::Relaxed);
+                    //  Проверяем ограничение по времени
+                    if let Some(dur) = cfg.duration_secs {
+                        if clock.now().saturating_sub(clock_start).as_secs() >= dur {
+                            info!("Duration limit reached ({dur}s). Finalizing...");
+                            break;
+                        }
+                    }
 
-            acc.extend_from_slice(&chunk.data);
-            acc_samples += chunk.sample_count;
+                    //  Проверяем ограничение по числу выборок
+                    if let Some(limit) = cfg.sample_limit {
+                        if samples_received >= limit {
+                            info!("Sample limit reached ({limit} samples). Finalizing...");
+                            break;
+                        }
+                    }
 
-            // Пишем полные блоки
-            while acc_samples >= block_samples {
-                let n_bytes = block_samples as usize * sample_size;
-                let block_data: Vec<u8> = acc.drain(..n_bytes).collect();
+                    //  Проверяем внешний stop_flag (Ctrl+C)
+                    if self.stop_flag.load(Ordering::Relaxed) {
+                        info!("Stop signal received. Finalizing...");
+                        break;
+                    }
 
-                let block_first_sample_index = global_sample_index;
+                    //  Получаем следующий chunk: сначала окно предзаписи
+                    let mut chunk = match pending.pop_front() {
+                        Some(c) => c,
+                        None => match rx.recv_timeout(recv_timeout) {
+                            Ok(c) => {
+                                // Занятость вместе с только что принятым chunk'ом
+                                let occupancy = rx.len() + 1;
+                                metrics
+                                    .ring_peak_chunks
+                                    .fetch_max(occupancy as u64, Ordering::Relaxed);
+
+                                if let Some(d) = drift.as_mut() {
+                                    d.observe(
+                                        c.first_sample + c.sample_count as u64,
+                                        clock.now().saturating_sub(clock_start),
+                                    );
+                                }
+
+                                if let Some(t) = ring_tuner.as_mut() {
+                                    let elapsed = clock.now().saturating_sub(clock_start);
+                                    if t.observe(elapsed, occupancy).is_some() {
+                                        self.report_ring_tune(t);
+                                    }
+                                }
+                                c
+                            }
+                            Err(RecvTimeoutError::Timeout) => continue,
+                            Err(RecvTimeoutError::Disconnected) => {
+                                info!("Capture channel closed. Flushing...");
+                                break;
+                            }
+                        },
+                    };
+
+                    // Обрезаем последний chunk до лимита выборок
+                    if let Some(limit) = cfg.sample_limit {
+                        let remaining = limit.saturating_sub(samples_received);
+
+                        if (chunk.sample_count as u64) > remaining {
+                            chunk.sample_count = remaining as u32;
+                            chunk.data.truncate(remaining as usize * sample_size);
+                        }
+                    }
 
-                let timestamp_ns = session_start_unix_ns
-                    + (block_first_sample_index * 1_000_000_000) / cfg.sample_rate_hz as u64;
+                    samples_received += chunk.sample_count as u64;
+
+                    let first_chunk = stream_base.is_none();
+                    let base = *stream_base.get_or_insert(chunk.first_sample);
+                    let expected = acc_first + acc_samples as u64;
+                    if acc_samples == 0 && chunk.first_sample > acc_first {
+                        acc_first = chunk.first_sample;
+                    } else if chunk.first_sample > expected {
+                        // Пропуск в потоке: копившийся блок закрывается раньше,
+                        // следующий начнётся с реальной позиции
+                        debug!(
+                            "Capture gap: {} samples lost before sample {}",
+                            chunk.first_sample - expected,
+                            chunk.first_sample
+                        );
+                        emit_block(
+                            std::mem::replace(&mut acc, buffers.take()),
+                            acc_samples,
+                            stream_offset_ns(acc_first - base, cfg.sample_rate_hz, drift.as_ref()),
+                            events.take(acc_first, acc_samples),
+                            acc_digest.as_mut().map(AccDigest::take),
+                        );
+                        acc_samples = 0;
+                        acc_first = chunk.first_sample;
+                    }
+                    if !first_chunk && chunk.first_sample > expected {
+                        // Потеря попадает в атрибуты следующего блока: по ним
+                        // читатель восстанавливает, где и сколько пропало
+                        let lost = chunk.first_sample - expected;
+                        events.dropped(lost);
+                        metrics.dropped_samples.fetch_add(lost, Ordering::Relaxed);
+                    }
+                    events.push(&chunk);
+
+                    // Обновляем счётчик выборок
+                    metrics
+                        .samples_recorded
+                        .fetch_add(chunk.sample_count as u64, Ordering::Relaxed);
+
+                    if !cfg.iq_correction.is_identity() {
+                        cfg.iq_correction.apply(
+                            &mut chunk.data,
+                            cfg.iq_format,
+                            cfg.samples_little_endian,
+                        )?;
+                    }
 
-                let block = IqBlock::new(timestamp_ns, block_samples, block_data);
-                let block_bytes = block_samples as u64 * sample_size as u64 + 20;
+                    if let Some(a) = advisor.as_mut() {
+                        a.push(&chunk.data);
+                    }
 
-                match writer.write_block(block) {
-                    Ok(()) => {
-                        metrics.blocks_written.fetch_add(1, Ordering::Relaxed);
-                        metrics
-                            .bytes_written
-                            .fetch_add(block_bytes, Ordering::Relaxed);
+                    // Выборки копируются прямо в буфер блока, не дальше его
+                    // конца: полный буфер уходит писателю целиком, остаток
+                    // chunk'а начинает следующий
+                    let mut data = chunk.data.as_slice();
+                    while !data.is_empty() {
+                        let n_bytes = match &cfg.chunking {
+                            Some(_) => data.len(),
+                            None => {
+                                (block_samples as usize * sample_size - acc.len()).min(data.len())
+                            }
+                        };
+                        let piece = &data[..n_bytes];
+                        acc.extend_from_slice(piece);
+                        acc_samples += (n_bytes / sample_size) as u32;
+                        data = &data[n_bytes..];
+
+                        // Пишем полные блоки
+                        loop {
+                            let n_samples = match &cfg.chunking {
+                                Some(chunker) => match chunker.next_boundary(&acc, sample_size) {
+                                    Some(n) => n,
+                                    None => break,
+                                },
+                                None if acc_samples >= block_samples => block_samples,
+                                None => break,
+                            };
+
+                            let n_bytes = n_samples as usize * sample_size;
+                            let digest = acc_digest.as_mut().map(|d| {
+                                d.feed(piece, acc.len(), n_bytes);
+                                d.take()
+                            });
+                            emit_block(
+                                split_block(&mut acc, n_bytes, &buffers),
+                                n_samples,
+                                stream_offset_ns(
+                                    acc_first - base,
+                                    cfg.sample_rate_hz,
+                                    drift.as_ref(),
+                                ),
+                                events.take(acc_first, n_samples),
+                                digest,
+                            );
+
+                            acc_samples -= n_samples;
+                            acc_first += n_samples as u64;
+                        }
+
+                        // Остаток куска — в дайджест следующего блока, пока
+                        // chunk ещё не отпущен
+                        if let Some(d) = acc_digest.as_mut() {
+                            d.feed(piece, acc.len(), acc.len());
+                        }
                     }
-                    Err(e) => {
-                        metrics.write_errors.fetch_add(1, Ordering::Relaxed);
-                        warn!("Write error: {e}");
+
+                    if let (Some(d), Some(dc)) = (&drift, &cfg.clock_drift) {
+                        if clock.now().saturating_sub(last_drift_log) >= dc.log_interval {
+                            if let Some(e) = d.estimate() {
+                                info!(
+                                    "Clock drift: {:+.2} ppm over {:.0}s",
+                                    e.ppm, e.observed_secs
+                                );
+                            }
+                            last_drift_log = clock.now();
+                        }
+                    }
+
+                    // Периодически выводим статистику
+                    if clock.now().saturating_sub(last_stats) >= self.control.stats_interval() {
+                        self.log_progress(&session_start);
+                        self.export_stats(&mut stats_exporter, &session_start);
+                        last_stats = clock.now();
+                    }
+
+                    // Диск заполнен: запись продолжается (место может
+                    // освободиться), но станция должна узнать сразу
+                    if !disk_full_reported && metrics.disk_full.load(Ordering::Relaxed) {
+                        disk_full_reported = true;
+                        warn!("Disk full: blocks are not being written");
+                        self.notify_in_background(
+                            NotifyEvent::DiskFull,
+                            "Disk full: blocks are not being written".to_string(),
+                            &session_start,
+                        );
                     }
                 }
 
-                acc_samples -= block_samples;
-                global_sample_index += block_samples as u64;
+                // Flush частичного блока (если есть)
+                let base = stream_base.unwrap_or(acc_first);
+                if acc_samples > 0 {
+                    emit_block(
+                        acc,
+                        acc_samples,
+                        stream_offset_ns(acc_first - base, cfg.sample_rate_hz, drift.as_ref()),
+                        events.take(acc_first, acc_samples),
+                        acc_digest.as_mut().map(AccDigest::take),
+                    );
+                    info!("Flushed partial block ({acc_samples} samples)");
+                }
+
+                let precompressed_stored = compress.map_or(0, |pool| {
+                    pool.finish(|b| queue.push(b, pending_digests.pop_front().flatten()))
+                });
+
+                // Длительность записи в выборках, включая пропуски
+                let stream_span = acc_first + acc_samples as u64 - base;
+
+                drop(health_stop);
+                let disk_health = disk_health.and_then(|h| h.join().ok());
+
+                Ok((stream_span, disk_health, precompressed_stored, sync_points))
+            },
+        )?;
+
+        // Запись короче окна замера: рекомендация только в лог
+        if let Some(t) = ring_tuner.as_ref().filter(|t| !t.is_reported()) {
+            self.report_ring_tune(t);
+        }
+
+        // Блоки, сжатые до очереди, писатель не считает
+        let stored_blocks = writer.stored_blocks() + precompressed_stored;
+        if stored_blocks > 0 {
+            info!(
+                "Compression: {stored_blocks} of {} blocks stored uncompressed",
+                writer.block_count()
+            );
+        }
+
+        let spilled = metrics.spilled_blocks.load(Ordering::Relaxed);
+        let stalls = metrics.queue_stalls.load(Ordering::Relaxed);
+        if spilled > 0 || stalls > 0 {
+            warn!(
+                "Write queue: disk fell behind (peak {:.1} MB in RAM, {spilled} blocks spilled, {stalls} stalls)",
+                metrics.queue_peak_bytes.load(Ordering::Relaxed) as f64 / 1e6
+            );
+        }
+
+        // Finalize: перезаписываем заголовок с total_samples
+        let timestamp_end = match cfg.start_time_ns {
+            // Время окончания — по числу выборок, а не по часам
+            Some(ns) => {
+                let end_ns = ns + stream_offset_ns(stream_span, cfg.sample_rate_hz, drift.as_ref());
+                end_ns / 1_000_000_000
             }
+            None => disciplined_unix_ns(&**clock, clock_offset) / 1_000_000_000,
+        };
 
-            // Периодически выводим статистику
-            if last_stats.elapsed() >= stats_interval {
-                self.log_progress(&session_start);
-                last_stats = Instant::now();
+        let segments = match &writer {
+            RecordingWriter::Rotating(w) => Some(w.segments().to_vec()),
+            RecordingWriter::Single(_) => None,
+        };
+
+        // Зеркало финализируем даже при ошибке основного файла: оно может
+        // оказаться единственной целой копией
+        let primary = writer.finish_at(timestamp_end);
+        if let Some(m) = mirror.take() {
+            self.finish_mirror(m, timestamp_end, primary.is_ok());
+        }
+        for b in branches {
+            let path = b.path().to_path_buf();
+            let report = b.finish(timestamp_end);
+
+            match report.error {
+                Some(e) => warn!(
+                    "Branch {path:?} is incomplete ({} blocks written): {e}",
+                    report.blocks_written
+                ),
+                None => info!(
+                    "Branch finalized: {path:?} ({} samples in {} blocks)",
+                    report.samples_written, report.blocks_written
+                ),
             }
         }
+        primary?;
 
-        // Flush частичного блока (если есть)
-        if acc_samples > 0 {
-            let block_first_sample_index = global_sample_index;
+        // При ротации архив финализирует RotatingWriter
+        if let Some(a) = archive {
+            a.finish()?;
+        }
 
-            let timestamp_ns = session_start_unix_ns
-                + (block_first_sample_index * 1_000_000_000) / cfg.sample_rate_hz as u64;
+        if let Some(mut m) = disk_health {
+            m.finish(recording_path);
+        }
 
-            let block = IqBlock::new(timestamp_ns, acc_samples, acc);
+        let clock_drift = drift.as_ref().and_then(DriftEstimator::estimate);
+        match clock_drift {
+            Some(e) => info!(
+                "Clock drift: {:+.2} ppm over {:.0}s{}",
+                e.ppm,
+                e.observed_secs,
+                if e.timestamps_corrected {
+                    ", block timestamps corrected"
+                } else {
+                    ""
+                }
+            ),
+            None if drift.is_some() => info!("Clock drift: recording too short to estimate"),
+            None => {}
+        }
 
-            if let Err(e) = writer.write_block(block) {
-                warn!("Failed to write final partial block: {e}");
-                metrics.write_errors.fetch_add(1, Ordering::Relaxed);
-            } else {
-                metrics.blocks_written.fetch_add(1, Ordering::Relaxed);
-                info!("Flushed partial block ({acc_samples} samples)");
-            }
+        let meta = RecordingMetadata {
+            rf_chain: cfg.rf_chain.clone(),
+            thumbnail: thumbnail.as_ref().and_then(ThumbnailBuilder::finish),
+            session_log: cfg
+                .session_log
+                .as_ref()
+                .map(SessionLog::take)
+                .unwrap_or_default(),
+            clock_drift,
+            sync_markers: sync_points,
+            ..Default::default()
+        };
+        if !meta.is_empty() {
+            self.save_metadata(&meta);
         }
 
-        // Finalize: перезаписываем заголовок с total_samples
-        writer.finish()?;
+        // Финальный snapshot, чтобы ряд заканчивался итоговыми значениями
+        self.export_stats(&mut stats_exporter, &session_start);
+
+        match (&cfg.archive_path, &segments) {
+            (Some(path), _) => info!("Archive finalized: {path:?}"),
+            (None, Some(s)) => info!(
+                "Files finalized: {} segments, {:?} … {:?}",
+                s.len(),
+                s.first().unwrap_or(&cfg.output_path),
+                s.last().unwrap_or(&cfg.output_path)
+            ),
+            (None, None) => info!("File finalized: {:?}", cfg.output_path),
+        }
 
-        info!("File finalized: {:?}", cfg.output_path);
+        if let Some(a) = &advisor {
+            let advice = a.advice();
+            if advice.is_beneficial() {
+                warn!(
+                    "Format advice: {advice}. Use --format {}",
+                    advice.recommended
+                );
+            } else {
+                info!("Format advice: {advice}");
+            }
+        }
 
         Ok(())
     }
 
-    fn log_progress(
+    /// Уведомление об итоге сессии: завершена, сбой устройства, диск
+    /// заполнен или другая ошибка.
+    fn notify_outcome(
         &self,
-        start: &Instant,
+        result: &RecorderResult<()>,
+        capture_error: Option<&str>,
+        session_start: &Instant,
     ) {
-        let m = &self.metrics;
+        let Some(notifier) = &self.config.notify else {
+            return;
+        };
 
-        info!(
-            "[ {:.0}s ] samples={} blocks={} dropped={} ({:.2}%) speed={:.1}MB/s",
-            start.elapsed().as_secs_f64(),
-            m.samples_recorded.load(Ordering::Relaxed),
-            m.blocks_written.load(Ordering::Relaxed),
-            m.dropped_samples.load(Ordering::Relaxed),
-            m.drop_rate_pct(),
-            m.write_speed_mbps(start),
-        );
+        let (event, message) = match (result, capture_error) {
+            (Err(e), _) if e.is_disk_full() => {
+                (NotifyEvent::DiskFull, format!("Recording failed: {e}"))
+            }
+            (Err(e), _) => (NotifyEvent::Failed, format!("Recording failed: {e}")),
+            (Ok(()), Some(e)) => (
+                NotifyEvent::DeviceFailure,
+                format!("Capture stopped by device error: {e}"),
+            ),
+            (Ok(()), None) => (NotifyEvent::Completed, "Recording complete".to_string()),
+        };
+
+        notifier.send(&self.notification(event, message, session_start));
     }
-}
 
-////////////////////////////////////////////////////////////////////////////////
-// Тесты
-////////////////////////////////////////////////////////////////////////////////
+    /// Уведомление посреди записи: отправляется из отдельного потока, чтобы
+    /// ожидание сервера не задерживало приём chunk'ов.
+    fn notify_in_background(
+        &self,
+        event: NotifyEvent,
+        message: String,
+        session_start: &Instant,
+    ) {
+        if let Some(notifier) = self.config.notify.clone() {
+            let notification = self.notification(event, message, session_start);
+            std::thread::spawn(move || notifier.send(&notification));
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use std::path::PathBuf;
+    fn notification(
+        &self,
+        event: NotifyEvent,
+        message: String,
+        session_start: &Instant,
+    ) -> Notification {
+        let cfg = &self.config;
 
-    use glos_core::{read_all_blocks, GlosReader};
-    use glos_hal::{DeviceKind, SimulatedDevice};
-    use glos_types::{Compression, IqFormat};
-    use tempfile::NamedTempFile;
+        Notification {
+            event,
+            recording: cfg
+                .archive_path
+                .as_ref()
+                .unwrap_or(&cfg.output_path)
+                .clone(),
+            message,
+            summary: self.metrics.summary(session_start),
+        }
+    }
 
-    use super::*;
+    /// Измеряет поправку часов по `time_sync`. При сбое запись идёт по
+    /// часам хоста (`TimeSource::Host` в заголовке) с предупреждением.
+    fn measure_clock_offset(&self) -> Option<ClockOffset> {
+        let sync = self.config.time_sync.as_ref()?;
+        if self.config.start_time_ns.is_some() {
+            info!(
+                "Time sync: start time is fixed, {} offset not applied",
+                sync.source()
+            );
+            return None;
+        }
 
-    fn test_config(path: PathBuf) -> RecorderConfig {
-        RecorderConfig {
-            device: DeviceKind::Simulated,
-            center_freq_hz: 1_602_000_000,
-            sample_rate_hz: 2_000_000,
-            gain_db: 40.0,
-            iq_format: IqFormat::Int16,
-            compression: Compression::None,
-            output_path: path,
-            duration_secs: Some(1), // 1 секунда -> завершается сам
-            block_samples: 10_000,
-            ring_capacity: 32,
-            stats_interval_secs: 60, // не выводим stats в тестах
+        match sync.estimate(&*self.config.clock) {
+            Ok(offset) => {
+                info!(
+                    "Time sync ({}): clock offset {:+.3} ms ± {:.3} ms",
+                    offset.source,
+                    offset.offset_ns as f64 / 1e6,
+                    offset.uncertainty_ns as f64 / 1e6
+                );
+                Some(offset)
+            }
+            Err(e) => {
+                warn!(
+                    "Time sync ({}) failed, using host clock: {e}",
+                    sync.source()
+                );
+                None
+            }
         }
     }
 
-    #[test]
-    fn test_pipeline_writes_valid_glos() {
-        let tmp = NamedTempFile::new().unwrap();
-        let path = tmp.path().to_path_buf();
+    /// Копит последние `window` сигнала до триггера. Возвращает окно и
+    /// Unix-время (нс) его первой выборки; `None` — остановка раньше триггера.
+    fn wait_for_trigger(
+        &self,
+        rx: &crossbeam_channel::Receiver<IqChunk>,
+        window: Duration,
+        clock_offset: Option<ClockOffset>,
+    ) -> Option<(VecDeque<IqChunk>, u64)> {
+        let cfg = &self.config;
+        let armed_unix_ns = cfg
+            .start_time_ns
+            .unwrap_or_else(|| disciplined_unix_ns(&*cfg.clock, clock_offset));
+        let mut buffer = PreBuffer::new(window, cfg.sample_rate_hz, cfg.iq_format.sample_size());
 
-        let config = test_config(path.clone());
-        let sample_rate = config.sample_rate_hz;
-        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        info!(
+            "Pre-buffer armed: keeping last {:.1}s in RAM, waiting for trigger...",
+            window.as_secs_f64()
+        );
 
-        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
-        pipeline.run(device).unwrap();
+        while !self.trigger_flag.load(Ordering::Relaxed) {
+            if self.stop_flag.load(Ordering::Relaxed) {
+                info!("Stop signal received before trigger. Nothing recorded");
+                return None;
+            }
 
-        // Читаем файл и валидируем
-        let file = std::fs::File::open(&path).unwrap();
-        let mut reader = GlosReader::new(file).unwrap();
-        let blocks = read_all_blocks(&mut reader).unwrap();
+            match rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(chunk) => buffer.push(chunk),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    info!("Capture channel closed before trigger. Nothing recorded");
+                    return None;
+                }
+            }
+        }
 
-        assert!(!blocks.is_empty(), "there must be at least 1 block");
-        assert_eq!(reader.stats().blocks_corrupted, 0, "no corrupted blocks");
-        reader.validate_totals().unwrap();
+        let start_ns =
+            armed_unix_ns + samples_to_ns(buffer.discarded_samples(), cfg.sample_rate_hz);
 
-        // каждый блок должен иметь корректное соотношение sample_count / data.len()
-        for b in &blocks {
-            b.validate_sample_count(IqFormat::Int16).unwrap();
-        }
+        info!(
+            "Trigger: saving {:.2}s of pre-buffered signal and recording onwards",
+            buffer.samples() as f64 / cfg.sample_rate_hz as f64
+        );
+
+        Some((buffer.into_chunks(), start_ns))
     }
 
-    #[test]
-    fn test_pipeline_metrics_are_updated() {
-        let tmp = NamedTempFile::new().unwrap();
-        let config = test_config(tmp.path().to_path_buf());
-        let sample_rate = config.sample_rate_hz;
-        let (pipeline, metrics) = RecordingPipeline::new(config);
+    /// Дожидается зеркала и сверяет его с основным файлом.
+    fn finish_mirror(
+        &self,
+        mirror: MirrorWriter,
+        timestamp_end: u64,
+        primary_ok: bool,
+    ) {
+        let path = mirror.path().to_path_buf();
+        let report = mirror.finish(timestamp_end);
+
+        if let Some(e) = report.error {
+            warn!(
+                "Mirror {path:?} is incomplete ({} blocks written): {e}",
+                report.blocks_written
+            );
+            return;
+        }
 
-        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
-        pipeline.run(device).unwrap();
+        if !primary_ok {
+            warn!("Primary file failed to finalize; mirror {path:?} is intact");
+            return;
+        }
 
-        assert!(
-            metrics.samples_recorded.load(Ordering::Relaxed) > 0,
-            "samples_recorded должен быть > 0"
-        );
-        assert!(
-            metrics.blocks_written.load(Ordering::Relaxed) > 0,
-            "blocks_written должен быть > 0"
-        );
-        assert_eq!(metrics.write_errors.load(Ordering::Relaxed), 0);
+        match files_identical(&self.config.output_path, &path) {
+            Ok(true) => info!("Mirror verified: {path:?} matches primary"),
+            Ok(false) => warn!(
+                "Mirror {path:?} differs from primary {:?}",
+                self.config.output_path
+            ),
+            Err(e) => warn!("Cannot verify mirror {path:?}: {e}"),
+        }
     }
 
-    #[test]
-    fn test_pipeline_stop_flag_works() {
-        let tmp = NamedTempFile::new().unwrap();
-        let mut config = test_config(tmp.path().to_path_buf());
-        config.duration_secs = None; // без ограничения по времени
-
-        let sample_rate = config.sample_rate_hz;
-        let (pipeline, _metrics) = RecordingPipeline::new(config);
-        let stop = pipeline.stop_flag();
+    /// Пишет файл метаданных рядом с записью и зеркалом. Ошибка не
+    /// прерывает сессию: IQ уже на диске.
+    fn save_metadata(
+        &self,
+        meta: &RecordingMetadata,
+    ) {
+        let cfg = &self.config;
 
-        // Останавливаем через 200 мс из отдельного потока
-        std::thread::spawn(move || {
-            std::thread::sleep(Duration::from_millis(200));
-            stop.store(true, Ordering::Relaxed);
-        });
+        if cfg.archive_path.is_some() {
+            warn!("Metadata (RF chain, thumbnail, session log) is not stored for archive members");
+            return;
+        }
 
-        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
-        let result = pipeline.run(device);
-        assert!(result.is_ok(), "graceful stop не должен быть ошибкой");
+        for path in std::iter::once(&cfg.output_path).chain(&cfg.mirror_path) {
+            match meta.save(path) {
+                Ok(p) => info!("Metadata: {p:?}"),
+                Err(e) => warn!("Cannot write metadata for {path:?}: {e}"),
+            }
+        }
     }
 
-    #[test]
-    fn test_pipeline_lz4_compression() {
-        let tmp = NamedTempFile::new().unwrap();
-        let mut config = test_config(tmp.path().to_path_buf());
-        config.compression = Compression::Lz4;
+    /// Сообщает рекомендуемую ёмкость кольцевого буфера. Полный замер
+    /// дописывается в историю, если она задана; ошибка истории не прерывает
+    /// запись.
+    fn report_ring_tune(
+        &self,
+        tuner: &RingTuner,
+    ) {
+        let cfg = &self.config;
+        let Some(tune) = &cfg.ring_autotune else {
+            return;
+        };
 
-        let sample_rate = config.sample_rate_hz;
-        let (pipeline, _) = RecordingPipeline::new(config);
+        let capacity = cfg.effective_ring_capacity();
+        let recommended = tuner.recommendation();
+
+        if !tuner.is_reported() {
+            info!(
+                "Ring auto-tune: session ended after {:.0}s of {:.0}s window, peak {} of {capacity} chunks; \
+                 suggested --ring-capacity {recommended} (not saved)",
+                tuner.observed().as_secs_f64(),
+                tune.window.as_secs_f64(),
+                tuner.peak()
+            );
+            return;
+        }
 
-        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        info!(
+            "Ring auto-tune: peak {} of {capacity} chunks over {:.0}s, recommended --ring-capacity {recommended}",
+            tuner.peak(),
+            tuner.observed().as_secs_f64()
+        );
+        if tuner.peak() >= capacity {
+            warn!("Ring auto-tune: ring was full, the real peak may be higher");
+        }
+
+        if let Some(path) = &tune.history {
+            let key = RingHistory::key(&cfg.device, cfg.sample_rate_hz);
+            let saved = RingHistory::load(path).and_then(|mut h| {
+                h.record(&key, recommended);
+                h.save(path)
+            });
+
+            match saved {
+                Ok(()) => info!("Ring auto-tune: saved to {path:?} ({key})"),
+                Err(e) => warn!("Cannot update ring history {path:?}: {e}"),
+            }
+        }
+    }
+
+    fn log_progress(
+        &self,
+        start: &Instant,
+    ) {
+        let m = &self.metrics;
+
+        info!(
+            "[ {:.0}s ] samples={} blocks={} dropped={} ({:.2}%) speed={:.1}MB/s",
+            start.elapsed().as_secs_f64(),
+            m.samples_recorded.load(Ordering::Relaxed),
+            m.blocks_written.load(Ordering::Relaxed),
+            m.dropped_samples.load(Ordering::Relaxed),
+            m.drop_rate_pct(),
+            m.write_speed_mbps(start),
+        );
+    }
+
+    /// Дописывает snapshot метрик в файл статистики. Ошибка экспорта не
+    /// прерывает запись: после первой неудачи экспорт отключается.
+    fn export_stats(
+        &self,
+        exporter: &mut Option<StatsExporter>,
+        start: &Instant,
+    ) {
+        let Some(e) = exporter else {
+            return;
+        };
+
+        if let Err(err) = e.write(&self.metrics.summary(start)) {
+            warn!("Stats export to {:?} failed: {err}. Disabling", e.path());
+            *exporter = None;
+        }
+    }
+}
+
+impl BlockEvents {
+    /// Учитывает события, о которых устройство сообщило вместе с `chunk`.
+    fn push(
+        &mut self,
+        chunk: &IqChunk,
+    ) {
+        let reported = chunk.attributes;
+        if reported.overload {
+            let range = chunk.first_sample..chunk.first_sample + chunk.sample_count as u64;
+            // Подряд идущие перегруженные chunk'и — один участок
+            self.overload = match self.overload.take() {
+                Some(r) if r.end >= range.start => Some(r.start..range.end),
+                _ => Some(range),
+            };
+        }
+
+        self.pending.merge(BlockAttributes {
+            overload: false,
+            ..reported
+        });
+    }
+
+    /// Перед следующим блоком потеряно `samples` выборок.
+    fn dropped(
+        &mut self,
+        samples: u64,
+    ) {
+        self.pending.merge(BlockAttributes {
+            dropped_samples: Some(samples.min(u32::MAX as u64) as u32),
+            ..Default::default()
+        });
+    }
+
+    /// Атрибуты блока из `n_samples` выборок устройства, начиная с `first`.
+    fn take(
+        &mut self,
+        first: u64,
+        n_samples: u32,
+    ) -> BlockAttributes {
+        let block = first..first + n_samples as u64;
+        let mut attributes = std::mem::take(&mut self.pending);
+
+        if let Some(overload) = &self.overload {
+            attributes.overload = overload.start < block.end && block.start < overload.end;
+            if overload.end <= block.end {
+                self.overload = None;
+            }
+        }
+
+        attributes
+    }
+}
+
+impl AccDigest {
+    fn new() -> Self {
+        Self {
+            hasher: Xxh64::for_block(),
+            hashed: 0,
+        }
+    }
+
+    /// Досчитывает первые `len` байт накопителя длиной `acc_len`, который
+    /// заканчивается куском `piece`.
+    fn feed(
+        &mut self,
+        piece: &[u8],
+        acc_len: usize,
+        len: usize,
+    ) {
+        // Байт накопителя `i` — байт куска `i + piece.len() - acc_len`;
+        // непосчитанные байты всегда лежат в куске
+        let start = self.hashed + piece.len() - acc_len;
+        let end = len + piece.len() - acc_len;
+        self.hasher.update(&piece[start..end]);
+        self.hashed = len;
+    }
+
+    /// Дайджест посчитанных байт — данных уходящего блока; счёт
+    /// начинается заново.
+    fn take(&mut self) -> u64 {
+        std::mem::replace(self, Self::new()).hasher.finish()
+    }
+}
+
+impl RecordingWriter<'_> {
+    fn with_checkpoint_interval(
+        self,
+        interval: Duration,
+    ) -> Self {
+        match self {
+            RecordingWriter::Single(w) => {
+                RecordingWriter::Single(w.with_checkpoint_interval(interval))
+            }
+            RecordingWriter::Rotating(w) => {
+                RecordingWriter::Rotating(Box::new(w.with_checkpoint_interval(interval)))
+            }
+        }
+    }
+
+    /// Уведомления о ротации; у одного файла их нет.
+    fn with_rotation_notifier(
+        self,
+        notifier: Notifier,
+        metrics: Arc<RecorderMetrics>,
+    ) -> Self {
+        match self {
+            RecordingWriter::Single(w) => RecordingWriter::Single(w),
+            RecordingWriter::Rotating(w) => {
+                RecordingWriter::Rotating(Box::new(w.with_notifier(notifier, metrics)))
+            }
+        }
+    }
+
+    fn finish_at(
+        self,
+        timestamp_end: u64,
+    ) -> RecorderResult<()> {
+        match self {
+            RecordingWriter::Single(w) => Ok(w.finish_at(timestamp_end)?),
+            RecordingWriter::Rotating(w) => w.finish_at(timestamp_end),
+        }
+    }
+
+    fn block_count(&self) -> u64 {
+        match self {
+            RecordingWriter::Single(w) => w.block_count(),
+            RecordingWriter::Rotating(w) => w.block_count(),
+        }
+    }
+
+    fn stored_blocks(&self) -> u64 {
+        match self {
+            RecordingWriter::Single(w) => w.stored_blocks(),
+            RecordingWriter::Rotating(w) => w.stored_blocks(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlockSink for RecordingWriter<'_> {
+    fn write_block(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<()> {
+        match self {
+            RecordingWriter::Single(w) => Ok(w.write_block(block)?),
+            RecordingWriter::Rotating(w) => w.write_block(block),
+        }
+    }
+
+    fn write_block_reclaim(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<Option<Vec<u8>>> {
+        match self {
+            RecordingWriter::Single(w) => Ok(Some(w.write_block_reclaim(block)?)),
+            RecordingWriter::Rotating(w) => w.write_block(block).map(|()| None),
+        }
+    }
+}
+
+impl Write for RecordingOutput<'_> {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> std::io::Result<usize> {
+        match self {
+            RecordingOutput::File(f) => f.write(buf),
+            RecordingOutput::Archive(m) => m.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            RecordingOutput::File(f) => f.flush(),
+            RecordingOutput::Archive(m) => m.flush(),
+        }
+    }
+}
+
+impl Seek for RecordingOutput<'_> {
+    fn seek(
+        &mut self,
+        pos: SeekFrom,
+    ) -> std::io::Result<u64> {
+        match self {
+            RecordingOutput::File(f) => f.seek(pos),
+            RecordingOutput::Archive(m) => m.seek(pos),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Смещение выборки `samples` от начала записи (нс): по номинальной
+/// частоте или через оценку дрейфа (см. [`DriftEstimator::samples_to_ns`]).
+fn stream_offset_ns(
+    samples: u64,
+    sample_rate_hz: u32,
+    drift: Option<&DriftEstimator>,
+) -> u64 {
+    match drift {
+        Some(d) => d.samples_to_ns(samples),
+        None => samples_to_ns(samples, sample_rate_hz),
+    }
+}
+
+/// Отделяет первые `n_bytes` накопителя как данные блока. Остаток (он
+/// бывает только при разбиении по содержимому) переносится в буфер из
+/// запаса, так что накопитель не сдвигается и не растёт.
+fn split_block(
+    acc: &mut Vec<u8>,
+    n_bytes: usize,
+    buffers: &BlockBuffers,
+) -> Vec<u8> {
+    let mut rest = buffers.take();
+    rest.extend_from_slice(&acc[n_bytes..]);
+    acc.truncate(n_bytes);
+    std::mem::replace(acc, rest)
+}
+
+/// Unix-время часов сессии с поправкой по внешнему эталону.
+fn disciplined_unix_ns(
+    clock: &dyn Clock,
+    offset: Option<ClockOffset>,
+) -> u64 {
+    let now = clock.unix_time_ns();
+    offset.map_or(now, |o| o.apply(now))
+}
+
+/// Длительность `samples` выборок в наносекундах. Через u128: при 20 Msps
+/// `samples * 10^9` переполняет u64 уже через 15 минут записи.
+fn samples_to_ns(
+    samples: u64,
+    sample_rate_hz: u32,
+) -> u64 {
+    (samples as u128 * 1_000_000_000 / sample_rate_hz as u128) as u64
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use glos_core::{dsp::IqCorrection, read_all_blocks, GapEvent, GlosHeaderExt, GlosReader};
+    use glos_hal::{DeviceKind, SimulatedDevice};
+    use glos_types::{Compression, IqFormat, SystemClock, VirtualClock};
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::{StatsFormat, WriteQueueConfig};
+
+    fn test_config(path: PathBuf) -> RecorderConfig {
+        RecorderConfig {
+            device: DeviceKind::Simulated,
+            center_freq_hz: 1_602_000_000,
+            sample_rate_hz: 2_000_000,
+            gain_db: 40.0,
+            iq_format: IqFormat::Int16,
+            compression: Compression::None,
+            compression_options: Default::default(),
+            compress_threads: 0,
+            iq_correction: Default::default(),
+            samples_little_endian: false,
+            output_path: path,
+            duration_secs: Some(1), // 1 секунда -> завершается сам
+            block_samples: 10_000,
+            chunking: None,
+            ring_capacity: 32,
+            ring_autotune: None,
+            stats_interval_secs: 60, // не выводим stats в тестах
+            stats_export: None,
+            start_time_ns: None,
+            clock: SystemClock::shared(),
+            mirror_path: None,
+            archive_path: None,
+            rotation: None,
+            sample_limit: None,
+            prebuffer: None,
+            format_advice: false,
+            write_queue: Default::default(),
+            rf_chain: None,
+            thumbnail: false,
+            disk_health: None,
+            telemetry: None,
+            session_log: None,
+            notify: None,
+            clock_drift: None,
+            checkpoint_interval: None,
+            branches: Vec::new(),
+            time_sync: None,
+        }
+    }
+
+    #[test]
+    fn test_pipeline_writes_valid_glos() {
+        let tmp = NamedTempFile::new().unwrap();
+        let path = tmp.path().to_path_buf();
+
+        let config = test_config(path.clone());
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
         pipeline.run(device).unwrap();
 
-        let file = std::fs::File::open(tmp.path()).unwrap();
+        // Читаем файл и валидируем
+        let file = std::fs::File::open(&path).unwrap();
         let mut reader = GlosReader::new(file).unwrap();
-        assert_eq!(reader.header().compression, Compression::Lz4);
-
-        // Блоки должны читаться и распаковываться без ошибок
         let blocks = read_all_blocks(&mut reader).unwrap();
-        assert!(!blocks.is_empty());
+
+        assert!(!blocks.is_empty(), "there must be at least 1 block");
+        assert_eq!(reader.stats().blocks_corrupted, 0, "no corrupted blocks");
+        reader.validate_totals().unwrap();
+
+        // каждый блок должен иметь корректное соотношение sample_count / data.len()
         for b in &blocks {
-            assert!(!b.is_compressed, "GlosReader должен распаковать блоки");
+            b.validate_sample_count(IqFormat::Int16).unwrap();
         }
     }
 
     #[test]
-    fn test_pipeline_partial_block_flushed() {
+    fn test_pipeline_metrics_are_updated() {
         let tmp = NamedTempFile::new().unwrap();
-        let mut config = test_config(tmp.path().to_path_buf());
-
-        // Уменьшаем block_samples, чтобы хотя бы один chunk успел записаться
-        config.block_samples = 100_000;
-        config.duration_secs = Some(1);
-
+        let config = test_config(tmp.path().to_path_buf());
         let sample_rate = config.sample_rate_hz;
         let (pipeline, metrics) = RecordingPipeline::new(config);
 
-        // Симулируем устройство, которое гарантированно выдаст хотя бы один chunk
         let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
         pipeline.run(device).unwrap();
 
-        // Проверяем, что один частичный блок записан
+        assert!(
+            metrics.samples_recorded.load(Ordering::Relaxed) > 0,
+            "samples_recorded должен быть > 0"
+        );
         assert!(
             metrics.blocks_written.load(Ordering::Relaxed) > 0,
-            "один частичный блок должен быть flush-нут"
+            "blocks_written должен быть > 0"
         );
+        assert_eq!(metrics.write_errors.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_pipeline_ring_autotune_saves_recommendation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let history = dir.path().join("ring.json");
+
+        let mut config = test_config(dir.path().join("rec.glos"));
+        config.duration_secs = None;
+        config.sample_limit = Some(200_000);
+        config.ring_autotune = Some(crate::RingAutoTune {
+            window: Duration::ZERO,
+            history: Some(history.clone()),
+        });
+        assert_eq!(config.effective_ring_capacity(), crate::RING_AUTOTUNE_START);
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(3, 5);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let peak = metrics.ring_peak_chunks.load(Ordering::Relaxed);
+        assert!(peak > 0);
+
+        let key = RingHistory::key(&DeviceKind::Simulated, sample_rate);
+        let recommended = RingHistory::load(&history)
+            .unwrap()
+            .recommended(&key)
+            .unwrap();
+        assert!(recommended >= crate::MIN_RING_CAPACITY);
+    }
+
+    #[test]
+    fn test_pipeline_disk_health_does_not_affect_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("health.glos");
+
+        let mut config = test_config(path.clone());
+        config.duration_secs = None;
+        config.sample_limit = Some(200_000);
+        config.disk_health = Some(crate::DiskHealthConfig {
+            interval: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(3, 5);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+
+        // Счётчик устройства есть не везде (tmpfs, не Linux); если есть —
+        // fsync в конце делает запись видимой на устройстве
+        if let Some(wa) = metrics.write_amplification() {
+            assert!(wa > 0.0);
+        }
+        assert_eq!(metrics.media_wear_pct(), None);
+    }
+
+    #[test]
+    fn test_pipeline_polls_device_telemetry() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut config = test_config(dir.path().join("telemetry.glos"));
+        config.duration_secs = None;
+        config.telemetry = Some(crate::TelemetryConfig {
+            interval: Duration::from_millis(10),
+            ..Default::default()
+        });
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(3, 5);
+        pipeline.run(Box::new(device)).unwrap();
+
+        // Первый замер — сразу при старте, до первых chunk'ов
+        let t = metrics.device_telemetry().expect("sensors polled");
+        assert!(t.temperature_c.unwrap() >= 35.0);
+        assert_eq!(t.usb_errors, Some(0));
+    }
+
+    #[test]
+    fn test_pipeline_stop_flag_works() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.duration_secs = None; // без ограничения по времени
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        let stop = pipeline.stop_flag();
+
+        // Останавливаем через 200 мс из отдельного потока
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        let result = pipeline.run(device);
+        assert!(result.is_ok(), "graceful stop не должен быть ошибкой");
+    }
+
+    #[test]
+    fn test_pipeline_lz4_compression() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.compression = Compression::Lz4;
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
 
-        // Проверяем читаемость файла
         let file = std::fs::File::open(tmp.path()).unwrap();
         let mut reader = GlosReader::new(file).unwrap();
-        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(reader.header().compression, Compression::Lz4);
+
+        // Блоки должны читаться и распаковываться без ошибок
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert!(!blocks.is_empty());
+        for b in &blocks {
+            assert!(!b.is_compressed, "GlosReader должен распаковать блоки");
+        }
+    }
+
+    #[test]
+    fn test_pipeline_zstd_compression() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.compression = Compression::Zstd;
+        config.compression_options.level = 9;
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().compression, Compression::Zstd);
+
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert!(!blocks.is_empty());
+        assert_eq!(reader.stats().blocks_corrupted, 0);
+        reader.validate_totals().unwrap();
+    }
+
+    #[test]
+    fn test_pipeline_parallel_compression_matches_writer() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let record = |name: &str, compress_threads: usize| {
+            let path = dir.path().join(name);
+            let mut config = test_config(path.clone());
+            config.duration_secs = None;
+            config.start_time_ns = Some(1_700_000_000_000_000_000);
+            config.block_samples = 2_000;
+            config.compression = Compression::Zstd;
+            config.compression_options.skip_incompressible = true;
+            config.compress_threads = compress_threads;
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, _) = RecordingPipeline::new(config);
+            let device =
+                SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 30);
+
+            pipeline.run(Box::new(device)).unwrap();
+            std::fs::read(path).unwrap()
+        };
+
+        let sequential = record("writer.glos", 0);
+        let parallel = record("pool.glos", 4);
+        assert_eq!(parallel, sequential);
+
+        let mut reader = GlosReader::new(parallel.as_slice()).unwrap();
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 62);
+        reader.validate_totals().unwrap();
+    }
+
+    #[test]
+    fn test_pipeline_partial_block_flushed() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+
+        // Уменьшаем block_samples, чтобы хотя бы один chunk успел записаться
+        config.block_samples = 100_000;
+        config.duration_secs = Some(1);
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+
+        // Симулируем устройство, которое гарантированно выдаст хотя бы один chunk
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+
+        // Проверяем, что один частичный блок записан
+        assert!(
+            metrics.blocks_written.load(Ordering::Relaxed) > 0,
+            "один частичный блок должен быть flush-нут"
+        );
+
+        // Проверяем читаемость файла
+        let file = std::fs::File::open(tmp.path()).unwrap();
+        let mut reader = GlosReader::new(file).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
 
         assert!(!blocks.is_empty(), "должен быть хотя бы один блок в файле");
     }
+
+    #[test]
+    fn test_pipeline_content_defined_chunking() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.chunking = Some("2000:20000".parse().unwrap());
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+
+        let file = std::fs::File::open(tmp.path()).unwrap();
+        let mut reader = GlosReader::new(file).unwrap();
+        assert!(reader.header().is_content_defined());
+
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+
+        // Все блоки, кроме хвостового, укладываются в [min, max]
+        let (_, full) = blocks.split_last().unwrap();
+        assert!(!full.is_empty());
+        for b in full {
+            assert!((2000..=20000).contains(&b.sample_count));
+        }
+    }
+
+    #[test]
+    fn test_pipeline_digests_survive_chunking_and_compression() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.duration_secs = None;
+        config.chunking = Some("2000:20000".parse().unwrap());
+        config.compression = Compression::Zstd;
+        config.compress_threads = 2;
+        config.write_queue.verify_digests = true;
+
+        // Границы блоков проходят внутри chunk'ов, а дайджест считается по
+        // chunk'ам при приёме: целые данные не должны давать расхождений
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 30);
+        pipeline.run(Box::new(device)).unwrap();
+
+        assert!(metrics.blocks_written.load(Ordering::Relaxed) > 1);
+        assert_eq!(metrics.digest_mismatches.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_pipeline_stats_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rec.glos");
+        let mut config = test_config(path.clone());
+        config.stats_export = Some(StatsFormat::Csv);
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+
+        let text = std::fs::read_to_string(dir.path().join("rec.stats.csv")).unwrap();
+        let last = text.lines().last().unwrap();
+        let blocks: u64 = last.split(',').nth(2).unwrap().parse().unwrap();
+
+        // Заголовок + как минимум финальный snapshot
+        assert!(text.lines().count() >= 2);
+        assert_eq!(blocks, metrics.blocks_written.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_pipeline_deterministic_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let record = |name: &str| {
+            let path = dir.path().join(name);
+            let mut config = test_config(path.clone());
+            config.duration_secs = None;
+            config.start_time_ns = Some(1_700_000_000_000_000_000);
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, metrics) = RecordingPipeline::new(config);
+            let device =
+                SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 30);
+
+            pipeline.run(Box::new(device)).unwrap();
+            assert_eq!(metrics.samples_recorded.load(Ordering::Relaxed), 30 * 4_096);
+
+            std::fs::read(path).unwrap()
+        };
+
+        let a = record("a.glos");
+        let b = record("b.glos");
+
+        assert_eq!(a, b, "детерминированные записи должны совпадать побайтно");
+
+        let mut reader = GlosReader::new(a.as_slice()).unwrap();
+        assert_eq!(reader.header().timestamp_start, 1_700_000_000);
+        assert_eq!(read_all_blocks(&mut reader).unwrap().len(), 13);
+    }
+
+    #[test]
+    fn test_pipeline_records_into_archive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive_path = dir.path().join("session.glosa");
+
+        for name in ["seg_000.glos", "seg_001.glos"] {
+            let mut config = test_config(dir.path().join(name));
+            config.duration_secs = None;
+            config.archive_path = Some(archive_path.clone());
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, _) = RecordingPipeline::new(config);
+            let device =
+                SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 10);
+            pipeline.run(Box::new(device)).unwrap();
+
+            // Отдельный файл не создаётся
+            assert!(!dir.path().join(name).exists());
+        }
+
+        let file = std::fs::File::open(&archive_path).unwrap();
+        let mut archive = glos_core::GlosArchive::open(file).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+
+        let mut reader = archive.open_member("seg_001.glos").unwrap();
+        assert_eq!(reader.header().total_samples, 10 * 4_096);
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(
+            blocks.iter().map(|b| b.sample_count as u64).sum::<u64>(),
+            10 * 4_096
+        );
+    }
+
+    #[test]
+    fn test_pipeline_rotates_by_size() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let mut config = test_config(dir.path().join("rec_000.glos"));
+        config.duration_secs = None;
+        config.start_time_ns = Some(1_700_000_000_000_000_000);
+        // Два блока по 10 000 выборок Int16 на файл
+        config.rotation = Some(crate::RotationConfig {
+            max_bytes: Some(100_000),
+            max_duration: None,
+            path_template: dir.path().join("rec_{seq}.glos"),
+        });
+        assert!(RecordingPipeline::new(RecorderConfig {
+            mirror_path: Some(dir.path().join("mirror.glos")),
+            ..config.clone()
+        })
+        .0
+        .run(Box::new(SimulatedDevice::new(
+            2_000_000,
+            1_602_000_000,
+            40.0
+        )))
+        .is_err());
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 30);
+        pipeline.run(Box::new(device)).unwrap();
+        assert_eq!(metrics.blocks_written.load(Ordering::Relaxed), 13);
+
+        let mut next_ns = 1_700_000_000_000_000_000;
+        let mut total = 0;
+        for seq in 0..6 {
+            let path = dir.path().join(format!("rec_{seq:03}.glos"));
+            assert!(std::fs::metadata(&path).unwrap().len() <= 100_000);
+
+            let mut reader = GlosReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+            let blocks = read_all_blocks(&mut reader).unwrap();
+            reader.validate_totals().unwrap();
+            // Короткий последний блок ещё помещается в шестой файл
+            assert_eq!(blocks.len(), if seq < 5 { 2 } else { 3 });
+            assert_eq!(reader.header().timestamp_start, next_ns / 1_000_000_000);
+
+            // Время непрерывно через границу файлов
+            for b in &blocks {
+                assert_eq!(b.timestamp_ns, next_ns);
+                next_ns += samples_to_ns(b.sample_count as u64, sample_rate);
+            }
+            total += reader.header().total_samples;
+        }
+
+        assert_eq!(total, 30 * 4_096);
+        assert!(!dir.path().join("rec_006.glos").exists());
+    }
+
+    #[test]
+    fn test_pipeline_rotates_into_archive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let archive_path = dir.path().join("session.glosa");
+
+        let mut config = test_config(dir.path().join("rec_000.glos"));
+        config.duration_secs = None;
+        config.start_time_ns = Some(1_700_000_000_000_000_000);
+        config.archive_path = Some(archive_path.clone());
+        config.rotation = Some(crate::RotationConfig {
+            max_bytes: Some(100_000),
+            max_duration: None,
+            path_template: dir.path().join("rec_{seq}.glos"),
+        });
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 30);
+        pipeline.run(Box::new(device)).unwrap();
+        assert_eq!(metrics.blocks_written.load(Ordering::Relaxed), 13);
+
+        // Сегменты — члены архива, отдельных файлов нет
+        assert!(!dir.path().join("rec_000.glos").exists());
+
+        let file = std::fs::File::open(&archive_path).unwrap();
+        let mut archive = glos_core::GlosArchive::open(file).unwrap();
+        let names: Vec<String> = archive.entries().iter().map(|e| e.name.clone()).collect();
+        assert_eq!(
+            names,
+            (0..6)
+                .map(|seq| format!("rec_{seq:03}.glos"))
+                .collect::<Vec<_>>()
+        );
+
+        let mut next_ns = 1_700_000_000_000_000_000;
+        let mut total = 0;
+        for name in &names {
+            let mut reader = archive.open_member(name).unwrap();
+            let blocks = read_all_blocks(&mut reader).unwrap();
+            assert_eq!(reader.header().timestamp_start, next_ns / 1_000_000_000);
+
+            for b in &blocks {
+                assert_eq!(b.timestamp_ns, next_ns);
+                next_ns += samples_to_ns(b.sample_count as u64, sample_rate);
+            }
+            total += reader.header().total_samples;
+        }
+
+        assert_eq!(total, 30 * 4_096);
+    }
+
+    #[test]
+    fn test_pipeline_rejects_config_before_trigger() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let mut config = test_config(dir.path().join("rec_000.glos"));
+        config.duration_secs = None;
+        config.prebuffer = Some(Duration::from_millis(50));
+        config.mirror_path = Some(dir.path().join("mirror.glos"));
+        config.rotation = Some(crate::RotationConfig {
+            max_bytes: Some(100_000),
+            max_duration: None,
+            path_template: dir.path().join("rec_{seq}.glos"),
+        });
+
+        // Триггера нет: ошибка конфигурации не должна ждать его
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 10);
+        assert!(pipeline.run(Box::new(device)).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_prebuffer_saves_window_before_trigger() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.duration_secs = None;
+        config.prebuffer = Some(Duration::from_millis(100));
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let trigger = pipeline.trigger_flag();
+        let stop = pipeline.stop_flag();
+
+        let control = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            let trigger_ns = SystemClock::shared().unix_time_ns();
+            trigger.store(true, Ordering::Relaxed);
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+            trigger_ns
+        });
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+        let trigger_ns = control.join().unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+
+        // В файле окно до триггера (200k выборок) и продолжение после него
+        let recorded = metrics.samples_recorded.load(Ordering::Relaxed);
+        assert!(recorded > 200_000, "recorded only {recorded} samples");
+        assert!(
+            blocks[0].timestamp_ns + 50_000_000 < trigger_ns,
+            "first block must precede the trigger"
+        );
+        assert!(blocks[0].timestamp_ns + 500_000_000 > trigger_ns);
+    }
+
+    #[test]
+    fn test_pipeline_prebuffer_without_trigger_writes_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("never.glos");
+        let mut config = test_config(path.clone());
+        config.duration_secs = None;
+        config.prebuffer = Some(Duration::from_millis(50));
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let stop = pipeline.stop_flag();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        });
+
+        let device = Box::new(SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0));
+        pipeline.run(device).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(metrics.samples_recorded.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_pipeline_branch_decimated_float32() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let primary = dir.path().join("primary.glos");
+        let narrow = dir.path().join("narrow.glos");
+
+        let mut config = test_config(primary.clone());
+        config.duration_secs = None;
+        config.branches = vec![format!(
+            "{},format=float32,compress=zstd,rate=500kHz,shift=-250kHz",
+            narrow.display()
+        )
+        .parse()
+        .unwrap()];
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 20);
+
+        pipeline.run(Box::new(device)).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(&primary).unwrap()).unwrap();
+        let primary_blocks = read_all_blocks(&mut reader).unwrap();
+        let primary_header = reader.header().clone();
+
+        let mut reader = GlosReader::new(std::fs::File::open(&narrow).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+        let header = reader.header();
+
+        assert_eq!(header.sample_rate, 500_000);
+        assert_eq!(header.iq_format, IqFormat::Float32);
+        assert_eq!(header.compression, Compression::Zstd);
+        assert_eq!(header.center_freq, 1_601_750_000);
+        assert_eq!(header.timestamp_end, primary_header.timestamp_end);
+
+        // Вчетверо меньше выборок, минус хвост в фильтре
+        let expected = primary_header.total_samples / 4;
+        assert!(
+            (expected - 16..=expected).contains(&header.total_samples),
+            "{} of {expected}",
+            header.total_samples
+        );
+        assert_eq!(blocks[0].timestamp_ns, primary_blocks[0].timestamp_ns);
+        for b in &blocks {
+            b.validate_sample_count(IqFormat::Float32).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_pipeline_mirror_matches_primary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let primary = dir.path().join("primary.glos");
+        let mirror = dir.path().join("mirror.glos");
+
+        let mut config = test_config(primary.clone());
+        config.duration_secs = None;
+        config.compression = Compression::Lz4;
+        config.mirror_path = Some(mirror.clone());
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 20);
+
+        pipeline.run(Box::new(device)).unwrap();
+
+        assert!(files_identical(&primary, &mirror).unwrap());
+        assert_eq!(
+            std::fs::read(&primary).unwrap(),
+            std::fs::read(&mirror).unwrap()
+        );
+
+        let file = std::fs::File::open(&mirror).unwrap();
+        let mut reader = GlosReader::new(file).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(
+            blocks.len() as u64,
+            metrics.blocks_written.load(Ordering::Relaxed)
+        );
+    }
+
+    #[test]
+    fn test_pipeline_checkpoints_keep_final_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let primary = dir.path().join("primary.glos");
+        let mirror = dir.path().join("mirror.glos");
+
+        // Контрольная точка после каждого блока основного файла; зеркало
+        // пишется без них
+        let mut config = test_config(primary.clone());
+        config.duration_secs = None;
+        config.compression = Compression::Zstd;
+        config.mirror_path = Some(mirror.clone());
+        config.checkpoint_interval = Some(Duration::ZERO);
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 20);
+
+        pipeline.run(Box::new(device)).unwrap();
+
+        assert_eq!(
+            std::fs::read(&primary).unwrap(),
+            std::fs::read(&mirror).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_survives_broken_mirror() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let primary = dir.path().join("primary.glos");
+
+        let mut config = test_config(primary.clone());
+        config.duration_secs = None;
+        // Каталога не существует — зеркало не откроется
+        config.mirror_path = Some(dir.path().join("missing").join("mirror.glos"));
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 10);
+
+        pipeline.run(Box::new(device)).unwrap();
+
+        assert_eq!(metrics.write_errors.load(Ordering::Relaxed), 0);
+        let file = std::fs::File::open(&primary).unwrap();
+        let mut reader = GlosReader::new(file).unwrap();
+        assert!(!read_all_blocks(&mut reader).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_spilled_queue_matches_in_memory() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let record = |name: &str, write_queue: WriteQueueConfig| {
+            let path = dir.path().join(name);
+            let mut config = test_config(path.clone());
+            config.duration_secs = None;
+            config.compression = Compression::Lz4;
+            config.start_time_ns = Some(1_700_000_000_000_000_000);
+            config.write_queue = write_queue;
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, metrics) = RecordingPipeline::new(config);
+            let device =
+                SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(42, 30);
+
+            pipeline.run(Box::new(device)).unwrap();
+            assert_eq!(metrics.write_errors.load(Ordering::Relaxed), 0);
+
+            std::fs::read(path).unwrap()
+        };
+
+        let in_memory = record("memory.glos", WriteQueueConfig::default());
+        // Лимит меньше блока: в памяти не больше одного блока, остальное —
+        // через файл перелива
+        let spilled = record(
+            "spilled.glos",
+            WriteQueueConfig {
+                memory_cap_bytes: 1,
+                spill_dir: Some(dir.path().to_path_buf()),
+                verify_digests: true,
+            },
+        );
+
+        assert_eq!(in_memory, spilled);
+    }
+
+    #[test]
+    fn test_pipeline_writes_rf_chain_and_thumbnail() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rf.glos");
+
+        let chain = glos_types::RfChain {
+            cable_loss_db: Some(3.0),
+            lna: Some(glos_types::Lna {
+                gain_db: 30.0,
+                noise_figure_db: None,
+            }),
+            ..Default::default()
+        };
+
+        let mut config = test_config(path.clone());
+        config.duration_secs = None;
+        config.rf_chain = Some(chain.clone());
+        config.thumbnail = true;
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 5);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let meta = RecordingMetadata::load(&path).unwrap().unwrap();
+        assert_eq!(meta.rf_chain, Some(chain));
+
+        let thumb = meta.thumbnail.unwrap();
+        assert!(thumb.rows > 0);
+        assert_eq!(thumb.levels.len(), thumb.rows * thumb.cols);
+    }
+
+    #[test]
+    fn test_pipeline_saves_session_log() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("log.glos");
+
+        let mut config = test_config(path.clone());
+        config.duration_secs = None;
+        let log = SessionLog::new(config.clock.clone());
+        log.record(log::Level::Warn, "Gain changed to 20 dB".to_string());
+        config.session_log = Some(log.clone());
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(7, 5);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let meta = RecordingMetadata::load(&path).unwrap().unwrap();
+        assert_eq!(meta.session_log.len(), 1);
+        assert_eq!(meta.session_log[0].message, "Gain changed to 20 dB");
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_estimates_clock_drift() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("drift.glos");
+
+        let mut config = test_config(path.clone());
+        config.clock_drift = Some(crate::ClockDriftConfig {
+            min_span: Duration::from_millis(300),
+            correct_timestamps: true,
+            ..Default::default()
+        });
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _metrics) = RecordingPipeline::new(config);
+        // Симулятор держит темп по часам хоста: дрейф близок к нулю с
+        // точностью до джиттера планировщика за секунду записи
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let drift = RecordingMetadata::load(&path)
+            .unwrap()
+            .unwrap()
+            .clock_drift
+            .unwrap();
+        assert!(drift.ppm.abs() < 50_000.0, "{drift:?}");
+        assert!(drift.observed_secs >= 0.3);
+        assert!(drift.timestamps_corrected);
+
+        let mut reader = GlosReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert!(blocks
+            .windows(2)
+            .all(|w| w[0].timestamp_ns <= w[1].timestamp_ns));
+    }
+
+    #[test]
+    fn test_pipeline_sample_limit_is_exact() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let record = |name: &str| {
+            let path = dir.path().join(name);
+            let mut config = test_config(path.clone());
+            config.duration_secs = None;
+            config.sample_limit = Some(25_001);
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, metrics) = RecordingPipeline::new(config);
+            // Источник дал бы 30 × 4096 выборок, лимит срабатывает раньше
+            let device =
+                SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(1, 30);
+
+            pipeline.run(Box::new(device)).unwrap();
+            assert_eq!(metrics.samples_recorded.load(Ordering::Relaxed), 25_001);
+
+            path
+        };
+
+        let path = record("a.glos");
+        let mut reader = GlosReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 25_001);
+
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let counts: Vec<u32> = blocks.iter().map(|b| b.sample_count).collect();
+        assert_eq!(counts, vec![10_000, 10_000, 5_001]);
+
+        let expected_len = 128 + 25_001 * 4 + 3 * 20;
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), expected_len);
+        assert_eq!(
+            std::fs::metadata(record("b.glos")).unwrap().len(),
+            expected_len
+        );
+    }
+
+    #[test]
+    fn test_pipeline_virtual_clock() {
+        let tmp = NamedTempFile::new().unwrap();
+        let clock = VirtualClock::new(1_700_000_000_000_000_000);
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.duration_secs = None;
+        config.clock = clock.clone();
+
+        let sample_rate = config.sample_rate_hz;
+        let (pipeline, _) = RecordingPipeline::new(config);
+
+        let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0)
+            .deterministic(1, 50)
+            .with_clock(clock.clone());
+        let start = Instant::now();
+        pipeline.run(Box::new(device)).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // Время окончания — по виртуальным часам, которые никто не двигал
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().timestamp_start, 1_700_000_000);
+        assert_eq!(reader.header().timestamp_end, 1_700_000_000);
+
+        // Метки блоков отсчитываются от виртуального Unix-времени
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        assert_eq!(blocks[0].timestamp_ns, 1_700_000_000_000_000_000);
+        assert_eq!(blocks[1].timestamp_ns, 1_700_000_000_005_000_000);
+    }
+
+    /// Устройство с заранее заданными номерами первых выборок chunk'ов.
+    struct GapDevice {
+        first_samples: Vec<u64>,
+        /// События устройства по номеру chunk'а
+        attributes: Vec<BlockAttributes>,
+        /// Чем завершить поток после всех chunk'ов
+        error: Option<glos_hal::HalError>,
+    }
+
+    impl SdrDevice for GapDevice {
+        fn info(&self) -> glos_hal::DeviceInfo {
+            glos_hal::DeviceInfo {
+                name: "gap".into(),
+                serial: None,
+                sample_rate_hz: 1_000_000,
+                center_freq_hz: 1_602_000_000,
+                gain_db: 0.0,
+                sample_format: IqFormat::Int16,
+            }
+        }
+
+        fn run(
+            &mut self,
+            tx: crossbeam_channel::Sender<IqChunk>,
+            _stop_flag: Arc<AtomicBool>,
+        ) -> Result<glos_hal::HalStats, glos_hal::HalError> {
+            for (i, &first_sample) in self.first_samples.iter().enumerate() {
+                let chunk = IqChunk {
+                    first_sample,
+                    sample_count: 1000,
+                    data: vec![0; 4000],
+                    attributes: self.attributes.get(i).copied().unwrap_or_default(),
+                };
+                if tx.send(chunk).is_err() {
+                    break;
+                }
+            }
+            match self.error.take() {
+                Some(e) => Err(e),
+                None => Ok(Default::default()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pipeline_timestamps_survive_capture_gap() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.sample_rate_hz = 1_000_000;
+        config.block_samples = 1500;
+        config.duration_secs = None;
+        config.start_time_ns = Some(1_700_000_000_000_000_000);
+
+        // Chunk с выборками 2000..3000 потерян
+        let device = GapDevice {
+            first_samples: vec![0, 1000, 3000, 4000],
+            attributes: Vec::new(),
+            error: None,
+        };
+        let (pipeline, metrics) = RecordingPipeline::new(config);
+        pipeline.run(Box::new(device)).unwrap();
+        assert_eq!(metrics.dropped_samples.load(Ordering::Relaxed), 1000);
+
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let timing: Vec<(u64, u32)> = blocks
+            .iter()
+            .map(|b| (b.timestamp_ns - 1_700_000_000_000_000_000, b.sample_count))
+            .collect();
+
+        // Блок перед пропуском закрыт раньше, после — время от реальной позиции
+        assert_eq!(
+            timing,
+            [
+                (0, 1500),
+                (1_500_000, 500),
+                (3_000_000, 1500),
+                (4_500_000, 500)
+            ]
+        );
+
+        // Читатель видит пропуск по атрибутам блока
+        let stats = reader.stats();
+        assert_eq!(stats.samples_dropped, 1000);
+        assert_eq!(
+            stats.gaps,
+            [GapEvent {
+                start_ns: 1_700_000_000_002_000_000,
+                end_ns: 1_700_000_000_003_000_000,
+                samples: 1000,
+            }]
+        );
+    }
+
+    /// Устройство, на середине потока ставящее метку синхронизации, пока
+    /// писатель ждёт следующий chunk.
+    struct MarkerDevice {
+        markers: SyncMarkers,
+    }
+
+    impl SdrDevice for MarkerDevice {
+        fn info(&self) -> glos_hal::DeviceInfo {
+            glos_hal::DeviceInfo {
+                name: "marker".into(),
+                serial: None,
+                sample_rate_hz: 1_000_000,
+                center_freq_hz: 1_602_000_000,
+                gain_db: 0.0,
+                sample_format: IqFormat::Int16,
+            }
+        }
+
+        fn run(
+            &mut self,
+            tx: crossbeam_channel::Sender<IqChunk>,
+            _stop_flag: Arc<AtomicBool>,
+        ) -> Result<glos_hal::HalStats, glos_hal::HalError> {
+            for i in 0..4u64 {
+                if i == 2 {
+                    while !tx.is_empty() {
+                        std::thread::sleep(Duration::from_millis(5));
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                    self.markers.mark(Some(42_000));
+                    std::thread::sleep(Duration::from_millis(250));
+                }
+                let chunk = IqChunk {
+                    first_sample: i * 1000,
+                    sample_count: 1000,
+                    data: vec![i as u8; 4000],
+                    attributes: BlockAttributes::default(),
+                };
+                if tx.send(chunk).is_err() {
+                    break;
+                }
+            }
+            Ok(Default::default())
+        }
+    }
+
+    #[test]
+    fn test_pipeline_inserts_sync_markers() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.sample_rate_hz = 1_000_000;
+        config.block_samples = 1500;
+        config.duration_secs = None;
+        config.start_time_ns = Some(1_700_000_000_000_000_000);
+
+        let (pipeline, _) = RecordingPipeline::new(config);
+        let markers = pipeline.sync_markers();
+        // Метка до первых выборок встаёт в начало записи
+        assert_eq!(markers.mark(Some(7_000)), 1);
+        let device = MarkerDevice { markers };
+        pipeline.run(Box::new(device)).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let timing: Vec<(u64, u32, Option<u32>)> = blocks
+            .iter()
+            .map(|b| {
+                let id = b.attributes.sync_marker.map(|m| m.id);
+                (
+                    b.timestamp_ns - 1_700_000_000_000_000_000,
+                    b.sample_count,
+                    id,
+                )
+            })
+            .collect();
+
+        // Копившийся блок закрыт перед меткой, выборки не потеряны
+        assert_eq!(
+            timing,
+            [
+                (0, 0, Some(1)),
+                (0, 1500, None),
+                (1_500_000, 500, None),
+                (2_000_000, 0, Some(2)),
+                (2_000_000, 1500, None),
+                (3_500_000, 500, None),
+            ]
+        );
+        assert_eq!(reader.stats().samples_recovered, 4000);
+
+        let meta = RecordingMetadata::load(tmp.path()).unwrap().unwrap();
+        assert_eq!(
+            meta.sync_markers,
+            [
+                SyncPoint {
+                    id: 1,
+                    external_ns: 7_000,
+                    timestamp_ns: 1_700_000_000_000_000_000,
+                    sample_index: 0,
+                },
+                SyncPoint {
+                    id: 2,
+                    external_ns: 42_000,
+                    timestamp_ns: 1_700_000_000_002_000_000,
+                    sample_index: 2000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pipeline_marks_block_events() {
+        let tmp = NamedTempFile::new().unwrap();
+        let mut config = test_config(tmp.path().to_path_buf());
+        config.sample_rate_hz = 1_000_000;
+        config.block_samples = 1500;
+        config.duration_secs = None;
+
+        let overload = BlockAttributes {
+            overload: true,
+            ..Default::default()
+        };
+        let gain = BlockAttributes {
+            gain_db: Some(20.0),
+            ..Default::default()
+        };
+
+        // Перегрузка в 3000..4000 (задевает два блока), смена усиления в
+        // 4000..5000, выборки 1000..2000 потеряны
+        let device = GapDevice {
+            first_samples: vec![0, 2000, 3000, 4000],
+            attributes: vec![
+                BlockAttributes::default(),
+                BlockAttributes::default(),
+                overload,
+                gain,
+            ],
+            error: None,
+        };
+        let (pipeline, _) = RecordingPipeline::new(config);
+        pipeline.run(Box::new(device)).unwrap();
+
+        let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+        assert_eq!(
+            reader.header().version,
+            glos_core::GLOS_BLOCK_ATTRIBUTES_VERSION
+        );
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        let attributes: Vec<_> = blocks.iter().map(|b| b.attributes).collect();
+
+        // Блоки: 0..1000, 2000..3500, 3500..5000
+        assert_eq!(
+            attributes,
+            [
+                BlockAttributes::default(),
+                BlockAttributes {
+                    overload: true,
+                    dropped_samples: Some(1000),
+                    ..Default::default()
+                },
+                BlockAttributes {
+                    overload: true,
+                    gain_db: Some(20.0),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pipeline_notifies_device_failure() {
+        use std::{
+            io::{BufRead, BufReader, Read},
+            net::TcpListener,
+        };
+
+        use crate::{Notifier, NotifyConfig};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(v) = line.strip_prefix("Content-Length: ") {
+                    length = v.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        });
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("fail.glos");
+        let mut config = test_config(path.clone());
+        config.sample_rate_hz = 1_000_000;
+        config.block_samples = 1000;
+        config.duration_secs = None;
+        config.notify = Some(Notifier::new(NotifyConfig {
+            webhook: Some(format!("http://127.0.0.1:{port}/hook").parse().unwrap()),
+            smtp: None,
+            events: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }));
+
+        let device = GapDevice {
+            first_samples: vec![0, 1000],
+            attributes: Vec::new(),
+            error: Some(glos_hal::HalError::DeviceDisconected),
+        };
+        let (pipeline, _) = RecordingPipeline::new(config);
+        // Запись, сделанная до сбоя, сохраняется штатно
+        pipeline.run(Box::new(device)).unwrap();
+
+        let json = server.join().unwrap();
+        assert_eq!(json["event"], "device-failure");
+        assert_eq!(json["recording"], path.display().to_string());
+        assert!(json["message"]
+            .as_str()
+            .unwrap()
+            .contains("Device disconected"));
+        assert_eq!(json["summary"]["blocks_written"], 2);
+    }
+
+    #[test]
+    fn test_samples_to_ns_does_not_overflow() {
+        // Сутки при 20 Msps: samples * 10^9 не помещается в u64
+        let samples = 20_000_000u64 * 86_400;
+        assert_eq!(samples_to_ns(samples, 20_000_000), 86_400 * 1_000_000_000);
+    }
+
+    #[test]
+    fn test_pipeline_iq_correction() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let record = |name: &str, swap_iq: bool| {
+            let path = dir.path().join(name);
+            let mut config = test_config(path.clone());
+            config.duration_secs = None;
+            config.iq_correction.swap_iq = swap_iq;
+
+            let sample_rate = config.sample_rate_hz;
+            let (pipeline, _) = RecordingPipeline::new(config);
+            let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0).deterministic(3, 5);
+            pipeline.run(Box::new(device)).unwrap();
+
+            let mut reader = GlosReader::new(std::fs::File::open(path).unwrap()).unwrap();
+            let blocks = read_all_blocks(&mut reader).unwrap();
+            (reader.header().clone(), blocks)
+        };
+
+        let (plain_hdr, plain) = record("plain.glos", false);
+        let (swapped_hdr, swapped) = record("swapped.glos", true);
+
+        assert!(!IqCorrection::from_header(&plain_hdr).swap_iq);
+        assert!(IqCorrection::from_header(&swapped_hdr).swap_iq);
+
+        // Повторная перестановка возвращает исходный сигнал
+        let mut restored = swapped[0].data.clone();
+        glos_core::dsp::swap_iq(&mut restored, IqFormat::Int16).unwrap();
+        assert_ne!(swapped[0].data, plain[0].data);
+        assert_eq!(restored, plain[0].data);
+    }
 }
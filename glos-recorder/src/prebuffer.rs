@@ -0,0 +1,139 @@
+use std::{collections::VecDeque, time::Duration};
+
+use glos_hal::IqChunk;
+
+/// Кольцевой буфер последних выборок до срабатывания триггера.
+///
+/// Хранит не больше `window` сигнала: старые выборки вытесняются, в том
+/// числе из середины chunk'а, так что в файл попадает ровно окно до
+/// триггера. Вытесненные выборки учитываются, чтобы метка времени первого
+/// блока соответствовала реально записанной выборке.
+#[derive(Debug)]
+pub struct PreBuffer {
+    chunks: VecDeque<IqChunk>,
+    samples: u64,
+    capacity_samples: u64,
+    sample_size: usize,
+    discarded_samples: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl PreBuffer {
+    /// Буфер на `window` сигнала при частоте `sample_rate_hz`.
+    pub fn new(
+        window: Duration,
+        sample_rate_hz: u32,
+        sample_size: usize,
+    ) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            samples: 0,
+            capacity_samples: (window.as_secs_f64() * sample_rate_hz as f64).round() as u64,
+            sample_size,
+            discarded_samples: 0,
+        }
+    }
+
+    /// Добавляет chunk, вытесняя самые старые выборки сверх окна.
+    pub fn push(
+        &mut self,
+        chunk: IqChunk,
+    ) {
+        self.samples += chunk.sample_count as u64;
+        self.chunks.push_back(chunk);
+
+        while self.samples > self.capacity_samples {
+            let excess = self.samples - self.capacity_samples;
+            let Some(front) = self.chunks.front_mut() else {
+                break;
+            };
+
+            if front.sample_count as u64 <= excess {
+                self.samples -= front.sample_count as u64;
+                self.discarded_samples += front.sample_count as u64;
+                self.chunks.pop_front();
+            } else {
+                front.data.drain(..excess as usize * self.sample_size);
+                front.first_sample += excess;
+                front.sample_count -= excess as u32;
+                self.samples -= excess;
+                self.discarded_samples += excess;
+            }
+        }
+    }
+
+    /// Выборок в буфере.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn capacity_samples(&self) -> u64 {
+        self.capacity_samples
+    }
+
+    /// Выборок, вытесненных с момента создания буфера.
+    pub fn discarded_samples(&self) -> u64 {
+        self.discarded_samples
+    }
+
+    /// Содержимое буфера от старых к новым.
+    pub fn into_chunks(self) -> VecDeque<IqChunk> {
+        self.chunks
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(
+        first: u8,
+        samples: u32,
+    ) -> IqChunk {
+        // Int8: 2 байта на выборку, значение = номер выборки
+        let data = (0..samples)
+            .flat_map(|i| {
+                let v = first.wrapping_add(i as u8);
+                [v, v]
+            })
+            .collect();
+
+        IqChunk {
+            first_sample: first as u64,
+            sample_count: samples,
+            data,
+            attributes: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_prebuffer_keeps_last_window() {
+        // 10 мс при 1 кГц — 10 выборок
+        let mut buf = PreBuffer::new(Duration::from_millis(10), 1_000, 2);
+
+        buf.push(chunk(0, 4));
+        buf.push(chunk(4, 4));
+        assert_eq!(buf.samples(), 8);
+        assert_eq!(buf.discarded_samples(), 0);
+
+        // Первый chunk вытесняется целиком, второй — наполовину
+        buf.push(chunk(8, 4));
+        buf.push(chunk(12, 4));
+        assert_eq!(buf.samples(), 10);
+        assert_eq!(buf.discarded_samples(), 6);
+
+        let chunks = buf.into_chunks();
+        let data: Vec<u8> = chunks.iter().flat_map(|c| c.data.iter().copied()).collect();
+        assert_eq!(chunks.iter().map(|c| c.sample_count).sum::<u32>(), 10);
+        assert_eq!(chunks[0].first_sample, 6);
+        assert_eq!(data[0], 6);
+        assert_eq!(data[data.len() - 1], 15);
+    }
+}
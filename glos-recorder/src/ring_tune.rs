@@ -0,0 +1,247 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glos_hal::DeviceKind;
+use serde_json::Value;
+
+use crate::{RecorderError, RecorderResult};
+
+/// Ёмкость кольцевого буфера без подсказок (chunk-слотов).
+pub const DEFAULT_RING_CAPACITY: usize = 256;
+
+/// С какой ёмкости начинает авто-подбор: с запасом, чтобы пик занятости
+/// измерялся, а не упирался в потолок.
+pub const RING_AUTOTUNE_START: usize = 1024;
+
+/// Сколько длится замер пиковой занятости.
+pub const RING_AUTOTUNE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Рекомендация — пик, умноженный на запас: первая минута не обязательно
+/// худшая.
+pub const RING_HEADROOM: f64 = 2.0;
+
+/// Меньше не рекомендуем, даже если буфер почти не заполнялся.
+pub const MIN_RING_CAPACITY: usize = 8;
+
+/// Сколько последних рекомендаций хранится на устройство и частоту.
+pub const RING_HISTORY_LEN: usize = 10;
+
+/// Настройки авто-подбора ёмкости кольцевого буфера.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingAutoTune {
+    /// Длительность замера с начала записи
+    pub window: Duration,
+    /// Файл истории: рекомендация дописывается в него, следующая сессия
+    /// берёт из него ёмкость (None — только отчёт в лог)
+    pub history: Option<PathBuf>,
+}
+
+/// Замер пиковой занятости кольцевого буфера в начале записи.
+#[derive(Debug, Clone)]
+pub struct RingTuner {
+    window: Duration,
+    peak: usize,
+    observed: Duration,
+    reported: bool,
+}
+
+/// Рекомендации прошлых сессий по ключу «устройство@частота».
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RingHistory {
+    entries: BTreeMap<String, Vec<usize>>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RingTuner {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            peak: 0,
+            observed: Duration::ZERO,
+            reported: false,
+        }
+    }
+
+    /// Учитывает занятость буфера (chunk'ов) через `elapsed` от начала
+    /// записи. Возвращает рекомендацию один раз — когда замер закончен.
+    pub fn observe(
+        &mut self,
+        elapsed: Duration,
+        occupancy: usize,
+    ) -> Option<usize> {
+        if self.reported {
+            return None;
+        }
+
+        self.peak = self.peak.max(occupancy);
+        self.observed = elapsed;
+
+        if elapsed < self.window {
+            return None;
+        }
+
+        self.reported = true;
+        Some(self.recommendation())
+    }
+
+    /// Наибольшая замеченная занятость (chunk'ов).
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Сколько записи уже покрыто замером.
+    pub fn observed(&self) -> Duration {
+        self.observed
+    }
+
+    /// Рекомендация уже выдана [`observe`](Self::observe).
+    pub fn is_reported(&self) -> bool {
+        self.reported
+    }
+
+    /// Рекомендуемая ёмкость по текущему пику.
+    pub fn recommendation(&self) -> usize {
+        ((self.peak as f64 * RING_HEADROOM).ceil() as usize).max(MIN_RING_CAPACITY)
+    }
+}
+
+impl RingHistory {
+    /// Ключ истории: пик зависит прежде всего от устройства и частоты.
+    pub fn key(
+        device: &DeviceKind,
+        sample_rate_hz: u32,
+    ) -> String {
+        format!("{device}@{sample_rate_hz}")
+    }
+
+    /// Читает историю; отсутствующий файл — пустая история.
+    pub fn load(path: &Path) -> RecorderResult<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let invalid =
+            || RecorderError::Pipeline(format!("{}: invalid ring history", path.display()));
+        let value: Value = serde_json::from_str(&text).map_err(|_| invalid())?;
+        let object = value.as_object().ok_or_else(invalid)?;
+
+        let mut entries = BTreeMap::new();
+        for (key, list) in object {
+            let values = list
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|v| v.as_u64().map(|n| n as usize).ok_or_else(invalid))
+                .collect::<RecorderResult<Vec<_>>>()?;
+            entries.insert(key.clone(), values);
+        }
+
+        Ok(Self { entries })
+    }
+
+    pub fn save(
+        &self,
+        path: &Path,
+    ) -> RecorderResult<()> {
+        let value = Value::Object(
+            self.entries
+                .iter()
+                .map(|(k, v)| (k.clone(), Value::from(v.clone())))
+                .collect(),
+        );
+        let text = serde_json::to_string_pretty(&value)
+            .map_err(|e| RecorderError::Pipeline(e.to_string()))?;
+
+        fs::write(path, text + "\n")?;
+        Ok(())
+    }
+
+    /// Добавляет рекомендацию сессии, оставляя последние
+    /// [`RING_HISTORY_LEN`].
+    pub fn record(
+        &mut self,
+        key: &str,
+        capacity: usize,
+    ) {
+        let list = self.entries.entry(key.to_string()).or_default();
+        list.push(capacity);
+        if list.len() > RING_HISTORY_LEN {
+            list.drain(..list.len() - RING_HISTORY_LEN);
+        }
+    }
+
+    /// Ёмкость для следующей сессии: наибольшая из последних рекомендаций,
+    /// чтобы одна спокойная сессия не урезала буфер.
+    pub fn recommended(
+        &self,
+        key: &str,
+    ) -> Option<usize> {
+        self.entries.get(key)?.iter().copied().max()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuner_reports_once_after_window() {
+        let mut tuner = RingTuner::new(Duration::from_secs(60));
+
+        assert_eq!(tuner.observe(Duration::from_secs(1), 3), None);
+        assert_eq!(tuner.observe(Duration::from_secs(30), 40), None);
+        assert_eq!(tuner.recommendation(), 80);
+
+        assert_eq!(tuner.observe(Duration::from_secs(60), 5), Some(80));
+        assert!(tuner.is_reported());
+
+        // После замера пик не меняется
+        assert_eq!(tuner.observe(Duration::from_secs(61), 500), None);
+        assert_eq!(tuner.peak(), 40);
+
+        let idle = RingTuner::new(Duration::ZERO);
+        assert_eq!(idle.recommendation(), MIN_RING_CAPACITY);
+    }
+
+    #[test]
+    fn test_history_roundtrip_keeps_recent_maximum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ring.json");
+
+        let mut history = RingHistory::load(&path).unwrap();
+        assert_eq!(history, RingHistory::default());
+
+        let key = RingHistory::key(&DeviceKind::Simulated, 2_000_000);
+        assert_eq!(key, "sim@2000000");
+
+        history.record(&key, 500);
+        for _ in 0..RING_HISTORY_LEN {
+            history.record(&key, 64);
+        }
+        // 500 вытеснено последними десятью
+        assert_eq!(history.recommended(&key), Some(64));
+        history.record(&key, 96);
+        history.save(&path).unwrap();
+
+        let loaded = RingHistory::load(&path).unwrap();
+        assert_eq!(loaded, history);
+        assert_eq!(loaded.recommended(&key), Some(96));
+        assert_eq!(loaded.recommended("hackrf@1"), None);
+
+        fs::write(&path, "[1, 2]").unwrap();
+        assert!(RingHistory::load(&path).is_err());
+    }
+}
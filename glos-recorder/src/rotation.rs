@@ -0,0 +1,610 @@
+use std::{
+    fs::File,
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use glos_core::{
+    ArchiveMember, CompressionOptions, GlosArchiveWriter, GlosWriter, IqBlockExt,
+    GLOS_BLOCK_OVERHEAD, GLOS_HEADER_SIZE,
+};
+use glos_types::{GlosError, GlosHeader, IqBlock};
+use log::{info, warn};
+
+use crate::{
+    naming, BlockSink, Notification, Notifier, NotifyEvent, RecorderError, RecorderMetrics,
+    RecorderResult,
+};
+
+/// Подстановка номера сегмента в шаблоне имени (см. [`naming`]).
+const SEQ_FIELD: &str = "{seq}";
+
+/// Ротация записи: по достижении предела файл закрывается, и запись
+/// продолжается в следующий.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationConfig {
+    /// Предельный размер файла (байт, с заголовком). Блок, после которого
+    /// файл превысил бы предел, начинает новый файл; считается несжатый
+    /// размер блока, так что файл предела не превышает (None = без предела)
+    pub max_bytes: Option<u64>,
+    /// Предельная длительность файла по меткам блоков (None = без предела)
+    pub max_duration: Option<Duration>,
+    /// Шаблон пути сегментов с `{seq}`, раскрывается
+    /// [`naming::resolve_output_path`]. Первый сегмент —
+    /// `RecorderConfig::output_path`
+    pub path_template: PathBuf,
+}
+
+/// Писатель серии файлов `.glos` по [`RotationConfig`].
+///
+/// Метки блоков не меняются, поэтому время непрерывно через границы
+/// файлов. Заголовок каждого файла описывает только его блоки:
+/// `timestamp_start` — первый блок, `timestamp_end` — конец последнего,
+/// `total_samples` — выборки файла. С архивом (см.
+/// [`create_in_archive`](Self::create_in_archive)) сегменты пишутся его
+/// членами с именами файлов сегментов.
+pub struct RotatingWriter {
+    config: RotationConfig,
+    compression: CompressionOptions,
+    /// Период контрольных точек заголовка каждого сегмента
+    checkpoint_interval: Option<Duration>,
+    /// Заголовок, из которого строятся заголовки сегментов
+    header: GlosHeader,
+    /// Архив сегментов (None — отдельные файлы)
+    archive: Option<GlosArchiveWriter<File>>,
+    /// Текущий сегмент; None — следующий член архива не удалось начать,
+    /// попытка повторится со следующим блоком
+    current: Option<GlosWriter<SegmentOutput>>,
+    segments: Vec<PathBuf>,
+    /// Метка первого блока и конец последнего блока текущего сегмента (нс)
+    first_ns: Option<u64>,
+    end_ns: u64,
+    /// Блоки закрытых сегментов
+    closed_blocks: u64,
+    closed_stored: u64,
+    /// Первая ошибка финализации закрытого сегмента; запись при этом
+    /// продолжается, ошибка возвращается из [`finish_at`](Self::finish_at)
+    finalize_error: Option<GlosError>,
+    /// Уведомление о каждой ротации
+    notify: Option<RotationNotify>,
+}
+
+/// Чем и с какими метриками уведомлять о ротации.
+struct RotationNotify {
+    notifier: Notifier,
+    metrics: Arc<RecorderMetrics>,
+    session_start: Instant,
+}
+
+/// Поток сегмента: свой файл или член архива.
+enum SegmentOutput {
+    File(File),
+    Archive(ArchiveMember<File>),
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RotationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_bytes.is_none() && self.max_duration.is_none() {
+            return Err("rotation needs a size or a duration limit".to_string());
+        }
+        if let Some(max) = self.max_bytes {
+            let min = (GLOS_HEADER_SIZE + GLOS_BLOCK_OVERHEAD) as u64;
+            if max <= min {
+                return Err(format!("rotation size must be > {min} bytes"));
+            }
+        }
+        if self.max_duration.is_some_and(|d| d.is_zero()) {
+            return Err("rotation duration must be > 0".to_string());
+        }
+        if !self.path_template.to_string_lossy().contains(SEQ_FIELD) {
+            return Err(format!(
+                "rotation path template {:?} must contain {SEQ_FIELD}",
+                self.path_template
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl RotatingWriter {
+    /// Создаёт первый сегмент `first_path` с заголовком `header`.
+    pub fn create(
+        first_path: &Path,
+        header: GlosHeader,
+        compression: CompressionOptions,
+        config: RotationConfig,
+    ) -> RecorderResult<Self> {
+        Self::start(None, first_path, header, compression, config)
+    }
+
+    /// Как [`create`](Self::create), но сегменты пишутся членами `archive`;
+    /// архив финализируется вместе с последним сегментом.
+    pub fn create_in_archive(
+        archive: GlosArchiveWriter<File>,
+        first_path: &Path,
+        header: GlosHeader,
+        compression: CompressionOptions,
+        config: RotationConfig,
+    ) -> RecorderResult<Self> {
+        Self::start(Some(archive), first_path, header, compression, config)
+    }
+
+    fn start(
+        archive: Option<GlosArchiveWriter<File>>,
+        first_path: &Path,
+        header: GlosHeader,
+        compression: CompressionOptions,
+        config: RotationConfig,
+    ) -> RecorderResult<Self> {
+        config.validate().map_err(RecorderError::Pipeline)?;
+
+        let mut writer = Self {
+            config,
+            compression,
+            checkpoint_interval: None,
+            header: header.clone(),
+            archive,
+            current: None,
+            segments: Vec::new(),
+            first_ns: None,
+            end_ns: 0,
+            closed_blocks: 0,
+            closed_stored: 0,
+            finalize_error: None,
+            notify: None,
+        };
+
+        let (current, name) = writer.open_segment(first_path, header)?;
+        writer.current = Some(current);
+        writer.segments.push(name);
+
+        Ok(writer)
+    }
+
+    /// Контрольные точки заголовка в каждом сегменте (см.
+    /// [`GlosWriter::with_checkpoint_interval`]).
+    pub fn with_checkpoint_interval(
+        mut self,
+        interval: Duration,
+    ) -> Self {
+        self.current = self.current.map(|w| w.with_checkpoint_interval(interval));
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Уведомлять о ротации ([`NotifyEvent::Rotated`]) после финализации
+    /// каждого сегмента. Сводка в уведомлении — `metrics` с момента вызова.
+    pub fn with_notifier(
+        mut self,
+        notifier: Notifier,
+        metrics: Arc<RecorderMetrics>,
+    ) -> Self {
+        self.notify = Some(RotationNotify {
+            notifier,
+            metrics,
+            session_start: Instant::now(),
+        });
+        self
+    }
+
+    /// Финализирует последний сегмент и архив, если он есть.
+    /// `timestamp_end` (Unix-секунды) пишется в заголовок сегмента;
+    /// закрытые раньше сегменты уже финализированы.
+    pub fn finish_at(
+        self,
+        timestamp_end: u64,
+    ) -> RecorderResult<()> {
+        if let Some(current) = self.current {
+            current.finish_at(timestamp_end)?;
+        }
+        if let Some(archive) = self.archive {
+            archive.finish()?;
+        }
+
+        match self.finalize_error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Пути сегментов по порядку, включая текущий; в архиве — имена
+    /// членов.
+    pub fn segments(&self) -> &[PathBuf] {
+        &self.segments
+    }
+
+    /// Блоков во всех сегментах.
+    pub fn block_count(&self) -> u64 {
+        self.closed_blocks + self.current.as_ref().map_or(0, GlosWriter::block_count)
+    }
+
+    /// Блоков, записанных без сжатия, во всех сегментах.
+    pub fn stored_blocks(&self) -> u64 {
+        self.closed_stored + self.current.as_ref().map_or(0, GlosWriter::stored_blocks)
+    }
+
+    /// Пора ли начать новый сегмент перед `block`. Пустой сегмент не
+    /// закрывается никогда: блок больше предела получает свой файл.
+    fn should_rotate(
+        &self,
+        block: &IqBlock,
+    ) -> bool {
+        let Some(current) = &self.current else {
+            return true;
+        };
+        let Some(first_ns) = self.first_ns else {
+            return false;
+        };
+
+        let frame_bytes = block.frame_size() as u64;
+        let by_size = self
+            .config
+            .max_bytes
+            .is_some_and(|max| current.bytes_written() + frame_bytes > max);
+        let by_time = self.config.max_duration.is_some_and(|max| {
+            block.timestamp_ns.saturating_sub(first_ns) >= max.as_nanos() as u64
+        });
+
+        by_size || by_time
+    }
+
+    /// Закрывает текущий сегмент и открывает следующий, начинающийся с
+    /// блока с меткой `timestamp_ns`.
+    fn rotate(
+        &mut self,
+        timestamp_ns: u64,
+    ) -> RecorderResult<()> {
+        let seq = self.segments.len() as u32;
+
+        let mut header = self.header.clone();
+        header.timestamp_start = timestamp_ns / 1_000_000_000;
+
+        let path = naming::resolve_output_path(&self.config.path_template, &header, seq)
+            .map_err(RecorderError::Pipeline)?;
+        let prev_path = self.segments.last().cloned().unwrap_or_default();
+
+        // Файл следующего сегмента создаётся до закрытия текущего, чтобы при
+        // ошибке запись продолжалась в текущий. Член архива пишется только
+        // после финализации предыдущего
+        let (next, name) = if self.archive.is_some() {
+            self.close_current(&prev_path);
+            self.open_segment(&path, header)?
+        } else {
+            let next = self.open_segment(&path, header)?;
+            self.close_current(&prev_path);
+            next
+        };
+
+        info!("Rotated: {prev_path:?} → {name:?}");
+        self.notify_rotated(&prev_path, &name);
+
+        self.current = Some(next);
+        self.segments.push(name);
+        self.first_ns = None;
+
+        Ok(())
+    }
+
+    /// Рассылает [`NotifyEvent::Rotated`] из отдельного потока, чтобы
+    /// ожидание сервера не задерживало запись.
+    fn notify_rotated(
+        &self,
+        prev: &Path,
+        next: &Path,
+    ) {
+        let Some(notify) = &self.notify else {
+            return;
+        };
+
+        let notification = Notification {
+            event: NotifyEvent::Rotated {
+                prev: prev.to_path_buf(),
+                next: next.to_path_buf(),
+            },
+            recording: next.to_path_buf(),
+            message: format!("Segment finalized: {prev:?}, recording continues in {next:?}"),
+            summary: notify.metrics.summary(&notify.session_start),
+        };
+        let notifier = notify.notifier.clone();
+        std::thread::spawn(move || notifier.send(&notification));
+    }
+
+    /// Финализирует текущий сегмент `path`. Ошибка запоминается в
+    /// `finalize_error`, запись продолжается.
+    fn close_current(
+        &mut self,
+        path: &Path,
+    ) {
+        let Some(prev) = self.current.take() else {
+            return;
+        };
+
+        self.closed_blocks += prev.block_count();
+        self.closed_stored += prev.stored_blocks();
+
+        if let Err(e) = prev.finish_at(self.end_ns / 1_000_000_000) {
+            warn!("Cannot finalize segment {path:?}: {e}");
+            self.finalize_error.get_or_insert(e);
+        }
+    }
+
+    /// Открывает сегмент `path`; в архиве — член с именем файла `path`.
+    /// Возвращает писатель и путь сегмента для [`segments`](Self::segments).
+    fn open_segment(
+        &mut self,
+        path: &Path,
+        header: GlosHeader,
+    ) -> RecorderResult<(GlosWriter<SegmentOutput>, PathBuf)> {
+        let (output, name) = match self.archive.as_mut() {
+            Some(archive) => {
+                let name = path
+                    .file_name()
+                    .unwrap_or(path.as_os_str())
+                    .to_string_lossy()
+                    .into_owned();
+                let member = archive.begin_member_owned(&name)?;
+                (SegmentOutput::Archive(member), PathBuf::from(name))
+            }
+            None => (SegmentOutput::File(File::create(path)?), path.to_path_buf()),
+        };
+
+        let mut writer = GlosWriter::new(output, header)?.with_compression(self.compression)?;
+        if let Some(interval) = self.checkpoint_interval {
+            writer = writer.with_checkpoint_interval(interval);
+        }
+
+        Ok((writer, name))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl BlockSink for RotatingWriter {
+    /// Если следующий сегмент не удалось создать, блок не пишется (ошибка
+    /// записи), а попытка повторяется со следующим блоком.
+    fn write_block(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<()> {
+        if self.should_rotate(&block) {
+            self.rotate(block.timestamp_ns)?;
+        }
+
+        let timestamp_ns = block.timestamp_ns;
+        let samples = block.sample_count as u128;
+
+        if let Some(current) = self.current.as_mut() {
+            current.write_block(block)?;
+        }
+
+        self.first_ns.get_or_insert(timestamp_ns);
+        self.end_ns =
+            timestamp_ns + (samples * 1_000_000_000 / self.header.sample_rate as u128) as u64;
+
+        Ok(())
+    }
+}
+
+impl Write for SegmentOutput {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> std::io::Result<usize> {
+        match self {
+            SegmentOutput::File(f) => f.write(buf),
+            SegmentOutput::Archive(m) => m.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            SegmentOutput::File(f) => f.flush(),
+            SegmentOutput::Archive(m) => m.flush(),
+        }
+    }
+}
+
+impl Seek for SegmentOutput {
+    fn seek(
+        &mut self,
+        pos: SeekFrom,
+    ) -> std::io::Result<u64> {
+        match self {
+            SegmentOutput::File(f) => f.seek(pos),
+            SegmentOutput::Archive(m) => m.seek(pos),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Шаблон путей сегментов для `output`: как есть, если в нём уже есть
+/// `{seq}`, иначе `_{seq}` перед расширением (`capture.glos` →
+/// `capture_{seq}.glos`).
+pub fn segment_template(output: &Path) -> PathBuf {
+    if output.to_string_lossy().contains(SEQ_FIELD) {
+        return output.to_path_buf();
+    }
+
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match output.extension() {
+        Some(ext) => format!("{stem}_{SEQ_FIELD}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{SEQ_FIELD}"),
+    };
+
+    output.with_file_name(name)
+}
+
+/// Парсит размер в байтах: `2GB`, `500MB`, `1.5GiB`, `65536`
+/// (регистронезависимо; KB/MB/GB/TB — степени 1000, KiB/MiB/GiB/TiB —
+/// степени 1024).
+pub fn parse_byte_size(s: &str) -> Result<u64, String> {
+    let lower = s.trim().to_lowercase();
+    let split = lower
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(lower.len());
+    let (num, unit) = lower.split_at(split);
+
+    let n: f64 = num
+        .parse()
+        .map_err(|e| format!("Invalid size '{s}': {e}"))?;
+
+    let multiplier: f64 = match unit.trim() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => {
+            return Err(format!(
+                "Unknown unit in size '{s}'. Use: B, KB, MB, GB, TB, KiB, MiB, GiB, TiB"
+            ))
+        }
+    };
+
+    let bytes = n * multiplier;
+    if !(bytes.is_finite() && bytes >= 1.0) {
+        return Err(format!("Size must be positive, got '{s}'"));
+    }
+
+    Ok(bytes as u64)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read},
+        net::TcpListener,
+    };
+
+    use glos_core::GlosHeaderExt;
+    use glos_types::SdrType;
+
+    use super::*;
+    use crate::NotifyConfig;
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("2GB"), Ok(2_000_000_000));
+        assert_eq!(parse_byte_size("1.5 GiB"), Ok(1_610_612_736));
+        assert_eq!(parse_byte_size("500mb"), Ok(500_000_000));
+        assert_eq!(parse_byte_size("65536"), Ok(65_536));
+        assert!(parse_byte_size("2PB").is_err());
+        assert!(parse_byte_size("0GB").is_err());
+        assert!(parse_byte_size("GB").is_err());
+    }
+
+    #[test]
+    fn test_segment_template() {
+        assert_eq!(
+            segment_template(Path::new("/data/capture.glos")),
+            PathBuf::from("/data/capture_{seq}.glos")
+        );
+        assert_eq!(
+            segment_template(Path::new("cap_{date}_{seq}.glos")),
+            PathBuf::from("cap_{date}_{seq}.glos")
+        );
+        assert_eq!(
+            segment_template(Path::new("capture")),
+            PathBuf::from("capture_{seq}")
+        );
+    }
+
+    #[test]
+    fn test_rotation_notifies_after_finalize() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(v) = line.strip_prefix("Content-Length: ") {
+                    length = v.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let notifier = Notifier::new(NotifyConfig {
+            webhook: Some(format!("http://127.0.0.1:{port}/").parse().unwrap()),
+            smtp: None,
+            events: vec!["rotated".parse().unwrap()],
+            timeout: Duration::from_secs(5),
+        });
+
+        let first = dir.path().join("rec_000.glos");
+        let header = GlosHeader::new(SdrType::Unknown, 1_000_000, 0);
+        let mut writer = RotatingWriter::create(
+            &first,
+            header,
+            CompressionOptions::default(),
+            RotationConfig {
+                max_bytes: None,
+                max_duration: Some(Duration::from_millis(1)),
+                path_template: dir.path().join("rec_{seq}.glos"),
+            },
+        )
+        .unwrap()
+        .with_notifier(notifier, RecorderMetrics::new());
+
+        // 1000 выборок при 1 Msps — 1 мс: второй блок начинает новый сегмент
+        for i in 0..2 {
+            writer
+                .write_block(IqBlock::new(i * 1_000_000, 1_000, vec![0; 4_000]))
+                .unwrap();
+        }
+
+        let body = server.join().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["event"], "rotated");
+        assert_eq!(json["prev"], first.display().to_string());
+        assert_eq!(
+            json["next"],
+            dir.path().join("rec_001.glos").display().to_string()
+        );
+
+        // Уведомление уходит, когда предыдущий сегмент уже финализирован
+        let mut reader = glos_core::GlosReader::new(std::fs::File::open(&first).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 1_000);
+        assert_eq!(glos_core::read_all_blocks(&mut reader).unwrap().len(), 1);
+
+        writer.finish_at(0).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use glos_core::LogEntry;
+use glos_types::SharedClock;
+use log::{Level, Log, Metadata, Record};
+use parking_lot::Mutex;
+
+/// Сколько событий журнал хранит за сессию; остальные только считаются.
+pub const SESSION_LOG_LIMIT: usize = 1_000;
+
+/// Журнал сессии: предупреждения и ошибки рекордера, которые сохраняются в
+/// метаданные записи ([`glos_core::RecordingMetadata::session_log`]).
+/// Клоны разделяют один журнал.
+#[derive(Debug, Clone)]
+pub struct SessionLog {
+    state: Arc<Mutex<LogState>>,
+    clock: SharedClock,
+}
+
+#[derive(Debug, Default)]
+struct LogState {
+    entries: Vec<LogEntry>,
+    dropped: u64,
+}
+
+/// Логгер-обёртка: всё передаёт `inner`, а события уровня `WARN` и выше
+/// дублирует в [`SessionLog`].
+#[derive(Debug)]
+pub struct SessionLogger<L> {
+    inner: L,
+    log: SessionLog,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SessionLog {
+    /// Журнал, отмечающий события по `clock` — тем же часам, что метки
+    /// времени блоков.
+    pub fn new(clock: SharedClock) -> Self {
+        Self {
+            state: Arc::default(),
+            clock,
+        }
+    }
+
+    pub fn record(
+        &self,
+        level: Level,
+        message: String,
+    ) {
+        let timestamp_ns = self.clock.unix_time_ns();
+        let mut state = self.state.lock();
+
+        if state.entries.len() >= SESSION_LOG_LIMIT {
+            state.dropped += 1;
+            return;
+        }
+
+        state.entries.push(LogEntry {
+            timestamp_ns,
+            level: level.to_string(),
+            message,
+        });
+    }
+
+    /// Забирает накопленные события (для метаданных записи), журнал
+    /// начинается заново. Не поместившиеся в лимит отмечаются последним
+    /// событием.
+    pub fn take(&self) -> Vec<LogEntry> {
+        let mut state = self.state.lock();
+        let mut entries = std::mem::take(&mut state.entries);
+
+        if state.dropped > 0 {
+            entries.push(LogEntry {
+                timestamp_ns: self.clock.unix_time_ns(),
+                level: Level::Warn.to_string(),
+                message: format!("{} more events not recorded", state.dropped),
+            });
+            state.dropped = 0;
+        }
+
+        entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<L: Log> SessionLogger<L> {
+    pub fn new(
+        inner: L,
+        log: SessionLog,
+    ) -> Self {
+        Self { inner, log }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl<L: Log> Log for SessionLogger<L> {
+    fn enabled(
+        &self,
+        metadata: &Metadata,
+    ) -> bool {
+        metadata.level() <= Level::Warn || self.inner.enabled(metadata)
+    }
+
+    fn log(
+        &self,
+        record: &Record,
+    ) {
+        if record.level() <= Level::Warn {
+            self.log.record(record.level(), record.args().to_string());
+        }
+
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use glos_types::VirtualClock;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct Counting(AtomicUsize);
+
+    impl Log for Counting {
+        fn enabled(
+            &self,
+            _: &Metadata,
+        ) -> bool {
+            true
+        }
+
+        fn log(
+            &self,
+            _: &Record,
+        ) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn emit(
+        logger: &impl Log,
+        level: Level,
+        message: &str,
+    ) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_logger_mirrors_warnings_with_clock_time() {
+        let clock = VirtualClock::new(1_700_000_000_000_000_000);
+        let log = SessionLog::new(clock.clone());
+        let logger = SessionLogger::new(Counting::default(), log.clone());
+
+        emit(&logger, Level::Info, "Recording started");
+        clock.advance(std::time::Duration::from_millis(1_500));
+        emit(&logger, Level::Warn, "Ring buffer full, dropped 3 chunks");
+        emit(&logger, Level::Error, "Device disconnected");
+
+        // Консоль получает всё
+        assert_eq!(logger.inner.0.load(Ordering::Relaxed), 3);
+
+        let entries = log.take();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].timestamp_ns, 1_700_000_001_500_000_000);
+        assert_eq!(entries[0].level, "WARN");
+        assert_eq!(entries[0].message, "Ring buffer full, dropped 3 chunks");
+        assert_eq!(entries[1].level, "ERROR");
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_log_limit_reports_dropped() {
+        let log = SessionLog::new(VirtualClock::new(0));
+
+        for i in 0..SESSION_LOG_LIMIT + 5 {
+            log.record(Level::Warn, format!("drop #{i}"));
+        }
+
+        let entries = log.take();
+        assert_eq!(entries.len(), SESSION_LOG_LIMIT + 1);
+        assert_eq!(
+            entries.last().unwrap().message,
+            "5 more events not recorded"
+        );
+        assert!(log.take().is_empty());
+    }
+}
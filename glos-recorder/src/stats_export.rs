@@ -0,0 +1,267 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::MetricsSummary;
+
+/// Формат файла с временным рядом метрик.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsFormat {
+    Csv,
+    Jsonl,
+}
+
+/// Пишет периодические snapshot'ы [`MetricsSummary`] рядом с записью.
+///
+/// Каждая строка сбрасывается на диск сразу, поэтому при аварийном
+/// завершении в файле остаётся вся история до момента сбоя.
+pub struct StatsExporter {
+    writer: BufWriter<File>,
+    format: StatsFormat,
+    path: PathBuf,
+}
+
+const CSV_HEADER: &str = "duration_secs,samples_recorded,blocks_written,dropped_samples,\
+write_errors,bytes_written,throughput_msps,write_speed_mbps,drop_rate_pct,\
+digest_mismatches,write_amplification,media_wear_pct,device_temperature_c,rssi_dbfs,\
+usb_errors,usb_throughput_mbps";
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl StatsFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StatsFormat::Csv => "stats.csv",
+            StatsFormat::Jsonl => "stats.jsonl",
+        }
+    }
+}
+
+impl StatsExporter {
+    /// Путь файла статистики: `rec.glos` → `rec.stats.csv`.
+    pub fn sidecar_path(
+        recording: &Path,
+        format: StatsFormat,
+    ) -> PathBuf {
+        recording.with_extension(format.extension())
+    }
+
+    /// Создаёт файл статистики (перезаписывая существующий).
+    pub fn create(
+        path: &Path,
+        format: StatsFormat,
+    ) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        if format == StatsFormat::Csv {
+            writeln!(writer, "{CSV_HEADER}")?;
+            writer.flush()?;
+        }
+
+        Ok(Self {
+            writer,
+            format,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Дописывает snapshot и сбрасывает буфер на диск.
+    pub fn write(
+        &mut self,
+        s: &MetricsSummary,
+    ) -> std::io::Result<()> {
+        let t = s.telemetry.unwrap_or_default();
+
+        match self.format {
+            StatsFormat::Csv => writeln!(
+                self.writer,
+                "{:.3},{},{},{},{},{},{:.6},{:.3},{:.4},{},{},{},{},{},{},{}",
+                s.duration_secs,
+                s.samples_recorded,
+                s.blocks_written,
+                s.dropped_samples,
+                s.write_errors,
+                s.bytes_written,
+                s.throughput_msps,
+                s.write_speed_mbps,
+                s.drop_rate_pct,
+                s.digest_mismatches,
+                s.write_amplification
+                    .map(|wa| format!("{wa:.3}"))
+                    .unwrap_or_default(),
+                s.media_wear_pct.map(|w| w.to_string()).unwrap_or_default(),
+                csv_field(t.temperature_c.map(|v| format!("{v:.1}"))),
+                csv_field(t.rssi_dbfs.map(|v| format!("{v:.1}"))),
+                csv_field(t.usb_errors.map(|v| v.to_string())),
+                csv_field(t.usb_throughput_mbps.map(|v| format!("{v:.3}"))),
+            )?,
+            StatsFormat::Jsonl => writeln!(
+                self.writer,
+                "{{\"duration_secs\":{:.3},\"samples_recorded\":{},\"blocks_written\":{},\
+                 \"dropped_samples\":{},\"write_errors\":{},\"bytes_written\":{},\
+                 \"throughput_msps\":{:.6},\"write_speed_mbps\":{:.3},\"drop_rate_pct\":{:.4},\
+                 \"digest_mismatches\":{},\"write_amplification\":{},\"media_wear_pct\":{},\
+                 \"device_temperature_c\":{},\"rssi_dbfs\":{},\"usb_errors\":{},\
+                 \"usb_throughput_mbps\":{}}}",
+                s.duration_secs,
+                s.samples_recorded,
+                s.blocks_written,
+                s.dropped_samples,
+                s.write_errors,
+                s.bytes_written,
+                s.throughput_msps,
+                s.write_speed_mbps,
+                s.drop_rate_pct,
+                s.digest_mismatches,
+                s.write_amplification
+                    .map_or("null".to_string(), |wa| format!("{wa:.3}")),
+                s.media_wear_pct
+                    .map_or("null".to_string(), |w| w.to_string()),
+                json_field(t.temperature_c.map(|v| format!("{v:.1}"))),
+                json_field(t.rssi_dbfs.map(|v| format!("{v:.1}"))),
+                json_field(t.usb_errors.map(|v| v.to_string())),
+                json_field(t.usb_throughput_mbps.map(|v| format!("{v:.3}"))),
+            )?,
+        }
+
+        self.writer.flush()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl FromStr for StatsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(StatsFormat::Csv),
+            "jsonl" | "ndjson" => Ok(StatsFormat::Jsonl),
+            _ => Err(format!("Unknown stats format '{s}'. Use: csv, jsonl")),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Пустое поле CSV для отсутствующего значения.
+fn csv_field(v: Option<String>) -> String {
+    v.unwrap_or_default()
+}
+
+/// `null` в JSON для отсутствующего значения.
+fn json_field(v: Option<String>) -> String {
+    v.unwrap_or_else(|| "null".to_string())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn summary(secs: f64) -> MetricsSummary {
+        MetricsSummary {
+            duration_secs: secs,
+            samples_recorded: 2_000_000,
+            blocks_written: 40,
+            dropped_samples: 10,
+            write_errors: 0,
+            bytes_written: 8_000_800,
+            throughput_msps: 2.0,
+            write_speed_mbps: 8.0,
+            drop_rate_pct: 0.0005,
+            digest_mismatches: 0,
+            write_amplification: None,
+            media_wear_pct: None,
+            ring_peak_chunks: 4,
+            telemetry: None,
+        }
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        let p = StatsExporter::sidecar_path(Path::new("/data/rec.glos"), StatsFormat::Csv);
+        assert_eq!(p, PathBuf::from("/data/rec.stats.csv"));
+
+        let p = StatsExporter::sidecar_path(Path::new("rec.glos"), StatsFormat::Jsonl);
+        assert_eq!(p, PathBuf::from("rec.stats.jsonl"));
+    }
+
+    #[test]
+    fn test_csv_rows_are_flushed() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rec.stats.csv");
+        let mut exporter = StatsExporter::create(&path, StatsFormat::Csv).unwrap();
+
+        exporter.write(&summary(1.0)).unwrap();
+        exporter.write(&summary(2.0)).unwrap();
+
+        // Читаем не закрывая экспортёр — данные уже должны быть на диске
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[2].starts_with("2.000,2000000,40,10,0,8000800,"));
+        assert_eq!(lines[1].split(',').count(), CSV_HEADER.split(',').count());
+        assert!(lines[1].ends_with(",,"));
+    }
+
+    #[test]
+    fn test_csv_device_telemetry_columns() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rec.stats.csv");
+        let mut exporter = StatsExporter::create(&path, StatsFormat::Csv).unwrap();
+
+        let mut s = summary(1.0);
+        s.telemetry = Some(crate::TelemetryReading {
+            temperature_c: Some(52.3),
+            rssi_dbfs: None,
+            usb_errors: Some(0),
+            usb_throughput_mbps: Some(8.0),
+        });
+        exporter.write(&s).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let row = text.lines().nth(1).unwrap();
+        assert!(row.ends_with(",52.3,,0,8.000"), "{row}");
+    }
+
+    #[test]
+    fn test_jsonl_format() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rec.stats.jsonl");
+        let mut exporter = StatsExporter::create(&path, StatsFormat::Jsonl).unwrap();
+
+        exporter.write(&summary(1.5)).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let line = text.lines().next().unwrap();
+
+        assert!(line.starts_with("{\"duration_secs\":1.500,"));
+        assert!(line.contains("\"dropped_samples\":10,"));
+        assert!(line.contains("\"write_amplification\":null,"));
+        assert!(line.contains("\"device_temperature_c\":null,"));
+        assert!(line.ends_with('}'));
+        assert_eq!("ndjson".parse::<StatsFormat>().unwrap(), StatsFormat::Jsonl);
+        assert!("xml".parse::<StatsFormat>().is_err());
+    }
+}
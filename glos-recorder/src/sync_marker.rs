@@ -0,0 +1,52 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use crossbeam_channel::{Receiver, Sender};
+
+/// Запрос метки синхронизации: номер и, если задано, время по внешним
+/// часам (нс).
+pub(crate) type SyncRequest = (u32, Option<u64>);
+
+/// Вставка меток синхронизации в идущую запись — для сведения записи с
+/// камерой, IMU и другими датчиками.
+///
+/// Метка встаёт между выборками: писатель закрывает копившийся блок и
+/// пишет за ним пустой блок с номером метки и временем по внешним часам.
+/// Клоны разделяют одну нумерацию; вызывать можно из любого потока.
+#[derive(Debug, Clone)]
+pub struct SyncMarkers {
+    next_id: Arc<AtomicU32>,
+    tx: Sender<SyncRequest>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SyncMarkers {
+    /// Нумерация с 1 и приёмник запросов для писателя.
+    pub(crate) fn new() -> (Self, Receiver<SyncRequest>) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let markers = Self {
+            next_id: Arc::new(AtomicU32::new(1)),
+            tx,
+        };
+
+        (markers, rx)
+    }
+
+    /// Ставит метку в текущее место записи. `external_ns` — время события
+    /// по внешним часам; без него берётся время хоста (с поправкой по
+    /// эталону) в момент вставки. Возвращает номер метки.
+    pub fn mark(
+        &self,
+        external_ns: Option<u64>,
+    ) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        // Писатель уже завершился — метку ставить некуда
+        let _ = self.tx.send((id, external_ns));
+        id
+    }
+}
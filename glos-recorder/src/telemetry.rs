@@ -0,0 +1,247 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use glos_hal::{DeviceTelemetry, TelemetrySource};
+use log::{info, warn};
+
+use crate::RecorderMetrics;
+
+/// Порог температуры устройства по умолчанию (°C): выше PlutoSDR и HackRF
+/// заметно теряют в чувствительности и стабильности опорного генератора.
+pub const DEFAULT_TEMP_WARN_C: f32 = 70.0;
+
+/// Настройки опроса датчиков устройства.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+    /// Как часто читать датчики
+    pub interval: Duration,
+    /// Предупреждать, когда температура достигает порога (°C)
+    pub temp_warn_c: Option<f32>,
+    /// Предупреждать, когда уровень на входе АЦП достигает порога (дБFS)
+    pub rssi_warn_dbfs: Option<f32>,
+    /// Предупреждать, когда ошибок USB за сессию набирается столько
+    pub usb_errors_warn: Option<u64>,
+}
+
+/// Последние показания датчиков в метриках записи.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TelemetryReading {
+    pub temperature_c: Option<f32>,
+    pub rssi_dbfs: Option<f32>,
+    pub usb_errors: Option<u64>,
+    /// Пропускная способность USB между двумя последними замерами (МБ/с)
+    pub usb_throughput_mbps: Option<f64>,
+}
+
+/// Периодически читает датчики устройства, обновляет [`RecorderMetrics`] и
+/// предупреждает о пересечении порогов.
+pub struct TelemetryMonitor {
+    config: TelemetryConfig,
+    source: Arc<dyn TelemetrySource>,
+    metrics: Arc<RecorderMetrics>,
+    /// Прошлый замер счётчика байт USB
+    last_usb: Option<(Instant, u64)>,
+    temp_warned: bool,
+    rssi_warned: bool,
+    usb_warned: bool,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl TelemetryMonitor {
+    pub fn new(
+        config: TelemetryConfig,
+        source: Arc<dyn TelemetrySource>,
+        metrics: Arc<RecorderMetrics>,
+    ) -> Self {
+        Self {
+            config,
+            source,
+            metrics,
+            last_usb: None,
+            temp_warned: false,
+            rssi_warned: false,
+            usb_warned: false,
+        }
+    }
+
+    /// Опрашивает датчики до закрытия `stop` (отправитель сброшен).
+    pub fn run(
+        &mut self,
+        stop: &Receiver<()>,
+    ) {
+        loop {
+            if !self.poll() {
+                return;
+            }
+
+            match stop.recv_timeout(self.config.interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    /// Один замер. Ошибка чтения отключает опрос (`false`).
+    pub fn poll(&mut self) -> bool {
+        match self.source.read() {
+            Ok(t) => {
+                self.check(&t, Instant::now());
+                true
+            }
+            Err(e) => {
+                warn!("Device telemetry: cannot read sensors: {e}. Disabling");
+                false
+            }
+        }
+    }
+
+    /// Публикует показания в метрики и один раз предупреждает о каждом
+    /// пересечении порога; предупреждение снова взводится, когда значение
+    /// возвращается ниже порога.
+    pub fn check(
+        &mut self,
+        t: &DeviceTelemetry,
+        at: Instant,
+    ) {
+        let usb_throughput_mbps = match (t.usb_bytes, self.last_usb) {
+            (Some(bytes), Some((prev_at, prev))) => {
+                let secs = at.saturating_duration_since(prev_at).as_secs_f64();
+                (secs > 1e-3).then(|| bytes.saturating_sub(prev) as f64 / secs / 1e6)
+            }
+            _ => None,
+        };
+        if let Some(bytes) = t.usb_bytes {
+            self.last_usb = Some((at, bytes));
+        }
+
+        self.metrics.set_device_telemetry(TelemetryReading {
+            temperature_c: t.temperature_c,
+            rssi_dbfs: t.rssi_dbfs,
+            usb_errors: t.usb_errors,
+            usb_throughput_mbps,
+        });
+
+        if let (Some(temp), Some(limit)) = (t.temperature_c, self.config.temp_warn_c) {
+            if temp >= limit && !self.temp_warned {
+                warn!(
+                    "⚠ Device temperature {temp:.1} °C reached the {limit:.1} °C threshold. Check cooling"
+                );
+            } else if temp < limit && self.temp_warned {
+                info!("Device temperature back to {temp:.1} °C");
+            }
+            self.temp_warned = temp >= limit;
+        }
+
+        if let (Some(rssi), Some(limit)) = (t.rssi_dbfs, self.config.rssi_warn_dbfs) {
+            if rssi >= limit && !self.rssi_warned {
+                warn!(
+                    "⚠ Input level {rssi:.1} dBFS reached the {limit:.1} dBFS threshold. Reduce gain"
+                );
+            }
+            self.rssi_warned = rssi >= limit;
+        }
+
+        if let (Some(errors), Some(limit)) = (t.usb_errors, self.config.usb_errors_warn) {
+            if errors >= limit && !self.usb_warned {
+                warn!("⚠ {errors} USB transfer errors this session. Check cable and hub");
+                self.usb_warned = true;
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            temp_warn_c: Some(DEFAULT_TEMP_WARN_C),
+            rssi_warn_dbfs: None,
+            usb_errors_warn: Some(1),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_hal::HalError;
+
+    use super::*;
+
+    struct Broken;
+
+    impl TelemetrySource for Broken {
+        fn read(&self) -> Result<DeviceTelemetry, HalError> {
+            Err(HalError::Unsupported)
+        }
+    }
+
+    fn reading(
+        temperature_c: f32,
+        usb_bytes: u64,
+    ) -> DeviceTelemetry {
+        DeviceTelemetry {
+            temperature_c: Some(temperature_c),
+            rssi_dbfs: None,
+            usb_errors: Some(0),
+            usb_bytes: Some(usb_bytes),
+        }
+    }
+
+    #[test]
+    fn test_telemetry_thresholds_and_throughput() {
+        let metrics = RecorderMetrics::new();
+        let mut monitor = TelemetryMonitor::new(
+            TelemetryConfig::default(),
+            Arc::new(Broken),
+            metrics.clone(),
+        );
+        let t0 = Instant::now();
+
+        monitor.check(&reading(45.0, 0), t0);
+        let r = metrics.device_telemetry().unwrap();
+        assert_eq!(r.temperature_c, Some(45.0));
+        assert_eq!(r.usb_throughput_mbps, None);
+        assert!(!monitor.temp_warned);
+
+        // 16 МБ за 2 с
+        monitor.check(&reading(71.5, 16_000_000), t0 + Duration::from_secs(2));
+        let r = metrics.device_telemetry().unwrap();
+        assert!((r.usb_throughput_mbps.unwrap() - 8.0).abs() < 1e-9);
+        assert!(monitor.temp_warned);
+
+        // Остыло — порог снова взведён
+        monitor.check(&reading(60.0, 32_000_000), t0 + Duration::from_secs(4));
+        assert!(!monitor.temp_warned);
+        assert!(!monitor.usb_warned);
+
+        let summary = metrics.summary(&t0);
+        assert_eq!(summary.telemetry, metrics.device_telemetry());
+    }
+
+    #[test]
+    fn test_telemetry_read_error_disables_polling() {
+        let metrics = RecorderMetrics::new();
+        let mut monitor = TelemetryMonitor::new(
+            TelemetryConfig::default(),
+            Arc::new(Broken),
+            metrics.clone(),
+        );
+
+        assert!(!monitor.poll());
+        assert_eq!(metrics.device_telemetry(), None);
+    }
+}
@@ -0,0 +1,453 @@
+use std::{
+    net::{ToSocketAddrs, UdpSocket},
+    path::PathBuf,
+    time::Duration,
+};
+
+use glos_types::{Clock, TimeSource};
+
+use crate::{RecorderError, RecorderResult};
+
+/// Порт NTP по умолчанию.
+pub const NTP_PORT: u16 = 123;
+
+/// Сколько SNTP-запросов делать: берётся ответ с наименьшей задержкой.
+pub const DEFAULT_NTP_SAMPLES: u32 = 4;
+
+/// Сколько секундных меток PPS усреднять (медиана).
+pub const DEFAULT_PPS_PULSES: u32 = 5;
+
+/// Ожидание ответа сервера или очередной секундной метки.
+pub const TIME_SYNC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Секунд от эпохи NTP (1900) до эпохи Unix (1970).
+const NTP_UNIX_OFFSET_SECS: u64 = 2_208_988_800;
+
+/// Внешний источник времени для меток блоков.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSyncConfig {
+    /// Поправка системных часов по SNTP-запросам к серверу
+    Ntp {
+        /// `host` или `host:port`
+        server: String,
+        samples: u32,
+    },
+    /// Поправка по секундной метке приёмника GNSS на линии DCD
+    /// последовательного порта (только Linux). Часы хоста должны уже
+    /// совпадать с UTC точнее полсекунды: PPS задаёт только фазу секунды
+    Pps { device: PathBuf, pulses: u32 },
+}
+
+/// Измеренная поправка к часам сессии.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockOffset {
+    pub source: TimeSource,
+    /// Эталон − часы хоста (нс): прибавляется к Unix-времени хоста
+    pub offset_ns: i64,
+    /// Оценка погрешности (нс): половина задержки NTP или разброс меток PPS
+    pub uncertainty_ns: u64,
+}
+
+/// Одно измерение SNTP: смещение и круговая задержка (нс).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct NtpSample {
+    offset_ns: i64,
+    delay_ns: i64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl TimeSyncConfig {
+    /// NTP-сервер с числом запросов по умолчанию.
+    pub fn ntp(server: impl Into<String>) -> Self {
+        Self::Ntp {
+            server: server.into(),
+            samples: DEFAULT_NTP_SAMPLES,
+        }
+    }
+
+    /// PPS на последовательном порту с числом меток по умолчанию.
+    pub fn pps(device: impl Into<PathBuf>) -> Self {
+        Self::Pps {
+            device: device.into(),
+            pulses: DEFAULT_PPS_PULSES,
+        }
+    }
+
+    pub fn source(&self) -> TimeSource {
+        match self {
+            Self::Ntp { .. } => TimeSource::Ntp,
+            Self::Pps { .. } => TimeSource::Pps,
+        }
+    }
+
+    /// Измеряет поправку к часам `clock`. Блокируется: NTP — на время
+    /// запросов, PPS — на `pulses` секунд.
+    pub fn estimate(
+        &self,
+        clock: &dyn Clock,
+    ) -> RecorderResult<ClockOffset> {
+        match self {
+            Self::Ntp { server, samples } => ntp_offset(server, *samples, clock),
+            Self::Pps { device, pulses } => pps_offset(device, *pulses, clock),
+        }
+    }
+}
+
+impl ClockOffset {
+    /// Unix-время хоста (нс), переведённое в шкалу эталона.
+    pub fn apply(
+        &self,
+        host_unix_ns: u64,
+    ) -> u64 {
+        host_unix_ns.saturating_add_signed(self.offset_ns)
+    }
+}
+
+impl NtpSample {
+    /// Смещение и задержка по четырём меткам обмена (Unix нс): отправка
+    /// запроса `t1`, приём сервером `t2`, ответ сервера `t3`, приём
+    /// ответа `t4`.
+    fn from_timestamps(
+        t1: u64,
+        t2: u64,
+        t3: u64,
+        t4: u64,
+    ) -> Self {
+        let (t1, t2, t3, t4) = (t1 as i128, t2 as i128, t3 as i128, t4 as i128);
+
+        Self {
+            offset_ns: (((t2 - t1) + (t3 - t4)) / 2) as i64,
+            delay_ns: ((t4 - t1) - (t3 - t2)).max(0) as i64,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// SNTP (RFC 4330): `samples` запросов, в зачёт идёт ответ с наименьшей
+/// круговой задержкой — у него асимметрия пути меньше всего искажает
+/// смещение.
+fn ntp_offset(
+    server: &str,
+    samples: u32,
+    clock: &dyn Clock,
+) -> RecorderResult<ClockOffset> {
+    let addr = if server.contains(':') {
+        server.to_string()
+    } else {
+        format!("{server}:{NTP_PORT}")
+    };
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| RecorderError::TimeSync(format!("cannot resolve {server}")))?;
+
+    let bind = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(bind)?;
+    socket.connect(addr)?;
+    socket.set_read_timeout(Some(TIME_SYNC_TIMEOUT))?;
+
+    let mut best: Option<NtpSample> = None;
+    let mut last_error = None;
+
+    for _ in 0..samples.max(1) {
+        match ntp_query(&socket, clock) {
+            Ok(s) => {
+                if best.is_none_or(|b| s.delay_ns < b.delay_ns) {
+                    best = Some(s);
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    let best = best.ok_or_else(|| {
+        RecorderError::TimeSync(format!(
+            "no NTP response from {server}: {}",
+            last_error.map_or_else(|| "unknown error".into(), |e| e.to_string())
+        ))
+    })?;
+
+    Ok(ClockOffset {
+        source: TimeSource::Ntp,
+        offset_ns: best.offset_ns,
+        uncertainty_ns: best.delay_ns as u64 / 2,
+    })
+}
+
+/// Один обмен SNTP по уже подключённому сокету.
+fn ntp_query(
+    socket: &UdpSocket,
+    clock: &dyn Clock,
+) -> RecorderResult<NtpSample> {
+    let mut request = [0u8; 48];
+    // LI = 0, VN = 4, Mode = 3 (клиент)
+    request[0] = 0x23;
+
+    let t1 = clock.unix_time_ns();
+    let origin = unix_ns_to_ntp(t1);
+    request[40..48].copy_from_slice(&origin.to_be_bytes());
+    socket.send(&request)?;
+
+    let mut reply = [0u8; 48];
+    let n = socket.recv(&mut reply)?;
+    let t4 = clock.unix_time_ns();
+
+    if n < 48 {
+        return Err(RecorderError::TimeSync(format!(
+            "short NTP reply: {n} bytes"
+        )));
+    }
+    if reply[0] & 0x07 != 4 {
+        return Err(RecorderError::TimeSync(
+            "NTP reply is not from a server".into(),
+        ));
+    }
+    if reply[1] == 0 {
+        return Err(RecorderError::TimeSync(
+            "NTP server sent kiss-of-death".into(),
+        ));
+    }
+    // Сервер возвращает нашу метку отправки как originate — иначе это
+    // ответ на чужой или старый запрос
+    if reply[24..32] != origin.to_be_bytes() {
+        return Err(RecorderError::TimeSync(
+            "NTP reply does not match request".into(),
+        ));
+    }
+
+    let read = |at: usize| u64::from_be_bytes(reply[at..at + 8].try_into().unwrap());
+    let t2 = ntp_to_unix_ns(read(32));
+    let t3 = ntp_to_unix_ns(read(40));
+
+    Ok(NtpSample::from_timestamps(t1, t2, t3, t4))
+}
+
+/// Unix нс → метка NTP (32 бита секунд с 1900 + 32 бита дробной части).
+fn unix_ns_to_ntp(ns: u64) -> u64 {
+    let secs = ns / 1_000_000_000 + NTP_UNIX_OFFSET_SECS;
+    let frac = ((ns % 1_000_000_000) << 32) / 1_000_000_000;
+
+    (secs << 32) | frac
+}
+
+/// Метка NTP → Unix нс (метки до 1970 дают 0).
+fn ntp_to_unix_ns(ntp: u64) -> u64 {
+    let secs = (ntp >> 32).saturating_sub(NTP_UNIX_OFFSET_SECS);
+    let frac = ((ntp & 0xFFFF_FFFF) * 1_000_000_000) >> 32;
+
+    secs * 1_000_000_000 + frac
+}
+
+/// Поправка по одной метке PPS, принятой в `host_unix_ns`: фронт
+/// отмечает начало секунды UTC, ближайшей к показаниям хоста.
+fn pps_pulse_offset(host_unix_ns: u64) -> i64 {
+    let second = (host_unix_ns + 500_000_000) / 1_000_000_000 * 1_000_000_000;
+    second as i64 - host_unix_ns as i64
+}
+
+/// Медиана поправок по меткам и их разброс (половина размаха).
+fn pps_offset_from_pulses(pulses: &[u64]) -> Option<(i64, u64)> {
+    let mut offsets: Vec<i64> = pulses.iter().map(|&t| pps_pulse_offset(t)).collect();
+    offsets.sort_unstable();
+
+    let median = *offsets.get(offsets.len() / 2)?;
+    let spread = (offsets[offsets.len() - 1] - offsets[0]) as u64 / 2;
+
+    Some((median, spread))
+}
+
+fn pps_offset(
+    device: &std::path::Path,
+    pulses: u32,
+    clock: &dyn Clock,
+) -> RecorderResult<ClockOffset> {
+    let stamps = pps::capture(device, pulses.max(1), clock)?;
+    let (offset_ns, uncertainty_ns) = pps_offset_from_pulses(&stamps)
+        .ok_or_else(|| RecorderError::TimeSync("no PPS pulses captured".into()))?;
+
+    Ok(ClockOffset {
+        source: TimeSource::Pps,
+        offset_ns,
+        uncertainty_ns,
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod pps {
+    use std::{fs::File, os::fd::AsRawFd, path::Path, sync::mpsc};
+
+    use glos_types::Clock;
+
+    use super::TIME_SYNC_TIMEOUT;
+    use crate::{RecorderError, RecorderResult};
+
+    /// Unix-время (нс) передних фронтов DCD. Ожидание фронта
+    /// (`TIOCMIWAIT`) не прерывается, поэтому идёт в отдельном потоке: без
+    /// сигнала PPS поток остаётся ждать, а запись продолжается без
+    /// привязки.
+    pub(super) fn capture(
+        device: &Path,
+        pulses: u32,
+        clock: &dyn Clock,
+    ) -> RecorderResult<Vec<u64>> {
+        let port = File::open(device)?;
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("glos-pps".into())
+            .spawn(move || {
+                for _ in 0..pulses {
+                    let edge = wait_rising_edge(&port);
+                    let failed = edge.is_err();
+                    if tx.send(edge).is_err() || failed {
+                        return;
+                    }
+                }
+            })?;
+
+        let mut stamps = Vec::with_capacity(pulses as usize);
+        for _ in 0..pulses {
+            match rx.recv_timeout(TIME_SYNC_TIMEOUT) {
+                Ok(Ok(())) => stamps.push(clock.unix_time_ns()),
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_) => {
+                    return Err(RecorderError::TimeSync(format!(
+                        "no PPS pulse on {} within {:?}",
+                        device.display(),
+                        TIME_SYNC_TIMEOUT
+                    )))
+                }
+            }
+        }
+
+        Ok(stamps)
+    }
+
+    fn wait_rising_edge(port: &File) -> std::io::Result<()> {
+        let fd = port.as_raw_fd();
+
+        loop {
+            // SAFETY: fd открыт и принадлежит `port` на всё время вызова
+            if unsafe { libc::ioctl(fd, libc::TIOCMIWAIT, libc::TIOCM_CD) } < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+
+            let mut status: libc::c_int = 0;
+            // SAFETY: TIOCMGET пишет один c_int по переданному указателю
+            if unsafe { libc::ioctl(fd, libc::TIOCMGET, &mut status) } < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if status & libc::TIOCM_CD != 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod pps {
+    use std::path::Path;
+
+    use glos_types::Clock;
+
+    use crate::{RecorderError, RecorderResult};
+
+    pub(super) fn capture(
+        _device: &Path,
+        _pulses: u32,
+        _clock: &dyn Clock,
+    ) -> RecorderResult<Vec<u64>> {
+        Err(RecorderError::TimeSync(
+            "PPS via serial port is only supported on Linux".into(),
+        ))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::SystemClock;
+
+    use super::*;
+
+    #[test]
+    fn test_ntp_timestamp_conversion() {
+        let ns = 1_700_000_000_123_456_789;
+        let back = ntp_to_unix_ns(unix_ns_to_ntp(ns));
+
+        // Дробная часть NTP — шаг ~0.23 нс
+        assert!(ns - back <= 1);
+        assert_eq!(unix_ns_to_ntp(0) >> 32, NTP_UNIX_OFFSET_SECS);
+
+        // Часы сервера на 5 мс впереди, путь 3 мс в каждую сторону,
+        // обработка 1 мс
+        let s = NtpSample::from_timestamps(100_000_000, 108_000_000, 109_000_000, 107_000_000);
+        assert_eq!(s.offset_ns, 5_000_000);
+        assert_eq!(s.delay_ns, 6_000_000);
+    }
+
+    #[test]
+    fn test_pps_offset_from_pulses() {
+        // Хост отстаёт на 1.2 мс: фронты приходят за 1.2 мс до целой секунды
+        let pulses = [
+            9_998_800_000,
+            10_998_790_000,
+            11_998_810_000,
+            12_998_800_000,
+        ];
+        let (offset, spread) = pps_offset_from_pulses(&pulses).unwrap();
+        assert_eq!(offset, 1_200_000);
+        assert_eq!(spread, 10_000);
+
+        // Хост спешит: фронт уже после начала секунды
+        assert_eq!(pps_pulse_offset(5_000_300_000), -300_000);
+        assert!(pps_offset_from_pulses(&[]).is_none());
+    }
+
+    #[test]
+    fn test_ntp_offset_against_local_server() {
+        // Сервер, чьи часы на 250 мс впереди системных
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let skew = 250_000_000u64;
+
+        std::thread::spawn(move || {
+            let clock = SystemClock::new();
+            let mut buf = [0u8; 48];
+            while let Ok((_, peer)) = server.recv_from(&mut buf) {
+                let now = unix_ns_to_ntp(clock.unix_time_ns() + skew);
+                let mut reply = [0u8; 48];
+                reply[0] = 0x24; // VN = 4, Mode = 4 (сервер)
+                reply[1] = 1; // stratum
+                reply[24..32].copy_from_slice(&buf[40..48]);
+                reply[32..40].copy_from_slice(&now.to_be_bytes());
+                reply[40..48].copy_from_slice(&now.to_be_bytes());
+                server.send_to(&reply, peer).unwrap();
+            }
+        });
+
+        let config = TimeSyncConfig::ntp(addr.to_string());
+        let offset = config.estimate(&SystemClock::new()).unwrap();
+
+        assert_eq!(offset.source, TimeSource::Ntp);
+        assert!(
+            (offset.offset_ns - skew as i64).abs() < 20_000_000,
+            "offset {} ns",
+            offset.offset_ns
+        );
+        assert_eq!(offset.apply(1_000), 1_000 + offset.offset_ns as u64);
+    }
+}
@@ -0,0 +1,611 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, Arc},
+};
+
+use glos_core::{block_digest, GlosWriter, IqBlockExt};
+use glos_types::{Compression, IqBlock};
+use log::{info, warn};
+use parking_lot::{Condvar, Mutex};
+
+use crate::{BlockBuffers, RecorderMetrics, RecorderResult};
+
+/// Объём очереди записи в RAM по умолчанию (64 МБ).
+pub const DEFAULT_WRITE_QUEUE_BYTES: usize = 64_000_000;
+
+/// Флаги и XXH64 перед блоком в файле перелива.
+const SPILL_DIGEST_SIZE: usize = 9;
+
+/// Флаг перелива: за ним идёт дайджест.
+const SPILL_HAS_DIGEST: u8 = 0x01;
+
+/// Флаг перелива: данные блока уже сжаты (см. [`crate::CompressPool`]).
+const SPILL_COMPRESSED: u8 = 0x02;
+
+/// Настройки очереди между накоплением блоков и записью на диск.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteQueueConfig {
+    /// Сколько байт IQ данных держать в памяти. Один блок принимается
+    /// всегда, даже если он больше лимита
+    pub memory_cap_bytes: usize,
+    /// Каталог временного файла перелива. Блоки сверх лимита уходят туда
+    /// и дописываются в запись по порядку (None = ждать писателя, захват
+    /// при этом упирается в кольцевой буфер)
+    pub spill_dir: Option<PathBuf>,
+    /// Двойная проверка: XXH64 данных блока считается по chunk'ам при
+    /// приёме и сверяется с несжатыми данными перед сериализацией. Ловит
+    /// порчу в RAM при накоплении, сжатии и в очереди до подсчёта CRC (он
+    /// сам такую порчу «узаконит»)
+    pub verify_digests: bool,
+}
+
+/// Очередь блоков с ограниченной памятью и необязательным переливом во
+/// временный файл.
+///
+/// Поток накопления кладёт блоки через [`push`](Self::push), поток записи
+/// забирает их через [`pop`](Self::pop) в том же порядке. Если диск или
+/// сжатие ненадолго не успевают, блоки копятся в RAM до
+/// [`WriteQueueConfig::memory_cap_bytes`], затем — в файле перелива; без
+/// него `push` ждёт, пока писатель освободит место.
+pub struct WriteQueue {
+    config: WriteQueueConfig,
+    metrics: Arc<RecorderMetrics>,
+    state: Mutex<QueueState>,
+    /// Появился блок или очередь закрыта
+    ready: Condvar,
+    /// Освободилось место в памяти
+    space: Condvar,
+    /// Куда вернуть буферы записанных блоков
+    buffers: Option<BlockBuffers>,
+}
+
+/// Блок в очереди и дайджест его несжатых данных на момент приёма.
+struct Queued {
+    block: IqBlock,
+    digest: Option<u64>,
+}
+
+struct QueueState {
+    memory: VecDeque<Queued>,
+    memory_bytes: usize,
+    spill: Option<SpillFile>,
+    /// Перелив не удалось создать или записать — дальше только ожидание
+    spill_failed: bool,
+    closed: bool,
+}
+
+/// Приёмник блоков для [`WriteQueue::drain_into`]: один файл
+/// ([`GlosWriter`]) или серия файлов ([`crate::RotatingWriter`]).
+pub trait BlockSink {
+    fn write_block(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<()>;
+
+    /// Как `write_block`, но отдаёт опустевший буфер данных блока для
+    /// следующего; `None` — приёмник буфер не возвращает.
+    fn write_block_reclaim(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<Option<Vec<u8>>> {
+        self.write_block(block).map(|()| None)
+    }
+}
+
+/// См. [`WriteQueue::close_on_drop`].
+#[must_use]
+pub struct CloseOnDrop<'a>(&'a WriteQueue);
+
+/// FIFO из сериализованных блоков во временном файле. Перед каждым блоком
+/// лежит его дайджест из очереди ([`SPILL_DIGEST_SIZE`]). Когда все блоки
+/// прочитаны, файл обрезается до нуля.
+struct SpillFile {
+    file: File,
+    read_pos: u64,
+    write_pos: u64,
+    blocks: usize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl WriteQueue {
+    pub fn new(
+        config: WriteQueueConfig,
+        metrics: Arc<RecorderMetrics>,
+    ) -> Self {
+        Self {
+            config,
+            metrics,
+            state: Mutex::new(QueueState {
+                memory: VecDeque::new(),
+                memory_bytes: 0,
+                spill: None,
+                spill_failed: false,
+                closed: false,
+            }),
+            ready: Condvar::new(),
+            space: Condvar::new(),
+            buffers: None,
+        }
+    }
+
+    /// Буферы записанных блоков возвращаются в `buffers` (если приёмник
+    /// их отдаёт, см. [`BlockSink::write_block_reclaim`]).
+    pub fn with_buffers(
+        mut self,
+        buffers: BlockBuffers,
+    ) -> Self {
+        self.buffers = Some(buffers);
+        self
+    }
+
+    /// Ставит блок в очередь. `digest` — XXH64 несжатых данных блока,
+    /// посчитанный при их приёме ([`glos_core::Xxh64`]); с ним блок
+    /// сверяется перед записью. Не теряет блоки: при ошибке перелива он
+    /// отключается, и `push` переходит к ожиданию писателя.
+    pub fn push(
+        &self,
+        block: IqBlock,
+        digest: Option<u64>,
+    ) {
+        let len = block.data.len();
+        let item = Queued { block, digest };
+        let mut state = self.state.lock();
+
+        loop {
+            // Пока в переливе есть блоки, новые идут за ними — иначе
+            // нарушится порядок
+            let spilling = state.spill.as_ref().is_some_and(|s| s.blocks > 0);
+
+            if !spilling
+                && (state.memory.is_empty()
+                    || state.memory_bytes + len <= self.config.memory_cap_bytes)
+            {
+                state.memory_bytes += len;
+                state.memory.push_back(item);
+                self.metrics
+                    .queue_peak_bytes
+                    .fetch_max(state.memory_bytes as u64, Ordering::Relaxed);
+                break;
+            }
+
+            if self.config.spill_dir.is_some() && !state.spill_failed {
+                match self.spill(&mut state, &item) {
+                    Ok(()) => {
+                        self.metrics.spilled_blocks.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("Write queue spill failed: {e}. Waiting for the writer instead");
+                        state.spill_failed = true;
+                        continue;
+                    }
+                }
+            }
+
+            self.metrics.queue_stalls.fetch_add(1, Ordering::Relaxed);
+            self.space.wait(&mut state);
+        }
+
+        self.ready.notify_one();
+    }
+
+    /// Следующий блок по порядку. `None` — очередь закрыта и пуста.
+    ///
+    /// Если перелив не читается, его блоки считаются ошибками записи, а
+    /// очередь дальше работает без перелива.
+    pub fn pop(&self) -> Option<IqBlock> {
+        self.pop_queued().map(|q| q.block)
+    }
+
+    fn pop_queued(&self) -> Option<Queued> {
+        let mut state = self.state.lock();
+
+        loop {
+            if let Some(item) = state.memory.pop_front() {
+                state.memory_bytes -= item.block.data.len();
+                self.space.notify_one();
+                return Some(item);
+            }
+
+            if let Some(spill) = state.spill.as_mut().filter(|s| s.blocks > 0) {
+                let result = spill.pop();
+                let lost = spill.blocks as u64 + 1;
+
+                // Освободился перелив — продюсер мог ждать именно его
+                self.space.notify_one();
+
+                match result {
+                    Ok(item) => return Some(item),
+                    Err(e) => {
+                        warn!("Cannot read write queue spill back: {e}. {lost} blocks lost");
+                        self.metrics.write_errors.fetch_add(lost, Ordering::Relaxed);
+                        state.spill = None;
+                        state.spill_failed = true;
+                        continue;
+                    }
+                }
+            }
+
+            if state.closed {
+                return None;
+            }
+
+            self.ready.wait(&mut state);
+        }
+    }
+
+    /// Новых блоков не будет: писатель дописывает остаток и выходит.
+    pub fn close(&self) {
+        self.state.lock().closed = true;
+        self.ready.notify_all();
+    }
+
+    /// Закрывает очередь при выходе из области видимости, в том числе по
+    /// ошибке или панике накопления.
+    pub fn close_on_drop(&self) -> CloseOnDrop<'_> {
+        CloseOnDrop(self)
+    }
+
+    /// Пишет блоки из очереди, пока она не закрыта и не опустела. Ошибки
+    /// записи считаются в метриках и не прерывают цикл.
+    ///
+    /// Блок с дайджестом сверяется по несжатым данным (сжатый —
+    /// распаковывается) прямо перед сериализацией. Изменившийся в памяти
+    /// после приёма блок всё равно пишется (иначе в записи будет дыра), но
+    /// считается в `digest_mismatches`, а его номер попадает в лог.
+    pub fn drain_into<S: BlockSink>(
+        &self,
+        writer: &mut S,
+    ) {
+        let mut index: u64 = 0;
+
+        while let Some(Queued { block, digest }) = self.pop_queued() {
+            if digest.is_some_and(|d| Some(d) != payload_digest(&block)) {
+                self.metrics
+                    .digest_mismatches
+                    .fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Block #{index} @ {} ns changed in RAM after it was received (digest mismatch)",
+                    block.timestamp_ns
+                );
+            }
+            index += 1;
+
+            let block_bytes = block.frame_size() as u64;
+
+            match writer.write_block_reclaim(block) {
+                Ok(buf) => {
+                    if let (Some(buffers), Some(buf)) = (&self.buffers, buf) {
+                        buffers.give(buf);
+                    }
+                    self.metrics.blocks_written.fetch_add(1, Ordering::Relaxed);
+                    self.metrics
+                        .bytes_written
+                        .fetch_add(block_bytes, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    self.metrics.write_errors.fetch_add(1, Ordering::Relaxed);
+                    warn!("Write error: {e}");
+
+                    if e.is_disk_full() {
+                        self.metrics.disk_full.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+
+    fn spill(
+        &self,
+        state: &mut QueueState,
+        item: &Queued,
+    ) -> RecorderResult<()> {
+        if state.spill.is_none() {
+            let dir = self.config.spill_dir.as_deref().unwrap_or(Path::new("."));
+            state.spill = Some(SpillFile::new(tempfile::tempfile_in(dir)?));
+            info!("Write queue: memory cap reached, spilling to {dir:?}");
+        }
+
+        match state.spill.as_mut() {
+            Some(s) => s.push(item),
+            None => Ok(()),
+        }
+    }
+}
+
+impl SpillFile {
+    fn new(file: File) -> Self {
+        Self {
+            file,
+            read_pos: 0,
+            write_pos: 0,
+            blocks: 0,
+        }
+    }
+
+    fn push(
+        &mut self,
+        item: &Queued,
+    ) -> RecorderResult<()> {
+        let bytes = item.block.serialize()?;
+
+        let mut digest = [0u8; SPILL_DIGEST_SIZE];
+        if let Some(d) = item.digest {
+            digest[0] = SPILL_HAS_DIGEST;
+            digest[1..].copy_from_slice(&d.to_be_bytes());
+        }
+        if item.block.is_compressed {
+            digest[0] |= SPILL_COMPRESSED;
+        }
+
+        self.file.seek(SeekFrom::Start(self.write_pos))?;
+        self.file.write_all(&digest)?;
+        self.file.write_all(&bytes)?;
+        self.write_pos += (digest.len() + bytes.len()) as u64;
+        self.blocks += 1;
+
+        Ok(())
+    }
+
+    fn pop(&mut self) -> RecorderResult<Queued> {
+        self.blocks -= 1;
+        self.file.seek(SeekFrom::Start(self.read_pos))?;
+
+        let mut digest = [0u8; SPILL_DIGEST_SIZE];
+        self.file.read_exact(&mut digest)?;
+        let compressed = digest[0] & SPILL_COMPRESSED != 0;
+        let digest = (digest[0] & SPILL_HAS_DIGEST != 0)
+            .then(|| u64::from_be_bytes(digest[1..].try_into().unwrap_or_default()));
+
+        // Первые 4 байта блока — размер содержимого без себя и CRC
+        let mut size = [0u8; 4];
+        self.file.read_exact(&mut size)?;
+        let total = u32::from_be_bytes(size) as usize + 8;
+
+        let mut buf = vec![0u8; total];
+        buf[..4].copy_from_slice(&size);
+        self.file.read_exact(&mut buf[4..])?;
+
+        self.read_pos += (SPILL_DIGEST_SIZE + total) as u64;
+
+        if self.blocks == 0 {
+            self.read_pos = 0;
+            self.write_pos = 0;
+            self.file.set_len(0)?;
+        }
+
+        // Обычно очередь держит блоки до сжатия, но их могли сжать и раньше
+        let (mut block, _) = IqBlock::deserialize(&buf, Compression::None)?;
+        block.is_compressed = compressed;
+        Ok(Queued { block, digest })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl<W: Write + Seek> BlockSink for GlosWriter<W> {
+    fn write_block(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<()> {
+        Ok(GlosWriter::write_block(self, block)?)
+    }
+
+    fn write_block_reclaim(
+        &mut self,
+        block: IqBlock,
+    ) -> RecorderResult<Option<Vec<u8>>> {
+        Ok(Some(GlosWriter::write_block_reclaim(self, block)?))
+    }
+}
+
+impl Drop for CloseOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+impl Default for WriteQueueConfig {
+    fn default() -> Self {
+        Self {
+            memory_cap_bytes: DEFAULT_WRITE_QUEUE_BYTES,
+            spill_dir: None,
+            verify_digests: false,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// XXH64 несжатых данных блока; `None` — сжатые данные не распаковались.
+fn payload_digest(block: &IqBlock) -> Option<u64> {
+    if !block.is_compressed {
+        return Some(block_digest(&block.data));
+    }
+
+    block
+        .get_uncompressed_data()
+        .ok()
+        .map(|data| block_digest(&data))
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::GlosHeaderExt;
+    use glos_types::{GlosHeader, SdrType};
+
+    use super::*;
+
+    fn block(i: u64) -> IqBlock {
+        IqBlock::new(i * 1_000, 256, vec![i as u8; 1024])
+    }
+
+    #[test]
+    fn test_spill_keeps_order_and_bounds_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let metrics = RecorderMetrics::new();
+        let queue = WriteQueue::new(
+            WriteQueueConfig {
+                memory_cap_bytes: 2048,
+                spill_dir: Some(dir.path().to_path_buf()),
+                verify_digests: true,
+            },
+            metrics.clone(),
+        );
+
+        // Писатель стоит: всё сверх двух блоков уходит в перелив. Нечётные
+        // блоки как будто уже сжаты до очереди
+        for i in 0..10 {
+            let mut b = block(i);
+            b.is_compressed = i % 2 == 1;
+            let digest = block_digest(&b.data);
+            queue.push(b, Some(digest));
+        }
+        for i in 0..10 {
+            let b = queue.pop().unwrap();
+            assert_eq!(b.timestamp_ns, i * 1_000);
+            assert_eq!(b.is_compressed, i % 2 == 1);
+        }
+        for i in 10..20 {
+            queue.push(block(i), None);
+        }
+        // Перелив опустел — новые блоки снова сначала идут в память
+        queue.close();
+
+        let rest: Vec<u64> = std::iter::from_fn(|| queue.pop())
+            .map(|b| {
+                assert_eq!(b.data, vec![(b.timestamp_ns / 1_000) as u8; 1024]);
+                b.timestamp_ns / 1_000
+            })
+            .collect();
+
+        assert_eq!(rest, (10..20).collect::<Vec<_>>());
+        assert_eq!(metrics.spilled_blocks.load(Ordering::Relaxed), 16);
+        assert!(metrics.queue_peak_bytes.load(Ordering::Relaxed) <= 2048);
+        assert_eq!(metrics.write_errors.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_without_spill_push_waits_for_writer() {
+        let metrics = RecorderMetrics::new();
+        let queue = WriteQueue::new(
+            WriteQueueConfig {
+                memory_cap_bytes: 1024,
+                spill_dir: None,
+                verify_digests: false,
+            },
+            metrics.clone(),
+        );
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..50 {
+                    queue.push(block(i), None);
+                }
+                queue.close();
+            });
+
+            let got: Vec<u64> = std::iter::from_fn(|| queue.pop())
+                .map(|b| b.timestamp_ns / 1_000)
+                .collect();
+            assert_eq!(got, (0..50).collect::<Vec<_>>());
+        });
+
+        assert_eq!(metrics.queue_peak_bytes.load(Ordering::Relaxed), 1024);
+        assert_eq!(metrics.spilled_blocks.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_verify_digests_detects_ram_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let metrics = RecorderMetrics::new();
+        let queue = WriteQueue::new(
+            WriteQueueConfig {
+                memory_cap_bytes: 2048,
+                spill_dir: Some(dir.path().to_path_buf()),
+                verify_digests: true,
+            },
+            metrics.clone(),
+        );
+
+        for i in 0..6 {
+            let b = block(i);
+            let digest = block_digest(&b.data);
+            queue.push(b, Some(digest));
+        }
+
+        // Бит «перевернулся» в памяти уже после постановки в очередь
+        queue.state.lock().memory[1].block.data[100] ^= 0x04;
+        queue.close();
+
+        let header = GlosHeader::new(SdrType::Unknown, 2_000_000, 0);
+        let mut writer = GlosWriter::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        queue.drain_into(&mut writer);
+
+        // Остальные блоки, в том числе прошедшие через перелив, целы
+        assert_eq!(metrics.digest_mismatches.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.spilled_blocks.load(Ordering::Relaxed), 4);
+        assert_eq!(metrics.blocks_written.load(Ordering::Relaxed), 6);
+        assert_eq!(writer.block_count(), 6);
+    }
+
+    #[test]
+    fn test_verify_digests_checks_uncompressed_payload() {
+        let metrics = RecorderMetrics::new();
+        let queue = WriteQueue::new(WriteQueueConfig::default(), metrics.clone());
+
+        // Дайджест посчитан до сжатия и сверяется с распакованными данными
+        for i in 0..3 {
+            let mut b = IqBlock::new(i * 1_000, 256, (0..1024).map(|j| (j % 7) as u8).collect());
+            let digest = block_digest(&b.data);
+            b.compress().unwrap();
+            if i == 2 {
+                b.data[20] ^= 0x10;
+            }
+            queue.push(b, Some(digest));
+        }
+        queue.close();
+
+        let header = GlosHeader::new(SdrType::Unknown, 2_000_000, 0);
+        let mut writer = GlosWriter::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        queue.drain_into(&mut writer);
+
+        assert_eq!(metrics.digest_mismatches.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_drain_returns_block_buffers() {
+        let buffers = BlockBuffers::new(1024);
+        let queue = WriteQueue::new(WriteQueueConfig::default(), RecorderMetrics::new())
+            .with_buffers(buffers.clone());
+
+        let mut data = buffers.take();
+        data.extend_from_slice(&[7; 1024]);
+        let ptr = data.as_ptr();
+        queue.push(IqBlock::new(0, 256, data), None);
+        queue.close();
+
+        let header = GlosHeader::new(SdrType::Unknown, 2_000_000, 0);
+        let mut writer = GlosWriter::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        queue.drain_into(&mut writer);
+
+        // Записанный блок вернул свой буфер в запас
+        assert_eq!(writer.block_count(), 1);
+        let reused = buffers.take();
+        assert_eq!(reused.as_ptr(), ptr);
+    }
+}
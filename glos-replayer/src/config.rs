@@ -1,13 +1,67 @@
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use glos_core::{dsp::ResampleQuality, UdpCompression, UdpProtocol};
+use glos_types::{SharedClock, SystemClock};
+
+use crate::{FeedbackConfig, FileSinkConfig, ImpairmentConfig, PreloadConfig};
+
+/// `input_path`, означающий стандартный ввод (`ssh host cat rec.glos |`).
+pub const STDIN_INPUT: &str = "-";
 
 #[derive(Debug, Clone)]
 pub struct ReplayConfig {
+    /// Входной файл или [`STDIN_INPUT`]
     pub input_path: PathBuf,
     pub target_addr: SocketAddr,
     pub speed: f64,
     pub loop_playback: bool,
     pub stats_interval_secs: u64,
     pub bind_addr: SocketAddr,
+    /// Версия UDP-протокола; v2 добавляет дайджест блока для сквозной
+    /// проверки на приёмнике
+    pub protocol: UdpProtocol,
+    /// Сжатие данных блоков в UDP-потоке (только протокол v2)
+    pub compression: UdpCompression,
+    /// Расписание смены скорости (None = постоянная `speed`)
+    pub speed_profile: Option<SpeedProfile>,
+    /// Управление потоком по обратной связи от приёмника (None = выключено)
+    pub feedback: Option<FeedbackConfig>,
+    /// Передискретизация на лету перед отправкой (None = как в файле)
+    pub resample: Option<ResampleConfig>,
+    /// Искусственные потери / задержки / перестановки (None = выключено)
+    pub impairments: Option<ImpairmentConfig>,
+    /// Писать поток в новый .glos вместо UDP (None = UDP на `target_addr`)
+    pub file_sink: Option<FileSinkConfig>,
+    /// Предзагрузка входного файла до старта (не для stdin)
+    pub preload: PreloadConfig,
+    /// Часы для pacing'а и расписания скорости (в тестах — `VirtualClock`)
+    pub clock: SharedClock,
+}
+
+/// Параметры передискретизации при воспроизведении.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResampleConfig {
+    /// Целевая частота дискретизации, Гц
+    pub out_rate: u32,
+    pub quality: ResampleQuality,
+}
+
+/// Одна ступень профиля скорости.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedStep {
+    /// Коэффициент скорости на этой ступени
+    pub speed: f64,
+    /// Длительность ступени (реальное время)
+    pub duration: Duration,
+}
+
+/// Ступенчатый профиль скорости воспроизведения.
+///
+/// Ступени идут подряд от начала сессии; после окончания расписания
+/// сохраняется скорость последней ступени.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedProfile {
+    steps: Vec<SpeedStep>,
 }
 
 impl ReplayConfig {
@@ -19,8 +73,93 @@ impl ReplayConfig {
             loop_playback: false,
             stats_interval_secs: 5,
             bind_addr: "0.0.0.0:0".parse().unwrap(),
+            protocol: UdpProtocol::V1,
+            compression: UdpCompression::None,
+            speed_profile: None,
+            feedback: None,
+            resample: None,
+            impairments: None,
+            file_sink: None,
+            preload: PreloadConfig::default(),
+            clock: SystemClock::shared(),
         }
     }
+
+    /// Вход — стандартный ввод: поток читается один раз, перемотать его
+    /// для повтора нельзя.
+    pub fn reads_stdin(&self) -> bool {
+        self.input_path.as_os_str() == STDIN_INPUT
+    }
+}
+
+impl SpeedProfile {
+    /// Создаёт профиль из набора ступеней. Пустой профиль недопустим.
+    pub fn new(steps: Vec<SpeedStep>) -> Result<Self, String> {
+        if steps.is_empty() {
+            return Err("Speed profile must contain at least one step".to_string());
+        }
+
+        if let Some(bad) = steps
+            .iter()
+            .find(|s| !s.speed.is_finite() || s.speed <= 0.0)
+        {
+            return Err(format!(
+                "Speed profile step speed must be > 0, got {}",
+                bad.speed
+            ));
+        }
+
+        Ok(Self { steps })
+    }
+
+    pub fn steps(&self) -> &[SpeedStep] {
+        &self.steps
+    }
+
+    /// Скорость, действующая через `elapsed` от начала сессии.
+    pub fn speed_at(
+        &self,
+        elapsed: Duration,
+    ) -> f64 {
+        let mut boundary = Duration::ZERO;
+
+        for step in &self.steps {
+            boundary += step.duration;
+            if elapsed < boundary {
+                return step.speed;
+            }
+        }
+
+        self.steps.last().map(|s| s.speed).unwrap_or(1.0)
+    }
+
+    /// Суммарная длительность расписания.
+    pub fn total_duration(&self) -> Duration {
+        self.steps.iter().map(|s| s.duration).sum()
+    }
+
+    /// Реальное время воспроизведения `file` секунд записи по профилю: на
+    /// ступени файл идёт в `speed` раз быстрее, после расписания — со
+    /// скоростью последней ступени.
+    pub fn playback_duration(
+        &self,
+        file: Duration,
+    ) -> Duration {
+        let mut remaining = file.as_secs_f64();
+        let mut real = 0.0;
+
+        for step in &self.steps {
+            let covered = step.speed * step.duration.as_secs_f64();
+            if remaining <= covered {
+                return Duration::from_secs_f64(real + remaining / step.speed);
+            }
+            remaining -= covered;
+            real += step.duration.as_secs_f64();
+        }
+
+        let last = self.steps.last().map(|s| s.speed).unwrap_or(1.0);
+        Duration::from_secs_f64(real + remaining / last)
+    }
 }
 
 impl Default for ReplayConfig {
@@ -28,3 +167,117 @@ impl Default for ReplayConfig {
         Self::new()
     }
 }
+
+impl std::fmt::Display for SpeedProfile {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}", step.speed, step.duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SpeedProfile {
+    type Err = String;
+
+    /// Парсит `speed:secs[,speed:secs...]`, например `1.0:60,2.0:60,4.0:60`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = s
+            .split(',')
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let (speed_str, secs_str) = part
+                    .split_once(':')
+                    .ok_or_else(|| format!("Invalid speed step '{part}': expected speed:secs"))?;
+
+                let speed: f64 = speed_str
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid speed '{speed_str}': {e}"))?;
+
+                let secs: f64 = secs_str
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("Invalid duration '{secs_str}': {e}"))?;
+
+                if !secs.is_finite() || secs <= 0.0 {
+                    return Err(format!("Step duration must be > 0, got '{secs_str}'"));
+                }
+
+                Ok(SpeedStep {
+                    speed,
+                    duration: Duration::from_secs_f64(secs),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        SpeedProfile::new(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_profile_parse() {
+        let p: SpeedProfile = "1.0:60,2.0:60,4.0:60".parse().unwrap();
+
+        assert_eq!(p.steps().len(), 3);
+        assert_eq!(p.steps()[1].speed, 2.0);
+        assert_eq!(p.steps()[2].duration, Duration::from_secs(60));
+        assert_eq!(p.total_duration(), Duration::from_secs(180));
+        assert_eq!(p.to_string(), "1:60,2:60,4:60");
+    }
+
+    #[test]
+    fn test_speed_profile_parse_errors() {
+        assert!("".parse::<SpeedProfile>().is_err());
+        assert!("2.0".parse::<SpeedProfile>().is_err());
+        assert!("x:10".parse::<SpeedProfile>().is_err());
+        assert!("1.0:0".parse::<SpeedProfile>().is_err());
+        assert!("0:10".parse::<SpeedProfile>().is_err());
+        assert!("-1.0:10".parse::<SpeedProfile>().is_err());
+    }
+
+    #[test]
+    fn test_speed_profile_schedule() {
+        let p: SpeedProfile = "1.0:1,2.0:0.5,4.0:2".parse().unwrap();
+
+        assert_eq!(p.speed_at(Duration::ZERO), 1.0);
+        assert_eq!(p.speed_at(Duration::from_millis(999)), 1.0);
+        assert_eq!(p.speed_at(Duration::from_millis(1_000)), 2.0);
+        assert_eq!(p.speed_at(Duration::from_millis(1_499)), 2.0);
+        assert_eq!(p.speed_at(Duration::from_millis(1_500)), 4.0);
+        // После окончания расписания держим последнюю ступень
+        assert_eq!(p.speed_at(Duration::from_secs(3_600)), 4.0);
+    }
+
+    #[test]
+    fn test_speed_profile_playback_duration() {
+        let p: SpeedProfile = "1.0:10,2.0:10".parse().unwrap();
+
+        // Внутри первой ступени
+        assert_eq!(
+            p.playback_duration(Duration::from_secs(4)),
+            Duration::from_secs(4)
+        );
+        // 10 с на 1x + 20 с на 2x за 10 с
+        assert_eq!(
+            p.playback_duration(Duration::from_secs(30)),
+            Duration::from_secs(20)
+        );
+        // Остаток после расписания — на скорости последней ступени
+        assert_eq!(
+            p.playback_duration(Duration::from_secs(50)),
+            Duration::from_secs(30)
+        );
+    }
+}
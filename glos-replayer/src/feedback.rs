@@ -0,0 +1,369 @@
+use std::{
+    collections::VecDeque,
+    net::{SocketAddr, UdpSocket},
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+use crate::{ReplayMetrics, ReplayResult};
+
+/// Магическое число feedback-пакета: b"GLFB".
+pub const FEEDBACK_MAGIC: [u8; 4] = *b"GLFB";
+
+/// Тип сообщения: состояние буфера приёмника.
+pub const FEEDBACK_KIND_STATUS: u8 = 1;
+
+/// Тип сообщения: запрос повторной отправки блока.
+pub const FEEDBACK_KIND_NACK: u8 = 2;
+
+/// Сообщение обратной связи от приёмника.
+///
+/// Формат (big-endian):
+/// ```text
+/// [0..4]  MAGIC       [u8; 4] — "GLFB"
+/// [4]     KIND        u8      — 1 = STATUS, 2 = NACK
+/// STATUS:
+/// [5]     OCCUPANCY   u8      — заполненность буфера приёмника, 0..=100 %
+/// NACK:
+/// [5..13] TIMESTAMP   u64     — метка времени блока для повторной отправки
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackMessage {
+    Status { occupancy_pct: u8 },
+    Nack { timestamp_ns: u64 },
+}
+
+/// Настройки управления потоком по обратной связи.
+#[derive(Debug, Clone)]
+pub struct FeedbackConfig {
+    /// Адрес, на котором слушаем сообщения приёмника
+    pub bind_addr: SocketAddr,
+    /// Порог заполненности буфера (%), выше которого притормаживаем
+    pub high_watermark_pct: u8,
+    /// Максимальная пауза на один блок при торможении
+    pub max_throttle: Duration,
+    /// Сколько последних блоков держать для повторной отправки
+    pub retransmit_window: usize,
+}
+
+/// Приёмная сторона feedback-канала + кэш недавних блоков.
+pub struct FlowController {
+    config: FeedbackConfig,
+    socket: UdpSocket,
+    occupancy_pct: u8,
+    recent: VecDeque<(u64, Vec<Vec<u8>>)>,
+}
+
+impl FeedbackMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(13);
+        buf.extend_from_slice(&FEEDBACK_MAGIC);
+
+        match *self {
+            FeedbackMessage::Status { occupancy_pct } => {
+                buf.push(FEEDBACK_KIND_STATUS);
+                buf.push(occupancy_pct.min(100));
+            }
+            FeedbackMessage::Nack { timestamp_ns } => {
+                buf.push(FEEDBACK_KIND_NACK);
+                buf.extend_from_slice(&timestamp_ns.to_be_bytes());
+            }
+        }
+
+        buf
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self, String> {
+        if buf.len() < 5 || buf[0..4] != FEEDBACK_MAGIC {
+            return Err("Not a GLOS feedback packet".to_string());
+        }
+
+        match buf[4] {
+            FEEDBACK_KIND_STATUS if buf.len() >= 6 => Ok(FeedbackMessage::Status {
+                occupancy_pct: buf[5].min(100),
+            }),
+            FEEDBACK_KIND_NACK if buf.len() >= 13 => Ok(FeedbackMessage::Nack {
+                timestamp_ns: u64::from_be_bytes(buf[5..13].try_into().unwrap()),
+            }),
+            kind => Err(format!(
+                "Unknown or truncated feedback message: kind={kind}, len={}",
+                buf.len()
+            )),
+        }
+    }
+}
+
+impl Default for FeedbackConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: "0.0.0.0:5556".parse().unwrap(),
+            high_watermark_pct: 80,
+            max_throttle: Duration::from_millis(200),
+            retransmit_window: 64,
+        }
+    }
+}
+
+impl FlowController {
+    /// Открывает неблокирующий feedback-сокет.
+    pub fn bind(config: FeedbackConfig) -> ReplayResult<Self> {
+        let socket = UdpSocket::bind(config.bind_addr)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            config,
+            socket,
+            occupancy_pct: 0,
+            recent: VecDeque::new(),
+        })
+    }
+
+    /// Фактический адрес feedback-сокета (полезно при порте 0).
+    pub fn local_addr(&self) -> ReplayResult<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Последняя известная заполненность буфера приёмника.
+    pub fn occupancy_pct(&self) -> u8 {
+        self.occupancy_pct
+    }
+
+    /// Запоминает пакеты отправленного блока для возможного NACK.
+    pub fn remember(
+        &mut self,
+        timestamp_ns: u64,
+        packets: Vec<Vec<u8>>,
+    ) {
+        if self.config.retransmit_window == 0 {
+            return;
+        }
+
+        if self.recent.len() >= self.config.retransmit_window {
+            self.recent.pop_front();
+        }
+
+        self.recent.push_back((timestamp_ns, packets));
+    }
+
+    /// Пакеты блока из кэша повторной отправки.
+    pub fn lookup(
+        &self,
+        timestamp_ns: u64,
+    ) -> Option<&[Vec<u8>]> {
+        self.recent
+            .iter()
+            .find(|(ts, _)| *ts == timestamp_ns)
+            .map(|(_, p)| p.as_slice())
+    }
+
+    /// Вычитывает все накопившиеся сообщения. Возвращает NACK-и по порядку.
+    pub fn poll(
+        &mut self,
+        metrics: &ReplayMetrics,
+    ) -> Vec<u64> {
+        let mut nacks = Vec::new();
+        let mut buf = [0u8; 64];
+
+        while let Ok(n) = self.socket.recv(&mut buf) {
+            match FeedbackMessage::decode(&buf[..n]) {
+                Ok(msg) => {
+                    metrics.feedback_messages.fetch_add(1, Ordering::Relaxed);
+                    self.apply(msg, &mut nacks);
+                }
+                Err(e) => eprintln!("[replayer] Ignoring feedback packet: {e}"),
+            }
+        }
+
+        nacks
+    }
+
+    fn apply(
+        &mut self,
+        msg: FeedbackMessage,
+        nacks: &mut Vec<u64>,
+    ) {
+        match msg {
+            FeedbackMessage::Status { occupancy_pct } => self.occupancy_pct = occupancy_pct,
+            FeedbackMessage::Nack { timestamp_ns } => nacks.push(timestamp_ns),
+        }
+    }
+
+    /// Повторно отправляет блоки по NACK-ам через `socket`.
+    pub fn retransmit(
+        &self,
+        socket: &UdpSocket,
+        nacks: &[u64],
+        metrics: &ReplayMetrics,
+    ) {
+        for &ts in nacks {
+            let Some(packets) = self.lookup(ts) else {
+                metrics.nacks_unserved.fetch_add(1, Ordering::Relaxed);
+                continue;
+            };
+
+            for payload in packets {
+                match socket.send(payload) {
+                    Ok(n) => {
+                        metrics.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        metrics.send_errors.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+
+            metrics.retransmits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Притормаживает, пока приёмник сообщает о заполненном буфере.
+    ///
+    /// Ждёт не дольше `max_throttle`. Возвращает фактическую паузу, чтобы
+    /// вызывающий мог перепривязать таймер и не получить burst.
+    pub fn throttle(
+        &mut self,
+        socket: &UdpSocket,
+        metrics: &ReplayMetrics,
+    ) -> Duration {
+        if self.occupancy_pct < self.config.high_watermark_pct {
+            return Duration::ZERO;
+        }
+
+        let start = std::time::Instant::now();
+        metrics.throttle_events.fetch_add(1, Ordering::Relaxed);
+
+        while self.occupancy_pct >= self.config.high_watermark_pct
+            && start.elapsed() < self.config.max_throttle
+        {
+            std::thread::sleep(Duration::from_millis(5));
+            let nacks = self.poll(metrics);
+            self.retransmit(socket, &nacks, metrics);
+        }
+
+        let waited = start.elapsed();
+        metrics
+            .throttle_ns_total
+            .fetch_add(waited.as_nanos() as u64, Ordering::Relaxed);
+        waited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller(window: usize) -> FlowController {
+        FlowController::bind(FeedbackConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            high_watermark_pct: 80,
+            max_throttle: Duration::from_millis(50),
+            retransmit_window: window,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feedback_encode_decode_roundtrip() {
+        let status = FeedbackMessage::Status { occupancy_pct: 42 };
+        let nack = FeedbackMessage::Nack {
+            timestamp_ns: 0x0102030405060708,
+        };
+
+        assert_eq!(FeedbackMessage::decode(&status.encode()).unwrap(), status);
+        assert_eq!(FeedbackMessage::decode(&nack.encode()).unwrap(), nack);
+        assert_eq!(
+            &nack.encode()[5..13],
+            &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+        );
+    }
+
+    #[test]
+    fn test_feedback_decode_rejects_garbage() {
+        assert!(FeedbackMessage::decode(b"GLF").is_err());
+        assert!(FeedbackMessage::decode(b"XXXX\x01\x10").is_err());
+        assert!(FeedbackMessage::decode(b"GLFB\x02\x00").is_err());
+        assert!(FeedbackMessage::decode(b"GLFB\x09\x00").is_err());
+    }
+
+    #[test]
+    fn test_retransmit_window_bounded() {
+        let mut fc = controller(2);
+
+        fc.remember(1, vec![vec![1]]);
+        fc.remember(2, vec![vec![2]]);
+        fc.remember(3, vec![vec![3]]);
+
+        assert!(fc.lookup(1).is_none(), "oldest block must be evicted");
+        assert_eq!(fc.lookup(3).unwrap(), &[vec![3u8]]);
+    }
+
+    #[test]
+    fn test_nack_retransmits_cached_block() {
+        let mut fc = controller(8);
+        let metrics = ReplayMetrics::new();
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_millis(500)))
+            .unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.connect(receiver.local_addr().unwrap()).unwrap();
+
+        fc.remember(1_000, vec![b"block-1000".to_vec()]);
+
+        // Приёмник просит повторить блок 1000 и несуществующий блок 7
+        let fb_addr = fc.local_addr().unwrap();
+        receiver
+            .send_to(
+                &FeedbackMessage::Nack {
+                    timestamp_ns: 1_000,
+                }
+                .encode(),
+                fb_addr,
+            )
+            .unwrap();
+        receiver
+            .send_to(&FeedbackMessage::Nack { timestamp_ns: 7 }.encode(), fb_addr)
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        let nacks = fc.poll(&metrics);
+        assert_eq!(nacks, vec![1_000, 7]);
+
+        fc.retransmit(&sender, &nacks, &metrics);
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"block-1000");
+        assert_eq!(metrics.retransmits.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.nacks_unserved.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.feedback_messages.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_throttle_on_high_occupancy() {
+        let mut fc = controller(0);
+        let metrics = ReplayMetrics::new();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // Буфер свободен — без паузы
+        assert_eq!(fc.throttle(&sender, &metrics), Duration::ZERO);
+
+        let reporter = UdpSocket::bind("127.0.0.1:0").unwrap();
+        reporter
+            .send_to(
+                &FeedbackMessage::Status { occupancy_pct: 95 }.encode(),
+                fc.local_addr().unwrap(),
+            )
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        fc.poll(&metrics);
+        assert_eq!(fc.occupancy_pct(), 95);
+
+        // Приёмник не разгружается — ждём ровно max_throttle
+        let waited = fc.throttle(&sender, &metrics);
+        assert!(waited >= Duration::from_millis(50));
+        assert_eq!(metrics.throttle_events.load(Ordering::Relaxed), 1);
+        assert!(metrics.throttle_ns_total.load(Ordering::Relaxed) >= 50_000_000);
+    }
+}
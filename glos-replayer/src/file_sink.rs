@@ -0,0 +1,273 @@
+use std::{fs::File, path::PathBuf, time::Duration};
+
+use glos_core::GlosWriter;
+use glos_types::{GlosHeader, IqBlock};
+
+use crate::ReplayResult;
+
+/// Как пересчитываются метки блоков при записи в файл.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SinkTimestamps {
+    /// Исходные метки (плюс сдвиг); скорость на них не влияет
+    #[default]
+    Source,
+    /// Метки времени воспроизведения: интервалы между блоками делятся на
+    /// текущую скорость, как их увидел бы приёмник
+    Replay,
+}
+
+/// Запись воспроизводимого потока в новый .glos вместо UDP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSinkConfig {
+    pub path: PathBuf,
+    pub timestamps: SinkTimestamps,
+    /// Сдвиг всех меток (нс), может быть отрицательным
+    pub offset_ns: i64,
+    /// Выдерживать темп воспроизведения (false = копировать как можно
+    /// быстрее; метки от этого не зависят)
+    pub paced: bool,
+}
+
+/// Пишет блоки сессии воспроизведения в .glos через [`GlosWriter`].
+///
+/// Метки считаются детерминированно от исходных, а не по часам, поэтому
+/// результат не зависит от `paced` и нагрузки машины. При зацикливании
+/// метки продолжают расти: следующий проход начинается сразу после
+/// последнего блока предыдущего.
+pub struct FileSink {
+    writer: GlosWriter<File>,
+    timestamps: SinkTimestamps,
+    offset_ns: i64,
+    sample_rate: u32,
+    /// Исходная метка и число выборок последнего блока
+    last_source: Option<(u64, u32)>,
+    first_out_ns: u64,
+    last_out_ns: u64,
+    end_ns: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl FileSinkConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            timestamps: SinkTimestamps::Source,
+            offset_ns: 0,
+            paced: false,
+        }
+    }
+}
+
+impl FileSink {
+    /// Создаёт выходной файл с заголовком источника. `sample_rate` —
+    /// частота после передискретизации (или исходная).
+    pub fn create(
+        config: &FileSinkConfig,
+        source: &GlosHeader,
+        sample_rate: u32,
+    ) -> ReplayResult<Self> {
+        let mut header = source.clone();
+        header.sample_rate = sample_rate;
+        header.total_samples = 0;
+        header.timestamp_end = 0;
+        header.timestamp_start =
+            shift(source.timestamp_start * 1_000_000_000, config.offset_ns) / 1_000_000_000;
+
+        let writer = GlosWriter::new(File::create(&config.path)?, header)?;
+
+        Ok(Self {
+            writer,
+            timestamps: config.timestamps,
+            offset_ns: config.offset_ns,
+            sample_rate,
+            last_source: None,
+            first_out_ns: 0,
+            last_out_ns: 0,
+            end_ns: 0,
+        })
+    }
+
+    /// Пересчитывает метку блока и записывает его. `speed` — скорость,
+    /// действующая на момент блока.
+    pub fn write(
+        &mut self,
+        mut block: IqBlock,
+        speed: f64,
+    ) -> ReplayResult<()> {
+        let scale = match self.timestamps {
+            SinkTimestamps::Source => 1.0,
+            SinkTimestamps::Replay => 1.0 / speed,
+        };
+
+        let out_ns = match self.last_source {
+            None => {
+                let ts = shift(block.timestamp_ns, self.offset_ns);
+                self.first_out_ns = ts;
+                ts
+            }
+            Some((last_ts, last_count)) => {
+                // Новый проход цикла: продолжаем сразу за предыдущим блоком
+                let delta = if block.timestamp_ns >= last_ts {
+                    block.timestamp_ns - last_ts
+                } else {
+                    self.samples_to_ns(last_count)
+                };
+
+                self.last_out_ns + (delta as f64 * scale) as u64
+            }
+        };
+
+        self.last_source = Some((block.timestamp_ns, block.sample_count));
+        self.last_out_ns = out_ns;
+        self.end_ns = out_ns + (self.samples_to_ns(block.sample_count) as f64 * scale) as u64;
+
+        block.timestamp_ns = out_ns;
+        self.writer.write_block(block)?;
+
+        Ok(())
+    }
+
+    /// Время воспроизведения от первого блока по выходным меткам.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.last_out_ns.saturating_sub(self.first_out_ns))
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.writer.total_samples()
+    }
+
+    /// Финализирует заголовок (total_samples, timestamp_end).
+    pub fn finish(self) -> ReplayResult<()> {
+        let end_secs = self.end_ns.div_ceil(1_000_000_000);
+        self.writer.finish_at(end_secs)?;
+
+        Ok(())
+    }
+
+    fn samples_to_ns(
+        &self,
+        samples: u32,
+    ) -> u64 {
+        samples as u64 * 1_000_000_000 / self.sample_rate.max(1) as u64
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for SinkTimestamps {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            SinkTimestamps::Source => write!(f, "source"),
+            SinkTimestamps::Replay => write!(f, "replay"),
+        }
+    }
+}
+
+impl std::str::FromStr for SinkTimestamps {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "source" => Ok(SinkTimestamps::Source),
+            "replay" => Ok(SinkTimestamps::Replay),
+            _ => Err(format!("Unknown timestamp mode '{s}'. Use: source, replay")),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn shift(
+    ts_ns: u64,
+    offset_ns: i64,
+) -> u64 {
+    ts_ns.saturating_add_signed(offset_ns)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::{read_all_blocks, GlosHeaderExt, GlosReader, IqBlockExt};
+    use glos_types::SdrType;
+
+    use super::*;
+
+    const T0: u64 = 1_704_067_200_000_000_000;
+
+    /// Пишет блоки по 1000 выборок (500 мкс при 2 Msps) с заданными
+    /// исходными метками и возвращает выходные метки.
+    fn retime(
+        config: FileSinkConfig,
+        source_ts: &[u64],
+        speed: f64,
+    ) -> (Vec<u64>, GlosHeader) {
+        let mut source = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        source.timestamp_start = T0 / 1_000_000_000;
+
+        let mut sink = FileSink::create(&config, &source, 2_000_000).unwrap();
+        for &ts in source_ts {
+            sink.write(IqBlock::new(ts, 1000, vec![0u8; 4000]), speed)
+                .unwrap();
+        }
+        sink.finish().unwrap();
+
+        let mut reader = GlosReader::new(File::open(&config.path).unwrap()).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+
+        (
+            blocks.iter().map(|b| b.timestamp_ns).collect(),
+            reader.header().clone(),
+        )
+    }
+
+    #[test]
+    fn test_file_sink_source_timestamps_with_offset_and_loop() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = FileSinkConfig {
+            offset_ns: -2_000_000_000,
+            ..FileSinkConfig::new(dir.path().join("out.glos"))
+        };
+
+        // Второй проход цикла начинается с исходного T0
+        let (ts, header) = retime(config, &[T0, T0 + 500_000, T0, T0 + 500_000], 4.0);
+
+        let base = T0 - 2_000_000_000;
+        assert_eq!(
+            ts,
+            vec![base, base + 500_000, base + 1_000_000, base + 1_500_000]
+        );
+        assert_eq!(header.timestamp_start, base / 1_000_000_000);
+        assert_eq!(header.total_samples, 4000);
+    }
+
+    #[test]
+    fn test_file_sink_replay_timestamps_follow_speed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = FileSinkConfig {
+            timestamps: SinkTimestamps::Replay,
+            ..FileSinkConfig::new(dir.path().join("out.glos"))
+        };
+
+        let (ts, _) = retime(config, &[T0, T0 + 500_000, T0 + 1_000_000], 2.0);
+
+        assert_eq!(ts, vec![T0, T0 + 250_000, T0 + 500_000]);
+        assert_eq!(
+            "replay".parse::<SinkTimestamps>(),
+            Ok(SinkTimestamps::Replay)
+        );
+        assert!("wallclock".parse::<SinkTimestamps>().is_err());
+    }
+}
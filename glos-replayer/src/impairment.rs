@@ -0,0 +1,326 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use crate::ReplayMetrics;
+
+/// Распределение искусственной задержки перед отправкой блока.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JitterDistribution {
+    None,
+    /// Равномерно в `[0, max]`
+    Uniform {
+        max: Duration,
+    },
+    /// Модуль нормальной величины N(0, σ²), ограниченный `4σ`
+    Normal {
+        std_dev: Duration,
+    },
+}
+
+/// Искусственные сетевые искажения потока (для проверки устойчивости
+/// приёмника).
+///
+/// Все решения принимаются на уровне блока: теряется, дублируется или
+/// переставляется блок целиком. Генератор детерминирован по `seed`, поэтому
+/// один и тот же сценарий можно повторить.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImpairmentConfig {
+    /// Вероятность потери блока, 0.0..=1.0
+    pub loss: f64,
+    /// Задержка перед отправкой блока
+    pub jitter: JitterDistribution,
+    /// Вероятность того, что блок уйдёт после следующего
+    pub reorder: f64,
+    /// Вероятность повторной отправки блока
+    pub duplicate: f64,
+    pub seed: u64,
+}
+
+/// Блок после применения искажений.
+#[derive(Debug, Default)]
+pub struct ImpairedBlock {
+    /// Дополнительная задержка перед отправкой
+    pub delay: Duration,
+    /// Пакеты к отправке (возможно пусто, возможно с дублями или с
+    /// придержанным ранее блоком в конце)
+    pub packets: Vec<Vec<u8>>,
+}
+
+/// Применяет [`ImpairmentConfig`] к последовательности блоков.
+pub struct Impairer {
+    config: ImpairmentConfig,
+    rng: u64,
+    /// Блок, придержанный для перестановки
+    held: Option<Vec<Vec<u8>>>,
+}
+
+impl ImpairmentConfig {
+    /// Проверяет вероятности.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, p) in [
+            ("loss", self.loss),
+            ("reorder", self.reorder),
+            ("duplicate", self.duplicate),
+        ] {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(format!("{name} probability must be in 0..=1, got {p}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ни одно искажение не включено.
+    pub fn is_noop(&self) -> bool {
+        self.loss == 0.0
+            && self.reorder == 0.0
+            && self.duplicate == 0.0
+            && self.jitter == JitterDistribution::None
+    }
+}
+
+impl Impairer {
+    pub fn new(config: ImpairmentConfig) -> Self {
+        Self {
+            rng: config.seed,
+            config,
+            held: None,
+        }
+    }
+
+    /// Решает судьбу очередного блока и обновляет счётчики в `metrics`.
+    pub fn apply(
+        &mut self,
+        packets: Vec<Vec<u8>>,
+        metrics: &ReplayMetrics,
+    ) -> ImpairedBlock {
+        // Все случайные величины тянем всегда — последовательность решений
+        // зависит только от seed и номера блока
+        let lost = self.next_f64() < self.config.loss;
+        let reordered = self.next_f64() < self.config.reorder;
+        let duplicated = self.next_f64() < self.config.duplicate;
+        let delay = self.jitter();
+
+        let mut out = ImpairedBlock {
+            delay,
+            packets: Vec::new(),
+        };
+
+        if !delay.is_zero() {
+            metrics.impair_jittered.fetch_add(1, Ordering::Relaxed);
+            metrics
+                .impair_jitter_ns_total
+                .fetch_add(delay.as_nanos() as u64, Ordering::Relaxed);
+        }
+
+        if lost {
+            metrics.impair_lost.fetch_add(1, Ordering::Relaxed);
+        } else if reordered && self.held.is_none() {
+            metrics.impair_reordered.fetch_add(1, Ordering::Relaxed);
+            self.held = Some(packets);
+            return out;
+        } else {
+            if duplicated {
+                metrics.impair_duplicated.fetch_add(1, Ordering::Relaxed);
+                out.packets.extend(packets.iter().cloned());
+            }
+            out.packets.extend(packets);
+        }
+
+        if let Some(held) = self.held.take() {
+            out.packets.extend(held);
+        }
+
+        out
+    }
+
+    /// Отдаёт придержанный блок (в конце файла / прохода).
+    pub fn flush(&mut self) -> Vec<Vec<u8>> {
+        self.held.take().unwrap_or_default()
+    }
+
+    fn jitter(&mut self) -> Duration {
+        match self.config.jitter {
+            JitterDistribution::None => Duration::ZERO,
+            JitterDistribution::Uniform { max } => max.mul_f64(self.next_f64()),
+            JitterDistribution::Normal { std_dev } => {
+                // Бокс — Мюллер
+                let u1 = 1.0 - self.next_f64();
+                let u2 = self.next_f64();
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                std_dev.mul_f64(z.abs().min(4.0))
+            }
+        }
+    }
+
+    /// Равномерное в `[0, 1)` (splitmix64).
+    fn next_f64(&mut self) -> f64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for ImpairmentConfig {
+    fn default() -> Self {
+        Self {
+            loss: 0.0,
+            jitter: JitterDistribution::None,
+            reorder: 0.0,
+            duplicate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for JitterDistribution {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            JitterDistribution::None => write!(f, "none"),
+            JitterDistribution::Uniform { max } => {
+                write!(f, "uniform:{}", max.as_secs_f64() * 1e3)
+            }
+            JitterDistribution::Normal { std_dev } => {
+                write!(f, "normal:{}", std_dev.as_secs_f64() * 1e3)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for JitterDistribution {
+    type Err = String;
+
+    /// Парсит `none`, `uniform:<ms>` или `normal:<ms>` (σ), например
+    /// `uniform:2.5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("none") {
+            return Ok(JitterDistribution::None);
+        }
+
+        let (kind, ms) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid jitter '{s}'. Use: none, uniform:<ms>, normal:<ms>"))?;
+
+        let ms: f64 = ms
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid jitter milliseconds '{ms}': {e}"))?;
+
+        if !ms.is_finite() || ms < 0.0 {
+            return Err(format!("Jitter must be >= 0 ms, got {ms}"));
+        }
+
+        let d = Duration::from_secs_f64(ms / 1e3);
+
+        match kind.trim().to_lowercase().as_str() {
+            "uniform" => Ok(JitterDistribution::Uniform { max: d }),
+            "normal" => Ok(JitterDistribution::Normal { std_dev: d }),
+            _ => Err(format!(
+                "Unknown jitter distribution '{kind}'. Use: uniform, normal"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: u8) -> Vec<Vec<u8>> {
+        vec![vec![id; 4]]
+    }
+
+    fn run(
+        config: ImpairmentConfig,
+        n: u8,
+    ) -> (Vec<u8>, ReplayMetrics) {
+        let metrics = ReplayMetrics::default();
+        let mut imp = Impairer::new(config);
+        let mut sent = Vec::new();
+
+        for id in 0..n {
+            sent.extend(imp.apply(block(id), &metrics).packets.iter().map(|p| p[0]));
+        }
+        sent.extend(imp.flush().iter().map(|p| p[0]));
+
+        (sent, metrics)
+    }
+
+    #[test]
+    fn test_impairer_noop_passes_through() {
+        let config = ImpairmentConfig::default();
+        assert!(config.is_noop());
+
+        let (sent, metrics) = run(config, 10);
+
+        assert_eq!(sent, (0..10).collect::<Vec<_>>());
+        assert_eq!(metrics.impair_lost.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_impairer_counts_and_is_reproducible() {
+        let config = ImpairmentConfig {
+            loss: 0.1,
+            reorder: 0.1,
+            duplicate: 0.1,
+            jitter: JitterDistribution::Uniform {
+                max: Duration::from_millis(2),
+            },
+            seed: 42,
+        };
+
+        let (a, m) = run(config, 200);
+        let (b, _) = run(config, 200);
+        assert_eq!(a, b, "same seed must give the same stream");
+
+        let lost = m.impair_lost.load(Ordering::Relaxed) as usize;
+        let dup = m.impair_duplicated.load(Ordering::Relaxed) as usize;
+        let reordered = m.impair_reordered.load(Ordering::Relaxed);
+
+        assert!(lost > 5 && dup > 5 && reordered > 5);
+        assert_eq!(a.len(), 200 - lost + dup);
+        assert_eq!(m.impair_jittered.load(Ordering::Relaxed), 200);
+        assert!(m.impair_jitter_ns_total.load(Ordering::Relaxed) <= 200 * 2_000_000);
+
+        // Переставленные блоки действительно идут не по порядку
+        assert!(a.windows(2).any(|w| w[1] < w[0]));
+
+        let (c, _) = run(ImpairmentConfig { seed: 43, ..config }, 200);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_jitter_parse_and_validate() {
+        assert_eq!(
+            "uniform:2.5".parse::<JitterDistribution>().unwrap(),
+            JitterDistribution::Uniform {
+                max: Duration::from_micros(2_500)
+            }
+        );
+        assert_eq!(
+            "normal:1".parse::<JitterDistribution>().unwrap(),
+            JitterDistribution::Normal {
+                std_dev: Duration::from_millis(1)
+            }
+        );
+        assert_eq!(
+            "none".parse::<JitterDistribution>().unwrap(),
+            JitterDistribution::None
+        );
+        assert!("uniform".parse::<JitterDistribution>().is_err());
+        assert!("pareto:1".parse::<JitterDistribution>().is_err());
+        assert!("normal:-1".parse::<JitterDistribution>().is_err());
+
+        let bad = ImpairmentConfig {
+            loss: 1.5,
+            ..Default::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+}
@@ -1,9 +1,19 @@
 pub mod config;
 pub mod error;
+pub mod feedback;
+pub mod file_sink;
+pub mod impairment;
+pub mod preload;
 pub mod replayer;
 pub mod session;
+pub mod watch;
 
 pub use config::*;
 pub use error::*;
+pub use feedback::*;
+pub use file_sink::*;
+pub use impairment::*;
+pub use preload::*;
 pub use replayer::*;
 pub use session::*;
+pub use watch::*;
@@ -4,13 +4,23 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use clap::Parser;
-use glos_replayer::{parse_udp_target, ReplayConfig, ReplaySession};
-use log::{error, info, warn};
-use signal_hook::{consts::signal::SIGTSTP, flag};
+use glos_core::{dsp::ResampleQuality, UdpCompression, UdpProtocol};
+use glos_replayer::{
+    parse_udp_target, FeedbackConfig, FileSinkConfig, ImpairmentConfig, JitterDistribution,
+    PreloadConfig, PreloadMode, ReplayConfig, ReplaySession, ResampleConfig, SinkTimestamps,
+    SpeedProfile, WatchConfig, WatchSession, STDIN_INPUT,
+};
+use glos_types::{RuntimeControl, VERBOSE_LOG_LEVEL};
+use log::{error, info, warn, LevelFilter};
+use signal_hook::{
+    consts::signal::{SIGTSTP, SIGUSR2},
+    flag,
+    iterator::Signals,
+};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -20,22 +30,75 @@ use signal_hook::{consts::signal::SIGTSTP, flag};
     long_about = None,
 )]
 struct Cli {
-    /// Входной .glos файл
-    #[arg(short, long)]
-    input: PathBuf,
+    /// Входной .glos файл (`-` — стандартный ввод, без --loop)
+    #[arg(short, long, required_unless_present = "watch")]
+    input: Option<PathBuf>,
+
+    /// Следить за каталогом и воспроизводить законченные .glos файлы по
+    /// мере появления (пропуская уже воспроизведённые)
+    #[arg(long, conflicts_with_all = ["input", "loop", "output_file"])]
+    watch: Option<PathBuf>,
+
+    /// Период опроса каталога в режиме --watch (секунды)
+    #[arg(long, default_value = "1.0")]
+    watch_interval: f64,
 
     /// UDP адрес назначения (udp://host:port или host:port)
     #[arg(short, long, default_value = "udp://127.0.0.1:5555")]
     output: String,
 
+    /// Писать поток в новый .glos вместо UDP (копия с учётом скорости,
+    /// передискретизации и сдвига времени)
+    #[arg(long, conflicts_with_all = ["feedback"])]
+    output_file: Option<PathBuf>,
+
+    /// Метки блоков в --output-file: source (исходные) или replay
+    /// (интервалы делятся на скорость)
+    #[arg(long, default_value = "source")]
+    timestamps: String,
+
+    /// Сдвиг меток в --output-file (секунды, можно отрицательный)
+    #[arg(long, default_value = "0", allow_hyphen_values = true)]
+    time_offset: f64,
+
+    /// Выдерживать темп воспроизведения при записи в --output-file
+    #[arg(long)]
+    paced: bool,
+
+    /// Версия UDP-протокола: v1 или v2 (с XXH64 дайджестом каждого блока
+    /// для сквозной проверки на приёмнике)
+    #[arg(long, default_value = "v1")]
+    protocol: String,
+
+    /// Сжатие данных блоков в UDP-потоке: none или lz4 (только --protocol
+    /// v2; приёмник распаковывает по флагу в пакете)
+    #[arg(long, default_value = "none", conflicts_with = "output_file")]
+    udp_compression: String,
+
     /// Коэффициент скорости: 0.5, 1.0, 2.0 и т.д.
     #[arg(short, long, default_value = "1.0")]
     speed: f64,
 
+    /// Ступенчатый профиль скорости `speed:secs,...` (например
+    /// `1.0:60,2.0:60,4.0:60`). Переопределяет --speed
+    #[arg(long)]
+    speed_profile: Option<String>,
+
     /// Повторять файл бесконечно
     #[arg(long)]
     r#loop: bool,
 
+    /// Предзагрузка входного файла до старта: none, mmap (прогреть
+    /// страничный кэш) или ram (прочитать целиком в память)
+    #[arg(long, default_value = "none")]
+    preload: String,
+
+    /// С --preload mmap: вместо прогрева всего файла держать прочитанным
+    /// окно такого размера впереди воспроизведения (МиБ), для файлов
+    /// больше RAM
+    #[arg(long)]
+    readahead_mb: Option<u64>,
+
     /// Интервал вывода статистики (секунды)
     #[arg(long, default_value = "5")]
     stats_interval: u64,
@@ -44,6 +107,43 @@ struct Cli {
     #[arg(long, default_value = "0.0.0.0:0")]
     bind: String,
 
+    /// Адрес для приёма обратной связи от приёмника (занятость буфера /
+    /// NACK). Включает управление потоком
+    #[arg(long)]
+    feedback: Option<String>,
+
+    /// Порог заполненности буфера приёмника (%), выше которого тормозим
+    #[arg(long, default_value = "80")]
+    feedback_high_water: u8,
+
+    /// Передискретизировать поток на лету до указанной частоты (Гц)
+    #[arg(long)]
+    resample_rate: Option<u32>,
+
+    /// Качество передискретизации: fast, balanced, high
+    #[arg(long, default_value = "balanced")]
+    resample_quality: String,
+
+    /// Вероятность потери блока (0.0..=1.0), для проверки приёмника
+    #[arg(long, default_value = "0.0")]
+    impair_loss: f64,
+
+    /// Задержка блоков: none, uniform:<ms>, normal:<ms>
+    #[arg(long, default_value = "none")]
+    impair_jitter: String,
+
+    /// Вероятность перестановки блока со следующим (0.0..=1.0)
+    #[arg(long, default_value = "0.0")]
+    impair_reorder: f64,
+
+    /// Вероятность дублирования блока (0.0..=1.0)
+    #[arg(long, default_value = "0.0")]
+    impair_duplicate: f64,
+
+    /// Seed генератора искажений (для воспроизводимости)
+    #[arg(long, default_value = "0")]
+    impair_seed: u64,
+
     /// Тихий режим (только ошибки)
     #[arg(short, long)]
     quiet: bool,
@@ -52,13 +152,20 @@ struct Cli {
 fn main() {
     let cli = Cli::parse();
 
-    let level = if cli.quiet { "error" } else { "info" };
+    let level = if cli.quiet {
+        LevelFilter::Error
+    } else {
+        LevelFilter::Info
+    };
 
+    // Логгер пропускает и debug, реальный уровень задаёт max_level —
+    // его можно поднять на лету (SIGUSR2)
     env_logger::Builder::new()
-        .filter_level(level.parse().unwrap())
+        .filter_level(VERBOSE_LOG_LEVEL)
         .format_target(false)
         .format_timestamp_secs()
         .init();
+    log::set_max_level(level);
 
     // ⚠️ Правовое предупреждение
     warn!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
@@ -76,25 +183,185 @@ fn main() {
         }
     };
 
-    if !cli.input.exists() {
-        error!("Input file not found: {:?}", cli.input);
+    let protocol = match cli.protocol.parse::<UdpProtocol>() {
+        Ok(p) => p,
+        Err(e) => {
+            error!("--protocol: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let compression = match cli.udp_compression.parse::<UdpCompression>() {
+        Ok(c) => c,
+        Err(e) => {
+            error!("--udp-compression: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if compression != UdpCompression::None && protocol != UdpProtocol::V2 {
+        error!("--udp-compression: requires --protocol v2");
         std::process::exit(1);
     }
 
+    if let Some(input) = &cli.input {
+        if input.as_os_str() != STDIN_INPUT && !input.exists() {
+            error!("Input file not found: {input:?}");
+            std::process::exit(1);
+        }
+    }
+
+    let from_stdin = cli
+        .input
+        .as_deref()
+        .is_some_and(|i| i.as_os_str() == STDIN_INPUT);
+    let loop_playback = cli.r#loop && !from_stdin;
+    if cli.r#loop && from_stdin {
+        warn!("--loop: ignored, stdin can only be read once");
+    }
+
     if cli.speed <= 0.0 {
         error!("--speed must be > 0");
         std::process::exit(1);
     }
 
+    let preload = PreloadConfig {
+        mode: match cli.preload.parse::<PreloadMode>() {
+            Ok(m) => m,
+            Err(e) => {
+                error!("--preload: {e}");
+                std::process::exit(1);
+            }
+        },
+        readahead_bytes: cli.readahead_mb.map(|mb| mb << 20),
+    };
+
+    if let Err(e) = preload.validate() {
+        error!("--readahead-mb: {e}");
+        std::process::exit(1);
+    }
+
+    if from_stdin && preload.mode != PreloadMode::None {
+        error!("--preload: stdin cannot be preloaded");
+        std::process::exit(1);
+    }
+
+    let speed_profile = match cli.speed_profile.as_deref().map(str::parse::<SpeedProfile>) {
+        None => None,
+        Some(Ok(p)) => Some(p),
+        Some(Err(e)) => {
+            error!("--speed-profile: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let feedback = match cli.feedback.as_deref().map(parse_udp_target) {
+        None => None,
+        Some(Ok(bind_addr)) => Some(FeedbackConfig {
+            bind_addr,
+            high_watermark_pct: cli.feedback_high_water,
+            ..Default::default()
+        }),
+        Some(Err(e)) => {
+            error!("--feedback: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let resample = match cli.resample_rate {
+        None => None,
+        Some(0) => {
+            error!("--resample-rate must be > 0");
+            std::process::exit(1);
+        }
+        Some(out_rate) => match cli.resample_quality.parse::<ResampleQuality>() {
+            Ok(quality) => Some(ResampleConfig { out_rate, quality }),
+            Err(e) => {
+                error!("--resample-quality: {e}");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let jitter = match cli.impair_jitter.parse::<JitterDistribution>() {
+        Ok(j) => j,
+        Err(e) => {
+            error!("--impair-jitter: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let impairments = ImpairmentConfig {
+        loss: cli.impair_loss,
+        jitter,
+        reorder: cli.impair_reorder,
+        duplicate: cli.impair_duplicate,
+        seed: cli.impair_seed,
+    };
+
+    if let Err(e) = impairments.validate() {
+        error!("--impair-*: {e}");
+        std::process::exit(1);
+    }
+
+    let file_sink = match cli.output_file.clone() {
+        None => None,
+        Some(path) => {
+            let timestamps = match cli.timestamps.parse::<SinkTimestamps>() {
+                Ok(t) => t,
+                Err(e) => {
+                    error!("--timestamps: {e}");
+                    std::process::exit(1);
+                }
+            };
+
+            if !cli.time_offset.is_finite() {
+                error!("--time-offset must be a finite number of seconds");
+                std::process::exit(1);
+            }
+
+            if Some(&path) == cli.input.as_ref() {
+                error!("--output-file: must differ from --input");
+                std::process::exit(1);
+            }
+
+            Some(FileSinkConfig {
+                path,
+                timestamps,
+                offset_ns: (cli.time_offset * 1e9) as i64,
+                paced: cli.paced,
+            })
+        }
+    };
+
+    if file_sink.is_some() && !impairments.is_noop() {
+        error!("--output-file: cannot be combined with --impair-*");
+        std::process::exit(1);
+    }
+
     let config = ReplayConfig {
-        input_path: cli.input.clone(),
+        input_path: cli.input.clone().unwrap_or_default(),
         target_addr,
         speed: cli.speed,
-        loop_playback: cli.r#loop,
+        loop_playback,
         stats_interval_secs: cli.stats_interval,
         bind_addr: cli.bind.parse().expect("invalid bind addr"),
+        protocol,
+        compression,
+        speed_profile,
+        feedback,
+        resample,
+        impairments: (!impairments.is_noop()).then_some(impairments),
+        file_sink,
+        preload,
+        ..Default::default()
     };
 
+    if let Some(dir) = cli.watch.clone() {
+        run_watch(&cli, config, dir);
+        return;
+    }
+
     let session = match ReplaySession::new(config) {
         Ok(s) => s,
         Err(e) => {
@@ -129,12 +396,28 @@ fn main() {
         warn!("Failed to register SIGTSTP handler: {e}");
     }
 
+    // SIGUSR2 → подробная статистика и debug-логи
+    spawn_sigusr2_handler(session.runtime_control());
+
+    let destination = match &cli.output_file {
+        Some(path) => format!("{path:?}"),
+        None => cli.output.clone(),
+    };
+
     info!(
-        "Starting replay: {:?} → {} @ {}x{}",
-        cli.input,
-        cli.output,
-        cli.speed,
-        if cli.r#loop { " (loop)" } else { "" }
+        "Starting replay: {:?} → {} @ {}{}",
+        cli.input.clone().unwrap_or_default(),
+        destination,
+        cli.speed_profile
+            .as_deref()
+            .map(|p| format!("profile {p}"))
+            .unwrap_or_else(|| format!("{}x", cli.speed)),
+        if loop_playback { " (loop)" } else { "" }
+    );
+
+    info!(
+        "Pause: Ctrl+Z, verbose stats: kill -USR2 {}",
+        std::process::id()
     );
 
     let session_start = Instant::now();
@@ -163,3 +446,79 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+/// Режим --watch: сессия на каждый новый файл, общие обработчики сигналов.
+fn run_watch(
+    cli: &Cli,
+    config: ReplayConfig,
+    dir: PathBuf,
+) {
+    if !cli.watch_interval.is_finite() || cli.watch_interval <= 0.0 {
+        error!("--watch-interval must be > 0");
+        std::process::exit(1);
+    }
+
+    let watch = WatchConfig {
+        poll_interval: Duration::from_secs_f64(cli.watch_interval),
+        ..WatchConfig::new(dir)
+    };
+
+    let session = match WatchSession::new(config, watch.clone()) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to create watch session: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let stop_ctrlc = session.stop_flag();
+    ctrlc::set_handler(move || {
+        if stop_ctrlc.swap(true, Ordering::Relaxed) {
+            warn!("Force exit");
+            std::process::exit(130);
+        }
+        warn!("Ctrl+C — stopping watch after current block...");
+    })
+    .unwrap_or_else(|e| warn!("Failed to set Ctrl+C handler: {e}"));
+
+    if let Err(e) = flag::register(SIGTSTP, session.pause_flag()) {
+        warn!("Failed to register SIGTSTP handler: {e}");
+    }
+
+    spawn_sigusr2_handler(session.runtime_control());
+
+    info!(
+        "Watching {:?} → {} (poll every {:?}, skip list {:?})",
+        watch.dir, cli.output, watch.poll_interval, watch.skip_list
+    );
+    info!(
+        "Pause: Ctrl+Z, verbose stats: kill -USR2 {}",
+        std::process::id()
+    );
+
+    match session.run() {
+        Ok(played) => info!("✓ Watch stopped, {played} files replayed"),
+        Err(e) => {
+            error!("Watch failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// SIGUSR2 переключает подробный режим: статистика раз в секунду и
+/// debug-логи; повторный сигнал возвращает прежние настройки.
+fn spawn_sigusr2_handler(control: Arc<RuntimeControl>) {
+    let mut signals = match Signals::new([SIGUSR2]) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to register SIGUSR2 handler: {e}");
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            control.toggle_verbose();
+        }
+    });
+}
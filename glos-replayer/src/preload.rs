@@ -0,0 +1,356 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use memmap2::Mmap;
+
+use crate::ReplayResult;
+
+/// Шаг прогрева: одно чтение на страницу.
+const PAGE_SIZE: usize = 4096;
+
+/// Сколько прогревать за раз, не проверяя остановку и позицию.
+const READAHEAD_CHUNK: usize = 1 << 20;
+
+/// Пауза потока упреждающего чтения, когда окно уже прогрето.
+const READAHEAD_POLL: Duration = Duration::from_millis(10);
+
+/// Как подготовить входной файл до начала воспроизведения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreloadMode {
+    /// Читать с диска по ходу воспроизведения
+    #[default]
+    None,
+    /// Отобразить файл в память и заранее прогреть страничный кэш
+    Mmap,
+    /// Прочитать файл целиком в память
+    Ram,
+}
+
+/// Предзагрузка входа: убирает подвисания на границах блоков при чтении
+/// с медленных дисков.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PreloadConfig {
+    pub mode: PreloadMode,
+    /// Только для [`PreloadMode::Mmap`]: вместо прогрева всего файла до
+    /// старта фоновый поток держит прочитанным окно такого размера впереди
+    /// позиции воспроизведения (для файлов больше RAM)
+    pub readahead_bytes: Option<u64>,
+}
+
+/// Входной файл, подготовленный к воспроизведению. Каждый проход цикла
+/// читает его заново из памяти, без обращения к диску.
+pub struct PreloadedInput {
+    data: Arc<Preloaded>,
+    /// Позиция текущего читателя — для потока упреждающего чтения
+    position: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    readahead: Option<JoinHandle<()>>,
+}
+
+enum Preloaded {
+    Mmap(Mmap),
+    Ram(Vec<u8>),
+}
+
+/// Читатель предзагруженного файла.
+struct PreloadedReader {
+    data: Arc<Preloaded>,
+    pos: usize,
+    position: Arc<AtomicU64>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl PreloadConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.readahead_bytes {
+            Some(_) if self.mode != PreloadMode::Mmap => {
+                Err("readahead requires preload mode mmap".to_string())
+            }
+            Some(0) => Err("readahead window must be > 0".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PreloadedInput {
+    /// Готовит `path` согласно `config`; `None` для [`PreloadMode::None`].
+    pub fn prepare(
+        path: &Path,
+        config: &PreloadConfig,
+    ) -> ReplayResult<Option<Self>> {
+        let started = Instant::now();
+
+        let data = match config.mode {
+            PreloadMode::None => return Ok(None),
+            PreloadMode::Ram => Preloaded::Ram(std::fs::read(path)?),
+            PreloadMode::Mmap => {
+                let file = File::open(path)?;
+                // SAFETY: воспроизводятся законченные записи; файл только
+                // читается. Усечение файла во время воспроизведения
+                // обернётся SIGBUS — как и для любого mmap.
+                let map = unsafe { Mmap::map(&file)? };
+
+                #[cfg(unix)]
+                let _ = map.advise(memmap2::Advice::Sequential);
+
+                Preloaded::Mmap(map)
+            }
+        };
+
+        let data = Arc::new(data);
+        let position = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let readahead = match (config.mode, config.readahead_bytes) {
+            (PreloadMode::Mmap, Some(window)) => Some(spawn_readahead(
+                data.clone(),
+                position.clone(),
+                stop.clone(),
+                window as usize,
+            )),
+            (PreloadMode::Mmap, None) => {
+                touch(data.as_slice());
+                None
+            }
+            _ => None,
+        };
+
+        eprintln!(
+            "[replayer] Preloaded {:.1} MiB ({}) in {:.2}s",
+            data.as_slice().len() as f64 / (1 << 20) as f64,
+            match readahead {
+                Some(_) => "mmap, readahead",
+                None => data.kind(),
+            },
+            started.elapsed().as_secs_f64()
+        );
+
+        Ok(Some(Self {
+            data,
+            position,
+            stop,
+            readahead,
+        }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Новый читатель с начала файла.
+    pub fn reader(&self) -> Box<dyn Read> {
+        self.position.store(0, Ordering::Relaxed);
+
+        Box::new(PreloadedReader {
+            data: self.data.clone(),
+            pos: 0,
+            position: self.position.clone(),
+        })
+    }
+}
+
+impl Preloaded {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Preloaded::Mmap(map) => map,
+            Preloaded::Ram(buf) => buf,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Preloaded::Mmap(_) => "mmap",
+            Preloaded::Ram(_) => "ram",
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Read for PreloadedReader {
+    fn read(
+        &mut self,
+        buf: &mut [u8],
+    ) -> std::io::Result<usize> {
+        let rest = &self.data.as_slice()[self.pos..];
+        let n = rest.len().min(buf.len());
+
+        buf[..n].copy_from_slice(&rest[..n]);
+        self.pos += n;
+        self.position.store(self.pos as u64, Ordering::Relaxed);
+
+        Ok(n)
+    }
+}
+
+impl Drop for PreloadedInput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(handle) = self.readahead.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl std::fmt::Display for PreloadMode {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            PreloadMode::None => write!(f, "none"),
+            PreloadMode::Mmap => write!(f, "mmap"),
+            PreloadMode::Ram => write!(f, "ram"),
+        }
+    }
+}
+
+impl std::str::FromStr for PreloadMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(PreloadMode::None),
+            "mmap" => Ok(PreloadMode::Mmap),
+            "ram" => Ok(PreloadMode::Ram),
+            _ => Err(format!("Unknown preload mode '{s}'. Use: none, mmap, ram")),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Читает по байту со страницы, чтобы ОС подгрузила её в кэш.
+fn touch(data: &[u8]) {
+    let sum = data
+        .iter()
+        .step_by(PAGE_SIZE)
+        .fold(0u8, |acc, &b| acc.wrapping_add(b));
+
+    std::hint::black_box(sum);
+}
+
+/// Поток, прогревающий `window` байт впереди позиции читателя. Новый
+/// проход цикла (позиция ушла назад) начинает прогрев заново.
+fn spawn_readahead(
+    data: Arc<Preloaded>,
+    position: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    window: usize,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let data = data.as_slice();
+        let mut warm_until = 0usize;
+        let mut last_pos = 0usize;
+
+        while !stop.load(Ordering::Relaxed) {
+            let pos = (position.load(Ordering::Relaxed) as usize).min(data.len());
+            if pos < last_pos || pos > warm_until {
+                warm_until = pos;
+            }
+            last_pos = pos;
+
+            let target = pos.saturating_add(window).min(data.len());
+            if warm_until >= target {
+                std::thread::sleep(READAHEAD_POLL);
+                continue;
+            }
+
+            let end = target.min(warm_until + READAHEAD_CHUNK);
+            touch(&data[warm_until..end]);
+            warm_until = end;
+        }
+    })
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preloaded_readers_match_file() {
+        let expected: Vec<u8> = (0..3 * READAHEAD_CHUNK as u32)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), &expected).unwrap();
+
+        let configs = [
+            PreloadConfig {
+                mode: PreloadMode::Ram,
+                readahead_bytes: None,
+            },
+            PreloadConfig {
+                mode: PreloadMode::Mmap,
+                readahead_bytes: None,
+            },
+            PreloadConfig {
+                mode: PreloadMode::Mmap,
+                readahead_bytes: Some(READAHEAD_CHUNK as u64),
+            },
+        ];
+
+        for config in configs {
+            let input = PreloadedInput::prepare(tmp.path(), &config)
+                .unwrap()
+                .unwrap();
+            assert_eq!(input.len(), expected.len());
+
+            // Два прохода, как при --loop
+            for _ in 0..2 {
+                let mut actual = Vec::new();
+                input.reader().read_to_end(&mut actual).unwrap();
+                assert!(actual == expected, "{config:?}");
+            }
+        }
+
+        let none = PreloadConfig::default();
+        assert!(PreloadedInput::prepare(tmp.path(), &none)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_preload_config_validate() {
+        assert_eq!("RAM".parse::<PreloadMode>(), Ok(PreloadMode::Ram));
+        assert_eq!(PreloadMode::Mmap.to_string(), "mmap");
+        assert!("disk".parse::<PreloadMode>().is_err());
+
+        let readahead = PreloadConfig {
+            mode: PreloadMode::Ram,
+            readahead_bytes: Some(1 << 20),
+        };
+        assert!(readahead.validate().is_err());
+        assert!(PreloadConfig {
+            mode: PreloadMode::Mmap,
+            ..readahead
+        }
+        .validate()
+        .is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
@@ -6,28 +7,9 @@ use std::{
     time::{Duration, Instant},
 };
 
-use glos_types::IqBlock;
-
-/// Максимальный размер UDP payload (стандартный IPv4).
-pub const UDP_MAX_PAYLOAD: usize = 65_507;
-
-pub const UDP_TIMESTAMP_SIZE: usize = 8;
-pub const UDP_SAMPLE_COUNT_SIZE: usize = 2;
-
-/// Размер заголовка UDP-пакета GLOS.
-pub const UDP_HEADER_SIZE: usize = UDP_TIMESTAMP_SIZE + UDP_SAMPLE_COUNT_SIZE;
-
-/// UDP-пакет с IQ-данными.
-///
-/// Формат передачи данных (big-endian):
-/// ```text
-/// [0..8]  TIMESTAMP       u64  — метка времени блока (наносекунды)
-/// [8..10] SAMPLE_COUNT    u16  — количество IQ пар
-/// [10..]  IQ_DATA         [u8] — сырые IQ байты
-/// ```
-pub struct UdpPacket;
-
-pub struct UdpPacketizer;
+use glos_core::SessionDigest;
+use glos_types::{IqBlock, SharedClock, SystemClock};
+use parking_lot::Mutex;
 
 /// Lock-free метрики сессии воспроизведения.
 #[derive(Debug, Default)]
@@ -38,6 +20,55 @@ pub struct ReplayMetrics {
     pub underruns: AtomicU64,
     pub send_errors: AtomicU64,
     pub timing_error_ns_total: AtomicU64,
+    /// Принято сообщений обратной связи от приёмника
+    pub feedback_messages: AtomicU64,
+    /// Блоков, повторно отправленных по NACK
+    pub retransmits: AtomicU64,
+    /// NACK-ов на блоки, уже вытесненные из кэша
+    pub nacks_unserved: AtomicU64,
+    /// Сколько раз притормаживали из-за заполненного буфера приёмника
+    pub throttle_events: AtomicU64,
+    /// Суммарное время торможения (нс)
+    pub throttle_ns_total: AtomicU64,
+    /// Блоков, выброшенных слоем искажений
+    pub impair_lost: AtomicU64,
+    /// Блоков, отправленных повторно слоем искажений
+    pub impair_duplicated: AtomicU64,
+    /// Блоков, переставленных со следующим
+    pub impair_reordered: AtomicU64,
+    /// Блоков с искусственной задержкой
+    pub impair_jittered: AtomicU64,
+    /// Суммарная искусственная задержка (нс)
+    pub impair_jitter_ns_total: AtomicU64,
+    /// Байт IQ данных до сжатия UDP-потока
+    pub compress_bytes_in: AtomicU64,
+    /// Байт IQ данных после сжатия
+    pub compress_bytes_out: AtomicU64,
+    /// Время, потраченное на сжатие (нс)
+    pub compress_ns_total: AtomicU64,
+    /// Итоги завершённых проходов по файлу
+    loops: Mutex<Vec<LoopSummary>>,
+    /// Дайджест отправленных блоков (только протокол v2)
+    digest: Mutex<Option<SessionDigest>>,
+}
+
+/// Итоги одного прохода по файлу (одной итерации `--loop`).
+///
+/// Счётчики — приращения общих метрик за время прохода, так что проблема в
+/// конкретной итерации не растворяется в сумме за всю сессию.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopSummary {
+    /// Номер прохода, начиная с 1
+    pub index: u64,
+    /// Воспроизводимый файл
+    pub file: PathBuf,
+    pub packets_sent: u64,
+    pub samples_sent: u64,
+    pub underruns: u64,
+    pub send_errors: u64,
+    pub timing_error_ns_total: u64,
+    /// Проход дошёл до конца файла (`false` — прерван остановкой)
+    pub completed: bool,
 }
 
 /// Управляет темпом воспроизведения с учётом `speed` и компенсаций дрейфа.
@@ -47,89 +78,13 @@ pub struct ReplayMetrics {
 /// отстаём - фиксируем underrun и продолжаем без задержки.
 pub struct TimingController {
     pub speed: f64,
-    session_start: Instant,
+    clock: SharedClock,
+    session_start: Duration,
     file_start_ns: Option<u64>,
+    last_timestamp_ns: Option<u64>,
     paused: Arc<AtomicBool>,
 }
 
-impl UdpPacket {
-    /// Сериализует блок в UDP payload.
-    pub fn encode(block: &IqBlock) -> Result<Vec<u8>, String> {
-        let max_data = UDP_MAX_PAYLOAD - UDP_HEADER_SIZE;
-
-        if block.data.len() > max_data {
-            return Err(format!(
-                "Block data {} bytes exceeds UDP payload lomit {} bytes",
-                block.data.len(),
-                max_data,
-            ));
-        }
-
-        if block.sample_count > u16::MAX as u32 {
-            return Err(format!(
-                "sample_count {} exceeds u16 range ({})",
-                block.sample_count,
-                u16::MAX
-            ));
-        }
-
-        let sample_count = block.sample_count as u16;
-        let mut buf = Vec::with_capacity(UDP_HEADER_SIZE + block.data.len());
-
-        buf.extend_from_slice(&block.timestamp_ns.to_be_bytes());
-        buf.extend_from_slice(&sample_count.to_be_bytes());
-        buf.extend_from_slice(&block.data);
-
-        Ok(buf)
-    }
-
-    /// Десериализует UDP payload в `(timestamp_ns, sample_count, iq_data)`.
-    pub fn decode(buf: &[u8]) -> Result<(u64, u16, &[u8]), String> {
-        if buf.len() < UDP_HEADER_SIZE {
-            return Err(format!(
-                "Packet too short: {} < {}",
-                buf.len(),
-                UDP_HEADER_SIZE,
-            ));
-        }
-
-        let timestamp_ns = u64::from_be_bytes(buf[0..8].try_into().unwrap());
-        let sample_count = u16::from_be_bytes(buf[8..10].try_into().unwrap());
-        let iq_data = &buf[UDP_HEADER_SIZE..];
-
-        Ok((timestamp_ns, sample_count, iq_data))
-    }
-}
-
-impl UdpPacketizer {
-    pub fn packetize(block: &IqBlock) -> Result<Vec<Vec<u8>>, String> {
-        let max_data = UDP_MAX_PAYLOAD - UDP_HEADER_SIZE;
-
-        if block.sample_count > u16::MAX as u32 {
-            return Err("sample_count exceeds u16".into());
-        }
-
-        let mut packets = Vec::new();
-        let mut offset = 0;
-
-        while offset < block.data.len() {
-            let end = (offset + max_data).min(block.data.len());
-            let chunk = &block.data[offset..end];
-            let mut buf = Vec::with_capacity(UDP_HEADER_SIZE + chunk.len());
-
-            buf.extend_from_slice(&block.timestamp_ns.to_be_bytes());
-            buf.extend_from_slice(&(block.sample_count as u16).to_be_bytes());
-            buf.extend_from_slice(chunk);
-
-            packets.push(buf);
-
-            offset = end;
-        }
-
-        Ok(packets)
-    }
-}
-
 impl ReplayMetrics {
     pub fn new() -> Arc<Self> {
         Arc::new(Self::default())
@@ -156,6 +111,88 @@ impl ReplayMetrics {
         self.timing_error_ns_total.load(Ordering::Relaxed) as f64 / pkts as f64 / 1_000.0
     }
 
+    /// Начало прохода: снимок счётчиков, от которого [`Self::end_loop`]
+    /// посчитает приращения.
+    pub fn begin_loop(
+        &self,
+        index: u64,
+        file: &Path,
+    ) -> LoopSummary {
+        LoopSummary {
+            index,
+            file: file.to_path_buf(),
+            packets_sent: self.packets_sent.load(Ordering::Relaxed),
+            samples_sent: self.samples_sent.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            send_errors: self.send_errors.load(Ordering::Relaxed),
+            timing_error_ns_total: self.timing_error_ns_total.load(Ordering::Relaxed),
+            completed: false,
+        }
+    }
+
+    /// Конец прохода, начатого [`Self::begin_loop`].
+    pub fn end_loop(
+        &self,
+        start: LoopSummary,
+        completed: bool,
+    ) {
+        let now = self.begin_loop(start.index, &start.file);
+        let summary = LoopSummary {
+            packets_sent: now.packets_sent - start.packets_sent,
+            samples_sent: now.samples_sent - start.samples_sent,
+            underruns: now.underruns - start.underruns,
+            send_errors: now.send_errors - start.send_errors,
+            timing_error_ns_total: now.timing_error_ns_total - start.timing_error_ns_total,
+            completed,
+            ..start
+        };
+
+        self.loops.lock().push(summary);
+    }
+
+    /// Итоги проходов в порядке воспроизведения.
+    pub fn loop_summaries(&self) -> Vec<LoopSummary> {
+        self.loops.lock().clone()
+    }
+
+    /// Учитывает сжатие одного блока: `bytes_in` до, `bytes_out` после.
+    pub fn record_compression(
+        &self,
+        bytes_in: usize,
+        bytes_out: usize,
+        elapsed: Duration,
+    ) {
+        self.compress_bytes_in
+            .fetch_add(bytes_in as u64, Ordering::Relaxed);
+        self.compress_bytes_out
+            .fetch_add(bytes_out as u64, Ordering::Relaxed);
+        self.compress_ns_total
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Степень сжатия UDP-потока (None, если сжатие выключено).
+    pub fn compression_ratio(&self) -> Option<f64> {
+        let out = self.compress_bytes_out.load(Ordering::Relaxed);
+
+        (out > 0).then(|| self.compress_bytes_in.load(Ordering::Relaxed) as f64 / out as f64)
+    }
+
+    /// Учитывает отправленный блок в дайджесте сессии.
+    pub fn record_digest(
+        &self,
+        block: &IqBlock,
+    ) {
+        self.digest
+            .lock()
+            .get_or_insert_with(SessionDigest::new)
+            .push_block(block);
+    }
+
+    /// Дайджест отправленных блоков (None, если протокол v1).
+    pub fn session_digest(&self) -> Option<SessionDigest> {
+        *self.digest.lock()
+    }
+
     pub fn print_summary(
         &self,
         start: &Instant,
@@ -186,27 +223,131 @@ impl ReplayMetrics {
         );
         eprintln!("  Throughput    : {:.3} Msps", self.throughput_msps(start));
         eprintln!("  Timing error  : {:.1} µs avg", self.avg_timing_error_us());
+
+        if self.feedback_messages.load(Ordering::Relaxed) > 0 {
+            eprintln!(
+                "  Throttled     : {} times, {:.1}s total",
+                self.throttle_events.load(Ordering::Relaxed),
+                self.throttle_ns_total.load(Ordering::Relaxed) as f64 / 1e9
+            );
+            eprintln!(
+                "  Retransmits   : {} ({} unserved NACKs)",
+                self.retransmits.load(Ordering::Relaxed),
+                self.nacks_unserved.load(Ordering::Relaxed)
+            );
+        }
+
+        let impaired = self.impair_lost.load(Ordering::Relaxed)
+            + self.impair_duplicated.load(Ordering::Relaxed)
+            + self.impair_reordered.load(Ordering::Relaxed)
+            + self.impair_jittered.load(Ordering::Relaxed);
+
+        if impaired > 0 {
+            eprintln!(
+                "  Impairments   : lost={} dup={} reordered={} jittered={} ({:.1} ms total)",
+                self.impair_lost.load(Ordering::Relaxed),
+                self.impair_duplicated.load(Ordering::Relaxed),
+                self.impair_reordered.load(Ordering::Relaxed),
+                self.impair_jittered.load(Ordering::Relaxed),
+                self.impair_jitter_ns_total.load(Ordering::Relaxed) as f64 / 1e6
+            );
+        }
+
+        if let Some(ratio) = self.compression_ratio() {
+            eprintln!(
+                "  Compression   : {ratio:.2}x ({:.1} MB → {:.1} MB, {:.1} ms CPU)",
+                self.compress_bytes_in.load(Ordering::Relaxed) as f64 / 1e6,
+                self.compress_bytes_out.load(Ordering::Relaxed) as f64 / 1e6,
+                self.compress_ns_total.load(Ordering::Relaxed) as f64 / 1e6
+            );
+        }
+
+        if let Some(digest) = self.session_digest() {
+            eprintln!("  Digest        : {digest}");
+        }
+
+        let loops = self.loop_summaries();
+        if loops.len() > 1 {
+            eprintln!("  Per loop      :");
+            eprintln!(
+                "  {:>5} {:>10} {:>12} {:>9} {:>7} {:>10}  file",
+                "#", "packets", "samples", "underruns", "errors", "timing µs"
+            );
+            for l in &loops {
+                eprintln!(
+                    "  {:>5} {:>10} {:>12} {:>9} {:>7} {:>10.1}  {}{}",
+                    l.index,
+                    l.packets_sent,
+                    l.samples_sent,
+                    l.underruns,
+                    l.send_errors,
+                    l.avg_timing_error_us(),
+                    l.file.file_name().unwrap_or_default().to_string_lossy(),
+                    if l.completed { "" } else { " (stopped)" }
+                );
+            }
+        }
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     }
 }
 
+impl LoopSummary {
+    /// Средняя ошибка тайминга за проход (мкс).
+    pub fn avg_timing_error_us(&self) -> f64 {
+        if self.packets_sent == 0 {
+            return 0.0;
+        }
+
+        self.timing_error_ns_total as f64 / self.packets_sent as f64 / 1_000.0
+    }
+}
+
 impl TimingController {
     pub fn new(
         speed: f64,
         paused: Arc<AtomicBool>,
+    ) -> Self {
+        Self::with_clock(speed, paused, SystemClock::shared())
+    }
+
+    /// Контроллер, работающий по заданным часам (в тестах — виртуальным).
+    pub fn with_clock(
+        speed: f64,
+        paused: Arc<AtomicBool>,
+        clock: SharedClock,
     ) -> Self {
         Self {
             speed: speed.max(0.01),
-            session_start: Instant::now(),
+            session_start: clock.now(),
+            clock,
             file_start_ns: None,
+            last_timestamp_ns: None,
             paused,
         }
     }
 
     /// Сбрасывает таймер (вызывается при старте / resume после длинной паузы).
     pub fn reset(&mut self) {
-        self.session_start = Instant::now();
+        self.session_start = self.clock.now();
         self.file_start_ns = None;
+        self.last_timestamp_ns = None;
+    }
+
+    /// Меняет скорость на лету без скачка во времени.
+    ///
+    /// Привязка файлового времени к реальному переносится на последний
+    /// отправленный блок, так что новая скорость действует только для
+    /// последующих блоков.
+    pub fn set_speed(
+        &mut self,
+        speed: f64,
+    ) {
+        if let Some(ts) = self.last_timestamp_ns {
+            self.file_start_ns = Some(ts);
+            self.session_start = self.clock.now();
+        }
+
+        self.speed = speed.max(0.01);
     }
 
     /// Ждёт нужного момента для отправки блока с `timestamp_ns`.
@@ -219,15 +360,15 @@ impl TimingController {
     ) -> u64 {
         // Ждём пока пауза активна
         while self.paused.load(Ordering::Relaxed) {
-            std::thread::sleep(Duration::from_millis(20));
+            let before = self.clock.now();
+            self.clock.sleep(Duration::from_millis(20));
             // Сдвигаем session_start на время паузы чтобы не получить burst
-            self.session_start = Instant::now()
-                .checked_sub(self.elapsed_virtual_ns())
-                .unwrap_or_else(Instant::now);
+            self.session_start += self.clock.now().saturating_sub(before);
         }
 
         // Инициализируем привязку файлового времени к реальному
         let file_start = *self.file_start_ns.get_or_insert(timestamp_ns);
+        self.last_timestamp_ns = Some(timestamp_ns);
 
         // Сколько виртуального времени файла прошло от начала
         let file_offset_ns = timestamp_ns.saturating_sub(file_start);
@@ -236,14 +377,14 @@ impl TimingController {
         let real_offset_ns = (file_offset_ns as f64 / self.speed) as u64;
 
         // Сколько реального времени прошло с начала сессии
-        let elapsed_ns = self.session_start.elapsed().as_nanos() as u64;
+        let elapsed_ns = self.elapsed_real().as_nanos() as u64;
 
         if real_offset_ns > elapsed_ns {
             let sleep_ns = real_offset_ns - elapsed_ns;
-            std::thread::sleep(Duration::from_nanos(sleep_ns));
+            self.clock.sleep(Duration::from_nanos(sleep_ns));
 
             // Ошибка тайминга после сна
-            let actual_elapsed = self.session_start.elapsed().as_nanos() as u64;
+            let actual_elapsed = self.elapsed_real().as_nanos() as u64;
             let error = actual_elapsed.saturating_sub(real_offset_ns);
             metrics
                 .timing_error_ns_total
@@ -264,58 +405,21 @@ impl TimingController {
     }
 
     pub fn elapsed_virtual_ns(&self) -> Duration {
-        Duration::from_nanos((self.session_start.elapsed().as_nanos() as f64 * self.speed) as u64)
+        Duration::from_nanos((self.elapsed_real().as_nanos() as f64 * self.speed) as u64)
+    }
+
+    /// Время по часам контроллера с момента привязки.
+    fn elapsed_real(&self) -> Duration {
+        self.clock.now().saturating_sub(self.session_start)
     }
 }
 
 #[cfg(test)]
 mod tests {
-
-    use glos_core::IqBlockExt;
+    use glos_types::{Clock, VirtualClock};
 
     use super::*;
 
-    #[test]
-    fn test_udp_packet_encode_decode_roundtrip() {
-        let block = IqBlockExt::new(1_704_067_200_000_000_000, 100, vec![42u8; 400]);
-        let encoded = UdpPacket::encode(&block).unwrap();
-
-        assert_eq!(encoded.len(), UDP_HEADER_SIZE + 400);
-
-        let (ts, count, data) = UdpPacket::decode(&encoded).unwrap();
-
-        assert_eq!(ts, 1_704_067_200_000_000_000);
-        assert_eq!(count, 100);
-        assert_eq!(data, vec![42u8; 400]);
-    }
-
-    #[test]
-    fn test_udp_packet_header_big_endian() {
-        let block = IqBlock::new(0x0102030405060708, 0x0A0B, vec![0u8; 4]);
-        let encoded = UdpPacket::encode(&block).unwrap();
-
-        // timestamp BE
-        assert_eq!(
-            &encoded[0..8],
-            &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
-        );
-
-        // sample_count BE (0x0A0B)
-        assert_eq!(&encoded[8..10], &[0x0A, 0x0B]);
-    }
-
-    #[test]
-    fn test_udp_packet_too_large() {
-        let block = IqBlock::new(0, 1, vec![0u8; UDP_MAX_PAYLOAD]);
-
-        assert!(UdpPacket::encode(&block).is_err());
-    }
-
-    #[test]
-    fn test_udp_decode_too_short() {
-        assert!(UdpPacket::decode(&[0u8; 5]).is_err());
-    }
-
     #[test]
     fn test_replay_metrics_throughput() {
         let m = ReplayMetrics::new();
@@ -377,6 +481,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_timing_controller_set_speed_no_burst() {
+        let paused = Arc::new(AtomicBool::new(false));
+        let mut ctrl = TimingController::new(1.0, paused);
+        let metrics = ReplayMetrics::new();
+
+        ctrl.wait_for(0, &metrics);
+        ctrl.wait_for(20_000_000, &metrics);
+
+        // Ускоряемся в 2 раза: следующие 100мс файлового времени ~ 50мс реального,
+        // отсчёт идёт от последнего блока, а не от начала файла
+        ctrl.set_speed(2.0);
+        assert_eq!(ctrl.speed, 2.0);
+
+        let before = Instant::now();
+        ctrl.wait_for(120_000_000, &metrics);
+        let elapsed = before.elapsed();
+
+        assert!(
+            elapsed.as_millis() >= 30,
+            "Expected pause ~50ms, got {}ms",
+            elapsed.as_millis()
+        );
+        assert!(
+            elapsed.as_millis() <= 120,
+            "Pause too long: {}ms",
+            elapsed.as_millis()
+        );
+    }
+
     #[test]
     fn test_timing_controller_pause_resume() {
         let paused = Arc::new(AtomicBool::new(false));
@@ -411,4 +545,43 @@ mod tests {
             elapsed.as_millis()
         );
     }
+
+    #[test]
+    fn test_timing_controller_virtual_clock_exact() {
+        let clock = VirtualClock::new(0);
+        let paused = Arc::new(AtomicBool::new(false));
+        let mut ctrl = TimingController::with_clock(2.0, paused, clock.clone());
+        let metrics = ReplayMetrics::new();
+
+        ctrl.wait_for(5_000_000_000, &metrics);
+        assert_eq!(clock.now(), Duration::ZERO);
+
+        // 10 с файлового времени при 2х — ровно 5 с по часам, без ошибки
+        assert_eq!(ctrl.wait_for(15_000_000_000, &metrics), 0);
+        assert_eq!(clock.now(), Duration::from_secs(5));
+
+        // Ускорение действует от последнего блока
+        ctrl.set_speed(4.0);
+        ctrl.wait_for(19_000_000_000, &metrics);
+        assert_eq!(clock.now(), Duration::from_secs(6));
+        assert_eq!(metrics.underruns.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_timing_controller_virtual_clock_underrun() {
+        let clock = VirtualClock::new(0);
+        let paused = Arc::new(AtomicBool::new(false));
+        let mut ctrl = TimingController::with_clock(1.0, paused, clock.clone());
+        let metrics = ReplayMetrics::new();
+
+        ctrl.wait_for(0, &metrics);
+
+        // Отправитель «задумался» на 30 мс при 10 мс запаса
+        clock.advance(Duration::from_millis(30));
+        let lag = ctrl.wait_for(10_000_000, &metrics);
+
+        assert_eq!(lag, 20_000_000);
+        assert_eq!(metrics.underruns.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.timing_error_ns_total.load(Ordering::Relaxed), lag);
+    }
 }
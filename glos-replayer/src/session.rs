@@ -1,18 +1,25 @@
 use std::{
     fs::File,
+    io::Read,
     net::UdpSocket,
+    path::Path,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use glos_core::{GlosReader, ReadStats};
-use glos_types::GlosHeader;
+use glos_core::{
+    dsp::Resampler, GlosReader, ReadStats, UdpCompression, UdpPacketizer, UdpProtocol,
+    UDP_V2_HEADER_SIZE,
+};
+use glos_types::{GlosHeader, RuntimeControl};
+use log::debug;
 
 use crate::{
-    ReplayConfig, ReplayError, ReplayMetrics, ReplayResult, TimingController, UdpPacketizer,
+    FileSink, FlowController, Impairer, PreloadMode, PreloadedInput, ReplayConfig, ReplayError,
+    ReplayMetrics, ReplayResult, TimingController,
 };
 
 /// Сессия воспроизведения (single-threaded).
@@ -21,17 +28,59 @@ pub struct ReplaySession {
     metrics: Arc<ReplayMetrics>,
     stop_flag: Arc<AtomicBool>,
     pause_flag: Arc<AtomicBool>,
+    control: Arc<RuntimeControl>,
 }
 
 impl ReplaySession {
     /// Создаёт сессию, проверяя конфигурацию.
-    pub fn new(config: ReplayConfig) -> ReplayResult<Self> {
+    pub fn new(mut config: ReplayConfig) -> ReplayResult<Self> {
         if config.speed <= 0.0 {
             return Err(ReplayError::Config("speed must be > 0".to_string()));
         }
 
+        if let Some(fb) = &config.feedback {
+            if fb.high_watermark_pct == 0 || fb.high_watermark_pct > 100 {
+                return Err(ReplayError::Config(
+                    "feedback high watermark must be in 1..=100".to_string(),
+                ));
+            }
+        }
+
+        if config.compression != UdpCompression::None && config.protocol != UdpProtocol::V2 {
+            return Err(ReplayError::Config(
+                "UDP compression requires protocol v2".to_string(),
+            ));
+        }
+
+        if let Some(imp) = &config.impairments {
+            imp.validate().map_err(ReplayError::Config)?;
+        }
+
+        if config.file_sink.is_some() && (config.feedback.is_some() || config.impairments.is_some())
+        {
+            return Err(ReplayError::Config(
+                "file output cannot be combined with feedback or impairments".to_string(),
+            ));
+        }
+
+        config.preload.validate().map_err(ReplayError::Config)?;
+
+        if config.preload.mode != PreloadMode::None && config.reads_stdin() {
+            return Err(ReplayError::Config(
+                "preload requires a file input, not stdin".to_string(),
+            ));
+        }
+
+        if config.loop_playback && config.reads_stdin() {
+            eprintln!("[replayer] Loop disabled: stdin can only be read once");
+            config.loop_playback = false;
+        }
+
+        let control = RuntimeControl::new(Duration::from_secs(config.stats_interval_secs));
+
         Ok(Self {
             config,
+            control,
             metrics: ReplayMetrics::new(),
             stop_flag: Arc::new(AtomicBool::new(false)),
             pause_flag: Arc::new(AtomicBool::new(false)),
@@ -50,28 +99,128 @@ impl ReplaySession {
         self.metrics.clone()
     }
 
+    /// Интервал статистики и уровень логов, меняемые во время
+    /// воспроизведения.
+    pub fn runtime_control(&self) -> Arc<RuntimeControl> {
+        self.control.clone()
+    }
+
+    /// Подменяет флаги и управление общими — для нескольких сессий подряд
+    /// под одними обработчиками сигналов (см. [`crate::WatchSession`]).
+    pub(crate) fn with_shared_flags(
+        mut self,
+        stop_flag: Arc<AtomicBool>,
+        pause_flag: Arc<AtomicBool>,
+        control: Arc<RuntimeControl>,
+    ) -> Self {
+        self.stop_flag = stop_flag;
+        self.pause_flag = pause_flag;
+        self.control = control;
+        self
+    }
+
     /// Запускает воспроизведение. Блокирует до EOF или stop_flag.
     pub fn run(self) -> ReplayResult<()> {
+        let preloaded = PreloadedInput::prepare(&self.config.input_path, &self.config.preload)?;
+        let input = match &preloaded {
+            Some(p) => p.reader(),
+            None => Self::open_input(&self.config.input_path)?,
+        };
+
+        self.play(input, preloaded)
+    }
+
+    /// Воспроизводит уже открытый поток `.glos` (канал, сокет) вместо
+    /// `input_path`. Заголовок и блоки читаются из него подряд, без
+    /// перемотки, поэтому проход один: `loop_playback` игнорируется.
+    pub fn run_stream<R: Read + 'static>(
+        mut self,
+        input: R,
+    ) -> ReplayResult<()> {
+        if self.config.loop_playback {
+            eprintln!("[replayer] Loop disabled: a stream can only be read once");
+            self.config.loop_playback = false;
+        }
+
+        self.play(Box::new(input), None)
+    }
+
+    fn play(
+        self,
+        input: Box<dyn Read>,
+        preloaded: Option<PreloadedInput>,
+    ) -> ReplayResult<()> {
         let cfg = &self.config;
         let metrics = &self.metrics;
         let stop = &self.stop_flag;
         let session_start = Instant::now();
-        let stats_interval = std::time::Duration::from_secs(cfg.stats_interval_secs);
+        let clock = &cfg.clock;
+        let clock_start = clock.now();
+        let control = &self.control;
 
-        let socket = UdpSocket::bind(cfg.bind_addr)?;
-        socket.connect(cfg.target_addr)?;
-
-        let file = File::open(&cfg.input_path)?;
-        let mut reader = GlosReader::new(file)?;
-        let header = reader.header().clone();
+        // Заголовок читается один раз: первый проход продолжает тот же
+        // поток, повторы открывают файл заново
+        let first = GlosReader::new(input)?;
+        let header = first.header().clone();
+        let mut first = Some(first);
 
         Self::print_header_info(&header, cfg);
 
-        let mut timing = TimingController::new(cfg.speed, self.pause_flag.clone());
-        let mut last_stats = Instant::now();
+        // Либо UDP, либо файл: сокет при записи в файл не нужен
+        let mut sink = match &cfg.file_sink {
+            Some(fs) => {
+                let rate = cfg
+                    .resample
+                    .map(|r| r.out_rate)
+                    .unwrap_or(header.sample_rate);
+                Some(FileSink::create(fs, &header, rate)?)
+            }
+            None => None,
+        };
+
+        let socket = match sink {
+            Some(_) => None,
+            None => {
+                let socket = UdpSocket::bind(cfg.bind_addr)?;
+                socket.connect(cfg.target_addr)?;
+                Some(socket)
+            }
+        };
+        let paced = cfg.file_sink.as_ref().is_none_or(|fs| fs.paced);
+
+        let mut resampler = match &cfg.resample {
+            Some(rs) => Some(Resampler::new(header.sample_rate, rs.out_rate, rs.quality)?),
+            None => None,
+        };
+
+        let mut flow = match &cfg.feedback {
+            Some(fb) => {
+                let fc = FlowController::bind(fb.clone())?;
+                eprintln!(
+                    "[replayer] Listening for receiver feedback on {}",
+                    fc.local_addr()?
+                );
+                Some(fc)
+            }
+            None => None,
+        };
+
+        let initial_speed = cfg
+            .speed_profile
+            .as_ref()
+            .map(|p| p.speed_at(std::time::Duration::ZERO))
+            .unwrap_or(cfg.speed);
+        let mut timing =
+            TimingController::with_clock(initial_speed, self.pause_flag.clone(), clock.clone());
+        // Последняя скорость профиля. С `timing.speed` сравнивать нельзя:
+        // контроллер ограничивает её снизу, и ступень медленнее предела
+        // перепривязывала бы таймер на каждом блоке.
+        let mut profile_speed = initial_speed;
+        let mut impairer = cfg.impairments.map(Impairer::new);
+        let mut last_stats = clock_start;
         let mut loop_count = 0u64;
 
-        'outer: loop {
+        loop {
             if stop.load(Ordering::Acquire) {
                 break;
             }
@@ -81,17 +230,29 @@ impl ReplaySession {
             if loop_count > 1 {
                 eprintln!("[replayer] Loop #{loop_count}");
                 timing.reset();
+
+                if let Some(r) = resampler.as_mut() {
+                    r.reset();
+                }
             }
 
-            let file = File::open(&cfg.input_path)?;
-            reader = GlosReader::new(file)?;
+            let mut reader = match first.take() {
+                Some(r) => r,
+                None => match &preloaded {
+                    Some(p) => GlosReader::new(p.reader())?,
+                    None => GlosReader::new(Self::open_input(&cfg.input_path)?)?,
+                },
+            };
+            let pass = metrics.begin_loop(loop_count, &cfg.input_path);
+            let mut stopped = false;
 
             while let Some(result) = reader.next_block() {
                 if stop.load(Ordering::Acquire) {
-                    break 'outer;
+                    stopped = true;
+                    break;
                 }
 
-                let block = match result {
+                let mut block = match result {
                     Ok(b) => b,
                     Err(e) => {
                         eprintln!("[replayer] Skipping corrupted block: {e}");
@@ -99,9 +260,88 @@ impl ReplaySession {
                     }
                 };
 
-                timing.wait_for(block.timestamp_ns, metrics);
+                if let Some(profile) = &cfg.speed_profile {
+                    // Без темпа часы стоят — расписание идёт по выходным меткам
+                    let elapsed = match sink.as_ref() {
+                        Some(s) if !paced => s.elapsed(),
+                        _ => clock.now().saturating_sub(clock_start),
+                    };
+                    let speed = profile.speed_at(elapsed);
+                    if speed != profile_speed {
+                        eprintln!(
+                            "[replayer] Speed {}x → {}x at {:.1}s",
+                            profile_speed,
+                            speed,
+                            elapsed.as_secs_f64()
+                        );
+                        profile_speed = speed;
+                        timing.set_speed(speed);
+                    }
+                }
 
-                let packets = match UdpPacketizer::packetize(&block) {
+                if let (Some(fc), Some(socket)) = (flow.as_mut(), socket.as_ref()) {
+                    let nacks = fc.poll(metrics);
+                    fc.retransmit(socket, &nacks, metrics);
+
+                    // После паузы перепривязываем таймер, иначе догоним burst-ом
+                    if !fc.throttle(socket, metrics).is_zero() {
+                        timing.set_speed(timing.speed);
+                    }
+                }
+
+                if let Some(r) = resampler.as_mut() {
+                    match r.process_bytes(&block.data, header.iq_format) {
+                        Ok(data) => {
+                            block.sample_count =
+                                (data.len() / header.iq_format.sample_size()) as u32;
+                            block.data = data;
+                        }
+                        Err(e) => {
+                            eprintln!("[replayer] Resample error: {e}");
+                            continue;
+                        }
+                    }
+
+                    // Фильтру ещё не хватает истории — выборки уйдут со следующим блоком
+                    if block.sample_count == 0 {
+                        continue;
+                    }
+                }
+
+                if paced {
+                    timing.wait_for(block.timestamp_ns, metrics);
+                }
+
+                if let Some(fs) = sink.as_mut() {
+                    let samples = block.sample_count as u64;
+                    fs.write(block, timing.speed)?;
+                    metrics.samples_sent.fetch_add(samples, Ordering::Relaxed);
+
+                    if clock.now().saturating_sub(last_stats) >= control.stats_interval() {
+                        Self::log_progress(metrics, &session_start, reader.stats(), timing.speed);
+                        last_stats = clock.now();
+                    }
+                    continue;
+                }
+
+                let Some(socket) = socket.as_ref() else {
+                    continue;
+                };
+
+                let packets = match cfg.compression {
+                    UdpCompression::None => UdpPacketizer::packetize_with(&block, cfg.protocol),
+                    compression => {
+                        let started = Instant::now();
+                        let packets = UdpPacketizer::packetize_v2_with(&block, compression);
+                        if let Ok(p) = &packets {
+                            let out = p.iter().map(|p| p.len() - UDP_V2_HEADER_SIZE).sum();
+                            metrics.record_compression(block.data.len(), out, started.elapsed());
+                        }
+                        packets
+                    }
+                };
+
+                let packets = match packets {
                     Ok(p) => p,
                     Err(e) => {
                         eprintln!("[replayer] Packetize error: {e}");
@@ -110,29 +350,55 @@ impl ReplaySession {
                     }
                 };
 
-                for payload in packets {
-                    match socket.send(&payload) {
-                        Ok(n) => {
-                            metrics.packets_sent.fetch_add(1, Ordering::Relaxed);
-                            metrics.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
-                        }
-                        Err(e) => {
-                            metrics.send_errors.fetch_add(1, Ordering::Relaxed);
-                            return Err(ReplayError::Network(e));
-                        }
+                let impaired = impairer
+                    .as_mut()
+                    .map(|imp| imp.apply(packets.clone(), metrics));
+
+                if let Some(delay) = impaired.as_ref().map(|i| i.delay) {
+                    if !delay.is_zero() {
+                        clock.sleep(delay);
                     }
                 }
 
+                let outgoing = impaired.as_ref().map(|i| &i.packets).unwrap_or(&packets);
+                Self::send_packets(socket, outgoing, metrics)?;
+
+                debug!(
+                    "Block @ {} ns: {} samples in {} packets",
+                    block.timestamp_ns,
+                    block.sample_count,
+                    outgoing.len()
+                );
+
                 metrics
                     .samples_sent
                     .fetch_add(block.sample_count as u64, Ordering::Relaxed);
 
-                if last_stats.elapsed() >= stats_interval {
-                    Self::log_progress(metrics, &session_start, reader.stats());
-                    last_stats = Instant::now();
+                if cfg.protocol == UdpProtocol::V2 {
+                    metrics.record_digest(&block);
+                }
+
+                if let Some(fc) = flow.as_mut() {
+                    fc.remember(block.timestamp_ns, packets);
                 }
+
+                if clock.now().saturating_sub(last_stats) >= control.stats_interval() {
+                    Self::log_progress(metrics, &session_start, reader.stats(), timing.speed);
+                    last_stats = clock.now();
+                }
+            }
+
+            if stopped {
+                metrics.end_loop(pass, false);
+                break;
             }
 
+            if let (Some(imp), Some(socket)) = (impairer.as_mut(), socket.as_ref()) {
+                Self::send_packets(socket, &imp.flush(), metrics)?;
+            }
+
+            metrics.end_loop(pass, true);
+
             eprintln!(
                 "[replayer] EOF: {} blocks, {} samples",
                 reader.stats().blocks_ok,
@@ -144,25 +410,128 @@ impl ReplaySession {
             }
         }
 
+        if let Some(fs) = sink {
+            let written = fs.total_samples();
+            fs.finish()?;
+
+            if let Some(cfg_fs) = &cfg.file_sink {
+                eprintln!("[replayer] Wrote {written} samples to {:?}", cfg_fs.path);
+            }
+        }
+
         metrics.print_summary(&session_start);
 
         Ok(())
     }
 
+    /// Файл или стандартный ввод ([`crate::STDIN_INPUT`]).
+    fn open_input(path: &Path) -> ReplayResult<Box<dyn Read>> {
+        if path.as_os_str() == crate::STDIN_INPUT {
+            return Ok(Box::new(std::io::stdin().lock()));
+        }
+
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn send_packets(
+        socket: &UdpSocket,
+        packets: &[Vec<u8>],
+        metrics: &ReplayMetrics,
+    ) -> ReplayResult<()> {
+        for payload in packets {
+            match socket.send(payload) {
+                Ok(n) => {
+                    metrics.packets_sent.fetch_add(1, Ordering::Relaxed);
+                    metrics.bytes_sent.fetch_add(n as u64, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    metrics.send_errors.fetch_add(1, Ordering::Relaxed);
+                    return Err(ReplayError::Network(e));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn print_header_info(
         h: &GlosHeader,
         cfg: &ReplayConfig,
     ) {
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        eprintln!("  Input         : {:?}", cfg.input_path);
-        eprintln!("  Target        : {}", cfg.target_addr);
-        eprintln!("  Speed         : {}x", cfg.speed);
+        if cfg.reads_stdin() {
+            eprintln!("  Input         : stdin");
+        } else {
+            eprintln!("  Input         : {:?}", cfg.input_path);
+        }
+        match &cfg.file_sink {
+            Some(fs) => eprintln!(
+                "  Output file   : {:?} (timestamps={}, offset={:.3}s, paced={})",
+                fs.path,
+                fs.timestamps,
+                fs.offset_ns as f64 / 1e9,
+                fs.paced
+            ),
+            None => eprintln!(
+                "  Target        : {} (protocol {}, compression {})",
+                cfg.target_addr, cfg.protocol, cfg.compression
+            ),
+        }
+        match &cfg.speed_profile {
+            Some(profile) => eprintln!("  Speed profile : {profile}"),
+            None => eprintln!("  Speed         : {}x", cfg.speed),
+        }
         eprintln!("  Loop          : {}", cfg.loop_playback);
+        if cfg.preload.mode != PreloadMode::None {
+            match cfg.preload.readahead_bytes {
+                Some(window) => eprintln!(
+                    "  Preload       : {} (readahead {} MiB)",
+                    cfg.preload.mode,
+                    window >> 20
+                ),
+                None => eprintln!("  Preload       : {}", cfg.preload.mode),
+            }
+        }
+        if let Some(fb) = &cfg.feedback {
+            eprintln!(
+                "  Feedback      : {} (high water {}%)",
+                fb.bind_addr, fb.high_watermark_pct
+            );
+        }
+        if let Some(rs) = &cfg.resample {
+            eprintln!(
+                "  Resample      : {:.3} → {:.3} MHz ({})",
+                h.sample_rate as f64 / 1e6,
+                rs.out_rate as f64 / 1e6,
+                rs.quality
+            );
+        }
+        if let Some(imp) = &cfg.impairments {
+            eprintln!(
+                "  Impairments   : loss={} reorder={} dup={} jitter={} seed={}",
+                imp.loss, imp.reorder, imp.duplicate, imp.jitter, imp.seed
+            );
+        }
         eprintln!("  SDR type      : {:?}", h.sdr_type);
         eprintln!("  Sample rate   : {:.3} MHz", h.sample_rate as f64 / 1e6);
         eprintln!("  Center freq   : {:.3} MHz", h.center_freq as f64 / 1e6);
         eprintln!("  IQ format     : {:?}", h.iq_format);
         eprintln!("  Total samples : {}", h.total_samples);
+        if let Some(d) = h.duration() {
+            match &cfg.speed_profile {
+                Some(profile) => eprintln!(
+                    "  Duration      : {:.3}s ({:.1}s with profile)",
+                    d.as_secs_f64(),
+                    profile.playback_duration(d).as_secs_f64()
+                ),
+                None => eprintln!(
+                    "  Duration      : {:.3}s ({:.1}s at {}x)",
+                    d.as_secs_f64(),
+                    d.as_secs_f64() / cfg.speed,
+                    cfg.speed
+                ),
+            }
+        }
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     }
 
@@ -170,10 +539,12 @@ impl ReplaySession {
         m: &ReplayMetrics,
         start: &Instant,
         stats: &ReadStats,
+        speed: f64,
     ) {
         eprintln!(
-            "[ {:.0}s ] pkts={} sampled={} underruns={} errors={} timing_err={:.1}µs blocks_ok={}",
+            "[ {:.0}s ] speed={}x pkts={} sampled={} underruns={} errors={} timing_err={:.1}µs blocks_ok={}",
             start.elapsed().as_secs_f64(),
+            speed,
             m.packets_sent.load(Ordering::Relaxed),
             m.samples_sent.load(Ordering::Relaxed),
             m.underruns.load(Ordering::Relaxed),
@@ -196,12 +567,11 @@ pub fn parse_udp_target(s: &str) -> Result<std::net::SocketAddr, String> {
 mod tests {
     use std::net::SocketAddr;
 
-    use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt};
-    use glos_types::{Compression, IqBlock, IqFormat, SdrType};
+    use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt, StreamVerifier, UdpPacket};
+    use glos_types::{Clock, Compression, IqBlock, IqFormat, SdrType, VirtualClock};
     use tempfile::NamedTempFile;
 
     use super::*;
-    use crate::UdpPacket;
 
     /// Создаёт временный .glos файд с `n_blocks` блоками по `samples` выборок.
     fn make_glos_file(
@@ -253,6 +623,7 @@ mod tests {
             loop_playback: false,
             stats_interval_secs: 60,
             bind_addr: "0.0.0.0:0".parse().unwrap(),
+            ..Default::default()
         };
 
         let session = ReplaySession::new(config).unwrap();
@@ -275,6 +646,255 @@ mod tests {
         assert_eq!(received, 3, "expecting 3 packets (one per block)");
     }
 
+    #[test]
+    fn test_replay_resample_on_the_fly() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let tmp = make_glos_file(4, 1000);
+
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: addr.parse().unwrap(),
+            speed: 100.0,
+            stats_interval_secs: 60,
+            bind_addr: "0.0.0.0:0".parse().unwrap(),
+            resample: Some(crate::ResampleConfig {
+                out_rate: 1_000_000,
+                quality: glos_core::dsp::ResampleQuality::Fast,
+            }),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        // 4000 выборок при 2 → 1 Msps ≈ 2000, минус хвост фильтра
+        let sent = metrics.samples_sent.load(Ordering::Relaxed);
+        assert!((1990..=2000).contains(&sent), "sent={sent}");
+
+        let mut buf = vec![0u8; 65536];
+        let n = listener.recv(&mut buf).unwrap();
+        let (_, count, data) = UdpPacket::decode(&buf[..n]).unwrap();
+
+        assert_eq!(data.len(), count as usize * 4);
+    }
+
+    #[test]
+    fn test_replay_impairments_applied() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        listener
+            .set_read_timeout(Some(std::time::Duration::from_millis(300)))
+            .unwrap();
+
+        let tmp = make_glos_file(20, 10);
+
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: addr.parse().unwrap(),
+            speed: 100.0,
+            stats_interval_secs: 60,
+            bind_addr: "0.0.0.0:0".parse().unwrap(),
+            impairments: Some(crate::ImpairmentConfig {
+                loss: 0.2,
+                reorder: 0.2,
+                duplicate: 0.2,
+                seed: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        let lost = metrics.impair_lost.load(Ordering::Relaxed);
+        let dup = metrics.impair_duplicated.load(Ordering::Relaxed);
+        let sent = metrics.packets_sent.load(Ordering::Relaxed);
+
+        assert!(lost > 0);
+        assert_eq!(sent, 20 - lost + dup);
+
+        let mut buf = vec![0u8; 65536];
+        let mut received = 0;
+        while listener.recv(&mut buf).is_ok() {
+            received += 1;
+        }
+        assert_eq!(received, sent);
+
+        let bad = ReplayConfig {
+            impairments: Some(crate::ImpairmentConfig {
+                duplicate: -0.1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(ReplaySession::new(bad).is_err());
+    }
+
+    #[test]
+    fn test_replay_to_file_sink() {
+        let tmp = make_glos_file(4, 100);
+        let out = NamedTempFile::new().unwrap();
+
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            speed: 2.0,
+            stats_interval_secs: 60,
+            file_sink: Some(crate::FileSinkConfig {
+                timestamps: crate::SinkTimestamps::Replay,
+                offset_ns: 10_000_000_000,
+                ..crate::FileSinkConfig::new(out.path())
+            }),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        assert_eq!(metrics.samples_sent.load(Ordering::Relaxed), 400);
+        assert_eq!(metrics.packets_sent.load(Ordering::Relaxed), 0);
+
+        let mut reader = GlosReader::new(File::open(out.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 400);
+        let source = GlosReader::new(File::open(tmp.path()).unwrap()).unwrap();
+        assert_eq!(
+            reader.header().timestamp_start,
+            source.header().timestamp_start + 10
+        );
+
+        let blocks = glos_core::read_all_blocks(&mut reader).unwrap();
+        let ts: Vec<u64> = blocks.iter().map(|b| b.timestamp_ns).collect();
+
+        // 100 выборок = 50 мкс, на скорости 2x — 25 мкс
+        let t0 = 1_704_067_210_000_000_000u64;
+        assert_eq!(ts, vec![t0, t0 + 25_000, t0 + 50_000, t0 + 75_000]);
+    }
+
+    #[test]
+    fn test_replay_preloaded_input() {
+        let tmp = make_glos_file(4, 100);
+        let out = NamedTempFile::new().unwrap();
+
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            stats_interval_secs: 60,
+            file_sink: Some(crate::FileSinkConfig::new(out.path())),
+            preload: crate::PreloadConfig {
+                mode: PreloadMode::Ram,
+                readahead_bytes: None,
+            },
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config.clone()).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        assert_eq!(metrics.samples_sent.load(Ordering::Relaxed), 400);
+        let reader = GlosReader::new(File::open(out.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 400);
+
+        // Стандартный ввод заранее не прочитать
+        let stdin = ReplayConfig {
+            input_path: crate::STDIN_INPUT.into(),
+            ..config
+        };
+        assert!(ReplaySession::new(stdin).is_err());
+    }
+
+    #[test]
+    fn test_replay_from_stream_plays_once() {
+        let tmp = make_glos_file(4, 100);
+        let out = NamedTempFile::new().unwrap();
+
+        // Поток без Seek: заголовок и блоки читаются из него один раз
+        let bytes = std::fs::read(tmp.path()).unwrap();
+        let stream = std::io::Cursor::new(bytes).take(u64::MAX);
+
+        let config = ReplayConfig {
+            input_path: crate::STDIN_INPUT.into(),
+            loop_playback: true,
+            stats_interval_secs: 60,
+            file_sink: Some(crate::FileSinkConfig {
+                paced: false,
+                ..crate::FileSinkConfig::new(out.path())
+            }),
+            ..Default::default()
+        };
+        assert!(config.reads_stdin());
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run_stream(stream).unwrap();
+
+        assert_eq!(metrics.samples_sent.load(Ordering::Relaxed), 400);
+        assert_eq!(metrics.loop_summaries().len(), 1);
+
+        let reader = GlosReader::new(File::open(out.path()).unwrap()).unwrap();
+        assert_eq!(reader.header().total_samples, 400);
+    }
+
+    #[test]
+    fn test_replay_compressed_stream_verifies() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(std::time::Duration::from_millis(500)))
+            .unwrap();
+
+        let tmp = make_glos_file(5, 1_000);
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: listener.local_addr().unwrap(),
+            speed: 100.0,
+            protocol: UdpProtocol::V2,
+            compression: UdpCompression::Lz4,
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        let mut verifier = StreamVerifier::new();
+        let mut buf = vec![0u8; 65536];
+        while let Ok(n) = listener.recv(&mut buf) {
+            verifier.accept(&buf[..n]).unwrap();
+        }
+
+        // Нулевые выборки сжимаются в десятки раз
+        assert!(verifier.matches(&metrics.session_digest().unwrap()));
+        assert_eq!(verifier.stats().verified, 5);
+        assert!(metrics.compression_ratio().unwrap() > 10.0);
+        assert_eq!(
+            metrics.compress_bytes_in.load(Ordering::Relaxed),
+            verifier.stats().decompressed_bytes
+        );
+
+        // В v1 флагу сжатия негде жить
+        let v1 = ReplayConfig {
+            compression: UdpCompression::Lz4,
+            ..Default::default()
+        };
+        assert!(ReplaySession::new(v1).is_err());
+    }
+
+    #[test]
+    fn test_replay_file_sink_rejects_impairments() {
+        let config = ReplayConfig {
+            file_sink: Some(crate::FileSinkConfig::new("out.glos")),
+            impairments: Some(crate::ImpairmentConfig::default()),
+            ..Default::default()
+        };
+
+        assert!(ReplaySession::new(config).is_err());
+    }
+
     #[test]
     fn test_replay_metrics_updated() {
         let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
@@ -288,6 +908,7 @@ mod tests {
             loop_playback: false,
             stats_interval_secs: 60,
             bind_addr: "0.0.0.0:0".parse().unwrap(),
+            ..Default::default()
         };
 
         let session = ReplaySession::new(config).unwrap();
@@ -299,6 +920,113 @@ mod tests {
         assert_eq!(metrics.send_errors.load(Ordering::Relaxed), 0);
     }
 
+    #[test]
+    fn test_replay_loop_breakdown() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tmp = make_glos_file(3, 100);
+
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: listener.local_addr().unwrap(),
+            speed: 1000.0,
+            loop_playback: true,
+            stats_interval_secs: 60,
+            bind_addr: "0.0.0.0:0".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        let stop = session.stop_flag();
+
+        let watcher = {
+            let metrics = metrics.clone();
+            std::thread::spawn(move || {
+                while metrics.loop_summaries().len() < 2 {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                stop.store(true, Ordering::Release);
+            })
+        };
+
+        session.run().unwrap();
+        watcher.join().unwrap();
+
+        let loops = metrics.loop_summaries();
+        assert!(loops.len() >= 2);
+
+        for (i, l) in loops.iter().enumerate().take(2) {
+            assert_eq!(l.index, i as u64 + 1);
+            assert_eq!(l.file, tmp.path());
+            assert_eq!((l.packets_sent, l.samples_sent), (3, 300));
+            assert!(l.completed);
+        }
+
+        // Сумма по проходам совпадает с общими счётчиками
+        let total: u64 = loops.iter().map(|l| l.packets_sent).sum();
+        assert_eq!(total, metrics.packets_sent.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_replay_retransmits_on_nack() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        listener
+            .set_read_timeout(Some(std::time::Duration::from_millis(500)))
+            .unwrap();
+
+        // Свободный порт под feedback-канал
+        let feedback_addr = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        // 20 блоков по 1000 выборок = 10мс файла, при 0.1x ≈ 100мс
+        let tmp = make_glos_file(20, 1000);
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: addr,
+            speed: 0.1,
+            stats_interval_secs: 60,
+            feedback: Some(crate::FeedbackConfig {
+                bind_addr: feedback_addr,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+
+        // Приёмник: на первый блок отвечает NACK, дальше просто считает
+        let receiver = std::thread::spawn(move || {
+            let mut buf = vec![0u8; 65536];
+            let mut timestamps = Vec::new();
+
+            while let Ok(n) = listener.recv(&mut buf) {
+                let (ts, ..) = UdpPacket::decode(&buf[..n]).unwrap();
+                if timestamps.is_empty() {
+                    let nack = crate::FeedbackMessage::Nack { timestamp_ns: ts };
+                    listener.send_to(&nack.encode(), feedback_addr).unwrap();
+                }
+                timestamps.push(ts);
+            }
+
+            timestamps
+        });
+
+        session.run().unwrap();
+        let timestamps = receiver.join().unwrap();
+
+        assert_eq!(metrics.retransmits.load(Ordering::Relaxed), 1);
+        assert_eq!(timestamps.len(), 21, "20 blocks + 1 retransmission");
+        assert_eq!(
+            timestamps.iter().filter(|&&ts| ts == timestamps[0]).count(),
+            2
+        );
+    }
+
     #[test]
     fn test_replay_stop_flag() {
         let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
@@ -313,6 +1041,7 @@ mod tests {
             loop_playback: false,
             stats_interval_secs: 60,
             bind_addr: "0.0.0.0:0".parse().unwrap(),
+            ..Default::default()
         };
 
         let session = ReplaySession::new(config).unwrap();
@@ -367,6 +1096,7 @@ mod tests {
             loop_playback: false,
             stats_interval_secs: 60,
             bind_addr: "0.0.0.0:0".parse().unwrap(),
+            ..Default::default()
         };
 
         let session = ReplaySession::new(config).unwrap();
@@ -380,6 +1110,58 @@ mod tests {
         assert_eq!(data, vec![42u8; 200]);
     }
 
+    #[test]
+    fn test_replay_with_speed_profile() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        let tmp = make_glos_file(20, 1000);
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: addr.parse().unwrap(),
+            stats_interval_secs: 60,
+            speed_profile: Some("50.0:0.05,200.0:60".parse().unwrap()),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        session.run().unwrap();
+
+        assert_eq!(metrics.packets_sent.load(Ordering::Relaxed), 20);
+        assert_eq!(metrics.samples_sent.load(Ordering::Relaxed), 20_000);
+    }
+
+    #[test]
+    fn test_replay_virtual_clock_realtime_speed() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // 100 блоков по 20000 выборок при 2 Msps = 1 с записи
+        let tmp = make_glos_file(100, 20_000);
+        let clock = VirtualClock::new(0);
+        let config = ReplayConfig {
+            input_path: tmp.path().to_path_buf(),
+            target_addr: addr,
+            speed: 1.0,
+            stats_interval_secs: 60,
+            clock: clock.clone(),
+            ..Default::default()
+        };
+
+        let session = ReplaySession::new(config).unwrap();
+        let metrics = session.metrics();
+        let start = Instant::now();
+        session.run().unwrap();
+
+        // Реальная скорость 1x, но без реального ожидания: последний блок
+        // уходит ровно через 990 мс виртуального времени
+        assert!(start.elapsed() < std::time::Duration::from_millis(900));
+        assert_eq!(clock.now(), std::time::Duration::from_millis(990));
+        assert_eq!(metrics.samples_sent.load(Ordering::Relaxed), 2_000_000);
+        assert_eq!(metrics.underruns.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn test_parse_udp_target() {
         assert_eq!(
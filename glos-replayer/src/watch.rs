@@ -0,0 +1,353 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use glos_core::{GlosHeaderExt, GLOS_HEADER_SIZE};
+use glos_types::{GlosHeader, RuntimeControl};
+
+use crate::{ReplayConfig, ReplayError, ReplayResult, ReplaySession};
+
+/// Имя списка уже воспроизведённых файлов в наблюдаемом каталоге.
+pub const WATCH_SKIP_LIST: &str = ".glos-replayed";
+
+/// Параметры режима наблюдения за каталогом.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchConfig {
+    pub dir: PathBuf,
+    /// Период опроса каталога
+    pub poll_interval: Duration,
+    /// Файл со списком воспроизведённых файлов (по имени, по строке)
+    pub skip_list: PathBuf,
+}
+
+/// Наблюдатель за каталогом: находит законченные `.glos` файлы, которые
+/// ещё не воспроизводились.
+///
+/// Файл считается законченным, когда он называется `*.glos` (запись под
+/// временным именем и переименование в конце) и его заголовок
+/// финализирован — [`GlosWriter`](glos_core::GlosWriter) перезаписывает
+/// его последним, заполняя `total_samples` и `timestamp_end`.
+#[derive(Debug)]
+pub struct DirectoryWatcher {
+    config: WatchConfig,
+    played: HashSet<String>,
+    /// Найденные, но ещё не законченные файлы в порядке появления
+    pending: Vec<PathBuf>,
+}
+
+/// Воспроизведение файлов по мере их появления в каталоге.
+///
+/// Каждый файл проигрывается отдельной [`ReplaySession`] с общими флагами
+/// остановки и паузы, со своей сводкой в конце.
+pub struct WatchSession {
+    base: ReplayConfig,
+    watch: WatchConfig,
+    stop_flag: Arc<AtomicBool>,
+    pause_flag: Arc<AtomicBool>,
+    control: Arc<RuntimeControl>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl WatchConfig {
+    /// Опрос раз в секунду, список воспроизведённых — [`WATCH_SKIP_LIST`]
+    /// в самом каталоге.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        Self {
+            skip_list: dir.join(WATCH_SKIP_LIST),
+            dir,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl DirectoryWatcher {
+    /// Открывает каталог и загружает список воспроизведённых файлов.
+    pub fn open(config: WatchConfig) -> ReplayResult<Self> {
+        if !config.dir.is_dir() {
+            return Err(ReplayError::Config(format!(
+                "watch directory {:?} does not exist",
+                config.dir
+            )));
+        }
+
+        let played = match fs::read_to_string(&config.skip_list) {
+            Ok(s) => s
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            config,
+            played,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Законченные и ещё не воспроизведённые файлы в порядке появления.
+    pub fn poll(&mut self) -> ReplayResult<Vec<PathBuf>> {
+        let mut fresh: Vec<(SystemTime, PathBuf)> = Vec::new();
+
+        for entry in fs::read_dir(&self.config.dir)? {
+            let path = entry?.path();
+
+            if path.extension().is_none_or(|e| e != "glos")
+                || self.is_played(&path)
+                || self.pending.contains(&path)
+            {
+                continue;
+            }
+
+            let modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            fresh.push((modified, path));
+        }
+
+        // Найденные за один опрос — по времени изменения, затем по имени
+        fresh.sort();
+        self.pending.extend(fresh.into_iter().map(|(_, p)| p));
+
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .filter(|p| p.exists())
+            .partition(|p| is_finalized(p));
+        self.pending = waiting;
+
+        Ok(ready)
+    }
+
+    /// Отмечает файл воспроизведённым (дописывает в список на диске).
+    pub fn mark_played(
+        &mut self,
+        path: &Path,
+    ) -> ReplayResult<()> {
+        let name = file_key(path);
+
+        if self.played.insert(name.clone()) {
+            let mut list = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.config.skip_list)?;
+            writeln!(list, "{name}")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_played(
+        &self,
+        path: &Path,
+    ) -> bool {
+        self.played.contains(&file_key(path))
+    }
+
+    pub fn config(&self) -> &WatchConfig {
+        &self.config
+    }
+}
+
+impl WatchSession {
+    /// `base` задаёт параметры воспроизведения; `input_path` в нём
+    /// заменяется путём очередного файла.
+    pub fn new(
+        base: ReplayConfig,
+        watch: WatchConfig,
+    ) -> ReplayResult<Self> {
+        if base.loop_playback {
+            return Err(ReplayError::Config(
+                "watch mode cannot loop files".to_string(),
+            ));
+        }
+
+        if base.file_sink.is_some() {
+            return Err(ReplayError::Config(
+                "watch mode cannot write to a file".to_string(),
+            ));
+        }
+
+        let control = RuntimeControl::new(Duration::from_secs(base.stats_interval_secs));
+
+        Ok(Self {
+            base,
+            watch,
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            pause_flag: Arc::new(AtomicBool::new(false)),
+            control,
+        })
+    }
+
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_flag.clone()
+    }
+
+    pub fn pause_flag(&self) -> Arc<AtomicBool> {
+        self.pause_flag.clone()
+    }
+
+    pub fn runtime_control(&self) -> Arc<RuntimeControl> {
+        self.control.clone()
+    }
+
+    /// Наблюдает за каталогом до `stop_flag`. Возвращает число
+    /// воспроизведённых файлов. Ошибка в одном файле не останавливает
+    /// наблюдение: файл отмечается воспроизведённым, чтобы не повторять его.
+    pub fn run(self) -> ReplayResult<u64> {
+        let mut watcher = DirectoryWatcher::open(self.watch.clone())?;
+        let mut played = 0u64;
+
+        eprintln!(
+            "[replayer] Watching {:?} every {:?} (skip list {:?}, {} already played)",
+            self.watch.dir,
+            self.watch.poll_interval,
+            self.watch.skip_list,
+            watcher.played.len()
+        );
+
+        while !self.stop_flag.load(Ordering::Acquire) {
+            for path in watcher.poll()? {
+                if self.stop_flag.load(Ordering::Acquire) {
+                    break;
+                }
+
+                eprintln!("[replayer] Watch: playing {path:?}");
+
+                let config = ReplayConfig {
+                    input_path: path.clone(),
+                    ..self.base.clone()
+                };
+                let result = ReplaySession::new(config).and_then(|s| {
+                    s.with_shared_flags(
+                        self.stop_flag.clone(),
+                        self.pause_flag.clone(),
+                        self.control.clone(),
+                    )
+                    .run()
+                });
+
+                // Прерванный остановкой файл не отмечаем: доиграем в следующий раз
+                if self.stop_flag.load(Ordering::Acquire) {
+                    break;
+                }
+
+                match result {
+                    Ok(()) => played += 1,
+                    Err(e) => eprintln!("[replayer] Watch: {path:?} failed: {e}"),
+                }
+                watcher.mark_played(&path)?;
+            }
+
+            self.base.clock.sleep(self.watch.poll_interval);
+        }
+
+        eprintln!("[replayer] Watch stopped: {played} files played");
+        Ok(played)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Заголовок файла финализирован писателем.
+pub fn is_finalized(path: &Path) -> bool {
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+
+    let mut buf = [0u8; GLOS_HEADER_SIZE];
+    if file.read_exact(&mut buf).is_err() {
+        return false;
+    }
+
+    // Заголовок, пойманный на середине перезаписи, не пройдёт CRC
+    match GlosHeader::deserialize(&buf) {
+        Ok(h) => h.total_samples > 0 || h.timestamp_end != 0,
+        Err(_) => false,
+    }
+}
+
+fn file_key(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::{GlosWriter, IqBlockExt};
+    use glos_types::{IqBlock, SdrType};
+
+    use super::*;
+
+    fn write_glos(
+        path: &Path,
+        finish: bool,
+    ) {
+        let header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        let mut writer = GlosWriter::new(File::create(path).unwrap(), header).unwrap();
+        writer
+            .write_block(IqBlock::new(0, 100, vec![0u8; 400]))
+            .unwrap();
+
+        if finish {
+            writer.finish_at(1).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_watcher_picks_finished_files_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = WatchConfig::new(dir.path());
+
+        write_glos(&dir.path().join("a.glos"), true);
+        // Ещё пишется: заголовок не финализирован
+        write_glos(&dir.path().join("b.glos"), false);
+        // Временное имя до переименования
+        write_glos(&dir.path().join("c.glos.part"), true);
+
+        let mut watcher = DirectoryWatcher::open(config.clone()).unwrap();
+        let ready = watcher.poll().unwrap();
+        assert_eq!(ready, [dir.path().join("a.glos")]);
+        watcher.mark_played(&ready[0]).unwrap();
+
+        // Запись закончилась, временный файл переименован
+        write_glos(&dir.path().join("b.glos"), true);
+        fs::rename(dir.path().join("c.glos.part"), dir.path().join("c.glos")).unwrap();
+
+        let ready = watcher.poll().unwrap();
+        assert_eq!(
+            ready,
+            [dir.path().join("b.glos"), dir.path().join("c.glos")]
+        );
+        for p in &ready {
+            watcher.mark_played(p).unwrap();
+        }
+
+        // После перезапуска список воспроизведённых читается с диска
+        let mut watcher = DirectoryWatcher::open(config).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        assert!(watcher.is_played(&dir.path().join("a.glos")));
+    }
+}
@@ -1,7 +1,10 @@
-use std::net::UdpSocket;
+use std::{net::UdpSocket, sync::atomic::Ordering};
 
-use glos_core::{GlosHeaderExt, IqBlockExt};
-use glos_replayer::{ReplayConfig, ReplaySession, UdpPacket};
+use glos_core::{
+    GlosHeaderExt, GlosReader, IqBlockExt, SessionDigest, StreamVerifier, UdpPacket, UdpProtocol,
+};
+use glos_replayer::{ReplayConfig, ReplaySession};
+use glos_test_support::{GlosFixture, UdpCollector};
 use glos_types::{GlosHeader, IqBlock, IqFormat, SdrType};
 use tempfile::NamedTempFile;
 
@@ -44,6 +47,7 @@ fn test_integration_record_then_replay() {
         loop_playback: false,
         stats_interval_secs: 60,
         bind_addr: "0.0.0.0:0".parse().unwrap(),
+        ..Default::default()
     };
     let session = ReplaySession::new(config).unwrap();
     session.run().unwrap();
@@ -74,3 +78,160 @@ fn test_integration_record_then_replay() {
         );
     }
 }
+
+#[test]
+fn test_integration_replay_fixture_collector() {
+    // --- Та же запись, но через фикстуру и сборщик пакетов ---
+    let fixture = GlosFixture::new(IqFormat::Int16).blocks(10, 200);
+    let tmp = fixture.write_temp().unwrap();
+
+    // --- Воспроизведение ---
+    let collector = UdpCollector::bind(UdpProtocol::V1).unwrap();
+
+    let config = ReplayConfig {
+        input_path: tmp.path().to_path_buf(),
+        target_addr: collector.addr(),
+        speed: 100.0,
+        loop_playback: false,
+        stats_interval_secs: 60,
+        bind_addr: "0.0.0.0:0".parse().unwrap(),
+        ..Default::default()
+    };
+    let session = ReplaySession::new(config).unwrap();
+    session.run().unwrap();
+
+    // --- Проверка ---
+    let capture = collector.collect();
+    assert!(capture
+        .frames
+        .iter()
+        .all(|f| f.sample_count == 200 && f.data.len() == 200 * 4));
+
+    // Все пакеты дошли, timestamp'ы монотонно возрастают
+    capture.assert_no_loss(&fixture.timestamps());
+    capture.assert_ordered();
+    assert_eq!(capture.len(), 10);
+}
+
+#[test]
+fn test_integration_record_8msps_then_replay() {
+    use glos_hal::SimulatedDevice;
+    use glos_recorder::{RecorderConfig, RecordingPipeline, DEFAULT_BLOCK_TARGET};
+
+    // --- Запись с размером блока по умолчанию: 20 мс при 8 Msps — больше
+    // 65535 выборок, блок должен ограничиться полем UDP-пакета ---
+    let sample_rate = 8_000_000;
+    let tmp = NamedTempFile::new().unwrap();
+    let config = RecorderConfig {
+        sample_rate_hz: sample_rate,
+        iq_format: IqFormat::Int16,
+        output_path: tmp.path().to_path_buf(),
+        block_samples: DEFAULT_BLOCK_TARGET.block_samples(sample_rate, IqFormat::Int16),
+        sample_limit: Some(150_000),
+        stats_interval_secs: 60,
+        checkpoint_interval: None,
+        ..Default::default()
+    };
+    let (pipeline, _metrics) = RecordingPipeline::new(config);
+    let device = SimulatedDevice::new(sample_rate, 1_602_000_000, 40.0);
+    pipeline.run(Box::new(device)).unwrap();
+
+    let mut reader = GlosReader::new(std::fs::File::open(tmp.path()).unwrap()).unwrap();
+    let blocks = glos_core::read_all_blocks(&mut reader).unwrap();
+    assert_eq!(blocks[0].sample_count, u16::MAX as u32);
+    let expected: Vec<u64> = blocks.iter().map(|b| b.timestamp_ns).collect();
+
+    // --- Воспроизведение: приём идёт параллельно, чтобы не переполнить
+    // буфер сокета ---
+    let collector = UdpCollector::bind(UdpProtocol::V2).unwrap();
+    let config = ReplayConfig {
+        input_path: tmp.path().to_path_buf(),
+        target_addr: collector.addr(),
+        speed: 1.0,
+        loop_playback: false,
+        stats_interval_secs: 60,
+        bind_addr: "0.0.0.0:0".parse().unwrap(),
+        protocol: UdpProtocol::V2,
+        ..Default::default()
+    };
+    let receiver = std::thread::spawn(move || collector.collect());
+    let session = ReplaySession::new(config).unwrap();
+    let metrics = session.metrics();
+    session.run().unwrap();
+    let capture = receiver.join().unwrap();
+
+    // --- Проверка: ни один блок не отвергнут при разбиении на пакеты.
+    // Отдельные фрагменты на loopback могут теряться, блоки — нет ---
+    let send_errors = metrics.send_errors.load(Ordering::Relaxed);
+    assert_eq!(send_errors, 0);
+    assert_eq!(capture.missing(&expected), Vec::<u64>::new());
+    assert_eq!(capture.total_samples(), 150_000);
+}
+
+#[test]
+fn test_integration_end_to_end_digest() {
+    use std::time::Duration;
+
+    use glos_core::serialization::GlosWriter;
+
+    // --- Запись: блок 3 больше одного UDP-пакета ---
+    let tmp = NamedTempFile::new().unwrap();
+    {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.iq_format = IqFormat::Int16;
+        let file = std::fs::File::create(tmp.path()).unwrap();
+        let mut writer = GlosWriter::new(file, header).unwrap();
+        for i in 0..6u64 {
+            let samples: u32 = if i == 3 { 20_000 } else { 200 };
+            let data = (0..samples as usize * 4).map(|j| (i as usize * 31 + j) as u8);
+            writer
+                .write_block(IqBlock::new(
+                    1_000_000_000 + i * 1_000_000,
+                    samples,
+                    data.collect(),
+                ))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    // Дайджест файла — эталон для приёмника
+    let file = std::fs::File::open(tmp.path()).unwrap();
+    let blocks: Vec<IqBlock> = GlosReader::new(file).unwrap().map(Result::unwrap).collect();
+    let expected = SessionDigest::of_blocks(&blocks);
+
+    // --- Воспроизведение по протоколу v2 ---
+    let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+    listener
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+
+    let config = ReplayConfig {
+        input_path: tmp.path().to_path_buf(),
+        target_addr: listener.local_addr().unwrap(),
+        speed: 100.0,
+        stats_interval_secs: 60,
+        protocol: UdpProtocol::V2,
+        ..Default::default()
+    };
+    let session = ReplaySession::new(config).unwrap();
+    let metrics = session.metrics();
+    session.run().unwrap();
+
+    assert_eq!(metrics.session_digest(), Some(expected));
+
+    // --- Приём и сверка ---
+    let mut verifier = StreamVerifier::new();
+    let mut buf = vec![0u8; 65536];
+    while let Ok(n) = listener.recv(&mut buf) {
+        let check = verifier.accept(&buf[..n]).unwrap();
+        assert!(check.is_none_or(|c| c.is_ok()));
+    }
+
+    assert_eq!(verifier.stats().verified, 6);
+    assert!(
+        verifier.matches(&expected),
+        "{} != {expected}",
+        verifier.session_digest()
+    );
+}
@@ -0,0 +1,59 @@
+use std::{sync::Arc, time::Duration};
+
+use glos_types::{Clock, SharedClock, VirtualClock};
+
+use crate::FIXTURE_START_NS;
+
+/// Виртуальные часы, стоящие на [`FIXTURE_START_NS`]: управляющая ручка и
+/// те же часы как [`SharedClock`] для поля `clock` конфигов.
+pub fn virtual_clock() -> (Arc<VirtualClock>, SharedClock) {
+    let clock = VirtualClock::new(FIXTURE_START_NS);
+    let shared: SharedClock = clock.clone();
+    (clock, shared)
+}
+
+/// Сдвигает часы шагами `step`, пока `done` не вернёт `true`, но не дальше
+/// `limit` виртуального времени. Между шагами уступает процессор другим
+/// потокам. Возвращает прошедшее виртуальное время или `None` по лимиту.
+pub fn advance_until(
+    clock: &VirtualClock,
+    step: Duration,
+    limit: Duration,
+    mut done: impl FnMut() -> bool,
+) -> Option<Duration> {
+    let start = clock.now();
+
+    loop {
+        if done() {
+            return Some(clock.now() - start);
+        }
+        if clock.now() - start >= limit {
+            return None;
+        }
+        clock.advance(step);
+        std::thread::yield_now();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_until() {
+        let (clock, shared) = virtual_clock();
+        assert_eq!(shared.unix_time_ns(), FIXTURE_START_NS);
+
+        let step = Duration::from_millis(10);
+        let elapsed = advance_until(&clock, step, Duration::from_secs(1), || {
+            shared.now() >= Duration::from_millis(35)
+        });
+        assert_eq!(elapsed, Some(Duration::from_millis(40)));
+
+        assert_eq!(advance_until(&clock, step, step * 3, || false), None);
+    }
+}
@@ -0,0 +1,249 @@
+use std::io::{Cursor, Write};
+
+use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt};
+use glos_types::{Compression, GlosHeader, GlosResult, IqBlock, IqFormat, SdrType};
+use tempfile::NamedTempFile;
+
+/// Unix-время (нс) начала фикстур по умолчанию: 2024-01-01T00:00:00Z.
+pub const FIXTURE_START_NS: u64 = 1_704_067_200_000_000_000;
+
+/// Построитель детерминированных `.glos` файлов.
+///
+/// Выборка с номером `n` (сквозным по файлу, с учётом пропусков) кодирует
+/// `seed + n`, поэтому по данным видно, какая выборка пропала или
+/// переставлена. Метки времени блоков считаются от `start_ns` по частоте
+/// дискретизации, время окончания в заголовке фиксировано.
+#[derive(Debug, Clone)]
+pub struct GlosFixture {
+    header: GlosHeader,
+    start_ns: u64,
+    blocks: usize,
+    block_samples: u32,
+    /// (номер блока, выборок пропущено перед ним)
+    gaps: Vec<(usize, u64)>,
+    seed: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl GlosFixture {
+    /// 10 блоков по 200 выборок, HackRF, 2 МГц, 1602 МГц.
+    pub fn new(format: IqFormat) -> Self {
+        let mut header = GlosHeader::new(SdrType::HackRf, 2_000_000, 1_602_000_000);
+        header.iq_format = format;
+
+        Self {
+            header,
+            start_ns: FIXTURE_START_NS,
+            blocks: 10,
+            block_samples: 200,
+            gaps: Vec::new(),
+            seed: 0,
+        }
+    }
+
+    pub fn sample_rate(
+        mut self,
+        hz: u32,
+    ) -> Self {
+        self.header.sample_rate = hz;
+        self
+    }
+
+    pub fn center_freq(
+        mut self,
+        hz: u64,
+    ) -> Self {
+        self.header.center_freq = hz;
+        self
+    }
+
+    pub fn compression(
+        mut self,
+        compression: Compression,
+    ) -> Self {
+        self.header.compression = compression;
+        self
+    }
+
+    /// Метка времени первой выборки (Unix-нс).
+    pub fn start_ns(
+        mut self,
+        start_ns: u64,
+    ) -> Self {
+        self.start_ns = start_ns;
+        self
+    }
+
+    pub fn blocks(
+        mut self,
+        count: usize,
+        samples_per_block: u32,
+    ) -> Self {
+        self.blocks = count;
+        self.block_samples = samples_per_block;
+        self
+    }
+
+    /// Пропуск `samples` выборок перед блоком `index` (метки времени
+    /// сдвигаются, данные пропущенных выборок в файл не попадают).
+    pub fn gap_before(
+        mut self,
+        index: usize,
+        samples: u64,
+    ) -> Self {
+        self.gaps.push((index, samples));
+        self
+    }
+
+    pub fn seed(
+        mut self,
+        seed: u64,
+    ) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Заголовок будущего файла (до финализации).
+    pub fn header(&self) -> GlosHeader {
+        let mut header = self.header.clone();
+        header.timestamp_start = self.start_ns / 1_000_000_000;
+        header
+    }
+
+    /// Блоки фикстуры в порядке записи.
+    pub fn iq_blocks(&self) -> Vec<IqBlock> {
+        let format = self.header.iq_format;
+        let mut first_sample = 0u64;
+
+        (0..self.blocks)
+            .map(|i| {
+                first_sample += self.gap_at(i);
+                let data = (0..self.block_samples as u64)
+                    .flat_map(|k| encode_sample(self.seed + first_sample + k, format))
+                    .collect();
+                let block = IqBlock::new(self.timestamp_of(first_sample), self.block_samples, data);
+                first_sample += self.block_samples as u64;
+                block
+            })
+            .collect()
+    }
+
+    /// Метки времени блоков в порядке записи.
+    pub fn timestamps(&self) -> Vec<u64> {
+        self.iq_blocks().iter().map(|b| b.timestamp_ns).collect()
+    }
+
+    /// Файл целиком в памяти.
+    pub fn to_bytes(&self) -> GlosResult<Vec<u8>> {
+        let mut out = Cursor::new(Vec::new());
+        let mut writer = GlosWriter::new(&mut out, self.header())?;
+        let mut end_ns = self.start_ns;
+
+        for block in self.iq_blocks() {
+            end_ns = self.timestamp_of_block_end(&block);
+            writer.write_block(block)?;
+        }
+
+        writer.finish_at(end_ns / 1_000_000_000)?;
+        Ok(out.into_inner())
+    }
+
+    /// Записывает файл во временный `.glos`, удаляемый при drop.
+    pub fn write_temp(&self) -> GlosResult<NamedTempFile> {
+        let mut file = tempfile::Builder::new().suffix(".glos").tempfile()?;
+        file.write_all(&self.to_bytes()?)?;
+        file.flush()?;
+        Ok(file)
+    }
+
+    fn gap_at(
+        &self,
+        index: usize,
+    ) -> u64 {
+        self.gaps
+            .iter()
+            .filter(|(i, _)| *i == index)
+            .map(|(_, n)| n)
+            .sum()
+    }
+
+    fn timestamp_of(
+        &self,
+        sample: u64,
+    ) -> u64 {
+        let elapsed = sample as u128 * 1_000_000_000 / self.header.sample_rate.max(1) as u128;
+        self.start_ns + elapsed as u64
+    }
+
+    fn timestamp_of_block_end(
+        &self,
+        block: &IqBlock,
+    ) -> u64 {
+        let duration =
+            block.sample_count as u128 * 1_000_000_000 / self.header.sample_rate.max(1) as u128;
+        block.timestamp_ns + duration as u64
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Выборка со значением `n` в формате `format` (big-endian): I = n, Q = !n.
+fn encode_sample(
+    n: u64,
+    format: IqFormat,
+) -> Vec<u8> {
+    match format {
+        IqFormat::Int8 => vec![n as u8, !n as u8],
+        IqFormat::Int16 => [n as i16, !n as i16]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect(),
+        IqFormat::Float32 => {
+            let v = (n % 65_536) as f32 / 65_536.0;
+            [v, -v].iter().flat_map(|v| v.to_be_bytes()).collect()
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::{read_all_blocks, GlosReader};
+
+    use super::*;
+
+    #[test]
+    fn test_fixture_deterministic_with_gap() {
+        let fixture = GlosFixture::new(IqFormat::Int16)
+            .sample_rate(1_000_000)
+            .blocks(3, 100)
+            .gap_before(2, 50);
+
+        let bytes = fixture.to_bytes().unwrap();
+        assert_eq!(bytes, fixture.to_bytes().unwrap());
+
+        let mut reader = GlosReader::new(Cursor::new(bytes)).unwrap();
+        let blocks = read_all_blocks(&mut reader).unwrap();
+        reader.validate_totals().unwrap();
+
+        // 1 выборка = 1 мкс; перед третьим блоком пропущено 50
+        let ts: Vec<u64> = blocks
+            .iter()
+            .map(|b| b.timestamp_ns - FIXTURE_START_NS)
+            .collect();
+        assert_eq!(ts, [0, 100_000, 250_000]);
+        let expected = fixture.iq_blocks();
+        assert!(blocks.iter().zip(&expected).all(|(a, b)| a.data == b.data));
+
+        // Первая выборка третьего блока кодирует свой сквозной номер
+        assert_eq!(blocks[2].samples_i16().next(), Some(250));
+    }
+}
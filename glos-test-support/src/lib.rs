@@ -0,0 +1,19 @@
+//! Общие помощники для тестов, работающих с glos.
+//!
+//! Вместо самодельных UDP-слушателей и ручной сборки файлов в каждом
+//! тесте:
+//!
+//! - [`UdpCollector`] — принимает поток replayer'а (v1 и v2), разбирает пакеты
+//!   и проверяет порядок и потери;
+//! - [`GlosFixture`] — строит детерминированные `.glos` файлы, побайтово
+//!   одинаковые при одинаковых параметрах;
+//! - [`virtual_clock`] / [`advance_until`] — виртуальные часы для конфигов
+//!   recorder'а и replayer'а.
+
+pub mod clock;
+pub mod fixture;
+pub mod udp;
+
+pub use clock::{advance_until, virtual_clock};
+pub use fixture::{GlosFixture, FIXTURE_START_NS};
+pub use udp::{Capture, Frame, UdpCollector, DEFAULT_IDLE_TIMEOUT};
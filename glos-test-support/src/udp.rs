@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use glos_core::{UdpPacket, UdpProtocol};
+
+/// Тишина, после которой [`UdpCollector::collect`] считает поток
+/// законченным.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Приёмник UDP-потока replayer'а на свободном порту `127.0.0.1`.
+#[derive(Debug)]
+pub struct UdpCollector {
+    socket: UdpSocket,
+    protocol: UdpProtocol,
+    origin: Instant,
+}
+
+/// Разобранный UDP-пакет.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Время приёма от создания коллектора
+    pub received_at: Duration,
+    pub timestamp_ns: u64,
+    pub sample_count: u16,
+    /// Номер фрагмента блока (v1: всегда 0)
+    pub index: u8,
+    /// Фрагментов в блоке (v1: всегда 1)
+    pub count: u8,
+    /// Данные блока сжаты LZ4 (только v2; `data` — часть сжатого потока)
+    pub compressed: bool,
+    /// Дайджест блока (только v2)
+    pub digest: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+/// Всё, что принял [`UdpCollector`], с проверками порядка и потерь.
+#[derive(Debug, Clone, Default)]
+pub struct Capture {
+    pub frames: Vec<Frame>,
+    /// Пакетов, которые не удалось разобрать
+    pub malformed: usize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl UdpCollector {
+    pub fn bind(protocol: UdpProtocol) -> io::Result<Self> {
+        let socket = UdpSocket::bind("127.0.0.1:0")?;
+        socket.set_read_timeout(Some(DEFAULT_IDLE_TIMEOUT))?;
+
+        Ok(Self {
+            socket,
+            protocol,
+            origin: Instant::now(),
+        })
+    }
+
+    /// Меняет время тишины, завершающее [`collect`](Self::collect).
+    pub fn with_idle_timeout(
+        self,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        self.socket.set_read_timeout(Some(timeout))?;
+        Ok(self)
+    }
+
+    /// Адрес для `target_addr` replayer'а.
+    pub fn addr(&self) -> SocketAddr {
+        self.socket
+            .local_addr()
+            .expect("bound socket has an address")
+    }
+
+    /// Принимает пакеты, пока поток не замолчит.
+    pub fn collect(&self) -> Capture {
+        self.collect_n(usize::MAX)
+    }
+
+    /// Как [`collect`](Self::collect), но не больше `max` пакетов.
+    pub fn collect_n(
+        &self,
+        max: usize,
+    ) -> Capture {
+        let mut capture = Capture::default();
+        let mut buf = vec![0u8; 65_536];
+
+        while capture.frames.len() + capture.malformed < max {
+            let Ok(n) = self.socket.recv(&mut buf) else {
+                break;
+            };
+
+            match self.decode(&buf[..n]) {
+                Ok(frame) => capture.frames.push(frame),
+                Err(_) => capture.malformed += 1,
+            }
+        }
+
+        capture
+    }
+
+    fn decode(
+        &self,
+        packet: &[u8],
+    ) -> Result<Frame, String> {
+        let received_at = self.origin.elapsed();
+
+        match self.protocol {
+            UdpProtocol::V1 => {
+                let (timestamp_ns, sample_count, data) = UdpPacket::decode(packet)?;
+                Ok(Frame {
+                    received_at,
+                    timestamp_ns,
+                    sample_count,
+                    index: 0,
+                    count: 1,
+                    compressed: false,
+                    digest: None,
+                    data: data.to_vec(),
+                })
+            }
+            UdpProtocol::V2 => {
+                let f = UdpPacket::decode_v2(packet)?;
+                Ok(Frame {
+                    received_at,
+                    timestamp_ns: f.timestamp_ns,
+                    sample_count: f.sample_count,
+                    index: f.index,
+                    count: f.count,
+                    compressed: f.compressed,
+                    digest: Some(f.digest),
+                    data: f.data.to_vec(),
+                })
+            }
+        }
+    }
+}
+
+impl Capture {
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Метки времени блоков в порядке прихода; фрагменты одного блока,
+    /// пришедшие подряд, дают одну метку.
+    pub fn block_timestamps(&self) -> Vec<u64> {
+        let mut ts: Vec<u64> = self.frames.iter().map(|f| f.timestamp_ns).collect();
+        ts.dedup();
+        ts
+    }
+
+    /// Выборок в принятых блоках (каждый блок считается один раз).
+    pub fn total_samples(&self) -> u64 {
+        self.fragments_by_block()
+            .values()
+            .map(|(count, _)| *count as u64)
+            .sum()
+    }
+
+    /// Метки из `expected`, ни одного фрагмента которых не пришло.
+    pub fn missing(
+        &self,
+        expected: &[u64],
+    ) -> Vec<u64> {
+        let blocks = self.fragments_by_block();
+        expected
+            .iter()
+            .copied()
+            .filter(|ts| !blocks.contains_key(ts))
+            .collect()
+    }
+
+    /// Блоки, у которых пришли не все фрагменты.
+    pub fn incomplete(&self) -> Vec<u64> {
+        let mut out = Vec::new();
+        for frame in &self.frames {
+            let received = self
+                .frames
+                .iter()
+                .filter(|f| f.timestamp_ns == frame.timestamp_ns)
+                .map(|f| f.index)
+                .collect::<BTreeSet<_>>();
+            if received.len() < frame.count as usize && !out.contains(&frame.timestamp_ns) {
+                out.push(frame.timestamp_ns);
+            }
+        }
+        out
+    }
+
+    /// Метки блоков строго возрастают.
+    pub fn assert_ordered(&self) {
+        for w in self.block_timestamps().windows(2) {
+            assert!(
+                w[1] > w[0],
+                "block timestamps out of order: {} after {}",
+                w[1],
+                w[0]
+            );
+        }
+    }
+
+    /// Пришли все блоки из `expected`, целиком и без мусора.
+    pub fn assert_no_loss(
+        &self,
+        expected: &[u64],
+    ) {
+        assert_eq!(self.malformed, 0, "malformed packets received");
+        assert_eq!(self.missing(expected), Vec::<u64>::new(), "blocks lost");
+        assert_eq!(
+            self.incomplete(),
+            Vec::<u64>::new(),
+            "blocks with missing fragments"
+        );
+    }
+
+    /// (sample_count, принятые фрагменты) по метке блока.
+    fn fragments_by_block(&self) -> BTreeMap<u64, (u16, usize)> {
+        let mut blocks = BTreeMap::new();
+        for f in &self.frames {
+            let entry = blocks.entry(f.timestamp_ns).or_insert((f.sample_count, 0));
+            entry.1 += 1;
+        }
+        blocks
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_core::UdpPacketizer;
+    use glos_types::IqFormat;
+
+    use super::*;
+    use crate::GlosFixture;
+
+    #[test]
+    fn test_collector_detects_loss_and_order() {
+        let collector = UdpCollector::bind(UdpProtocol::V2)
+            .unwrap()
+            .with_idle_timeout(Duration::from_millis(200))
+            .unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let fixture = GlosFixture::new(IqFormat::Int8).blocks(4, 100);
+        let blocks = fixture.iq_blocks();
+
+        // Блок 2 потерян
+        for (i, block) in blocks.iter().enumerate() {
+            if i == 2 {
+                continue;
+            }
+            for p in UdpPacketizer::packetize_v2(block).unwrap() {
+                sender.send_to(&p, collector.addr()).unwrap();
+            }
+        }
+        sender.send_to(&[1, 2, 3], collector.addr()).unwrap();
+
+        let capture = collector.collect();
+        capture.assert_ordered();
+        assert_eq!(capture.len(), 3);
+        assert_eq!(capture.malformed, 1);
+        assert_eq!(capture.total_samples(), 300);
+        assert_eq!(
+            capture.missing(&fixture.timestamps()),
+            [blocks[2].timestamp_ns]
+        );
+        assert!(capture.frames.iter().all(|f| f.digest.is_some()));
+    }
+}
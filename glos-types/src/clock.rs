@@ -0,0 +1,129 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Источник времени для пайплайнов записи и воспроизведения.
+///
+/// Весь pacing (sleep, ограничения по длительности, интервалы статистики)
+/// идёт через эти часы. В тестах подставляется [`VirtualClock`], который
+/// не спит, а мгновенно сдвигает время.
+pub trait Clock: Send + Sync + Debug {
+    /// Монотонное время от создания часов.
+    fn now(&self) -> Duration;
+
+    /// Ждёт `duration`.
+    fn sleep(
+        &self,
+        duration: Duration,
+    );
+
+    /// Текущее Unix-время, нс.
+    fn unix_time_ns(&self) -> u64;
+}
+
+/// Часы, разделяемые между потоками.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Реальные часы: `Instant` + `thread::sleep`.
+#[derive(Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+/// Виртуальные часы: `sleep` сдвигает время без ожидания.
+///
+/// Время общее для всех владельцев `Arc`, поэтому «сон» одного потока
+/// виден остальным.
+#[derive(Debug)]
+pub struct VirtualClock {
+    elapsed_ns: AtomicU64,
+    unix_origin_ns: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+
+    /// Реальные часы в виде [`SharedClock`].
+    pub fn shared() -> SharedClock {
+        Arc::new(Self::new())
+    }
+}
+
+impl VirtualClock {
+    /// Создаёт часы, стоящие на `unix_origin_ns`.
+    pub fn new(unix_origin_ns: u64) -> Arc<Self> {
+        Arc::new(Self {
+            elapsed_ns: AtomicU64::new(0),
+            unix_origin_ns,
+        })
+    }
+
+    /// Сдвигает время вперёд.
+    pub fn advance(
+        &self,
+        duration: Duration,
+    ) {
+        self.elapsed_ns
+            .fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(
+        &self,
+        duration: Duration,
+    ) {
+        std::thread::sleep(duration);
+    }
+
+    fn unix_time_ns(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_ns.load(Ordering::SeqCst))
+    }
+
+    fn sleep(
+        &self,
+        duration: Duration,
+    ) {
+        self.advance(duration);
+    }
+
+    fn unix_time_ns(&self) -> u64 {
+        self.unix_origin_ns + self.elapsed_ns.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,12 +2,16 @@ use crate::{GlosError, GlosResult};
 
 /// Тип сжатия IQ данных
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[repr(u8)]
 pub enum Compression {
     /// Без сжатия
+    #[cfg_attr(feature = "clap", value(alias = "no", alias = "off"))]
     None = 0,
     /// Сжатие LZ4
     Lz4 = 1,
+    /// Сжатие Zstd: медленнее LZ4, но заметно плотнее — для архива
+    Zstd = 2,
 }
 
 impl Compression {
@@ -15,6 +19,7 @@ impl Compression {
         match v {
             0 => Ok(Compression::None),
             1 => Ok(Compression::Lz4),
+            2 => Ok(Compression::Zstd),
             _ => Err(GlosError::FormatViolation(format!(
                 "Unknown compression: {v}"
             ))),
@@ -25,3 +30,33 @@ impl Compression {
         *self as u8
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для Compression
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for Compression {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Lz4 => write!(f, "lz4"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+impl std::str::FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "no" | "off" => Ok(Compression::None),
+            "lz4" => Ok(Compression::Lz4),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err(format!("Unknown compression '{s}'. Use: none, lz4, zstd")),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use log::{info, LevelFilter};
+
+/// Интервал статистики в подробном режиме.
+pub const VERBOSE_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Уровень логов в подробном режиме.
+pub const VERBOSE_LOG_LEVEL: LevelFilter = LevelFilter::Debug;
+
+/// Параметры сессии, которые можно менять без перезапуска.
+///
+/// Сессия читает интервал статистики на каждой проверке, так что изменение
+/// вступает в силу сразу. Уровень логов глобальный (`log::set_max_level`);
+/// логгер должен быть инициализирован с фильтром не строже
+/// [`VERBOSE_LOG_LEVEL`], иначе подробные сообщения отсечёт он сам. Каждое
+/// изменение отмечается в логе.
+#[derive(Debug)]
+pub struct RuntimeControl {
+    stats_interval_ms: AtomicU64,
+    verbose: AtomicBool,
+    /// Что восстановить при выходе из подробного режима
+    saved_interval_ms: AtomicU64,
+    saved_log_level: AtomicUsize,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RuntimeControl {
+    pub fn new(stats_interval: Duration) -> Arc<Self> {
+        let ms = stats_interval.as_millis() as u64;
+
+        Arc::new(Self {
+            stats_interval_ms: AtomicU64::new(ms),
+            verbose: AtomicBool::new(false),
+            saved_interval_ms: AtomicU64::new(ms),
+            saved_log_level: AtomicUsize::new(log::max_level() as usize),
+        })
+    }
+
+    pub fn stats_interval(&self) -> Duration {
+        Duration::from_millis(self.stats_interval_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn set_stats_interval(
+        &self,
+        interval: Duration,
+    ) {
+        let old = self
+            .stats_interval_ms
+            .swap(interval.as_millis() as u64, Ordering::Relaxed);
+        info!(
+            "Stats interval: {:?} → {interval:?}",
+            Duration::from_millis(old)
+        );
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        log::max_level()
+    }
+
+    pub fn set_log_level(
+        &self,
+        level: LevelFilter,
+    ) {
+        let old = log::max_level();
+
+        // Сообщение пишется на более подробном из двух уровней, чтобы
+        // попасть в лог и при повышении, и при понижении
+        if level > old {
+            log::set_max_level(level);
+            info!("Log level: {old} → {level}");
+        } else {
+            info!("Log level: {old} → {level}");
+            log::set_max_level(level);
+        }
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose.load(Ordering::Relaxed)
+    }
+
+    /// Включает подробный режим ([`VERBOSE_STATS_INTERVAL`],
+    /// [`VERBOSE_LOG_LEVEL`]) или возвращает параметры, действовавшие до
+    /// него.
+    pub fn set_verbose(
+        &self,
+        on: bool,
+    ) {
+        if self.verbose.swap(on, Ordering::Relaxed) == on {
+            return;
+        }
+
+        info!("Verbose mode {}", if on { "on" } else { "off" });
+
+        if on {
+            self.saved_interval_ms.store(
+                self.stats_interval_ms.load(Ordering::Relaxed),
+                Ordering::Relaxed,
+            );
+            self.saved_log_level
+                .store(log::max_level() as usize, Ordering::Relaxed);
+            self.set_stats_interval(VERBOSE_STATS_INTERVAL);
+            self.set_log_level(VERBOSE_LOG_LEVEL.max(log::max_level()));
+        } else {
+            let saved = self.saved_log_level.load(Ordering::Relaxed);
+            self.set_stats_interval(Duration::from_millis(
+                self.saved_interval_ms.load(Ordering::Relaxed),
+            ));
+            self.set_log_level(LevelFilter::iter().nth(saved).unwrap_or(LevelFilter::Info));
+        }
+    }
+
+    /// Переключает подробный режим (обработчик SIGUSR2). Возвращает новое
+    /// состояние.
+    pub fn toggle_verbose(&self) -> bool {
+        let on = !self.is_verbose();
+        self.set_verbose(on);
+        on
+    }
+}
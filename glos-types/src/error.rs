@@ -33,6 +33,13 @@ pub enum GlosError {
     /// Нарушение спецификации формата
     #[error("Format violation: {0}")]
     FormatViolation(String),
+
+    /// Чтение целиком не уложилось в отведённую память
+    #[error(
+        "Memory budget exceeded: {needed} bytes of block data, budget {budget}; \
+         read blocks one by one instead"
+    )]
+    MemoryBudgetExceeded { budget: usize, needed: usize },
 }
 
 impl GlosError {
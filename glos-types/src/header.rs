@@ -1,4 +1,6 @@
-use crate::{Compression, IqFormat, SdrType};
+use std::time::Duration;
+
+use crate::{Compression, IqFormat, SdrType, TimeSource};
 
 /// Заголовок GLOS файла (фиксированный размер 128 байт)
 #[derive(Debug, Clone)]
@@ -25,4 +27,54 @@ pub struct GlosHeader {
     pub timestamp_end: u64,
     /// Общее количество IQ выборок в файле
     pub total_samples: u64,
+    /// Источник времени меток блоков
+    pub time_source: TimeSource,
+    /// Поправка «эталон − часы хоста» (нс), уже учтённая в метках блоков;
+    /// 0 для [`TimeSource::Host`]
+    pub clock_offset_ns: i64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl GlosHeader {
+    /// Длительность записи.
+    ///
+    /// Считается по `total_samples / sample_rate` (точно до выборки), а если
+    /// счётчик не заполнен — по `timestamp_end - timestamp_start` (точность
+    /// до секунды). `None`, если запись не финализирована.
+    pub fn duration(&self) -> Option<Duration> {
+        if self.total_samples > 0 && self.sample_rate > 0 {
+            let nanos = self.total_samples as u128 * 1_000_000_000 / self.sample_rate as u128;
+            return Some(Duration::from_nanos(nanos.min(u64::MAX as u128) as u64));
+        }
+
+        if self.timestamp_end >= self.timestamp_start && self.timestamp_end != 0 {
+            return Some(Duration::from_secs(
+                self.timestamp_end - self.timestamp_start,
+            ));
+        }
+
+        None
+    }
+
+    /// Объём несжатых IQ данных (без служебных полей блоков), байт.
+    pub fn expected_data_bytes(&self) -> u64 {
+        self.total_samples * self.iq_format.sample_size() as u64
+    }
+
+    /// Битрейт несжатого IQ потока.
+    pub fn bits_per_second(&self) -> u64 {
+        self.sample_rate as u64 * self.iq_format.sample_size() as u64 * 8
+    }
+
+    /// Период дискретизации, нс (0.0 при неизвестной частоте).
+    pub fn sample_period_ns(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+
+        1e9 / self.sample_rate as f64
+    }
 }
@@ -1,3 +1,13 @@
+pub use num_complex::Complex;
+
+use crate::{GlosError, GlosResult, IqFormat};
+
+/// Шкала Int8: `i8 / 128` → `f32`.
+const I8_SCALE: f32 = 128.0;
+
+/// Шкала Int16: `i16 / 32767` → `f32`.
+const I16_SCALE: f32 = 32767.0;
+
 /// Блок IQ данных (переменный размер)
 #[derive(Debug, Clone)]
 pub struct IqBlock {
@@ -9,4 +19,161 @@ pub struct IqBlock {
     pub data: Vec<u8>,
     /// Флаг: данные в `data` находятся в сжатом виде
     pub is_compressed: bool,
+    /// События приёмника за время блока (формат v2+)
+    pub attributes: BlockAttributes,
+}
+
+/// Необязательные атрибуты блока: что происходило с приёмником, пока
+/// записывались его выборки. Блок без событий атрибутов не несёт, и в файле
+/// он такой же, как в v1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BlockAttributes {
+    /// АЦП был в насыщении хотя бы на части блока
+    pub overload: bool,
+    /// Усиление сменилось в пределах блока: новое значение, дБ
+    pub gain_db: Option<f32>,
+    /// Перед блоком потеряно столько выборок
+    pub dropped_samples: Option<u32>,
+    /// Блок — метка синхронизации: выборок в нём нет, а стоит он точно
+    /// между выборками соседних блоков
+    pub sync_marker: Option<SyncMarker>,
+}
+
+/// Точка синхронизации записи с другими датчиками (камера, IMU): её номер
+/// и время по их часам сопоставляются с их журналами.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncMarker {
+    /// Номер метки, за сессию растёт с 1
+    pub id: u32,
+    /// Время метки по внешним часам (нс)
+    pub external_ns: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+/// Типизированный доступ к выборкам. Компоненты идут подряд: I, Q, I, Q…;
+/// многобайтовые значения — big-endian, как в `.glos` по умолчанию (для
+/// файлов с флагом little-endian — [`as_complex_f32_with`]). У сжатого блока
+/// выборок нет: итераторы пусты, вектор — тоже. Неполный хвост `data`
+/// отбрасывается.
+///
+/// [`as_complex_f32_with`]: IqBlock::as_complex_f32_with
+impl IqBlock {
+    /// Компоненты блока формата Int8.
+    pub fn samples_i8(&self) -> impl ExactSizeIterator<Item = i8> + '_ {
+        self.payload().iter().map(|&b| b as i8)
+    }
+
+    /// Компоненты блока формата Int16.
+    pub fn samples_i16(&self) -> impl ExactSizeIterator<Item = i16> + '_ {
+        self.payload()
+            .chunks_exact(2)
+            .map(|c| i16::from_be_bytes([c[0], c[1]]))
+    }
+
+    /// Компоненты блока формата Float32.
+    pub fn samples_f32(&self) -> impl ExactSizeIterator<Item = f32> + '_ {
+        self.payload()
+            .chunks_exact(4)
+            .map(|c| f32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+    }
+
+    /// IQ пары блока в `f32`, нормированные к ±1.0 (Int8 — `/128`, Int16 —
+    /// `/32767`).
+    pub fn as_complex_f32(
+        &self,
+        format: IqFormat,
+    ) -> Vec<Complex<f32>> {
+        self.as_complex_f32_with(format, false)
+    }
+
+    /// Как [`as_complex_f32`](Self::as_complex_f32), но с явным порядком
+    /// байт компонент.
+    pub fn as_complex_f32_with(
+        &self,
+        format: IqFormat,
+        little_endian: bool,
+    ) -> Vec<Complex<f32>> {
+        let component = |c: &[u8]| match (format, little_endian) {
+            (IqFormat::Int8, _) => c[0] as i8 as f32 / I8_SCALE,
+            (IqFormat::Int16, false) => i16::from_be_bytes([c[0], c[1]]) as f32 / I16_SCALE,
+            (IqFormat::Int16, true) => i16::from_le_bytes([c[0], c[1]]) as f32 / I16_SCALE,
+            (IqFormat::Float32, false) => f32::from_be_bytes([c[0], c[1], c[2], c[3]]),
+            (IqFormat::Float32, true) => f32::from_le_bytes([c[0], c[1], c[2], c[3]]),
+        };
+        let half = format.sample_size() / 2;
+
+        self.payload()
+            .chunks_exact(format.sample_size())
+            .map(|c| Complex::new(component(&c[..half]), component(&c[half..])))
+            .collect()
+    }
+
+    /// Переставляет байты каждой IQ-компоненты: big-endian ↔
+    /// little-endian. Для Int8 ничего не делает.
+    pub fn swap_byte_order(
+        &mut self,
+        format: IqFormat,
+    ) -> GlosResult<()> {
+        if self.is_compressed {
+            return Err(GlosError::format_violation(
+                "cannot swap byte order of a compressed block",
+            ));
+        }
+
+        let width = format.sample_size() / 2;
+        if width > 1 {
+            for component in self.data.chunks_exact_mut(width) {
+                component.reverse();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Приводит компоненты, записанные в порядке `little_endian`, к
+    /// big-endian — порядку, который ждут `samples_*` и
+    /// [`as_complex_f32`](Self::as_complex_f32).
+    pub fn normalize_byte_order(
+        &mut self,
+        format: IqFormat,
+        little_endian: bool,
+    ) -> GlosResult<()> {
+        if little_endian {
+            self.swap_byte_order(format)?;
+        }
+        Ok(())
+    }
+
+    fn payload(&self) -> &[u8] {
+        if self.is_compressed {
+            &[]
+        } else {
+            &self.data
+        }
+    }
+}
+
+impl BlockAttributes {
+    /// Событий нет — блок пишется без поля атрибутов.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Добавляет события `other`: перегрузка складывается, потери
+    /// суммируются, из смен усиления остаётся последняя. Метка
+    /// синхронизации не переносится — она не событие внутри блока.
+    pub fn merge(
+        &mut self,
+        other: BlockAttributes,
+    ) {
+        self.overload |= other.overload;
+        self.gain_db = other.gain_db.or(self.gain_db);
+        self.dropped_samples = match (self.dropped_samples, other.dropped_samples) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            (a, b) => a.or(b),
+        };
+    }
 }
@@ -2,13 +2,17 @@ use crate::{GlosError, GlosResult};
 
 /// Формат IQ выборок
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[repr(u8)]
 pub enum IqFormat {
     /// 8-битные целые числа (I8, Q8) — компактно
+    #[cfg_attr(feature = "clap", value(alias = "i8"))]
     Int8 = 0,
     /// 16-битные целые числа (I16, Q16) — выше точность
+    #[cfg_attr(feature = "clap", value(alias = "i16"))]
     Int16 = 1,
     /// 32-битные числа с плавающей точкой (F32, F32) — полная точность
+    #[cfg_attr(feature = "clap", value(alias = "f32"))]
     Float32 = 2,
 }
 
@@ -37,3 +41,35 @@ impl IqFormat {
         }
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для IqFormat
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for IqFormat {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            IqFormat::Int8 => write!(f, "int8"),
+            IqFormat::Int16 => write!(f, "int16"),
+            IqFormat::Float32 => write!(f, "float32"),
+        }
+    }
+}
+
+impl std::str::FromStr for IqFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "int8" | "i8" => Ok(IqFormat::Int8),
+            "int16" | "i16" => Ok(IqFormat::Int16),
+            "float32" | "f32" => Ok(IqFormat::Float32),
+            _ => Err(format!(
+                "Unknown IQ format '{s}'. Use: int8, int16, float32"
+            )),
+        }
+    }
+}
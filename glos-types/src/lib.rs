@@ -1,13 +1,21 @@
+pub mod clock;
 pub mod compression;
+pub mod control;
 pub mod error;
 pub mod header;
 pub mod iq_block;
 pub mod iq_format;
+pub mod rf_chain;
 pub mod sdr;
+pub mod time_source;
 
+pub use clock::*;
 pub use compression::*;
+pub use control::*;
 pub use error::*;
 pub use header::*;
 pub use iq_block::*;
 pub use iq_format::*;
+pub use rf_chain::*;
 pub use sdr::*;
+pub use time_source::*;
@@ -0,0 +1,193 @@
+/// Описание радиочасти перед SDR: антенна, кабель, МШУ и фильтры.
+///
+/// Все поля необязательны: записывается то, что известно. Потери задаются
+/// положительными числами (дБ), усиления — со своим знаком.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RfChain {
+    /// Антенна
+    pub antenna: Option<Antenna>,
+    /// Потери в кабеле от антенны до МШУ/SDR (дБ)
+    pub cable_loss_db: Option<f32>,
+    /// Малошумящий усилитель (активная антенна или отдельный МШУ)
+    pub lna: Option<Lna>,
+    /// Фильтры в порядке прохождения сигнала
+    pub filters: Vec<RfFilter>,
+}
+
+/// Антенна.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Antenna {
+    pub kind: AntennaKind,
+    /// Модель, например `Trimble Zephyr 3`
+    pub model: Option<String>,
+    /// Усиление в зените (дБи)
+    pub gain_dbi: Option<f32>,
+}
+
+/// Тип антенны.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AntennaKind {
+    /// Микрополосковая (patch)
+    Patch,
+    /// Квадрифилярная спираль
+    Helix,
+    /// С дроссельными кольцами (choke ring)
+    ChokeRing,
+    /// Диполь или штырь
+    Dipole,
+    #[default]
+    Other,
+}
+
+/// Малошумящий усилитель.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Lna {
+    /// Усиление (дБ)
+    pub gain_db: f32,
+    /// Коэффициент шума (дБ)
+    pub noise_figure_db: Option<f32>,
+}
+
+/// Полосовой или иной фильтр в тракте.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RfFilter {
+    /// Тип или модель, например `SAW` или `Mini-Circuits VBFZ-1560`
+    pub name: String,
+    /// Центральная частота (Гц)
+    pub center_hz: Option<u64>,
+    /// Полоса пропускания (Гц)
+    pub bandwidth_hz: Option<u64>,
+    /// Вносимые потери (дБ)
+    pub insertion_loss_db: Option<f32>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl RfChain {
+    /// Ничего не известно — описывать нечего.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Суммарное усиление тракта от выхода антенны до входа SDR (дБ):
+    /// МШУ минус кабель и вносимые потери фильтров. Усиление антенны не
+    /// входит — оно зависит от угла места.
+    pub fn net_gain_db(&self) -> f32 {
+        let lna = self.lna.as_ref().map_or(0.0, |l| l.gain_db);
+        let cable = self.cable_loss_db.unwrap_or(0.0);
+        let filters: f32 = self
+            .filters
+            .iter()
+            .filter_map(|f| f.insertion_loss_db)
+            .sum();
+
+        lna - cable - filters
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for RfChain {
+    /// Одна строка по ходу сигнала, например
+    /// `patch Zephyr (+3.0 dBi) → cable -2.5 dB → LNA +28.0 dB → net +25.5 dB`.
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(a) = &self.antenna {
+            let mut s = a.kind.to_string();
+            if let Some(m) = &a.model {
+                s.push_str(&format!(" {m}"));
+            }
+            if let Some(g) = a.gain_dbi {
+                s.push_str(&format!(" ({g:+.1} dBi)"));
+            }
+            parts.push(s);
+        }
+
+        if let Some(loss) = self.cable_loss_db {
+            parts.push(format!("cable {:.1} dB", -loss));
+        }
+
+        if let Some(l) = &self.lna {
+            let mut s = format!("LNA {:+.1} dB", l.gain_db);
+            if let Some(nf) = l.noise_figure_db {
+                s.push_str(&format!(" (NF {nf:.1} dB)"));
+            }
+            parts.push(s);
+        }
+
+        for filter in &self.filters {
+            let mut s = filter.name.clone();
+            if let Some(c) = filter.center_hz {
+                s.push_str(&format!(" {:.3} MHz", c as f64 / 1e6));
+            }
+            if let Some(bw) = filter.bandwidth_hz {
+                s.push_str(&format!("/{:.3} MHz", bw as f64 / 1e6));
+            }
+            if let Some(loss) = filter.insertion_loss_db {
+                s.push_str(&format!(" {:.1} dB", -loss));
+            }
+            parts.push(s);
+        }
+
+        if parts.is_empty() {
+            return write!(f, "unknown");
+        }
+
+        write!(
+            f,
+            "{} → net {:+.1} dB",
+            parts.join(" → "),
+            self.net_gain_db()
+        )
+    }
+}
+
+impl std::fmt::Display for AntennaKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            AntennaKind::Patch => write!(f, "patch"),
+            AntennaKind::Helix => write!(f, "helix"),
+            AntennaKind::ChokeRing => write!(f, "choke_ring"),
+            AntennaKind::Dipole => write!(f, "dipole"),
+            AntennaKind::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl std::str::FromStr for AntennaKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "patch" => Ok(AntennaKind::Patch),
+            "helix" | "quadrifilar" => Ok(AntennaKind::Helix),
+            "choke_ring" | "choke-ring" | "chokering" => Ok(AntennaKind::ChokeRing),
+            "dipole" | "whip" => Ok(AntennaKind::Dipole),
+            "other" => Ok(AntennaKind::Other),
+            _ => Err(format!(
+                "Unknown antenna kind '{s}'. Use: patch, helix, choke_ring, dipole, other"
+            )),
+        }
+    }
+}
@@ -1,12 +1,19 @@
 /// Тип SDR устройства
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[repr(u8)]
 pub enum SdrType {
     /// Hack RF One
+    #[cfg_attr(feature = "clap", value(name = "hackrf", alias = "hackrf_one"))]
     HackRf = 0,
     /// ADALM-PlutoSDR
+    #[cfg_attr(
+        feature = "clap",
+        value(name = "pluto", alias = "plutosdr", alias = "adalm-pluto")
+    )]
     PlutoSdr = 1,
     /// USRP B200 family
+    #[cfg_attr(feature = "clap", value(name = "b200", alias = "usrp-b200"))]
     UsrpB200 = 2,
     /// Unknown device
     Unknown = 255,
@@ -26,3 +33,37 @@ impl SdrType {
         *self as u8
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для SdrType
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for SdrType {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            SdrType::HackRf => write!(f, "hackrf"),
+            SdrType::PlutoSdr => write!(f, "pluto"),
+            SdrType::UsrpB200 => write!(f, "b200"),
+            SdrType::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl std::str::FromStr for SdrType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hackrf" | "hackrf_one" => Ok(SdrType::HackRf),
+            "pluto" | "plutosdr" | "adalm-pluto" => Ok(SdrType::PlutoSdr),
+            "b200" | "usrp-b200" => Ok(SdrType::UsrpB200),
+            "unknown" => Ok(SdrType::Unknown),
+            _ => Err(format!(
+                "Unknown SDR type '{s}'. Use: hackrf, pluto, b200, unknown"
+            )),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+/// Источник времени, по которому ставились метки блоков
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TimeSource {
+    /// Системные часы хоста без внешней привязки (так же читаются файлы,
+    /// записанные до появления поля)
+    #[default]
+    Host = 0,
+    /// Системные часы с поправкой, измеренной по NTP-серверу
+    Ntp = 1,
+    /// Системные часы с поправкой по секундной метке (PPS) приёмника
+    /// GNSS
+    Pps = 2,
+    /// Значение из более новой версии формата
+    Unknown = 255,
+}
+
+impl TimeSource {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            0 => TimeSource::Host,
+            1 => TimeSource::Ntp,
+            2 => TimeSource::Pps,
+            _ => TimeSource::Unknown,
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Метки привязаны к внешнему эталону времени.
+    pub fn is_disciplined(&self) -> bool {
+        matches!(self, TimeSource::Ntp | TimeSource::Pps)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов для TimeSource
+////////////////////////////////////////////////////////////////////////////////
+
+impl std::fmt::Display for TimeSource {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            TimeSource::Host => write!(f, "host"),
+            TimeSource::Ntp => write!(f, "ntp"),
+            TimeSource::Pps => write!(f, "pps"),
+            TimeSource::Unknown => write!(f, "unknown"),
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use parking_lot::RwLock;
 
 use crate::{
-    data::{AppState, MockDataGenerator},
-    panels::{Dashboard, LogsPanel, SatellitesPanel, SignalPanel},
+    data::{AppState, MockDataGenerator, SessionState, SESSION_KEY},
+    panels::{
+        ActivePanel, AnnotationsPanel, Dashboard, IqStatsPanel, LogsPanel, SatellitesPanel,
+        SettingsPanel, SignalPanel, UiSettings,
+    },
     theme,
 };
 
@@ -12,14 +15,12 @@ pub struct GlosApp {
     state: Arc<RwLock<AppState>>,
     mock_generator: MockDataGenerator,
     active_panel: ActivePanel,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ActivePanel {
-    Dashboard,
-    Signal,
-    Satellites,
-    Logs,
+    /// Куда сохранять UiSettings (None — не сохраняем)
+    settings_path: Option<PathBuf>,
+    /// Масштаб шрифта, применённый к egui в последний раз
+    applied_font_scale: f32,
+    /// Сессия прошлого запуска, ждущая ответа «восстановить?»
+    pending_restore: Option<SessionState>,
 }
 
 impl GlosApp {
@@ -27,15 +28,123 @@ impl GlosApp {
         theme::configure_style(&cc.egui_ctx);
 
         let state = AppState::new();
+        let settings_path = UiSettings::config_path();
+
+        if let Some(path) = &settings_path {
+            state.write().settings = UiSettings::load(path);
+        }
+
         let mock_generator = MockDataGenerator::new(Arc::clone(&state));
+        let pending_restore = cc
+            .storage
+            .and_then(|s| eframe::get_value::<SessionState>(s, SESSION_KEY))
+            .filter(|s| !s.is_empty());
 
         Self {
             state,
             mock_generator,
             active_panel: ActivePanel::Dashboard,
+            settings_path,
+            applied_font_scale: 1.0,
+            pending_restore,
+        }
+    }
+
+    /// Восстанавливает источник данных, панель и вид прошлой сессии.
+    fn restore_session(
+        &mut self,
+        session: &SessionState,
+    ) {
+        self.active_panel = session.active_panel;
+
+        if let Some(path) = &session.open_file {
+            SignalPanel::open_file(&self.state, path, false);
+        }
+
+        {
+            let mut state = self.state.write();
+            session.apply_view(&mut state);
+            state.add_log("Previous session restored".to_string());
+        }
+
+        if session.mock_running && !self.mock_generator.is_running() {
+            self.mock_generator.start();
+        }
+    }
+
+    fn render_restore_prompt(
+        &mut self,
+        ctx: &egui::Context,
+    ) {
+        let Some(session) = &self.pending_restore else {
+            return;
+        };
+
+        let mut choice = None;
+        egui::Window::new("Восстановить предыдущую сессию?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0_f32, 0.0_f32])
+            .show(ctx, |ui| {
+                ui.label(format!("Панель: {:?}", session.active_panel));
+                if let Some(path) = &session.open_file {
+                    ui.label(format!("Файл: {}", path.display()));
+                }
+                if session.mock_running {
+                    ui.label("Источник: генератор тестовых данных");
+                }
+                if !session.markers_mhz.is_empty() {
+                    ui.label(format!("Маркеров: {}", session.markers_mhz.len()));
+                }
+                if let Some((lo, hi)) = session.channel_band {
+                    ui.label(format!("Полоса канала: {lo:.3}–{hi:.3} МГц"));
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("↺ Восстановить").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Начать заново").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        if let Some(restore) = choice {
+            if let Some(session) = self.pending_restore.take() {
+                if restore {
+                    self.restore_session(&session);
+                }
+            }
         }
     }
 
+    fn render_settings(
+        &mut self,
+        ui: &mut egui::Ui,
+    ) {
+        // Редактируем копию, чтобы не держать write-lock во время отрисовки
+        let mut settings = self.state.read().settings.clone();
+        let before = settings.clone();
+
+        SettingsPanel::render(ui, &mut settings);
+
+        if settings == before {
+            return;
+        }
+
+        if let Some(path) = &self.settings_path {
+            if let Err(e) = settings.save(path) {
+                self.state
+                    .write()
+                    .add_log(format!("Error saving UI settings: {e}"));
+            }
+        }
+
+        self.state.write().settings = settings;
+    }
+
     fn render_top_bar(
         &mut self,
         ctx: &egui::Context,
@@ -101,16 +210,31 @@ impl GlosApp {
                     ActivePanel::Signal,
                     "📡 Просмотр сигнала",
                 );
+                ui.selectable_value(
+                    &mut self.active_panel,
+                    ActivePanel::IqStats,
+                    "📈 Статистика IQ",
+                );
                 ui.selectable_value(
                     &mut self.active_panel,
                     ActivePanel::Satellites,
                     "🛰 Спутники",
                 );
+                ui.selectable_value(
+                    &mut self.active_panel,
+                    ActivePanel::Annotations,
+                    "🏷 Аннотации",
+                );
                 ui.selectable_value(
                     &mut self.active_panel,
                     ActivePanel::Logs,
                     "📜 Журнал событий",
                 );
+                ui.selectable_value(
+                    &mut self.active_panel,
+                    ActivePanel::Settings,
+                    "⚙️ Настройки",
+                );
 
                 ui.separator();
 
@@ -141,6 +265,13 @@ impl eframe::App for GlosApp {
         // Обновление каждые 50ms
         ctx.request_repaint_after(std::time::Duration::from_millis(50));
 
+        let font_scale = self.state.read().settings.font_scale;
+        if font_scale != self.applied_font_scale {
+            theme::apply_font_scale(ctx, font_scale);
+            self.applied_font_scale = font_scale;
+        }
+
+        self.render_restore_prompt(ctx);
         self.render_top_bar(ctx);
         self.render_side_panel(ctx);
 
@@ -151,12 +282,40 @@ impl eframe::App for GlosApp {
             ActivePanel::Signal => {
                 SignalPanel::render(ui, &self.state);
             }
+            ActivePanel::IqStats => {
+                IqStatsPanel::render(ui, &self.state);
+            }
             ActivePanel::Satellites => {
                 SatellitesPanel::render(ui, &self.state);
             }
+            ActivePanel::Annotations => {
+                if AnnotationsPanel::render(ui, &self.state) {
+                    self.active_panel = ActivePanel::Signal;
+                }
+            }
             ActivePanel::Logs => {
                 LogsPanel::render(ui, &self.state);
             }
+            ActivePanel::Settings => {
+                self.render_settings(ui);
+            }
         });
     }
+
+    fn save(
+        &mut self,
+        storage: &mut dyn eframe::Storage,
+    ) {
+        // Пока вопрос о восстановлении без ответа, прошлую сессию не затираем
+        let session = match &self.pending_restore {
+            Some(s) => s.clone(),
+            None => SessionState::capture(
+                self.active_panel,
+                self.mock_generator.is_running(),
+                &self.state.read(),
+            ),
+        };
+
+        eframe::set_value(storage, SESSION_KEY, &session);
+    }
 }
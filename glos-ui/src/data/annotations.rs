@@ -0,0 +1,389 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glos_core::{cut_range, CutRange};
+use glos_types::{GlosError, GlosResult};
+use serde::{Deserialize, Serialize};
+
+/// Версия формата sidecar-файла аннотаций
+pub const ANNOTATIONS_VERSION: u32 = 1;
+
+/// Сколько записи брать до и после аннотации при экспорте из списка.
+pub const ANNOTATION_EXPORT_MARGIN: Duration = Duration::from_secs(5);
+
+/// Размеченная область записи: интервал времени × полоса частот.
+///
+/// Время хранится в Unix-наносекундах — в той же шкале, что и
+/// `timestamp_ns` блоков .glos файла.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: u64,
+    pub label: String,
+    pub start_ns: u64,
+    pub end_ns: u64,
+    pub freq_lo_mhz: f64,
+    pub freq_hi_mhz: f64,
+}
+
+/// Набор аннотаций сессии (сохраняется рядом с записью в JSON).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationStore {
+    version: u32,
+    next_id: u64,
+    annotations: Vec<Annotation>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+/// Итог экспорта окрестности аннотации.
+#[derive(Debug, Clone)]
+pub struct AnnotationExport {
+    pub cut: CutRange,
+    /// Аннотации, попавшие в вырезанный интервал
+    pub annotations: usize,
+    /// Их sidecar-файл рядом с фрагментом (None — не попало ни одной)
+    pub annotations_path: Option<PathBuf>,
+}
+
+impl Annotation {
+    /// Пересекается ли аннотация с интервалом времени `[start_ns, end_ns]`.
+    pub fn overlaps(
+        &self,
+        start_ns: u64,
+        end_ns: u64,
+    ) -> bool {
+        self.start_ns <= end_ns && start_ns <= self.end_ns
+    }
+
+    pub fn duration_secs(&self) -> f64 {
+        (self.end_ns - self.start_ns) as f64 / 1e9
+    }
+}
+
+impl AnnotationStore {
+    pub fn new() -> Self {
+        Self {
+            version: ANNOTATIONS_VERSION,
+            next_id: 1,
+            annotations: Vec::new(),
+            dirty: false,
+        }
+    }
+
+    /// Путь sidecar-файла для записи: `rec.glos` → `rec.glos.annotations.json`.
+    pub fn sidecar_path(recording: &Path) -> PathBuf {
+        let mut name = recording.as_os_str().to_owned();
+        name.push(".annotations.json");
+        PathBuf::from(name)
+    }
+
+    /// Обратное к [`sidecar_path`](Self::sidecar_path): запись, к которой
+    /// относится sidecar-файл (None — путь не оканчивается на
+    /// `.annotations.json`).
+    pub fn recording_path(sidecar: &Path) -> Option<PathBuf> {
+        let name = sidecar.to_str()?.strip_suffix(".annotations.json")?;
+        (!name.is_empty()).then(|| PathBuf::from(name))
+    }
+
+    /// Добавляет аннотацию. Границы упорядочиваются автоматически.
+    pub fn add(
+        &mut self,
+        label: impl Into<String>,
+        time_ns: (u64, u64),
+        freq_mhz: (f64, f64),
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.annotations.push(Annotation {
+            id,
+            label: label.into(),
+            start_ns: time_ns.0.min(time_ns.1),
+            end_ns: time_ns.0.max(time_ns.1),
+            freq_lo_mhz: freq_mhz.0.min(freq_mhz.1),
+            freq_hi_mhz: freq_mhz.0.max(freq_mhz.1),
+        });
+        self.annotations.sort_by_key(|a| (a.start_ns, a.id));
+        self.dirty = true;
+
+        id
+    }
+
+    pub fn remove(
+        &mut self,
+        id: u64,
+    ) -> Option<Annotation> {
+        let pos = self.annotations.iter().position(|a| a.id == id)?;
+        self.dirty = true;
+        Some(self.annotations.remove(pos))
+    }
+
+    pub fn rename(
+        &mut self,
+        id: u64,
+        label: impl Into<String>,
+    ) -> bool {
+        match self.annotations.iter_mut().find(|a| a.id == id) {
+            Some(a) => {
+                a.label = label.into();
+                self.dirty = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(
+        &self,
+        id: u64,
+    ) -> Option<&Annotation> {
+        self.annotations.iter().find(|a| a.id == id)
+    }
+
+    /// Аннотации в порядке времени начала.
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Есть несохранённые изменения.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Сохраняет аннотации в JSON.
+    pub fn save(
+        &mut self,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Вырезает из записи `recording` окрестность аннотации `id`: `pre` до
+    /// её начала и `post` после конца — в новый файл `out` (см.
+    /// [`cut_range`]). Метаданные записи и аннотации, пересекающие
+    /// интервал, переносятся в sidecar-файлы рядом с `out`.
+    pub fn export_around(
+        &self,
+        recording: &Path,
+        id: u64,
+        pre: Duration,
+        post: Duration,
+        out: &Path,
+    ) -> GlosResult<AnnotationExport> {
+        let annotation = self
+            .get(id)
+            .ok_or_else(|| GlosError::format_violation(format!("no annotation with id {id}")))?;
+
+        let start_ns = annotation.start_ns.saturating_sub(pre.as_nanos() as u64);
+        let end_ns = annotation.end_ns.saturating_add(post.as_nanos() as u64);
+        let cut = cut_range(recording, out, start_ns, end_ns)?;
+
+        let mut copied = AnnotationStore::new();
+        for a in self.iter().filter(|a| a.overlaps(cut.start_ns, cut.end_ns)) {
+            copied.add(
+                a.label.clone(),
+                (a.start_ns, a.end_ns),
+                (a.freq_lo_mhz, a.freq_hi_mhz),
+            );
+        }
+
+        let annotations_path = if copied.is_empty() {
+            None
+        } else {
+            let path = Self::sidecar_path(out);
+            copied.save(&path)?;
+            Some(path)
+        };
+
+        Ok(AnnotationExport {
+            cut,
+            annotations: copied.len(),
+            annotations_path,
+        })
+    }
+
+    /// Путь фрагмента записи вокруг аннотации: `rec.glos` →
+    /// `rec_ann3.glos` рядом с записью.
+    pub fn export_path(
+        recording: &Path,
+        id: u64,
+    ) -> PathBuf {
+        let stem = recording
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        recording.with_file_name(format!("{stem}_ann{id}.glos"))
+    }
+
+    /// Загружает аннотации из JSON.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut store: Self = serde_json::from_reader(reader)?;
+
+        if store.version > ANNOTATIONS_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Unsupported annotations version {}", store.version),
+            ));
+        }
+
+        // Защита от ручной правки файла: id должны оставаться уникальными
+        let max_id = store.annotations.iter().map(|a| a.id).max().unwrap_or(0);
+        store.next_id = store.next_id.max(max_id + 1);
+        store.annotations.sort_by_key(|a| (a.start_ns, a.id));
+
+        Ok(store)
+    }
+}
+
+impl Default for AnnotationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Вырезает окрестность аннотации `annotation_id` из записи `file` в `out`;
+/// аннотации берутся из sidecar-файла записи (см.
+/// [`AnnotationStore::export_around`]).
+pub fn export_around_annotation(
+    file: &Path,
+    annotation_id: u64,
+    pre: Duration,
+    post: Duration,
+    out: &Path,
+) -> GlosResult<AnnotationExport> {
+    let store = AnnotationStore::load(&AnnotationStore::sidecar_path(file))?;
+    store.export_around(file, annotation_id, pre, post, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use glos_core::{GlosHeaderExt, GlosWriter, IqBlockExt};
+    use glos_types::{GlosHeader, IqBlock, SdrType};
+
+    use super::*;
+
+    #[test]
+    fn test_add_normalizes_and_sorts() {
+        let mut store = AnnotationStore::new();
+
+        let late = store.add("late", (5_000, 4_000), (1575.5, 1575.4));
+        let early = store.add("early", (1_000, 2_000), (1575.0, 1576.0));
+
+        let ids: Vec<u64> = store.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![early, late]);
+
+        let a = store.get(late).unwrap();
+        assert_eq!((a.start_ns, a.end_ns), (4_000, 5_000));
+        assert_eq!((a.freq_lo_mhz, a.freq_hi_mhz), (1575.4, 1575.5));
+        assert!(store.is_dirty());
+    }
+
+    #[test]
+    fn test_sidecar_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("glos-ui-ann-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = AnnotationStore::sidecar_path(&dir.join("rec.glos"));
+        assert!(path.ends_with("rec.glos.annotations.json"));
+        assert_eq!(
+            AnnotationStore::recording_path(&path),
+            Some(dir.join("rec.glos"))
+        );
+        assert_eq!(AnnotationStore::recording_path(Path::new("rec.json")), None);
+
+        let mut store = AnnotationStore::new();
+        let id = store.add("jammer", (10, 20), (1575.0, 1576.0));
+        store.add("spoof", (30, 40), (1600.0, 1601.0));
+        store.rename(id, "jammer burst");
+        store.save(&path).unwrap();
+        assert!(!store.is_dirty());
+
+        let mut loaded = AnnotationStore::load(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(id).unwrap().label, "jammer burst");
+
+        // Новый id не должен совпасть с загруженными
+        let new_id = loaded.add("new", (50, 60), (1.0, 2.0));
+        assert!(loaded.iter().filter(|a| a.id == new_id).count() == 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let mut store = AnnotationStore::new();
+        let id = store.add("a", (100, 200), (0.0, 1.0));
+        let a = store.get(id).unwrap();
+
+        assert!(a.overlaps(150, 300));
+        assert!(a.overlaps(0, 100));
+        assert!(!a.overlaps(201, 300));
+        assert!(store.remove(id).is_some());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_export_around_annotation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let recording = dir.path().join("rec.glos");
+
+        // 20 с записи: блок в секунду по 1000 выборок, начало в 1000 с
+        let t0 = 1_000_000_000_000u64;
+        let header = GlosHeader::new(SdrType::HackRf, 1_000, 1_602_000_000);
+        let mut writer = GlosWriter::new(File::create(&recording).unwrap(), header).unwrap();
+        for i in 0..20u64 {
+            let block = IqBlock::new(t0 + i * 1_000_000_000, 1_000, vec![i as u8; 4_000]);
+            writer.write_block(block).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let mut store = AnnotationStore::new();
+        let jammer = store.add(
+            "jammer on",
+            (t0 + 8_500_000_000, t0 + 10_000_000_000),
+            (1601.0, 1603.0),
+        );
+        store.add(
+            "later",
+            (t0 + 17_000_000_000, t0 + 18_000_000_000),
+            (1.0, 2.0),
+        );
+        store
+            .save(&AnnotationStore::sidecar_path(&recording))
+            .unwrap();
+
+        let out = AnnotationStore::export_path(&recording, jammer);
+        assert!(out.ends_with(format!("rec_ann{jammer}.glos")));
+
+        let margin = Duration::from_secs(5);
+        let export = export_around_annotation(&recording, jammer, margin, margin, &out).unwrap();
+
+        assert_eq!(
+            (export.cut.start_ns, export.cut.end_ns),
+            (t0 + 3_500_000_000, t0 + 15_000_000_000)
+        );
+        assert_eq!(export.cut.samples, 11_500);
+        assert_eq!(export.annotations, 1);
+
+        let copied = AnnotationStore::load(&export.annotations_path.unwrap()).unwrap();
+        assert_eq!(copied.iter().next().unwrap().label, "jammer on");
+
+        assert!(export_around_annotation(&recording, 99, margin, margin, &out).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+
+use super::SignalData;
+
+/// Сколько последних измерений хранится для экспорта.
+pub const CHANNEL_HISTORY_LEN: usize = 3600;
+
+/// Порог занятости по умолчанию (дБ).
+pub const DEFAULT_OCCUPANCY_THRESHOLD_DB: f32 = -70.0;
+
+/// Одно измерение мощности в канале.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelMeasurement {
+    pub timestamp: DateTime<Utc>,
+    pub lo_mhz: f64,
+    pub hi_mhz: f64,
+    /// Суммарная мощность бинов полосы (дБ)
+    pub power_db: f32,
+    /// Максимальный бин полосы (дБ)
+    pub peak_db: f32,
+    /// Пик полосы выше порога занятости
+    pub occupied: bool,
+}
+
+/// Непрерывное измерение мощности и занятости в выбранной полосе.
+///
+/// Занятость (duty cycle) — доля кадров, в которых хотя бы один бин полосы
+/// превысил порог. Смена полосы или порога начинает счёт заново.
+#[derive(Debug, Clone)]
+pub struct ChannelPowerMeter {
+    band: Option<(f64, f64)>,
+    threshold_db: f32,
+    history: VecDeque<ChannelMeasurement>,
+    frames: u64,
+    occupied_frames: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl ChannelPowerMeter {
+    pub fn band(&self) -> Option<(f64, f64)> {
+        self.band
+    }
+
+    /// Задаёт полосу (МГц, границы в любом порядке).
+    pub fn set_band(
+        &mut self,
+        a_mhz: f64,
+        b_mhz: f64,
+    ) {
+        self.band = Some((a_mhz.min(b_mhz), a_mhz.max(b_mhz)));
+        self.reset();
+    }
+
+    pub fn clear_band(&mut self) {
+        self.band = None;
+        self.reset();
+    }
+
+    pub fn threshold_db(&self) -> f32 {
+        self.threshold_db
+    }
+
+    pub fn set_threshold_db(
+        &mut self,
+        threshold_db: f32,
+    ) {
+        if threshold_db != self.threshold_db {
+            self.threshold_db = threshold_db;
+            self.reset();
+        }
+    }
+
+    /// Измеряет полосу на очередном кадре. Ничего не делает, если полоса не
+    /// выбрана или не попадает в спектр.
+    pub fn update(
+        &mut self,
+        sd: &SignalData,
+    ) {
+        let Some((lo, hi)) = self.band else {
+            return;
+        };
+        let Some((power_db, peak_db)) = sd.channel_power(lo, hi) else {
+            return;
+        };
+
+        let occupied = peak_db > self.threshold_db;
+
+        self.frames += 1;
+        self.occupied_frames += occupied as u64;
+
+        if self.history.len() >= CHANNEL_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(ChannelMeasurement {
+            timestamp: sd.timestamp,
+            lo_mhz: lo,
+            hi_mhz: hi,
+            power_db,
+            peak_db,
+            occupied,
+        });
+    }
+
+    pub fn latest(&self) -> Option<&ChannelMeasurement> {
+        self.history.back()
+    }
+
+    pub fn history(&self) -> &VecDeque<ChannelMeasurement> {
+        &self.history
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Занятость [0, 1] с последнего сброса.
+    pub fn occupancy(&self) -> Option<f64> {
+        (self.frames > 0).then(|| self.occupied_frames as f64 / self.frames as f64)
+    }
+
+    /// Сбрасывает счётчики и историю, полоса сохраняется.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.frames = 0;
+        self.occupied_frames = 0;
+    }
+}
+
+impl SignalData {
+    /// Мощность в полосе [lo, hi] МГц: (сумма бинов в линейной шкале, дБ;
+    /// максимальный бин, дБ). `None`, если в полосу не попал ни один бин.
+    pub fn channel_power(
+        &self,
+        lo_mhz: f64,
+        hi_mhz: f64,
+    ) -> Option<(f32, f32)> {
+        let len = self.fft_data.len();
+        let first = self.mhz_to_bin(lo_mhz).ceil().max(0.0) as usize;
+        let last = (self.mhz_to_bin(hi_mhz).floor() as usize).min(len.checked_sub(1)?);
+
+        let band = self.fft_data.get(first..=last).filter(|b| !b.is_empty())?;
+        let linear: f64 = band.iter().map(|&p| 10f64.powf(p as f64 / 10.0)).sum();
+        let peak = band.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        Some(((10.0 * linear.log10()) as f32, peak))
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for ChannelPowerMeter {
+    fn default() -> Self {
+        Self {
+            band: None,
+            threshold_db: DEFAULT_OCCUPANCY_THRESHOLD_DB,
+            history: VecDeque::with_capacity(CHANNEL_HISTORY_LEN),
+            frames: 0,
+            occupied_frames: 0,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    /// 4 бина по 1 МГц от 1000 МГц.
+    fn signal(fft: [f32; 4]) -> SignalData {
+        let mut sd = SignalData::new(1002.0, 4.0, 4);
+        sd.fft_data = fft.to_vec();
+        sd
+    }
+
+    #[test]
+    fn test_channel_power_sums_linear_bins() {
+        let sd = signal([-80.0, -60.0, -60.0, -70.0]);
+
+        // Два бина по -60 дБ → -60 + 10·lg 2
+        let (power, peak) = sd.channel_power(1000.5, 1002.5).unwrap();
+        assert_relative_eq!(power, -56.9897, epsilon = 1e-3);
+        assert_relative_eq!(peak, -60.0);
+
+        // Полоса между бинами и вне спектра
+        assert!(sd.channel_power(1000.2, 1000.8).is_none());
+        assert!(sd.channel_power(1010.0, 1020.0).is_none());
+    }
+
+    #[test]
+    fn test_meter_occupancy() {
+        let mut meter = ChannelPowerMeter::default();
+        meter.update(&signal([-60.0; 4]));
+        assert_eq!(meter.frames(), 0);
+
+        meter.set_band(1003.0, 1001.0);
+        meter.set_threshold_db(-65.0);
+        assert_eq!(meter.band(), Some((1001.0, 1003.0)));
+
+        meter.update(&signal([-90.0, -60.0, -90.0, -90.0]));
+        meter.update(&signal([-90.0, -90.0, -90.0, -90.0]));
+        meter.update(&signal([-90.0, -90.0, -90.0, -62.0]));
+        meter.update(&signal([-60.0, -90.0, -90.0, -90.0]));
+
+        assert_eq!(meter.frames(), 4);
+        assert_relative_eq!(meter.occupancy().unwrap(), 0.5);
+        assert!(!meter.latest().unwrap().occupied);
+
+        meter.set_threshold_db(-95.0);
+        assert_eq!(meter.occupancy(), None);
+        assert!(meter.history().is_empty());
+    }
+}
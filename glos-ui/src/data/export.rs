@@ -5,12 +5,29 @@ use std::{
 };
 
 use chrono::{DateTime, Utc};
+use egui::Color32;
 use serde_json::json;
 
-use super::{AppState, Satellite};
+use super::{AppState, ChannelMeasurement, IqStats, Satellite, SkyEpoch, IQ_HISTOGRAM_BINS};
 
 pub struct DataExporter;
 
+/// Параметры анимации полярной диаграммы.
+#[derive(Debug, Clone, Copy)]
+pub struct SkyGifOptions {
+    /// Сторона кадра (пиксели)
+    pub size: u16,
+    /// Длительность кадра (мс, GIF хранит сотые доли секунды)
+    pub frame_ms: u16,
+    /// Больше эпох — берётся каждая N-я, чтобы файл оставался разумным
+    pub max_frames: usize,
+}
+
+/// Индексы служебных цветов палитры GIF; цвета спутников идут за ними.
+const GIF_BACKGROUND: u8 = 0;
+const GIF_GRID: u8 = 1;
+const GIF_PROGRESS: u8 = 2;
+
 impl DataExporter {
     /// Экспорт спутника в CSV.
     pub fn export_satellites_csv(
@@ -72,6 +89,162 @@ impl DataExporter {
         Ok(())
     }
 
+    /// Экспорт измерений мощности в канале в CSV.
+    pub fn export_channel_power_csv<'a>(
+        measurements: impl IntoIterator<Item = &'a ChannelMeasurement>,
+        threshold_db: f32,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        writeln!(writer, "# Occupancy Threshold: {threshold_db:.1} dB")?;
+        writeln!(
+            writer,
+            "timestamp,lo_mhz,hi_mhz,channel_power_db,peak_db,occupied"
+        )?;
+
+        for m in measurements {
+            writeln!(
+                writer,
+                "{},{:.6},{:.6},{:.2},{:.2},{}",
+                m.timestamp.to_rfc3339(),
+                m.lo_mhz,
+                m.hi_mhz,
+                m.power_db,
+                m.peak_db,
+                m.occupied as u8,
+            )?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Экспорт статистики IQ в CSV: сводка в комментариях, затем
+    /// гистограммы I и Q по бинам.
+    pub fn export_iq_stats_csv(
+        stats: &IqStats,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        writeln!(writer, "# Source: {}", stats.source)?;
+        writeln!(writer, "# IQ Format: {}", stats.format)?;
+        writeln!(writer, "# Samples: {}", stats.samples)?;
+        for (name, c) in [("I", &stats.i), ("Q", &stats.q)] {
+            writeln!(
+                writer,
+                "# {name}: mean={:.6} std={:.6} clipped={:.3}%",
+                c.mean,
+                c.std,
+                c.clipping_percent(stats.samples),
+            )?;
+            if !c.bit_occupancy.is_empty() {
+                let bits: Vec<String> = c.bit_occupancy.iter().map(|p| format!("{p:.4}")).collect();
+                writeln!(
+                    writer,
+                    "# {name} bit occupancy (LSB first): {}",
+                    bits.join(",")
+                )?;
+            }
+        }
+        writeln!(writer, "bin_center,i_count,q_count")?;
+
+        for bin in 0..IQ_HISTOGRAM_BINS {
+            writeln!(
+                writer,
+                "{:.6},{},{}",
+                IqStats::bin_center(bin),
+                stats.i.histogram[bin],
+                stats.q.histogram[bin],
+            )?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Экспорт time-lapse полярной диаграммы в анимированный GIF.
+    ///
+    /// `color` задаёт цвет точки спутника (`None` — не рисовать, например
+    /// выключенное созвездие). Внизу кадра — полоса прогресса по сессии.
+    /// Возвращает число записанных кадров.
+    pub fn export_sky_gif<'a>(
+        epochs: impl IntoIterator<Item = &'a SkyEpoch>,
+        color: impl Fn(&Satellite) -> Option<Color32>,
+        options: &SkyGifOptions,
+        path: &Path,
+    ) -> std::io::Result<usize> {
+        let epochs: Vec<&SkyEpoch> = epochs.into_iter().collect();
+        if epochs.is_empty() {
+            return Err(std::io::Error::other("sky history is empty"));
+        }
+
+        let stride = epochs.len().div_ceil(options.max_frames.max(1));
+        let frames: Vec<&SkyEpoch> = epochs.iter().step_by(stride).copied().collect();
+
+        // Общая палитра: служебные цвета и все цвета спутников по порядку
+        // появления (их единицы, так что 256 хватает с запасом)
+        let mut colors = vec![
+            Color32::from_gray(27),
+            Color32::from_gray(70),
+            Color32::from_rgb(50, 150, 200),
+        ];
+        for sat in frames.iter().flat_map(|e| &e.satellites) {
+            if let Some(c) = color(sat) {
+                if !colors.contains(&c) && colors.len() < 256 {
+                    colors.push(c);
+                }
+            }
+        }
+        let palette: Vec<u8> = colors.iter().flat_map(|c| [c.r(), c.g(), c.b()]).collect();
+
+        let size = options.size.max(32);
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder =
+            gif::Encoder::new(file, size, size, &palette).map_err(std::io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(std::io::Error::other)?;
+
+        for (i, epoch) in frames.iter().enumerate() {
+            let mut pixels = sky_frame_grid(size);
+
+            for sat in &epoch.satellites {
+                let Some(c) = color(sat) else {
+                    continue;
+                };
+                let index = colors
+                    .iter()
+                    .position(|&p| p == c)
+                    .map_or(GIF_GRID, |p| p as u8);
+                let radius = if sat.used_in_fix { 5 } else { 3 };
+                let (x, y) = sky_to_pixel(size, sat.elevation, sat.azimuth);
+
+                fill_disc(&mut pixels, size, x, y, radius, index);
+            }
+
+            // Полоса прогресса: где в сессии находится кадр
+            let done = (i + 1) * size as usize / frames.len();
+            for y in size as usize - 3..size as usize {
+                pixels[y * size as usize..y * size as usize + done].fill(GIF_PROGRESS);
+            }
+
+            let mut frame = gif::Frame::from_indexed_pixels(size, size, pixels, None);
+            frame.delay = (options.frame_ms / 10).max(1);
+            encoder.write_frame(&frame).map_err(std::io::Error::other)?;
+        }
+
+        encoder
+            .into_inner()
+            .map_err(std::io::Error::other)?
+            .flush()?;
+
+        Ok(frames.len())
+    }
+
     /// Экспорт скриншота (через egui)
     pub fn export_screenshot(
         _ctx: &egui::Context,
@@ -95,6 +268,7 @@ impl DataExporter {
                 "used_in_fix": state.used_satellites(),
                 "avg_cn0": state.avg_cn0(),
             },
+            "constellations": state.constellation_stats(),
             "position": {
                 "latitude": state.position_lat,
                 "longitude": state.position_lon,
@@ -112,3 +286,86 @@ impl DataExporter {
         Ok(())
     }
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Пиксель кадра для высоты и азимута (градусы): зенит в центре, север
+/// сверху, как на полярной диаграмме панели.
+fn sky_to_pixel(
+    size: u16,
+    elevation: f32,
+    azimuth: f32,
+) -> (i32, i32) {
+    let half = size as f32 / 2.0;
+    let r = (half - 6.0) * (90.0 - elevation.clamp(0.0, 90.0)) / 90.0;
+    let az = azimuth.to_radians();
+
+    (
+        (half + r * az.sin()).round() as i32,
+        (half - r * az.cos()).round() as i32,
+    )
+}
+
+/// Пустой кадр с кругами высоты 0°/30°/60° и осями С–Ю, З–В.
+fn sky_frame_grid(size: u16) -> Vec<u8> {
+    let n = size as usize;
+    let mut pixels = vec![GIF_BACKGROUND; n * n];
+
+    for elevation in [0.0, 30.0, 60.0] {
+        for step in 0..720 {
+            let (x, y) = sky_to_pixel(size, elevation, step as f32 * 0.5);
+            set_pixel(&mut pixels, size, x, y, GIF_GRID);
+        }
+    }
+
+    for (a, b) in [(0.0, 180.0), (90.0, 270.0)] {
+        let (x0, y0) = sky_to_pixel(size, 0.0, a);
+        let (x1, y1) = sky_to_pixel(size, 0.0, b);
+        for t in 0..=n {
+            let k = t as f32 / n as f32;
+            let x = x0 as f32 + (x1 - x0) as f32 * k;
+            let y = y0 as f32 + (y1 - y0) as f32 * k;
+            set_pixel(
+                &mut pixels,
+                size,
+                x.round() as i32,
+                y.round() as i32,
+                GIF_GRID,
+            );
+        }
+    }
+
+    pixels
+}
+
+fn fill_disc(
+    pixels: &mut [u8],
+    size: u16,
+    cx: i32,
+    cy: i32,
+    radius: i32,
+    index: u8,
+) {
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy <= radius * radius {
+                set_pixel(pixels, size, cx + dx, cy + dy, index);
+            }
+        }
+    }
+}
+
+fn set_pixel(
+    pixels: &mut [u8],
+    size: u16,
+    x: i32,
+    y: i32,
+    index: u8,
+) {
+    let n = size as i32;
+    if (0..n).contains(&x) && (0..n).contains(&y) {
+        pixels[(y * n + x) as usize] = index;
+    }
+}
@@ -0,0 +1,269 @@
+use glos_types::IqFormat;
+use rustfft::num_complex::Complex32;
+
+/// Число бинов гистограммы на всю шкалу [-1, 1). Для Int8 бин совпадает с
+/// кодом АЦП.
+pub const IQ_HISTOGRAM_BINS: usize = 256;
+
+/// Сколько выборок берётся для статистики: больше — прореживаются
+/// равномерно по записи.
+pub const IQ_STATS_MAX_SAMPLES: usize = 262_144;
+
+/// Статистика одной компоненты (I или Q) в долях полной шкалы.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentStats {
+    /// Число выборок в каждом из [`IQ_HISTOGRAM_BINS`] бинов [-1, 1)
+    pub histogram: Vec<u64>,
+    pub mean: f64,
+    pub std: f64,
+    /// Выборок на краю шкалы (минимальный или максимальный код)
+    pub clipped: u64,
+    /// Доля выборок с установленным битом модуля кода, от младшего. Пусто
+    /// для Float32: у него нет кодов АЦП.
+    pub bit_occupancy: Vec<f64>,
+}
+
+/// Распределение IQ-выборок: гистограммы I и Q, среднее, СКО, доля
+/// клиппинга и занятость разрядов. По ним видно слишком малое или
+/// слишком большое усиление и постоянную составляющую.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IqStats {
+    /// Откуда взяты выборки: имя файла или живой поток
+    pub source: String,
+    pub format: IqFormat,
+    pub samples: u64,
+    pub i: ComponentStats,
+    pub q: ComponentStats,
+}
+
+/// Последние выборки живого источника.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IqCapture {
+    pub format: IqFormat,
+    /// Выборки, нормированные как в [`glos_types::IqBlock::as_complex_f32`]
+    pub samples: Vec<Complex32>,
+}
+
+/// Накопитель одной компоненты.
+struct Accumulator {
+    histogram: Vec<u64>,
+    sum: f64,
+    sum_sq: f64,
+    clipped: u64,
+    bits: Vec<u64>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl ComponentStats {
+    /// Доля клиппинга, %.
+    pub fn clipping_percent(
+        &self,
+        samples: u64,
+    ) -> f64 {
+        if samples == 0 {
+            return 0.0;
+        }
+        self.clipped as f64 * 100.0 / samples as f64
+    }
+
+    /// Сколько разрядов кода реально используется, включая знаковый:
+    /// старший бит модуля, встретившийся хоть раз, плюс знак. `None` для
+    /// Float32.
+    pub fn used_bits(&self) -> Option<u32> {
+        if self.bit_occupancy.is_empty() {
+            return None;
+        }
+
+        let top = self.bit_occupancy.iter().rposition(|&p| p > 0.0);
+        Some(top.map_or(1, |b| b as u32 + 2))
+    }
+}
+
+impl IqStats {
+    /// Считает статистику по выборкам, нормированным к полной шкале
+    /// формата `format`.
+    pub fn compute(
+        source: impl Into<String>,
+        samples: &[Complex32],
+        format: IqFormat,
+    ) -> Self {
+        let mut i = Accumulator::new(format);
+        let mut q = Accumulator::new(format);
+
+        for s in samples {
+            i.push(s.re, format);
+            q.push(s.im, format);
+        }
+
+        let n = samples.len() as u64;
+        Self {
+            source: source.into(),
+            format,
+            samples: n,
+            i: i.finish(n),
+            q: q.finish(n),
+        }
+    }
+
+    /// Центр бина гистограммы в долях полной шкалы.
+    pub fn bin_center(bin: usize) -> f64 {
+        (bin as f64 + 0.5) * 2.0 / IQ_HISTOGRAM_BINS as f64 - 1.0
+    }
+}
+
+impl Accumulator {
+    fn new(format: IqFormat) -> Self {
+        Self {
+            histogram: vec![0; IQ_HISTOGRAM_BINS],
+            sum: 0.0,
+            sum_sq: 0.0,
+            clipped: 0,
+            bits: vec![0; magnitude_bits(format)],
+        }
+    }
+
+    fn push(
+        &mut self,
+        v: f32,
+        format: IqFormat,
+    ) {
+        let bin = ((v as f64 + 1.0) * 0.5 * IQ_HISTOGRAM_BINS as f64).floor();
+        self.histogram[bin.clamp(0.0, (IQ_HISTOGRAM_BINS - 1) as f64) as usize] += 1;
+        self.sum += v as f64;
+        self.sum_sq += v as f64 * v as f64;
+
+        let Some((code, min, max)) = adc_code(v, format) else {
+            self.clipped += (v.abs() >= 1.0) as u64;
+            return;
+        };
+
+        self.clipped += (code <= min || code >= max) as u64;
+
+        let magnitude = code.unsigned_abs().min(max as u32);
+        for (k, count) in self.bits.iter_mut().enumerate() {
+            *count += (magnitude >> k & 1) as u64;
+        }
+    }
+
+    fn finish(
+        self,
+        n: u64,
+    ) -> ComponentStats {
+        let (mean, std) = if n == 0 {
+            (0.0, 0.0)
+        } else {
+            let mean = self.sum / n as f64;
+            (mean, (self.sum_sq / n as f64 - mean * mean).max(0.0).sqrt())
+        };
+
+        ComponentStats {
+            histogram: self.histogram,
+            mean,
+            std,
+            clipped: self.clipped,
+            bit_occupancy: self
+                .bits
+                .iter()
+                .map(|&c| if n == 0 { 0.0 } else { c as f64 / n as f64 })
+                .collect(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Разрядов модуля кода (без знака); 0 для Float32.
+fn magnitude_bits(format: IqFormat) -> usize {
+    match format {
+        IqFormat::Int8 => 7,
+        IqFormat::Int16 => 15,
+        IqFormat::Float32 => 0,
+    }
+}
+
+/// Код АЦП для нормированной выборки и границы шкалы: обратное к
+/// нормировке [`glos_types::IqBlock::as_complex_f32_with`].
+fn adc_code(
+    v: f32,
+    format: IqFormat,
+) -> Option<(i32, i32, i32)> {
+    match format {
+        IqFormat::Int8 => Some(((v * 128.0).round() as i32, i8::MIN as i32, i8::MAX as i32)),
+        IqFormat::Int16 => Some((
+            (v * 32767.0).round() as i32,
+            i16::MIN as i32,
+            i16::MAX as i32,
+        )),
+        IqFormat::Float32 => None,
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn int8(codes: &[(i8, i8)]) -> Vec<Complex32> {
+        codes
+            .iter()
+            .map(|&(i, q)| Complex32::new(i as f32 / 128.0, q as f32 / 128.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_int8_stats() {
+        let samples = int8(&[(-128, 0), (127, 1), (4, -2), (-3, 1)]);
+        let stats = IqStats::compute("test", &samples, IqFormat::Int8);
+
+        assert_eq!(stats.samples, 4);
+
+        // Бин Int8 — это код + 128
+        assert_eq!(stats.i.histogram[0], 1);
+        assert_eq!(stats.i.histogram[255], 1);
+        assert_eq!(stats.i.histogram[132], 1);
+        assert_eq!(stats.i.histogram.iter().sum::<u64>(), 4);
+        assert_relative_eq!(IqStats::bin_center(132), 4.5 / 128.0);
+
+        assert_eq!(stats.i.clipped, 2);
+        assert_relative_eq!(stats.i.clipping_percent(stats.samples), 50.0);
+        assert_eq!(stats.q.clipped, 0);
+
+        assert_relative_eq!(stats.q.mean, 0.0);
+        assert_relative_eq!(stats.q.std, (6.0f64 / 4.0).sqrt() / 128.0);
+
+        // Q: модули 0, 1, 2, 1 — заняты только два младших разряда
+        assert_eq!(stats.q.bit_occupancy.len(), 7);
+        assert_relative_eq!(stats.q.bit_occupancy[0], 0.5);
+        assert_relative_eq!(stats.q.bit_occupancy[1], 0.25);
+        assert_eq!(stats.q.used_bits(), Some(3));
+        assert_eq!(stats.i.used_bits(), Some(8));
+    }
+
+    #[test]
+    fn test_float32_and_empty_stats() {
+        let samples = [Complex32::new(1.5, 0.25), Complex32::new(-0.25, -1.0)];
+        let stats = IqStats::compute("test", &samples, IqFormat::Float32);
+
+        assert_eq!((stats.i.clipped, stats.q.clipped), (1, 1));
+        assert!(stats.i.bit_occupancy.is_empty());
+        assert_eq!(stats.i.used_bits(), None);
+        // За шкалой — в крайних бинах
+        assert_eq!(stats.i.histogram[IQ_HISTOGRAM_BINS - 1], 1);
+
+        let empty = IqStats::compute("test", &[], IqFormat::Int16);
+        assert_eq!(empty.samples, 0);
+        assert_eq!(empty.i.std, 0.0);
+        assert_eq!(empty.i.clipping_percent(0), 0.0);
+        assert_eq!(empty.i.used_bits(), Some(1));
+    }
+}
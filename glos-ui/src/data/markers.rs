@@ -0,0 +1,218 @@
+use super::SignalData;
+
+/// Максимальное число маркеров на спектре.
+pub const MAX_MARKERS: usize = 4;
+
+/// Измерительные маркеры спектра (частоты в МГц, по порядку установки).
+///
+/// Маркер хранит только частоту: мощность читается из текущего кадра при
+/// каждой отрисовке, так что показания следуют за живым спектром.
+#[derive(Debug, Clone, Default)]
+pub struct SpectrumMarkers {
+    freqs_mhz: Vec<f64>,
+}
+
+/// Показания маркера на текущем кадре.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarkerReading {
+    pub freq_mhz: f64,
+    pub power_db: f32,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SpectrumMarkers {
+    /// Ставит маркер на частоту. Когда все маркеры заняты, переносится
+    /// ближайший к ней.
+    pub fn place(
+        &mut self,
+        freq_mhz: f64,
+    ) {
+        if self.freqs_mhz.len() < MAX_MARKERS {
+            self.freqs_mhz.push(freq_mhz);
+        } else if let Some(i) = self.nearest(freq_mhz) {
+            self.freqs_mhz[i] = freq_mhz;
+        }
+    }
+
+    /// Удаляет маркер, ближайший к частоте.
+    pub fn remove_nearest(
+        &mut self,
+        freq_mhz: f64,
+    ) {
+        if let Some(i) = self.nearest(freq_mhz) {
+            self.freqs_mhz.remove(i);
+        }
+    }
+
+    /// Ставит M1 на максимум текущего спектра.
+    pub fn peak_search(
+        &mut self,
+        sd: &SignalData,
+    ) {
+        let Some(peak) = sd.peak() else {
+            return;
+        };
+
+        match self.freqs_mhz.first_mut() {
+            Some(m1) => *m1 = peak.freq_mhz,
+            None => self.freqs_mhz.push(peak.freq_mhz),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.freqs_mhz.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.freqs_mhz.is_empty()
+    }
+
+    /// Частоты маркеров по порядку установки (МГц).
+    pub fn freqs_mhz(&self) -> &[f64] {
+        &self.freqs_mhz
+    }
+
+    /// Показания всех маркеров на кадре `sd` (маркеры вне полосы
+    /// пропускаются).
+    pub fn readings(
+        &self,
+        sd: &SignalData,
+    ) -> Vec<(usize, MarkerReading)> {
+        self.freqs_mhz
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &f)| {
+                sd.power_at_mhz(f).map(|power_db| {
+                    (
+                        i,
+                        MarkerReading {
+                            freq_mhz: f,
+                            power_db,
+                        },
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Разность M2 − M1 (МГц, дБ).
+    pub fn delta(
+        &self,
+        sd: &SignalData,
+    ) -> Option<(f64, f32)> {
+        let (&f1, &f2) = (self.freqs_mhz.first()?, self.freqs_mhz.get(1)?);
+        let (p1, p2) = (sd.power_at_mhz(f1)?, sd.power_at_mhz(f2)?);
+
+        Some((f2 - f1, p2 - p1))
+    }
+
+    fn nearest(
+        &self,
+        freq_mhz: f64,
+    ) -> Option<usize> {
+        self.freqs_mhz
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| (*a - freq_mhz).abs().total_cmp(&(*b - freq_mhz).abs()))
+            .map(|(i, _)| i)
+    }
+}
+
+impl SignalData {
+    /// Мощность (дБ) на частоте с линейной интерполяцией между бинами.
+    /// `None`, если частота вне полосы.
+    pub fn power_at_mhz(
+        &self,
+        mhz: f64,
+    ) -> Option<f32> {
+        let data = &self.fft_data;
+        let last = data.len().checked_sub(1)?;
+        let bin = self.mhz_to_bin(mhz);
+
+        if !(0.0..=last as f64).contains(&bin) {
+            return None;
+        }
+
+        let i = bin.floor() as usize;
+        let j = (i + 1).min(last);
+        let frac = (bin - i as f64) as f32;
+
+        Some(data[i] + (data[j] - data[i]) * frac)
+    }
+
+    /// Максимум текущего спектра.
+    pub fn peak(&self) -> Option<MarkerReading> {
+        let (i, &power_db) = self
+            .fft_data
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+        Some(MarkerReading {
+            freq_mhz: self.bin_to_mhz(i as f64),
+            power_db,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    /// 4 бина по 1 МГц от 1000 МГц: -80, -60, -40, -70 дБ.
+    fn signal() -> SignalData {
+        let mut sd = SignalData::new(1002.0, 4.0, 4);
+        sd.fft_data = vec![-80.0, -60.0, -40.0, -70.0];
+        sd
+    }
+
+    #[test]
+    fn test_power_at_mhz_and_peak() {
+        let sd = signal();
+
+        assert_relative_eq!(sd.power_at_mhz(1001.0).unwrap(), -60.0);
+        assert_relative_eq!(sd.power_at_mhz(1001.5).unwrap(), -50.0);
+        assert!(sd.power_at_mhz(999.0).is_none());
+        assert!(sd.power_at_mhz(1004.0).is_none());
+
+        let peak = sd.peak().unwrap();
+        assert_relative_eq!(peak.freq_mhz, 1002.0);
+        assert_relative_eq!(peak.power_db, -40.0);
+    }
+
+    #[test]
+    fn test_markers_place_delta_and_peak_search() {
+        let sd = signal();
+        let mut m = SpectrumMarkers::default();
+
+        m.place(1000.0);
+        m.place(1003.0);
+        let (df, dp) = m.delta(&sd).unwrap();
+        assert_relative_eq!(df, 3.0);
+        assert_relative_eq!(dp, 10.0);
+
+        // Поиск пика переносит M1, M2 остаётся
+        m.peak_search(&sd);
+        assert_relative_eq!(m.readings(&sd)[0].1.freq_mhz, 1002.0);
+        assert_relative_eq!(m.delta(&sd).unwrap().1, -30.0);
+
+        // Сверх лимита переносится ближайший маркер
+        m.place(1000.5);
+        m.place(1001.0);
+        m.place(1001.2);
+        assert_eq!(m.readings(&sd).len(), MAX_MARKERS);
+        assert_relative_eq!(m.readings(&sd)[3].1.freq_mhz, 1001.2);
+
+        m.remove_nearest(1003.1);
+        assert_eq!(m.readings(&sd).len(), 3);
+    }
+}
@@ -8,10 +8,15 @@ use std::{
 };
 
 use chrono::Utc;
+use glos_types::IqFormat;
 use parking_lot::RwLock;
 use rand::Rng;
+use rustfft::num_complex::Complex32;
 
-use crate::data::{AppState, ConnectionStatus, Satellite, SystemMetrics};
+use crate::{
+    data::{AppState, ConnectionStatus, IqCapture, Satellite, SystemMetrics},
+    panels::{Constellation, EnabledConstellations},
+};
 
 pub struct MockDataGenerator {
     state: Arc<RwLock<AppState>>,
@@ -52,8 +57,10 @@ impl MockDataGenerator {
                     // Обновляем статус
                     state.status = ConnectionStatus::Mock;
 
-                    // Генерируем спутники
-                    state.satellites = Self::generate_satellites(&mut rng, time);
+                    // Генерируем спутники (только включённые созвездия)
+                    let enabled = state.settings.constellations;
+                    let satellites = Self::generate_satellites(&mut rng, time, &enabled);
+                    state.set_satellites(satellites);
 
                     // Обновляем CN0 историю
                     let avg_cn0 = state.avg_cn0();
@@ -63,10 +70,16 @@ impl MockDataGenerator {
                     }
 
                     // Генерируем FFT данные
+                    // Блоков у генератора нет — метка кадра по часам хоста
                     let fft_data = Self::generate_fft(&mut rng, time);
-                    state.signal_data.fft_data = fft_data.clone();
-                    state.signal_data.push_waterfall(fft_data);
-                    state.signal_data.timestamp = Utc::now();
+                    let timestamp_ns = Utc::now().timestamp_nanos_opt().unwrap_or(0).max(0);
+                    state.push_spectrum(fft_data, timestamp_ns as u64);
+
+                    // IQ-выборки для гистограмм
+                    state.push_iq(IqCapture {
+                        format: IqFormat::Int8,
+                        samples: Self::generate_iq(&mut rng, time),
+                    });
 
                     // Обновляем метрики
                     state.metrics = SystemMetrics {
@@ -120,23 +133,30 @@ impl MockDataGenerator {
     fn generate_satellites(
         rng: &mut impl Rng,
         time: f32,
+        enabled: &EnabledConstellations,
     ) -> Vec<Satellite> {
         let constellations = [
-            ("GPS", "G", 12),
-            ("ГЛОНАСС", "R", 8),
-            ("Галилео", "E", 6),
-            ("Бэйдоу", "C", 5),
+            (Constellation::Gps, 12),
+            (Constellation::Glonass, 8),
+            (Constellation::Galileo, 6),
+            (Constellation::BeiDou, 5),
         ];
 
         let mut satellites = Vec::new();
 
-        for (const_name, prefix, count) in constellations {
+        for (constellation, count) in constellations {
+            let prefix = constellation.code();
+            // Выключенное созвездие не ищем вовсе
+            if !enabled.is_enabled(constellation) {
+                continue;
+            }
+
             for i in 1..=count {
                 let phase = time * 0.1 + i as f32 * 0.5;
 
                 satellites.push(Satellite {
                     id: format!("{prefix}{i:02}"),
-                    constellation: const_name.to_string(),
+                    constellation: constellation.name().to_string(),
                     cn0: 30.0 + 10.0 * (phase.sin() + 1.0) + rng.random::<f32>() * 3.0,
                     elevation: 15.0 + 60.0 * (phase.cos() + 1.0) / 2.0,
                     azimuth: ((i as f32 * 360.0 / count as f32) + time * 5.0) % 360.0,
@@ -178,4 +198,39 @@ impl MockDataGenerator {
 
         fft
     }
+
+    /// Шум в кодах Int8, как у HackRF. Уровень медленно «плывёт», так что
+    /// в гистограмме видны и недогруз, и клиппинг.
+    fn generate_iq(
+        rng: &mut impl Rng,
+        time: f32,
+    ) -> Vec<Complex32> {
+        let sigma = 30.0 + 25.0 * (time * 0.2).sin();
+        // Сумма четырёх равномерных ≈ нормальное распределение, σ = 1
+        let mut gauss = || (0..4).map(|_| rng.random::<f32>() - 0.5).sum::<f32>() * 3f32.sqrt();
+        let code = |v: f32| (v * sigma).round().clamp(-128.0, 127.0) / 128.0;
+
+        (0..4096)
+            .map(|_| Complex32::new(code(gauss()), code(gauss())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_constellations_not_generated() {
+        let enabled = EnabledConstellations {
+            gps: false,
+            beidou: false,
+            ..Default::default()
+        };
+
+        let sats = MockDataGenerator::generate_satellites(&mut rand::rng(), 0.0, &enabled);
+
+        assert_eq!(sats.len(), 8 + 6);
+        assert!(sats.iter().all(|s| enabled.allows(&s.constellation)));
+    }
 }
@@ -1,7 +1,23 @@
+pub mod annotations;
+pub mod channel_power;
 pub mod export;
+pub mod iq_stats;
+pub mod markers;
 pub mod mock;
+pub mod multires;
+pub mod session;
+pub mod sky_history;
+pub mod spectrum;
 pub mod state;
 
+pub use annotations::*;
+pub use channel_power::*;
 pub use export::*;
+pub use iq_stats::*;
+pub use markers::*;
 pub use mock::*;
+pub use multires::*;
+pub use session::*;
+pub use sky_history::*;
+pub use spectrum::*;
 pub use state::*;
@@ -0,0 +1,714 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use glos_analyzer::WindowFunction;
+use glos_core::{
+    FollowOptions, GlosHeaderExt, GlosReader, ReadStats, RecordingMetadata, DEFAULT_FOLLOW_POLL,
+};
+use glos_types::{GlosError, IqFormat, RfChain};
+use parking_lot::{Mutex, RwLock};
+use rustfft::{num_complex::Complex32, FftPlanner};
+
+/// Размер FFT обзорного водопада (весь файл).
+pub const BASE_FFT_SIZE: usize = 512;
+
+/// Максимальный размер FFT при увеличении.
+pub const MAX_ZOOM_FFT_SIZE: usize = 65_536;
+
+/// Максимум выборок, загружаемых из файла в память.
+pub const MAX_FILE_SAMPLES: usize = 32 * 1024 * 1024;
+
+/// Видимая область водопада файла и её разрешение на экране.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterfallView {
+    pub f_lo_mhz: f64,
+    pub f_hi_mhz: f64,
+    /// Время от начала файла (с)
+    pub t0_s: f64,
+    pub t1_s: f64,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// Водопад, посчитанный для конкретной области: `rows × cols` дБ, строки по
+/// возрастанию времени.
+#[derive(Debug, Clone)]
+pub struct WaterfallTile {
+    pub view: WaterfallView,
+    pub fft_size: usize,
+    pub power_db: Vec<Vec<f32>>,
+}
+
+/// IQ записи, загруженные для пересчёта водопада.
+pub struct FileSpectrumSource {
+    path: PathBuf,
+    name: String,
+    samples: RwLock<Vec<Complex32>>,
+    sample_rate: u32,
+    center_freq_hz: u64,
+    iq_format: IqFormat,
+    little_endian: bool,
+    /// Радиочасть из файла метаданных записи, если он есть
+    rf_chain: Option<RfChain>,
+    /// Статистика чтения всего файла, включая повреждения
+    read_stats: Mutex<ReadStats>,
+    /// Читатель активной записи в режиме `tail -f` (см.
+    /// [`FileSpectrumSource::attach`]); None — файл прочитан целиком
+    tail: Mutex<Option<GlosReader<BufReader<File>>>>,
+}
+
+/// Водопад файла с несколькими уровнями разрешения.
+///
+/// Обзор всего файла считается при открытии FFT на [`BASE_FFT_SIZE`]
+/// бинов. При увеличении видимая область пересчитывается в фоне FFT
+/// большего размера (до [`MAX_ZOOM_FFT_SIZE`]), так что узкополосный
+/// сигнал показывает детали, а не растянутые бины. Одновременно идёт не
+/// больше одного пересчёта; запрошенная во время расчёта область
+/// считается следующей.
+pub struct MultiResWaterfall {
+    source: Arc<FileSpectrumSource>,
+    /// Пересчитывается, когда активная запись дорастает (см.
+    /// [`MultiResWaterfall::poll_live`])
+    overview: Mutex<Arc<WaterfallTile>>,
+    inner: Arc<Mutex<ZoomState>>,
+}
+
+#[derive(Default)]
+struct ZoomState {
+    zoomed: Option<Arc<WaterfallTile>>,
+    wanted: Option<WaterfallView>,
+    in_flight: Option<WaterfallView>,
+    generation: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl WaterfallView {
+    pub fn span_mhz(&self) -> f64 {
+        self.f_hi_mhz - self.f_lo_mhz
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.t1_s - self.t0_s
+    }
+
+    /// Области отличаются меньше чем на долю пикселя — пересчёт не нужен.
+    pub fn approx_eq(
+        &self,
+        other: &WaterfallView,
+    ) -> bool {
+        let fx = self.span_mhz() / self.cols.max(1) as f64;
+        let ty = self.duration_s() / self.rows.max(1) as f64;
+
+        self.rows == other.rows
+            && self.cols == other.cols
+            && (self.f_lo_mhz - other.f_lo_mhz).abs() < fx * 0.5
+            && (self.f_hi_mhz - other.f_hi_mhz).abs() < fx * 0.5
+            && (self.t0_s - other.t0_s).abs() < ty * 0.5
+            && (self.t1_s - other.t1_s).abs() < ty * 0.5
+    }
+}
+
+impl FileSpectrumSource {
+    /// Загружает выборки файла (не больше [`MAX_FILE_SAMPLES`]).
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Self::load(path, None)
+    }
+
+    /// Как [`open`](Self::open), но для записи, которая ещё идёт: файл
+    /// читается в режиме [`GlosReader::follow`], а новые блоки
+    /// добавляются через [`poll`](Self::poll). Недописанный последний блок
+    /// ждёт следующего опроса и повреждением не считается.
+    pub fn attach(path: &Path) -> Result<Self, String> {
+        Self::load(
+            path,
+            Some(FollowOptions {
+                poll_interval: DEFAULT_FOLLOW_POLL,
+                timeout: Some(Duration::ZERO),
+            }),
+        )
+    }
+
+    fn load(
+        path: &Path,
+        follow: Option<FollowOptions>,
+    ) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut reader = GlosReader::new(BufReader::new(file)).map_err(|e| e.to_string())?;
+        if let Some(options) = follow {
+            reader = reader.follow(options);
+        }
+        let header = reader.header().clone();
+
+        let source = Self {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            samples: RwLock::new(Vec::new()),
+            sample_rate: header.sample_rate,
+            center_freq_hz: header.center_freq,
+            iq_format: header.iq_format,
+            little_endian: header.samples_little_endian(),
+            // Битый файл метаданных не мешает смотреть водопад
+            rf_chain: RecordingMetadata::load(path)
+                .ok()
+                .flatten()
+                .and_then(|m| m.rf_chain),
+            read_stats: Mutex::new(ReadStats::default()),
+            tail: Mutex::new(None),
+        };
+
+        if source.append_from(&mut reader)? && follow.is_some() {
+            *source.tail.lock() = Some(reader);
+        }
+
+        let len = source.samples.read().len();
+        if len < BASE_FFT_SIZE {
+            return Err(format!(
+                "{}: only {len} samples, need at least {BASE_FFT_SIZE}",
+                path.display(),
+            ));
+        }
+
+        Ok(source)
+    }
+
+    /// Дочитывает блоки, дописанные в активную запись с прошлого опроса;
+    /// возвращает число новых выборок. Не блокирует: недописанный блок
+    /// подождёт следующего вызова.
+    pub fn poll(&self) -> Result<usize, String> {
+        let mut tail = self.tail.lock();
+        let Some(reader) = tail.as_mut() else {
+            return Ok(0);
+        };
+
+        let before = self.samples.read().len();
+        if !self.append_from(reader)? {
+            // Достигнут предел памяти — дальше не следим
+            *tail = None;
+        }
+
+        Ok(self.samples.read().len() - before)
+    }
+
+    /// Запись ещё дочитывается (см. [`attach`](Self::attach)).
+    pub fn is_live(&self) -> bool {
+        self.tail.lock().is_some()
+    }
+
+    /// Читает доступные блоки в `samples`; `false`, если упёрлись в
+    /// [`MAX_FILE_SAMPLES`].
+    fn append_from(
+        &self,
+        reader: &mut GlosReader<BufReader<File>>,
+    ) -> Result<bool, String> {
+        // По блоку за раз: в памяти не больше MAX_FILE_SAMPLES выборок,
+        // сколько бы ни весил файл
+        let mut room = true;
+        while let Some(result) = reader.next_block() {
+            let block = match result {
+                Ok(b) => b,
+                Err(GlosError::CrcMismatch { .. }) => continue,
+                Err(e) => return Err(e.to_string()),
+            };
+
+            let mut samples = self.samples.write();
+            samples.extend(block.as_complex_f32_with(self.iq_format, self.little_endian));
+            if samples.len() >= MAX_FILE_SAMPLES {
+                samples.truncate(MAX_FILE_SAMPLES);
+                room = false;
+                break;
+            }
+        }
+        *self.read_stats.lock() = reader.stats().clone();
+
+        Ok(room)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn iq_format(&self) -> IqFormat {
+        self.iq_format
+    }
+
+    /// Не больше `max` выборок, равномерно по всей записи.
+    pub fn spread_samples(
+        &self,
+        max: usize,
+    ) -> Vec<Complex32> {
+        let samples = self.samples.read();
+        let stride = samples.len().div_ceil(max.max(1)).max(1);
+
+        samples.iter().step_by(stride).copied().collect()
+    }
+
+    pub fn rf_chain(&self) -> Option<&RfChain> {
+        self.rf_chain.as_ref()
+    }
+
+    pub fn read_stats(&self) -> ReadStats {
+        self.read_stats.lock().clone()
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.samples.read().len() as f64 / self.sample_rate.max(1) as f64
+    }
+
+    /// Полоса файла (МГц).
+    pub fn freq_range_mhz(&self) -> (f64, f64) {
+        let center = self.center_freq_hz as f64 / 1e6;
+        let half = self.sample_rate as f64 / 2e6;
+
+        (center - half, center + half)
+    }
+
+    /// Область всего файла.
+    pub fn full_view(
+        &self,
+        rows: usize,
+        cols: usize,
+    ) -> WaterfallView {
+        let (f_lo_mhz, f_hi_mhz) = self.freq_range_mhz();
+
+        WaterfallView {
+            f_lo_mhz,
+            f_hi_mhz,
+            t0_s: 0.0,
+            t1_s: self.duration_s(),
+            rows,
+            cols,
+        }
+    }
+
+    /// Размер FFT, при котором бин не шире столбца области (степень двойки
+    /// в пределах [`BASE_FFT_SIZE`]..=[`MAX_ZOOM_FFT_SIZE`] и длины файла).
+    pub fn fft_size_for(
+        &self,
+        view: &WaterfallView,
+    ) -> usize {
+        let col_hz = view.span_mhz() * 1e6 / view.cols.max(1) as f64;
+        let wanted = if col_hz > 0.0 {
+            (self.sample_rate as f64 / col_hz).ceil() as usize
+        } else {
+            MAX_ZOOM_FFT_SIZE
+        };
+
+        let limit = prev_power_of_two(self.samples.read().len()).min(MAX_ZOOM_FFT_SIZE);
+
+        wanted.next_power_of_two().clamp(BASE_FFT_SIZE, limit)
+    }
+
+    /// Считает водопад области: по одной FFT на строку, столбец = максимум
+    /// бинов, попавших в его полосу (узкие сигналы не пропадают при
+    /// сжатии).
+    pub fn render(
+        &self,
+        view: &WaterfallView,
+        fft_size: usize,
+    ) -> WaterfallTile {
+        let n = fft_size;
+        let fft = FftPlanner::new().plan_fft_forward(n);
+        let window = WindowFunction::Hann.coefficients(n);
+        let norm = WindowFunction::Hann.power_norm(n);
+
+        let rate = self.sample_rate as f64;
+        let bin_mhz = rate / n as f64 / 1e6;
+        let (band_lo, _) = self.freq_range_mhz();
+        let col_mhz = view.span_mhz() / view.cols.max(1) as f64;
+        let samples = self.samples.read();
+        let last_start = samples.len() - n;
+
+        let mut buf = vec![Complex32::new(0.0, 0.0); n];
+        let mut power = vec![0.0f32; n];
+        let mut rows = Vec::with_capacity(view.rows);
+
+        for r in 0..view.rows {
+            let t = view.t0_s + (r as f64 + 0.5) / view.rows as f64 * view.duration_s();
+            let center = (t * rate) as i64 - n as i64 / 2;
+            let start = center.clamp(0, last_start as i64) as usize;
+
+            for ((b, s), &w) in buf.iter_mut().zip(&samples[start..start + n]).zip(&window) {
+                *b = Complex32::new(s.re * w, s.im * w);
+            }
+            fft.process(&mut buf);
+
+            // fftshift + дБ
+            for (i, p) in power.iter_mut().enumerate() {
+                let c = buf[(i + n / 2) % n];
+                *p = 10.0 * (c.norm_sqr() / norm).max(1e-12).log10();
+            }
+
+            let row = (0..view.cols)
+                .map(|c| {
+                    let lo = view.f_lo_mhz + c as f64 * col_mhz;
+                    // Бин i покрывает [i - 0.5, i + 0.5) в единицах бинов
+                    let x0 = (lo - band_lo) / bin_mhz + 0.5;
+                    let x1 = x0 + col_mhz / bin_mhz;
+                    let first = x0.floor().max(0.0) as usize;
+                    let last = (x1.ceil().max(0.0) as usize).max(first + 1);
+
+                    power
+                        .get(first.min(n - 1)..last.min(n))
+                        .and_then(|bins| bins.iter().copied().reduce(f32::max))
+                        .unwrap_or(f32::NEG_INFINITY)
+                })
+                .collect();
+
+            rows.push(row);
+        }
+
+        WaterfallTile {
+            view: *view,
+            fft_size: n,
+            power_db: rows,
+        }
+    }
+}
+
+impl MultiResWaterfall {
+    /// Открывает файл и считает обзорный водопад.
+    pub fn open(
+        path: &Path,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, String> {
+        Ok(Self::new(FileSpectrumSource::open(path)?, rows, cols))
+    }
+
+    /// Как [`open`](Self::open), но для идущей записи (см.
+    /// [`FileSpectrumSource::attach`]); обзор растёт через
+    /// [`poll_live`](Self::poll_live).
+    pub fn attach(
+        path: &Path,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, String> {
+        Ok(Self::new(FileSpectrumSource::attach(path)?, rows, cols))
+    }
+
+    fn new(
+        source: FileSpectrumSource,
+        rows: usize,
+        cols: usize,
+    ) -> Self {
+        let overview = source.render(&source.full_view(rows, cols), BASE_FFT_SIZE);
+
+        Self {
+            source: Arc::new(source),
+            overview: Mutex::new(Arc::new(overview)),
+            inner: Arc::default(),
+        }
+    }
+
+    pub fn source(&self) -> &FileSpectrumSource {
+        &self.source
+    }
+
+    /// Обзорный водопад всего файла.
+    pub fn overview(&self) -> Arc<WaterfallTile> {
+        self.overview.lock().clone()
+    }
+
+    /// Дочитывает активную запись и, если она выросла, пересчитывает обзор
+    /// на всю новую длительность. Возвращает `true`, если обзор сменился.
+    pub fn poll_live(&self) -> Result<bool, String> {
+        if self.source.poll()? == 0 {
+            return Ok(false);
+        }
+
+        let old = self.overview();
+        let view = self.source.full_view(old.view.rows, old.view.cols);
+        let overview = Arc::new(self.source.render(&view, BASE_FFT_SIZE));
+
+        *self.overview.lock() = overview;
+        self.inner.lock().generation += 1;
+
+        Ok(true)
+    }
+
+    /// Лучший готовый водопад для области: пересчитанный, если он совпадает
+    /// с запрошенной областью, иначе обзорный.
+    pub fn tile(&self) -> Arc<WaterfallTile> {
+        let inner = self.inner.lock();
+
+        match (&inner.zoomed, &inner.wanted) {
+            (Some(z), Some(w)) if z.view.approx_eq(w) => z.clone(),
+            _ => self.overview(),
+        }
+    }
+
+    /// Счётчик готовых пересчётов (меняется, когда нужно обновить текстуру).
+    pub fn generation(&self) -> u64 {
+        self.inner.lock().generation
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.inner.lock().in_flight.is_some()
+    }
+
+    /// Запрашивает водопад для видимой области. Область, которую обзор уже
+    /// показывает в полном разрешении, не пересчитывается.
+    pub fn request(
+        &self,
+        view: WaterfallView,
+    ) {
+        let mut inner = self.inner.lock();
+
+        if inner.wanted.is_some_and(|w| w.approx_eq(&view)) {
+            return;
+        }
+
+        let overview = self.overview();
+        let needs_zoom = self.source.fft_size_for(&view) > overview.fft_size
+            || view.duration_s() < overview.view.duration_s() * 0.5;
+
+        inner.wanted = needs_zoom.then_some(view);
+        inner.generation += 1;
+
+        if needs_zoom && inner.in_flight.is_none() {
+            self.spawn(&mut inner, view);
+        }
+    }
+
+    fn spawn(
+        &self,
+        inner: &mut ZoomState,
+        view: WaterfallView,
+    ) {
+        inner.in_flight = Some(view);
+
+        let source = self.source.clone();
+        let state = self.inner.clone();
+
+        std::thread::spawn(move || {
+            let mut view = view;
+
+            loop {
+                let tile = source.render(&view, source.fft_size_for(&view));
+
+                let mut inner = state.lock();
+                inner.zoomed = Some(Arc::new(tile));
+                inner.generation += 1;
+
+                // Пока считали, область могла смениться — досчитываем
+                match inner.wanted {
+                    Some(w) if !w.approx_eq(&view) => {
+                        inner.in_flight = Some(w);
+                        view = w;
+                    }
+                    _ => {
+                        inner.in_flight = None;
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn prev_power_of_two(n: usize) -> usize {
+    match n {
+        0 => 0,
+        n => 1 << (usize::BITS - 1 - n.leading_zeros()),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::TAU, io::Write};
+
+    use glos_core::{GlosWriter, IqBlockExt, GLOS_HEADER_SIZE};
+    use glos_types::{GlosHeader, IqBlock, SdrType};
+
+    use super::*;
+
+    const RATE: u32 = 2_000_000;
+
+    /// Два тона на +100 и +103 кГц от центра 1602 МГц, 2^17 выборок.
+    fn two_tone_file(path: &Path) {
+        let header = GlosHeader::new(SdrType::HackRf, RATE, 1_602_000_000);
+        let mut writer = GlosWriter::new(File::create(path).unwrap(), header).unwrap();
+
+        let total = 1usize << 17;
+        let per_block = 8192;
+
+        for b in 0..total / per_block {
+            let mut data = Vec::with_capacity(per_block * 4);
+            for k in 0..per_block {
+                let t = (b * per_block + k) as f64 / RATE as f64;
+                let (mut i, mut q) = (0.0, 0.0);
+                for f in [100_000.0, 103_000.0] {
+                    i += (TAU * f * t).cos() * 0.4;
+                    q += (TAU * f * t).sin() * 0.4;
+                }
+                data.extend_from_slice(&((i * 32767.0) as i16).to_be_bytes());
+                data.extend_from_slice(&((q * 32767.0) as i16).to_be_bytes());
+            }
+            writer
+                .write_block(IqBlock::new(b as u64, per_block as u32, data))
+                .unwrap();
+        }
+
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_attach_picks_up_appended_blocks() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let full = dir.path().join("full.glos");
+        let live = dir.path().join("live.glos");
+        two_tone_file(&full);
+
+        // Запись «на ходу»: половина блоков и начало следующего
+        let bytes = std::fs::read(&full).unwrap();
+        let frame = (bytes.len() - GLOS_HEADER_SIZE) / 16;
+        let cut = GLOS_HEADER_SIZE + 8 * frame + frame / 2;
+        std::fs::write(&live, &bytes[..cut]).unwrap();
+
+        let wf = MultiResWaterfall::attach(&live, 4, 128).unwrap();
+        assert!(wf.source().is_live());
+        assert_eq!(wf.source().read_stats().corruption_events.len(), 0);
+        let before = wf.source().duration_s();
+
+        assert!(!wf.poll_live().unwrap());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&live)
+            .unwrap();
+        file.write_all(&bytes[cut..]).unwrap();
+
+        assert!(wf.poll_live().unwrap());
+        assert!((wf.source().duration_s() - 2.0 * before).abs() < 1e-9);
+        assert_eq!(wf.tile().view.rows, 4);
+    }
+
+    /// Число локальных максимумов выше `floor_db` в строке (ступеньки
+    /// одинаковых значений считаются одной точкой).
+    fn count_peaks(
+        row: &[f32],
+        floor_db: f32,
+    ) -> usize {
+        let mut steps = row.to_vec();
+        steps.dedup();
+
+        steps
+            .windows(3)
+            .filter(|w| w[1] > floor_db && w[1] > w[0] && w[1] > w[2])
+            .count()
+    }
+
+    #[test]
+    fn test_zoom_uses_larger_fft_and_resolves_tones() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("two_tone.glos");
+        two_tone_file(&path);
+
+        let source = FileSpectrumSource::open(&path).unwrap();
+        assert_eq!(source.freq_range_mhz(), (1601.0, 1603.0));
+
+        let full = source.full_view(4, 512);
+        assert_eq!(source.fft_size_for(&full), BASE_FFT_SIZE);
+
+        // 1602.095–1602.108 МГц на 64 столбца: ~200 Гц на столбец
+        let zoom = WaterfallView {
+            f_lo_mhz: 1602.095,
+            f_hi_mhz: 1602.108,
+            cols: 64,
+            ..full
+        };
+        let n = source.fft_size_for(&zoom);
+        assert_eq!(n, 16_384);
+
+        // На обзорном FFT (3.9 кГц на бин) тоны сливаются, на увеличенном
+        // — два отдельных пика
+        let coarse = source.render(&zoom, BASE_FFT_SIZE);
+        let fine = source.render(&zoom, n);
+        let max = |row: &[f32]| row.iter().copied().fold(f32::MIN, f32::max);
+
+        let row = &coarse.power_db[1];
+        assert_eq!(count_peaks(row, max(row) - 20.0), 1);
+        let row = &fine.power_db[1];
+        assert_eq!(count_peaks(row, max(row) - 20.0), 2);
+    }
+
+    #[test]
+    fn test_open_reports_corruption_events() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("two_tone.glos");
+        two_tone_file(&path);
+
+        // Портим данные третьего блока
+        let third = glos_core::GLOS_HEADER_SIZE + 2 * (8192 * 4 + glos_core::GLOS_BLOCK_OVERHEAD);
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[third + 1_000] ^= 0xFF;
+        std::fs::write(&path, bytes).unwrap();
+
+        let source = FileSpectrumSource::open(&path).unwrap();
+        let stats = source.read_stats();
+        assert_eq!(stats.blocks_ok, 15);
+        assert_eq!(stats.corruption_events[0].approx_offset, third as u64);
+        assert_eq!(
+            stats.corruption_events[0].kind,
+            glos_core::CorruptionKind::Crc
+        );
+    }
+
+    #[test]
+    fn test_multires_request_falls_back_to_overview() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("two_tone.glos");
+        two_tone_file(&path);
+
+        let wf = MultiResWaterfall::open(&path, 4, 128).unwrap();
+        let overview = wf.tile();
+        assert_eq!(overview.fft_size, BASE_FFT_SIZE);
+
+        // Вся полоса — пересчёт не нужен
+        wf.request(wf.source().full_view(4, 128));
+        assert!(!wf.is_busy());
+        assert_eq!(wf.tile().fft_size, BASE_FFT_SIZE);
+
+        let zoom = WaterfallView {
+            f_lo_mhz: 1602.09,
+            f_hi_mhz: 1602.11,
+            ..wf.source().full_view(4, 128)
+        };
+        wf.request(zoom);
+
+        while wf.is_busy() {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let tile = wf.tile();
+        assert!(tile.fft_size > BASE_FFT_SIZE);
+        assert!(tile.view.approx_eq(&zoom));
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{AppState, DEFAULT_OCCUPANCY_THRESHOLD_DB};
+use crate::panels::ActivePanel;
+
+/// Ключ состояния сессии в хранилище eframe.
+pub const SESSION_KEY: &str = "glos_session";
+
+/// Состояние сессии, переживающее перезапуск: источник данных, открытая
+/// панель и настройки просмотра. Размеры панелей egui сохраняет сам.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub active_panel: ActivePanel,
+    /// Генератор тестовых данных был запущен
+    pub mock_running: bool,
+    /// .glos-файл, открытый в водопаде
+    pub open_file: Option<PathBuf>,
+    /// Маркеры спектра (МГц)
+    pub markers_mhz: Vec<f64>,
+    /// Полоса измерения мощности в канале (МГц)
+    pub channel_band: Option<(f64, f64)>,
+    pub channel_threshold_db: f32,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SessionState {
+    /// Снимок текущей сессии.
+    pub fn capture(
+        active_panel: ActivePanel,
+        mock_running: bool,
+        state: &AppState,
+    ) -> Self {
+        Self {
+            active_panel,
+            mock_running,
+            open_file: state
+                .file_waterfall
+                .as_ref()
+                .map(|wf| wf.source().path().to_path_buf()),
+            markers_mhz: state.markers.freqs_mhz().to_vec(),
+            channel_band: state.channel_power.band(),
+            channel_threshold_db: state.channel_power.threshold_db(),
+        }
+    }
+
+    /// Восстанавливать нечего: сессия не отличается от новой.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Переносит маркеры и измерение канала в состояние. Источник данных
+    /// и панель восстанавливает приложение.
+    pub fn apply_view(
+        &self,
+        state: &mut AppState,
+    ) {
+        state.markers.clear();
+        for &f in &self.markers_mhz {
+            state.markers.place(f);
+        }
+
+        state
+            .channel_power
+            .set_threshold_db(self.channel_threshold_db);
+        match self.channel_band {
+            Some((lo, hi)) => state.channel_power.set_band(lo, hi),
+            None => state.channel_power.clear_band(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for SessionState {
+    fn default() -> Self {
+        Self {
+            active_panel: ActivePanel::default(),
+            mock_running: false,
+            open_file: None,
+            markers_mhz: Vec::new(),
+            channel_band: None,
+            channel_threshold_db: DEFAULT_OCCUPANCY_THRESHOLD_DB,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut state = AppState::default();
+        state.markers.place(1575.0);
+        state.markers.place(1576.5);
+        state.channel_power.set_band(1575.2, 1575.6);
+        state.channel_power.set_threshold_db(-80.0);
+
+        let session = SessionState::capture(ActivePanel::Signal, true, &state);
+        assert!(!session.is_empty());
+
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: SessionState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, session);
+
+        let mut fresh = AppState::default();
+        restored.apply_view(&mut fresh);
+        assert_eq!(fresh.markers.freqs_mhz(), [1575.0, 1576.5]);
+        assert_eq!(fresh.channel_power.band(), Some((1575.2, 1575.6)));
+        assert_eq!(fresh.channel_power.threshold_db(), -80.0);
+    }
+
+    #[test]
+    fn test_session_defaults_for_missing_fields() {
+        // Сессия от старой версии без части полей
+        let s: SessionState = serde_json::from_str(r#"{"active_panel":"Logs"}"#).unwrap();
+
+        assert_eq!(s.active_panel, ActivePanel::Logs);
+        assert_eq!(s.channel_threshold_db, DEFAULT_OCCUPANCY_THRESHOLD_DB);
+        assert!(SessionState::default().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::Satellite;
+
+/// Сколько эпох неба хранится (при шаге 1 с — два часа сессии).
+pub const SKY_HISTORY_LEN: usize = 7200;
+
+/// Минимальный шаг между сохранёнными эпохами: источник обновляет спутники
+/// чаще, а для time-lapse хватает секунды.
+pub const SKY_HISTORY_STEP_MS: i64 = 1000;
+
+/// Положение и C/N0 спутников в один момент времени.
+#[derive(Debug, Clone)]
+pub struct SkyEpoch {
+    pub time: DateTime<Utc>,
+    pub satellites: Vec<Satellite>,
+}
+
+/// История геометрии спутников для time-lapse полярной диаграммы.
+///
+/// Эпохи адресуются сквозным номером (`seq`), который не сдвигается, когда
+/// старые эпохи вытесняются: позиция ползунка остаётся на той же эпохе.
+#[derive(Debug, Clone)]
+pub struct SkyHistory {
+    epochs: VecDeque<SkyEpoch>,
+    capacity: usize,
+    step: TimeDelta,
+    /// Номер первой хранимой эпохи
+    first_seq: u64,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SkyHistory {
+    pub fn new(
+        capacity: usize,
+        step: TimeDelta,
+    ) -> Self {
+        Self {
+            epochs: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+            step,
+            first_seq: 0,
+        }
+    }
+
+    /// Запоминает эпоху, если с предыдущей прошло не меньше шага. Возвращает
+    /// `true`, если эпоха сохранена.
+    pub fn push(
+        &mut self,
+        time: DateTime<Utc>,
+        satellites: &[Satellite],
+    ) -> bool {
+        if self
+            .epochs
+            .back()
+            .is_some_and(|last| time - last.time < self.step)
+        {
+            return false;
+        }
+
+        if self.epochs.len() >= self.capacity {
+            self.epochs.pop_front();
+            self.first_seq += 1;
+        }
+
+        self.epochs.push_back(SkyEpoch {
+            time,
+            satellites: satellites.to_vec(),
+        });
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.epochs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epochs.is_empty()
+    }
+
+    /// Диапазон номеров хранимых эпох (включительно).
+    pub fn seq_range(&self) -> Option<(u64, u64)> {
+        let last = self.epochs.len().checked_sub(1)?;
+        Some((self.first_seq, self.first_seq + last as u64))
+    }
+
+    /// Эпоха по сквозному номеру; вытесненные номера прижимаются к первой.
+    pub fn get(
+        &self,
+        seq: u64,
+    ) -> Option<&SkyEpoch> {
+        let i = seq.saturating_sub(self.first_seq) as usize;
+        self.epochs.get(i.min(self.epochs.len().checked_sub(1)?))
+    }
+
+    pub fn latest(&self) -> Option<&SkyEpoch> {
+        self.epochs.back()
+    }
+
+    /// Эпохи `[from..=to]` по сквозным номерам.
+    pub fn range(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> impl Iterator<Item = &SkyEpoch> + '_ {
+        let start = from.saturating_sub(self.first_seq) as usize;
+        let end = (to.saturating_sub(self.first_seq) as usize)
+            .saturating_add(1)
+            .min(self.epochs.len());
+
+        self.epochs.range(start.min(end)..end)
+    }
+
+    /// Все хранимые эпохи, от старых к новым.
+    pub fn epochs(&self) -> impl Iterator<Item = &SkyEpoch> + '_ {
+        self.epochs.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.first_seq += self.epochs.len() as u64;
+        self.epochs.clear();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Общие реализации трейтов
+////////////////////////////////////////////////////////////////////////////////
+
+impl Default for SkyHistory {
+    fn default() -> Self {
+        Self::new(
+            SKY_HISTORY_LEN,
+            TimeDelta::milliseconds(SKY_HISTORY_STEP_MS),
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sat(elevation: f32) -> Satellite {
+        Satellite {
+            id: "R01".into(),
+            constellation: "ГЛОНАСС".into(),
+            cn0: 40.0,
+            elevation,
+            azimuth: 90.0,
+            doppler: 0.0,
+            used_in_fix: true,
+        }
+    }
+
+    #[test]
+    fn test_history_decimates_and_keeps_seq_on_eviction() {
+        let t0 = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut h = SkyHistory::new(3, TimeDelta::seconds(1));
+
+        // 4 обновления в секунду — сохраняется одно
+        for i in 0..4 {
+            let kept = h.push(t0 + TimeDelta::milliseconds(i * 250), &[sat(10.0)]);
+            assert_eq!(kept, i == 0);
+        }
+
+        for s in 1..5 {
+            assert!(h.push(t0 + TimeDelta::seconds(s), &[sat(10.0 + s as f32)]));
+        }
+
+        // Вместимость 3: эпохи 0 и 1 вытеснены, номера не сдвинулись
+        assert_eq!(h.seq_range(), Some((2, 4)));
+        assert_eq!(h.get(3).unwrap().satellites[0].elevation, 13.0);
+        assert_eq!(h.get(0).unwrap().satellites[0].elevation, 12.0);
+        assert_eq!(h.get(99).unwrap().satellites[0].elevation, 14.0);
+        assert_eq!(h.range(3, u64::MAX).count(), 2);
+
+        h.clear();
+        assert!(h.get(4).is_none());
+        assert!(h.push(t0, &[sat(1.0)]));
+        assert_eq!(h.seq_range(), Some((5, 5)));
+    }
+
+    #[test]
+    fn test_export_sky_gif_decimates_frames() {
+        use egui::Color32;
+
+        use crate::data::{DataExporter, SkyGifOptions};
+
+        let t0 = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut h = SkyHistory::new(100, TimeDelta::seconds(1));
+        for s in 0..25 {
+            h.push(t0 + TimeDelta::seconds(s), &[sat(s as f32 * 3.0)]);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sky.gif");
+        let options = SkyGifOptions {
+            size: 64,
+            frame_ms: 100,
+            max_frames: 10,
+        };
+
+        let frames =
+            DataExporter::export_sky_gif(h.epochs(), |_| Some(Color32::RED), &options, &path)
+                .unwrap();
+        // 25 эпох при пределе 10 кадров — каждая третья
+        assert_eq!(frames, 9);
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(std::fs::File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (64, 64));
+
+        let mut decoded = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            decoded += 1;
+        }
+        assert_eq!(decoded, frames);
+
+        assert!(DataExporter::export_sky_gif(
+            SkyHistory::default().epochs(),
+            |_| None,
+            &options,
+            &path
+        )
+        .is_err());
+    }
+}
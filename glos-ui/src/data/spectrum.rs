@@ -0,0 +1,127 @@
+use super::ConnectionStatus;
+
+/// Коэффициент усреднения по умолчанию (доля нового кадра).
+pub const DEFAULT_AVERAGE_ALPHA: f32 = 0.2;
+
+/// Параметры источника спектра. Смена любого из них (или числа бинов)
+/// сбрасывает следы.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectrumSource {
+    pub status: ConnectionStatus,
+    pub frequency_mhz: f32,
+    pub sample_rate_mhz: f32,
+}
+
+/// Накопленные следы спектра: экспоненциальное среднее, peak-hold и
+/// min-hold по входящим кадрам FFT.
+#[derive(Debug, Clone, Default)]
+pub struct SpectrumTraces {
+    pub average: Vec<f32>,
+    pub peak_hold: Vec<f32>,
+    pub min_hold: Vec<f32>,
+    /// Число кадров с последнего сброса
+    pub frames: u64,
+    source: Option<SpectrumSource>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SpectrumTraces {
+    /// Учитывает очередной кадр (дБ). `alpha` ∈ (0, 1] — вес нового кадра в
+    /// среднем: 1.0 = без усреднения. Если источник сменился, следы
+    /// начинаются заново с этого кадра.
+    pub fn update(
+        &mut self,
+        frame: &[f32],
+        alpha: f32,
+        source: SpectrumSource,
+    ) {
+        if self.source != Some(source) || self.average.len() != frame.len() {
+            self.reset();
+            self.source = Some(source);
+        }
+
+        if self.frames == 0 {
+            self.average = frame.to_vec();
+            self.peak_hold = frame.to_vec();
+            self.min_hold = frame.to_vec();
+        } else {
+            let alpha = alpha.clamp(0.01, 1.0);
+
+            for (i, &p) in frame.iter().enumerate() {
+                self.average[i] += alpha * (p - self.average[i]);
+                self.peak_hold[i] = self.peak_hold[i].max(p);
+                self.min_hold[i] = self.min_hold[i].min(p);
+            }
+        }
+
+        self.frames += 1;
+    }
+
+    /// Сбрасывает все следы; следующий кадр станет начальным.
+    pub fn reset(&mut self) {
+        self.average.clear();
+        self.peak_hold.clear();
+        self.min_hold.clear();
+        self.frames = 0;
+        self.source = None;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames == 0
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn source(frequency_mhz: f32) -> SpectrumSource {
+        SpectrumSource {
+            status: ConnectionStatus::Mock,
+            frequency_mhz,
+            sample_rate_mhz: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_traces_average_and_holds() {
+        let mut t = SpectrumTraces::default();
+
+        t.update(&[-80.0, -60.0], 0.5, source(1602.0));
+        t.update(&[-60.0, -70.0], 0.5, source(1602.0));
+        t.update(&[-70.0, -90.0], 0.5, source(1602.0));
+
+        assert_eq!(t.frames, 3);
+        assert_relative_eq!(t.average[0], -70.0);
+        assert_relative_eq!(t.average[1], -77.5);
+        assert_eq!(t.peak_hold, vec![-60.0, -60.0]);
+        assert_eq!(t.min_hold, vec![-80.0, -90.0]);
+    }
+
+    #[test]
+    fn test_traces_reset_on_source_change() {
+        let mut t = SpectrumTraces::default();
+
+        t.update(&[-50.0, -50.0], 0.2, source(1602.0));
+        t.update(&[-90.0, -90.0], 0.2, source(1602.0));
+        t.update(&[-70.0, -75.0], 0.2, source(1575.42));
+
+        assert_eq!(t.frames, 1);
+        assert_eq!(t.average, vec![-70.0, -75.0]);
+        assert_eq!(t.peak_hold, vec![-70.0, -75.0]);
+
+        // Другое число бинов — тоже новый источник
+        t.update(&[-40.0; 4], 0.2, source(1575.42));
+        assert_eq!(t.frames, 1);
+        assert_eq!(t.min_hold.len(), 4);
+    }
+}
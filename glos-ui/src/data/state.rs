@@ -1,7 +1,14 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
+use serde::Serialize;
+
+use super::{
+    AnnotationStore, ChannelPowerMeter, IqCapture, IqStats, MultiResWaterfall, SkyHistory,
+    SpectrumMarkers, SpectrumSource, SpectrumTraces, IQ_STATS_MAX_SAMPLES,
+};
+use crate::panels::{Constellation, UiSettings};
 
 /// Статус подключения источника данных
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,14 +51,27 @@ pub struct Satellite {
     pub used_in_fix: bool,
 }
 
+/// Сводка по спутникам одного созвездия.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConstellationStats {
+    pub constellation: String,
+    pub count: usize,
+    pub used_in_fix: usize,
+    pub mean_cn0: f32,       // dBHz
+    pub max_cn0: f32,        // dBHz
+    pub mean_elevation: f32, // градусы
+}
+
 /// Спектральные данные
 #[derive(Debug, Clone)]
 pub struct SignalData {
     pub timestamp: DateTime<Utc>,
     pub frequency_mhz: f32,
     pub sample_rate_mhz: f32,
-    pub fft_data: Vec<f32>,            // Мощность в dB
-    pub waterfall: VecDeque<Vec<f32>>, // История для waterfall
+    pub fft_data: Vec<f32>,             // Мощность в dB
+    pub waterfall: VecDeque<Vec<f32>>,  // История для waterfall
+    pub waterfall_times: VecDeque<u64>, // Метка (Unix нс) каждой строки waterfall
+    pub traces: SpectrumTraces,         // Среднее, peak-hold, min-hold
 }
 
 impl SignalData {
@@ -66,17 +86,92 @@ impl SignalData {
             sample_rate_mhz: sample_rate,
             fft_data: vec![0.0; fft_size],
             waterfall: VecDeque::with_capacity(256),
+            waterfall_times: VecDeque::with_capacity(256),
+            traces: SpectrumTraces::default(),
         }
     }
 
+    /// Добавляет строку waterfall; `timestamp_ns` — метка исходного блока,
+    /// чтобы аннотации совпадали со временем записи.
     pub fn push_waterfall(
         &mut self,
         data: Vec<f32>,
+        timestamp_ns: u64,
     ) {
         if self.waterfall.len() >= 256 {
             self.waterfall.pop_front();
+            self.waterfall_times.pop_front();
         }
         self.waterfall.push_back(data);
+        self.waterfall_times.push_back(timestamp_ns);
+    }
+
+    /// Частота (МГц) для дробного индекса FFT-бина.
+    pub fn bin_to_mhz(
+        &self,
+        bin: f64,
+    ) -> f64 {
+        let len = self.fft_data.len().max(1) as f64;
+        (bin / len - 0.5) * self.sample_rate_mhz as f64 + self.frequency_mhz as f64
+    }
+
+    /// Дробный индекс FFT-бина для частоты (МГц).
+    pub fn mhz_to_bin(
+        &self,
+        mhz: f64,
+    ) -> f64 {
+        let len = self.fft_data.len().max(1) as f64;
+        ((mhz - self.frequency_mhz as f64) / self.sample_rate_mhz as f64 + 0.5) * len
+    }
+
+    /// Время (Unix нс) для дробного номера строки waterfall.
+    pub fn frame_to_ns(
+        &self,
+        frame: f64,
+    ) -> Option<u64> {
+        let times = &self.waterfall_times;
+        let last = times.len().checked_sub(1)?;
+        let frame = frame.clamp(0.0, last as f64);
+        let i = frame.floor() as usize;
+        let t0 = times[i];
+        let t1 = times[(i + 1).min(last)];
+
+        Some(t0 + ((t1 - t0) as f64 * frame.fract()) as u64)
+    }
+
+    /// Дробный номер строки waterfall для времени (Unix нс), с отсечкой по
+    /// краям истории.
+    pub fn ns_to_frame(
+        &self,
+        ns: u64,
+    ) -> Option<f64> {
+        let times = &self.waterfall_times;
+        let last = times.len().checked_sub(1)?;
+        let i = times.partition_point(|t| *t <= ns);
+
+        if i == 0 {
+            return Some(0.0);
+        }
+        if i > last {
+            return Some(last as f64);
+        }
+
+        let (t0, t1) = (times[i - 1], times[i]);
+        let frac = if t1 > t0 {
+            (ns - t0) as f64 / (t1 - t0) as f64
+        } else {
+            0.0
+        };
+
+        Some((i - 1) as f64 + frac)
+    }
+
+    /// Интервал времени (Unix нс), покрытый историей waterfall.
+    pub fn waterfall_span_ns(&self) -> Option<(u64, u64)> {
+        Some((
+            *self.waterfall_times.front()?,
+            *self.waterfall_times.back()?,
+        ))
     }
 }
 
@@ -117,9 +212,33 @@ pub struct AppState {
 
     // История CN0 для графиков
     pub cn0_history: VecDeque<(DateTime<Utc>, f32)>,
+    // История положений спутников для time-lapse полярной диаграммы
+    pub sky_history: SkyHistory,
 
     // Логи
     pub log_messages: VecDeque<(DateTime<Utc>, String)>,
+
+    // Настройки интерфейса (палитры, масштаб шрифта и т.д.)
+    pub settings: UiSettings,
+
+    // Аннотации
+    pub annotations: AnnotationStore,
+    pub annotations_path: PathBuf,
+    /// Аннотация, к которой нужно перейти на waterfall
+    pub annotation_focus: Option<u64>,
+
+    // Измерительные маркеры спектра
+    pub markers: SpectrumMarkers,
+    // Мощность и занятость в выбранной полосе
+    pub channel_power: ChannelPowerMeter,
+
+    // Водопад открытого .glos-файла
+    pub file_waterfall: Option<Arc<MultiResWaterfall>>,
+
+    // Последние IQ-выборки живого источника и статистика по активному
+    // источнику (см. [`AppState::refresh_iq_stats`])
+    pub live_iq: Option<IqCapture>,
+    pub iq_stats: Option<IqStats>,
 }
 
 impl Default for AppState {
@@ -136,8 +255,63 @@ impl Default for AppState {
             hdop: 1.0,
             pdop: 1.5,
             cn0_history: VecDeque::with_capacity(300),
+            sky_history: SkyHistory::default(),
             log_messages: VecDeque::with_capacity(1000),
+            settings: UiSettings::default(),
+            annotations: AnnotationStore::new(),
+            annotations_path: PathBuf::new(),
+            annotation_focus: None,
+            markers: SpectrumMarkers::default(),
+            channel_power: ChannelPowerMeter::default(),
+            file_waterfall: None,
+            live_iq: None,
+            iq_stats: None,
+        }
+    }
+}
+
+impl ConstellationStats {
+    /// Группирует спутники по созвездиям: известные — в порядке
+    /// [`Constellation::ALL`], остальные — по первому появлению.
+    pub fn aggregate<'a>(satellites: impl IntoIterator<Item = &'a Satellite>) -> Vec<Self> {
+        let mut stats: Vec<ConstellationStats> = Vec::new();
+
+        for sat in satellites {
+            let i = match stats
+                .iter()
+                .position(|s| s.constellation == sat.constellation)
+            {
+                Some(i) => i,
+                None => {
+                    stats.push(ConstellationStats {
+                        constellation: sat.constellation.clone(),
+                        max_cn0: f32::MIN,
+                        ..Default::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+
+            let s = &mut stats[i];
+            s.count += 1;
+            s.used_in_fix += sat.used_in_fix as usize;
+            s.mean_cn0 += sat.cn0;
+            s.max_cn0 = s.max_cn0.max(sat.cn0);
+            s.mean_elevation += sat.elevation;
+        }
+
+        for s in &mut stats {
+            s.mean_cn0 /= s.count as f32;
+            s.mean_elevation /= s.count as f32;
         }
+
+        stats.sort_by_key(|s| {
+            Constellation::from_name(&s.constellation)
+                .and_then(|c| Constellation::ALL.iter().position(|a| *a == c))
+                .unwrap_or(Constellation::ALL.len())
+        });
+
+        stats
     }
 }
 
@@ -156,18 +330,172 @@ impl AppState {
         self.log_messages.push_back((Utc::now(), message));
     }
 
+    /// Новая эпоха спутников: текущий список и история для time-lapse.
+    pub fn set_satellites(
+        &mut self,
+        satellites: Vec<Satellite>,
+    ) {
+        self.sky_history.push(Utc::now(), &satellites);
+        self.satellites = satellites;
+    }
+
+    /// Спутники включённых в настройках созвездий.
+    pub fn visible_satellites(&self) -> impl Iterator<Item = &Satellite> + '_ {
+        self.satellites
+            .iter()
+            .filter(|s| self.settings.constellations.allows(&s.constellation))
+    }
+
     pub fn avg_cn0(&self) -> f32 {
-        if self.satellites.is_empty() {
+        let count = self.satellite_count();
+        if count == 0 {
             return 0.0;
         }
-        self.satellites.iter().map(|s| s.cn0).sum::<f32>() / self.satellites.len() as f32
+        self.visible_satellites().map(|s| s.cn0).sum::<f32>() / count as f32
     }
 
     pub fn satellite_count(&self) -> usize {
-        self.satellites.len()
+        self.visible_satellites().count()
     }
 
     pub fn used_satellites(&self) -> usize {
-        self.satellites.iter().filter(|s| s.used_in_fix).count()
+        self.visible_satellites().filter(|s| s.used_in_fix).count()
+    }
+
+    /// Сводка по созвездиям видимых спутников.
+    pub fn constellation_stats(&self) -> Vec<ConstellationStats> {
+        ConstellationStats::aggregate(self.visible_satellites())
+    }
+
+    /// Принимает новый кадр спектра: обновляет текущий FFT, следы
+    /// усреднения/удержания, waterfall и измерение канала. `timestamp_ns` —
+    /// метка блока, по которому посчитан кадр.
+    pub fn push_spectrum(
+        &mut self,
+        fft_data: Vec<f32>,
+        timestamp_ns: u64,
+    ) {
+        let source = SpectrumSource {
+            status: self.status,
+            frequency_mhz: self.signal_data.frequency_mhz,
+            sample_rate_mhz: self.signal_data.sample_rate_mhz,
+        };
+        let alpha = self.settings.fft_average_alpha;
+
+        let sd = &mut self.signal_data;
+        sd.traces.update(&fft_data, alpha, source);
+        sd.fft_data = fft_data.clone();
+        sd.push_waterfall(fft_data, timestamp_ns);
+        sd.timestamp = Utc::now();
+
+        self.channel_power.update(&self.signal_data);
+    }
+
+    /// Принимает очередной кадр IQ живого источника.
+    pub fn push_iq(
+        &mut self,
+        capture: IqCapture,
+    ) {
+        self.live_iq = Some(capture);
+    }
+
+    /// Пересчитывает статистику IQ по активному источнику: открытый файл
+    /// важнее живого потока. Без данных статистика сбрасывается.
+    pub fn refresh_iq_stats(&mut self) {
+        self.iq_stats = if let Some(wf) = &self.file_waterfall {
+            let source = wf.source();
+            Some(IqStats::compute(
+                source.name(),
+                &source.spread_samples(IQ_STATS_MAX_SAMPLES),
+                source.iq_format(),
+            ))
+        } else {
+            self.live_iq
+                .as_ref()
+                .map(|c| IqStats::compute(self.status.as_str(), &c.samples, c.format))
+        };
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sat(
+        constellation: &str,
+        cn0: f32,
+        elevation: f32,
+        used_in_fix: bool,
+    ) -> Satellite {
+        Satellite {
+            id: format!("{constellation}-{cn0}"),
+            constellation: constellation.into(),
+            cn0,
+            elevation,
+            azimuth: 0.0,
+            doppler: 0.0,
+            used_in_fix,
+        }
+    }
+
+    #[test]
+    fn test_constellation_stats_aggregate() {
+        let sats = [
+            sat("Галилео", 40.0, 30.0, true),
+            sat("GPS", 45.0, 60.0, true),
+            sat("SBAS", 35.0, 20.0, false),
+            sat("GPS", 35.0, 20.0, false),
+            sat("GPS", 40.0, 10.0, true),
+        ];
+
+        let stats = ConstellationStats::aggregate(&sats);
+        let names: Vec<&str> = stats.iter().map(|s| s.constellation.as_str()).collect();
+        assert_eq!(names, ["GPS", "Галилео", "SBAS"]);
+
+        let gps = &stats[0];
+        assert_eq!((gps.count, gps.used_in_fix), (3, 2));
+        assert!((gps.mean_cn0 - 40.0).abs() < 1e-5);
+        assert_eq!(gps.max_cn0, 45.0);
+        assert!((gps.mean_elevation - 30.0).abs() < 1e-5);
+
+        assert_eq!(stats[1].count, 1);
+        assert_eq!(stats[1].max_cn0, 40.0);
+        assert!(ConstellationStats::aggregate(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_constellation_stats_respect_enabled_constellations() {
+        let mut state = AppState::default();
+        state.set_satellites(vec![
+            sat("GPS", 45.0, 60.0, true),
+            sat("ГЛОНАСС", 38.0, 40.0, true),
+            sat("ГЛОНАСС", 42.0, 50.0, false),
+        ]);
+        state.settings.constellations.gps = false;
+
+        let stats = state.constellation_stats();
+
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].constellation, "ГЛОНАСС");
+        assert_eq!(stats[0].count, state.satellite_count());
+        assert_eq!(stats[0].used_in_fix, state.used_satellites());
+        assert!((stats[0].mean_cn0 - state.avg_cn0()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_waterfall_times_follow_block_timestamps() {
+        let mut state = AppState::default();
+        for i in 0..3u64 {
+            state.push_spectrum(vec![0.0; 8], 1_000_000_000 + i * 50_000_000);
+        }
+
+        let sd = &state.signal_data;
+        assert_eq!(sd.waterfall_span_ns(), Some((1_000_000_000, 1_100_000_000)));
+        assert_eq!(sd.frame_to_ns(1.5), Some(1_075_000_000));
+        assert_eq!(sd.ns_to_frame(1_025_000_000), Some(0.5));
     }
 }
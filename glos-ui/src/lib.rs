@@ -7,6 +7,7 @@ pub mod app;
 pub mod data;
 pub mod panels;
 pub mod theme;
+pub mod view;
 
 pub use app::GlosApp;
 pub use data::{AppState, MockDataGenerator};
@@ -17,7 +18,9 @@ pub fn run() -> eframe::Result<()> {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1400.0, 900.0])
             .with_min_inner_size([800.0, 600.0])
-            .with_title("ГЛОС - Инструмент анализа GNSS/SDR"),
+            .with_title("ГЛОС - Инструмент анализа GNSS/SDR")
+            // Имя каталога хранилища eframe (сессия, раскладка окон)
+            .with_app_id("glos-ui"),
         ..Default::default()
     };
 
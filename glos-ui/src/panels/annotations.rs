@@ -0,0 +1,160 @@
+use std::{path::PathBuf, sync::Arc};
+
+use parking_lot::RwLock;
+
+use crate::{
+    data::{AnnotationStore, AppState, ANNOTATION_EXPORT_MARGIN},
+    view::AnnotationsView,
+};
+
+pub struct AnnotationsPanel;
+
+enum Action {
+    Focus(u64),
+    Remove(u64),
+    Export(u64),
+    Save,
+    Load,
+}
+
+impl AnnotationsPanel {
+    /// Рисует список аннотаций. Возвращает `true`, если пользователь выбрал
+    /// аннотацию для перехода (нужно открыть waterfall).
+    pub fn render(
+        ui: &mut egui::Ui,
+        state: &Arc<RwLock<AppState>>,
+    ) -> bool {
+        let mut action = None;
+        let view = AnnotationsView::from_state(&state.read());
+        let mut path_text = view.path.clone();
+
+        ui.heading("🏷 Аннотации");
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Файл:");
+            ui.add(egui::TextEdit::singleline(&mut path_text).desired_width(360.0));
+
+            if ui.button("💾 Сохранить").clicked() {
+                action = Some(Action::Save);
+            }
+            if ui.button("📂 Загрузить").clicked() {
+                action = Some(Action::Load);
+            }
+            if view.dirty {
+                ui.colored_label(view.dirty_color, "● не сохранено");
+            }
+        });
+
+        ui.label(
+            egui::RichText::new("Выделите область на водопаде мышью, чтобы создать аннотацию")
+                .weak(),
+        );
+
+        ui.add_space(10.0);
+
+        if view.rows.is_empty() {
+            ui.label("Аннотаций нет");
+        }
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("annotations_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for row in &view.rows {
+                            ui.add(
+                                egui::Label::new(egui::RichText::new(&row.label).strong())
+                                    .sense(egui::Sense::click()),
+                            )
+                            .context_menu(|ui| {
+                                let secs = ANNOTATION_EXPORT_MARGIN.as_secs();
+                                if ui.button(format!("✂ Экспорт ±{secs} с")).clicked() {
+                                    action = Some(Action::Export(row.id));
+                                    ui.close();
+                                }
+                            });
+                            ui.label(&row.time);
+                            ui.label(&row.band);
+                            if ui.button("➡ Перейти").clicked() {
+                                action = Some(Action::Focus(row.id));
+                            }
+                            if ui.button("🗑").clicked() {
+                                action = Some(Action::Remove(row.id));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if action.is_none() && path_text == view.path {
+            return false;
+        }
+
+        let mut state = state.write();
+        state.annotations_path = PathBuf::from(path_text);
+
+        match action {
+            Some(Action::Focus(id)) => {
+                state.annotation_focus = Some(id);
+                return true;
+            }
+            Some(Action::Remove(id)) => {
+                if let Some(a) = state.annotations.remove(id) {
+                    state.add_log(format!("Annotation '{}' removed", a.label));
+                }
+            }
+            Some(Action::Export(id)) => {
+                let Some(recording) = AnnotationStore::recording_path(&state.annotations_path)
+                else {
+                    state
+                        .add_log("Export: annotations file is not a recording sidecar".to_string());
+                    return false;
+                };
+                let out = AnnotationStore::export_path(&recording, id);
+                let result = state.annotations.export_around(
+                    &recording,
+                    id,
+                    ANNOTATION_EXPORT_MARGIN,
+                    ANNOTATION_EXPORT_MARGIN,
+                    &out,
+                );
+                let msg = match result {
+                    Ok(export) => format!(
+                        "Exported {:.3} s ({} annotations) to {}",
+                        (export.cut.end_ns - export.cut.start_ns) as f64 / 1e9,
+                        export.annotations,
+                        out.display()
+                    ),
+                    Err(e) => format!("Error exporting annotation: {e}"),
+                };
+                state.add_log(msg);
+            }
+            Some(Action::Save) => {
+                let path = state.annotations_path.clone();
+                let result = state.annotations.save(&path);
+                let msg = match result {
+                    Ok(()) => format!("Annotations saved to {}", path.display()),
+                    Err(e) => format!("Error saving annotations: {e}"),
+                };
+                state.add_log(msg);
+            }
+            Some(Action::Load) => {
+                let path = state.annotations_path.clone();
+                match AnnotationStore::load(&path) {
+                    Ok(store) => {
+                        let n = store.len();
+                        state.annotations = store;
+                        state.add_log(format!("Loaded {n} annotations from {}", path.display()));
+                    }
+                    Err(e) => state.add_log(format!("Error loading annotations: {e}")),
+                }
+            }
+            None => {}
+        }
+
+        false
+    }
+}
@@ -3,7 +3,10 @@ use std::sync::Arc;
 use egui_plot::{Line, Plot, PlotPoints};
 use parking_lot::RwLock;
 
-use crate::AppState;
+use crate::{
+    view::{DashboardView, MetricCard},
+    AppState,
+};
 
 pub struct Dashboard;
 
@@ -12,64 +15,41 @@ impl Dashboard {
         ui: &mut egui::Ui,
         state: &Arc<RwLock<AppState>>,
     ) {
-        let state = state.read();
+        let view = DashboardView::from_state(&state.read());
 
         ui.heading("Панель мониторинга");
         ui.separator();
 
         // Верхняя строка - основные метрики
         ui.horizontal(|ui| {
-            Self::metric_card(ui, "Спутники", &format!("{}", state.satellite_count()), "🛰");
-            Self::metric_card(
-                ui,
-                "Используются в решении",
-                &format!("{}", state.used_satellites()),
-                "✓",
-            );
-            Self::metric_card(
-                ui,
-                "Средний CN0",
-                &format!("{:.1} дБГц", state.avg_cn0()),
-                "📡",
-            );
-            Self::metric_card(
-                ui,
-                "Гор. точность (HDOP)",
-                &format!("{:.2}", state.hdop),
-                "🎯",
-            );
+            for card in &view.summary {
+                Self::metric_card(ui, card);
+            }
         });
 
         ui.add_space(10.0);
 
         // Вторая строка - положение и метрики
         ui.horizontal(|ui| {
-            Self::metric_card(
-                ui,
-                "Положение",
-                &format!("{:.4}°N\n{:.4}°E", state.position_lat, state.position_lon),
-                "🌍",
-            );
-            Self::metric_card(ui, "Высота", &format!("{:.1} м", state.altitude), "⛰");
-            Self::metric_card(ui, "Скорость", &format!("{:.2} м/с", state.velocity), "💨");
-            Self::metric_card(
-                ui,
-                "Загрузка ЦП",
-                &format!("{:.1}%", state.metrics.cpu_usage),
-                "💻",
-            );
+            for card in &view.position {
+                Self::metric_card(ui, card);
+            }
+        });
+
+        ui.add_space(10.0);
+
+        // Третья строка — по созвездиям
+        ui.horizontal_wrapped(|ui| {
+            for card in &view.constellations {
+                Self::metric_card(ui, card);
+            }
         });
 
         ui.add_space(20.0);
 
         // График CN0 во времени
         ui.heading("История CN0");
-        let cn0_history: PlotPoints = state
-            .cn0_history
-            .iter()
-            .enumerate()
-            .map(|(i, (_, cn0))| [i as f64, *cn0 as f64])
-            .collect();
+        let cn0_history = PlotPoints::from(view.cn0_history);
 
         Plot::new("cn0_plot")
             .height(200.0)
@@ -80,7 +60,7 @@ impl Dashboard {
             .show(ui, |plot_ui| {
                 plot_ui.line(
                     Line::new("CN0", cn0_history)
-                        .color(egui::Color32::from_rgb(100, 200, 100))
+                        .color(view.cn0_color)
                         .width(2.0),
                 );
             });
@@ -93,26 +73,17 @@ impl Dashboard {
                 ui.vertical(|ui| {
                     ui.label("Системные метрики");
                     ui.separator();
-                    ui.label(format!(
-                        "Полоса пропускания: {:.1} МГц",
-                        state.metrics.bandwidth_mhz
-                    ));
-                    ui.label(format!("Буфер: {:.1}%", state.metrics.buffer_usage));
-                    ui.label(format!("Пакеты/с: {}", state.metrics.packets_per_sec));
+                    for line in &view.system {
+                        ui.label(line);
+                    }
                 });
             });
 
             ui.group(|ui| {
                 ui.vertical(|ui| {
-                    ui.label(format!(
-                        "Центральная частота: {:.2} МГц",
-                        state.signal_data.frequency_mhz
-                    ));
-                    ui.label(format!(
-                        "Частота дискретизации: {:.1} МГц",
-                        state.signal_data.sample_rate_mhz
-                    ));
-                    ui.label(format!("Размер FFT: {}", state.signal_data.fft_data.len()));
+                    for line in &view.signal {
+                        ui.label(line);
+                    }
                 });
             });
         });
@@ -120,16 +91,14 @@ impl Dashboard {
 
     pub fn metric_card(
         ui: &mut egui::Ui,
-        label: &str,
-        value: &str,
-        icon: &str,
+        card: &MetricCard,
     ) {
         ui.group(|ui| {
             ui.set_min_width(120.0);
             ui.vertical_centered(|ui| {
-                ui.label(egui::RichText::new(icon).size(24.0));
-                ui.label(egui::RichText::new(label).small());
-                ui.label(egui::RichText::new(value).strong());
+                ui.label(egui::RichText::new(card.icon).size(24.0));
+                ui.label(egui::RichText::new(&card.label).small());
+                ui.label(egui::RichText::new(&card.value).strong());
             });
         });
     }
@@ -0,0 +1,147 @@
+use std::{path::PathBuf, sync::Arc};
+
+use egui_plot::{Bar, BarChart, Legend, Plot};
+use parking_lot::RwLock;
+
+use crate::{
+    data::{AppState, DataExporter},
+    view::{IqComponentView, IqStatsView},
+};
+
+pub struct IqStatsPanel;
+
+impl IqStatsPanel {
+    pub fn render(
+        ui: &mut egui::Ui,
+        state_lock: &Arc<RwLock<AppState>>,
+    ) {
+        ui.heading("📈 Статистика IQ");
+        ui.separator();
+
+        Self::refresh(ui, state_lock);
+
+        let csv_id = ui.id().with("iq_stats_csv");
+        let mut csv_path: String =
+            ui.data_mut(|d| d.get_temp(csv_id).unwrap_or_else(|| "iq_stats.csv".into()));
+
+        let Some(view) = IqStatsView::from_state(&state_lock.read()) else {
+            ui.label(
+                egui::RichText::new("Нет IQ-данных: запустите генератор или откройте запись")
+                    .weak(),
+            );
+            return;
+        };
+
+        let mut export = false;
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(&view.header).strong());
+            ui.separator();
+            ui.label("CSV:");
+            ui.add(egui::TextEdit::singleline(&mut csv_path).desired_width(220.0));
+            export = ui.button("💾 Экспорт").clicked();
+        });
+        ui.data_mut(|d| d.insert_temp(csv_id, csv_path.clone()));
+
+        if export {
+            Self::export_csv(state_lock, PathBuf::from(&csv_path));
+        }
+
+        ui.add_space(5.0);
+
+        Plot::new("iq_histogram_plot")
+            .height(260.0)
+            .legend(Legend::default())
+            .show_axes([true, true])
+            .show_grid([true, true])
+            .x_axis_label("Значение (доля шкалы)")
+            .y_axis_label("Выборок")
+            .show(ui, |plot_ui| {
+                for c in &view.components {
+                    let bars = c
+                        .bars
+                        .iter()
+                        .map(|&[x, n]| Bar::new(x, n).width(view.bar_width))
+                        .collect();
+                    plot_ui.bar_chart(BarChart::new(c.name, bars).color(c.color));
+                }
+            });
+
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            for c in &view.components {
+                ui.group(|ui| {
+                    ui.vertical(|ui| {
+                        Self::component_summary(ui, c);
+                    });
+                });
+            }
+        });
+    }
+
+    fn export_csv(
+        state_lock: &Arc<RwLock<AppState>>,
+        path: PathBuf,
+    ) {
+        let mut state = state_lock.write();
+        let Some(stats) = &state.iq_stats else {
+            return;
+        };
+
+        let msg = match DataExporter::export_iq_stats_csv(stats, &path) {
+            Ok(()) => format!("IQ statistics exported to {}", path.display()),
+            Err(e) => format!("Error exporting IQ statistics: {e}"),
+        };
+        state.add_log(msg);
+    }
+
+    /// Пересчитывает статистику не чаще, чем задано в настройках
+    /// (`update_rate_ms`), и планирует следующую перерисовку.
+    fn refresh(
+        ui: &egui::Ui,
+        state_lock: &Arc<RwLock<AppState>>,
+    ) {
+        let refreshed_id = ui.id().with("iq_stats_refreshed");
+        let now = ui.input(|i| i.time);
+        let last: f64 = ui.data(|d| d.get_temp(refreshed_id).unwrap_or(f64::MIN));
+        let rate_s = state_lock.read().settings.update_rate_ms as f64 / 1e3;
+
+        if now - last >= rate_s {
+            ui.data_mut(|d| d.insert_temp(refreshed_id, now));
+            state_lock.write().refresh_iq_stats();
+        }
+
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_secs_f64(rate_s));
+    }
+
+    fn component_summary(
+        ui: &mut egui::Ui,
+        c: &IqComponentView,
+    ) {
+        ui.label(egui::RichText::new(c.name).strong());
+        for line in &c.summary {
+            ui.monospace(line);
+        }
+
+        if c.clipping_warn {
+            ui.colored_label(ui.visuals().warn_fg_color, &c.clipping);
+        } else {
+            ui.monospace(&c.clipping);
+        }
+
+        if let Some(used) = &c.used_bits {
+            ui.monospace(used);
+            for row in &c.bit_occupancy {
+                ui.horizontal(|ui| {
+                    ui.monospace(&row.label);
+                    ui.add(
+                        egui::ProgressBar::new(row.fraction)
+                            .desired_width(160.0)
+                            .text(&row.text),
+                    );
+                });
+            }
+        }
+    }
+}
@@ -1,11 +1,29 @@
+pub mod annotations;
 pub mod dashboard;
+pub mod iq_stats;
 pub mod logs;
 pub mod satellites;
 pub mod settings;
 pub mod signals;
 
+pub use annotations::*;
 pub use dashboard::*;
+pub use iq_stats::*;
 pub use logs::*;
 pub use satellites::*;
+use serde::{Deserialize, Serialize};
 pub use settings::*;
 pub use signals::*;
+
+/// Панель в центральной области окна.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivePanel {
+    #[default]
+    Dashboard,
+    Signal,
+    IqStats,
+    Satellites,
+    Annotations,
+    Logs,
+    Settings,
+}
@@ -1,10 +1,18 @@
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use egui::Color32;
 use egui_plot::{Plot, Points};
 use parking_lot::RwLock;
 
-use crate::{data::Satellite, AppState};
+use crate::{
+    data::{DataExporter, Satellite, SkyGifOptions},
+    panels::{Constellation, MarkerShape},
+    view::{point_color, LegendEntry, SatellitesView, SkyPlotView},
+    AppState,
+};
 
 #[derive(Clone, Copy, PartialEq)]
 enum SortColumn {
@@ -18,6 +26,21 @@ enum SortColumn {
 
 pub struct SatellitesPanel;
 
+/// Ползунок time-lapse полярной диаграммы (хранится в памяти egui).
+#[derive(Clone)]
+struct SkyTimelapse {
+    /// Эпоха на ползунке (None — последняя, диаграмма следует за данными)
+    seq: Option<u64>,
+    playing: bool,
+    /// Эпох в секунду при воспроизведении и в GIF
+    speed: f32,
+    /// Цвет точки по C/N0 вместо созвездия
+    color_by_cn0: bool,
+    gif_path: String,
+    /// Время egui (с) последнего шага воспроизведения
+    last_step: f64,
+}
+
 /// Интерактивная таблица с фильтрацией и сортировкой.
 pub struct InteractiveSatelliteTable {
     sort_by: SortColumn,
@@ -29,40 +52,51 @@ pub struct InteractiveSatelliteTable {
 impl SatellitesPanel {
     pub fn render(
         ui: &mut egui::Ui,
-        state: &Arc<RwLock<AppState>>,
+        state_lock: &Arc<RwLock<AppState>>,
     ) {
-        let state = state.read();
+        let timelapse_id = ui.id().with("sky_timelapse");
+        let mut timelapse: SkyTimelapse =
+            ui.data_mut(|d| d.get_temp(timelapse_id).unwrap_or_default());
+        let mut export = None;
 
-        ui.heading("🛰 Спутники");
-        ui.separator();
+        {
+            let state = state_lock.read();
+            let view = SatellitesView::from_state(&state);
 
-        ui.label(format!(
-            "Всего: {} | Используются в решении: {}",
-            state.satellite_count(),
-            state.used_satellites(),
-        ));
+            ui.heading("🛰 Спутники");
+            ui.separator();
 
-        ui.add_space(10.0);
+            ui.label(&view.summary);
 
-        // Sky Plot (полярная диаграмма)
-        ui.horizontal(|ui| {
-            ui.vertical(|ui| {
-                ui.set_width(ui.available_width() * 0.6);
-                Self::render_table(ui, &state);
-            });
+            ui.add_space(10.0);
 
-            ui.separator();
+            // Sky Plot (полярная диаграмма)
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(ui.available_width() * 0.6);
+                    Self::render_table(ui, &view);
+                });
+
+                ui.separator();
 
-            // Sky plot
-            ui.vertical(|ui| {
-                Self::render_sky_plot(ui, &state);
+                // Sky plot
+                ui.vertical(|ui| {
+                    export = Self::render_sky_plot(ui, &state, &mut timelapse);
+                });
             });
-        });
+        }
+
+        // Экспорт пишет лог, поэтому идёт после снятия блокировки чтения
+        if let Some(path) = export {
+            Self::export_sky_gif(state_lock, &timelapse, &path);
+        }
+
+        ui.data_mut(|d| d.insert_temp(timelapse_id, timelapse));
     }
 
     pub fn render_table(
         ui: &mut egui::Ui,
-        state: &AppState,
+        view: &SatellitesView,
     ) {
         use egui_extras::{Column, TableBuilder};
 
@@ -99,43 +133,29 @@ impl SatellitesPanel {
                 });
             })
             .body(|mut body| {
-                for sat in &state.satellites {
+                for sat in &view.rows {
                     body.row(18.0, |mut row| {
                         row.col(|ui| {
                             ui.label(&sat.id);
                         });
                         row.col(|ui| {
-                            let color = match sat.constellation.as_str() {
-                                "GPS" => Color32::from_rgb(100, 150, 255),
-                                "ГЛОНАСС" => Color32::from_rgb(255, 100, 100),
-                                "Галилео" => Color32::from_rgb(100, 255, 150),
-                                "Бэйдоу" => Color32::from_rgb(225, 200, 100),
-                                _ => Color32::WHITE,
-                            };
-                            ui.colored_label(color, &sat.constellation);
+                            ui.colored_label(sat.constellation_color, &sat.constellation);
                         });
                         row.col(|ui| {
-                            let cn0_color = if sat.cn0 > 35.0 {
-                                Color32::from_rgb(100, 255, 100)
-                            } else if sat.cn0 > 25.0 {
-                                Color32::from_rgb(255, 200, 100)
-                            } else {
-                                Color32::from_rgb(255, 100, 100)
-                            };
-                            ui.colored_label(cn0_color, format!("{:.1}", sat.cn0));
+                            ui.colored_label(sat.cn0_color, &sat.cn0);
                         });
                         row.col(|ui| {
-                            ui.label(format!("{:.0}°", sat.elevation));
+                            ui.label(&sat.elevation);
                         });
                         row.col(|ui| {
-                            ui.label(format!("{:.0}°", sat.azimuth));
+                            ui.label(&sat.azimuth);
                         });
                         row.col(|ui| {
-                            ui.label(format!("{:.0} Гц", sat.doppler));
+                            ui.label(&sat.doppler);
                         });
                         row.col(|ui| {
                             if sat.used_in_fix {
-                                ui.colored_label(Color32::from_rgb(100, 255, 100), "✓");
+                                ui.colored_label(view.fix_color, "✓");
                             } else {
                                 ui.label("-");
                             }
@@ -145,12 +165,21 @@ impl SatellitesPanel {
             });
     }
 
+    /// Полярная диаграмма с ползунком по истории сессии. Возвращает путь,
+    /// если запрошен экспорт GIF.
     fn render_sky_plot(
         ui: &mut egui::Ui,
         state: &AppState,
-    ) {
+        timelapse: &mut SkyTimelapse,
+    ) -> Option<PathBuf> {
+        if timelapse.playing {
+            Self::advance_timelapse(ui, timelapse, state.sky_history.seq_range());
+        }
+
+        let view = SkyPlotView::from_state(state, timelapse.seq, timelapse.color_by_cn0);
+
         ui.heading("Полярная диаграмма");
-        ui.label("Высота vs Азимут");
+        ui.label(&view.caption);
 
         // Преобразуем данные спутников в полярные координаты для отображения
         Plot::new("sky_plot")
@@ -182,56 +211,185 @@ impl SatellitesPanel {
                 }
 
                 // Рисуем спутники
-                for (i, sat) in state.satellites.iter().enumerate() {
-                    let radius = (90.0 - sat.elevation) / 90.0;
-                    let azimuth_rad = sat.azimuth.to_radians();
-
-                    let x = radius * azimuth_rad.sin();
-                    let y = radius * azimuth_rad.cos();
-
-                    let color = match sat.constellation.as_str() {
-                        "GPS" => Color32::from_rgb(100, 150, 255),
-                        "ГЛОНАСС" => Color32::from_rgb(255, 100, 100),
-                        "Галилео" => Color32::from_rgb(100, 255, 150),
-                        "Бэйдоу" => Color32::from_rgb(255, 200, 100),
-                        _ => Color32::WHITE,
-                    };
-
-                    let size = if sat.used_in_fix { 8.0 } else { 4.0 };
-
+                for (i, point) in view.points.iter().enumerate() {
                     // Points::new тоже требует имя + данные — даём уникальное имя на спутник
                     plot_ui.points(
-                        Points::new(format!("sat_{i}"), vec![[x as f64, y as f64]])
-                            .color(color)
-                            .radius(size),
+                        Points::new(format!("sat_{i}"), vec![point.pos])
+                            .color(point.color)
+                            .shape(point.marker.plot_shape())
+                            .radius(point.radius),
                     );
                 }
             });
 
         // Легенда
         ui.add_space(5.0);
+        Self::render_legend(ui, &view.legend);
+
+        ui.add_space(5.0);
+        Self::render_scrubber(ui, state, timelapse)
+    }
+
+    /// Ползунок, воспроизведение и экспорт истории неба.
+    fn render_scrubber(
+        ui: &mut egui::Ui,
+        state: &AppState,
+        timelapse: &mut SkyTimelapse,
+    ) -> Option<PathBuf> {
+        let history = &state.sky_history;
+        let Some((first, last)) = history.seq_range() else {
+            ui.label(egui::RichText::new("История пуста: нет данных о спутниках").weak());
+            return None;
+        };
+
+        let mut export = None;
+
         ui.horizontal(|ui| {
-            // Кружок
-            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-            ui.painter()
-                .circle_filled(rect.center(), 6.0, Color32::from_rgb(100, 150, 255));
-            // Текст
-            ui.label("GPS");
-
-            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-            ui.painter()
-                .circle_filled(rect.center(), 6.0, Color32::from_rgb(255, 100, 100));
-            ui.label("ГЛОНАСС");
-
-            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-            ui.painter()
-                .circle_filled(rect.center(), 6.0, Color32::from_rgb(100, 255, 150));
-            ui.label("Галилео");
-
-            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-            ui.painter()
-                .circle_filled(rect.center(), 6.0, Color32::from_rgb(255, 200, 100));
-            ui.label("Бэйдоу");
+            let icon = if timelapse.playing { "⏸" } else { "▶" };
+            if ui
+                .button(icon)
+                .on_hover_text("Воспроизвести историю")
+                .clicked()
+            {
+                timelapse.playing = !timelapse.playing;
+                if timelapse.playing {
+                    // С последней эпохи — заново с начала
+                    if timelapse.seq.is_none() {
+                        timelapse.seq = Some(first);
+                    }
+                    timelapse.last_step = ui.input(|i| i.time);
+                }
+            }
+
+            let mut pos = timelapse.seq.unwrap_or(last).clamp(first, last);
+            if ui
+                .add(egui::Slider::new(&mut pos, first..=last).show_value(false))
+                .changed()
+            {
+                timelapse.seq = (pos < last).then_some(pos);
+                timelapse.playing = false;
+            }
+
+            if ui
+                .add_enabled(timelapse.seq.is_some(), egui::Button::new("⏭ Сейчас"))
+                .clicked()
+            {
+                timelapse.seq = None;
+                timelapse.playing = false;
+            }
+        });
+
+        if let (Some(a), Some(b)) = (history.get(first), history.latest()) {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} эпох: {} — {} UTC",
+                    history.len(),
+                    a.time.format("%H:%M:%S"),
+                    b.time.format("%H:%M:%S")
+                ))
+                .weak(),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Скорость:");
+            ui.add(
+                egui::Slider::new(&mut timelapse.speed, 1.0..=60.0)
+                    .logarithmic(true)
+                    .suffix(" эп/с"),
+            );
+            ui.checkbox(&mut timelapse.color_by_cn0, "Цвет по C/N0");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("GIF:");
+            ui.add(egui::TextEdit::singleline(&mut timelapse.gif_path).desired_width(180.0));
+            if ui.button("💾 Экспорт").clicked() {
+                export = Some(PathBuf::from(&timelapse.gif_path));
+            }
+        });
+
+        export
+    }
+
+    /// Шаг воспроизведения по часам egui; в конце истории диаграмма снова
+    /// следует за данными.
+    fn advance_timelapse(
+        ui: &egui::Ui,
+        timelapse: &mut SkyTimelapse,
+        range: Option<(u64, u64)>,
+    ) {
+        let Some((first, last)) = range else {
+            timelapse.playing = false;
+            return;
+        };
+
+        let now = ui.input(|i| i.time);
+        let steps = ((now - timelapse.last_step) * timelapse.speed as f64).floor();
+
+        if steps >= 1.0 {
+            let next = timelapse.seq.unwrap_or(first).max(first) + steps as u64;
+            if next >= last {
+                timelapse.seq = None;
+                timelapse.playing = false;
+            } else {
+                timelapse.seq = Some(next);
+            }
+            timelapse.last_step = now;
+        }
+
+        ui.ctx().request_repaint();
+    }
+
+    fn export_sky_gif(
+        state_lock: &Arc<RwLock<AppState>>,
+        timelapse: &SkyTimelapse,
+        path: &Path,
+    ) {
+        let result = {
+            let state = state_lock.read();
+            let settings = &state.settings;
+
+            DataExporter::export_sky_gif(
+                state.sky_history.epochs(),
+                |sat| {
+                    settings
+                        .constellations
+                        .allows(&sat.constellation)
+                        .then(|| point_color(settings, timelapse.color_by_cn0, sat))
+                },
+                &SkyGifOptions {
+                    size: 320,
+                    frame_ms: (1000.0 / timelapse.speed.max(1.0)) as u16,
+                    max_frames: 600,
+                },
+                path,
+            )
+        };
+
+        let msg = match result {
+            Ok(frames) => format!(
+                "Sky plot time-lapse ({frames} frames) exported to {}",
+                path.display()
+            ),
+            Err(e) => format!("Error exporting sky plot GIF: {e}"),
+        };
+        state_lock.write().add_log(msg);
+    }
+
+    fn render_legend(
+        ui: &mut egui::Ui,
+        legend: &[LegendEntry],
+    ) {
+        ui.horizontal(|ui| {
+            for entry in legend {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                entry
+                    .marker
+                    .paint(ui.painter(), rect.center(), 6.0, entry.color);
+                ui.label(format!("{} ({})", entry.name, entry.code));
+            }
         });
     }
 
@@ -314,7 +472,7 @@ impl SatellitesPanel {
         );
 
         // Рисуем спутники
-        for sat in &state.satellites {
+        for sat in state.visible_satellites() {
             let r = radius * (90.0 - sat.elevation) / 90.0;
             let angle_rad = (90.0 - sat.azimuth).to_radians(); // поворот чтобы север был вверху
 
@@ -322,16 +480,18 @@ impl SatellitesPanel {
             let y = center.y - r * angle_rad.sin();
             let pos = Pos2::new(x, y);
 
-            let color = match sat.constellation.as_str() {
-                "GPS" => Color32::from_rgb(100, 150, 255),
-                "ГЛОНАСС" => Color32::from_rgb(255, 100, 100),
-                "Галилео" => Color32::from_rgb(100, 255, 150),
-                "Бэйдоу" => Color32::from_rgb(255, 200, 100),
-                _ => Color32::WHITE,
-            };
+            let color = state
+                .settings
+                .palette
+                .constellation_color(&sat.constellation);
 
             let point_radius = if sat.used_in_fix { 6.0 } else { 4.0 };
-            painter.circle_filled(pos, point_radius, color);
+            MarkerShape::for_constellation(&sat.constellation).paint(
+                painter,
+                pos,
+                point_radius,
+                color,
+            );
 
             // Рисуем ID спутника рядом
             painter.text(
@@ -345,6 +505,19 @@ impl SatellitesPanel {
     }
 }
 
+impl Default for SkyTimelapse {
+    fn default() -> Self {
+        Self {
+            seq: None,
+            playing: false,
+            speed: 10.0,
+            color_by_cn0: false,
+            gif_path: "skyplot.gif".to_string(),
+            last_step: 0.0,
+        }
+    }
+}
+
 impl InteractiveSatelliteTable {
     pub fn new() -> Self {
         Self {
@@ -368,26 +541,13 @@ impl InteractiveSatelliteTable {
                 .selected_text(self.filter_constellation.as_deref().unwrap_or("Все"))
                 .show_ui(ui, |ui| {
                     ui.selectable_value(&mut self.filter_constellation, None, "Все");
-                    ui.selectable_value(
-                        &mut self.filter_constellation,
-                        Some("GPS".to_string()),
-                        "GPS",
-                    );
-                    ui.selectable_value(
-                        &mut self.filter_constellation,
-                        Some("ГЛОНАСС".to_string()),
-                        "ГЛОНАСС",
-                    );
-                    ui.selectable_value(
-                        &mut self.filter_constellation,
-                        Some("Галилео".to_string()),
-                        "Галилео",
-                    );
-                    ui.selectable_value(
-                        &mut self.filter_constellation,
-                        Some("Бэйдоу".to_string()),
-                        "Бэйдоу",
-                    );
+                    for c in Constellation::ALL {
+                        ui.selectable_value(
+                            &mut self.filter_constellation,
+                            Some(c.name().to_string()),
+                            c.name(),
+                        );
+                    }
                 });
 
             ui.label("Мин. CN0:");
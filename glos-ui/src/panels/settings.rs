@@ -1,18 +1,100 @@
-#[derive(Clone, Copy, PartialEq)]
+use std::path::{Path, PathBuf};
+
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::DEFAULT_AVERAGE_ALPHA,
+    view::{SettingsChoice, SettingsView},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ColormapType {
     Jet,
     Viridis,
+    Cividis,
     Grayscale,
 }
 
-#[derive(Clone)]
+/// Палитра категориальных цветов (созвездия, уровни качества, серии).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CategoricalPalette {
+    /// Исходные цвета ГЛОС
+    Classic,
+    /// Okabe–Ito: различима при всех распространённых формах дальтонизма
+    OkabeIto,
+}
+
+/// Спутниковая навигационная система.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Constellation {
+    Gps,
+    Glonass,
+    Galileo,
+    BeiDou,
+}
+
+/// Форма отметки созвездия на графиках. Дублирует цвет, чтобы созвездия
+/// различались и без него (дальтонизм, раскраска по C/N0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Diamond,
+    Triangle,
+}
+
+/// Оформление созвездия в таблицах, диаграммах и легендах. Берётся только
+/// из [`CategoricalPalette::constellation_style`], чтобы панели не
+/// заводили собственных цветов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstellationStyle {
+    pub constellation: Constellation,
+    pub color: Color32,
+    pub marker: MarkerShape,
+    /// Однобуквенный код RINEX: G, R, E, C
+    pub code: &'static str,
+    /// Название для интерфейса
+    pub name: &'static str,
+}
+
+/// Какие созвездия обрабатываются и показываются. Выключенные не ищутся
+/// при захвате и не попадают в таблицу спутников и на небесную диаграмму.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnabledConstellations {
+    pub gps: bool,
+    pub glonass: bool,
+    pub galileo: bool,
+    pub beidou: bool,
+}
+
+/// Уровень качества сигнала для цветовой индикации.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalQuality {
+    Good,
+    Fair,
+    Poor,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UiSettings {
+    // Accessibility
+    pub palette: CategoricalPalette,
+    pub font_scale: f32,
+
     // Signal view
     pub fft_window_size: usize,
     pub waterfall_colormap: ColormapType,
     pub show_grid: bool,
+    pub fft_average_alpha: f32,
+    pub show_fft_average: bool,
+    pub show_peak_hold: bool,
+    pub show_min_hold: bool,
 
     // Satellites
+    pub constellations: EnabledConstellations,
     pub min_cn0_threshold: f32,
     pub show_doppler_arrows: bool,
     pub skyplot_labels: bool,
@@ -24,59 +106,424 @@ pub struct UiSettings {
 
 pub struct SettingsPanel;
 
+/// Опорные точки Viridis (matplotlib), равномерно по [0, 1].
+const VIRIDIS: [(u8, u8, u8); 5] = [
+    (68, 1, 84),
+    (59, 82, 139),
+    (33, 145, 140),
+    (94, 201, 98),
+    (253, 231, 37),
+];
+
+/// Опорные точки Cividis, равномерно по [0, 1].
+const CIVIDIS: [(u8, u8, u8); 5] = [
+    (0, 32, 76),
+    (65, 77, 107),
+    (124, 123, 120),
+    (188, 175, 111),
+    (255, 233, 69),
+];
+
+impl ColormapType {
+    pub const ALL: [ColormapType; 4] = [
+        ColormapType::Jet,
+        ColormapType::Viridis,
+        ColormapType::Cividis,
+        ColormapType::Grayscale,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColormapType::Jet => "Jet",
+            ColormapType::Viridis => "Viridis",
+            ColormapType::Cividis => "Cividis",
+            ColormapType::Grayscale => "Оттенки серого",
+        }
+    }
+
+    /// Цвет для нормализованного значения `t` ∈ [0, 1].
+    pub fn color(
+        &self,
+        t: f32,
+    ) -> Color32 {
+        let t = if t.is_finite() {
+            t.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        match self {
+            ColormapType::Jet => {
+                // синий -> голубой -> зелёный -> жёлтый -> красный
+                let (r, g, b) = if t < 0.25 {
+                    (0.0, 255.0 * t / 0.25, 255.0)
+                } else if t < 0.5 {
+                    (0.0, 255.0, 255.0 * (1.0 - (t - 0.25) / 0.25))
+                } else if t < 0.75 {
+                    (255.0 * (t - 0.5) / 0.25, 255.0, 0.0)
+                } else {
+                    (255.0, 255.0 * (1.0 - (t - 0.75) / 0.25), 0.0)
+                };
+                Color32::from_rgb(r.round() as u8, g.round() as u8, b.round() as u8)
+            }
+            ColormapType::Viridis => lerp_stops(&VIRIDIS, t),
+            ColormapType::Cividis => lerp_stops(&CIVIDIS, t),
+            ColormapType::Grayscale => Color32::from_gray((t * 255.0).round() as u8),
+        }
+    }
+}
+
+impl Constellation {
+    pub const ALL: [Constellation; 4] = [
+        Constellation::Gps,
+        Constellation::Glonass,
+        Constellation::Galileo,
+        Constellation::BeiDou,
+    ];
+
+    /// Название, под которым созвездие приходит в [`crate::data::Satellite`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constellation::Gps => "GPS",
+            Constellation::Glonass => "ГЛОНАСС",
+            Constellation::Galileo => "Галилео",
+            Constellation::BeiDou => "Бэйдоу",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+
+    /// Однобуквенный код RINEX, он же префикс номера спутника.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Constellation::Gps => "G",
+            Constellation::Glonass => "R",
+            Constellation::Galileo => "E",
+            Constellation::BeiDou => "C",
+        }
+    }
+
+    pub fn marker(&self) -> MarkerShape {
+        match self {
+            Constellation::Gps => MarkerShape::Circle,
+            Constellation::Glonass => MarkerShape::Square,
+            Constellation::Galileo => MarkerShape::Diamond,
+            Constellation::BeiDou => MarkerShape::Triangle,
+        }
+    }
+}
+
+impl MarkerShape {
+    /// Форма созвездия по его названию; неизвестные — круги.
+    pub fn for_constellation(name: &str) -> Self {
+        Constellation::from_name(name).map_or(MarkerShape::Circle, |c| c.marker())
+    }
+
+    /// Та же форма для точек `egui_plot`.
+    pub fn plot_shape(&self) -> egui_plot::MarkerShape {
+        match self {
+            MarkerShape::Circle => egui_plot::MarkerShape::Circle,
+            MarkerShape::Square => egui_plot::MarkerShape::Square,
+            MarkerShape::Diamond => egui_plot::MarkerShape::Diamond,
+            MarkerShape::Triangle => egui_plot::MarkerShape::Up,
+        }
+    }
+
+    /// Рисует отметку с центром `center` и полуразмером `radius`.
+    pub fn paint(
+        &self,
+        painter: &egui::Painter,
+        center: egui::Pos2,
+        radius: f32,
+        color: Color32,
+    ) {
+        let at = |dx: f32, dy: f32| center + egui::vec2(dx * radius, dy * radius);
+        let stroke = egui::Stroke::NONE;
+
+        match self {
+            MarkerShape::Circle => painter.circle_filled(center, radius, color),
+            MarkerShape::Square => painter.rect_filled(
+                egui::Rect::from_center_size(center, egui::Vec2::splat(radius * 1.8)),
+                0.0,
+                color,
+            ),
+            MarkerShape::Diamond => painter.add(egui::Shape::convex_polygon(
+                vec![at(0.0, -1.0), at(1.0, 0.0), at(0.0, 1.0), at(-1.0, 0.0)],
+                color,
+                stroke,
+            )),
+            MarkerShape::Triangle => painter.add(egui::Shape::convex_polygon(
+                vec![at(0.0, -1.0), at(0.9, 0.7), at(-0.9, 0.7)],
+                color,
+                stroke,
+            )),
+        };
+    }
+}
+
+impl EnabledConstellations {
+    pub fn is_enabled(
+        &self,
+        constellation: Constellation,
+    ) -> bool {
+        match constellation {
+            Constellation::Gps => self.gps,
+            Constellation::Glonass => self.glonass,
+            Constellation::Galileo => self.galileo,
+            Constellation::BeiDou => self.beidou,
+        }
+    }
+
+    pub fn flag_mut(
+        &mut self,
+        constellation: Constellation,
+    ) -> &mut bool {
+        match constellation {
+            Constellation::Gps => &mut self.gps,
+            Constellation::Glonass => &mut self.glonass,
+            Constellation::Galileo => &mut self.galileo,
+            Constellation::BeiDou => &mut self.beidou,
+        }
+    }
+
+    /// Созвездие спутника включено. Неизвестные названия не фильтруются.
+    pub fn allows(
+        &self,
+        name: &str,
+    ) -> bool {
+        Constellation::from_name(name).is_none_or(|c| self.is_enabled(c))
+    }
+
+    /// Включённые созвездия в порядке [`Constellation::ALL`].
+    pub fn enabled(&self) -> impl Iterator<Item = Constellation> + '_ {
+        Constellation::ALL
+            .into_iter()
+            .filter(|c| self.is_enabled(*c))
+    }
+}
+
+impl CategoricalPalette {
+    pub const ALL: [CategoricalPalette; 2] =
+        [CategoricalPalette::Classic, CategoricalPalette::OkabeIto];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CategoricalPalette::Classic => "Классическая",
+            CategoricalPalette::OkabeIto => "Okabe–Ito (для дальтоников)",
+        }
+    }
+
+    /// Оформление созвездия в этой палитре.
+    pub fn constellation_style(
+        &self,
+        constellation: Constellation,
+    ) -> ConstellationStyle {
+        let color = match (self, constellation) {
+            (CategoricalPalette::Classic, Constellation::Gps) => Color32::from_rgb(100, 150, 255),
+            (CategoricalPalette::Classic, Constellation::Glonass) => {
+                Color32::from_rgb(255, 100, 100)
+            }
+            (CategoricalPalette::Classic, Constellation::Galileo) => {
+                Color32::from_rgb(100, 255, 150)
+            }
+            (CategoricalPalette::Classic, Constellation::BeiDou) => {
+                Color32::from_rgb(255, 200, 100)
+            }
+            (CategoricalPalette::OkabeIto, Constellation::Gps) => Color32::from_rgb(86, 180, 233),
+            (CategoricalPalette::OkabeIto, Constellation::Glonass) => Color32::from_rgb(213, 94, 0),
+            (CategoricalPalette::OkabeIto, Constellation::Galileo) => {
+                Color32::from_rgb(0, 158, 115)
+            }
+            (CategoricalPalette::OkabeIto, Constellation::BeiDou) => {
+                Color32::from_rgb(240, 228, 66)
+            }
+        };
+
+        ConstellationStyle {
+            constellation,
+            color,
+            marker: constellation.marker(),
+            code: constellation.code(),
+            name: constellation.name(),
+        }
+    }
+
+    /// Цвет созвездия по его названию; неизвестные — белые.
+    pub fn constellation_color(
+        &self,
+        constellation: &str,
+    ) -> Color32 {
+        Constellation::from_name(constellation)
+            .map_or(Color32::WHITE, |c| self.constellation_style(c).color)
+    }
+
+    /// Цвет индикатора качества (CN0, статус решения).
+    pub fn quality_color(
+        &self,
+        quality: SignalQuality,
+    ) -> Color32 {
+        match (self, quality) {
+            (CategoricalPalette::Classic, SignalQuality::Good) => Color32::from_rgb(100, 255, 100),
+            (CategoricalPalette::Classic, SignalQuality::Fair) => Color32::from_rgb(255, 200, 100),
+            (CategoricalPalette::Classic, SignalQuality::Poor) => Color32::from_rgb(255, 100, 100),
+            (CategoricalPalette::OkabeIto, SignalQuality::Good) => Color32::from_rgb(0, 158, 115),
+            (CategoricalPalette::OkabeIto, SignalQuality::Fair) => Color32::from_rgb(230, 159, 0),
+            (CategoricalPalette::OkabeIto, SignalQuality::Poor) => Color32::from_rgb(213, 94, 0),
+        }
+    }
+
+    /// Цвет линии графика: 0 — FFT, 1 — история CN0, 2–4 — среднее,
+    /// peak-hold и min-hold спектра.
+    pub fn series_color(
+        &self,
+        index: usize,
+    ) -> Color32 {
+        const CLASSIC: [Color32; 5] = [
+            Color32::from_rgb(100, 150, 250),
+            Color32::from_rgb(100, 200, 100),
+            Color32::from_rgb(255, 220, 100),
+            Color32::from_rgb(255, 100, 100),
+            Color32::from_rgb(180, 130, 255),
+        ];
+        const OKABE_ITO: [Color32; 5] = [
+            Color32::from_rgb(86, 180, 233),
+            Color32::from_rgb(0, 158, 115),
+            Color32::from_rgb(240, 228, 66),
+            Color32::from_rgb(213, 94, 0),
+            Color32::from_rgb(204, 121, 167),
+        ];
+
+        match self {
+            CategoricalPalette::Classic => CLASSIC[index % CLASSIC.len()],
+            CategoricalPalette::OkabeIto => OKABE_ITO[index % OKABE_ITO.len()],
+        }
+    }
+}
+
+impl SignalQuality {
+    /// Классификация по CN0 (дБГц).
+    pub fn from_cn0(cn0: f32) -> Self {
+        if cn0 > 35.0 {
+            SignalQuality::Good
+        } else if cn0 > 25.0 {
+            SignalQuality::Fair
+        } else {
+            SignalQuality::Poor
+        }
+    }
+}
+
+impl UiSettings {
+    /// Путь файла настроек: `$XDG_CONFIG_HOME/glos/ui-settings.json` или
+    /// `~/.config/glos/ui-settings.json`.
+    pub fn config_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+
+        Some(base.join("glos").join("ui-settings.json"))
+    }
+
+    /// Загружает настройки; при ошибке возвращает значения по умолчанию.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<UiSettings>(&bytes).ok())
+            .map(UiSettings::sanitized)
+            .unwrap_or_default()
+    }
+
+    pub fn save(
+        &self,
+        path: &Path,
+    ) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    fn sanitized(mut self) -> Self {
+        if !self.font_scale.is_finite() {
+            self.font_scale = 1.0;
+        }
+        self.font_scale = self.font_scale.clamp(0.75, 2.0);
+        if !self.fft_average_alpha.is_finite() {
+            self.fft_average_alpha = DEFAULT_AVERAGE_ALPHA;
+        }
+        self.fft_average_alpha = self.fft_average_alpha.clamp(0.01, 1.0);
+        self
+    }
+}
+
 impl SettingsPanel {
     pub fn render(
         ui: &mut egui::Ui,
         settings: &mut UiSettings,
     ) {
+        let view = SettingsView::from_settings(settings);
+
         ui.heading("⚙️ Настройки");
         ui.separator();
 
         egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.collapsing("♿ Доступность", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Палитра:");
+                    Self::choice(ui, "palette", &view.palette, &mut settings.palette);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Масштаб шрифта:");
+                    ui.add(
+                        egui::Slider::new(&mut settings.font_scale, 0.75..=2.0)
+                            .step_by(0.05)
+                            .suffix("×"),
+                    );
+                });
+
+                if ui.button("Пресет для дальтоников").clicked() {
+                    settings.palette = CategoricalPalette::OkabeIto;
+                    settings.waterfall_colormap = ColormapType::Cividis;
+                }
+            });
+
             ui.collapsing("📡 Просмотр сигнала", |ui| {
                 ui.horizontal(|ui| {
                     ui.label("Размер FFT:");
-                    egui::ComboBox::from_id_salt("fft_size")
-                        .selected_text(format!("{}", settings.fft_window_size))
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut settings.fft_window_size, 256, "256");
-                            ui.selectable_value(&mut settings.fft_window_size, 512, "512");
-                            ui.selectable_value(&mut settings.fft_window_size, 1024, "1024");
-                            ui.selectable_value(&mut settings.fft_window_size, 2048, "2048");
-                        });
+                    Self::choice(
+                        ui,
+                        "fft_size",
+                        &view.fft_size,
+                        &mut settings.fft_window_size,
+                    );
                 });
 
                 ui.horizontal(|ui| {
                     ui.label("Цветовая карта:");
-                    egui::ComboBox::from_id_salt("colormap")
-                        .selected_text(match settings.waterfall_colormap {
-                            ColormapType::Jet => "Jet",
-                            ColormapType::Viridis => "Viridis",
-                            ColormapType::Grayscale => "Оттенки серого",
-                        })
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value(
-                                &mut settings.waterfall_colormap,
-                                ColormapType::Jet,
-                                "Jet",
-                            );
-                            ui.selectable_value(
-                                &mut settings.waterfall_colormap,
-                                ColormapType::Viridis,
-                                "Viridis",
-                            );
-                            ui.selectable_value(
-                                &mut settings.waterfall_colormap,
-                                ColormapType::Grayscale,
-                                "Оттенки серого",
-                            );
-                        });
+                    Self::choice(
+                        ui,
+                        "colormap",
+                        &view.colormap,
+                        &mut settings.waterfall_colormap,
+                    );
                 });
 
                 ui.checkbox(&mut settings.show_grid, "Показывать сетку");
             });
 
             ui.collapsing("🛰 Спутники", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Созвездия:");
+                    for toggle in &view.constellations {
+                        let flag = settings.constellations.flag_mut(toggle.constellation);
+                        ui.checkbox(flag, toggle.label);
+                    }
+                });
+
                 ui.horizontal(|ui| {
                     ui.label("Мин. CN0 (дБГц):");
                     ui.add(egui::Slider::new(
@@ -111,14 +558,37 @@ impl SettingsPanel {
             }
         });
     }
+
+    /// Выпадающий список по модели; выбор пишется в `value`.
+    fn choice<T: Copy + PartialEq>(
+        ui: &mut egui::Ui,
+        id: &str,
+        choice: &SettingsChoice<T>,
+        value: &mut T,
+    ) {
+        egui::ComboBox::from_id_salt(id)
+            .selected_text(&choice.selected)
+            .show_ui(ui, |ui| {
+                for option in &choice.options {
+                    ui.selectable_value(value, option.value, &option.label);
+                }
+            });
+    }
 }
 
 impl Default for UiSettings {
     fn default() -> Self {
         Self {
+            palette: CategoricalPalette::Classic,
+            font_scale: 1.0,
             fft_window_size: 512,
             waterfall_colormap: ColormapType::Jet,
             show_grid: true,
+            fft_average_alpha: DEFAULT_AVERAGE_ALPHA,
+            show_fft_average: false,
+            show_peak_hold: false,
+            show_min_hold: false,
+            constellations: EnabledConstellations::default(),
             min_cn0_threshold: 25.0,
             show_doppler_arrows: false,
             skyplot_labels: true,
@@ -127,3 +597,130 @@ impl Default for UiSettings {
         }
     }
 }
+
+impl Default for EnabledConstellations {
+    fn default() -> Self {
+        Self {
+            gps: true,
+            glonass: true,
+            galileo: true,
+            beidou: true,
+        }
+    }
+}
+
+fn lerp_stops(
+    stops: &[(u8, u8, u8)],
+    t: f32,
+) -> Color32 {
+    let pos = t * (stops.len() - 1) as f32;
+    let i = (pos.floor() as usize).min(stops.len() - 2);
+    let f = pos - i as f32;
+    let (a, b) = (stops[i], stops[i + 1]);
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f).round() as u8;
+
+    Color32::from_rgb(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colormap_endpoints() {
+        assert_eq!(
+            ColormapType::Viridis.color(0.0),
+            Color32::from_rgb(68, 1, 84)
+        );
+        assert_eq!(
+            ColormapType::Viridis.color(1.0),
+            Color32::from_rgb(253, 231, 37)
+        );
+        assert_eq!(
+            ColormapType::Cividis.color(0.5),
+            Color32::from_rgb(124, 123, 120)
+        );
+        assert_eq!(ColormapType::Grayscale.color(2.0), Color32::from_gray(255));
+        assert_eq!(
+            ColormapType::Jet.color(f32::NAN),
+            Color32::from_rgb(0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_okabe_ito_constellations_distinct() {
+        let p = CategoricalPalette::OkabeIto;
+        let colors: Vec<_> = ["GPS", "ГЛОНАСС", "Галилео", "Бэйдоу"]
+            .iter()
+            .map(|c| p.constellation_color(c))
+            .collect();
+
+        for (i, a) in colors.iter().enumerate() {
+            assert!(colors[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_constellation_styles_distinct() {
+        for palette in [CategoricalPalette::Classic, CategoricalPalette::OkabeIto] {
+            let styles: Vec<_> = Constellation::ALL
+                .iter()
+                .map(|c| palette.constellation_style(*c))
+                .collect();
+
+            for (i, a) in styles.iter().enumerate() {
+                assert_eq!(palette.constellation_color(a.name), a.color);
+                assert!(styles[i + 1..]
+                    .iter()
+                    .all(|b| a.marker != b.marker && a.code != b.code));
+            }
+        }
+
+        assert_eq!(
+            CategoricalPalette::Classic.constellation_color("QZSS"),
+            Color32::WHITE
+        );
+    }
+
+    #[test]
+    fn test_constellation_toggles() {
+        let mut c = EnabledConstellations::default();
+        *c.flag_mut(Constellation::Gps) = false;
+
+        assert!(!c.allows("GPS"));
+        assert!(c.allows("ГЛОНАСС"));
+        assert!(c.allows("QZSS"));
+        assert_eq!(c.enabled().count(), 3);
+
+        // Старый файл настроек без поля — все созвездия включены
+        let s: UiSettings = serde_json::from_str(r#"{"font_scale": 1.0}"#).unwrap();
+        assert_eq!(s.constellations, EnabledConstellations::default());
+        let s: UiSettings = serde_json::from_str(r#"{"constellations": {"gps": false}}"#).unwrap();
+        assert!(!s.constellations.gps && s.constellations.glonass);
+    }
+
+    #[test]
+    fn test_settings_persist_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("glos-ui-settings-{}", std::process::id()))
+            .join("ui-settings.json");
+
+        let settings = UiSettings {
+            palette: CategoricalPalette::OkabeIto,
+            font_scale: 1.5,
+            waterfall_colormap: ColormapType::Cividis,
+            ..Default::default()
+        };
+        settings.save(&path).unwrap();
+
+        assert_eq!(UiSettings::load(&path), settings);
+
+        // Битый файл и значения вне диапазона не ломают запуск
+        std::fs::write(&path, br#"{"font_scale": 10.0}"#).unwrap();
+        assert_eq!(UiSettings::load(&path).font_scale, 2.0);
+        std::fs::write(&path, b"garbage").unwrap();
+        assert_eq!(UiSettings::load(&path), UiSettings::default());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
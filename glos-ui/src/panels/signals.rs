@@ -1,105 +1,260 @@
-use std::{f32, sync::Arc};
+use std::{
+    f32,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use egui::Color32;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui::{Color32, PointerButton, Stroke};
+use egui_plot::{
+    HLine, Line, LineStyle, MarkerShape, Plot, PlotBounds, PlotImage, PlotPoint, PlotPoints,
+    PlotUi, Points, Polygon, Text, VLine,
+};
 use parking_lot::RwLock;
 
-use crate::data::AppState;
+use crate::{
+    data::{
+        AnnotationStore, AppState, DataExporter, MultiResWaterfall, SignalData, SpectrumMarkers,
+        WaterfallTile, WaterfallView,
+    },
+    panels::ColormapType,
+    view::{ChannelView, MarkerPoint, SignalsView},
+};
 
 pub struct SignalPanel;
 
+/// Разрешение обзорного водопада файла (строки × столбцы).
+const FILE_OVERVIEW_ROWS: usize = 256;
+const FILE_OVERVIEW_COLS: usize = 512;
+
+/// Как часто дочитывать идущую запись в водопаде файла.
+const LIVE_FILE_POLL: Duration = Duration::from_millis(500);
+
+/// Черновик аннотации на waterfall (живёт в памяти egui между кадрами).
+#[derive(Clone, Default)]
+struct AnnotationDraft {
+    /// Начало/конец текущего выделения (координаты графика)
+    drag_from: Option<[f64; 2]>,
+    drag_to: Option<[f64; 2]>,
+    /// Готовая область: (время нс, частота МГц), ждёт подписи
+    pending: Option<((u64, u64), (f64, f64))>,
+    label: String,
+}
+
+/// Выбор полосы для измерения мощности в канале (живёт в памяти egui).
+#[derive(Clone)]
+struct BandDraft {
+    /// Режим выделения: перетаскивание выделяет полосу вместо сдвига графика
+    selecting: bool,
+    drag_from: Option<f64>,
+    drag_to: Option<f64>,
+    csv_path: String,
+}
+
+impl Default for BandDraft {
+    fn default() -> Self {
+        Self {
+            selecting: false,
+            drag_from: None,
+            drag_to: None,
+            csv_path: "channel_power.csv".to_string(),
+        }
+    }
+}
+
+/// Изменения измерителя канала, применяемые после снятия блокировки чтения.
+enum ChannelAction {
+    SetBand(f64, f64),
+    ClearBand,
+    Threshold(f32),
+    Reset,
+    Export(PathBuf),
+}
+
 impl SignalPanel {
     pub fn render(
         ui: &mut egui::Ui,
-        state: &Arc<RwLock<AppState>>,
+        state_lock: &Arc<RwLock<AppState>>,
     ) {
-        let state = state.read();
+        let draft_id = ui.id().with("annotation_draft");
+        let mut draft: AnnotationDraft = ui.data_mut(|d| d.get_temp(draft_id).unwrap_or_default());
+        let band_id = ui.id().with("band_draft");
+        let mut band: BandDraft = ui.data_mut(|d| d.get_temp(band_id).unwrap_or_default());
+        let mut new_annotation = None;
+        let mut focus_consumed = false;
+        let mut channel_actions = Vec::new();
+
+        let state = state_lock.read();
+        let view = SignalsView::from_state(&state);
 
         ui.heading("📡 Просмотр сигнала");
         ui.separator();
 
         // FFT спектр
-        ui.label(
-            egui::RichText::new(format!(
-                "Центральная частота: {:.2} МГц | Частота дискретизации: {:.1} МГц",
-                state.signal_data.frequency_mhz, state.signal_data.sample_rate_mhz
-            ))
-            .strong(),
-        );
+        ui.label(egui::RichText::new(&view.header).strong());
 
         ui.add_space(5.0);
 
-        // График FFT
-        let fft_points: PlotPoints = state
-            .signal_data
-            .fft_data
-            .iter()
-            .enumerate()
-            .map(|(i, power)| {
-                let freq = (i as f32 / state.signal_data.fft_data.len() as f32 - 0.5)
-                    * state.signal_data.sample_rate_mhz
-                    + state.signal_data.frequency_mhz;
-                [freq as f64, *power as f64]
-            })
-            .collect();
+        // Следы усреднения/удержания (правятся на копии, запись после
+        // снятия блокировки чтения)
+        let mut settings = state.settings.clone();
+        let mut reset_traces = false;
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut settings.show_fft_average, "Среднее");
+            ui.add_enabled(
+                settings.show_fft_average,
+                egui::Slider::new(&mut settings.fft_average_alpha, 0.01_f32..=1.0_f32)
+                    .logarithmic(true)
+                    .text("α"),
+            );
+            ui.toggle_value(&mut settings.show_peak_hold, "Peak-hold");
+            ui.toggle_value(&mut settings.show_min_hold, "Min-hold");
+            reset_traces = ui.button("⟲ Сброс").clicked();
+            ui.label(&view.frames);
+        });
+        let settings_changed = settings != state.settings;
+
+        let sd = &state.signal_data;
+
+        // Маркеры: ЛКМ — поставить, ПКМ — убрать ближайший
+        let mut markers = state.markers.clone();
+        let mut markers_changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("🔺 Поиск пика").clicked() {
+                markers.peak_search(sd);
+                markers_changed = true;
+            }
+            if ui
+                .add_enabled(!markers.is_empty(), egui::Button::new("✖ Убрать маркеры"))
+                .clicked()
+            {
+                markers.clear();
+                markers_changed = true;
+            }
+            ui.separator();
+            ui.toggle_value(&mut band.selecting, "📏 Выбор полосы");
+            let hint = if band.selecting {
+                "Перетащите по спектру, чтобы выбрать полосу"
+            } else {
+                "ЛКМ по спектру — маркер, ПКМ — убрать"
+            };
+            ui.label(egui::RichText::new(hint).small().weak());
+        });
 
         Plot::new("fft_plot")
             .height(300.0)
             .show_axes([true, true])
             .show_grid([true, true])
             .allow_zoom(true)
-            .allow_drag(true)
+            .allow_drag(!band.selecting)
             .x_axis_label("Частота (МГц)")
             .y_axis_label("Мощность (дБ)")
             .show(ui, |plot_ui| {
-                plot_ui.line(
-                    Line::new("FFT", fft_points)
-                        .color(egui::Color32::from_rgb(100, 150, 250))
-                        .width(1.5),
-                );
+                for trace in &view.traces {
+                    plot_ui.line(
+                        Line::new(trace.name, PlotPoints::from(trace.points.clone()))
+                            .color(trace.color)
+                            .width(trace.width),
+                    );
+                }
+
+                if band.selecting {
+                    if let Some((a, b)) = Self::handle_band_selection(plot_ui, &mut band) {
+                        channel_actions.push(ChannelAction::SetBand(a, b));
+                    }
+                } else {
+                    markers_changed |= Self::handle_marker_clicks(plot_ui, &mut markers);
+                }
+                Self::draw_channel_band(plot_ui, view.channel.as_ref(), &band);
+                Self::draw_markers(plot_ui, &view.markers);
             });
 
+        // Показания маркеров
+        for line in &view.marker_readout {
+            ui.monospace(line);
+        }
+
+        if let Some(channel) = &view.channel {
+            Self::render_channel_power(ui, channel, &mut band, &mut channel_actions);
+        }
+
         ui.add_space(15.0);
 
         // Waterfall (упрощенная версия)
         ui.heading("Водопад спектра");
 
-        let waterfall_size = state.signal_data.waterfall.len();
-        if waterfall_size > 0 {
-            ui.label(format!("История: {waterfall_size} кадров"));
+        ui.label(&view.waterfall_caption);
+        if !view.waterfall.is_empty() {
+            if let Some((time_ns, freq_mhz)) = draft.pending {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "Новая аннотация {:.4}–{:.4} МГц:",
+                        freq_mhz.0, freq_mhz.1
+                    ));
+                    let edit = ui.text_edit_singleline(&mut draft.label);
+                    let enter = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    if ui.button("➕ Добавить").clicked() || enter {
+                        let label = match draft.label.trim() {
+                            "" => "без названия".to_string(),
+                            l => l.to_string(),
+                        };
+                        new_annotation = Some((label, time_ns, freq_mhz));
+                        draft.pending = None;
+                        draft.label.clear();
+                    }
+                    if ui.button("Отмена").clicked() {
+                        draft.pending = None;
+                    }
+                });
+            }
 
-            // Рисуем waterfall как серию линий
+            let focus = state
+                .annotation_focus
+                .and_then(|id| state.annotations.get(id));
+
+            // Рисуем waterfall как серию линий. Перетаскивание левой кнопкой
+            // выделяет область для новой аннотации.
             Plot::new("waterfall_plot")
                 .height(300.0)
                 .show_axes([true, true])
                 .show_grid([false, false])
                 .allow_zoom(true)
+                .allow_drag(false)
+                .allow_boxed_zoom(false)
                 .x_axis_label("Бин частоты")
                 .y_axis_label("Время (кадры)")
                 .show(ui, |plot_ui| {
-                    for (time_idx, row) in state.signal_data.waterfall.iter().enumerate() {
-                        let points: PlotPoints = row
-                            .iter()
-                            .enumerate()
-                            .map(|(freq_idx, power)| {
-                                // Нормализуем мощность для цвета
-                                [freq_idx as f64, time_idx as f64 + (*power as f64) / 20.0]
-                            })
-                            .collect();
-
-                        let intensity = (time_idx as f32 / waterfall_size as f32 * 255.0) as u8;
-                        let color =
-                            egui::Color32::from_rgb(intensity / 2, intensity, 255 - intensity / 2);
-
+                    for (time_idx, row) in view.waterfall.iter().enumerate() {
                         plot_ui.line(
-                            Line::new(format!("wf_{time_idx}"), points)
-                                .color(color)
-                                .width(1.0),
+                            Line::new(
+                                format!("wf_{time_idx}"),
+                                PlotPoints::from(row.points.clone()),
+                            )
+                            .color(row.color)
+                            .width(1.0),
                         );
                     }
+
+                    Self::draw_annotations(plot_ui, &view);
+                    Self::handle_selection(plot_ui, sd, &mut draft);
+
+                    if let Some(a) = focus {
+                        if let (Some(y0), Some(y1)) =
+                            (sd.ns_to_frame(a.start_ns), sd.ns_to_frame(a.end_ns))
+                        {
+                            let x0 = sd.mhz_to_bin(a.freq_lo_mhz);
+                            let x1 = sd.mhz_to_bin(a.freq_hi_mhz);
+                            let mx = ((x1 - x0) * 0.5).max(8.0);
+                            let my = ((y1 - y0) * 0.5).max(4.0);
+                            plot_ui.set_plot_bounds(PlotBounds::from_min_max(
+                                [x0 - mx, y0 - my],
+                                [x1 + mx, y1 + my],
+                            ));
+                        }
+                        focus_consumed = true;
+                    }
                 });
-        } else {
-            ui.label("Данные водопада отсутствуют");
         }
 
         ui.add_space(10.0);
@@ -111,62 +266,607 @@ impl SignalPanel {
                     ui.label("Статистика сигнала");
                     ui.separator();
 
-                    let max_power = state
-                        .signal_data
-                        .fft_data
-                        .iter()
-                        .copied()
-                        .fold(f32::NEG_INFINITY, f32::max);
-                    let min_power = state
-                        .signal_data
-                        .fft_data
-                        .iter()
-                        .copied()
-                        .fold(f32::INFINITY, f32::min);
-                    let avg_power: f32 = state.signal_data.fft_data.iter().sum::<f32>()
-                        / state.signal_data.fft_data.len() as f32;
-
-                    ui.label(format!("Макс: {max_power:.1} дБ"));
-                    ui.label(format!("Мин: {min_power:.1} дБ"));
-                    ui.label(format!("Среднее: {avg_power:.1} дБ"));
-                    ui.label(format!(
-                        "Динамический диапазон: {:.1} дБ",
-                        max_power - min_power
-                    ));
+                    for line in &view.statistics {
+                        ui.label(line);
+                    }
+                });
+            });
+        });
+
+        // отпускаем read-guard перед получением write-guard
+        drop(state);
+        ui.data_mut(|d| {
+            d.insert_temp(draft_id, draft);
+            d.insert_temp(band_id, band);
+        });
+
+        if new_annotation.is_some()
+            || focus_consumed
+            || settings_changed
+            || reset_traces
+            || markers_changed
+            || !channel_actions.is_empty()
+        {
+            let mut state = state_lock.write();
+
+            for action in channel_actions {
+                Self::apply_channel_action(&mut state, action);
+            }
+
+            if markers_changed {
+                state.markers = markers;
+            }
+
+            if settings_changed {
+                state.settings = settings;
+            }
+            if reset_traces {
+                state.signal_data.traces.reset();
+            }
+
+            if let Some((label, time_ns, freq_mhz)) = new_annotation {
+                state.add_log(format!("Annotation '{label}' added"));
+                state.annotations.add(label, time_ns, freq_mhz);
+            }
+            if focus_consumed {
+                state.annotation_focus = None;
+            }
+        }
+
+        Self::render_file_waterfall(ui, state_lock);
+    }
+
+    /// Открывает .glos-файл в водопаде; результат пишется в журнал. С
+    /// `live` файл считается идущей записью, и водопад растёт вместе с ним.
+    pub fn open_file(
+        state_lock: &Arc<RwLock<AppState>>,
+        path: &Path,
+        live: bool,
+    ) {
+        // Файл читается без блокировки состояния
+        let result = if live {
+            MultiResWaterfall::attach(path, FILE_OVERVIEW_ROWS, FILE_OVERVIEW_COLS)
+        } else {
+            MultiResWaterfall::open(path, FILE_OVERVIEW_ROWS, FILE_OVERVIEW_COLS)
+        };
+        let mut state = state_lock.write();
+
+        match result {
+            Ok(wf) => {
+                let corrupted = wf.source().read_stats().blocks_corrupted;
+                state.add_log(format!(
+                    "{} {} ({:.1} s{})",
+                    if live { "Attached to" } else { "Opened" },
+                    path.display(),
+                    wf.source().duration_s(),
+                    if corrupted > 0 {
+                        format!(", {corrupted} corrupted blocks skipped")
+                    } else {
+                        String::new()
+                    }
+                ));
+                state.file_waterfall = Some(Arc::new(wf));
+
+                // Аннотации — в sidecar открытой записи; уже сохранённые
+                // подгружаются
+                let sidecar = AnnotationStore::sidecar_path(path);
+                if sidecar.exists() {
+                    match AnnotationStore::load(&sidecar) {
+                        Ok(store) => {
+                            state.add_log(format!(
+                                "Loaded {} annotations from {}",
+                                store.len(),
+                                sidecar.display()
+                            ));
+                            state.annotations = store;
+                        }
+                        Err(e) => state.add_log(format!("Error loading annotations: {e}")),
+                    }
+                }
+                state.annotations_path = sidecar;
+            }
+            Err(e) => state.add_log(format!("Error opening {}: {e}", path.display())),
+        }
+    }
+
+    /// Водопад .glos-файла: при увеличении видимая область пересчитывается
+    /// FFT большего размера.
+    fn render_file_waterfall(
+        ui: &mut egui::Ui,
+        state_lock: &Arc<RwLock<AppState>>,
+    ) {
+        ui.add_space(15.0);
+        ui.heading("Водопад файла");
+
+        let path_id = ui.id().with("file_waterfall_path");
+        let live_id = ui.id().with("file_waterfall_live");
+        let mut path: String = ui.data_mut(|d| d.get_temp(path_id).unwrap_or_default());
+        let mut live: bool = ui.data_mut(|d| d.get_temp(live_id).unwrap_or_default());
+        let (wf, colormap) = {
+            let state = state_lock.read();
+            (
+                state.file_waterfall.clone(),
+                state.settings.waterfall_colormap,
+            )
+        };
+
+        let (mut open, mut close) = (false, false);
+        ui.horizontal(|ui| {
+            ui.label("Файл .glos:");
+            ui.add(egui::TextEdit::singleline(&mut path).desired_width(300.0));
+            open = ui.button("📂 Открыть").clicked();
+            ui.checkbox(&mut live, "Идёт запись")
+                .on_hover_text("Дочитывать блоки, которые дописывает рекордер");
+            if wf.is_some() {
+                close = ui.button("✖ Закрыть").clicked();
+            }
+        });
+        ui.data_mut(|d| {
+            d.insert_temp(path_id, path.clone());
+            d.insert_temp(live_id, live);
+        });
+
+        if open {
+            Self::open_file(state_lock, Path::new(&path), live);
+            return;
+        }
+        if close {
+            state_lock.write().file_waterfall = None;
+            return;
+        }
+
+        let Some(wf) = wf else {
+            ui.label(
+                egui::RichText::new("Откройте запись, чтобы смотреть её водопад с увеличением")
+                    .weak(),
+            );
+            return;
+        };
+
+        if wf.source().is_live() {
+            Self::poll_live_file(ui, state_lock, &wf);
+        }
+
+        let overview = wf.overview();
+        let tile = wf.tile();
+        let overview_tex = Self::tile_texture(ui, "file_wf_overview", &overview, colormap);
+        let zoom_tex = (!Arc::ptr_eq(&overview, &tile))
+            .then(|| Self::tile_texture(ui, "file_wf_zoom", &tile, colormap));
+
+        let source = wf.source();
+        ui.label(format!(
+            "{} | FFT {} ({:.1} Гц/бин){}",
+            source.name(),
+            tile.fft_size,
+            source.sample_rate() as f64 / tile.fft_size as f64,
+            if wf.is_busy() {
+                " | пересчёт…"
+            } else {
+                ""
+            }
+        ));
+
+        if let Some(chain) = source.rf_chain() {
+            ui.label(egui::RichText::new(format!("Радиотракт: {chain}")).weak());
+        }
+
+        let stats = source.read_stats();
+        if stats.blocks_corrupted > 0 {
+            let title = egui::RichText::new(format!(
+                "⚠ Повреждённых блоков: {} (пропущены)",
+                stats.blocks_corrupted
+            ))
+            .color(ui.visuals().warn_fg_color);
+
+            egui::CollapsingHeader::new(title)
+                .id_salt("file_corruption")
+                .show(ui, |ui| {
+                    for event in &stats.corruption_events {
+                        ui.monospace(event.to_string());
+                    }
                 });
+        }
+
+        let (f_min, f_max) = source.freq_range_mhz();
+        let duration = source.duration_s();
+
+        Plot::new("file_waterfall_plot")
+            .height(300.0)
+            .show_axes([true, true])
+            .allow_zoom(true)
+            .allow_drag(true)
+            .x_axis_label("Частота (МГц)")
+            .y_axis_label("Время (с)")
+            .show(ui, |plot_ui| {
+                plot_ui.image(Self::tile_image(
+                    "file_wf_overview",
+                    &overview,
+                    &overview_tex,
+                ));
+                if let Some(tex) = &zoom_tex {
+                    plot_ui.image(Self::tile_image("file_wf_zoom", &tile, tex));
+                }
+
+                // Видимая область в пределах файла; разрешение — по пикселям
+                let bounds = plot_ui.plot_bounds();
+                let frame = *plot_ui.transform().frame();
+                let view = WaterfallView {
+                    f_lo_mhz: bounds.min()[0].max(f_min),
+                    f_hi_mhz: bounds.max()[0].min(f_max),
+                    t0_s: bounds.min()[1].max(0.0),
+                    t1_s: bounds.max()[1].min(duration),
+                    rows: ((frame.height() / 2.0) as usize).clamp(32, FILE_OVERVIEW_ROWS),
+                    cols: (frame.width() as usize).clamp(64, 1024),
+                };
+
+                if view.span_mhz() > 0.0 && view.duration_s() > 0.0 {
+                    wf.request(view);
+                }
+            });
+
+        if wf.is_busy() {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Дочитывает идущую запись не чаще [`LIVE_FILE_POLL`] и планирует
+    /// следующую перерисовку.
+    fn poll_live_file(
+        ui: &egui::Ui,
+        state_lock: &Arc<RwLock<AppState>>,
+        wf: &MultiResWaterfall,
+    ) {
+        let polled_id = ui.id().with("file_waterfall_polled");
+        let now = ui.input(|i| i.time);
+        let last: f64 = ui.data(|d| d.get_temp(polled_id).unwrap_or(f64::MIN));
+
+        if now - last >= LIVE_FILE_POLL.as_secs_f64() {
+            ui.data_mut(|d| d.insert_temp(polled_id, now));
+            if let Err(e) = wf.poll_live() {
+                state_lock.write().add_log(format!(
+                    "Error reading {}: {e}",
+                    wf.source().path().display()
+                ));
+            }
+        }
+
+        ui.ctx().request_repaint_after(LIVE_FILE_POLL);
+    }
+
+    /// Текстура водопада; перезагружается, только когда сменился водопад
+    /// или цветовая карта.
+    fn tile_texture(
+        ui: &egui::Ui,
+        name: &str,
+        tile: &Arc<WaterfallTile>,
+        colormap: ColormapType,
+    ) -> egui::TextureHandle {
+        let id = ui.id().with(name);
+        let key = (Arc::as_ptr(tile) as usize, colormap);
+
+        let cached: Option<((usize, ColormapType), egui::TextureHandle)> =
+            ui.data(|d| d.get_temp(id));
+        if let Some((k, tex)) = cached {
+            if k == key {
+                return tex;
+            }
+        }
+
+        let finite = tile
+            .power_db
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|p| p.is_finite());
+        let (lo, hi) = finite.fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p), hi.max(p)));
+        let range = (hi - lo).max(1.0);
+
+        // Строка 0 изображения — верх графика, т.е. последняя по времени
+        let (w, h) = (tile.view.cols, tile.view.rows);
+        let pixels = tile
+            .power_db
+            .iter()
+            .rev()
+            .flat_map(|row| row.iter().map(|&p| colormap.color((p - lo) / range)))
+            .collect();
+
+        let tex = ui.ctx().load_texture(
+            name,
+            egui::ColorImage::new([w, h], pixels),
+            egui::TextureOptions::NEAREST,
+        );
+        ui.data_mut(|d| d.insert_temp(id, (key, tex.clone())));
+
+        tex
+    }
+
+    fn tile_image(
+        name: &str,
+        tile: &WaterfallTile,
+        tex: &egui::TextureHandle,
+    ) -> PlotImage {
+        let v = &tile.view;
+
+        PlotImage::new(
+            name,
+            tex.id(),
+            PlotPoint::new((v.f_lo_mhz + v.f_hi_mhz) / 2.0, (v.t0_s + v.t1_s) / 2.0),
+            [v.span_mhz() as f32, v.duration_s() as f32],
+        )
+    }
+
+    /// Рисует аннотации, попадающие в окно истории waterfall.
+    fn draw_annotations(
+        plot_ui: &mut PlotUi,
+        view: &SignalsView,
+    ) {
+        let color = view.annotation_color;
+
+        for a in &view.annotations {
+            Self::draw_region(plot_ui, &format!("ann_{}", a.id), a.from, a.to, color);
+            plot_ui.text(
+                Text::new(
+                    format!("ann_label_{}", a.id),
+                    PlotPoint::new(a.from[0], a.to[1]),
+                    a.label.clone(),
+                )
+                .color(color)
+                .anchor(egui::Align2::LEFT_BOTTOM),
+            );
+        }
+    }
+
+    /// Обрабатывает выделение области мышью на waterfall.
+    fn handle_selection(
+        plot_ui: &mut PlotUi,
+        sd: &SignalData,
+        draft: &mut AnnotationDraft,
+    ) {
+        let response = plot_ui.response();
+        let started = response.drag_started_by(PointerButton::Primary);
+        let dragging = response.dragged_by(PointerButton::Primary);
+        let stopped = response.drag_stopped_by(PointerButton::Primary);
+        let pointer = plot_ui.pointer_coordinate().map(|p| [p.x, p.y]);
+
+        if started {
+            draft.drag_from = pointer;
+            draft.drag_to = pointer;
+        } else if dragging && pointer.is_some() {
+            draft.drag_to = pointer;
+        }
+
+        if let (Some(from), Some(to)) = (draft.drag_from, draft.drag_to) {
+            Self::draw_region(plot_ui, "ann_draft", from, to, Color32::WHITE);
+        }
+
+        if !stopped {
+            return;
+        }
+
+        let (Some(from), Some(to)) = (draft.drag_from.take(), draft.drag_to.take()) else {
+            return;
+        };
+
+        // Случайный клик без перетаскивания — не аннотация
+        if (from[0] - to[0]).abs() < 1.0 && (from[1] - to[1]).abs() < 0.5 {
+            return;
+        }
+
+        if let (Some(t0), Some(t1)) = (sd.frame_to_ns(from[1]), sd.frame_to_ns(to[1])) {
+            draft.pending = Some(((t0, t1), (sd.bin_to_mhz(from[0]), sd.bin_to_mhz(to[0]))));
+        }
+    }
+
+    /// Панель измерения мощности и занятости в выбранной полосе.
+    fn render_channel_power(
+        ui: &mut egui::Ui,
+        channel: &ChannelView,
+        band: &mut BandDraft,
+        actions: &mut Vec<ChannelAction>,
+    ) {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(&channel.title).strong());
+                if ui.button("✖ Убрать полосу").clicked() {
+                    actions.push(ChannelAction::ClearBand);
+                }
+            });
+
+            ui.monospace(&channel.reading);
+
+            ui.horizontal(|ui| {
+                let mut threshold = channel.threshold_db;
+                ui.label("Порог занятости (дБ):");
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut threshold)
+                            .speed(0.5_f32)
+                            .range(-150.0..=50.0),
+                    )
+                    .changed()
+                {
+                    actions.push(ChannelAction::Threshold(threshold));
+                }
+                if ui.button("⟲ Сброс").clicked() {
+                    actions.push(ChannelAction::Reset);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("CSV:");
+                ui.add(egui::TextEdit::singleline(&mut band.csv_path).desired_width(260.0));
+                if ui
+                    .add_enabled(channel.can_export, egui::Button::new("💾 Экспорт"))
+                    .clicked()
+                {
+                    actions.push(ChannelAction::Export(PathBuf::from(&band.csv_path)));
+                }
             });
         });
     }
 
-    /// Преобразует мощность (дБ) в цвет (типа Virdis или Jet colormap)
+    fn apply_channel_action(
+        state: &mut AppState,
+        action: ChannelAction,
+    ) {
+        match action {
+            ChannelAction::SetBand(a, b) => state.channel_power.set_band(a, b),
+            ChannelAction::ClearBand => state.channel_power.clear_band(),
+            ChannelAction::Threshold(t) => state.channel_power.set_threshold_db(t),
+            ChannelAction::Reset => state.channel_power.reset(),
+            ChannelAction::Export(path) => {
+                let meter = &state.channel_power;
+                let msg = match DataExporter::export_channel_power_csv(
+                    meter.history(),
+                    meter.threshold_db(),
+                    &path,
+                ) {
+                    Ok(()) => format!(
+                        "Channel power ({} measurements) exported to {}",
+                        meter.history().len(),
+                        path.display()
+                    ),
+                    Err(e) => format!("Error exporting channel power: {e}"),
+                };
+                state.add_log(msg);
+            }
+        }
+    }
+
+    /// Выделение полосы перетаскиванием; возвращает границы (МГц) по
+    /// окончании.
+    fn handle_band_selection(
+        plot_ui: &mut PlotUi,
+        band: &mut BandDraft,
+    ) -> Option<(f64, f64)> {
+        let response = plot_ui.response();
+        let started = response.drag_started_by(PointerButton::Primary);
+        let dragging = response.dragged_by(PointerButton::Primary);
+        let stopped = response.drag_stopped_by(PointerButton::Primary);
+        let pointer = plot_ui.pointer_coordinate().map(|p| p.x);
+
+        if started {
+            band.drag_from = pointer;
+            band.drag_to = pointer;
+        } else if dragging && pointer.is_some() {
+            band.drag_to = pointer;
+        }
+
+        if !stopped {
+            return None;
+        }
+
+        let (from, to) = (band.drag_from.take()?, band.drag_to.take()?);
+        (from != to).then_some((from, to))
+    }
+
+    /// Закрашивает выбранную полосу (и выделяемую сейчас) и рисует порог
+    /// занятости.
+    fn draw_channel_band(
+        plot_ui: &mut PlotUi,
+        channel: Option<&ChannelView>,
+        band: &BandDraft,
+    ) {
+        let bounds = plot_ui.plot_bounds();
+        let (y0, y1) = (bounds.min()[1], bounds.max()[1]);
+        let color = Color32::from_rgb(0, 200, 200);
+
+        if let Some(channel) = channel {
+            let [lo, hi] = channel.band;
+            Self::draw_region(plot_ui, "channel_band", [lo, y0], [hi, y1], color);
+            plot_ui.hline(
+                HLine::new("Порог", channel.threshold_db)
+                    .color(color)
+                    .style(LineStyle::dashed_loose()),
+            );
+        }
+
+        if let (Some(a), Some(b)) = (band.drag_from, band.drag_to) {
+            Self::draw_region(plot_ui, "channel_draft", [a, y0], [b, y1], Color32::WHITE);
+        }
+    }
+
+    /// Ставит или убирает маркер по клику; `true`, если набор изменился.
+    fn handle_marker_clicks(
+        plot_ui: &mut PlotUi,
+        markers: &mut SpectrumMarkers,
+    ) -> bool {
+        let response = plot_ui.response();
+        let (primary, secondary) = (
+            response.clicked_by(PointerButton::Primary),
+            response.secondary_clicked(),
+        );
+
+        let Some(pointer) = plot_ui.pointer_coordinate() else {
+            return false;
+        };
+
+        if primary {
+            markers.place(pointer.x);
+        } else if secondary {
+            markers.remove_nearest(pointer.x);
+        }
+
+        primary || secondary
+    }
+
+    fn draw_markers(
+        plot_ui: &mut PlotUi,
+        markers: &[MarkerPoint],
+    ) {
+        let color = Color32::from_rgb(255, 255, 255);
+
+        for m in markers {
+            let [freq_mhz, power_db] = m.pos;
+
+            plot_ui.vline(
+                VLine::new(m.name.clone(), freq_mhz)
+                    .color(color.gamma_multiply(0.5))
+                    .width(1.0_f32),
+            );
+            plot_ui.points(
+                Points::new(m.name.clone(), vec![m.pos])
+                    .color(color)
+                    .shape(MarkerShape::Down)
+                    .radius(5.0_f32),
+            );
+            plot_ui.text(
+                Text::new(
+                    m.name.clone(),
+                    PlotPoint::new(freq_mhz, power_db + 3.0),
+                    m.name.clone(),
+                )
+                .color(color)
+                .anchor(egui::Align2::CENTER_BOTTOM),
+            );
+        }
+    }
+
+    fn draw_region(
+        plot_ui: &mut PlotUi,
+        name: &str,
+        a: [f64; 2],
+        b: [f64; 2],
+        color: Color32,
+    ) {
+        let corners = vec![[a[0], a[1]], [b[0], a[1]], [b[0], b[1]], [a[0], b[1]]];
+
+        plot_ui.polygon(
+            Polygon::new(name, PlotPoints::from(corners))
+                .fill_color(color.gamma_multiply(0.15))
+                .stroke(Stroke::new(1.5_f32, color)),
+        );
+    }
+
+    /// Преобразует мощность (дБ) в цвет выбранной цветовой карты
     #[allow(dead_code)]
     fn power_to_color(
         power_db: f32,
         min_db: f32,
         max_db: f32,
+        colormap: ColormapType,
     ) -> Color32 {
-        let normalized = ((power_db - min_db) / (max_db - min_db)).clamp(0.0, 1.0);
-
-        // Jet-like colormap: синий -> голубой -> зелёный -> жёлтый -> красный
-        let (r, g, b) = if normalized < 0.25 {
-            let t = normalized / 0.25;
-            (0.0, 255.0 * t, 255.0)
-        } else if normalized < 0.5 {
-            let t = (normalized - 0.25) / 0.25;
-            (0.0, 255.0, 255.0 * (1.0 - t))
-        } else if normalized < 0.75 {
-            let t = (normalized - 0.5) / 0.25;
-            (255.0 * t, 255.0, 0.0)
-        } else {
-            let t = (normalized - 0.75) / 0.25;
-            (255.0, 255.0 * (1.0 - t), 0.0)
-        };
-
-        Color32::from_rgb(
-            r.round().clamp(0.0, 255.0) as u8,
-            g.round().clamp(0.0, 255.0) as u8,
-            b.round().clamp(0.0, 255.0) as u8,
-        )
+        colormap.color((power_db - min_db) / (max_db - min_db))
     }
 
     /// Рисуем waterfall как текстуру (быстрее чем линии)
@@ -174,6 +874,7 @@ impl SignalPanel {
     fn render_waterfall_texture(
         ui: &mut egui::Ui,
         waterfall: &std::collections::VecDeque<Vec<f32>>,
+        colormap: ColormapType,
     ) {
         if waterfall.is_empty() {
             return;
@@ -197,7 +898,7 @@ impl SignalPanel {
         let mut rgba: Vec<u8> = Vec::with_capacity(width * height * 4);
         for row in waterfall.iter() {
             for &power in row.iter() {
-                let color = Self::power_to_color(power, min_power, max_power);
+                let color = Self::power_to_color(power, min_power, max_power, colormap);
                 let [r, g, b, a] = color.to_array(); // Color32 -> [u8;4]
                 rgba.push(r);
                 rgba.push(g);
@@ -47,3 +47,19 @@ pub fn configure_style(ctx: &Context) {
 
     ctx.set_style(style);
 }
+
+/// Масштабирует все текстовые стили относительно размеров egui по умолчанию.
+pub fn apply_font_scale(
+    ctx: &Context,
+    scale: f32,
+) {
+    let base = Style::default().text_styles;
+
+    ctx.style_mut(|style| {
+        for (text_style, font) in style.text_styles.iter_mut() {
+            if let Some(default) = base.get(text_style) {
+                font.size = default.size * scale;
+            }
+        }
+    });
+}
@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use egui::Color32;
+use serde::Serialize;
+
+use super::serialize_color;
+use crate::{panels::SignalQuality, AppState};
+
+/// Строка таблицы аннотаций.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnnotationRow {
+    pub id: u64,
+    pub label: String,
+    /// Начало (UTC) и длительность
+    pub time: String,
+    /// Полоса частот
+    pub band: String,
+}
+
+/// Модель панели аннотаций.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnnotationsView {
+    pub path: String,
+    /// Есть несохранённые изменения
+    pub dirty: bool,
+    #[serde(serialize_with = "serialize_color")]
+    pub dirty_color: Color32,
+    pub rows: Vec<AnnotationRow>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl AnnotationsView {
+    pub fn from_state(state: &AppState) -> Self {
+        let rows = state
+            .annotations
+            .iter()
+            .map(|a| AnnotationRow {
+                id: a.id,
+                label: a.label.clone(),
+                time: format!("{} (+{:.3} с)", format_ns(a.start_ns), a.duration_secs()),
+                band: format!("{:.4}–{:.4} МГц", a.freq_lo_mhz, a.freq_hi_mhz),
+            })
+            .collect();
+
+        Self {
+            path: state.annotations_path.display().to_string(),
+            dirty: state.annotations.is_dirty(),
+            dirty_color: state.settings.palette.quality_color(SignalQuality::Fair),
+            rows,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+fn format_ns(ns: u64) -> String {
+    DateTime::<Utc>::from_timestamp_nanos(ns as i64)
+        .format("%H:%M:%S%.3f")
+        .to_string()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_annotations_snapshot() {
+        assert_snapshot("annotations", &AnnotationsView::from_state(&sample_state()));
+    }
+}
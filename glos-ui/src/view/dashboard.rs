@@ -0,0 +1,153 @@
+use egui::Color32;
+use serde::Serialize;
+
+use super::serialize_color;
+use crate::AppState;
+
+/// Карточка метрики на панели мониторинга.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricCard {
+    pub icon: &'static str,
+    pub label: String,
+    pub value: String,
+}
+
+/// Модель панели мониторинга.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DashboardView {
+    /// Спутники, решение, CN0, HDOP
+    pub summary: Vec<MetricCard>,
+    /// Положение, высота, скорость, загрузка ЦП
+    pub position: Vec<MetricCard>,
+    /// По карточке на созвездие
+    pub constellations: Vec<MetricCard>,
+    /// Точки графика CN0: номер отсчёта и значение
+    pub cn0_history: Vec<[f64; 2]>,
+    #[serde(serialize_with = "serialize_color")]
+    pub cn0_color: Color32,
+    /// Строки блока системных метрик
+    pub system: Vec<String>,
+    /// Строки блока параметров сигнала
+    pub signal: Vec<String>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl MetricCard {
+    fn new(
+        icon: &'static str,
+        label: impl Into<String>,
+        value: String,
+    ) -> Self {
+        Self {
+            icon,
+            label: label.into(),
+            value,
+        }
+    }
+}
+
+impl DashboardView {
+    pub fn from_state(state: &AppState) -> Self {
+        let summary = vec![
+            MetricCard::new("🛰", "Спутники", state.satellite_count().to_string()),
+            MetricCard::new(
+                "✓",
+                "Используются в решении",
+                state.used_satellites().to_string(),
+            ),
+            MetricCard::new("📡", "Средний CN0", format!("{:.1} дБГц", state.avg_cn0())),
+            MetricCard::new("🎯", "Гор. точность (HDOP)", format!("{:.2}", state.hdop)),
+        ];
+
+        let position = vec![
+            MetricCard::new(
+                "🌍",
+                "Положение",
+                format!("{:.4}°N\n{:.4}°E", state.position_lat, state.position_lon),
+            ),
+            MetricCard::new("⛰", "Высота", format!("{:.1} м", state.altitude)),
+            MetricCard::new("💨", "Скорость", format!("{:.2} м/с", state.velocity)),
+            MetricCard::new(
+                "💻",
+                "Загрузка ЦП",
+                format!("{:.1}%", state.metrics.cpu_usage),
+            ),
+        ];
+
+        let constellations = state
+            .constellation_stats()
+            .into_iter()
+            .map(|s| {
+                MetricCard::new(
+                    "🛰",
+                    s.constellation,
+                    format!(
+                        "{} / {} в решении\nCN0 {:.1} (макс {:.1})\nУгол места {:.0}°",
+                        s.count, s.used_in_fix, s.mean_cn0, s.max_cn0, s.mean_elevation
+                    ),
+                )
+            })
+            .collect();
+
+        let cn0_history = state
+            .cn0_history
+            .iter()
+            .enumerate()
+            .map(|(i, (_, cn0))| [i as f64, *cn0 as f64])
+            .collect();
+
+        let system = vec![
+            format!("Полоса пропускания: {:.1} МГц", state.metrics.bandwidth_mhz),
+            format!("Буфер: {:.1}%", state.metrics.buffer_usage),
+            format!("Пакеты/с: {}", state.metrics.packets_per_sec),
+        ];
+
+        let signal = vec![
+            format!(
+                "Центральная частота: {:.2} МГц",
+                state.signal_data.frequency_mhz
+            ),
+            format!(
+                "Частота дискретизации: {:.1} МГц",
+                state.signal_data.sample_rate_mhz
+            ),
+            format!("Размер FFT: {}", state.signal_data.fft_data.len()),
+        ];
+
+        Self {
+            summary,
+            position,
+            constellations,
+            cn0_history,
+            cn0_color: state.settings.palette.series_color(1),
+            system,
+            signal,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_dashboard_snapshot() {
+        assert_snapshot("dashboard", &DashboardView::from_state(&sample_state()));
+    }
+
+    #[test]
+    fn test_dashboard_empty_state_snapshot() {
+        assert_snapshot(
+            "dashboard_empty",
+            &DashboardView::from_state(&AppState::default()),
+        );
+    }
+}
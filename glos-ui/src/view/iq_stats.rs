@@ -0,0 +1,154 @@
+use egui::Color32;
+use serde::Serialize;
+
+use super::serialize_color;
+use crate::{
+    data::{ComponentStats, IqStats, IQ_HISTOGRAM_BINS},
+    AppState,
+};
+
+/// Клиппинг, с которого строка подсвечивается предупреждением, %.
+const CLIPPING_WARN_PERCENT: f64 = 0.1;
+
+/// Занятость одного разряда модуля кода.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BitOccupancyRow {
+    pub label: String,
+    /// Доля выборок с установленным разрядом, [0, 1]
+    pub fraction: f32,
+    pub text: String,
+}
+
+/// Гистограмма и сводка одной компоненты (I или Q).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IqComponentView {
+    pub name: &'static str,
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+    /// Непустые бины гистограммы: центр (доля шкалы) и число выборок
+    pub bars: Vec<[f64; 2]>,
+    /// Среднее и СКО
+    pub summary: Vec<String>,
+    pub clipping: String,
+    pub clipping_warn: bool,
+    /// Сколько разрядов используется; `None` для Float32
+    pub used_bits: Option<String>,
+    /// Занятость разрядов, от старшего к младшему
+    pub bit_occupancy: Vec<BitOccupancyRow>,
+}
+
+/// Модель панели статистики IQ.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IqStatsView {
+    pub header: String,
+    /// Ширина столбца гистограммы в долях шкалы
+    pub bar_width: f64,
+    pub components: Vec<IqComponentView>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl IqStatsView {
+    /// `None`, пока статистика не посчитана: нет ни живого потока, ни
+    /// открытой записи.
+    pub fn from_state(state: &AppState) -> Option<Self> {
+        let stats = state.iq_stats.as_ref()?;
+        let palette = state.settings.palette;
+
+        let components = [("I", &stats.i), ("Q", &stats.q)]
+            .into_iter()
+            .enumerate()
+            .map(|(k, (name, c))| {
+                IqComponentView::new(name, palette.series_color(k), c, stats.samples)
+            })
+            .collect();
+
+        Some(Self {
+            header: format!(
+                "Источник: {} | {} | {} выборок",
+                stats.source, stats.format, stats.samples
+            ),
+            bar_width: 2.0 / IQ_HISTOGRAM_BINS as f64,
+            components,
+        })
+    }
+}
+
+impl IqComponentView {
+    fn new(
+        name: &'static str,
+        color: Color32,
+        c: &ComponentStats,
+        samples: u64,
+    ) -> Self {
+        let bars = c
+            .histogram
+            .iter()
+            .enumerate()
+            .filter(|(_, &n)| n > 0)
+            .map(|(bin, &n)| [IqStats::bin_center(bin), n as f64])
+            .collect();
+
+        let clipping = c.clipping_percent(samples);
+
+        let bit_occupancy = c
+            .bit_occupancy
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(k, p)| BitOccupancyRow {
+                label: format!("b{k:<2}"),
+                fraction: *p as f32,
+                text: format!("{:.1}%", p * 100.0),
+            })
+            .collect();
+
+        Self {
+            name,
+            color,
+            bars,
+            summary: vec![
+                format!("Среднее:  {:+.4}", c.mean),
+                format!("СКО:      {:.4}", c.std),
+            ],
+            clipping: format!("Клиппинг: {clipping:.3}%"),
+            clipping_warn: clipping > CLIPPING_WARN_PERCENT,
+            used_bits: c
+                .used_bits()
+                .map(|used| format!("Разрядов: {used} из {}", c.bit_occupancy.len() + 1)),
+            bit_occupancy,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use glos_types::IqFormat;
+    use rustfft::num_complex::Complex32;
+
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_iq_stats_snapshot() {
+        let mut state = sample_state();
+        let samples: Vec<Complex32> = [(-128, 3), (-3, 0), (0, -5), (5, 127), (127, 3), (3, 0)]
+            .iter()
+            .map(|&(i, q)| Complex32::new(i as f32 / 128.0, q as f32 / 128.0))
+            .collect();
+        state.iq_stats = Some(IqStats::compute("session.glos", &samples, IqFormat::Int8));
+
+        assert_snapshot("iq_stats", &IqStatsView::from_state(&state).unwrap());
+    }
+
+    #[test]
+    fn test_iq_stats_empty_state() {
+        assert_eq!(IqStatsView::from_state(&sample_state()), None);
+    }
+}
@@ -0,0 +1,90 @@
+use egui::Color32;
+use serde::Serialize;
+
+use super::serialize_color;
+use crate::{
+    panels::{CategoricalPalette, SignalQuality},
+    AppState,
+};
+
+/// Строка журнала.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogLine {
+    /// Время `ЧЧ:ММ:СС.ммм` (UTC)
+    pub time: String,
+    pub message: String,
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+}
+
+/// Модель системного журнала.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogsView {
+    pub total: usize,
+    /// Новые сообщения первыми
+    pub lines: Vec<LogLine>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl LogsView {
+    pub fn from_state(state: &AppState) -> Self {
+        let palette = state.settings.palette;
+
+        let lines = state
+            .log_messages
+            .iter()
+            .rev()
+            .map(|(timestamp, message)| LogLine {
+                time: timestamp.format("%H:%M:%S%.3f").to_string(),
+                message: message.clone(),
+                color: message_color(palette, message),
+            })
+            .collect();
+
+        Self {
+            total: state.log_messages.len(),
+            lines,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Подсветка сообщения по ключевым словам.
+fn message_color(
+    palette: CategoricalPalette,
+    message: &str,
+) -> Color32 {
+    if message.contains("error") || message.contains("Error") {
+        palette.quality_color(SignalQuality::Poor)
+    } else if message.contains("warning") || message.contains("Warning") {
+        palette.quality_color(SignalQuality::Fair)
+    } else if message.contains("started") || message.contains("acquired") {
+        palette.quality_color(SignalQuality::Good)
+    } else {
+        Color32::from_rgb(220, 220, 220)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_logs_snapshot() {
+        let mut state = sample_state();
+        state.settings.palette = CategoricalPalette::OkabeIto;
+
+        assert_snapshot("logs", &LogsView::from_state(&state));
+    }
+}
@@ -0,0 +1,209 @@
+//! Модели представления панелей.
+//!
+//! Панель сначала собирает из [`AppState`](crate::AppState) модель — чистой
+//! функцией, без `egui::Ui` и блокировок, — а затем только рисует её. Модели
+//! сериализуются в JSON, и тесты сверяют их со снимками в
+//! `src/view/snapshots/`: регрессии в данных ловятся без запуска GUI.
+//! Намеренные изменения фиксируются запуском
+//! `UPDATE_SNAPSHOTS=1 cargo test -p glos-ui`.
+
+pub mod annotations;
+pub mod dashboard;
+pub mod iq_stats;
+pub mod logs;
+pub mod satellites;
+pub mod settings;
+pub mod signals;
+
+pub use annotations::*;
+pub use dashboard::*;
+use egui::Color32;
+pub use iq_stats::*;
+pub use logs::*;
+pub use satellites::*;
+use serde::Serializer;
+pub use settings::*;
+pub use signals::*;
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Цвет в снимке: `#rrggbb`, с прозрачностью — `#rrggbbaa`.
+fn serialize_color<S: Serializer>(
+    color: &Color32,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let [r, g, b, a] = color.to_array();
+    let hex = if a == u8::MAX {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    };
+
+    serializer.serialize_str(&hex)
+}
+
+/// Координаты в снимке с точностью 1e-4: последние биты `sin`/`cos`
+/// зависят от платформы, на экране эта разница меньше пикселя.
+fn serialize_point<S: Serializer>(
+    point: &[f64; 2],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeTuple;
+
+    let mut tuple = serializer.serialize_tuple(2)?;
+    for v in point {
+        tuple.serialize_element(&((v * 1e4).round() / 1e4 + 0.0))?;
+    }
+    tuple.end()
+}
+
+/// Ломаная или набор точек в снимке, с той же точностью, что и
+/// [`serialize_point`].
+fn serialize_points<S: Serializer>(
+    points: &[[f64; 2]],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::{ser::SerializeSeq, Serialize};
+
+    #[derive(Serialize)]
+    struct Point<'a>(#[serde(serialize_with = "serialize_point")] &'a [f64; 2]);
+
+    let mut seq = serializer.serialize_seq(Some(points.len()))?;
+    for point in points {
+        seq.serialize_element(&Point(point))?;
+    }
+    seq.end()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+/// Снимки моделей и общее для них состояние.
+#[cfg(test)]
+pub(crate) mod snapshot {
+    use std::{fs, path::PathBuf};
+
+    use chrono::{DateTime, TimeDelta, Utc};
+    use serde::Serialize;
+
+    use crate::data::{AppState, Satellite, SystemMetrics};
+
+    /// Начало фиктивной сессии: 2023-11-14 22:13:20 UTC.
+    pub fn t0() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn sat(
+        id: &str,
+        constellation: &str,
+        cn0: f32,
+        elevation: f32,
+        azimuth: f32,
+        doppler: f32,
+        used_in_fix: bool,
+    ) -> Satellite {
+        Satellite {
+            id: id.into(),
+            constellation: constellation.into(),
+            cn0,
+            elevation,
+            azimuth,
+            doppler,
+            used_in_fix,
+        }
+    }
+
+    /// Состояние без случайности и без `Utc::now()`: одно и то же при
+    /// каждом запуске.
+    pub fn sample_state() -> AppState {
+        let mut state = AppState::default();
+
+        let earlier = vec![
+            sat("G01", "GPS", 41.0, 55.0, 80.0, -1_100.0, true),
+            sat("R03", "ГЛОНАСС", 30.5, 35.0, 300.0, 2_300.0, false),
+        ];
+        state.sky_history.push(t0(), &earlier);
+
+        state.satellites = vec![
+            sat("G01", "GPS", 45.5, 60.0, 90.0, -1_250.0, true),
+            sat("G07", "GPS", 32.0, 15.0, 200.0, 830.0, false),
+            sat("R03", "ГЛОНАСС", 38.5, 40.0, 315.0, 2_100.0, true),
+            sat("E11", "Галилео", 28.0, 10.0, 45.0, -300.0, false),
+        ];
+        state
+            .sky_history
+            .push(t0() + TimeDelta::seconds(60), &state.satellites);
+
+        state.metrics = SystemMetrics {
+            cpu_usage: 37.5,
+            bandwidth_mhz: 4.0,
+            buffer_usage: 12.25,
+            packets_per_sec: 980,
+        };
+        state.velocity = 1.25;
+
+        for (i, cn0) in [40.0, 41.5, 43.0, 36.0].into_iter().enumerate() {
+            state
+                .cn0_history
+                .push_back((t0() + TimeDelta::seconds(i as i64), cn0));
+        }
+
+        for (ms, message) in [
+            (0, "Recording started"),
+            (250, "Satellite G01 acquired"),
+            (1_500, "Warning: buffer usage above 80%"),
+            (2_125, "Error reading device: timeout"),
+            (3_000, "Annotations saved to session.json"),
+        ] {
+            state
+                .log_messages
+                .push_back((t0() + TimeDelta::milliseconds(ms), message.into()));
+        }
+
+        state.annotations_path = "session.glos.annotations.json".into();
+        state.annotations.add(
+            "Помеха L1",
+            (1_700_000_002_000_000_000, 1_700_000_004_500_000_000),
+            (1575.1, 1575.9),
+        );
+        state.annotations.add(
+            "Пропуск",
+            (1_700_000_000_500_000_000, 1_700_000_000_750_000_000),
+            (1574.0, 1577.0),
+        );
+
+        state
+    }
+
+    /// Сверяет модель со снимком `snapshots/<name>.json`. С
+    /// `UPDATE_SNAPSHOTS=1` снимок перезаписывается.
+    pub fn assert_snapshot(
+        name: &str,
+        view: &impl Serialize,
+    ) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/view/snapshots")
+            .join(format!("{name}.json"));
+        let actual = serde_json::to_string_pretty(view).unwrap() + "\n";
+
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "snapshot {}: {e}; run with UPDATE_SNAPSHOTS=1 to create it",
+                path.display()
+            )
+        });
+        assert!(
+            actual == expected,
+            "snapshot {name} changed; review and rerun with UPDATE_SNAPSHOTS=1 if intended\n\
+             --- expected\n{expected}\n+++ actual\n{actual}"
+        );
+    }
+}
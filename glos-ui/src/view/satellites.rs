@@ -0,0 +1,220 @@
+use egui::Color32;
+use serde::Serialize;
+
+use super::{serialize_color, serialize_point};
+use crate::{
+    data::Satellite,
+    panels::{MarkerShape, SignalQuality, UiSettings},
+    AppState,
+};
+
+/// Строка таблицы спутников.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SatelliteRow {
+    pub id: String,
+    pub constellation: String,
+    #[serde(serialize_with = "serialize_color")]
+    pub constellation_color: Color32,
+    pub cn0: String,
+    #[serde(serialize_with = "serialize_color")]
+    pub cn0_color: Color32,
+    pub elevation: String,
+    pub azimuth: String,
+    pub doppler: String,
+    pub used_in_fix: bool,
+}
+
+/// Модель таблицы спутников.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SatellitesView {
+    pub summary: String,
+    pub rows: Vec<SatelliteRow>,
+    /// Цвет отметки «в решении»
+    #[serde(serialize_with = "serialize_color")]
+    pub fix_color: Color32,
+}
+
+/// Точка спутника на полярной диаграмме.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkyPoint {
+    pub id: String,
+    /// Координаты в единичном круге: зенит в центре, север сверху
+    #[serde(serialize_with = "serialize_point")]
+    pub pos: [f64; 2],
+    pub radius: f32,
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+    /// Форма по созвездию, при любой раскраске
+    pub marker: MarkerShape,
+}
+
+/// Элемент легенды полярной диаграммы.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LegendEntry {
+    pub name: &'static str,
+    pub code: &'static str,
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+    pub marker: MarkerShape,
+}
+
+/// Модель полярной диаграммы для текущей эпохи или эпохи из истории.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkyPlotView {
+    pub caption: String,
+    pub points: Vec<SkyPoint>,
+    pub legend: Vec<LegendEntry>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SatellitesView {
+    pub fn from_state(state: &AppState) -> Self {
+        let palette = state.settings.palette;
+
+        let rows = state
+            .visible_satellites()
+            .map(|sat| SatelliteRow {
+                id: sat.id.clone(),
+                constellation: sat.constellation.clone(),
+                constellation_color: palette.constellation_color(&sat.constellation),
+                cn0: format!("{:.1}", sat.cn0),
+                cn0_color: palette.quality_color(SignalQuality::from_cn0(sat.cn0)),
+                elevation: format!("{:.0}°", sat.elevation),
+                azimuth: format!("{:.0}°", sat.azimuth),
+                doppler: format!("{:.0} Гц", sat.doppler),
+                used_in_fix: sat.used_in_fix,
+            })
+            .collect();
+
+        Self {
+            summary: format!(
+                "Всего: {} | Используются в решении: {}",
+                state.satellite_count(),
+                state.used_satellites(),
+            ),
+            rows,
+            fix_color: palette.quality_color(SignalQuality::Good),
+        }
+    }
+}
+
+impl SkyPlotView {
+    /// Диаграмма эпохи `seq` истории неба (`None` или отсутствующая в
+    /// истории эпоха — текущие спутники).
+    pub fn from_state(
+        state: &AppState,
+        seq: Option<u64>,
+        color_by_cn0: bool,
+    ) -> Self {
+        let settings = &state.settings;
+        let epoch = seq.and_then(|seq| state.sky_history.get(seq));
+
+        let satellites: Vec<&Satellite> = match epoch {
+            Some(e) => e
+                .satellites
+                .iter()
+                .filter(|s| settings.constellations.allows(&s.constellation))
+                .collect(),
+            None => state.visible_satellites().collect(),
+        };
+
+        let caption = match epoch {
+            Some(e) => format!("Высота vs Азимут, {} UTC", e.time.format("%H:%M:%S")),
+            None => "Высота vs Азимут".to_string(),
+        };
+
+        let points = satellites
+            .into_iter()
+            .map(|sat| {
+                let radius = (90.0 - sat.elevation) / 90.0;
+                let azimuth_rad = sat.azimuth.to_radians();
+
+                SkyPoint {
+                    id: sat.id.clone(),
+                    pos: [
+                        (radius * azimuth_rad.sin()) as f64,
+                        (radius * azimuth_rad.cos()) as f64,
+                    ],
+                    radius: if sat.used_in_fix { 8.0 } else { 4.0 },
+                    color: point_color(settings, color_by_cn0, sat),
+                    marker: MarkerShape::for_constellation(&sat.constellation),
+                }
+            })
+            .collect();
+
+        let legend = settings
+            .constellations
+            .enabled()
+            .map(|c| {
+                let style = settings.palette.constellation_style(c);
+                LegendEntry {
+                    name: style.name,
+                    code: style.code,
+                    color: style.color,
+                    marker: style.marker,
+                }
+            })
+            .collect();
+
+        Self {
+            caption,
+            points,
+            legend,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Цвет точки спутника: по созвездию или по качеству C/N0.
+pub fn point_color(
+    settings: &UiSettings,
+    by_cn0: bool,
+    sat: &Satellite,
+) -> Color32 {
+    if by_cn0 {
+        settings
+            .palette
+            .quality_color(SignalQuality::from_cn0(sat.cn0))
+    } else {
+        settings.palette.constellation_color(&sat.constellation)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_satellites_snapshot() {
+        let mut state = sample_state();
+        state.settings.constellations.galileo = false;
+
+        assert_snapshot("satellites", &SatellitesView::from_state(&state));
+    }
+
+    #[test]
+    fn test_sky_plot_snapshots() {
+        let state = sample_state();
+        let (first, _) = state.sky_history.seq_range().unwrap();
+
+        assert_snapshot(
+            "sky_plot_current",
+            &SkyPlotView::from_state(&state, None, false),
+        );
+        assert_snapshot(
+            "sky_plot_history_by_cn0",
+            &SkyPlotView::from_state(&state, Some(first), true),
+        );
+    }
+}
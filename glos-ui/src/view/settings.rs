@@ -0,0 +1,126 @@
+use serde::Serialize;
+
+use crate::panels::{CategoricalPalette, ColormapType, Constellation, UiSettings};
+
+/// Размеры FFT, которые можно выбрать в настройках.
+const FFT_WINDOW_SIZES: [usize; 4] = [256, 512, 1024, 2048];
+
+/// Вариант выпадающего списка.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingsOption<T> {
+    pub value: T,
+    pub label: String,
+}
+
+/// Выпадающий список: подпись выбранного значения и варианты.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingsChoice<T> {
+    pub selected: String,
+    pub options: Vec<SettingsOption<T>>,
+}
+
+/// Переключатель созвездия.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConstellationToggle {
+    pub constellation: Constellation,
+    pub label: &'static str,
+    pub enabled: bool,
+}
+
+/// Модель панели настроек.
+///
+/// Панель правит копию настроек (см. `GlosApp::render_settings`), поэтому
+/// модель строится из неё, а не из [`AppState`](crate::AppState).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingsView {
+    pub palette: SettingsChoice<CategoricalPalette>,
+    pub fft_size: SettingsChoice<usize>,
+    pub colormap: SettingsChoice<ColormapType>,
+    pub constellations: Vec<ConstellationToggle>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl<T> SettingsChoice<T> {
+    fn new(
+        selected: String,
+        options: impl IntoIterator<Item = T>,
+        label: impl Fn(&T) -> String,
+    ) -> Self {
+        let options = options
+            .into_iter()
+            .map(|value| SettingsOption {
+                label: label(&value),
+                value,
+            })
+            .collect();
+
+        Self { selected, options }
+    }
+}
+
+impl SettingsView {
+    pub fn from_settings(settings: &UiSettings) -> Self {
+        let constellations = Constellation::ALL
+            .into_iter()
+            .map(|c| ConstellationToggle {
+                constellation: c,
+                label: c.name(),
+                enabled: settings.constellations.is_enabled(c),
+            })
+            .collect();
+
+        Self {
+            palette: SettingsChoice::new(
+                settings.palette.name().to_string(),
+                CategoricalPalette::ALL,
+                |p| p.name().to_string(),
+            ),
+            fft_size: SettingsChoice::new(
+                settings.fft_window_size.to_string(),
+                FFT_WINDOW_SIZES,
+                |n| n.to_string(),
+            ),
+            colormap: SettingsChoice::new(
+                settings.waterfall_colormap.name().to_string(),
+                ColormapType::ALL,
+                |cm| cm.name().to_string(),
+            ),
+            constellations,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_settings_snapshot() {
+        assert_snapshot(
+            "settings",
+            &SettingsView::from_settings(&sample_state().settings),
+        );
+    }
+
+    #[test]
+    fn test_settings_colorblind_preset_snapshot() {
+        let mut settings = sample_state().settings;
+        settings.palette = CategoricalPalette::OkabeIto;
+        settings.waterfall_colormap = ColormapType::Cividis;
+        settings.fft_window_size = 2048;
+        settings.constellations.beidou = false;
+
+        assert_snapshot(
+            "settings_colorblind",
+            &SettingsView::from_settings(&settings),
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use egui::Color32;
+use serde::Serialize;
+
+use super::{serialize_color, serialize_point, serialize_points};
+use crate::{data::SignalData, AppState};
+
+/// Линия спектра: текущий кадр или один из следов.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectrumTrace {
+    pub name: &'static str,
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+    pub width: f32,
+    /// Частота (МГц) и мощность (дБ)
+    #[serde(serialize_with = "serialize_points")]
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Маркер на спектре.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarkerPoint {
+    pub name: String,
+    /// Частота (МГц) и мощность (дБ)
+    #[serde(serialize_with = "serialize_point")]
+    pub pos: [f64; 2],
+}
+
+/// Измерение мощности в выбранной полосе.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChannelView {
+    /// Границы полосы, МГц
+    pub band: [f64; 2],
+    pub threshold_db: f32,
+    pub title: String,
+    pub reading: String,
+    /// Есть измерения для экспорта в CSV
+    pub can_export: bool,
+}
+
+/// Строка водопада, нарисованная линией со смещением по мощности.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WaterfallLine {
+    #[serde(serialize_with = "serialize_color")]
+    pub color: Color32,
+    /// Бин частоты и номер кадра со смещением по мощности
+    #[serde(serialize_with = "serialize_points")]
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Аннотация в координатах водопада (бин, кадр).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WaterfallRegion {
+    pub id: u64,
+    pub label: String,
+    #[serde(serialize_with = "serialize_point")]
+    pub from: [f64; 2],
+    #[serde(serialize_with = "serialize_point")]
+    pub to: [f64; 2],
+}
+
+/// Модель панели просмотра сигнала.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignalsView {
+    pub header: String,
+    pub frames: String,
+    /// Текущий кадр FFT и включённые следы, в порядке отрисовки
+    pub traces: Vec<SpectrumTrace>,
+    pub markers: Vec<MarkerPoint>,
+    /// Показания маркеров и разность M2 − M1
+    pub marker_readout: Vec<String>,
+    pub channel: Option<ChannelView>,
+    pub waterfall_caption: String,
+    pub waterfall: Vec<WaterfallLine>,
+    pub annotations: Vec<WaterfallRegion>,
+    #[serde(serialize_with = "serialize_color")]
+    pub annotation_color: Color32,
+    /// Строки блока статистики сигнала
+    pub statistics: Vec<String>,
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Собственные методы
+////////////////////////////////////////////////////////////////////////////////
+
+impl SignalsView {
+    pub fn from_state(state: &AppState) -> Self {
+        let sd = &state.signal_data;
+        let settings = &state.settings;
+        let palette = settings.palette;
+
+        let trace = |name, index, width, data: &[f32]| SpectrumTrace {
+            name,
+            color: palette.series_color(index),
+            width,
+            points: spectrum_points(sd, data),
+        };
+
+        let mut traces = vec![trace("FFT", 0, 1.5, &sd.fft_data)];
+        if !sd.traces.is_empty() {
+            if settings.show_min_hold {
+                traces.push(trace("Min-hold", 4, 1.0, &sd.traces.min_hold));
+            }
+            if settings.show_peak_hold {
+                traces.push(trace("Peak-hold", 3, 1.0, &sd.traces.peak_hold));
+            }
+            if settings.show_fft_average {
+                traces.push(trace("Среднее", 2, 2.0, &sd.traces.average));
+            }
+        }
+
+        let readings = state.markers.readings(sd);
+        let markers = readings
+            .iter()
+            .map(|(i, r)| MarkerPoint {
+                name: format!("M{}", i + 1),
+                pos: [r.freq_mhz, r.power_db as f64],
+            })
+            .collect();
+
+        let mut marker_readout: Vec<String> = readings
+            .iter()
+            .map(|(i, r)| format!("M{}: {:.4} МГц  {:.1} дБ", i + 1, r.freq_mhz, r.power_db))
+            .collect();
+        if let Some((df, dp)) = state.markers.delta(sd) {
+            marker_readout.push(format!(
+                "ΔM2−M1: {:+.4} МГц ({:+.1} кГц)  {:+.1} дБ",
+                df,
+                df * 1e3,
+                dp
+            ));
+        }
+
+        let waterfall_size = sd.waterfall.len();
+        let colormap = settings.waterfall_colormap;
+        let waterfall = sd
+            .waterfall
+            .iter()
+            .enumerate()
+            .map(|(time_idx, row)| WaterfallLine {
+                color: colormap.color(time_idx as f32 / waterfall_size as f32),
+                points: row
+                    .iter()
+                    .enumerate()
+                    // Мощность сдвигает строку вверх, чтобы рельеф был виден
+                    .map(|(freq_idx, power)| {
+                        [freq_idx as f64, time_idx as f64 + (*power as f64) / 20.0]
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let waterfall_caption = if waterfall_size > 0 {
+            format!("История: {waterfall_size} кадров")
+        } else {
+            "Данные водопада отсутствуют".to_string()
+        };
+
+        Self {
+            header: format!(
+                "Центральная частота: {:.2} МГц | Частота дискретизации: {:.1} МГц",
+                sd.frequency_mhz, sd.sample_rate_mhz
+            ),
+            frames: format!("Кадров: {}", sd.traces.frames),
+            traces,
+            markers,
+            marker_readout,
+            channel: ChannelView::from_state(state),
+            waterfall_caption,
+            waterfall,
+            annotations: waterfall_regions(state),
+            annotation_color: Color32::from_rgb(255, 200, 0),
+            statistics: statistics(&sd.fft_data),
+        }
+    }
+}
+
+impl ChannelView {
+    fn from_state(state: &AppState) -> Option<Self> {
+        let meter = &state.channel_power;
+        let (lo, hi) = meter.band()?;
+
+        let reading = match meter.latest() {
+            Some(m) => format!(
+                "Мощность: {:.1} дБ  Пик: {:.1} дБ  Занятость: {:.1}% ({} кадров)",
+                m.power_db,
+                m.peak_db,
+                meter.occupancy().unwrap_or(0.0) * 100.0,
+                meter.frames()
+            ),
+            None => "Нет бинов спектра в полосе".to_string(),
+        };
+
+        Some(Self {
+            band: [lo, hi],
+            threshold_db: meter.threshold_db(),
+            title: format!("Канал {lo:.4}–{hi:.4} МГц ({:.1} кГц)", (hi - lo) * 1e3),
+            reading,
+            can_export: !meter.history().is_empty(),
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Вспомогательные функции
+////////////////////////////////////////////////////////////////////////////////
+
+/// Точки спектра: бин `i` из `data.len()` — на своей частоте в полосе.
+fn spectrum_points(
+    sd: &SignalData,
+    data: &[f32],
+) -> Vec<[f64; 2]> {
+    data.iter()
+        .enumerate()
+        .map(|(i, power)| {
+            let freq = (i as f32 / data.len() as f32 - 0.5) * sd.sample_rate_mhz + sd.frequency_mhz;
+            [freq as f64, *power as f64]
+        })
+        .collect()
+}
+
+/// Аннотации, попадающие в окно истории водопада.
+fn waterfall_regions(state: &AppState) -> Vec<WaterfallRegion> {
+    let sd = &state.signal_data;
+    let Some((span_start, span_end)) = sd.waterfall_span_ns() else {
+        return Vec::new();
+    };
+
+    state
+        .annotations
+        .iter()
+        .filter(|a| a.overlaps(span_start, span_end))
+        .filter_map(|a| {
+            let (y0, y1) = (sd.ns_to_frame(a.start_ns)?, sd.ns_to_frame(a.end_ns)?);
+
+            Some(WaterfallRegion {
+                id: a.id,
+                label: a.label.clone(),
+                from: [sd.mhz_to_bin(a.freq_lo_mhz), y0],
+                to: [sd.mhz_to_bin(a.freq_hi_mhz), y1],
+            })
+        })
+        .collect()
+}
+
+/// Максимум, минимум, среднее и динамический диапазон кадра.
+fn statistics(fft_data: &[f32]) -> Vec<String> {
+    let max_power = fft_data.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let min_power = fft_data.iter().copied().fold(f32::INFINITY, f32::min);
+    let avg_power = fft_data.iter().sum::<f32>() / fft_data.len() as f32;
+
+    vec![
+        format!("Макс: {max_power:.1} дБ"),
+        format!("Мин: {min_power:.1} дБ"),
+        format!("Среднее: {avg_power:.1} дБ"),
+        format!("Динамический диапазон: {:.1} дБ", max_power - min_power),
+    ]
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Тесты
+////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::view::snapshot::{assert_snapshot, sample_state};
+
+    #[test]
+    fn test_signals_snapshot() {
+        let mut state = sample_state();
+        state.signal_data = SignalData::new(1575.42, 4.0, 8);
+        state.settings.show_fft_average = true;
+        state.settings.show_peak_hold = true;
+        state.channel_power.set_band(1575.0, 1576.0);
+
+        let frames = [
+            [-90.0, -85.0, -70.0, -40.0, -45.0, -72.0, -88.0, -91.0],
+            [-92.0, -80.0, -65.0, -42.0, -50.0, -70.0, -86.0, -90.0],
+            [-89.0, -84.0, -75.0, -55.0, -48.0, -66.0, -87.0, -93.0],
+        ];
+        for (k, frame) in frames.into_iter().enumerate() {
+            state.push_spectrum(
+                frame.to_vec(),
+                1_700_000_001_000_000_000 + k as u64 * 1_000_000_000,
+            );
+        }
+        state.markers.place(1575.42);
+        state.markers.place(1576.0);
+
+        assert_snapshot("signals", &SignalsView::from_state(&state));
+    }
+
+    #[test]
+    fn test_signals_empty_waterfall_snapshot() {
+        assert_snapshot(
+            "signals_empty",
+            &SignalsView::from_state(&AppState {
+                signal_data: SignalData::new(1602.0, 8.0, 4),
+                ..AppState::default()
+            }),
+        );
+    }
+}